@@ -63,6 +63,19 @@ fn join_paths(allowlist: &[String], d: &str) -> String {
     .join(d)
 }
 
+/// Quotas still apply with `--allow-all`, so they're passed on either way.
+fn push_quota_args(permissions: &PermissionFlags, args: &mut Vec<String>) {
+  if let Some(quotas) = &permissions.quota_net {
+    args.push(format!("--quota-net={}", quotas.join(",")));
+  }
+  if let Some(quotas) = &permissions.quota_write {
+    args.push(format!("--quota-write={}", join_paths(quotas, ",")));
+  }
+  if let Some(quotas) = &permissions.quota_run {
+    args.push(format!("--quota-run={}", quotas.join(",")));
+  }
+}
+
 /// Resolve a subcommand's include/ignore globs against `base`.
 pub fn resolve_file_patterns(
   files: &FileFlags,
@@ -190,6 +203,7 @@ impl FlagsExt for Flags {
 
     if self.permissions.allow_all {
       args.push("--allow-all".to_string());
+      push_quota_args(&self.permissions, &mut args);
       return args;
     }
 
@@ -405,6 +419,8 @@ impl FlagsExt for Flags {
       _ => {}
     }
 
+    push_quota_args(&self.permissions, &mut args);
+
     args
   }
 
@@ -4,6 +4,7 @@ mod flags;
 mod flags_net;
 
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::env;
 use std::net::SocketAddr;
@@ -46,6 +47,8 @@ use deno_npm::NpmSystemInfo;
 use deno_npm_installer::LifecycleScriptsConfig;
 use deno_npm_installer::graph::NpmCachingStrategy;
 use deno_resolver::factory::resolve_jsr_url;
+use deno_runtime::deno_fetch::FetchRecorder;
+use deno_runtime::deno_fetch::FetchRecordingError;
 use deno_runtime::deno_node::ops::ipc::ChildIpcSerialization;
 use deno_runtime::deno_permissions::AllowRunDescriptor;
 use deno_runtime::deno_permissions::PathDescriptor;
@@ -1125,6 +1128,26 @@ impl CliOptions {
     }
   }
 
+  /// Creates the recorder for `deno test --record-fetch` and
+  /// `--replay-fetch`.
+  pub fn fetch_recorder(
+    &self,
+  ) -> Result<Option<Arc<FetchRecorder>>, FetchRecordingError> {
+    let DenoSubcommand::Test(test) = &self.flags.subcommand else {
+      return Ok(None);
+    };
+    let recorder = match &test.fetch_cassette {
+      Some(FetchCassette::Record(path)) => {
+        FetchRecorder::record(self.initial_cwd.join(path))?
+      }
+      Some(FetchCassette::Replay(path)) => {
+        FetchRecorder::replay(self.initial_cwd.join(path))?
+      }
+      None => return Ok(None),
+    };
+    Ok(Some(Arc::new(recorder)))
+  }
+
   pub fn cpu_prof_dir(&self) -> Option<PathBuf> {
     self.flags.cpu_prof.as_ref().map(|f| {
       f.dir
@@ -1781,6 +1804,27 @@ fn flags_to_permissions_options(
     }
   }
 
+  /// Unlike the permissions of the process, a package inherits everything
+  /// that isn't restricted for it unless `all` is `false`.
+  fn handle_package_allow(
+    all: Option<bool>,
+    config: Option<&PermissionConfigValue>,
+    parse_config_value: &impl Fn(&str) -> String,
+  ) -> Option<Vec<String>> {
+    match config {
+      Some(PermissionConfigValue::All) => Some(vec![]),
+      Some(PermissionConfigValue::Some(items)) => Some(
+        items
+          .iter()
+          .map(|value| parse_config_value(value))
+          .collect(),
+      ),
+      Some(PermissionConfigValue::None) => None,
+      None if all == Some(false) => None,
+      None => Some(vec![]),
+    }
+  }
+
   fn handle_deny_or_ignore(
     value: Option<&Vec<String>>,
     config: Option<&PermissionConfigValue>,
@@ -1804,6 +1848,33 @@ fn flags_to_permissions_options(
     }
   }
 
+  /// Quotas from the config are keyed by host, path or program, with `*` for
+  /// a total, and are replaced by the flag of the same kind.
+  fn handle_quotas(
+    value: Option<&Vec<String>>,
+    config: Option<&BTreeMap<String, u64>>,
+    parse_config_value: &impl Fn(&str) -> String,
+  ) -> Option<Vec<String>> {
+    if let Some(value) = value {
+      Some(value.clone())
+    } else if let Some(config) = config.filter(|c| !c.is_empty()) {
+      Some(
+        config
+          .iter()
+          .map(|(key, limit)| {
+            if key == "*" {
+              limit.to_string()
+            } else {
+              format!("{}={}", parse_config_value(key), limit)
+            }
+          })
+          .collect(),
+      )
+    } else {
+      None
+    }
+  }
+
   if flags.allow_all {
     config = None;
   }
@@ -1843,6 +1914,142 @@ fn flags_to_permissions_options(
   };
   let identity = |value: &str| value.to_string();
 
+  let mut packages = BTreeMap::new();
+  for (package, permissions) in config
+    .map(|c| &c.permissions.packages)
+    .into_iter()
+    .flatten()
+  {
+    let name = package
+      .strip_prefix("npm:")
+      .or_else(|| package.strip_prefix("jsr:"));
+    let Some(name) = name else {
+      bail!(
+        "Invalid package '{}' in \"permissions\". Packages must start with 'npm:' or 'jsr:'.",
+        package
+      );
+    };
+    if name.strip_prefix('@').unwrap_or(name).contains('@') {
+      bail!(
+        "Invalid package '{}' in \"permissions\". Package permissions apply to every version, so remove the version.",
+        package
+      );
+    }
+    if !permissions.packages.is_empty() {
+      bail!(
+        "Invalid package '{}' in \"permissions\". Package permissions can't contain \"packages\".",
+        package
+      );
+    }
+    if !permissions.quotas.is_empty() {
+      bail!(
+        "Invalid package '{}' in \"permissions\". Package permissions can't contain \"quotas\".",
+        package
+      );
+    }
+    let all = permissions.all;
+    packages.insert(
+      package.clone(),
+      PermissionsOptions {
+        allow_env: handle_package_allow(
+          all,
+          permissions.env.allow.as_ref(),
+          &identity,
+        ),
+        deny_env: handle_deny_or_ignore(
+          None,
+          permissions.env.deny.as_ref(),
+          &identity,
+        ),
+        ignore_env: handle_deny_or_ignore(
+          None,
+          permissions.env.ignore.as_ref(),
+          &identity,
+        ),
+        allow_net: handle_package_allow(
+          all,
+          permissions.net.allow.as_ref(),
+          &identity,
+        ),
+        deny_net: handle_deny_or_ignore(
+          None,
+          permissions.net.deny.as_ref(),
+          &identity,
+        ),
+        allow_ffi: handle_package_allow(
+          all,
+          permissions.ffi.allow.as_ref(),
+          &make_fs_config_value_absolute,
+        ),
+        deny_ffi: handle_deny_or_ignore(
+          None,
+          permissions.ffi.deny.as_ref(),
+          &make_fs_config_value_absolute,
+        ),
+        allow_read: handle_package_allow(
+          all,
+          permissions.read.allow.as_ref(),
+          &make_fs_config_value_absolute,
+        ),
+        deny_read: handle_deny_or_ignore(
+          None,
+          permissions.read.deny.as_ref(),
+          &make_fs_config_value_absolute,
+        ),
+        ignore_read: handle_deny_or_ignore(
+          None,
+          permissions.read.ignore.as_ref(),
+          &make_fs_config_value_absolute,
+        ),
+        allow_run: handle_package_allow(
+          all,
+          permissions.run.allow.as_ref(),
+          &make_run_config_value_absolute,
+        ),
+        deny_run: handle_deny_or_ignore(
+          None,
+          permissions.run.deny.as_ref(),
+          &make_run_config_value_absolute,
+        ),
+        allow_sys: handle_package_allow(
+          all,
+          permissions.sys.allow.as_ref(),
+          &identity,
+        ),
+        deny_sys: handle_deny_or_ignore(
+          None,
+          permissions.sys.deny.as_ref(),
+          &identity,
+        ),
+        allow_write: handle_package_allow(
+          all,
+          permissions.write.allow.as_ref(),
+          &make_fs_config_value_absolute,
+        ),
+        deny_write: handle_deny_or_ignore(
+          None,
+          permissions.write.deny.as_ref(),
+          &make_fs_config_value_absolute,
+        ),
+        allow_import: handle_package_allow(
+          all,
+          permissions.import.allow.as_ref(),
+          &identity,
+        ),
+        deny_import: handle_deny_or_ignore(
+          None,
+          permissions.import.deny.as_ref(),
+          &identity,
+        ),
+        quota_net: None,
+        quota_write: None,
+        quota_run: None,
+        prompt: false,
+        packages: Default::default(),
+      },
+    );
+  }
+
   Ok(PermissionsOptions {
     allow_env: handle_allow(
       flags.allow_all,
@@ -1950,7 +2157,23 @@ fn flags_to_permissions_options(
       config.and_then(|c| c.permissions.import.deny.as_ref()),
       &identity,
     ),
+    quota_net: handle_quotas(
+      flags.quota_net.as_ref(),
+      config.map(|c| &c.permissions.quotas.net),
+      &identity,
+    ),
+    quota_write: handle_quotas(
+      flags.quota_write.as_ref(),
+      config.map(|c| &c.permissions.quotas.write),
+      &make_fs_config_value_absolute,
+    ),
+    quota_run: handle_quotas(
+      flags.quota_run.as_ref(),
+      config.map(|c| &c.permissions.quotas.run),
+      &make_run_config_value_absolute,
+    ),
     prompt: !resolve_no_prompt(flags),
+    packages,
   })
 }
 
@@ -1980,6 +2203,7 @@ fn augment_permissions_with_serve_flags(
 mod test {
   use deno_config::deno_json::AllowDenyIgnorePermissionConfig;
   use deno_config::deno_json::AllowDenyPermissionConfig;
+  use deno_config::deno_json::PermissionQuotasConfig;
   use deno_config::deno_json::PermissionsObject;
   use pretty_assertions::assert_eq;
 
@@ -2140,6 +2364,7 @@ mod test {
               "sys-deny".to_string(),
             ])),
           },
+          packages: Default::default(),
         },
       };
       let permissions_options =
@@ -2222,7 +2447,11 @@ mod test {
           ]),
           allow_import: Some(vec!["jsr.io".to_string()]),
           deny_import: Some(vec!["example.com".to_string()]),
-          prompt: true
+          quota_net: None,
+          quota_write: None,
+          quota_run: None,
+          prompt: true,
+          packages: Default::default(),
         }
       );
     }
@@ -2284,9 +2513,141 @@ mod test {
           ]),
           allow_import: Some(vec![]),
           deny_import: None,
-          prompt: true
+          quota_net: None,
+          quota_write: None,
+          quota_run: None,
+          prompt: true,
+          packages: Default::default(),
         }
       );
     }
+    {
+      let config = PermissionsObjectWithBase {
+        base: deno_path_util::url_from_file_path(&base_dir.join("deno.json"))
+          .unwrap(),
+        permissions: PermissionsObject {
+          all: Some(true),
+          packages: BTreeMap::from([(
+            "npm:some-logger".to_string(),
+            PermissionsObject {
+              read: AllowDenyIgnorePermissionConfig {
+                allow: Some(PermissionConfigValue::Some(vec![
+                  "./logs".to_string(),
+                ])),
+                ..Default::default()
+              },
+              net: AllowDenyPermissionConfig {
+                allow: Some(PermissionConfigValue::None),
+                deny: None,
+              },
+              ..Default::default()
+            },
+          )]),
+          ..Default::default()
+        },
+      };
+      let permissions_options =
+        flags_to_permissions_options(&Default::default(), Some(&config))
+          .unwrap();
+      assert_eq!(
+        permissions_options.packages,
+        BTreeMap::from([(
+          "npm:some-logger".to_string(),
+          PermissionsOptions {
+            allow_env: Some(vec![]),
+            allow_net: None,
+            allow_ffi: Some(vec![]),
+            allow_read: Some(vec![
+              base_dir
+                .join("logs")
+                .into_os_string()
+                .into_string()
+                .unwrap()
+            ]),
+            allow_run: Some(vec![]),
+            allow_sys: Some(vec![]),
+            allow_write: Some(vec![]),
+            allow_import: Some(vec![]),
+            prompt: false,
+            ..Default::default()
+          }
+        )])
+      );
+
+      // --allow-all ignores the config, including package restrictions
+      let permissions_options = flags_to_permissions_options(
+        &PermissionFlags {
+          allow_all: true,
+          ..Default::default()
+        },
+        Some(&config),
+      )
+      .unwrap();
+      assert!(permissions_options.packages.is_empty());
+
+      for package in ["some-logger", "npm:some-logger@1.0.0"] {
+        let config = PermissionsObjectWithBase {
+          base: config.base.clone(),
+          permissions: PermissionsObject {
+            packages: BTreeMap::from([(
+              package.to_string(),
+              PermissionsObject::default(),
+            )]),
+            ..Default::default()
+          },
+        };
+        assert!(
+          flags_to_permissions_options(&Default::default(), Some(&config))
+            .is_err()
+        );
+      }
+    }
+    {
+      let config = PermissionsObjectWithBase {
+        base: deno_path_util::url_from_file_path(&base_dir.join("deno.json"))
+          .unwrap(),
+        permissions: PermissionsObject {
+          quotas: PermissionQuotasConfig {
+            net: BTreeMap::from([
+              ("*".to_string(), 100),
+              ("api.example.com".to_string(), 10),
+            ]),
+            write: BTreeMap::from([("out".to_string(), 1024)]),
+            run: BTreeMap::from([("git".to_string(), 2)]),
+          },
+          ..Default::default()
+        },
+      };
+      let permissions_options =
+        flags_to_permissions_options(&Default::default(), Some(&config))
+          .unwrap();
+      assert_eq!(
+        permissions_options.quota_net,
+        Some(vec!["100".to_string(), "api.example.com=10".to_string()])
+      );
+      assert_eq!(
+        permissions_options.quota_write,
+        Some(vec![format!("{}=1024", base_dir.join("out").display())])
+      );
+      assert_eq!(
+        permissions_options.quota_run,
+        Some(vec!["git=2".to_string()])
+      );
+
+      // flags replace the quotas of the same kind
+      let permissions_options = flags_to_permissions_options(
+        &PermissionFlags {
+          quota_net: Some(vec!["5".to_string()]),
+          ..Default::default()
+        },
+        Some(&config),
+      )
+      .unwrap();
+      assert_eq!(permissions_options.quota_net, Some(vec!["5".to_string()]));
+      assert_eq!(
+        permissions_options.quota_run,
+        Some(vec!["git=2".to_string()])
+      );
+    }
   }
 }
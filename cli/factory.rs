@@ -51,6 +51,7 @@ use deno_runtime::deno_fs;
 use deno_runtime::deno_fs::RealFs;
 use deno_runtime::deno_permissions::Permissions;
 use deno_runtime::deno_permissions::PermissionsContainer;
+use deno_runtime::deno_permissions::packages::set_package_resolver;
 use deno_runtime::deno_tls::RootCertStoreProvider;
 use deno_runtime::deno_tls::rustls::RootCertStore;
 use deno_runtime::deno_web::BlobStore;
@@ -70,6 +71,7 @@ use crate::args::Flags;
 use crate::args::FlagsExt;
 use crate::args::InstallFlags;
 use crate::args::InstallFlagsLocal;
+use crate::args::jsr_url;
 use crate::args::npm_system_info;
 use crate::cache::Caches;
 use crate::cache::CodeCache;
@@ -103,6 +105,7 @@ use crate::npm::DenoTaskLifeCycleScriptsExecutor;
 use crate::npm::NpmPackumentFormat;
 use crate::resolver::CliCjsTracker;
 use crate::resolver::CliNpmReqResolver;
+use crate::resolver::CliPackagePermissionResolver;
 use crate::resolver::CliResolver;
 use crate::resolver::on_resolve_diagnostic;
 use crate::standalone::binary::DenoCompileBinaryWriter;
@@ -736,6 +739,7 @@ impl CliFactory {
             | DenoSubcommand::Clean { .. }
             | DenoSubcommand::Compile { .. }
             | DenoSubcommand::Completions { .. }
+            | DenoSubcommand::Cron { .. }
             | DenoSubcommand::Desktop { .. }
             | DenoSubcommand::Coverage { .. }
             | DenoSubcommand::Deploy { .. }
@@ -747,6 +751,7 @@ impl CliFactory {
             | DenoSubcommand::List { .. }
             | DenoSubcommand::JSONReference { .. }
             | DenoSubcommand::Jupyter { .. }
+            | DenoSubcommand::Kv { .. }
             | DenoSubcommand::Lsp
             | DenoSubcommand::SyncTypes { .. }
             | DenoSubcommand::Lint { .. }
@@ -757,6 +762,7 @@ impl CliFactory {
             | DenoSubcommand::Test { .. }
             | DenoSubcommand::Transpile { .. }
             | DenoSubcommand::Outdated { .. }
+            | DenoSubcommand::Permissions { .. }
             | DenoSubcommand::Types
             | DenoSubcommand::Upgrade { .. }
             | DenoSubcommand::Vendor
@@ -1196,10 +1202,16 @@ impl CliFactory {
       .root_permissions_container
       .get_or_try_init(|| {
         let desc_parser = self.permission_desc_parser()?.clone();
-        let permissions = Permissions::from_options(
-          desc_parser.as_ref(),
-          &self.cli_options()?.permissions_options()?,
-        )?;
+        let options = self.cli_options()?.permissions_options()?;
+        if !options.packages.is_empty() {
+          set_package_resolver(Arc::new(CliPackagePermissionResolver::new(
+            self.in_npm_pkg_checker()?.clone(),
+            self.pkg_json_resolver()?.clone(),
+            jsr_url().clone(),
+          )));
+        }
+        let permissions =
+          Permissions::from_options(desc_parser.as_ref(), &options)?;
 
         Ok(PermissionsContainer::new(desc_parser, permissions))
       })
@@ -1386,6 +1398,7 @@ impl CliFactory {
       unsafely_ignore_certificate_errors: cli_options
         .unsafely_ignore_certificate_errors()
         .clone(),
+      fetch_recorder: cli_options.fetch_recorder()?,
       node_ipc_init: cli_options.node_ipc_init(&self.sys())?,
       serve_port: cli_options.serve_port(),
       serve_host: cli_options.serve_host(),
@@ -16,6 +16,12 @@ const {
   op_test_event_step_wait,
   op_test_get_origin,
   op_test_isolate_exit,
+  op_test_clock_advance_to,
+  op_test_clock_elapsed,
+  op_test_clock_install,
+  op_test_clock_next_deadline,
+  op_test_clock_now,
+  op_test_clock_yield,
 } = core.ops;
 const {
   ArrayIsArray,
@@ -572,6 +578,68 @@ test.sanitizer = function (options) {
   }
 };
 
+let clockInstalled = false;
+
+function assertClockInstalled(method) {
+  if (!clockInstalled) {
+    throw new TypeError(
+      `Deno.test.clock.${method}: the virtual clock is not installed, call Deno.test.clock.install() first`,
+    );
+  }
+}
+
+// Number of event loop turns given to the timers and crons woken up by
+// advancing the clock, and to the work they schedule in turn.
+const CLOCK_SETTLE_TURNS = 8;
+const CLOCK_MAX_SETTLE_TURNS = 1000;
+
+async function settleClock() {
+  for (let i = 0; i < CLOCK_SETTLE_TURNS; i++) {
+    await op_test_clock_yield();
+  }
+  // Keep going while something is due but didn't run yet.
+  for (let i = 0; i < CLOCK_MAX_SETTLE_TURNS; i++) {
+    const next = op_test_clock_next_deadline();
+    if (next === null || next > op_test_clock_elapsed()) {
+      break;
+    }
+    await op_test_clock_yield();
+  }
+}
+
+test.clock = {
+  install() {
+    op_test_clock_install();
+    clockInstalled = true;
+  },
+  now() {
+    assertClockInstalled("now");
+    return op_test_clock_now();
+  },
+  async tick(ms) {
+    assertClockInstalled("tick");
+    if (typeof ms !== "number" || !NumberIsFinite(ms) || ms < 0) {
+      throw new TypeError(
+        "Deno.test.clock.tick: ms must be a non-negative finite number",
+      );
+    }
+    const target = op_test_clock_elapsed() + ms;
+    await settleClock();
+    // Stop at every deadline on the way, so that timers and crons fire in
+    // order and whatever they schedule within the tick runs too.
+    while (true) {
+      const next = op_test_clock_next_deadline();
+      if (next === null || next > target || next <= op_test_clock_elapsed()) {
+        break;
+      }
+      op_test_clock_advance_to(next);
+      await settleClock();
+    }
+    op_test_clock_advance_to(target);
+    await settleClock();
+  },
+};
+
 // Matches a `printf`-style token (`%s`, `%d`, `%i`, `%f`, `%j`, `%o`, `%O`,
 // `%#`, `%%`) or a `$`-prefixed object path (`$foo`, `$foo.bar`) inside a
 // `Deno.test.each()` name template.
@@ -306,6 +306,7 @@ async fn run_subcommand(
           no_prefix: false,
           concurrency: None,
           if_present: false,
+          explain_cache: false,
         };
         let mut flags = flags;
         flags.subcommand = DenoSubcommand::Task(task_flags.clone());
@@ -419,6 +420,7 @@ async fn run_subcommand(
                   no_prefix: false,
                   concurrency: None,
                   if_present: false,
+                  explain_cache: false,
                 };
                 new_flags.subcommand = DenoSubcommand::Task(task_flags.clone());
                 let result = tools::task::execute_script(
@@ -512,6 +514,15 @@ async fn run_subcommand(
     DenoSubcommand::Why(why_flags) => spawn_subcommand(async {
       tools::pm::why(Arc::new(flags), why_flags).await
     }),
+    DenoSubcommand::Cron(cron_flags) => spawn_subcommand(async {
+      tools::cron::cron(Arc::new(flags), cron_flags).await
+    }),
+    DenoSubcommand::Kv(kv_flags) => {
+      spawn_subcommand(async { tools::kv::kv(Arc::new(flags), kv_flags).await })
+    }
+    DenoSubcommand::Permissions(permissions_flags) => spawn_subcommand(async {
+      tools::permissions::permissions(Arc::new(flags), permissions_flags).await
+    }),
     DenoSubcommand::BumpVersion(version_flags) => spawn_subcommand(async {
       log::warn!(
         "{}",
@@ -834,9 +845,28 @@ fn maybe_setup_permission_broker() {
     "{} Permission broker is an experimental feature",
     colors::yellow("Warning")
   );
-  let broker =
-    deno_runtime::deno_permissions::broker::PermissionBroker::new(socket_path);
-  deno_runtime::deno_permissions::broker::set_broker(broker);
+  let broker = deno_runtime::deno_permissions::broker::IpcPermissionBroker::new(
+    socket_path,
+  );
+  if let Err(err) =
+    deno_runtime::deno_permissions::broker::set_broker(Arc::new(broker))
+  {
+    log::warn!("{} {}", colors::yellow("Warning"), err);
+  }
+}
+
+/// Lists what was granted at permission prompts as flags, so the decisions
+/// can be kept for the next run.
+fn log_prompt_grants() {
+  let flags = deno_runtime::deno_permissions::prompter::prompt_grant_flags();
+  if flags.is_empty() {
+    return;
+  }
+  log::info!(
+    "{} {}\n  Pass these flags or add them to \"permissions\" in the config file to skip the prompts next time.",
+    colors::bold("Granted at permission prompts:"),
+    flags.join(" ")
+  );
 }
 
 #[inline(always)]
@@ -881,6 +911,7 @@ pub fn main() {
     Box::new(util::draw_thread::DrawThread::hide),
     Box::new(util::draw_thread::DrawThread::show),
   );
+  deno_signals::before_exit(log_prompt_grants);
 
   maybe_setup_permission_broker();
 
@@ -1101,18 +1132,22 @@ async fn resolve_flags_and_init(
     use deno_runtime::deno_permissions::AuditSink;
 
     let sink = if audit_target == "otel" {
-      AuditSink::Otel(|permission, value, stack| {
-        let stack = stack.unwrap_or_default().join("\n");
-        let kvs = HashMap::from([
-          ("deno.permission.type", permission),
-          ("deno.permission.value", value),
-          ("deno.permission.stack", stack.as_str()),
-        ]);
+      AuditSink::Otel(|record| {
+        let attributes = record.otel_attributes();
+        let kvs = attributes
+          .iter()
+          .map(|(key, value)| (*key, value.as_str()))
+          .collect::<HashMap<_, _>>();
+        let value = record.value.as_deref().unwrap_or_default();
         deno_telemetry::handle_log(
           &log::Record::builder()
             .level(log::Level::Info)
             .target("deno.permission.access")
-            .args(format_args!("{permission}: {value}"))
+            .args(format_args!(
+              "{}: {value} ({})",
+              record.permission,
+              record.decision.as_str()
+            ))
             .key_values(&kvs)
             .build(),
         );
@@ -31,6 +31,7 @@ use deno_runtime::deno_core::ModuleLoader;
 use deno_runtime::deno_core::SharedArrayBufferStore;
 use deno_runtime::deno_core::error::CoreError;
 use deno_runtime::deno_core::v8;
+use deno_runtime::deno_fetch::FetchRecorder;
 use deno_runtime::deno_fs;
 use deno_runtime::deno_inspector_server::MainInspectorSessionChannel;
 use deno_runtime::deno_napi::DenoRtNativeAddonLoaderRc;
@@ -286,6 +287,7 @@ pub struct LibMainWorkerOptions {
   pub origin_data_folder_path: Option<PathBuf>,
   pub seed: Option<u64>,
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
+  pub fetch_recorder: Option<Arc<FetchRecorder>>,
   pub skip_op_registration: bool,
   pub node_ipc_init: Option<(i64, ChildIpcSerialization)>,
   pub no_legacy_abort: bool,
@@ -378,6 +380,8 @@ impl<TSys: DenoLibSys> LibWorkerFactorySharedState<TSys> {
       );
       let create_web_worker_cb =
         shared.create_web_worker_callback(stdio.clone());
+      // package scopes are checked against the modules on the stack
+      let has_package_scopes = args.permissions.has_package_scopes();
 
       let maybe_storage_key = shared
         .storage_key_resolver
@@ -515,6 +519,7 @@ impl<TSys: DenoLibSys> LibWorkerFactorySharedState<TSys> {
           .options
           .unsafely_ignore_certificate_errors
           .clone(),
+        fetch_recorder: shared.options.fetch_recorder.clone(),
         seed: shared.options.seed,
         create_web_worker_cb,
         format_js_error_fn: Some(Arc::new(move |a| {
@@ -532,7 +537,9 @@ impl<TSys: DenoLibSys> LibWorkerFactorySharedState<TSys> {
         enable_raw_imports: shared.options.enable_raw_imports,
         enable_stack_trace_arg_in_ops: has_trace_permissions_enabled(
           &shared.sys,
-        ),
+        ) || has_package_scopes,
+        // the broker from DENO_PERMISSION_BROKER_PATH is process wide
+        permission_broker: None,
         wait_for_debugger_on_start: args.wait_for_debugger_on_start,
         wait_for_page_wait_for_debugger: args.wait_for_page_wait_for_debugger,
       };
@@ -696,6 +703,8 @@ impl<TSys: DenoLibSys> LibMainWorkerFactory<TSys> {
       )
     });
 
+    // package scopes are checked against the modules on the stack
+    let has_package_scopes = permissions.has_package_scopes();
     let services = WorkerServiceOptions {
       deno_rt_native_addon_loader: shared.deno_rt_native_addon_loader.clone(),
       root_cert_store_provider: Some(shared.root_cert_store_provider.clone()),
@@ -765,6 +774,7 @@ impl<TSys: DenoLibSys> LibMainWorkerFactory<TSys> {
         .options
         .unsafely_ignore_certificate_errors
         .clone(),
+      fetch_recorder: shared.options.fetch_recorder.clone(),
       seed: shared.options.seed,
       format_js_error_fn: Some(Arc::new(move |e| {
         format_js_error(e, maybe_initial_cwd.as_ref())
@@ -778,7 +788,9 @@ impl<TSys: DenoLibSys> LibMainWorkerFactory<TSys> {
       stdio,
       skip_op_registration: shared.options.skip_op_registration,
       enable_raw_imports: shared.options.enable_raw_imports,
-      enable_stack_trace_arg_in_ops: has_trace_permissions_enabled(&shared.sys),
+      enable_stack_trace_arg_in_ops: has_trace_permissions_enabled(&shared.sys)
+        || has_package_scopes,
+      permission_broker: None,
       unconfigured_runtime,
     };
 
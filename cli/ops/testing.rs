@@ -2,16 +2,21 @@
 
 use std::borrow::Cow;
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use deno_core::ModuleSpecifier;
 use deno_core::OpState;
+use deno_core::VirtualClock;
 use deno_core::op2;
 use deno_core::v8;
 use deno_error::JsErrorBox;
 use deno_path_util::url_to_file_path;
+use deno_runtime::FeatureChecker;
+use deno_runtime::deno_cron::CronHandler;
 use deno_runtime::deno_permissions::ChildPermissionsArg;
 use deno_runtime::deno_permissions::OpenAccessKind;
 use deno_runtime::deno_permissions::PermissionsContainer;
@@ -47,6 +52,12 @@ deno_core::extension!(deno_test,
     op_test_snapshot_read,
     op_test_snapshot_write,
     op_test_event_snapshot_summary,
+    op_test_clock_install,
+    op_test_clock_now,
+    op_test_clock_elapsed,
+    op_test_clock_next_deadline,
+    op_test_clock_advance_to,
+    op_test_clock_yield,
   ],
   options = {
     sender: TestEventSender,
@@ -499,3 +510,65 @@ fn op_test_isolate_exit(state: &mut OpState, #[smi] exit_code: i32) {
   // up through user code into the Rust test runner.
   isolate_handle.terminate_execution();
 }
+
+/// Makes the timers, `performance.now()` and `Deno.cron` jobs of the test
+/// module follow a virtual clock that only moves with
+/// `Deno.test.clock.tick()`.
+#[op2(fast)]
+fn op_test_clock_install(
+  scope: &mut v8::PinScope<'_, '_>,
+  state: &mut OpState,
+) {
+  state
+    .borrow::<Arc<FeatureChecker>>()
+    .check_or_exit("test-clock", "Deno.test.clock.install");
+  if state.has::<VirtualClock>() {
+    return;
+  }
+  let clock = VirtualClock::new();
+  deno_core::set_virtual_clock(scope, clock.clone());
+  if let Some(cron_handler) = state.try_borrow::<Rc<dyn CronHandler>>() {
+    cron_handler.set_virtual_clock(clock.clone());
+  }
+  state.put(clock);
+}
+
+/// The virtual wall-clock time, in whole milliseconds since the epoch.
+#[op2(fast)]
+fn op_test_clock_now(state: &mut OpState) -> f64 {
+  let clock = state.borrow::<VirtualClock>();
+  clock
+    .now_system()
+    .duration_since(std::time::UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_millis() as f64
+}
+
+/// Virtual time elapsed since the clock was installed, in milliseconds.
+#[op2(fast)]
+fn op_test_clock_elapsed(state: &mut OpState) -> f64 {
+  state.borrow::<VirtualClock>().elapsed().as_secs_f64() * 1000.0
+}
+
+/// When the next timer or cron is due, in milliseconds since the clock was
+/// installed.
+#[op2]
+#[serde]
+fn op_test_clock_next_deadline(state: &mut OpState) -> Option<f64> {
+  let clock = state.borrow::<VirtualClock>();
+  clock
+    .next_deadline()
+    .map(|deadline| deadline.as_secs_f64() * 1000.0)
+}
+
+#[op2(fast)]
+fn op_test_clock_advance_to(state: &mut OpState, elapsed_ms: f64) {
+  let clock = state.borrow::<VirtualClock>();
+  clock.advance_to(Duration::from_secs_f64(elapsed_ms.max(0.0) / 1000.0));
+}
+
+/// Resolves on the next turn of the event loop, so that the timers and crons
+/// woken up by advancing the clock get to run.
+#[allow(clippy::unused_async, reason = "yields to the event loop")]
+#[op2(async(deferred), fast)]
+async fn op_test_clock_yield() {}
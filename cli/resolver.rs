@@ -1,8 +1,15 @@
 // Copyright 2018-2026 the Deno authors. MIT license.
 
+use std::sync::Arc;
+
+use deno_core::url::Url;
+use deno_path_util::url_to_file_path;
 use deno_resolver::npm::DenoInNpmPackageChecker;
+use deno_runtime::deno_permissions::packages::PackageResolver;
 use node_resolver::DenoIsBuiltInNodeModuleChecker;
+use node_resolver::InNpmPackageChecker;
 
+use crate::node::CliPackageJsonResolver;
 use crate::npm::CliNpmResolver;
 use crate::sys::CliSys;
 
@@ -34,3 +41,52 @@ pub fn on_resolve_diagnostic(
     diagnostic.start
   );
 }
+
+/// Attributes modules to the npm or JSR package they belong to, for the
+/// package restrictions of the "permissions" config.
+pub struct CliPackagePermissionResolver {
+  in_npm_pkg_checker: DenoInNpmPackageChecker,
+  pkg_json_resolver: Arc<CliPackageJsonResolver>,
+  jsr_url: Url,
+}
+
+impl CliPackagePermissionResolver {
+  pub fn new(
+    in_npm_pkg_checker: DenoInNpmPackageChecker,
+    pkg_json_resolver: Arc<CliPackageJsonResolver>,
+    jsr_url: Url,
+  ) -> Self {
+    Self {
+      in_npm_pkg_checker,
+      pkg_json_resolver,
+      jsr_url,
+    }
+  }
+}
+
+impl PackageResolver for CliPackagePermissionResolver {
+  fn resolve_package(&self, specifier: &Url) -> Option<String> {
+    if specifier.scheme() == "file" {
+      if !self.in_npm_pkg_checker.in_npm_package(specifier) {
+        return None;
+      }
+      let path = url_to_file_path(specifier).ok()?;
+      // nested package.json files (ex. `dist/package.json` with only a
+      // "type") don't have a name, so keep going up to the package root
+      self
+        .pkg_json_resolver
+        .get_closest_package_jsons(&path)
+        .filter_map(|pkg_json| pkg_json.ok())
+        .find_map(|pkg_json| {
+          pkg_json.name.as_ref().map(|name| format!("npm:{}", name))
+        })
+    } else {
+      // ex. https://jsr.io/@std/fs/1.0.0/mod.ts
+      let path = specifier.as_str().strip_prefix(self.jsr_url.as_str())?;
+      let mut parts = path.split('/');
+      let scope = parts.next().filter(|scope| scope.starts_with('@'))?;
+      let name = parts.next().filter(|name| !name.is_empty())?;
+      Some(format!("jsr:{}/{}", scope, name))
+    }
+  }
+}
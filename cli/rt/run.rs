@@ -1775,6 +1775,7 @@ pub async fn run_with_options(
     seed: metadata.seed,
     unsafely_ignore_certificate_errors: metadata
       .unsafely_ignore_certificate_errors,
+    fetch_recorder: None,
     node_ipc_init: deno_lib::args::node_ipc_init(&sys)?,
     serve_port: options.serve_port,
     serve_host: options.serve_host,
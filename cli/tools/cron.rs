@@ -0,0 +1,385 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! `deno cron` - finds the `Deno.cron` jobs registered by a module graph, and
+//! inspects and triggers the jobs of a running process through the control
+//! socket of its local cron handler (see `DENO_CRON_CONTROL_SOCK`).
+
+use std::sync::Arc;
+
+use chrono::DateTime;
+use chrono::SecondsFormat;
+use chrono::Utc;
+use deno_ast::ModuleSpecifier;
+use deno_ast::SourceRangedForSpanned;
+use deno_ast::SourceTextInfo;
+use deno_ast::swc::ast;
+use deno_ast::swc::ecma_visit::Visit;
+use deno_ast::swc::ecma_visit::VisitWith;
+use deno_core::anyhow::Context;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_graph::GraphKind;
+use deno_npm_installer::graph::NpmCachingStrategy;
+use deno_path_util::resolve_url_or_path;
+use deno_path_util::url_from_directory_path;
+use deno_runtime::deno_cron::control::ControlRequest;
+use deno_runtime::deno_cron::control::ControlResponse;
+use deno_runtime::deno_cron::control::send_control_request;
+use deno_runtime::deno_cron::local::next_tick_after;
+use deno_terminal::colors;
+
+use crate::args::CronFlags;
+use crate::args::CronFlagsKind;
+use crate::args::Flags;
+use crate::factory::CliFactory;
+use crate::util::path::relative_specifier_path_for_display;
+
+const CONTROL_SOCKET_ENV_VAR: &str = "DENO_CRON_CONTROL_SOCK";
+
+pub async fn cron(
+  flags: Arc<Flags>,
+  cron_flags: CronFlags,
+) -> Result<(), AnyError> {
+  match cron_flags.kind {
+    CronFlagsKind::List { files } => list(flags, files).await,
+    CronFlagsKind::Status => {
+      let socket_addr = control_socket_addr(cron_flags.connect)?;
+      status(&socket_addr).await
+    }
+    CronFlagsKind::Trigger { name } => {
+      let socket_addr = control_socket_addr(cron_flags.connect)?;
+      trigger(&socket_addr, name).await
+    }
+  }
+}
+
+fn control_socket_addr(connect: Option<String>) -> Result<String, AnyError> {
+  match connect.or_else(|| std::env::var(CONTROL_SOCKET_ENV_VAR).ok()) {
+    Some(socket_addr) => Ok(socket_addr),
+    None => bail!(
+      "Missing the control socket of the process to connect to. Pass --connect or set {CONTROL_SOCKET_ENV_VAR}, for example \"unix:/tmp/cron.sock\"."
+    ),
+  }
+}
+
+/// A `Deno.cron` call found in a module. Arguments that aren't literals are
+/// `None`.
+#[derive(Debug, PartialEq, Eq)]
+struct CronRegistration {
+  name: Option<String>,
+  schedule: Option<String>,
+  timezone: Option<String>,
+  line: usize,
+  column: usize,
+}
+
+async fn list(flags: Arc<Flags>, files: Vec<String>) -> Result<(), AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let cli_options = factory.cli_options()?;
+  let module_graph_creator = factory.module_graph_creator().await?;
+  let parsed_source_cache = factory.parsed_source_cache()?;
+
+  let roots = files
+    .iter()
+    .map(|file| resolve_url_or_path(file, cli_options.initial_cwd()))
+    .collect::<Result<Vec<_>, _>>()?;
+  let graph = module_graph_creator
+    .create_graph(GraphKind::CodeOnly, roots, NpmCachingStrategy::Eager)
+    .await?;
+  module_graph_creator.graph_valid(&graph)?;
+
+  let mut found = Vec::new();
+  for module in graph.modules() {
+    let Some(module) = module.js() else {
+      continue;
+    };
+    let parsed_source =
+      parsed_source_cache.get_parsed_source_from_js_module(module)?;
+    let mut collector = CronCollector::new(parsed_source.text_info_lazy());
+    parsed_source.program_ref().visit_with(&mut collector);
+    found.extend(
+      collector
+        .registrations
+        .into_iter()
+        .map(|registration| (&module.specifier, registration)),
+    );
+  }
+
+  if found.is_empty() {
+    log::info!("No crons found.");
+    return Ok(());
+  }
+  log::info!(
+    "Found {} {}:",
+    found.len(),
+    if found.len() == 1 { "cron" } else { "crons" }
+  );
+  let cwd_url = url_from_directory_path(cli_options.initial_cwd())?;
+  let now = Utc::now();
+  for (specifier, registration) in found {
+    log::info!(
+      "- {} {}",
+      match &registration.name {
+        Some(name) => colors::green(format!("\"{name}\"")).to_string(),
+        None => colors::gray("<computed name>").to_string(),
+      },
+      format_schedule(
+        registration.schedule.as_deref(),
+        registration.timezone.as_deref()
+      ),
+    );
+    if let Some(schedule) = &registration.schedule {
+      let next =
+        next_tick_after(schedule, registration.timezone.as_deref(), now)
+          .ok()
+          .flatten();
+      match next {
+        Some(next) => log::info!("    next run {}", format_deadline(next)),
+        None => log::info!("    {}", colors::red("invalid schedule")),
+      }
+    }
+    log::info!(
+      "    {}",
+      colors::gray(format!(
+        "at {}:{}:{}",
+        relative_specifier_path_for_display(&cwd_url, specifier),
+        registration.line,
+        registration.column
+      ))
+    );
+  }
+  Ok(())
+}
+
+async fn status(socket_addr: &str) -> Result<(), AnyError> {
+  let crons = match request(socket_addr, &ControlRequest::List).await? {
+    ControlResponse::Crons { crons } => crons,
+    response => {
+      bail!("Unexpected response from the cron process: {response:?}")
+    }
+  };
+  if crons.is_empty() {
+    log::info!("No crons are registered.");
+    return Ok(());
+  }
+  for cron in crons {
+    log::info!(
+      "- {} {}",
+      colors::green(format!("\"{}\"", cron.name)),
+      format_schedule(Some(&cron.schedule), cron.timezone.as_deref())
+    );
+    match cron.next_deadline {
+      Some(next) => log::info!("    next run {}", format_deadline(next)),
+      None => log::info!("    {}", colors::cyan("running")),
+    }
+    if cron.retries > 0 {
+      log::info!(
+        "    {}",
+        colors::yellow(format!("retrying, {} failed attempts", cron.retries))
+      );
+    }
+  }
+  Ok(())
+}
+
+async fn trigger(socket_addr: &str, name: String) -> Result<(), AnyError> {
+  match request(socket_addr, &ControlRequest::Invoke { name }).await? {
+    ControlResponse::Invoked { name } => {
+      log::info!("{} \"{name}\"", colors::green("Triggered"));
+      Ok(())
+    }
+    response => {
+      bail!("Unexpected response from the cron process: {response:?}")
+    }
+  }
+}
+
+async fn request(
+  socket_addr: &str,
+  request: &ControlRequest,
+) -> Result<ControlResponse, AnyError> {
+  let response = send_control_request(socket_addr, request)
+    .await
+    .with_context(|| {
+      format!("Failed to connect to the cron control socket {socket_addr}")
+    })?;
+  if let ControlResponse::Error { message } = response {
+    bail!("{message}");
+  }
+  Ok(response)
+}
+
+fn format_schedule(schedule: Option<&str>, timezone: Option<&str>) -> String {
+  let schedule = match schedule {
+    Some(schedule) => colors::cyan(schedule).to_string(),
+    None => colors::gray("<computed schedule>").to_string(),
+  };
+  match timezone {
+    Some(timezone) => format!("{schedule} ({timezone})"),
+    None => schedule,
+  }
+}
+
+fn format_deadline(deadline: u64) -> String {
+  match DateTime::<Utc>::from_timestamp_millis(deadline as i64) {
+    Some(date) => date.to_rfc3339_opts(SecondsFormat::Secs, true),
+    None => deadline.to_string(),
+  }
+}
+
+/// Collects the `Deno.cron(name, schedule, [options,] handler)` calls of a
+/// module.
+struct CronCollector<'a> {
+  text_info: &'a SourceTextInfo,
+  registrations: Vec<CronRegistration>,
+}
+
+impl<'a> CronCollector<'a> {
+  fn new(text_info: &'a SourceTextInfo) -> Self {
+    Self {
+      text_info,
+      registrations: Vec::new(),
+    }
+  }
+
+  fn registration(&self, node: &ast::CallExpr) -> Option<CronRegistration> {
+    let ast::Callee::Expr(callee) = &node.callee else {
+      return None;
+    };
+    let ast::Expr::Member(member) = callee.as_ref() else {
+      return None;
+    };
+    let ast::Expr::Ident(obj) = member.obj.as_ref() else {
+      return None;
+    };
+    let ast::MemberProp::Ident(prop) = &member.prop else {
+      return None;
+    };
+    if obj.sym != "Deno" || prop.sym != "cron" || node.args.len() < 2 {
+      return None;
+    }
+
+    let name = string_literal(&node.args[0].expr);
+    let schedule = string_literal(&node.args[1].expr);
+    let timezone = match node.args.get(2).map(|arg| arg.expr.as_ref()) {
+      Some(ast::Expr::Object(options)) => options
+        .props
+        .iter()
+        .filter_map(|prop| match prop {
+          ast::PropOrSpread::Prop(prop) => match prop.as_ref() {
+            ast::Prop::KeyValue(prop) => Some(prop),
+            _ => None,
+          },
+          ast::PropOrSpread::Spread(_) => None,
+        })
+        .find(|prop| {
+          matches!(&prop.key, ast::PropName::Ident(key) if key.sym == "timezone")
+        })
+        .and_then(|prop| string_literal(&prop.value)),
+      _ => None,
+    };
+    let position = self.text_info.line_and_column_display(node.start());
+    Some(CronRegistration {
+      name,
+      schedule,
+      timezone,
+      line: position.line_number,
+      column: position.column_number,
+    })
+  }
+}
+
+impl Visit for CronCollector<'_> {
+  fn visit_call_expr(&mut self, node: &ast::CallExpr) {
+    if let Some(registration) = self.registration(node) {
+      self.registrations.push(registration);
+    }
+    node.visit_children_with(self);
+  }
+}
+
+/// Returns the value of string literals and template literals without
+/// substitutions.
+fn string_literal(expr: &ast::Expr) -> Option<String> {
+  match expr {
+    ast::Expr::Lit(ast::Lit::Str(lit)) => {
+      Some(lit.value.to_string_lossy().to_string())
+    }
+    ast::Expr::Tpl(tpl) if tpl.quasis.len() == 1 => tpl.quasis[0]
+      .cooked
+      .as_ref()
+      .map(|cooked| cooked.to_string_lossy().to_string()),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_ast::MediaType;
+  use deno_ast::ParseParams;
+
+  use super::*;
+
+  fn collect(source: &str) -> Vec<CronRegistration> {
+    let parsed_source = deno_ast::parse_module(ParseParams {
+      specifier: ModuleSpecifier::parse("file:///main.ts").unwrap(),
+      text: source.into(),
+      media_type: MediaType::TypeScript,
+      capture_tokens: false,
+      scope_analysis: false,
+      maybe_syntax: None,
+    })
+    .unwrap();
+    let mut collector = CronCollector::new(parsed_source.text_info_lazy());
+    parsed_source.program_ref().visit_with(&mut collector);
+    collector.registrations
+  }
+
+  #[test]
+  fn collects_cron_registrations() {
+    let registrations = collect(
+      r#"Deno.cron("Daily report", "0 9 * * *", () => {});
+function setup(name: string) {
+  Deno.cron(name, `*/5 * * * *`, { timezone: "Europe/Berlin" }, async () => {
+    await Deno.cron("nested", "0 0 * * *", () => {});
+  });
+}
+Deno.test("not a cron", () => {});
+Deno.cron(`Weekly\u0020report`, "0 9 * * 1", () => {});
+"#,
+    );
+    assert_eq!(
+      registrations,
+      vec![
+        CronRegistration {
+          name: Some("Daily report".to_string()),
+          schedule: Some("0 9 * * *".to_string()),
+          timezone: None,
+          line: 1,
+          column: 1,
+        },
+        CronRegistration {
+          name: None,
+          schedule: Some("*/5 * * * *".to_string()),
+          timezone: Some("Europe/Berlin".to_string()),
+          line: 3,
+          column: 3,
+        },
+        CronRegistration {
+          name: Some("nested".to_string()),
+          schedule: Some("0 0 * * *".to_string()),
+          timezone: None,
+          line: 4,
+          column: 11,
+        },
+        CronRegistration {
+          name: Some("Weekly report".to_string()),
+          schedule: Some("0 9 * * 1".to_string()),
+          timezone: None,
+          line: 8,
+          column: 1,
+        },
+      ]
+    );
+  }
+}
@@ -0,0 +1,238 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! `deno kv` - exports, imports and backs up local Deno KV databases. The
+//! export format is described in `deno_kv::export`.
+
+use std::io::BufRead;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_core::anyhow::Context;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
+use deno_core::serde_json;
+use deno_runtime::deno_kv::KvConfig;
+use deno_runtime::deno_kv::export::EXPORT_PAGE_SIZE;
+use deno_runtime::deno_kv::export::ExportKeyPart;
+use deno_runtime::deno_kv::export::ExportNumber;
+use deno_runtime::deno_kv::export::backup_sqlite;
+use deno_runtime::deno_kv::export::encode_export_key;
+use deno_runtime::deno_kv::export::import_entries;
+use deno_runtime::deno_kv::export::parse_export_line;
+use deno_runtime::deno_kv::export::read_export_page;
+use deno_runtime::deno_kv::export::read_sqlite_expirations;
+use deno_runtime::deno_kv::export::write_export_line;
+use deno_runtime::deno_kv::sqlite::open_sqlite_file;
+use deno_runtime::deno_webstorage::rusqlite::Connection;
+use deno_runtime::deno_webstorage::rusqlite::OpenFlags;
+use deno_terminal::colors;
+
+use crate::args::Flags;
+use crate::args::KvFlags;
+use crate::args::KvFlagsKind;
+use crate::factory::CliFactory;
+
+/// Number of lines imported in one go.
+const IMPORT_BATCH_SIZE: usize = 1000;
+
+pub async fn kv(flags: Arc<Flags>, kv_flags: KvFlags) -> Result<(), AnyError> {
+  let factory = CliFactory::from_flags(flags);
+  let cwd = factory.cli_options()?.initial_cwd().to_path_buf();
+  match kv_flags.kind {
+    KvFlagsKind::Export {
+      database,
+      prefix,
+      output,
+    } => {
+      let database = resolve_database(&cwd, &database)?;
+      let prefix = match prefix {
+        Some(prefix) => parse_prefix(&prefix)?,
+        None => Vec::new(),
+      };
+      let output = output.map(|output| cwd.join(output));
+      export(&database, &prefix, output.as_deref()).await
+    }
+    KvFlagsKind::Import { database, input } => {
+      let database = resolve_database(&cwd, &database)?;
+      let input = input.map(|input| cwd.join(input));
+      import(database, input.as_deref()).await
+    }
+    KvFlagsKind::Backup { database, output } => {
+      let database = resolve_database(&cwd, &database)?;
+      let output = cwd.join(output);
+      if output.exists() {
+        bail!("{} already exists", output.display());
+      }
+      backup_sqlite(&database, &output)?;
+      log::info!(
+        "{} {} to {}",
+        colors::green("Backed up"),
+        database.display(),
+        output.display()
+      );
+      Ok(())
+    }
+  }
+}
+
+fn resolve_database(cwd: &Path, database: &str) -> Result<PathBuf, AnyError> {
+  if database.starts_with("https://") || database.starts_with("http://") {
+    bail!(
+      "deno kv only works on local databases. Use Deno.Kv.prototype.export() and import() in a script to move data to and from remote databases."
+    );
+  }
+  if database == ":memory:" {
+    bail!("In-memory databases can't be opened by another process");
+  }
+  Ok(cwd.join(database))
+}
+
+/// Parses a key prefix given as a JSON array. Strings, numbers and booleans
+/// can be written as such, bigints and bytes as typed key parts like
+/// `{"type":"bigint","value":"1"}`.
+fn parse_prefix(prefix: &str) -> Result<Vec<u8>, AnyError> {
+  let parts = serde_json::from_str::<Vec<serde_json::Value>>(prefix)
+    .context("The prefix must be a JSON array of key parts")?;
+  let parts = parts
+    .into_iter()
+    .map(|part| {
+      Ok(match part {
+        serde_json::Value::String(s) => ExportKeyPart::String(s),
+        serde_json::Value::Bool(b) => ExportKeyPart::Boolean(b),
+        serde_json::Value::Number(n) => match n.as_f64() {
+          Some(n) => ExportKeyPart::Number(ExportNumber::Finite(n)),
+          None => bail!("Invalid number {n} in prefix"),
+        },
+        part @ serde_json::Value::Object(_) => serde_json::from_value(part)
+          .context("Invalid typed key part in prefix")?,
+        part => bail!("Invalid key part {part} in prefix"),
+      })
+    })
+    .collect::<Result<Vec<_>, AnyError>>()?;
+  Ok(encode_export_key(parts)?)
+}
+
+async fn export(
+  database: &Path,
+  prefix: &[u8],
+  output: Option<&Path>,
+) -> Result<(), AnyError> {
+  if !database.exists() {
+    bail!("{} does not exist", database.display());
+  }
+  // Export from a backup, so that the export is a snapshot of the database
+  // even if it is written to in the meantime.
+  let temp_dir = tempfile::Builder::new()
+    .prefix("deno-kv-export-")
+    .tempdir()?;
+  let snapshot = temp_dir.path().join("kv.sqlite3");
+  backup_sqlite(database, &snapshot)?;
+  let expirations_conn =
+    Connection::open_with_flags(&snapshot, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+  let db = open_sqlite_file(snapshot)?;
+
+  let mut writer: Box<dyn Write> = match output {
+    Some(output) => Box::new(std::io::BufWriter::new(
+      std::fs::File::create(output)
+        .with_context(|| format!("Failed to create {}", output.display()))?,
+    )),
+    None => Box::new(std::io::BufWriter::new(std::io::stdout().lock())),
+  };
+  let mut count = 0;
+  let mut cursor = None;
+  let mut buf = Vec::new();
+  loop {
+    let (entries, next) =
+      read_export_page(&db, prefix, cursor.as_deref(), EXPORT_PAGE_SIZE)
+        .await?;
+    count += entries.len();
+    let keys = entries
+      .iter()
+      .map(|entry| entry.key.clone())
+      .collect::<Vec<_>>();
+    let expirations = read_sqlite_expirations(&expirations_conn, &keys)?;
+    buf.clear();
+    for (entry, expire_at) in entries.into_iter().zip(expirations) {
+      write_export_line(&mut buf, entry, expire_at)?;
+    }
+    writer.write_all(&buf)?;
+    match next {
+      Some(next) => cursor = Some(next),
+      None => break,
+    }
+  }
+  writer.flush()?;
+  log::info!("{} {count} entries", colors::green("Exported"));
+  Ok(())
+}
+
+async fn import(
+  database: PathBuf,
+  input: Option<&Path>,
+) -> Result<(), AnyError> {
+  let reader: Box<dyn BufRead> = match input {
+    Some(input) => Box::new(std::io::BufReader::new(
+      std::fs::File::open(input)
+        .with_context(|| format!("Failed to open {}", input.display()))?,
+    )),
+    None => Box::new(std::io::BufReader::new(std::io::stdin().lock())),
+  };
+  let db = open_sqlite_file(database)?;
+  let config = KvConfig::builder().build();
+
+  let mut count = 0;
+  let mut entries = Vec::new();
+  for (index, line) in reader.lines().enumerate() {
+    let line = line?;
+    if line.trim().is_empty() {
+      continue;
+    }
+    entries.push(
+      parse_export_line(&line)
+        .with_context(|| format!("Failed to import line {}", index + 1))?,
+    );
+    if entries.len() >= IMPORT_BATCH_SIZE {
+      count += import_entries(&db, std::mem::take(&mut entries), &config)
+        .await
+        .with_context(|| partially_imported(count))?;
+    }
+  }
+  count += import_entries(&db, entries, &config)
+    .await
+    .with_context(|| partially_imported(count))?;
+  log::info!("{} {count} entries", colors::green("Imported"));
+  Ok(())
+}
+
+/// Imports are written in batches, and the batches before a failed one stay
+/// imported.
+fn partially_imported(count: usize) -> String {
+  format!(
+    "Failed to import entries, {count} entries were imported before the error"
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_prefix() {
+    assert_eq!(
+      parse_prefix(r#"["users", 1, true, {"type":"bigint","value":"2"}]"#)
+        .unwrap(),
+      encode_export_key(vec![
+        ExportKeyPart::String("users".to_string()),
+        ExportKeyPart::Number(ExportNumber::Finite(1.0)),
+        ExportKeyPart::Boolean(true),
+        ExportKeyPart::Bigint("2".to_string()),
+      ])
+      .unwrap()
+    );
+    assert_eq!(parse_prefix("[]").unwrap(), Vec::<u8>::new());
+    assert!(parse_prefix(r#""users""#).is_err());
+    assert!(parse_prefix("[null]").is_err());
+  }
+}
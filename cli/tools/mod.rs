@@ -7,6 +7,7 @@ pub mod check;
 pub mod clean;
 pub mod compile;
 pub mod coverage;
+pub mod cron;
 pub mod deploy;
 pub mod desktop;
 pub mod desktop_devtools;
@@ -18,8 +19,10 @@ pub mod info;
 pub mod init;
 pub mod installer;
 pub mod jupyter;
+pub mod kv;
 pub mod lint;
 pub mod pack;
+pub mod permissions;
 pub mod pm;
 pub mod publish;
 pub mod repl;
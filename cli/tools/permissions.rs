@@ -0,0 +1,580 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! `deno permissions` - generates a least-privilege `permissions` config from
+//! the JSONL files written by `DENO_AUDIT_PERMISSIONS`.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use deno_core::anyhow::Context;
+use deno_core::error::AnyError;
+use deno_core::url::Url;
+use deno_path_util::normalize_path;
+use deno_path_util::url_to_file_path;
+use deno_runtime::deno_permissions::NetDescriptor;
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::args::Flags;
+use crate::args::PermissionsConfigFlags;
+use crate::util::display;
+use crate::util::env::resolve_cwd;
+
+/// Number of accessed entries in a directory before the whole directory is
+/// granted instead.
+const PATH_COLLAPSE_THRESHOLD: usize = 3;
+
+/// Number of env vars sharing a `PREFIX_` before they are granted as
+/// `PREFIX_*`. This is higher than for paths because a wildcard can easily
+/// cover secrets living next to the variables that were actually read (for
+/// example `AWS_REGION` and `AWS_SECRET_ACCESS_KEY`).
+const ENV_COLLAPSE_THRESHOLD: usize = 3;
+
+/// A single line of an audit log. Only the fields needed to rebuild the
+/// permissions are read; everything else is ignored so that newer versions
+/// of the format keep working.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuditLine {
+  permission: String,
+  #[serde(default)]
+  value: Option<serde_json::Value>,
+  /// Only written since v2 of the format.
+  #[serde(default)]
+  decision: Option<String>,
+  #[serde(default)]
+  decided_by: Option<String>,
+}
+
+#[derive(Debug)]
+struct Accessed<T> {
+  /// Whether an API requiring the whole permission was used (for example
+  /// `Deno.env.toObject()`).
+  all: bool,
+  values: BTreeSet<T>,
+}
+
+impl<T> Default for Accessed<T> {
+  fn default() -> Self {
+    Self {
+      all: false,
+      values: BTreeSet::new(),
+    }
+  }
+}
+
+impl<T: Ord> Accessed<T> {
+  fn insert(&mut self, value: Option<T>) {
+    match value {
+      Some(value) => {
+        self.values.insert(value);
+      }
+      None => self.all = true,
+    }
+  }
+
+  fn is_empty(&self) -> bool {
+    !self.all && self.values.is_empty()
+  }
+}
+
+#[derive(Debug, Default)]
+struct AccessedPermissions {
+  read: Accessed<PathBuf>,
+  write: Accessed<PathBuf>,
+  import: Accessed<String>,
+  env: Accessed<String>,
+  net: Accessed<String>,
+  run: Accessed<String>,
+  ffi: Accessed<PathBuf>,
+  sys: Accessed<String>,
+}
+
+impl AccessedPermissions {
+  fn add_line(&mut self, line: AuditLine, cwd: &Path) {
+    // access refused by a deny rule was meant to stay refused
+    if line.decision.as_deref() == Some("denied")
+      && line.decided_by.as_deref() == Some("rule")
+    {
+      return;
+    }
+    let value = match line.value {
+      Some(serde_json::Value::String(value)) => Some(value),
+      Some(serde_json::Value::Null) | None => None,
+      Some(other) => Some(other.to_string()),
+    };
+    match line.permission.as_str() {
+      "read" => self.read.insert(value.map(|v| resolve_path(&v, cwd))),
+      "write" => self.write.insert(value.map(|v| resolve_path(&v, cwd))),
+      "ffi" => self.ffi.insert(value.map(|v| resolve_path(&v, cwd))),
+      "net" => self.net.insert(value.map(|v| resolve_host(&v))),
+      "import" => self.import.insert(value.map(|v| resolve_host(&v))),
+      "env" => self.env.insert(value),
+      "run" => self.run.insert(value),
+      "sys" => self.sys.insert(value),
+      other => {
+        log::debug!("Ignoring unknown permission in audit log: {}", other);
+      }
+    }
+  }
+}
+
+pub async fn permissions(
+  flags: Arc<Flags>,
+  permissions_flags: PermissionsConfigFlags,
+) -> Result<(), AnyError> {
+  let cwd = resolve_cwd(flags.initial_cwd.as_deref())?;
+  let mut accessed = AccessedPermissions::default();
+  for audit_log in &permissions_flags.audit_logs {
+    let path = cwd.join(audit_log);
+    let text = std::fs::read_to_string(&path).with_context(|| {
+      format!("Failed reading audit log '{}'", path.display())
+    })?;
+    for (index, line) in text.lines().enumerate() {
+      if line.trim().is_empty() {
+        continue;
+      }
+      let line: AuditLine = serde_json::from_str(line).with_context(|| {
+        format!(
+          "Invalid audit log entry at {}:{}",
+          path.display(),
+          index + 1
+        )
+      })?;
+      accessed.add_line(line, &cwd);
+    }
+  }
+
+  let permissions =
+    build_permissions_object(accessed, &cwd, permissions_flags.exact);
+  let mut sets = IndexMap::with_capacity(1);
+  sets.insert(permissions_flags.name, permissions);
+  let mut config = IndexMap::with_capacity(1);
+  config.insert("permissions", sets);
+  display::write_json_to_stdout(&config)
+}
+
+fn resolve_path(value: &str, cwd: &Path) -> PathBuf {
+  // dynamic imports are audited as `file:` specifiers
+  if value.starts_with("file:")
+    && let Ok(path) = Url::parse(value)
+      .map_err(|_| ())
+      .and_then(|url| url_to_file_path(&url).map_err(|_| ()))
+  {
+    return path;
+  }
+  let path = Path::new(value);
+  if path.is_absolute() {
+    normalize_path(Cow::Borrowed(path)).into_owned()
+  } else {
+    normalize_path(Cow::Owned(cwd.join(path))).into_owned()
+  }
+}
+
+fn resolve_host(value: &str) -> String {
+  // `fetch` and dynamic imports are audited as full urls
+  if value.contains("://")
+    && let Ok(url) = Url::parse(value)
+    && let Ok(desc) = NetDescriptor::from_url(&url)
+  {
+    return desc.to_string();
+  }
+  match NetDescriptor::parse_for_query(value) {
+    Ok(desc) => desc.to_string(),
+    Err(_) => value.to_string(),
+  }
+}
+
+fn build_permissions_object(
+  accessed: AccessedPermissions,
+  cwd: &Path,
+  exact: bool,
+) -> IndexMap<&'static str, serde_json::Value> {
+  fn to_value<T>(
+    accessed: Accessed<T>,
+    values_to_strings: impl FnOnce(BTreeSet<T>) -> Vec<String>,
+  ) -> Option<serde_json::Value> {
+    if accessed.all {
+      Some(serde_json::Value::Bool(true))
+    } else if accessed.values.is_empty() {
+      None
+    } else {
+      Some(serde_json::Value::Array(
+        values_to_strings(accessed.values)
+          .into_iter()
+          .map(serde_json::Value::String)
+          .collect(),
+      ))
+    }
+  }
+
+  let paths = |paths: BTreeSet<PathBuf>| -> Vec<String> {
+    let paths = if exact {
+      paths
+    } else {
+      collapse_paths(paths, cwd)
+    };
+    paths.iter().map(|path| display_path(path, cwd)).collect()
+  };
+  let hosts = |hosts: BTreeSet<String>| -> Vec<String> {
+    if exact {
+      hosts.into_iter().collect()
+    } else {
+      collapse_hosts(hosts)
+    }
+  };
+  let env = |names: BTreeSet<String>| -> Vec<String> {
+    if exact {
+      names.into_iter().collect()
+    } else {
+      collapse_env(names)
+    }
+  };
+  let strings =
+    |values: BTreeSet<String>| -> Vec<String> { values.into_iter().collect() };
+
+  [
+    ("read", to_value(accessed.read, paths)),
+    ("write", to_value(accessed.write, paths)),
+    ("import", to_value(accessed.import, hosts)),
+    ("env", to_value(accessed.env, env)),
+    ("net", to_value(accessed.net, hosts)),
+    ("run", to_value(accessed.run, strings)),
+    ("ffi", to_value(accessed.ffi, paths)),
+    ("sys", to_value(accessed.sys, strings)),
+  ]
+  .into_iter()
+  .filter_map(|(name, value)| Some((name, value?)))
+  .collect()
+}
+
+/// Collapses accessed paths into the directories directly containing them.
+///
+/// Only directories strictly inside `cwd` and inside the common prefix of the
+/// accessed paths are granted as a whole. Anything outside of the project
+/// (temp dirs, the home directory, system files) is kept as the exact paths
+/// that were accessed. Collapsing is done once, so a granted directory never
+/// counts towards granting its parent.
+fn collapse_paths(paths: BTreeSet<PathBuf>, cwd: &Path) -> BTreeSet<PathBuf> {
+  let mut paths = remove_covered_paths(paths);
+  let Some(common_prefix) =
+    common_prefix(paths.iter().filter(|path| path.starts_with(cwd)))
+  else {
+    return paths;
+  };
+  let mut children_by_dir: BTreeMap<&Path, usize> = BTreeMap::new();
+  for path in &paths {
+    if let Some(parent) = path.parent()
+      && parent != cwd
+      && parent.starts_with(cwd)
+      && parent.starts_with(&common_prefix)
+    {
+      *children_by_dir.entry(parent).or_default() += 1;
+    }
+  }
+  let dirs = children_by_dir
+    .into_iter()
+    .filter(|(_, count)| *count >= PATH_COLLAPSE_THRESHOLD)
+    .map(|(dir, _)| dir.to_path_buf())
+    .collect::<Vec<_>>();
+  paths.extend(dirs);
+  remove_covered_paths(paths)
+}
+
+/// The deepest directory containing all of `paths`.
+fn common_prefix<'a>(
+  mut paths: impl Iterator<Item = &'a PathBuf>,
+) -> Option<PathBuf> {
+  let first = paths.next()?;
+  let mut prefix = first.parent().unwrap_or(first).to_path_buf();
+  for path in paths {
+    while !path.starts_with(&prefix) {
+      if !prefix.pop() {
+        break;
+      }
+    }
+  }
+  Some(prefix)
+}
+
+/// Removes paths that are already granted by one of their ancestors.
+fn remove_covered_paths(paths: BTreeSet<PathBuf>) -> BTreeSet<PathBuf> {
+  let mut result: BTreeSet<PathBuf> = BTreeSet::new();
+  for path in paths {
+    if !result.iter().any(|dir| path.starts_with(dir)) {
+      result.retain(|p| !p.starts_with(&path));
+      result.insert(path);
+    }
+  }
+  result
+}
+
+/// Formats a path relative to `cwd` when it's inside it, which is how paths
+/// in a config file are resolved.
+fn display_path(path: &Path, cwd: &Path) -> String {
+  match path.strip_prefix(cwd) {
+    Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+    Ok(relative) => {
+      let relative = relative.to_string_lossy();
+      if cfg!(windows) {
+        format!("./{}", relative.replace('\\', "/"))
+      } else {
+        format!("./{}", relative)
+      }
+    }
+    Err(_) => path.to_string_lossy().into_owned(),
+  }
+}
+
+/// Collapses `host:port` entries into a bare `host` when the host was also
+/// accessed without a port, since that already grants every port.
+fn collapse_hosts(hosts: BTreeSet<String>) -> Vec<String> {
+  let mut by_host: BTreeMap<String, Option<BTreeSet<String>>> = BTreeMap::new();
+  for value in hosts {
+    match NetDescriptor::parse_for_query(&value) {
+      Ok(NetDescriptor(host, Some(_))) => {
+        let host = NetDescriptor(host, None).to_string();
+        if let Some(ports) =
+          by_host.entry(host).or_insert_with(|| Some(BTreeSet::new()))
+        {
+          ports.insert(value);
+        }
+      }
+      Ok(NetDescriptor(host, None)) => {
+        by_host.insert(NetDescriptor(host, None).to_string(), None);
+      }
+      Err(_) => {
+        by_host
+          .entry(value.clone())
+          .or_insert_with(|| Some(BTreeSet::from([value])));
+      }
+    }
+  }
+  by_host
+    .into_iter()
+    .flat_map(|(host, ports)| match ports {
+      Some(ports) => ports.into_iter().collect::<Vec<_>>(),
+      None => vec![host],
+    })
+    .collect()
+}
+
+/// Groups env vars into `PREFIX_*` wildcards, using the most specific
+/// underscore-delimited prefix that is shared by enough of the accessed
+/// variables.
+fn collapse_env(names: BTreeSet<String>) -> Vec<String> {
+  fn prefixes(name: &str) -> impl Iterator<Item = &str> {
+    name
+      .match_indices('_')
+      .map(|(index, _)| &name[..=index])
+      // `_` alone or a trailing underscore isn't a meaningful group
+      .filter(move |prefix| prefix.len() > 1 && prefix.len() < name.len())
+  }
+
+  let mut prefix_counts: BTreeMap<&str, usize> = BTreeMap::new();
+  for name in &names {
+    for prefix in prefixes(name) {
+      *prefix_counts.entry(prefix).or_default() += 1;
+    }
+  }
+
+  let mut groups: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+  let mut result = BTreeSet::new();
+  for name in &names {
+    let prefix = prefixes(name)
+      .filter(|prefix| prefix_counts[prefix] >= ENV_COLLAPSE_THRESHOLD)
+      .max_by_key(|prefix| prefix.len());
+    match prefix {
+      Some(prefix) => groups.entry(prefix).or_default().push(name),
+      None => {
+        result.insert(name.to_string());
+      }
+    }
+  }
+  for (prefix, names) in groups {
+    if names.len() >= ENV_COLLAPSE_THRESHOLD {
+      result.insert(format!("{}*", prefix));
+    } else {
+      result.extend(names.into_iter().map(|n| n.to_string()));
+    }
+  }
+  result.into_iter().collect()
+}
+
+#[cfg(test)]
+mod test {
+  use pretty_assertions::assert_eq;
+
+  use super::*;
+
+  fn set<T: Ord + Clone>(items: &[T]) -> BTreeSet<T> {
+    items.iter().cloned().collect()
+  }
+
+  #[test]
+  fn collapses_paths_inside_cwd() {
+    let cwd = if cfg!(windows) {
+      PathBuf::from("C:\\project")
+    } else {
+      PathBuf::from("/project")
+    };
+    let paths = set(&[
+      cwd.join("public/a.css"),
+      cwd.join("public/b.css"),
+      cwd.join("public/c.css"),
+      cwd.join("public/img/d.png"),
+      cwd.join("deno.json"),
+      cwd.join("main.ts"),
+      cwd.join("src/lib.ts"),
+    ]);
+    let collapsed = collapse_paths(paths, &cwd);
+    assert_eq!(
+      collapsed
+        .iter()
+        .map(|p| display_path(p, &cwd))
+        .collect::<Vec<_>>(),
+      vec!["./deno.json", "./main.ts", "./public", "./src/lib.ts"],
+    );
+  }
+
+  #[test]
+  fn does_not_collapse_up_the_tree() {
+    let cwd = PathBuf::from("/project");
+    let mut paths = BTreeSet::new();
+    for dir in ["a", "b", "c"] {
+      for file in ["1.txt", "2.txt", "3.txt"] {
+        paths.insert(cwd.join("data").join(dir).join(file));
+      }
+    }
+    paths.insert(cwd.join("data/a/4.txt"));
+    paths.insert(cwd.join("main.ts"));
+    paths.insert(cwd.join("deps.ts"));
+    paths.insert(cwd.join("mod.ts"));
+    let collapsed = collapse_paths(paths, &cwd);
+    assert_eq!(
+      collapsed
+        .iter()
+        .map(|p| display_path(p, &cwd))
+        .collect::<Vec<_>>(),
+      vec![
+        "./data/a",
+        "./data/b",
+        "./data/c",
+        "./deps.ts",
+        "./main.ts",
+        "./mod.ts",
+      ],
+    );
+
+    // two siblings are not enough to grant their directory
+    let paths = set(&[cwd.join("src/a.ts"), cwd.join("src/b.ts")]);
+    assert_eq!(collapse_paths(paths.clone(), &cwd), paths);
+  }
+
+  #[test]
+  fn keeps_exact_paths_outside_cwd() {
+    let cwd = PathBuf::from("/project");
+    let paths = set(&[
+      PathBuf::from("/tmp/a"),
+      PathBuf::from("/tmp/b"),
+      PathBuf::from("/etc/hosts"),
+    ]);
+    let collapsed = collapse_paths(paths.clone(), &cwd);
+    assert_eq!(collapsed, paths);
+  }
+
+  #[test]
+  fn removes_paths_covered_by_ancestors() {
+    let paths = set(&[
+      PathBuf::from("/data"),
+      PathBuf::from("/data/a.txt"),
+      PathBuf::from("/data/nested/b.txt"),
+      PathBuf::from("/database"),
+    ]);
+    assert_eq!(
+      remove_covered_paths(paths),
+      set(&[PathBuf::from("/data"), PathBuf::from("/database")]),
+    );
+  }
+
+  #[test]
+  fn collapses_hosts() {
+    let hosts = set(&[
+      "deno.land:443".to_string(),
+      "deno.land".to_string(),
+      "example.com:443".to_string(),
+      "example.com:8080".to_string(),
+    ]);
+    assert_eq!(
+      collapse_hosts(hosts),
+      vec!["deno.land", "example.com:443", "example.com:8080"],
+    );
+  }
+
+  #[test]
+  fn resolves_hosts_from_urls() {
+    assert_eq!(resolve_host("https://deno.land/x/mod.ts"), "deno.land:443");
+    assert_eq!(resolve_host("http://localhost:8000/"), "localhost:8000");
+    assert_eq!(resolve_host("127.0.0.1:4500"), "127.0.0.1:4500");
+  }
+
+  #[test]
+  fn collapses_env_vars() {
+    let names = set(&[
+      "AWS_REGION".to_string(),
+      "AWS_PROFILE".to_string(),
+      "DB_HOST".to_string(),
+      "DB_PORT".to_string(),
+      "DB_USER".to_string(),
+      "HOME".to_string(),
+      "APP_FEATURE_A".to_string(),
+      "APP_FEATURE_B".to_string(),
+      "APP_FEATURE_C".to_string(),
+      "APP_NAME".to_string(),
+    ]);
+    assert_eq!(
+      collapse_env(names),
+      vec![
+        "APP_FEATURE_*",
+        "APP_NAME",
+        "AWS_PROFILE",
+        "AWS_REGION",
+        "DB_*",
+        "HOME",
+      ],
+    );
+  }
+
+  #[test]
+  fn builds_permissions_object() {
+    let cwd = PathBuf::from("/project");
+    let mut accessed = AccessedPermissions::default();
+    let lines = [
+      r#"{"v":1,"datetime":"","permission":"sys","value":"hostname"}"#,
+      r#"{"v":1,"datetime":"","permission":"read","value":"./data/a.json"}"#,
+      r#"{"v":1,"datetime":"","permission":"read","value":"/project/data/b.json"}"#,
+      r#"{"v":1,"datetime":"","permission":"read","value":"/project/data/c.json"}"#,
+      r#"{"v":1,"datetime":"","permission":"env","value":null}"#,
+      r#"{"v":1,"datetime":"","permission":"env","value":"FOO"}"#,
+      r#"{"v":1,"datetime":"","permission":"net","value":"https://api.example.com/v1"}"#,
+      r#"{"v":2,"datetime":"","monotonicTime":1.0,"workerId":0,"permission":"net","value":"evil.example.com:443","decision":"denied","decidedBy":"rule","rule":"--deny-net=evil.example.com"}"#,
+    ];
+    for line in lines {
+      accessed.add_line(serde_json::from_str(line).unwrap(), &cwd);
+    }
+    let object = build_permissions_object(accessed, &cwd, false);
+    assert_eq!(
+      serde_json::to_value(&object).unwrap(),
+      serde_json::json!({
+        "read": ["./data"],
+        "env": true,
+        "net": ["api.example.com:443"],
+        "sys": ["hostname"],
+      }),
+    );
+  }
+}
@@ -9,6 +9,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
 
 use console_static_text::ansi::strip_ansi_codes;
 use deno_config::workspace::FolderConfigs;
@@ -46,6 +47,9 @@ use crate::npm::CliNpmInstaller;
 use crate::npm::CliNpmResolver;
 use crate::task_runner;
 use crate::task_runner::run_future_forwarding_signals;
+use crate::tools::task_cache::CacheChange;
+use crate::tools::task_cache::ExplainOutcome;
+use crate::tools::task_cache::NotCacheableReason;
 use crate::util::fs::canonicalize_path;
 use crate::util::progress_bar::ProgressBar;
 
@@ -196,8 +200,17 @@ pub async fn execute_script(
   let npm_resolver = factory.npm_resolver().await?;
   let node_resolver = factory.node_resolver().await?;
   let progress_bar = factory.text_only_progress_bar();
-  let task_cache =
-    crate::tools::task_cache::TaskCache::new(&factory.deno_dir()?.root);
+  let remote_task_cache = match cli_options.workspace().task_cache()? {
+    Some(config) => Some(crate::tools::task_cache::RemoteTaskCache::new(
+      config,
+      factory.http_client_provider(),
+    )?),
+    None => None,
+  };
+  let task_cache = crate::tools::task_cache::TaskCache::new(
+    &factory.deno_dir()?.root,
+    remote_task_cache,
+  );
   let mut env_vars = task_runner::real_env_vars();
 
   if flags.tunnel {
@@ -229,8 +242,15 @@ pub async fn execute_script(
     concurrency: no_of_concurrent_tasks.into(),
     task_cache: &task_cache,
     task_fingerprints: std::cell::RefCell::new(HashMap::new()),
+    task_outcomes: std::cell::RefCell::new(Vec::new()),
   };
 
+  if task_flags.explain_cache {
+    return task_runner
+      .explain_cache(&packages_task_configs, name, cli_options.argv())
+      .await;
+  }
+
   let kill_signal = KillSignal::default();
   run_future_forwarding_signals(kill_signal.clone(), async {
     if task_flags.eval {
@@ -247,6 +267,9 @@ pub async fn execute_script(
             files: Vec::new(),
             output: Vec::new(),
             env: Vec::new(),
+            cwd: None,
+            timeout: None,
+            retries: 0,
           },
           kill_signal,
           cli_options.argv(),
@@ -412,8 +435,28 @@ struct TaskRunner<'a> {
   /// cache key (the dependency cascade). `None` marks a task that always runs
   /// (non-cacheable), which forces its dependents to be non-cacheable too.
   task_fingerprints: std::cell::RefCell<HashMap<usize, Option<String>>>,
+  /// How each task that ran ended, in completion order, for the summary
+  /// printed when tasks configure a `timeout` or `retries`.
+  task_outcomes: std::cell::RefCell<Vec<TaskOutcome>>,
+}
+
+struct TaskOutcome {
+  task_name: String,
+  package_name: Option<String>,
+  kind: TaskOutcomeKind,
 }
 
+enum TaskOutcomeKind {
+  Succeeded { attempts: u32 },
+  Failed { exit_code: i32, attempts: u32 },
+  TimedOut { seconds: u64, attempts: u32 },
+  Cached,
+}
+
+/// Exit code reported for a task whose last attempt hit its `timeout`,
+/// matching coreutils' `timeout`.
+const TIMEOUT_EXIT_CODE: i32 = 124;
+
 impl<'a> TaskRunner<'a> {
   /// Topologically sort all matched tasks across the given packages into a
   /// single flat list, then run them through `run_tasks_in_parallel` so tasks
@@ -465,7 +508,79 @@ impl<'a> TaskRunner<'a> {
       return Ok(1);
     }
 
-    self.run_tasks_in_parallel(sorted, kill_signal, argv).await
+    let print_summary = sorted.iter().any(|task| match task.task_or_script {
+      TaskOrScript::Task { task, .. } => {
+        task.timeout.is_some() || task.retries > 0
+      }
+      TaskOrScript::Script { .. } => false,
+    });
+    let result = self.run_tasks_in_parallel(sorted, kill_signal, argv).await;
+    if print_summary {
+      self.print_summary();
+    }
+    result
+  }
+
+  fn print_summary(&self) {
+    let outcomes = self.task_outcomes.borrow();
+    if outcomes.is_empty() {
+      return;
+    }
+    let attempts_suffix = |attempts: u32| {
+      if attempts > 1 {
+        format!(" after {attempts} attempts")
+      } else {
+        String::new()
+      }
+    };
+    log::info!("{}", colors::bold("Task summary"));
+    for outcome in outcomes.iter() {
+      let text = match outcome.kind {
+        TaskOutcomeKind::Succeeded { attempts } => {
+          colors::green(format!("succeeded{}", attempts_suffix(attempts)))
+            .to_string()
+        }
+        TaskOutcomeKind::Failed {
+          exit_code,
+          attempts,
+        } => colors::red(format!(
+          "failed with exit code {exit_code}{}",
+          attempts_suffix(attempts)
+        ))
+        .to_string(),
+        TaskOutcomeKind::TimedOut { seconds, attempts } => colors::red(
+          format!("timed out after {seconds}s{}", attempts_suffix(attempts)),
+        )
+        .to_string(),
+        TaskOutcomeKind::Cached => colors::gray("cached").to_string(),
+      };
+      log::info!(
+        "  {}{} {}",
+        colors::cyan(&outcome.task_name),
+        outcome
+          .package_name
+          .as_ref()
+          .filter(
+            |_| self.task_flags.recursive || self.task_flags.filter.is_some()
+          )
+          .map(|p| format!(" ({})", colors::gray(p)))
+          .unwrap_or_default(),
+        text,
+      );
+    }
+  }
+
+  fn record_outcome(
+    &self,
+    task_name: &str,
+    package_name: Option<&str>,
+    kind: TaskOutcomeKind,
+  ) {
+    self.task_outcomes.borrow_mut().push(TaskOutcome {
+      task_name: task_name.to_string(),
+      package_name: package_name.map(|p| p.to_string()),
+      kind,
+    });
   }
 
   pub fn print_available_tasks(
@@ -605,7 +720,7 @@ impl<'a> TaskRunner<'a> {
                   // npm scripts never participate in the input cache; mark
                   // non-cacheable so dependents don't cache against them.
                   runner.record_fingerprint(task.id, None);
-                  runner
+                  let exit_code = runner
                     .run_npm_script(
                       task.task_or_script.folder_url(),
                       task.task_or_script.package_name(),
@@ -616,7 +731,21 @@ impl<'a> TaskRunner<'a> {
                       args,
                       parallel_info,
                     )
-                    .await
+                    .await?;
+                  let kind = if exit_code == 0 {
+                    TaskOutcomeKind::Succeeded { attempts: 1 }
+                  } else {
+                    TaskOutcomeKind::Failed {
+                      exit_code,
+                      attempts: 1,
+                    }
+                  };
+                  runner.record_outcome(
+                    task.name,
+                    task.task_or_script.package_name(),
+                    kind,
+                  );
+                  Ok(exit_code)
                 }
               }
               .map(|exit_code| (exit_code, task))
@@ -730,13 +859,7 @@ impl<'a> TaskRunner<'a> {
 
     self.maybe_npm_install().await?;
 
-    let cwd = match &self.task_flags.cwd {
-      Some(path) => canonicalize_path(Path::new(path))
-        .context("failed canonicalizing --cwd")?,
-      None => {
-        normalize_path(Cow::Owned(dir_url.to_file_path().unwrap())).into_owned()
-      }
-    };
+    let cwd = self.task_cwd(dir_url, definition.cwd.as_deref())?;
 
     let node_modules_bin_dirs =
       task_runner::resolve_task_node_modules_bin_dirs(self.npm_resolver, &cwd);
@@ -747,26 +870,8 @@ impl<'a> TaskRunner<'a> {
     )?;
 
     // Input-based cache: if the task declares `files`, hash inputs +
-    // command + listed env values and skip on match. Only snapshot the env
-    // vars the task actually lists, and only for cacheable tasks, so the
-    // common (non-cacheable) path doesn't clone the whole environment.
-    let env_snapshot: std::collections::BTreeMap<String, String> =
-      if definition.files.is_empty() || definition.env.is_empty() {
-        std::collections::BTreeMap::new()
-      } else {
-        self
-          .env_vars
-          .iter()
-          .filter_map(|(k, v)| {
-            let k = k.to_str()?;
-            if definition.env.iter().any(|name| name == k) {
-              Some((k.to_string(), v.to_str()?.to_string()))
-            } else {
-              None
-            }
-          })
-          .collect()
-      };
+    // command + listed env values and skip on match.
+    let env_snapshot = self.cache_env_snapshot(definition);
     let cache_key = crate::tools::task_cache::TaskCacheKey {
       package_name,
       task_name,
@@ -782,6 +887,7 @@ impl<'a> TaskRunner<'a> {
     let pending_fingerprint = match self.task_cache.lookup(&cache_key) {
       crate::tools::task_cache::CacheLookup::Hit(fingerprint) => {
         self.record_fingerprint(task_id, Some(fingerprint));
+        self.record_outcome(task_name, package_name, TaskOutcomeKind::Cached);
         self.output_task(
           task_name,
           package_name,
@@ -796,6 +902,19 @@ impl<'a> TaskRunner<'a> {
         // Remove artifacts from a previous run so the rebuild doesn't mix
         // stale and fresh outputs.
         self.task_cache.clean_stale_outputs(&cache_key, &fp);
+        if self.task_cache.pull(&cache_key, &fp).await {
+          self.record_fingerprint(task_id, Some(fp.fingerprint));
+          self.record_outcome(task_name, package_name, TaskOutcomeKind::Cached);
+          self.output_task(
+            task_name,
+            package_name,
+            &format!(
+              "{} (cached remotely, inputs unchanged)",
+              colors::gray(task_runner::get_script_with_args(command, argv))
+            ),
+          );
+          return Ok(0);
+        }
         Some(fp)
       }
       crate::tools::task_cache::CacheLookup::NotCacheable => {
@@ -804,32 +923,296 @@ impl<'a> TaskRunner<'a> {
       }
     };
 
-    let exit_code = self
-      .run_single(RunSingleOptions {
+    let max_attempts = definition.retries.saturating_add(1);
+    let mut attempt = 1;
+    let (exit_code, outcome) = loop {
+      // Each attempt gets its own signal so a timeout only kills that
+      // attempt, while ctrl+c on the parent still reaches it.
+      let attempt_signal = kill_signal.child_signal();
+      let run = self.run_single(RunSingleOptions {
         task_name,
         package_name,
         label_name,
         script: command,
         cwd: cwd.clone(),
-        custom_commands,
-        node_modules_bin_dirs,
-        kill_signal,
+        custom_commands: custom_commands.clone(),
+        node_modules_bin_dirs: node_modules_bin_dirs.clone(),
+        kill_signal: attempt_signal.clone(),
         argv,
         parallel_info,
         // npm lifecycle env vars are only set for package.json scripts.
         extra_env_vars: &HashMap::new(),
-      })
-      .await?;
+      });
+      // `None` when the attempt timed out.
+      let exit_code = match definition.timeout {
+        Some(seconds) => {
+          let mut run = std::pin::pin!(run);
+          tokio::select! {
+            exit_code = &mut run => Some(exit_code?),
+            _ = tokio::time::sleep(Duration::from_secs(seconds)) => {
+              attempt_signal.send(deno_task_shell::SignalKind::SIGKILL);
+              run.await?;
+              None
+            }
+          }
+        }
+        None => Some(run.await?),
+      };
+
+      let (exit_code, outcome) = match exit_code {
+        Some(0) => {
+          break (0, TaskOutcomeKind::Succeeded { attempts: attempt });
+        }
+        Some(exit_code) => (
+          exit_code,
+          TaskOutcomeKind::Failed {
+            exit_code,
+            attempts: attempt,
+          },
+        ),
+        None => (
+          TIMEOUT_EXIT_CODE,
+          TaskOutcomeKind::TimedOut {
+            seconds: definition.timeout.unwrap_or_default(),
+            attempts: attempt,
+          },
+        ),
+      };
+      // Don't retry a task that was interrupted with ctrl+c.
+      if attempt >= max_attempts || exit_code == 130 {
+        break (exit_code, outcome);
+      }
+      let reason = match outcome {
+        TaskOutcomeKind::TimedOut { seconds, .. } => {
+          format!("timed out after {seconds}s")
+        }
+        _ => format!("failed with exit code {exit_code}"),
+      };
+      attempt += 1;
+      log::warn!(
+        "{} {} {}, retrying (attempt {} of {})",
+        colors::yellow("Task"),
+        colors::cyan(task_name),
+        reason,
+        attempt,
+        max_attempts,
+      );
+    };
+    self.record_outcome(task_name, package_name, outcome);
 
     if exit_code == 0
       && let Some(fp) = pending_fingerprint
     {
       self.record_fingerprint(task_id, Some(fp.fingerprint.clone()));
       self.task_cache.store(&cache_key, &fp);
+      self.task_cache.push(&cache_key, &fp).await;
     }
     Ok(exit_code)
   }
 
+  /// The directory a task runs in: its `cwd` resolved against the directory
+  /// of the config file that defines it, then `--cwd`, then that directory.
+  fn task_cwd(
+    &self,
+    dir_url: &Url,
+    task_cwd: Option<&str>,
+  ) -> Result<PathBuf, AnyError> {
+    if let Some(task_cwd) = task_cwd {
+      let dir = dir_url.to_file_path().unwrap();
+      let path = normalize_path(Cow::Owned(dir.join(task_cwd))).into_owned();
+      if !path.is_dir() {
+        bail!(
+          "Task \"cwd\" must be an existing directory: {}",
+          path.display()
+        );
+      }
+      return Ok(path);
+    }
+    Ok(match &self.task_flags.cwd {
+      Some(path) => canonicalize_path(Path::new(path))
+        .context("failed canonicalizing --cwd")?,
+      None => {
+        normalize_path(Cow::Owned(dir_url.to_file_path().unwrap())).into_owned()
+      }
+    })
+  }
+
+  /// Snapshot the env vars a task lists for its cache key. Only the listed
+  /// vars are read, and only for cacheable tasks, so the common
+  /// (non-cacheable) path doesn't clone the whole environment.
+  fn cache_env_snapshot(
+    &self,
+    definition: &TaskDefinition,
+  ) -> std::collections::BTreeMap<String, String> {
+    if definition.files.is_empty() || definition.env.is_empty() {
+      return std::collections::BTreeMap::new();
+    }
+    self
+      .env_vars
+      .iter()
+      .filter_map(|(k, v)| {
+        let k = k.to_str()?;
+        if definition.env.iter().any(|name| name == k) {
+          Some((k.to_string(), v.to_str()?.to_string()))
+        } else {
+          None
+        }
+      })
+      .collect()
+  }
+
+  /// Report whether the matched tasks and their dependencies would be
+  /// skipped by the task cache, and what changed since they last ran,
+  /// without running anything (`--explain-cache`).
+  pub async fn explain_cache(
+    &self,
+    packages: &'a [PackageTaskInfo],
+    task_name: &str,
+    argv: &'a [String],
+  ) -> Result<i32, AnyError> {
+    let mut sorted: Vec<ResolvedTask<'a>> = Vec::new();
+    for pkg in packages {
+      if let Err(err) = sort_tasks_topo(pkg, &mut sorted) {
+        return Err(match err {
+          TaskError::NotFound(name) => anyhow!("Task not found: {}", name),
+          TaskError::TaskDepCycle { path } => {
+            anyhow!("Task cycle detected: {}", path.join(" -> "))
+          }
+        });
+      }
+    }
+    if sorted.is_empty() {
+      bail!("Task not found: {}", task_name);
+    }
+
+    for (index, task) in sorted.iter().enumerate() {
+      // Like a real run, only the last task receives the appended arguments.
+      let argv = if index == sorted.len() - 1 { argv } else { &[] };
+      let package_name = task.task_or_script.package_name();
+      let TaskOrScript::Task { task: def, .. } = task.task_or_script else {
+        self.record_fingerprint(task.id, None);
+        self.output_task(
+          task.name,
+          package_name,
+          &colors::gray("would run (npm scripts are never cached)").to_string(),
+        );
+        continue;
+      };
+      let Some(command) = &def.command else {
+        self.record_fingerprint(task.id, None);
+        self.output_task(
+          task.name,
+          package_name,
+          &colors::gray("(no command)").to_string(),
+        );
+        continue;
+      };
+
+      let cwd =
+        self.task_cwd(task.task_or_script.folder_url(), def.cwd.as_deref())?;
+      let env_snapshot = self.cache_env_snapshot(def);
+      let dep_fingerprints = self.dependency_fingerprints(&task.dependencies);
+      let explanation = self
+        .task_cache
+        .explain(&crate::tools::task_cache::TaskCacheKey {
+          package_name,
+          task_name: task.name,
+          cwd: &cwd,
+          command,
+          argv,
+          files: &def.files,
+          output: &def.output,
+          env_names: &def.env,
+          env: &env_snapshot,
+          dep_fingerprints: dep_fingerprints.as_deref(),
+        })
+        .await;
+      self.record_fingerprint(task.id, explanation.fingerprint);
+
+      let outcome = match explanation.outcome {
+        ExplainOutcome::Hit => {
+          colors::green("would be skipped (cache hit)").to_string()
+        }
+        ExplainOutcome::RemoteHit => {
+          colors::green("would be restored from the remote cache").to_string()
+        }
+        ExplainOutcome::Miss => {
+          colors::yellow("would run (cache miss)").to_string()
+        }
+        ExplainOutcome::NoEntry => {
+          colors::yellow("would run (no cached run to compare with)")
+            .to_string()
+        }
+        ExplainOutcome::NotCacheable(reason) => {
+          let reason = match reason {
+            NotCacheableReason::NoFiles => "no \"files\" declared",
+            NotCacheableReason::Dependency => "a dependency always runs",
+            NotCacheableReason::NoMatchingFiles => "\"files\" match nothing",
+          };
+          colors::gray(format!("would run (not cacheable: {reason})"))
+            .to_string()
+        }
+      };
+      self.output_task(task.name, package_name, &outcome);
+
+      let dependency_name = |fingerprint: &str| {
+        let fingerprints = self.task_fingerprints.borrow();
+        task
+          .dependencies
+          .iter()
+          .find(|dep_id| {
+            fingerprints.get(dep_id).and_then(|fp| fp.as_deref())
+              == Some(fingerprint)
+          })
+          .and_then(|dep_id| sorted.iter().find(|t| t.id == *dep_id))
+          .map(|t| t.name)
+          .unwrap_or("unknown")
+      };
+      for change in &explanation.changes {
+        let line = match change {
+          CacheChange::FileAdded(path) => format!("input added: {path}"),
+          CacheChange::FileRemoved(path) => format!("input removed: {path}"),
+          CacheChange::FileChanged(path) => {
+            format!("input changed: {path} (contents)")
+          }
+          CacheChange::FileTouched(path) => {
+            format!("input touched: {path} (mtime only, contents unchanged)")
+          }
+          CacheChange::FileStatChanged(path) => {
+            format!("input changed: {path} (size or mtime)")
+          }
+          CacheChange::Env {
+            name,
+            was_set,
+            is_set,
+          } => match (was_set, is_set) {
+            (false, true) => format!("env var set: {name}"),
+            (true, false) => format!("env var unset: {name}"),
+            _ => format!("env var changed: {name}"),
+          },
+          CacheChange::Command { stored, current } => {
+            format!("command changed: {stored:?} -> {current:?}")
+          }
+          CacheChange::Argv { stored, current } => {
+            format!("appended arguments changed: {stored:?} -> {current:?}")
+          }
+          CacheChange::Output { stored, current } => {
+            format!("output globs changed: {stored:?} -> {current:?}")
+          }
+          CacheChange::Dependency(fingerprint) => {
+            format!("dependency changed: {}", dependency_name(fingerprint))
+          }
+          CacheChange::Dependencies => "a dependency was removed".to_string(),
+          CacheChange::DenoVersionOrPlatform => {
+            "the Deno version or platform changed".to_string()
+          }
+        };
+        log::info!("  {} {}", colors::gray("-"), line);
+      }
+    }
+    Ok(0)
+  }
+
   #[allow(
     clippy::too_many_arguments,
     reason = "parallel_info was added to an already-large signature; refactoring into a struct is deferred"
@@ -1415,6 +1798,9 @@ fn get_available_tasks(
             files: Vec::new(),
             output: Vec::new(),
             env: Vec::new(),
+            cwd: None,
+            timeout: None,
+            retries: 0,
           },
         });
       }
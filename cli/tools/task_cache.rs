@@ -12,10 +12,11 @@
 //! The fingerprint is split in two so the common "nothing changed" case
 //! avoids reading file contents at all:
 //!
-//! - a *static* hash over everything that isn't a file (command, appended
-//!   argv, listed env values, dependency fingerprints, and the
-//!   platform/version salt), and
-//! - a *content* hash over the input file set (relative paths + contents).
+//! - a *static* hash over everything that isn't a file (task and package
+//!   name, command, appended argv, `output` globs, listed env values,
+//!   dependency fingerprints, and the platform/version salt), and
+//! - a *content* hash over the input file set (relative paths + the SHA-256
+//!   of each file's contents).
 //!
 //! Each entry also records a per-input stat snapshot (size + mtime). On
 //! lookup, if the static hash and every input's size+mtime match the stored
@@ -28,35 +29,72 @@
 //! collision silently skipping a build with stale output, so we use a wide
 //! cryptographic hash rather than a fast non-cryptographic one.
 //!
+//! The manifest also records the static inputs themselves (with env values
+//! hashed, since they may be secrets) and each input's content hash, so
+//! `deno task --explain-cache` can say which of them changed rather than only
+//! that the fingerprint did.
+//!
 //! Outputs are captured into the cache directory after a successful run and
 //! restored on a hit, so deleting a task's declared outputs (e.g. removing
 //! `dist/`) and re-running regenerates them from the cache instead of
-//! silently leaving them missing. Stale outputs from a previous run are
+//! silently leaving them missing. Their permission bits are recorded and
+//! restored along with them. Stale outputs from a previous run are
 //! cleaned before a re-run so a build does not mix old and new artifacts.
 //!
 //! The on-disk format is a small JSON manifest per task plus a directory of
 //! captured outputs; an unrecognized or older payload simply fails to parse
 //! and is treated as a miss, so the schema can evolve without migration.
 //!
+//! A remote cache (`taskCache.remote` in `deno.json`) can be shared between
+//! machines. It is consulted after a local miss and written after a
+//! successful run. It is keyed by the fingerprint rather than the task's
+//! identity, since the fingerprint does not depend on where the workspace is
+//! checked out: `v1/manifests/<fingerprint>.json` lists the outputs and the
+//! SHA-256 of their contents, and the contents are stored under
+//! `v1/outputs/<sha256>`. Downloaded outputs are verified against their hash
+//! and must stay inside the task's directory, and in read-only mode (for
+//! untrusted builds) nothing is uploaded. Remote failures are reported as
+//! warnings and otherwise treated as a miss.
+//!
 //! Caveat: the fingerprint is captured before the run and stored afterwards,
 //! so a task that writes into its own `files` (formatters, codegen) changes
 //! its inputs as a side effect and will never match on the next run. It stays
 //! correct, just never caches.
 
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 use std::time::UNIX_EPOCH;
 
+use deno_cache_dir::file_fetcher::AuthTokens;
+use deno_config::deno_json::TaskCacheConfig;
+use deno_config::deno_json::TaskCacheRemote;
 use deno_config::glob::FileCollector;
 use deno_config::glob::FilePatterns;
 use deno_config::glob::PathOrPatternSet;
+use deno_core::anyhow::bail;
+use deno_core::error::AnyError;
 use deno_core::serde_json;
+use deno_core::url::Url;
+use deno_path_util::fs::atomic_write_file_with_retries;
+use deno_runtime::deno_fetch;
+use http::StatusCode;
+use http::header::AUTHORIZATION;
+use http::header::HeaderValue;
+use http_body_util::BodyExt;
 use serde::Deserialize;
 use serde::Serialize;
 use sha2::Digest;
 use sha2::Sha256;
 
+use crate::cache::CACHE_PERM;
+use crate::colors;
+use crate::http_util::HttpClient;
+use crate::http_util::HttpClientProvider;
 use crate::sys::CliSys;
 
 /// Result of consulting the cache for a task.
@@ -81,20 +119,107 @@ pub struct Fingerprint {
   pub fingerprint: String,
   static_hash: String,
   content_hash: String,
+  inputs: StaticInputs,
   files: Vec<FileStat>,
+  file_hashes: BTreeMap<String, String>,
   /// Outputs captured by the previous successful run, if any. Removed before
   /// the re-run so stale artifacts don't linger.
   prior_outputs: Vec<String>,
 }
 
+impl Fingerprint {
+  fn to_manifest(&self, outputs: Vec<String>) -> CacheManifest {
+    CacheManifest {
+      fingerprint: self.fingerprint.clone(),
+      static_hash: self.static_hash.clone(),
+      content_hash: self.content_hash.clone(),
+      inputs: Some(self.inputs.clone()),
+      files: self.files.clone(),
+      file_hashes: self.file_hashes.clone(),
+      outputs,
+      modes: BTreeMap::new(),
+    }
+  }
+}
+
+/// Why [`TaskCache::explain`] found a task to be non-cacheable.
+pub enum NotCacheableReason {
+  /// The task declares no `files`.
+  NoFiles,
+  /// A dependency always runs.
+  Dependency,
+  /// The `files` globs are invalid or match nothing.
+  NoMatchingFiles,
+}
+
+pub enum ExplainOutcome {
+  Hit,
+  /// The local entry misses, but the remote cache has an entry for the
+  /// fingerprint, from which the outputs would be restored.
+  RemoteHit,
+  Miss,
+  /// The task has no stored entry, ex. because it never completed.
+  NoEntry,
+  NotCacheable(NotCacheableReason),
+}
+
+/// A difference between the stored entry and the current state of a task's
+/// inputs.
+#[derive(Debug, Eq, PartialEq)]
+pub enum CacheChange {
+  FileAdded(String),
+  FileRemoved(String),
+  FileChanged(String),
+  /// The file's size or mtime changed, but its contents didn't.
+  FileTouched(String),
+  /// The file's size or mtime changed, and the entry predates per-file
+  /// content hashes, so whether its contents did is unknown.
+  FileStatChanged(String),
+  Env {
+    name: String,
+    was_set: bool,
+    is_set: bool,
+  },
+  Command {
+    stored: String,
+    current: String,
+  },
+  Argv {
+    stored: Vec<String>,
+    current: Vec<String>,
+  },
+  Output {
+    stored: Vec<String>,
+    current: Vec<String>,
+  },
+  /// A dependency with this fingerprint wasn't among those of the stored
+  /// entry.
+  Dependency(String),
+  /// A dependency was removed.
+  Dependencies,
+  /// None of the above differ, so the Deno version or the platform did (or
+  /// the entry predates recording the static inputs).
+  DenoVersionOrPlatform,
+}
+
+pub struct CacheExplanation {
+  pub outcome: ExplainOutcome,
+  /// The fingerprint the task would have after this run, or `None` when it
+  /// isn't cacheable.
+  pub fingerprint: Option<String>,
+  pub changes: Vec<CacheChange>,
+}
+
 pub struct TaskCache {
   dir: PathBuf,
+  remote: Option<RemoteTaskCache>,
 }
 
 impl TaskCache {
-  pub fn new(deno_dir_root: &Path) -> Self {
+  pub fn new(deno_dir_root: &Path, remote: Option<RemoteTaskCache>) -> Self {
     Self {
       dir: deno_dir_root.join("task_cache_v1"),
+      remote,
     }
   }
 
@@ -116,9 +241,7 @@ impl TaskCache {
     let static_hash = compute_static_hash(key, dep_fingerprints);
     let stats: Vec<FileStat> = inputs.iter().map(|i| i.stat.clone()).collect();
 
-    let stored = std::fs::read_to_string(self.manifest_path(key))
-      .ok()
-      .and_then(|s| serde_json::from_str::<CacheManifest>(&s).ok());
+    let stored = self.read_manifest(key);
 
     // Fast path: the static inputs and every file's size+mtime match the
     // stored manifest, so the contents cannot have changed. Skip reading them.
@@ -132,34 +255,120 @@ impl TaskCache {
 
     // Slow path: something drifted. Read contents to tell a real edit from an
     // mtime-only touch.
-    let content_hash = compute_content_hash(key, &inputs);
-    let fingerprint = combine_fingerprint(&static_hash, &content_hash);
+    let (content_hash, file_hashes) = compute_content_hash(key, &inputs);
+    let mut fingerprint = Fingerprint {
+      fingerprint: combine_fingerprint(&static_hash, &content_hash),
+      static_hash,
+      content_hash,
+      inputs: static_inputs(key, dep_fingerprints),
+      files: stats,
+      file_hashes,
+      prior_outputs: Vec::new(),
+    };
 
     if let Some(stored) = &stored
-      && stored.static_hash == static_hash
-      && stored.content_hash == content_hash
+      && stored.static_hash == fingerprint.static_hash
+      && stored.content_hash == fingerprint.content_hash
     {
       // Contents are identical; only stat metadata moved. Refresh the manifest
       // so the next run takes the fast path again, restore outputs, and skip.
-      let manifest = CacheManifest {
-        fingerprint: fingerprint.clone(),
-        static_hash,
-        content_hash,
-        files: stats,
-        outputs: stored.outputs.clone(),
-      };
+      let manifest = fingerprint.to_manifest(stored.outputs.clone());
       self.write_manifest(key, &manifest);
       self.restore_outputs(key, stored);
-      return CacheLookup::Hit(fingerprint);
+      return CacheLookup::Hit(fingerprint.fingerprint);
     }
 
-    CacheLookup::Miss(Fingerprint {
-      fingerprint,
-      static_hash,
-      content_hash,
-      files: stats,
-      prior_outputs: stored.map(|s| s.outputs).unwrap_or_default(),
-    })
+    fingerprint.prior_outputs = stored.map(|s| s.outputs).unwrap_or_default();
+    CacheLookup::Miss(fingerprint)
+  }
+
+  /// Compare the current state of a task's inputs with its stored entry, for
+  /// `deno task --explain-cache`. Unlike [`TaskCache::lookup`], this always
+  /// reads the inputs' contents, and neither restores outputs nor writes to
+  /// the cache. On a local miss, only the remote cache's manifest for the
+  /// fingerprint is read, not its outputs.
+  pub async fn explain(&self, key: &TaskCacheKey<'_>) -> CacheExplanation {
+    let not_cacheable = |reason| CacheExplanation {
+      outcome: ExplainOutcome::NotCacheable(reason),
+      fingerprint: None,
+      changes: Vec::new(),
+    };
+    if key.files.is_empty() {
+      return not_cacheable(NotCacheableReason::NoFiles);
+    }
+    let Some(dep_fingerprints) = key.dep_fingerprints else {
+      return not_cacheable(NotCacheableReason::Dependency);
+    };
+    let Some(inputs) = collect_inputs(key) else {
+      return not_cacheable(NotCacheableReason::NoMatchingFiles);
+    };
+    let static_hash = compute_static_hash(key, dep_fingerprints);
+    let (content_hash, file_hashes) = compute_content_hash(key, &inputs);
+    let fingerprint = combine_fingerprint(&static_hash, &content_hash);
+    let Some(stored) = self.read_manifest(key) else {
+      return CacheExplanation {
+        outcome: self
+          .explain_remote(&fingerprint, ExplainOutcome::NoEntry)
+          .await,
+        fingerprint: Some(fingerprint),
+        changes: Vec::new(),
+      };
+    };
+
+    let mut changes = Vec::new();
+    if stored.static_hash != static_hash {
+      if let Some(stored_inputs) = &stored.inputs {
+        diff_static_inputs(
+          stored_inputs,
+          &static_inputs(key, dep_fingerprints),
+          &mut changes,
+        );
+      }
+      if changes.is_empty() {
+        changes.push(CacheChange::DenoVersionOrPlatform);
+      }
+    }
+    let stats_match = diff_files(&stored, &inputs, &file_hashes, &mut changes);
+
+    // Mirrors the fast and slow paths of `lookup`.
+    let hit = stored.static_hash == static_hash
+      && (stats_match || stored.content_hash == content_hash);
+    let outcome = if hit {
+      ExplainOutcome::Hit
+    } else {
+      self
+        .explain_remote(&fingerprint, ExplainOutcome::Miss)
+        .await
+    };
+    CacheExplanation {
+      outcome,
+      fingerprint: Some(fingerprint),
+      changes,
+    }
+  }
+
+  /// After a local miss, check whether [`TaskCache::pull`] would find the
+  /// fingerprint in the remote cache. Returns `local` when it wouldn't, like
+  /// when reading the remote cache fails, which is reported as a warning.
+  async fn explain_remote(
+    &self,
+    fingerprint: &str,
+    local: ExplainOutcome,
+  ) -> ExplainOutcome {
+    let Some(remote) = &self.remote else {
+      return local;
+    };
+    match remote.has_manifest(fingerprint).await {
+      Ok(true) => ExplainOutcome::RemoteHit,
+      Ok(false) => local,
+      Err(err) => {
+        log::warn!(
+          "{} Failed reading from the remote task cache: {err:#}",
+          colors::yellow("Warning")
+        );
+        local
+      }
+    }
   }
 
   /// Remove the outputs captured by a previous run before re-running, so a
@@ -181,25 +390,148 @@ impl TaskCache {
   /// Persist a fingerprint and capture the task's declared outputs for a
   /// successfully completed run.
   pub fn store(&self, key: &TaskCacheKey<'_>, fingerprint: &Fingerprint) {
-    let outputs = self.capture_outputs(key);
-    let manifest = CacheManifest {
-      fingerprint: fingerprint.fingerprint.clone(),
-      static_hash: fingerprint.static_hash.clone(),
-      content_hash: fingerprint.content_hash.clone(),
-      files: fingerprint.files.clone(),
-      outputs,
+    let (outputs, modes) = self.capture_outputs(key);
+    let mut manifest = fingerprint.to_manifest(outputs);
+    manifest.modes = modes;
+    self.write_manifest(key, &manifest);
+  }
+
+  /// After a local miss, look the fingerprint up in the remote cache. On a
+  /// hit, the outputs are downloaded into the local cache and restored, and
+  /// the local manifest is written so the next run hits without the remote.
+  /// Returns whether the task may be skipped.
+  pub async fn pull(
+    &self,
+    key: &TaskCacheKey<'_>,
+    fingerprint: &Fingerprint,
+  ) -> bool {
+    let Some(remote) = &self.remote else {
+      return false;
     };
+    match self.pull_inner(remote, key, fingerprint).await {
+      Ok(hit) => hit,
+      Err(err) => {
+        log::warn!(
+          "{} Failed reading from the remote task cache: {err:#}",
+          colors::yellow("Warning")
+        );
+        false
+      }
+    }
+  }
+
+  async fn pull_inner(
+    &self,
+    remote: &RemoteTaskCache,
+    key: &TaskCacheKey<'_>,
+    fingerprint: &Fingerprint,
+  ) -> Result<bool, AnyError> {
+    let Some(bytes) = remote
+      .get(&remote_manifest_key(&fingerprint.fingerprint))
+      .await?
+    else {
+      return Ok(false);
+    };
+    let remote_manifest: RemoteManifest = serde_json::from_slice(&bytes)?;
+    let outputs_dir = self.outputs_dir(key);
+    remove_if_exists(&outputs_dir)?;
+    let mut outputs = Vec::with_capacity(remote_manifest.outputs.len());
+    let mut modes = BTreeMap::new();
+    for output in &remote_manifest.outputs {
+      let Some(rel) = remote_path_to_relative(&output.path) else {
+        bail!("invalid output path '{}'", output.path);
+      };
+      // An entry whose manifest was written but not all of its outputs (ex.
+      // a concurrent prune) is incomplete, so treat it as a miss.
+      let Some(bytes) = remote.get(&remote_output_key(&output.hash)).await?
+      else {
+        return Ok(false);
+      };
+      if sha256_hex(&bytes) != output.hash {
+        bail!("contents of '{}' don't match their hash", output.path);
+      }
+      let dest = outputs_dir.join(&rel);
+      if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+      }
+      std::fs::write(&dest, &bytes)?;
+      let rel = rel.to_string_lossy().into_owned();
+      // Setuid, setgid and sticky bits from an untrusted manifest are
+      // dropped, so only the permission bits are restored.
+      if let Some(mode) = output.mode {
+        modes.insert(rel.clone(), mode & 0o777);
+      }
+      outputs.push(rel);
+    }
+    let mut manifest = fingerprint.to_manifest(outputs);
+    manifest.modes = modes;
     self.write_manifest(key, &manifest);
+    self.restore_outputs(key, &manifest);
+    Ok(true)
+  }
+
+  /// Upload the entry written by [`TaskCache::store`] to the remote cache,
+  /// unless it is read-only.
+  pub async fn push(&self, key: &TaskCacheKey<'_>, fingerprint: &Fingerprint) {
+    let Some(remote) = &self.remote else {
+      return;
+    };
+    if remote.read_only {
+      return;
+    }
+    if let Err(err) = self.push_inner(remote, key, fingerprint).await {
+      log::warn!(
+        "{} Failed writing to the remote task cache: {err:#}",
+        colors::yellow("Warning")
+      );
+    }
+  }
+
+  async fn push_inner(
+    &self,
+    remote: &RemoteTaskCache,
+    key: &TaskCacheKey<'_>,
+    fingerprint: &Fingerprint,
+  ) -> Result<(), AnyError> {
+    let Some(manifest) = self.read_manifest(key) else {
+      return Ok(());
+    };
+    if manifest.fingerprint != fingerprint.fingerprint {
+      return Ok(());
+    }
+    let outputs_dir = self.outputs_dir(key);
+    let mut outputs = Vec::with_capacity(manifest.outputs.len());
+    for rel in &manifest.outputs {
+      let bytes = std::fs::read(outputs_dir.join(rel))?;
+      let hash = sha256_hex(&bytes);
+      remote.put_output(&hash, bytes).await?;
+      outputs.push(RemoteOutput {
+        path: rel.replace(std::path::MAIN_SEPARATOR, "/"),
+        hash,
+        mode: manifest.modes.get(rel).copied(),
+      });
+    }
+    // Write the manifest last so readers never see an entry whose outputs
+    // haven't been uploaded yet.
+    let json = serde_json::to_vec(&RemoteManifest { outputs })?;
+    remote
+      .put(&remote_manifest_key(&fingerprint.fingerprint), json)
+      .await
   }
 
   /// Copy the files matching the task's `output` globs into the cache so they
-  /// can be restored on a later hit. Returns the captured relative paths.
-  fn capture_outputs(&self, key: &TaskCacheKey<'_>) -> Vec<String> {
+  /// can be restored on a later hit. Returns the captured relative paths and
+  /// the permission bits of each.
+  fn capture_outputs(
+    &self,
+    key: &TaskCacheKey<'_>,
+  ) -> (Vec<String>, BTreeMap<String, u32>) {
+    let mut modes = BTreeMap::new();
     if key.output.is_empty() {
-      return Vec::new();
+      return (Vec::new(), modes);
     }
     let Some(files) = collect_files(key.cwd, key.output) else {
-      return Vec::new();
+      return (Vec::new(), modes);
     };
     let outputs_dir = self.outputs_dir(key);
     // Start from a clean slate so removed artifacts don't linger in the cache.
@@ -220,14 +552,19 @@ impl TaskCache {
         log::debug!("failed to cache output {}: {err}", abs.display());
         continue;
       }
-      captured.push(rel.to_string_lossy().into_owned());
+      let rel = rel.to_string_lossy().into_owned();
+      if let Some(mode) = file_mode(&abs) {
+        modes.insert(rel.clone(), mode);
+      }
+      captured.push(rel);
     }
-    captured
+    (captured, modes)
   }
 
   /// Restore captured outputs to the working tree. Files that are missing or
   /// whose contents differ from the cached copy are rewritten; identical files
-  /// are left untouched so we don't churn mtimes needlessly.
+  /// are left untouched so we don't churn mtimes needlessly. Either way, the
+  /// recorded permission bits are reapplied.
   fn restore_outputs(&self, key: &TaskCacheKey<'_>, manifest: &CacheManifest) {
     let outputs_dir = self.outputs_dir(key);
     for rel in &manifest.outputs {
@@ -236,21 +573,39 @@ impl TaskCache {
       if !cached.exists() {
         continue;
       }
-      if files_have_equal_contents(&cached, &dest) {
-        continue;
+      if !files_have_equal_contents(&cached, &dest) {
+        if let Some(parent) = dest.parent()
+          && let Err(err) = std::fs::create_dir_all(parent)
+        {
+          log::debug!(
+            "failed to create output dir {}: {err}",
+            parent.display()
+          );
+          continue;
+        }
+        if let Err(err) = std::fs::copy(&cached, &dest) {
+          log::debug!("failed to restore output {}: {err}", dest.display());
+          continue;
+        }
       }
-      if let Some(parent) = dest.parent()
-        && let Err(err) = std::fs::create_dir_all(parent)
+      if let Some(mode) = manifest.modes.get(rel)
+        && file_mode(&dest) != Some(*mode)
+        && let Err(err) = set_file_mode(&dest, *mode)
       {
-        log::debug!("failed to create output dir {}: {err}", parent.display());
-        continue;
-      }
-      if let Err(err) = std::fs::copy(&cached, &dest) {
-        log::debug!("failed to restore output {}: {err}", dest.display());
+        log::debug!(
+          "failed to restore permissions of {}: {err}",
+          dest.display()
+        );
       }
     }
   }
 
+  fn read_manifest(&self, key: &TaskCacheKey<'_>) -> Option<CacheManifest> {
+    std::fs::read_to_string(self.manifest_path(key))
+      .ok()
+      .and_then(|s| serde_json::from_str::<CacheManifest>(&s).ok())
+  }
+
   fn write_manifest(&self, key: &TaskCacheKey<'_>, manifest: &CacheManifest) {
     let entry_dir = self.entry_dir(key);
     if let Err(err) = std::fs::create_dir_all(&entry_dir) {
@@ -297,6 +652,165 @@ impl TaskCache {
   }
 }
 
+/// A cache shared between machines, configured by `taskCache` in `deno.json`.
+pub struct RemoteTaskCache {
+  store: RemoteStore,
+  /// Only pull from the remote cache. Set by `taskCache.readOnly` or the
+  /// `DENO_TASK_CACHE_READ_ONLY` environment variable.
+  read_only: bool,
+}
+
+enum RemoteStore {
+  Http {
+    client: HttpClient,
+    /// Always ends with a slash, so keys resolve beneath it.
+    base: Url,
+    /// From `DENO_AUTH_TOKENS`, like for remote modules.
+    auth: Option<HeaderValue>,
+  },
+  Directory(PathBuf),
+}
+
+impl RemoteTaskCache {
+  pub fn new(
+    config: TaskCacheConfig,
+    http_client_provider: &HttpClientProvider,
+  ) -> Result<Self, AnyError> {
+    let store = match config.remote {
+      TaskCacheRemote::Http(mut base) => {
+        if !base.path().ends_with('/') {
+          base.set_path(&format!("{}/", base.path()));
+        }
+        let auth = AuthTokens::new_from_sys(&CliSys::default())
+          .get(&base)
+          .map(|token| HeaderValue::from_str(&token.to_string()))
+          .transpose()?;
+        RemoteStore::Http {
+          client: http_client_provider.get_or_create()?,
+          base,
+          auth,
+        }
+      }
+      TaskCacheRemote::Directory(dir) => RemoteStore::Directory(dir),
+    };
+    let read_only = config.read_only
+      || std::env::var("DENO_TASK_CACHE_READ_ONLY").is_ok_and(|value| {
+        !matches!(value.to_ascii_lowercase().as_str(), "" | "0" | "false")
+      });
+    Ok(Self { store, read_only })
+  }
+
+  async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, AnyError> {
+    match &self.store {
+      RemoteStore::Http { client, base, auth } => {
+        let mut request = client.get(base.join(key)?)?;
+        if let Some(auth) = auth {
+          request = request.header(AUTHORIZATION, auth.clone());
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+          return Ok(None);
+        }
+        if !status.is_success() {
+          bail!("GET {} responded with {status}", base.join(key)?);
+        }
+        let bytes = response.into_body().collect().await?.to_bytes();
+        Ok(Some(bytes.to_vec()))
+      }
+      RemoteStore::Directory(dir) => match std::fs::read(dir.join(key)) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+      },
+    }
+  }
+
+  async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), AnyError> {
+    match &self.store {
+      RemoteStore::Http { client, base, auth } => {
+        let body = deno_fetch::ReqBody::full(bytes.into());
+        let mut request = client.put(base.join(key)?, body)?;
+        if let Some(auth) = auth {
+          request = request.header(AUTHORIZATION, auth.clone());
+        }
+        let status = request.send().await?.status();
+        if !status.is_success() {
+          bail!("PUT {} responded with {status}", base.join(key)?);
+        }
+        Ok(())
+      }
+      RemoteStore::Directory(dir) => {
+        let path = dir.join(key);
+        if let Some(parent) = path.parent() {
+          std::fs::create_dir_all(parent)?;
+        }
+        // Other machines may read the directory while this one writes it.
+        atomic_write_file_with_retries(
+          &CliSys::default(),
+          &path,
+          &bytes,
+          CACHE_PERM,
+        )?;
+        Ok(())
+      }
+    }
+  }
+
+  /// Whether there is a valid manifest for `fingerprint`. Its outputs are not
+  /// checked.
+  async fn has_manifest(&self, fingerprint: &str) -> Result<bool, AnyError> {
+    let Some(bytes) = self.get(&remote_manifest_key(fingerprint)).await? else {
+      return Ok(false);
+    };
+    serde_json::from_slice::<RemoteManifest>(&bytes)?;
+    Ok(true)
+  }
+
+  async fn put_output(
+    &self,
+    hash: &str,
+    bytes: Vec<u8>,
+  ) -> Result<(), AnyError> {
+    let key = remote_output_key(hash);
+    // Outputs are content-addressed, so an existing file already holds these
+    // contents. Over HTTP, the store is expected to handle repeated uploads.
+    if let RemoteStore::Directory(dir) = &self.store
+      && dir.join(&key).exists()
+    {
+      return Ok(());
+    }
+    self.put(&key, bytes).await
+  }
+}
+
+fn remote_manifest_key(fingerprint: &str) -> String {
+  format!("v1/manifests/{fingerprint}.json")
+}
+
+fn remote_output_key(hash: &str) -> String {
+  format!("v1/outputs/{hash}")
+}
+
+/// Convert an output path from a remote manifest, which uses `/` as the
+/// separator, into a relative path. Returns `None` for anything that could
+/// escape the task's directory, since the remote cache may not be trusted.
+fn remote_path_to_relative(path: &str) -> Option<PathBuf> {
+  let mut rel = PathBuf::new();
+  for part in path.split('/') {
+    let mut components = Path::new(part).components();
+    match (components.next(), components.next()) {
+      (Some(Component::Normal(part)), None) => rel.push(part),
+      _ => return None,
+    }
+  }
+  Some(rel)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+  faster_hex::hex_string(&Sha256::digest(bytes))
+}
+
 pub struct TaskCacheKey<'a> {
   pub package_name: Option<&'a str>,
   pub task_name: &'a str,
@@ -328,19 +842,64 @@ struct CacheManifest {
   /// Overall fingerprint, `hash(static_hash, content_hash)`. Used as the
   /// dependency-cascade key for downstream tasks.
   fingerprint: String,
-  /// Hash of everything that isn't an input file: the command, appended argv,
-  /// listed env values, dependency fingerprints, and the platform/version
-  /// salt.
+  /// Hash of everything that isn't an input file: the task and package name,
+  /// the command, appended argv, `output` globs, listed env values,
+  /// dependency fingerprints, and the platform/version salt.
   static_hash: String,
-  /// Hash of the input file set (relative paths + contents).
+  /// Hash of the input file set (relative paths + content hashes).
   content_hash: String,
+  /// The inputs behind `static_hash`, for explaining a miss. Missing from
+  /// entries written before they were recorded.
+  #[serde(default)]
+  inputs: Option<StaticInputs>,
   /// Per-input stat snapshot, enabling the size+mtime fast path that skips
   /// re-reading contents when nothing has changed.
   files: Vec<FileStat>,
+  /// SHA-256 of each readable input's contents, keyed by relative path, for
+  /// telling an edit from an mtime-only touch when explaining a miss.
+  #[serde(default)]
+  file_hashes: BTreeMap<String, String>,
   /// Relative paths of the outputs captured under `outputs/`, for restoration
   /// on a hit and cleanup before a re-run.
   #[serde(default)]
   outputs: Vec<String>,
+  /// Permission bits of each captured output, keyed by relative path. Empty
+  /// on platforms without them.
+  #[serde(default)]
+  modes: BTreeMap<String, u32>,
+}
+
+/// Remote cache entry for a fingerprint. Stat snapshots are machine-specific,
+/// so only the outputs are shared.
+#[derive(Serialize, Deserialize)]
+struct RemoteManifest {
+  outputs: Vec<RemoteOutput>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RemoteOutput {
+  /// Path relative to the task's cwd, separated by `/`.
+  path: String,
+  /// SHA-256 of the contents, which are stored under `v1/outputs/<hash>`.
+  hash: String,
+  /// Permission bits, when recorded.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  mode: Option<u32>,
+}
+
+/// The inputs hashed into `static_hash`, other than the task and package name
+/// (which also identify the entry) and the platform/version salt.
+#[derive(Clone, Serialize, Deserialize)]
+struct StaticInputs {
+  command: String,
+  argv: Vec<String>,
+  #[serde(default)]
+  output: Vec<String>,
+  /// SHA-256 of each listed env var's value, or `None` when it's unset. Only
+  /// the hash is stored since the values may be secrets.
+  env: BTreeMap<String, Option<String>>,
+  /// Dependency fingerprints, sorted.
+  dependencies: Vec<String>,
 }
 
 #[derive(Clone, PartialEq, Serialize, Deserialize)]
@@ -414,14 +973,14 @@ fn collect_files(cwd: &Path, globs: &[String]) -> Option<Vec<PathBuf>> {
   Some(files)
 }
 
-/// Hash everything that isn't an input file's contents: the command, the
-/// appended CLI args, the listed env values, the dependency fingerprints, and
-/// a platform/version salt.
+/// Hash everything that isn't an input file's contents: the task and package
+/// name, the command, the appended CLI args, the `output` globs, the listed
+/// env values, the dependency fingerprints, and a platform/version salt.
 fn compute_static_hash(
   key: &TaskCacheKey<'_>,
   dep_fingerprints: &[String],
 ) -> String {
-  let mut hasher = FingerprintHasher::new("deno-task-cache-static-v1");
+  let mut hasher = FingerprintHasher::new("deno-task-cache-static-v2");
   // Fold in the target OS, architecture, and Deno version: a task's output can
   // legitimately differ across platforms or releases even when its inputs are
   // byte-for-byte identical, so a cache shared across them (e.g. a synced
@@ -429,6 +988,16 @@ fn compute_static_hash(
   hasher.write_str(env!("CARGO_PKG_VERSION"));
   hasher.write_str(std::env::consts::OS);
   hasher.write_str(std::env::consts::ARCH);
+  // The remote cache is keyed by the fingerprint alone, so two tasks running
+  // the same command over the same files must not share an entry.
+  match key.package_name {
+    Some(package_name) => {
+      hasher.write_u8(1);
+      hasher.write_str(package_name);
+    }
+    None => hasher.write_u8(0),
+  }
+  hasher.write_str(key.task_name);
   hasher.write_str(key.command);
 
   // Appended CLI args materially change what runs, so fold them in.
@@ -437,6 +1006,12 @@ fn compute_static_hash(
     hasher.write_str(arg);
   }
 
+  // The outputs restored on a hit are the ones captured for these globs.
+  hasher.write_u64(key.output.len() as u64);
+  for glob in key.output {
+    hasher.write_str(glob);
+  }
+
   // Dependency fingerprints, sorted for determinism. A change upstream
   // (an input edit that made a dependency re-run) bubbles down here.
   let mut deps = dep_fingerprints.to_vec();
@@ -467,13 +1042,129 @@ fn compute_static_hash(
   hasher.finish_hex()
 }
 
-/// Hash the input file set: each file's relative path mixed with its contents,
-/// in the sorted order produced by [`collect_inputs`].
+fn static_inputs(
+  key: &TaskCacheKey<'_>,
+  dep_fingerprints: &[String],
+) -> StaticInputs {
+  let mut dependencies = dep_fingerprints.to_vec();
+  dependencies.sort();
+  StaticInputs {
+    command: key.command.to_string(),
+    argv: key.argv.to_vec(),
+    output: key.output.to_vec(),
+    env: key
+      .env_names
+      .iter()
+      .map(|name| {
+        let hash = key.env.get(name).map(|value| sha256_hex(value.as_bytes()));
+        (name.clone(), hash)
+      })
+      .collect(),
+    dependencies,
+  }
+}
+
+fn diff_static_inputs(
+  stored: &StaticInputs,
+  current: &StaticInputs,
+  changes: &mut Vec<CacheChange>,
+) {
+  if stored.command != current.command {
+    changes.push(CacheChange::Command {
+      stored: stored.command.clone(),
+      current: current.command.clone(),
+    });
+  }
+  if stored.argv != current.argv {
+    changes.push(CacheChange::Argv {
+      stored: stored.argv.clone(),
+      current: current.argv.clone(),
+    });
+  }
+  if stored.output != current.output {
+    changes.push(CacheChange::Output {
+      stored: stored.output.clone(),
+      current: current.output.clone(),
+    });
+  }
+  let names = stored.env.keys().chain(current.env.keys());
+  for name in names.collect::<BTreeSet<_>>() {
+    let stored_value = stored.env.get(name).and_then(|v| v.as_ref());
+    let current_value = current.env.get(name).and_then(|v| v.as_ref());
+    if stored_value != current_value {
+      changes.push(CacheChange::Env {
+        name: name.clone(),
+        was_set: stored_value.is_some(),
+        is_set: current_value.is_some(),
+      });
+    }
+  }
+  let mut dependency_changed = false;
+  for dep in &current.dependencies {
+    if !stored.dependencies.contains(dep) {
+      changes.push(CacheChange::Dependency(dep.clone()));
+      dependency_changed = true;
+    }
+  }
+  if !dependency_changed && stored.dependencies != current.dependencies {
+    changes.push(CacheChange::Dependencies);
+  }
+}
+
+/// Record how the input files differ from the stored entry. Returns whether
+/// every file's stat snapshot matches, in which case `lookup` hits without
+/// reading contents.
+fn diff_files(
+  stored: &CacheManifest,
+  inputs: &[InputFile],
+  file_hashes: &BTreeMap<String, String>,
+  changes: &mut Vec<CacheChange>,
+) -> bool {
+  let stored_files: HashMap<&str, &FileStat> = stored
+    .files
+    .iter()
+    .map(|stat| (stat.path.as_str(), stat))
+    .collect();
+  for input in inputs {
+    let path = &input.stat.path;
+    let Some(stored_stat) = stored_files.get(path.as_str()) else {
+      changes.push(CacheChange::FileAdded(path.clone()));
+      continue;
+    };
+    if **stored_stat == input.stat {
+      continue;
+    }
+    let change = match stored.file_hashes.get(path) {
+      None => CacheChange::FileStatChanged(path.clone()),
+      Some(hash) if file_hashes.get(path) == Some(hash) => {
+        CacheChange::FileTouched(path.clone())
+      }
+      Some(_) => CacheChange::FileChanged(path.clone()),
+    };
+    changes.push(change);
+  }
+  let current_paths: HashSet<&str> = inputs
+    .iter()
+    .map(|input| input.stat.path.as_str())
+    .collect();
+  for stat in &stored.files {
+    if !current_paths.contains(stat.path.as_str()) {
+      changes.push(CacheChange::FileRemoved(stat.path.clone()));
+    }
+  }
+  let current_stats = inputs.iter().map(|input| &input.stat);
+  stored.files.iter().eq(current_stats)
+}
+
+/// Hash the input file set: each file's relative path mixed with the hash of
+/// its contents, in the sorted order produced by [`collect_inputs`]. Also
+/// returns the content hash of each readable file, keyed by relative path.
 fn compute_content_hash(
   key: &TaskCacheKey<'_>,
   inputs: &[InputFile],
-) -> String {
-  let mut hasher = FingerprintHasher::new("deno-task-cache-content-v1");
+) -> (String, BTreeMap<String, String>) {
+  let mut hasher = FingerprintHasher::new("deno-task-cache-content-v2");
+  let mut file_hashes = BTreeMap::new();
   for input in inputs {
     let rel = input
       .abs_path
@@ -482,15 +1173,17 @@ fn compute_content_hash(
     hasher.write_bytes(rel.as_os_str().as_encoded_bytes());
     match std::fs::read(&input.abs_path) {
       Ok(bytes) => {
+        let file_hash = sha256_hex(&bytes);
         hasher.write_u8(1);
-        hasher.write_bytes(&bytes);
+        hasher.write_str(&file_hash);
+        file_hashes.insert(input.stat.path.clone(), file_hash);
       }
       Err(_) => {
         hasher.write_u8(0);
       }
     }
   }
-  hasher.finish_hex()
+  (hasher.finish_hex(), file_hashes)
 }
 
 /// Combine the static and content hashes into the task's overall fingerprint.
@@ -521,6 +1214,29 @@ fn remove_if_exists(path: &Path) -> std::io::Result<()> {
   }
 }
 
+#[cfg(unix)]
+fn file_mode(path: &Path) -> Option<u32> {
+  use std::os::unix::fs::PermissionsExt;
+  let meta = std::fs::metadata(path).ok()?;
+  Some(meta.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn file_mode(_path: &Path) -> Option<u32> {
+  None
+}
+
+#[cfg(unix)]
+fn set_file_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+  use std::os::unix::fs::PermissionsExt;
+  std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_file_mode(_path: &Path, _mode: u32) -> std::io::Result<()> {
+  Ok(())
+}
+
 /// Whether two files exist and have byte-identical contents. Used to avoid
 /// rewriting an output that already matches the cached copy.
 fn files_have_equal_contents(a: &Path, b: &Path) -> bool {
@@ -563,3 +1279,277 @@ impl FingerprintHasher {
     faster_hex::hex_string(&self.0.finalize())
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use test_util::TempDir;
+
+  use super::*;
+
+  #[tokio::test]
+  async fn explain_cache_changes() {
+    let temp_dir = TempDir::new();
+    temp_dir.write("src/a.ts", "a");
+    temp_dir.write("src/b.ts", "b");
+    let cwd = temp_dir.path().to_path_buf();
+    let cache = TaskCache::new(&cwd.join("deno_dir"), None);
+    let files = vec!["src".to_string()];
+    let env_names = vec!["MODE".to_string()];
+    let key = |command: &'static str, env| TaskCacheKey {
+      package_name: None,
+      task_name: "build",
+      cwd: &cwd,
+      command,
+      argv: &[],
+      files: &files,
+      output: &[],
+      env_names: &env_names,
+      env,
+      dep_fingerprints: Some(&[]),
+    };
+    let dev = BTreeMap::from([("MODE".to_string(), "dev".to_string())]);
+
+    let explanation = cache.explain(&key("build", &dev)).await;
+    assert!(matches!(explanation.outcome, ExplainOutcome::NoEntry));
+    let CacheLookup::Miss(fingerprint) = cache.lookup(&key("build", &dev))
+    else {
+      panic!("expected a miss");
+    };
+    cache.store(&key("build", &dev), &fingerprint);
+    let explanation = cache.explain(&key("build", &dev)).await;
+    assert!(matches!(explanation.outcome, ExplainOutcome::Hit));
+    assert_eq!(explanation.changes, vec![]);
+
+    let path =
+      |name: &str| Path::new("src").join(name).to_string_lossy().into_owned();
+    // only the mtime of a.ts moves
+    std::fs::File::options()
+      .write(true)
+      .open(cwd.join("src/a.ts"))
+      .unwrap()
+      .set_modified(UNIX_EPOCH + std::time::Duration::from_secs(60))
+      .unwrap();
+    let explanation = cache.explain(&key("build", &dev)).await;
+    assert!(matches!(explanation.outcome, ExplainOutcome::Hit));
+    assert_eq!(
+      explanation.changes,
+      vec![CacheChange::FileTouched(path("a.ts"))]
+    );
+
+    temp_dir.write("src/b.ts", "changed");
+    temp_dir.write("src/c.ts", "c");
+    let prod = BTreeMap::from([("MODE".to_string(), "prod".to_string())]);
+    let explanation = cache.explain(&key("build --minify", &prod)).await;
+    assert!(matches!(explanation.outcome, ExplainOutcome::Miss));
+    assert_eq!(
+      explanation.changes,
+      vec![
+        CacheChange::Command {
+          stored: "build".to_string(),
+          current: "build --minify".to_string(),
+        },
+        CacheChange::Env {
+          name: "MODE".to_string(),
+          was_set: true,
+          is_set: true,
+        },
+        CacheChange::FileTouched(path("a.ts")),
+        CacheChange::FileChanged(path("b.ts")),
+        CacheChange::FileAdded(path("c.ts")),
+      ]
+    );
+  }
+
+  #[test]
+  fn static_hash_covers_task_identity_and_outputs() {
+    let cwd = PathBuf::from("/project");
+    let env = BTreeMap::new();
+    let dist = vec!["dist".to_string()];
+    let hash =
+      |package_name: Option<&str>, task_name: &str, output: &[String]| {
+        let key = TaskCacheKey {
+          package_name,
+          task_name,
+          cwd: &cwd,
+          command: "build",
+          argv: &[],
+          files: &[],
+          output,
+          env_names: &[],
+          env: &env,
+          dep_fingerprints: Some(&[]),
+        };
+        compute_static_hash(&key, &[])
+      };
+    let hashes = [
+      hash(None, "build", &[]),
+      hash(None, "build", &dist),
+      hash(None, "bundle", &[]),
+      hash(Some("@scope/a"), "build", &[]),
+    ];
+    assert_eq!(hashes.iter().collect::<HashSet<_>>().len(), hashes.len());
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn restore_outputs_with_their_mode() {
+    let temp_dir = TempDir::new();
+    temp_dir.write("src/a.ts", "a");
+    temp_dir.write("bin/run", "#!/bin/sh");
+    let cwd = temp_dir.path().to_path_buf();
+    set_file_mode(&cwd.join("bin/run"), 0o755).unwrap();
+    let cache = TaskCache::new(&cwd.join("deno_dir"), None);
+    let files = vec!["src".to_string()];
+    let output = vec!["bin".to_string()];
+    let env = BTreeMap::new();
+    let key = TaskCacheKey {
+      package_name: None,
+      task_name: "build",
+      cwd: &cwd,
+      command: "build",
+      argv: &[],
+      files: &files,
+      output: &output,
+      env_names: &[],
+      env: &env,
+      dep_fingerprints: Some(&[]),
+    };
+    let CacheLookup::Miss(fingerprint) = cache.lookup(&key) else {
+      panic!("expected a miss");
+    };
+    cache.store(&key, &fingerprint);
+
+    // same contents, lost mode
+    set_file_mode(&cwd.join("bin/run"), 0o644).unwrap();
+    assert!(matches!(cache.lookup(&key), CacheLookup::Hit(_)));
+    assert_eq!(file_mode(&cwd.join("bin/run")), Some(0o755));
+
+    std::fs::remove_file(cwd.join("bin/run")).unwrap();
+    assert!(matches!(cache.lookup(&key), CacheLookup::Hit(_)));
+    assert_eq!(file_mode(&cwd.join("bin/run")), Some(0o755));
+  }
+
+  #[cfg(unix)]
+  #[tokio::test]
+  async fn remote_modes_are_masked() {
+    let temp_dir = TempDir::new();
+    temp_dir.write("src/a.ts", "a");
+    let cwd = temp_dir.path().to_path_buf();
+    let remote_dir = cwd.join("remote");
+    let cache = TaskCache::new(
+      &cwd.join("deno_dir"),
+      Some(RemoteTaskCache {
+        store: RemoteStore::Directory(remote_dir.clone()),
+        read_only: true,
+      }),
+    );
+    let files = vec!["src".to_string()];
+    let output = vec!["bin".to_string()];
+    let env = BTreeMap::new();
+    let key = TaskCacheKey {
+      package_name: None,
+      task_name: "build",
+      cwd: &cwd,
+      command: "build",
+      argv: &[],
+      files: &files,
+      output: &output,
+      env_names: &[],
+      env: &env,
+      dep_fingerprints: Some(&[]),
+    };
+    let CacheLookup::Miss(fingerprint) = cache.lookup(&key) else {
+      panic!("expected a miss");
+    };
+
+    let contents = b"#!/bin/sh";
+    let hash = sha256_hex(contents);
+    let output_path = remote_dir.join(remote_output_key(&hash));
+    std::fs::create_dir_all(output_path.parent().unwrap()).unwrap();
+    std::fs::write(&output_path, contents).unwrap();
+    let manifest = RemoteManifest {
+      outputs: vec![RemoteOutput {
+        path: "bin/run".to_string(),
+        hash,
+        mode: Some(0o4755),
+      }],
+    };
+    let manifest_path =
+      remote_dir.join(remote_manifest_key(&fingerprint.fingerprint));
+    std::fs::create_dir_all(manifest_path.parent().unwrap()).unwrap();
+    std::fs::write(&manifest_path, serde_json::to_vec(&manifest).unwrap())
+      .unwrap();
+
+    assert!(cache.pull(&key, &fingerprint).await);
+    assert_eq!(file_mode(&cwd.join("bin/run")), Some(0o755));
+  }
+
+  #[tokio::test]
+  async fn explain_cache_consults_the_remote_cache() {
+    let temp_dir = TempDir::new();
+    temp_dir.write("src/a.ts", "a");
+    let cwd = temp_dir.path().to_path_buf();
+    let cache = |deno_dir: &str| {
+      TaskCache::new(
+        &cwd.join(deno_dir),
+        Some(RemoteTaskCache {
+          store: RemoteStore::Directory(cwd.join("remote")),
+          read_only: false,
+        }),
+      )
+    };
+    let files = vec!["src".to_string()];
+    let env = BTreeMap::new();
+    let key = TaskCacheKey {
+      package_name: None,
+      task_name: "build",
+      cwd: &cwd,
+      command: "build",
+      argv: &[],
+      files: &files,
+      output: &[],
+      env_names: &[],
+      env: &env,
+      dep_fingerprints: Some(&[]),
+    };
+    let pushing = cache("deno_dir_a");
+    let CacheLookup::Miss(fingerprint) = pushing.lookup(&key) else {
+      panic!("expected a miss");
+    };
+    pushing.store(&key, &fingerprint);
+    pushing.push(&key, &fingerprint).await;
+
+    // no local entry, but the remote cache has one
+    let explanation = cache("deno_dir_b").explain(&key).await;
+    assert!(matches!(explanation.outcome, ExplainOutcome::RemoteHit));
+
+    temp_dir.write("src/a.ts", "changed");
+    let explanation = cache("deno_dir_b").explain(&key).await;
+    assert!(matches!(explanation.outcome, ExplainOutcome::NoEntry));
+    let explanation = pushing.explain(&key).await;
+    assert!(matches!(explanation.outcome, ExplainOutcome::Miss));
+  }
+
+  #[test]
+  fn remote_paths_stay_relative() {
+    assert_eq!(
+      remote_path_to_relative("dist/out.js"),
+      Some(PathBuf::from("dist").join("out.js"))
+    );
+    assert_eq!(
+      remote_path_to_relative("out.js"),
+      Some(PathBuf::from("out.js"))
+    );
+    for path in [
+      "",
+      "/etc/passwd",
+      "../out.js",
+      "dist/../../out.js",
+      "dist//out.js",
+      "./out.js",
+      "dist/",
+    ] {
+      assert_eq!(remote_path_to_relative(path), None, "{path}");
+    }
+  }
+}
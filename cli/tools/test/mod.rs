@@ -359,6 +359,7 @@ pub enum TestFailure {
   FailedSteps(usize),
   IncompleteSteps,
   PendingPromiseResolution,
+  PendingVirtualTimers,
   Leaked(Vec<String>, Vec<String>), // Details, trailer notes
   TimedOut(u32),
   // The rest are for steps only.
@@ -386,6 +387,9 @@ impl TestFailure {
       TestFailure::PendingPromiseResolution => Cow::Borrowed(
         "Promise resolution is still pending but the event loop has already resolved.",
       ),
+      TestFailure::PendingVirtualTimers => Cow::Borrowed(
+        "Test is waiting on pending virtual timers, but the virtual clock is never advanced. Call `Deno.test.clock.tick()` to fire them.",
+      ),
       TestFailure::Incomplete => Cow::Borrowed(
         "Didn't complete before parent. Await step with `await t.step(...)`.",
       ),
@@ -433,6 +437,9 @@ impl TestFailure {
       TestFailure::PendingPromiseResolution => {
         "Promise resolution is still pending".to_string()
       }
+      TestFailure::PendingVirtualTimers => {
+        "Virtual timers are still pending".to_string()
+      }
       TestFailure::Incomplete => "Didn't complete before parent".to_string(),
       TestFailure::Leaked(_, _) => "Leaks detected".to_string(),
       TestFailure::TimedOut(ms) => format!("Timed out after {}ms", ms),
@@ -1425,6 +1432,9 @@ async fn invoke_test_function(
           TestFailure::PendingPromiseResolution,
         )))
       }
+      CoreErrorKind::PendingVirtualTimers => Ok(AttemptInvocation::Completed(
+        TestResult::Failed(TestFailure::PendingVirtualTimers),
+      )),
       err => Err(err.into_box().into()),
     },
   }
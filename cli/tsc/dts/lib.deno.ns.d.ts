@@ -247,6 +247,41 @@ declare namespace Deno {
      *
      * @category Errors */
     export class TimedOut extends Error {}
+    /**
+     * Raised when the connection to the server could not be opened within
+     * the {@linkcode Deno.HttpClientTimeouts.connect} timeout of a
+     * {@linkcode Deno.HttpClient}.
+     *
+     * @category Errors */
+    export class ConnectTimeout extends TimedOut {}
+    /**
+     * Raised when the TLS handshake with the server did not complete within
+     * the {@linkcode Deno.HttpClientTimeouts.tlsHandshake} timeout of a
+     * {@linkcode Deno.HttpClient}.
+     *
+     * @category Errors */
+    export class TlsHandshakeTimeout extends TimedOut {}
+    /**
+     * Raised when the response headers were not received within the
+     * {@linkcode Deno.HttpClientTimeouts.responseHeaders} timeout of a
+     * {@linkcode Deno.HttpClient}.
+     *
+     * @category Errors */
+    export class ResponseHeadersTimeout extends TimedOut {}
+    /**
+     * Raised when no response body data was received within the
+     * {@linkcode Deno.HttpClientTimeouts.idleRead} timeout of a
+     * {@linkcode Deno.HttpClient}.
+     *
+     * @category Errors */
+    export class IdleReadTimeout extends TimedOut {}
+    /**
+     * Raised when a request, response body included, did not complete within
+     * the {@linkcode Deno.HttpClientTimeouts.total} timeout of a
+     * {@linkcode Deno.HttpClient}.
+     *
+     * @category Errors */
+    export class TotalTimeout extends TimedOut {}
     /**
      * Raised when the underlying operating system reports an `EINTR` error. In
      * many cases, this underlying IO error will be handled internally within
@@ -4572,10 +4607,13 @@ declare namespace Deno {
    * @category Permissions */
   export interface NetPermissionDescriptor {
     name: "net";
-    /** Optional host string of the form `"<hostname>[:<port>]"`. Examples:
+    /** Optional host string of the form `"<hostname>[:<port>]"`, where the
+     * port may also be a range or a comma separated list. Examples:
      *
      *      "github.com"
      *      "deno.land:8080"
+     *      "127.0.0.1:30000-31000"
+     *      "example.com:80,443"
      */
     host?: string;
   }
@@ -5171,6 +5209,33 @@ declare namespace Deno {
      * `node:http` server's `"upgrade"` event and must be forwarded so
      * those bytes are not lost. */
     head?: Uint8Array;
+    /** Accepts the permessage-deflate extension if the client offers it, with
+     * `true` for the default options. Only supported when upgrading a
+     * request from `Deno.serve`. */
+    perMessageDeflate?: boolean | PerMessageDeflateOptions;
+  }
+
+  /** Options of the permessage-deflate WebSocket extension (RFC 7692).
+   *
+   * Deno always compresses with a 15 bits window; the window bits options
+   * only limit the window of the peer.
+   *
+   * @category WebSockets */
+  export interface PerMessageDeflateOptions {
+    /** Resets the compression context of the server after each message. */
+    serverNoContextTakeover?: boolean;
+    /** Resets the compression context of the client after each message. */
+    clientNoContextTakeover?: boolean;
+    /** The window bits the server may compress with, between 8 and 15.
+     * Deno servers decline offers below 15, and `Deno.upgradeWebSocket`
+     * throws if it is set to anything but 15. */
+    serverMaxWindowBits?: number;
+    /** The window bits the client may compress with, between 8 and 15.
+     * Only used by servers. */
+    clientMaxWindowBits?: number;
+    /** The maximum size in bytes of a decompressed message. Larger messages
+     * error the socket and close it with code 1009. Defaults to 64 MiB. */
+    maxDecompressedSize?: number;
   }
 
   /**
@@ -5277,6 +5342,10 @@ declare namespace Deno {
        * @default {53} */
       port?: number;
     };
+    /** The name servers and host overrides to use for lookups. Host overrides
+     * answer `"A"` and `"AAAA"` queries for the names they list. Can't be
+     * used together with `nameServer`. */
+    dns?: DnsResolverOptions;
     /**
      * An abort signal to allow cancellation of the DNS resolution operation.
      * If the signal becomes aborted the resolveDns operation will be stopped
@@ -5807,6 +5876,17 @@ declare namespace Deno {
      *
      * @default {511} */
     tcpBacklog?: number;
+
+    /** Also serve HTTP/3 over QUIC, on the same port over UDP. Requires `cert`
+     * and `key`, which are used for QUIC as well. Responses sent over TCP
+     * advertise the HTTP/3 endpoint with an `Alt-Svc` header, unless the
+     * handler sets one itself.
+     *
+     * @experimental **UNSTABLE**: New API, yet to be vetted. Requires
+     * `--unstable-net`.
+     *
+     * @default {false} */
+    http3?: boolean;
   }
 
   /**
@@ -6885,6 +6965,74 @@ declare namespace Deno {
      *
      * If not set, the default value from the underlying HTTP library is used. */
     http2MaxHeaderListSize?: number;
+    /** Caches responses to `GET` requests made with this client, following
+     * the rules of RFC 9111: responses are reused while they are fresh,
+     * revalidated with `ETag` and `Last-Modified` once they are stale, and
+     * selected by their `Vary` header. The `cache` option of each `Request`
+     * controls how the cache is used for it.
+     *
+     * `true` keeps the responses in memory.
+     *
+     * @default {false}
+     */
+    cache?: boolean | HttpClientCacheOptions;
+    /** Resolve host names with these name servers and host overrides instead
+     * of the system resolver. */
+    dns?: DnsResolverOptions;
+    /** Bounds how long each phase of a request may take. By default, requests
+     * never time out. */
+    timeouts?: HttpClientTimeouts;
+  }
+
+  /**
+   * Timeouts of the requests of a {@linkcode Deno.HttpClient}, in
+   * milliseconds. See {@linkcode Deno.CreateHttpClientOptions.timeouts}.
+   *
+   * Each timeout fails the request with its own subclass of
+   * {@linkcode Deno.errors.TimedOut}, so that retries can be targeted.
+   *
+   * @category Fetch
+   */
+  export interface HttpClientTimeouts {
+    /** Resolving the host name and opening the connection. Fails with
+     * {@linkcode Deno.errors.ConnectTimeout}. */
+    connect?: number;
+    /** The TLS handshake, once the connection is open. Fails with
+     * {@linkcode Deno.errors.TlsHandshakeTimeout}. */
+    tlsHandshake?: number;
+    /** From sending the request until the response headers are received,
+     * connecting included. Fails with
+     * {@linkcode Deno.errors.ResponseHeadersTimeout}. */
+    responseHeaders?: number;
+    /** Between two chunks of the response body. Fails reading the body with
+     * {@linkcode Deno.errors.IdleReadTimeout}. */
+    idleRead?: number;
+    /** From sending the request until the response body is fully received.
+     * Fails with {@linkcode Deno.errors.TotalTimeout}. */
+    total?: number;
+  }
+
+  /**
+   * Options for the HTTP cache of a {@linkcode Deno.HttpClient}. See
+   * {@linkcode Deno.CreateHttpClientOptions.cache}.
+   *
+   * @category Fetch
+   */
+  export interface HttpClientCacheOptions {
+    /** A directory to store responses in, so that they are shared with other
+     * clients and processes using it. Requires read and write permission for
+     * the directory.
+     *
+     * If not set, responses are kept in memory.
+     */
+    path?: string;
+    /** The maximum size of the cache in bytes. Responses larger than this are
+     * not stored, and the least recently used responses are evicted to make
+     * room for new ones.
+     *
+     * @default {67108864}
+     */
+    maxSize?: number;
   }
 
   /**
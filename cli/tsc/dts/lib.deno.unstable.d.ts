@@ -541,7 +541,20 @@ declare namespace Deno {
    *
    * `schedule` can be a string in the Unix cron format or in JSON format
    * as specified by interface {@linkcode CronSchedule}, where time is specified
-   * using UTC time zone.
+   * using UTC time zone unless the `timezone` option is set.
+   *
+   * `timezone` option can be used to evaluate the schedule in an IANA time
+   * zone instead of UTC. Daylight saving time is followed: a time skipped
+   * when the clocks go forward runs when they do, and a time repeated when
+   * they go back runs only once.
+   *
+   * ```ts
+   * Deno.cron("report", "0 9 * * MON-FRI", {
+   *   timezone: "Europe/Berlin",
+   * }, () => {
+   *   console.log("it's 9am in Berlin");
+   * });
+   * ```
    *
    * `backoffSchedule` option can be used to specify the retry policy for failed
    * executions. Each element in the array represents the number of milliseconds
@@ -571,10 +584,73 @@ declare namespace Deno {
   export function cron(
     name: string,
     schedule: string | CronSchedule,
-    options: { backoffSchedule?: number[]; signal?: AbortSignal },
+    options: {
+      backoffSchedule?: number[];
+      signal?: AbortSignal;
+      timezone?: string;
+    },
     handler: () => Promise<void> | void,
   ): Promise<void>;
 
+  /**
+   * @category Testing
+   * @experimental
+   */
+  export interface DenoTest {
+    /** **UNSTABLE**: New API, yet to be vetted.
+     *
+     * A virtual clock for the timers, `performance.now()` and `Deno.cron`
+     * jobs of this module. See {@linkcode TestClock}.
+     *
+     * @category Testing
+     * @experimental
+     */
+    clock: TestClock;
+  }
+
+  /** **UNSTABLE**: New API, yet to be vetted.
+   *
+   * Controls the virtual clock of a test module.
+   *
+   * This is an unstable API and requires the `--unstable-test-clock` flag to
+   * be passed when running Deno.
+   *
+   * Once installed, time only moves when {@linkcode TestClock.tick} is
+   * called: `setTimeout()` and `setInterval()` callbacks fire when the clock
+   * reaches their deadline, and crons run at their scheduled times and retry
+   * after their backoff delays. `Date.now()` keeps following the real clock.
+   * A test that waits on a timer without ticking the clock fails instead of
+   * hanging.
+   *
+   * ```ts
+   * Deno.test.clock.install();
+   *
+   * Deno.test("fires after a second", async () => {
+   *   let calls = 0;
+   *   setTimeout(() => calls++, 1000);
+   *
+   *   await Deno.test.clock.tick(999);
+   *   assertEquals(calls, 0);
+   *   await Deno.test.clock.tick(1);
+   *   assertEquals(calls, 1);
+   * });
+   * ```
+   *
+   * @category Testing
+   * @experimental
+   */
+  export interface TestClock {
+    /** Switches the module to the virtual clock, starting at the current
+     * time. Timers that are already scheduled keep their remaining delay in
+     * virtual time. The clock stays installed for the rest of the module. */
+    install(): void;
+    /** The current virtual time, in milliseconds since the epoch. */
+    now(): number;
+    /** Advances the clock by `ms` milliseconds, stopping at every timer and
+     * cron deadline on the way and letting their callbacks run. */
+    tick(ms: number): Promise<void>;
+  }
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * A key to be persisted in a {@linkcode Deno.Kv}. A key is a sequence
@@ -768,6 +844,38 @@ declare namespace Deno {
     versionstamp: null;
   };
 
+  /**
+   * **UNSTABLE**: New API, yet to be vetted.
+   *
+   * A change made to a key by an atomic write committed to a
+   * {@linkcode Deno.Kv}, with the value that write stored. A key changed again
+   * since still appears with the value of this write.
+   *
+   * Changes are recorded from the rows the write stored, which don't tell
+   * how they were computed: `sum`, `min` and `max` mutations appear as `set`
+   * with the resulting value, and expired keys appear as `delete`.
+   *
+   * @category Cloud
+   * @experimental
+   */
+  export type KvChangeMutation =
+    | { type: "set"; key: KvKey; value: unknown }
+    | { type: "delete"; key: KvKey };
+
+  /**
+   * **UNSTABLE**: New API, yet to be vetted.
+   *
+   * An atomic write committed to a {@linkcode Deno.Kv}, as returned by
+   * {@linkcode Deno.Kv.changes}.
+   *
+   * @category Cloud
+   * @experimental
+   */
+  export interface KvCommit {
+    versionstamp: string;
+    mutations: KvChangeMutation[];
+  }
+
   /** **UNSTABLE**: New API, yet to be vetted.
    *
    * Options for listing key-value pairs in a {@linkcode Deno.Kv}.
@@ -824,6 +932,13 @@ declare namespace Deno {
      * clamped.
      */
     batchSize?: number;
+    /**
+     * The name of an index defined with {@linkcode Deno.Kv.defineIndex} to
+     * list entries from. The selector then applies to the index keys: the
+     * key part derived from the value, followed by the key of the entry.
+     * Listed entries have the key of the entry, without the index key part.
+     */
+    index?: string;
   }
 
   /**
@@ -1207,6 +1322,45 @@ declare namespace Deno {
     // deno-lint-ignore no-explicit-any
     listenQueue(handler: (value: any) => Promise<void> | void): Promise<void>;
 
+    /**
+     * Define a secondary index on the entries whose keys start with `prefix`.
+     * The `key` function derives the index key part of an entry from its
+     * value, or returns `null` to leave the entry out of the index. Writes
+     * made through this connection then keep the index up to date in the
+     * same atomic operation, and {@linkcode Deno.Kv.list} can query it with
+     * the `index` option.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * await db.defineIndex("usersByEmail", {
+     *   prefix: ["users"],
+     *   key: (user) => (user as { email: string }).email,
+     * });
+     * await db.set(["users", "ada"], { email: "ada@example.com" });
+     * for await (const entry of db.list({ prefix: ["ada@example.com"] }, {
+     *   index: "usersByEmail",
+     * })) {
+     *   entry.key; // ["users", "ada"]
+     * }
+     * ```
+     *
+     * The definition is stored in the database, and entries written before
+     * the index was defined are indexed before the returned promise resolves.
+     * The `key` function can't be stored, so other connections to the
+     * database must define the index too before they can set entries under
+     * its prefix. `sum`, `min`, `max` and versionstamped key mutations can't
+     * be applied to indexed keys. Index definitions and entries are stored
+     * under the `"__kv_index_def__"`, `"__kv_index__"` and
+     * `"__kv_index_ref__"` key parts.
+     */
+    defineIndex(
+      name: string,
+      options: {
+        prefix: KvKey;
+        key: (value: unknown, key: KvKey) => KvKeyPart | null | undefined;
+      },
+    ): Promise<void>;
+
     /**
      * Create a new {@linkcode Deno.AtomicOperation} object which can be used to
      * perform an atomic transaction on the database. This does not perform any
@@ -1258,6 +1412,76 @@ declare namespace Deno {
       options?: { raw?: boolean },
     ): ReadableStream<{ [K in keyof T]: KvEntryMaybe<T[K]> }>;
 
+    /**
+     * Tail every atomic write committed to the database, including the ones
+     * made by other processes. The stream yields the writes in order, each with
+     * its versionstamp and the keys it set or deleted.
+     *
+     * ```ts
+     * const db = await Deno.openKv("./app.db");
+     * for await (const { versionstamp, mutations } of db.changes()) {
+     *   await indexMutations(mutations);
+     *   await Deno.writeTextFile("./cursor", versionstamp);
+     * }
+     * ```
+     *
+     * By default the stream starts with the writes committed after it is
+     * opened. Pass the versionstamp of a write as `since` to resume after it.
+     * Writes are recorded once a change feed has been opened on the database,
+     * and the last 100,000 changed keys are kept, so resuming from older
+     * versionstamps throws a `TypeError`.
+     *
+     * Change feeds are only supported for local, on-disk databases.
+     */
+    changes(options?: { since?: string }): ReadableStream<KvCommit>;
+
+    /**
+     * Stop recording the writes committed to the database for
+     * {@linkcode Deno.Kv.changes}, and drop the ones recorded so far. Change
+     * feeds that are still open fail on their next read, and opening a new
+     * one starts recording again.
+     */
+    disableChanges(): Promise<void>;
+
+    /**
+     * Export the entries of the database, or the entries whose keys start
+     * with `prefix`, as newline-delimited JSON. Every line holds the typed key,
+     * value and versionstamp of an entry, and when it expires, so the export
+     * can be imported into another database with {@linkcode Deno.Kv.import}.
+     * Expiration times are only exported from local databases.
+     *
+     * ```ts
+     * const db = await Deno.openKv();
+     * using file = await Deno.open("users.ndjson", { write: true, create: true });
+     * await db.export({ prefix: ["users"] }).pipeTo(file.writable);
+     * ```
+     *
+     * Entries are read in batches, so entries written while the export runs
+     * may or may not be part of it. Use `deno kv export` for a point-in-time
+     * export of a local database.
+     */
+    export(options?: { prefix?: KvKey }): ReadableStream<Uint8Array>;
+
+    /**
+     * Import newline-delimited JSON written by {@linkcode Deno.Kv.export} into
+     * the database, overwriting entries with the same keys. Imported entries
+     * get new versionstamps and keep their expiration time. Resolves with the
+     * number of imported entries.
+     *
+     * The import is not atomic: entries are written in batches as they are
+     * read, so if it fails part way, the entries written before the failure
+     * stay in the database.
+     *
+     * ```ts
+     * const db = await Deno.openKv("https://api.deno.com/databases/<id>/connect");
+     * using file = await Deno.open("users.ndjson");
+     * const count = await db.import(file.readable);
+     * ```
+     */
+    import(
+      source: ReadableStream<Uint8Array> | AsyncIterable<Uint8Array>,
+    ): Promise<number>;
+
     /**
      * Close the database connection. This will prevent any further operations
      * from being performed on the database, and interrupt any in-flight
@@ -135,6 +135,9 @@ declare interface WorkerNavigator {
   /** Returns an array of strings representing the languages known to the user. */
   readonly languages: string[];
 
+  /** Returns the {@linkcode StorageManager} reporting the storage used by the origin. */
+  readonly storage: StorageManager;
+
   /** Returns a {@linkcode NavigatorUAData} object with information about the runtime's user agent. */
   readonly userAgentData: NavigatorUAData;
 }
@@ -111,6 +111,33 @@ declare var CacheStorage: {
   new (): never;
 };
 
+/** How much storage the origin uses, in bytes. `quota` is only set when the
+ * cache size is limited with the `DENO_CACHE_MAX_SIZE` environment variable.
+ *
+ * @category Cache */
+interface StorageEstimate {
+  usage?: number;
+  quota?: number;
+}
+
+/** Reports the storage used by the {@linkcode Cache} objects of the origin.
+ * Accessed via `navigator.storage`.
+ *
+ * @category Cache */
+interface StorageManager {
+  /** Returns how much storage the caches of the origin use, and how much they
+   * are allowed to use. */
+  estimate(): Promise<StorageEstimate>;
+}
+
+/** The constructor object for {@linkcode StorageManager}.
+ *
+ * @category Cache */
+declare var StorageManager: {
+  readonly prototype: StorageManager;
+  new (): never;
+};
+
 /** @category Cache */
 interface CacheQueryOptions {
   ignoreMethod?: boolean;
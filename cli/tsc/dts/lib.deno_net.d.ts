@@ -392,6 +392,56 @@ declare namespace Deno {
     options: ListenTlsOptions & TlsCertifiedKeyPem,
   ): TlsListener;
 
+  /** A name server queried by a {@linkcode Deno.DnsResolverOptions}
+   * resolver.
+   *
+   * @category Network */
+  export interface DnsNameServer {
+    /** The IP address of the name server. */
+    ipAddr: string;
+    /** The port the queries are sent to. Defaults to 53 for `"udp"` and
+     * `"tcp"`, 853 for `"tls"` and 443 for `"https"`. */
+    port?: number;
+    /** How the name server is queried: plain DNS over UDP or TCP,
+     * DNS-over-TLS, or DNS-over-HTTPS.
+     *
+     * @default {"udp"} */
+    protocol?: "udp" | "tcp" | "tls" | "https";
+    /** The name the name server's certificate is verified against. Required
+     * for the `"tls"` and `"https"` protocols. The certificate is verified
+     * with the same root certificates as other TLS connections, including
+     * those added with `--cert` or `DENO_TLS_CA_STORE`, and the `caCerts` of
+     * a {@linkcode Deno.HttpClient} using the resolver. */
+    serverName?: string;
+  }
+
+  /** Configures how host names are resolved by {@linkcode Deno.connect},
+   * {@linkcode Deno.connectTls}, {@linkcode Deno.resolveDns} and
+   * {@linkcode Deno.createHttpClient}, instead of using the system resolver.
+   *
+   * ```ts
+   * const conn = await Deno.connect({
+   *   hostname: "api.internal",
+   *   port: 80,
+   *   dns: {
+   *     nameServers: [
+   *       { ipAddr: "1.1.1.1", protocol: "https", serverName: "cloudflare-dns.com" },
+   *     ],
+   *     hosts: { "api.internal": ["10.0.0.5"] },
+   *   },
+   * });
+   * ```
+   *
+   * @category Network */
+  export interface DnsResolverOptions {
+    /** The name servers to query. If not specified, the system resolver is
+     * used. Connecting to a name server requires `net` permission for it. */
+    nameServers?: DnsNameServer[];
+    /** Static addresses for host names, which are used instead of querying
+     * for them, like entries of `/etc/hosts`. */
+    hosts?: Record<string, string[]>;
+  }
+
   /** Options which can be set when connecting via {@linkcode Deno.connect}.
    *
    * @category Network */
@@ -423,6 +473,9 @@ declare namespace Deno {
      * @default {250}
      */
     autoSelectFamilyAttemptDelay?: number;
+    /** Resolve `hostname` with these name servers and host overrides instead
+     * of the system resolver. */
+    dns?: DnsResolverOptions;
   }
 
   /**
@@ -596,6 +649,9 @@ declare namespace Deno {
      * @default {250}
      */
     autoSelectFamilyAttemptDelay?: number;
+    /** Resolve `hostname` with these name servers and host overrides instead
+     * of the system resolver. */
+    dns?: DnsResolverOptions;
   }
 
   /** Establishes a secure connection over TLS (transport layer security) using
@@ -327,6 +327,13 @@ interface WebSocketOptions {
    * @experimental
    */
   client?: Deno.HttpClient;
+  /**
+   * Offers the permessage-deflate extension to compress messages, with
+   * `true` for the default options. Whether the server accepted it can be
+   * checked with {@linkcode WebSocket.extensions}.
+   * This feature is non-standard.
+   */
+  perMessageDeflate?: boolean | Deno.PerMessageDeflateOptions;
 }
 
 /**
@@ -8,6 +8,7 @@ const {
   op_cache_match,
   op_cache_put,
   op_cache_storage_delete,
+  op_cache_storage_estimate,
   op_cache_storage_has,
   op_cache_storage_keys,
   op_cache_storage_open,
@@ -372,10 +373,32 @@ class Cache {
   }
 }
 
+class StorageManager {
+  constructor() {
+    webidl.illegalConstructor();
+  }
+
+  async estimate() {
+    webidl.assertBranded(this, StorageManagerPrototype);
+    const { usage, quota } = await op_cache_storage_estimate();
+    const estimate = { usage };
+    if (quota !== null && quota !== undefined) {
+      estimate.quota = quota;
+    }
+    return estimate;
+  }
+
+  [SymbolFor("Deno.privateCustomInspect")](inspect, inspectOptions) {
+    return `${this.constructor.name} ${inspect({}, inspectOptions)}`;
+  }
+}
+
 webidl.configureInterface(CacheStorage);
 webidl.configureInterface(Cache);
+webidl.configureInterface(StorageManager);
 const CacheStoragePrototype = CacheStorage.prototype;
 const CachePrototype = Cache.prototype;
+const StorageManagerPrototype = StorageManager.prototype;
 
 let cacheStorageStorage;
 function cacheStorage() {
@@ -385,5 +408,19 @@ function cacheStorage() {
   return cacheStorageStorage;
 }
 
-return { Cache, CacheStorage, cacheStorage };
+let storageManagerStorage;
+function storageManager() {
+  if (!storageManagerStorage) {
+    storageManagerStorage = webidl.createBranded(StorageManager);
+  }
+  return storageManagerStorage;
+}
+
+return {
+  Cache,
+  CacheStorage,
+  cacheStorage,
+  StorageManager,
+  storageManager,
+};
 })();
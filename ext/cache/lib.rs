@@ -24,11 +24,16 @@ use tokio::io::AsyncReadExt;
 
 mod lsc_shard;
 mod lscache;
+mod memory;
 mod sqlite;
+mod tiered;
 
 pub use lsc_shard::CacheShard;
 pub use lscache::LscBackend;
+pub use memory::InMemoryCache;
 pub use sqlite::SqliteBackedCache;
+pub use tiered::DEFAULT_HOT_CACHE_SIZE;
+pub use tiered::TieredCache;
 use tokio_util::io::StreamReader;
 
 #[derive(Debug, thiserror::Error, deno_error::JsError)]
@@ -54,6 +59,9 @@ pub enum CacheError {
   #[class(type)]
   #[error("Content-Encoding is not allowed in response headers")]
   ContentEncodingNotAllowed,
+  #[class("DOMExceptionQuotaExceededError")]
+  #[error("Response is larger than the maximum cache size")]
+  QuotaExceeded,
   #[class(generic)]
   #[error(transparent)]
   Sqlite(#[from] rusqlite::Error),
@@ -106,6 +114,7 @@ deno_core::extension!(deno_cache,
     op_cache_storage_has,
     op_cache_storage_delete,
     op_cache_storage_keys,
+    op_cache_storage_estimate,
     op_cache_put,
     op_cache_match,
     op_cache_keys,
@@ -172,10 +181,70 @@ pub struct CacheKeyEntry {
   pub request_headers: Vec<(ByteString, ByteString)>,
 }
 
+/// Backs `navigator.storage.estimate()`.
+#[derive(Debug, Default, ToV8)]
+pub struct CacheStorageEstimate {
+  pub usage: u64,
+  pub quota: Option<u64>,
+}
+
 #[derive(Clone)]
 pub enum CacheImpl {
   Sqlite(SqliteBackedCache),
   Lsc(LscBackend),
+  Memory(InMemoryCache),
+  Tiered(TieredCache),
+}
+
+/// How the runtime sets up the cache backend, from `DENO_CACHE_BACKEND`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheBackendMode {
+  /// The LSC backend when configured, SQLite otherwise.
+  #[default]
+  Default,
+  /// Only keep responses in memory.
+  Memory,
+  /// An in-memory layer in front of the default backend.
+  Tiered,
+}
+
+impl CacheBackendMode {
+  pub fn from_env() -> Self {
+    match std::env::var("DENO_CACHE_BACKEND")
+      .unwrap_or_default()
+      .as_str()
+    {
+      "" | "default" => Self::Default,
+      "memory" => Self::Memory,
+      "tiered" => Self::Tiered,
+      _ => {
+        log::warn!("Unknown DENO_CACHE_BACKEND value, using the default");
+        Self::Default
+      }
+    }
+  }
+
+  /// Puts an in-memory layer in front of `cache` in tiered mode.
+  pub fn wrap(self, cache: CacheImpl) -> CacheImpl {
+    match self {
+      Self::Default | Self::Memory => cache,
+      Self::Tiered => {
+        CacheImpl::Tiered(TieredCache::new(cache, DEFAULT_HOT_CACHE_SIZE))
+      }
+    }
+  }
+}
+
+/// The maximum cache size set with `DENO_CACHE_MAX_SIZE`, in bytes.
+pub fn max_size_from_env() -> Option<u64> {
+  let max_size = std::env::var("DENO_CACHE_MAX_SIZE").ok()?;
+  match max_size.parse::<u64>() {
+    Ok(max_size) => Some(max_size),
+    Err(_) => {
+      log::warn!("Invalid DENO_CACHE_MAX_SIZE value, not limiting cache size");
+      None
+    }
+  }
 }
 
 impl CacheImpl {
@@ -186,6 +255,9 @@ impl CacheImpl {
     match self {
       Self::Sqlite(cache) => cache.storage_open(cache_name).await,
       Self::Lsc(cache) => cache.storage_open(cache_name).await,
+      Self::Memory(cache) => Ok(cache.storage_open(cache_name)),
+      // The tiered cache wraps another `CacheImpl`, so its futures are boxed.
+      Self::Tiered(cache) => Box::pin(cache.storage_open(cache_name)).await,
     }
   }
 
@@ -196,6 +268,8 @@ impl CacheImpl {
     match self {
       Self::Sqlite(cache) => cache.storage_has(cache_name).await,
       Self::Lsc(cache) => cache.storage_has(cache_name).await,
+      Self::Memory(cache) => Ok(cache.storage_has(&cache_name)),
+      Self::Tiered(cache) => Box::pin(cache.storage_has(cache_name)).await,
     }
   }
 
@@ -206,6 +280,8 @@ impl CacheImpl {
     match self {
       Self::Sqlite(cache) => cache.storage_delete(cache_name).await,
       Self::Lsc(cache) => cache.storage_delete(cache_name).await,
+      Self::Memory(cache) => Ok(cache.storage_delete(&cache_name)),
+      Self::Tiered(cache) => Box::pin(cache.storage_delete(cache_name)).await,
     }
   }
 
@@ -213,6 +289,18 @@ impl CacheImpl {
     match self {
       Self::Sqlite(cache) => cache.storage_keys().await,
       Self::Lsc(cache) => cache.storage_keys().await,
+      Self::Memory(cache) => Ok(cache.storage_keys()),
+      Self::Tiered(cache) => Box::pin(cache.storage_keys()).await,
+    }
+  }
+
+  pub async fn estimate(&self) -> Result<CacheStorageEstimate, CacheError> {
+    match self {
+      Self::Sqlite(cache) => cache.estimate().await,
+      // Remote caches don't use local storage.
+      Self::Lsc(_) => Ok(CacheStorageEstimate::default()),
+      Self::Memory(cache) => Ok(cache.estimate()),
+      Self::Tiered(cache) => Box::pin(cache.estimate()).await,
     }
   }
 
@@ -224,6 +312,10 @@ impl CacheImpl {
     match self {
       Self::Sqlite(cache) => cache.put(request_response, resource).await,
       Self::Lsc(cache) => cache.put(request_response, resource).await,
+      Self::Memory(cache) => cache.put(request_response, resource).await,
+      Self::Tiered(cache) => {
+        Box::pin(cache.put(request_response, resource)).await
+      }
     }
   }
 
@@ -237,6 +329,12 @@ impl CacheImpl {
     match self {
       Self::Sqlite(cache) => cache.r#match(request).await,
       Self::Lsc(cache) => cache.r#match(request).await,
+      Self::Memory(cache) => Ok(
+        cache
+          .r#match(request)
+          .map(|(meta, body)| (meta, body.map(CacheResponseResource::memory))),
+      ),
+      Self::Tiered(cache) => Box::pin(cache.r#match(request)).await,
     }
   }
 
@@ -247,6 +345,8 @@ impl CacheImpl {
     match self {
       Self::Sqlite(cache) => cache.delete(request).await,
       Self::Lsc(cache) => cache.delete(request).await,
+      Self::Memory(cache) => Ok(cache.delete(request)),
+      Self::Tiered(cache) => Box::pin(cache.delete(request)).await,
     }
   }
 
@@ -258,6 +358,8 @@ impl CacheImpl {
     match self {
       Self::Sqlite(cache) => cache.keys(cache_id, request_url).await,
       Self::Lsc(cache) => cache.keys(cache_id, request_url).await,
+      Self::Memory(cache) => Ok(cache.keys(cache_id, request_url)),
+      Self::Tiered(cache) => Box::pin(cache.keys(cache_id, request_url)).await,
     }
   }
 }
@@ -265,6 +367,8 @@ impl CacheImpl {
 pub enum CacheResponseResource {
   Sqlite(AsyncRefCell<tokio::fs::File>),
   Lsc(AsyncRefCell<Pin<Box<dyn AsyncRead>>>),
+  Memory(AsyncRefCell<std::io::Cursor<Bytes>>),
+  Tiered(tiered::PromotingBody),
 }
 
 impl CacheResponseResource {
@@ -278,6 +382,23 @@ impl CacheResponseResource {
     Self::Lsc(AsyncRefCell::new(Box::pin(StreamReader::new(body))))
   }
 
+  fn memory(body: Bytes) -> Self {
+    Self::Memory(AsyncRefCell::new(std::io::Cursor::new(body)))
+  }
+
+  async fn read_to_end(self: Rc<Self>) -> Result<Bytes, std::io::Error> {
+    let mut body = Vec::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+      let nread = self.clone().read(&mut buf).await?;
+      if nread == 0 {
+        break;
+      }
+      body.extend_from_slice(&buf[..nread]);
+    }
+    Ok(body.into())
+  }
+
   async fn read(
     self: Rc<Self>,
     data: &mut [u8],
@@ -299,6 +420,15 @@ impl CacheResponseResource {
         let mut file = resource.borrow_mut().await;
         file.read(data).await?
       }
+      CacheResponseResource::Memory(_) => {
+        let resource = deno_core::RcRef::map(&self, |r| match r {
+          Self::Memory(r) => r,
+          _ => unreachable!(),
+        });
+        let mut body = resource.borrow_mut().await;
+        body.read(data).await?
+      }
+      CacheResponseResource::Tiered(body) => body.read(data).await?,
     };
 
     Ok(nread)
@@ -350,6 +480,14 @@ pub async fn op_cache_storage_keys(
   cache.storage_keys().await
 }
 
+#[op2]
+pub async fn op_cache_storage_estimate(
+  state: Rc<RefCell<OpState>>,
+) -> Result<CacheStorageEstimate, CacheError> {
+  let cache = get_cache(&state)?;
+  cache.estimate().await
+}
+
 #[op2]
 pub async fn op_cache_put(
   state: Rc<RefCell<OpState>>,
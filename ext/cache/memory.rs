@@ -0,0 +1,521 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use bytes::BytesMut;
+use deno_core::BufMutView;
+use deno_core::Resource;
+use deno_core::convert::ByteString;
+
+use crate::CacheDeleteRequest;
+use crate::CacheError;
+use crate::CacheKeyEntry;
+use crate::CacheMatchRequest;
+use crate::CacheMatchResponseMeta;
+use crate::CachePutRequest;
+use crate::CacheStorageEstimate;
+use crate::get_header;
+use crate::serialize_headers;
+use crate::sqlite::response_expires_at;
+use crate::sqlite::unix_millis;
+use crate::vary_header_matches;
+
+/// A cache backend that keeps everything in memory, for ephemeral workers
+/// and tests. Its contents are lost when the worker exits.
+#[derive(Clone, Default)]
+pub struct InMemoryCache(Rc<RefCell<MemoryStorage>>);
+
+#[derive(Default)]
+struct MemoryStorage {
+  /// Maximum total size of the entries in bytes. The least recently matched
+  /// entries are evicted when a `put` goes over it. Like for the SQLite
+  /// backend, stale entries are only dropped when it is set.
+  max_size: Option<u64>,
+  caches: BTreeMap<i64, MemoryCache>,
+  next_cache_id: i64,
+  usage: u64,
+  /// Incremented on every put and match, to order entries by insertion and
+  /// by last use.
+  clock: u64,
+  /// The entries of all caches by when they were last matched, so that the
+  /// least recently used one is found without scanning them all.
+  by_last_use: BTreeMap<u64, EntryKey>,
+  /// The entries that go stale, by when they do and when they were inserted.
+  by_expiry: BTreeMap<(u64, u64), EntryKey>,
+}
+
+/// A cache id and a request URL.
+type EntryKey = (i64, String);
+
+struct MemoryCache {
+  name: String,
+  entries: HashMap<String, MemoryEntry>,
+}
+
+struct MemoryEntry {
+  request_headers: Vec<(ByteString, ByteString)>,
+  response_headers: Vec<(ByteString, ByteString)>,
+  response_status: u16,
+  response_status_text: String,
+  body: Option<Bytes>,
+  size: u64,
+  expires_at: Option<u64>,
+  inserted_at: u64,
+  last_matched_at: u64,
+}
+
+impl InMemoryCache {
+  pub fn new(max_size: Option<u64>) -> Self {
+    Self(Rc::new(RefCell::new(MemoryStorage {
+      max_size,
+      ..Default::default()
+    })))
+  }
+
+  pub fn storage_open(&self, cache_name: String) -> i64 {
+    let mut storage = self.0.borrow_mut();
+    if let Some((id, _)) =
+      storage.caches.iter().find(|(_, c)| c.name == cache_name)
+    {
+      return *id;
+    }
+    storage.next_cache_id += 1;
+    let id = storage.next_cache_id;
+    storage.caches.insert(
+      id,
+      MemoryCache {
+        name: cache_name,
+        entries: HashMap::new(),
+      },
+    );
+    id
+  }
+
+  pub fn storage_has(&self, cache_name: &str) -> bool {
+    self
+      .0
+      .borrow()
+      .caches
+      .values()
+      .any(|c| c.name == cache_name)
+  }
+
+  pub fn storage_delete(&self, cache_name: &str) -> bool {
+    let mut storage = self.0.borrow_mut();
+    let Some(id) = storage
+      .caches
+      .iter()
+      .find(|(_, c)| c.name == cache_name)
+      .map(|(id, _)| *id)
+    else {
+      return false;
+    };
+    let urls = storage.caches[&id]
+      .entries
+      .keys()
+      .cloned()
+      .collect::<Vec<_>>();
+    for url in urls {
+      storage.remove_entry(id, &url);
+    }
+    storage.caches.remove(&id);
+    true
+  }
+
+  pub(crate) fn has_cache_id(&self, cache_id: i64) -> bool {
+    self.0.borrow().caches.contains_key(&cache_id)
+  }
+
+  pub fn storage_keys(&self) -> Vec<String> {
+    self
+      .0
+      .borrow()
+      .caches
+      .values()
+      .map(|c| c.name.clone())
+      .collect()
+  }
+
+  pub async fn put(
+    &self,
+    request_response: CachePutRequest,
+    resource: Option<Rc<dyn Resource>>,
+  ) -> Result<(), CacheError> {
+    let max_size = self.0.borrow().max_size;
+    let body = match resource {
+      Some(resource) => Some(read_body(resource, max_size).await?),
+      None => None,
+    };
+    self.put_body(request_response, body)
+  }
+
+  /// Stores an entry whose body was already read.
+  pub(crate) fn put_body(
+    &self,
+    request_response: CachePutRequest,
+    body: Option<Bytes>,
+  ) -> Result<(), CacheError> {
+    let size = (request_response.request_url.len()
+      + serialize_headers(&request_response.request_headers).len()
+      + serialize_headers(&request_response.response_headers).len())
+      as u64
+      + body.as_ref().map(|b| b.len() as u64).unwrap_or(0);
+    let now = SystemTime::now();
+    let mut storage = self.0.borrow_mut();
+    if size > storage.max_size.unwrap_or(u64::MAX) {
+      return Err(CacheError::QuotaExceeded);
+    }
+    if !storage.caches.contains_key(&request_response.cache_id) {
+      return Err(CacheError::NotFound);
+    }
+    storage.clock += 1;
+    let clock = storage.clock;
+    let entry = MemoryEntry {
+      expires_at: response_expires_at(&request_response.response_headers, now),
+      request_headers: request_response.request_headers,
+      response_headers: request_response.response_headers,
+      response_status: request_response.response_status,
+      response_status_text: request_response.response_status_text,
+      body,
+      size,
+      inserted_at: clock,
+      last_matched_at: clock,
+    };
+    let cache_id = request_response.cache_id;
+    let url = request_response.request_url;
+    storage.remove_entry(cache_id, &url);
+    storage.insert_entry(cache_id, url, entry);
+    storage.evict(unix_millis(now));
+    Ok(())
+  }
+
+  pub fn r#match(
+    &self,
+    request: CacheMatchRequest,
+  ) -> Option<(CacheMatchResponseMeta, Option<Bytes>)> {
+    let now = unix_millis(SystemTime::now());
+    let mut storage = self.0.borrow_mut();
+    let storage = &mut *storage;
+    storage.clock += 1;
+    let clock = storage.clock;
+    let cache = storage.caches.get_mut(&request.cache_id)?;
+    let entry = cache.entries.get_mut(&request.request_url)?;
+    if storage.max_size.is_some()
+      && entry.expires_at.is_some_and(|expires_at| expires_at <= now)
+    {
+      // Stale entries are dropped instead of being matched.
+      storage.remove_entry(request.cache_id, &request.request_url);
+      return None;
+    }
+    // From https://w3c.github.io/ServiceWorker/#request-matches-cached-item-algorithm
+    // If there's Vary header in the response, ensure all the
+    // headers of the cached request match the query request.
+    if let Some(vary_header) = get_header("vary", &entry.response_headers)
+      && !vary_header_matches(
+        &vary_header,
+        &request.request_headers,
+        &entry.request_headers,
+      )
+    {
+      return None;
+    }
+    let key = storage
+      .by_last_use
+      .remove(&entry.last_matched_at)
+      .expect("entry is indexed by last use");
+    storage.by_last_use.insert(clock, key);
+    entry.last_matched_at = clock;
+    Some((
+      CacheMatchResponseMeta {
+        request_headers: entry.request_headers.clone(),
+        response_headers: entry.response_headers.clone(),
+        response_status: entry.response_status,
+        response_status_text: entry.response_status_text.clone(),
+      },
+      entry.body.clone(),
+    ))
+  }
+
+  pub fn delete(&self, request: CacheDeleteRequest) -> bool {
+    self
+      .0
+      .borrow_mut()
+      .remove_entry(request.cache_id, &request.request_url)
+      .is_some()
+  }
+
+  pub fn keys(
+    &self,
+    cache_id: i64,
+    request_url: Option<String>,
+  ) -> Vec<CacheKeyEntry> {
+    let storage = self.0.borrow();
+    let Some(cache) = storage.caches.get(&cache_id) else {
+      return Vec::new();
+    };
+    let mut entries = cache
+      .entries
+      .iter()
+      .filter(|(url, _)| request_url.as_ref().is_none_or(|u| u == *url))
+      .collect::<Vec<_>>();
+    entries.sort_by_key(|(_, entry)| entry.inserted_at);
+    entries
+      .into_iter()
+      .map(|(url, entry)| CacheKeyEntry {
+        request_url: url.clone(),
+        request_headers: entry.request_headers.clone(),
+      })
+      .collect()
+  }
+
+  pub fn estimate(&self) -> CacheStorageEstimate {
+    let storage = self.0.borrow();
+    CacheStorageEstimate {
+      usage: storage.usage,
+      quota: storage.max_size,
+    }
+  }
+}
+
+impl MemoryStorage {
+  fn insert_entry(&mut self, cache_id: i64, url: String, entry: MemoryEntry) {
+    let Some(cache) = self.caches.get_mut(&cache_id) else {
+      return;
+    };
+    self
+      .by_last_use
+      .insert(entry.last_matched_at, (cache_id, url.clone()));
+    if let Some(expires_at) = entry.expires_at {
+      self
+        .by_expiry
+        .insert((expires_at, entry.inserted_at), (cache_id, url.clone()));
+    }
+    self.usage += entry.size;
+    cache.entries.insert(url, entry);
+  }
+
+  fn remove_entry(&mut self, cache_id: i64, url: &str) -> Option<MemoryEntry> {
+    let entry = self.caches.get_mut(&cache_id)?.entries.remove(url)?;
+    self.by_last_use.remove(&entry.last_matched_at);
+    if let Some(expires_at) = entry.expires_at {
+      self.by_expiry.remove(&(expires_at, entry.inserted_at));
+    }
+    self.usage -= entry.size;
+    Some(entry)
+  }
+
+  /// Drops the entries that went stale by `now`, then the least recently
+  /// matched ones until the cache fits in `max_size` again. Nothing is
+  /// evicted without a `max_size`.
+  fn evict(&mut self, now: u64) {
+    let Some(max_size) = self.max_size else {
+      return;
+    };
+    while let Some(entry) = self.by_expiry.first_entry()
+      && entry.key().0 <= now
+    {
+      let (cache_id, url) = entry.remove();
+      self.remove_entry(cache_id, &url);
+    }
+    while self.usage > max_size
+      && let Some((_, (cache_id, url))) = self.by_last_use.pop_first()
+    {
+      self.remove_entry(cache_id, &url);
+    }
+  }
+}
+
+/// Reads a response body to the end, failing once it gets larger than
+/// `max_size`.
+pub(crate) async fn read_body(
+  resource: Rc<dyn Resource>,
+  max_size: Option<u64>,
+) -> Result<Bytes, CacheError> {
+  let mut body = BytesMut::new();
+  let mut buf = BufMutView::new(64 * 1024);
+  loop {
+    let (size, buf2) = resource
+      .clone()
+      .read_byob(buf)
+      .await
+      .map_err(CacheError::Other)?;
+    if size == 0 {
+      break;
+    }
+    buf = buf2;
+    body.extend_from_slice(&buf[..size]);
+    if body.len() as u64 > max_size.unwrap_or(u64::MAX) {
+      return Err(CacheError::QuotaExceeded);
+    }
+  }
+  Ok(body.freeze())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn put_request(
+    cache_id: i64,
+    url: &str,
+    response_headers: &[(&str, &str)],
+  ) -> CachePutRequest {
+    CachePutRequest {
+      cache_id,
+      request_url: url.to_string(),
+      request_headers: vec![],
+      response_headers: response_headers
+        .iter()
+        .map(|(k, v)| (ByteString::from(*k), ByteString::from(*v)))
+        .collect(),
+      response_status: 200,
+      response_status_text: "OK".to_string(),
+      response_rid: None,
+    }
+  }
+
+  fn match_request(cache_id: i64, url: &str) -> CacheMatchRequest {
+    CacheMatchRequest {
+      cache_id,
+      request_url: url.to_string(),
+      request_headers: vec![],
+    }
+  }
+
+  #[test]
+  fn stores_and_matches_entries() {
+    let cache = InMemoryCache::new(None);
+    let cache_id = cache.storage_open("v1".to_string());
+    assert_eq!(cache.storage_open("v1".to_string()), cache_id);
+    assert!(cache.storage_has("v1"));
+
+    let body = Bytes::from_static(b"hello");
+    cache
+      .put_body(put_request(cache_id, "https://a/", &[]), Some(body.clone()))
+      .unwrap();
+    cache
+      .put_body(put_request(cache_id, "https://b/", &[]), None)
+      .unwrap();
+    // Freshness is ignored without a maximum size.
+    cache
+      .put_body(
+        put_request(cache_id, "https://stale/", &[("expires", "0")]),
+        None,
+      )
+      .unwrap();
+    assert!(
+      cache
+        .r#match(match_request(cache_id, "https://stale/"))
+        .is_some()
+    );
+    cache.delete(CacheDeleteRequest {
+      cache_id,
+      request_url: "https://stale/".to_string(),
+    });
+    let (meta, matched) = cache
+      .r#match(match_request(cache_id, "https://a/"))
+      .unwrap();
+    assert_eq!(meta.response_status, 200);
+    assert_eq!(matched, Some(body));
+    assert!(
+      cache
+        .r#match(match_request(cache_id, "https://c/"))
+        .is_none()
+    );
+
+    let keys = cache.keys(cache_id, None);
+    assert_eq!(
+      keys
+        .iter()
+        .map(|k| k.request_url.as_str())
+        .collect::<Vec<_>>(),
+      ["https://a/", "https://b/"]
+    );
+
+    assert!(cache.delete(CacheDeleteRequest {
+      cache_id,
+      request_url: "https://a/".to_string(),
+    }));
+    assert!(
+      cache
+        .r#match(match_request(cache_id, "https://a/"))
+        .is_none()
+    );
+
+    assert!(cache.storage_delete("v1"));
+    assert!(!cache.storage_has("v1"));
+    assert_eq!(cache.estimate().usage, 0);
+    assert!(cache.0.borrow().by_last_use.is_empty());
+    assert!(cache.0.borrow().by_expiry.is_empty());
+    assert!(matches!(
+      cache.put_body(put_request(cache_id, "https://a/", &[]), None),
+      Err(CacheError::NotFound)
+    ));
+  }
+
+  #[test]
+  fn evicts_stale_and_least_recently_matched_entries() {
+    let cache = InMemoryCache::new(Some(250));
+    let cache_id = cache.storage_open("v1".to_string());
+    let body = || Some(Bytes::from(vec![0; 100]));
+
+    cache
+      .put_body(
+        put_request(
+          cache_id,
+          "https://stale/",
+          &[("cache-control", "max-age=0")],
+        ),
+        None,
+      )
+      .unwrap();
+    assert!(
+      cache
+        .r#match(match_request(cache_id, "https://stale/"))
+        .is_none()
+    );
+
+    cache
+      .put_body(put_request(cache_id, "https://a/", &[]), body())
+      .unwrap();
+    cache
+      .put_body(put_request(cache_id, "https://b/", &[]), body())
+      .unwrap();
+    // Matching "a" makes "b" the least recently used entry.
+    cache
+      .r#match(match_request(cache_id, "https://a/"))
+      .unwrap();
+    cache
+      .put_body(put_request(cache_id, "https://c/", &[]), body())
+      .unwrap();
+    assert!(
+      cache
+        .r#match(match_request(cache_id, "https://a/"))
+        .is_some()
+    );
+    assert!(
+      cache
+        .r#match(match_request(cache_id, "https://b/"))
+        .is_none()
+    );
+    assert!(
+      cache
+        .r#match(match_request(cache_id, "https://c/"))
+        .is_some()
+    );
+    assert!(cache.estimate().usage <= 250);
+
+    assert!(matches!(
+      cache.put_body(
+        put_request(cache_id, "https://big/", &[]),
+        Some(Bytes::from(vec![0; 300]))
+      ),
+      Err(CacheError::QuotaExceeded)
+    ));
+  }
+}
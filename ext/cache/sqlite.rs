@@ -6,6 +6,7 @@ use std::path::PathBuf;
 use std::pin::Pin;
 use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
@@ -27,6 +28,7 @@ use crate::CacheMatchRequest;
 use crate::CacheMatchResponseMeta;
 use crate::CachePutRequest;
 use crate::CacheResponseResource;
+use crate::CacheStorageEstimate;
 use crate::deserialize_headers;
 use crate::get_header;
 use crate::serialize_headers;
@@ -36,6 +38,12 @@ use crate::vary_header_matches;
 pub struct SqliteBackedCache {
   pub connection: Arc<Mutex<Connection>>,
   pub cache_storage_dir: PathBuf,
+  /// Maximum total size of the cached entries in bytes, from
+  /// `DENO_CACHE_MAX_SIZE`. The least recently matched entries are evicted
+  /// when a `put` goes over it. Stale entries are only dropped when it is
+  /// set, otherwise `match` keeps the Cache API semantics of ignoring
+  /// freshness.
+  pub max_size: Option<u64>,
 }
 
 #[derive(Debug)]
@@ -147,13 +155,112 @@ impl SqliteBackedCache {
                 )",
           (),
         )?;
+    // Columns added after the table was first created: the size of the entry
+    // including its metadata, when it was last matched, in milliseconds, and
+    // when its response goes stale, in milliseconds.
+    if add_column_if_missing(
+      &connection,
+      "request_response_list",
+      "entry_size",
+      "INTEGER NOT NULL DEFAULT 0",
+    )? {
+      backfill_entry_sizes(&connection, &cache_storage_dir)?;
+    }
+    add_column_if_missing(
+      &connection,
+      "request_response_list",
+      "last_matched_at",
+      "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    add_column_if_missing(
+      &connection,
+      "request_response_list",
+      "expires_at",
+      "INTEGER",
+    )?;
+    connection.execute_batch(
+      "CREATE INDEX IF NOT EXISTS request_response_list_last_matched_at
+         ON request_response_list (last_matched_at);
+       CREATE INDEX IF NOT EXISTS request_response_list_expires_at
+         ON request_response_list (expires_at);",
+    )?;
+
+    let max_size = crate::max_size_from_env();
+
     Ok(SqliteBackedCache {
       connection: Arc::new(Mutex::new(connection)),
       cache_storage_dir,
+      max_size,
     })
   }
 }
 
+/// Returns whether the column had to be added.
+fn add_column_if_missing(
+  connection: &Connection,
+  table: &str,
+  column: &str,
+  definition: &str,
+) -> Result<bool, rusqlite::Error> {
+  let exists = connection
+    .prepare(&format!(
+      "SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"
+    ))?
+    .exists(params![column])?;
+  if !exists {
+    connection.execute(
+      &format!("ALTER TABLE {table} ADD COLUMN {column} {definition}"),
+      (),
+    )?;
+  }
+  Ok(!exists)
+}
+
+/// Computes the `entry_size` of the entries stored before the column existed,
+/// the same way `put` does: the request URL and the serialized headers, plus
+/// the size of the response body on disk.
+fn backfill_entry_sizes(
+  connection: &Connection,
+  cache_storage_dir: &Path,
+) -> Result<(), rusqlite::Error> {
+  let entries = connection
+    .prepare(
+      "SELECT id, cache_id, response_body_key,
+         length(CAST(request_url AS BLOB)) + length(request_headers)
+           + length(response_headers)
+         FROM request_response_list",
+    )?
+    .query_map((), |row| {
+      Ok((
+        row.get::<_, i64>(0)?,
+        row.get::<_, i64>(1)?,
+        row.get::<_, Option<String>>(2)?,
+        row.get::<_, u64>(3)?,
+      ))
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+  let mut stmt = connection.prepare(
+    "UPDATE request_response_list SET entry_size = ?1 WHERE id = ?2",
+  )?;
+  for (id, cache_id, response_body_key, metadata_size) in entries {
+    let mut body_size = 0;
+    if let Some(response_body_key) = response_body_key {
+      let path = get_responses_dir(cache_storage_dir.to_path_buf(), cache_id)
+        .join(response_body_key);
+      #[allow(
+        clippy::disallowed_methods,
+        reason = "cache storage manages its own directory"
+      )]
+      let metadata = std::fs::metadata(path);
+      if let Ok(metadata) = metadata {
+        body_size = metadata.len();
+      }
+    }
+    stmt.execute(params![metadata_size + body_size, id])?;
+  }
+  Ok(())
+}
+
 impl SqliteBackedCache {
   /// Open a cache storage. Internally, this creates a row in the
   /// sqlite db if the cache doesn't exist and returns the internal id
@@ -231,6 +338,12 @@ impl SqliteBackedCache {
         )
         .optional()?;
       if let Some(cache_id) = maybe_cache_id {
+        // Foreign keys aren't enforced, so the entries aren't deleted along
+        // with the cache and would keep counting towards its size.
+        db.execute(
+          "DELETE FROM request_response_list WHERE cache_id = ?1",
+          params![cache_id],
+        )?;
         let cache_dir = cache_storage_dir.join(cache_id.to_string());
         #[allow(
           clippy::disallowed_methods,
@@ -271,11 +384,17 @@ impl SqliteBackedCache {
     request_response: CachePutRequest,
     resource: Option<Rc<dyn Resource>>,
   ) -> Result<(), CacheError> {
-    let db = self.connection.clone();
-    let cache_storage_dir = self.cache_storage_dir.clone();
     let now = SystemTime::now()
       .duration_since(UNIX_EPOCH)
       .expect("SystemTime is before unix epoch");
+    let metadata_size = (request_response.request_url.len()
+      + serialize_headers(&request_response.request_headers).len()
+      + serialize_headers(&request_response.response_headers).len())
+      as u64;
+    let max_size = self.max_size.unwrap_or(u64::MAX);
+    if metadata_size > max_size {
+      return Err(CacheError::QuotaExceeded);
+    }
 
     if let Some(resource) = resource {
       let body_key = hash(&format!(
@@ -283,11 +402,14 @@ impl SqliteBackedCache {
         &request_response.request_url,
         now.as_nanos()
       ));
-      let responses_dir =
-        get_responses_dir(cache_storage_dir, request_response.cache_id);
+      let responses_dir = get_responses_dir(
+        self.cache_storage_dir.clone(),
+        request_response.cache_id,
+      );
       let response_path = responses_dir.join(&body_key);
-      let mut file = tokio::fs::File::create(response_path).await?;
+      let mut file = tokio::fs::File::create(&response_path).await?;
       let mut buf = BufMutView::new(64 * 1024);
+      let mut body_size = 0;
       loop {
         let (size, buf2) = resource
           .clone()
@@ -298,6 +420,13 @@ impl SqliteBackedCache {
           break;
         }
         buf = buf2;
+        body_size += size as u64;
+        if metadata_size + body_size > max_size {
+          // The entry could never fit, even in an empty cache.
+          drop(file);
+          tokio::fs::remove_file(&response_path).await?;
+          return Err(CacheError::QuotaExceeded);
+        }
 
         // Use poll_write to avoid holding a slice across await points
         poll_fn(|cx| Pin::new(&mut file).poll_write(cx, &buf[..size])).await?;
@@ -307,13 +436,20 @@ impl SqliteBackedCache {
       file.sync_all().await?;
 
       assert_eq!(
-        insert_cache_asset(db, request_response, Some(body_key.clone()),)
+        self
+          .insert_cache_asset(
+            request_response,
+            Some(body_key.clone()),
+            metadata_size + body_size,
+            now,
+          )
           .await?,
         Some(body_key)
       );
     } else {
       assert!(
-        insert_cache_asset(db, request_response, None)
+        self
+          .insert_cache_asset(request_response, None, metadata_size, now)
           .await?
           .is_none()
       );
@@ -330,10 +466,12 @@ impl SqliteBackedCache {
   > {
     let db = self.connection.clone();
     let cache_storage_dir = self.cache_storage_dir.clone();
+    let max_size = self.max_size;
     let (query_result, request) = spawn_blocking(move || {
       let db = db.lock();
+      let now = unix_millis(SystemTime::now());
       let result = db.query_row(
-        "SELECT response_body_key, response_headers, response_status, response_status_text, request_headers
+        "SELECT response_body_key, response_headers, response_status, response_status_text, request_headers, expires_at
              FROM request_response_list
              WHERE cache_id = ?1 AND request_url = ?2",
         (request.cache_id, &request.request_url),
@@ -343,6 +481,7 @@ impl SqliteBackedCache {
           let response_status: u16 = row.get(2)?;
           let response_status_text: String = row.get(3)?;
           let request_headers: Vec<u8> = row.get(4)?;
+          let expires_at: Option<u64> = row.get(5)?;
           let response_headers: Vec<(ByteString, ByteString)> = deserialize_headers(&response_headers);
           let request_headers: Vec<(ByteString, ByteString)> = deserialize_headers(&request_headers);
           Ok((CacheMatchResponseMeta {
@@ -350,14 +489,41 @@ impl SqliteBackedCache {
             response_headers,
             response_status,
             response_status_text},
-            response_body_key
+            response_body_key,
+            expires_at,
           ))
         },
-      );
+      ).optional()?;
+      let result = match result {
+        Some((_, response_body_key, Some(expires_at)))
+          if max_size.is_some() && expires_at <= now =>
+        {
+          // Stale entries are dropped instead of being matched.
+          db.execute(
+            "DELETE FROM request_response_list WHERE cache_id = ?1 AND request_url = ?2",
+            (request.cache_id, &request.request_url),
+          )?;
+          remove_response_bodies(
+            &cache_storage_dir,
+            [(request.cache_id, response_body_key)],
+          );
+          None
+        }
+        Some((cache_meta, response_body_key, _)) => {
+          db.execute(
+            "UPDATE request_response_list SET last_matched_at = ?3
+               WHERE cache_id = ?1 AND request_url = ?2",
+            (request.cache_id, &request.request_url, now),
+          )?;
+          Some((cache_meta, response_body_key))
+        }
+        None => None,
+      };
       // Return ownership of request to the caller
-      result.optional().map(|x| (x, request))
+      Ok::<_, CacheError>((result, request))
     })
     .await??;
+    let cache_storage_dir = self.cache_storage_dir.clone();
 
     match query_result {
       Some((cache_meta, Some(response_body_key))) => {
@@ -406,14 +572,99 @@ impl SqliteBackedCache {
     request: CacheDeleteRequest,
   ) -> Result<bool, CacheError> {
     let db = self.connection.clone();
+    let cache_storage_dir = self.cache_storage_dir.clone();
+    spawn_blocking(move || {
+      let db = db.lock();
+      let deleted = db
+        .query_row(
+          "DELETE FROM request_response_list WHERE cache_id = ?1 AND request_url = ?2
+             RETURNING response_body_key",
+          (request.cache_id, &request.request_url),
+          |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()?;
+      if let Some(response_body_key) = &deleted {
+        remove_response_bodies(
+          &cache_storage_dir,
+          [(request.cache_id, response_body_key.clone())],
+        );
+      }
+      Ok::<bool, CacheError>(deleted.is_some())
+    })
+    .await?
+  }
+
+  /// The total size of the cached entries and the configured maximum, in
+  /// bytes.
+  pub async fn estimate(&self) -> Result<CacheStorageEstimate, CacheError> {
+    let db = self.connection.clone();
+    let quota = self.max_size;
     spawn_blocking(move || {
-      // TODO(@satyarohith): remove the response body from disk if one exists
       let db = db.lock();
-      let rows_effected = db.execute(
-        "DELETE FROM request_response_list WHERE cache_id = ?1 AND request_url = ?2",
-        (request.cache_id, &request.request_url),
+      let usage = db.query_row(
+        "SELECT COALESCE(SUM(entry_size), 0) FROM request_response_list",
+        (),
+        |row| row.get::<_, u64>(0),
       )?;
-      Ok::<bool, CacheError>(rows_effected > 0)
+      Ok::<_, CacheError>(CacheStorageEstimate { usage, quota })
+    })
+    .await?
+  }
+
+  async fn insert_cache_asset(
+    &self,
+    put: CachePutRequest,
+    response_body_key: Option<String>,
+    entry_size: u64,
+    now: Duration,
+  ) -> Result<Option<String>, CacheError> {
+    let db = self.connection.clone();
+    let cache_storage_dir = self.cache_storage_dir.clone();
+    let max_size = self.max_size;
+    let now = UNIX_EPOCH + now;
+    spawn_blocking(move || {
+      let db = db.lock();
+      let cache_id = put.cache_id;
+      let replaced = db
+        .query_row(
+          "SELECT response_body_key FROM request_response_list
+             WHERE cache_id = ?1 AND request_url = ?2",
+          (cache_id, &put.request_url),
+          |row| row.get::<_, Option<String>>(0),
+        )
+        .optional()?;
+      let expires_at = response_expires_at(&put.response_headers, now);
+      let maybe_response_body = db.query_row(
+        "INSERT OR REPLACE INTO request_response_list
+             (cache_id, request_url, request_headers, response_headers,
+              response_body_key, response_status, response_status_text, last_inserted_at,
+              entry_size, last_matched_at, expires_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+             RETURNING response_body_key",
+        (
+          cache_id,
+          put.request_url,
+          serialize_headers(&put.request_headers),
+          serialize_headers(&put.response_headers),
+          response_body_key,
+          put.response_status,
+          put.response_status_text,
+          now.duration_since(UNIX_EPOCH).expect("SystemTime is before unix epoch").as_secs(),
+          entry_size,
+          unix_millis(now),
+          expires_at,
+        ),
+        |row| {
+          let response_body_key: Option<String> = row.get(0)?;
+          Ok(response_body_key)
+        },
+      )?;
+      if let Some(replaced) = replaced {
+        remove_response_bodies(&cache_storage_dir, [(cache_id, replaced)]);
+      }
+      let evicted = evict_entries(&db, unix_millis(now), max_size)?;
+      remove_response_bodies(&cache_storage_dir, evicted);
+      Ok::<Option<String>, CacheError>(maybe_response_body)
     })
     .await?
   }
@@ -460,38 +711,115 @@ impl SqliteBackedCache {
   }
 }
 
-async fn insert_cache_asset(
-  db: Arc<Mutex<Connection>>,
-  put: CachePutRequest,
-  response_body_key: Option<String>,
-) -> Result<Option<String>, CacheError> {
-  spawn_blocking(move || {
-    let maybe_response_body = {
-      let db = db.lock();
-      db.query_row(
-        "INSERT OR REPLACE INTO request_response_list
-             (cache_id, request_url, request_headers, response_headers,
-              response_body_key, response_status, response_status_text, last_inserted_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
-             RETURNING response_body_key",
-        (
-          put.cache_id,
-          put.request_url,
-          serialize_headers(&put.request_headers),
-          serialize_headers(&put.response_headers),
-          response_body_key,
-          put.response_status,
-          put.response_status_text,
-          SystemTime::now().duration_since(UNIX_EPOCH).expect("SystemTime is before unix epoch").as_secs(),
-        ),
-        |row| {
-          let response_body_key: Option<String> = row.get(0)?;
-          Ok(response_body_key)
-        },
-      )?
+/// Drops the entries that went stale by `now`, then the least recently
+/// matched ones until the cache fits in `max_size` again. Returns the response
+/// bodies of the removed entries. Nothing is evicted without a `max_size`.
+fn evict_entries(
+  db: &Connection,
+  now: u64,
+  max_size: Option<u64>,
+) -> Result<Vec<(i64, Option<String>)>, CacheError> {
+  let Some(max_size) = max_size else {
+    return Ok(Vec::new());
+  };
+  let mut evicted = db
+    .prepare(
+      "DELETE FROM request_response_list WHERE expires_at <= ?1
+         RETURNING cache_id, response_body_key",
+    )?
+    .query_map(params![now], |row| Ok((row.get(0)?, row.get(1)?)))?
+    .collect::<Result<Vec<_>, _>>()?;
+
+  let mut usage = db.query_row(
+    "SELECT COALESCE(SUM(entry_size), 0) FROM request_response_list",
+    (),
+    |row| row.get::<_, u64>(0),
+  )?;
+  if usage <= max_size {
+    return Ok(evicted);
+  }
+  let mut ids = Vec::new();
+  {
+    let mut stmt = db.prepare(
+      "SELECT id, cache_id, response_body_key, entry_size
+         FROM request_response_list ORDER BY last_matched_at, id",
+    )?;
+    let mut rows = stmt.query(())?;
+    while usage > max_size
+      && let Some(row) = rows.next()?
+    {
+      ids.push(row.get::<_, i64>(0)?);
+      evicted.push((row.get(1)?, row.get(2)?));
+      usage = usage.saturating_sub(row.get::<_, u64>(3)?);
+    }
+  }
+  let mut stmt =
+    db.prepare("DELETE FROM request_response_list WHERE id = ?1")?;
+  for id in ids {
+    stmt.execute(params![id])?;
+  }
+  Ok(evicted)
+}
+
+/// Best effort removal of the response bodies of deleted entries.
+fn remove_response_bodies(
+  cache_storage_dir: &Path,
+  bodies: impl IntoIterator<Item = (i64, Option<String>)>,
+) {
+  for (cache_id, response_body_key) in bodies {
+    let Some(response_body_key) = response_body_key else {
+      continue;
     };
-    Ok::<Option<String>, CacheError>(maybe_response_body)
-  }).await?
+    let path = get_responses_dir(cache_storage_dir.to_path_buf(), cache_id)
+      .join(response_body_key);
+    #[allow(
+      clippy::disallowed_methods,
+      reason = "cache storage manages its own directory"
+    )]
+    let _ = std::fs::remove_file(path);
+  }
+}
+
+/// When a response goes stale according to its `Cache-Control: max-age`, or
+/// else its `Expires` header, in milliseconds since the epoch. An `Expires`
+/// value that isn't a valid date, like `0`, means the response is already
+/// stale (RFC 9111, section 5.3).
+pub(crate) fn response_expires_at(
+  headers: &[(ByteString, ByteString)],
+  now: SystemTime,
+) -> Option<u64> {
+  let now = unix_millis(now);
+  let header = |name| {
+    get_header(name, headers)
+      .and_then(|value| String::from_utf8(value.to_vec()).ok())
+  };
+  let max_age = header("cache-control").and_then(|cache_control| {
+    cache_control.split(',').find_map(|directive| {
+      let (name, value) = directive.trim().split_once('=')?;
+      if !name.trim().eq_ignore_ascii_case("max-age") {
+        return None;
+      }
+      value.trim().trim_matches('"').parse::<u64>().ok()
+    })
+  });
+  if let Some(max_age) = max_age {
+    let age = header("age")
+      .and_then(|age| age.trim().parse::<u64>().ok())
+      .unwrap_or(0);
+    return Some(now + max_age.saturating_sub(age).saturating_mul(1000));
+  }
+  let expires = header("expires")?;
+  match chrono::DateTime::parse_from_rfc2822(expires.trim()) {
+    Ok(expires) => Some(expires.timestamp_millis().max(0) as u64),
+    Err(_) => Some(0),
+  }
+}
+
+pub(crate) fn unix_millis(time: SystemTime) -> u64 {
+  time
+    .duration_since(UNIX_EPOCH)
+    .expect("SystemTime is before unix epoch")
+    .as_millis() as u64
 }
 
 #[inline]
@@ -558,4 +886,144 @@ mod tests {
       .mode();
     assert_eq!(mode & 0o777, 0o700);
   }
+
+  #[test]
+  fn evicts_stale_and_least_recently_matched_entries() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cache = SqliteBackedCache::new(temp_dir.path().join("cache")).unwrap();
+    let db = cache.connection.lock();
+    db.execute("INSERT INTO cache_storage (cache_name) VALUES ('v1')", ())
+      .unwrap();
+    for (url, last_matched_at, expires_at) in [
+      ("https://example.com/a", 3, None),
+      ("https://example.com/b", 1, None),
+      ("https://example.com/c", 2, Some(50)),
+      ("https://example.com/d", 4, Some(500)),
+    ] {
+      db.execute(
+        "INSERT INTO request_response_list
+           (cache_id, request_url, request_headers, response_headers,
+            response_status, last_inserted_at, entry_size, last_matched_at,
+            expires_at)
+           VALUES (1, ?1, x'', x'', 200, 0, 100, ?2, ?3)",
+        params![url, last_matched_at, expires_at],
+      )
+      .unwrap();
+    }
+
+    // Without a maximum size even stale entries are kept.
+    assert!(evict_entries(&db, 100, None).unwrap().is_empty());
+
+    // c is stale, and b is the least recently matched of the others.
+    let evicted = evict_entries(&db, 100, Some(250)).unwrap();
+    assert_eq!(evicted, vec![(1, None), (1, None)]);
+    let remaining = db
+      .prepare("SELECT request_url FROM request_response_list ORDER BY id")
+      .unwrap()
+      .query_map((), |row| row.get::<_, String>(0))
+      .unwrap()
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+    assert_eq!(
+      remaining,
+      vec!["https://example.com/a", "https://example.com/d"]
+    );
+  }
+
+  #[test]
+  fn backfills_entry_sizes_of_existing_entries() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cache_storage_dir = temp_dir.path().join("cache");
+    create_cache_storage_dir(&cache_storage_dir).unwrap();
+    {
+      let db =
+        Connection::open(cache_storage_dir.join("cache_metadata.db")).unwrap();
+      db.execute_batch(
+        "CREATE TABLE cache_storage (
+           id INTEGER PRIMARY KEY,
+           cache_name TEXT NOT NULL UNIQUE
+         );
+         CREATE TABLE request_response_list (
+           id INTEGER PRIMARY KEY,
+           cache_id INTEGER NOT NULL,
+           request_url TEXT NOT NULL,
+           request_headers BLOB NOT NULL,
+           response_headers BLOB NOT NULL,
+           response_status INTEGER NOT NULL,
+           response_status_text TEXT,
+           response_body_key TEXT,
+           last_inserted_at INTEGER UNSIGNED NOT NULL
+         );
+         INSERT INTO cache_storage (cache_name) VALUES ('v1');
+         INSERT INTO request_response_list
+           (cache_id, request_url, request_headers, response_headers,
+            response_status, response_body_key, last_inserted_at)
+           VALUES
+             (1, 'https://example.com/a', x'0102', x'030405', 200, 'body', 0),
+             (1, 'https://example.com/b', x'', x'', 204, NULL, 0);",
+      )
+      .unwrap();
+    }
+    let responses_dir = get_responses_dir(cache_storage_dir.clone(), 1);
+    std::fs::create_dir_all(&responses_dir).unwrap();
+    std::fs::write(responses_dir.join("body"), [0; 10]).unwrap();
+
+    let cache = SqliteBackedCache::new(cache_storage_dir).unwrap();
+    let sizes = cache
+      .connection
+      .lock()
+      .prepare("SELECT entry_size FROM request_response_list ORDER BY id")
+      .unwrap()
+      .query_map((), |row| row.get::<_, u64>(0))
+      .unwrap()
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap();
+    assert_eq!(sizes, vec![21 + 2 + 3 + 10, 21]);
+  }
+
+  #[test]
+  fn computes_response_expiry() {
+    let headers = |headers: &[(&str, &str)]| {
+      headers
+        .iter()
+        .map(|(name, value)| ((*name).into(), (*value).into()))
+        .collect::<Vec<(ByteString, ByteString)>>()
+    };
+    let now = UNIX_EPOCH + Duration::from_secs(1_000);
+    assert_eq!(
+      response_expires_at(
+        &headers(&[("cache-control", "public, max-age=60")]),
+        now
+      ),
+      Some(1_060_000)
+    );
+    // max-age takes precedence over Expires, and the age is deducted.
+    assert_eq!(
+      response_expires_at(
+        &headers(&[
+          ("Cache-Control", "max-age=60"),
+          ("age", "10"),
+          ("expires", "Thu, 01 Jan 1970 00:00:05 GMT"),
+        ]),
+        now
+      ),
+      Some(1_050_000)
+    );
+    assert_eq!(
+      response_expires_at(
+        &headers(&[("expires", "Thu, 01 Jan 1970 00:00:05 GMT")]),
+        now
+      ),
+      Some(5_000)
+    );
+    assert_eq!(
+      response_expires_at(&headers(&[("expires", "0")]), now),
+      Some(0)
+    );
+    assert_eq!(
+      response_expires_at(&headers(&[("expires", "not a date")]), now),
+      Some(0)
+    );
+    assert_eq!(response_expires_at(&headers(&[]), now), None);
+  }
 }
@@ -0,0 +1,518 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use bytes::Bytes;
+use bytes::BytesMut;
+use deno_core::AsyncResult;
+use deno_core::BufMutView;
+use deno_core::BufView;
+use deno_core::Resource;
+
+use crate::CacheDeleteRequest;
+use crate::CacheError;
+use crate::CacheImpl;
+use crate::CacheKeyEntry;
+use crate::CacheMatchRequest;
+use crate::CacheMatchResponseMeta;
+use crate::CachePutRequest;
+use crate::CacheResponseResource;
+use crate::CacheStorageEstimate;
+use crate::memory::InMemoryCache;
+
+/// Size of the in-memory layer of a tiered cache when it isn't set
+/// explicitly.
+pub const DEFAULT_HOT_CACHE_SIZE: u64 = 32 * 1024 * 1024;
+
+/// An in-memory layer in front of another backend. Writes go through to both
+/// layers, and matches are served from memory when possible, falling back to
+/// the backing cache and keeping the response in memory for the next match.
+///
+/// Bodies are streamed to the backing cache and to the caller, and copied to
+/// memory along the way. Bodies larger than the in-memory layer are only
+/// kept in the backing cache.
+///
+/// The in-memory layer isn't invalidated by writes from other processes
+/// sharing the backing cache.
+#[derive(Clone)]
+pub struct TieredCache {
+  hot: InMemoryCache,
+  hot_max_size: u64,
+  cold: Rc<CacheImpl>,
+  /// Ids of the in-memory caches, by id of the backing cache.
+  hot_ids: Rc<RefCell<HashMap<i64, i64>>>,
+  /// Incremented on every write, so that a response read from the backing
+  /// cache isn't promoted over a newer one.
+  generation: Rc<Cell<u64>>,
+}
+
+impl TieredCache {
+  pub fn new(cold: CacheImpl, hot_max_size: u64) -> Self {
+    Self {
+      hot: InMemoryCache::new(Some(hot_max_size)),
+      hot_max_size,
+      cold: Rc::new(cold),
+      hot_ids: Default::default(),
+      generation: Default::default(),
+    }
+  }
+
+  fn hot_id(&self, cache_id: i64) -> Option<i64> {
+    self.hot_ids.borrow().get(&cache_id).copied()
+  }
+
+  fn bump_generation(&self) {
+    self.generation.set(self.generation.get() + 1);
+  }
+
+  pub async fn storage_open(
+    &self,
+    cache_name: String,
+  ) -> Result<i64, CacheError> {
+    let cache_id = self.cold.storage_open(cache_name.clone()).await?;
+    let hot_id = self.hot.storage_open(cache_name);
+    self.hot_ids.borrow_mut().insert(cache_id, hot_id);
+    Ok(cache_id)
+  }
+
+  pub async fn storage_has(
+    &self,
+    cache_name: String,
+  ) -> Result<bool, CacheError> {
+    self.cold.storage_has(cache_name).await
+  }
+
+  pub async fn storage_delete(
+    &self,
+    cache_name: String,
+  ) -> Result<bool, CacheError> {
+    self.bump_generation();
+    if self.hot.storage_delete(&cache_name) {
+      // The in-memory cache is recreated when the cache is opened again.
+      let hot = &self.hot;
+      self
+        .hot_ids
+        .borrow_mut()
+        .retain(|_, hot_id| hot.has_cache_id(*hot_id));
+    }
+    self.cold.storage_delete(cache_name).await
+  }
+
+  pub async fn storage_keys(&self) -> Result<Vec<String>, CacheError> {
+    self.cold.storage_keys().await
+  }
+
+  pub async fn put(
+    &self,
+    request_response: CachePutRequest,
+    resource: Option<Rc<dyn Resource>>,
+  ) -> Result<(), CacheError> {
+    self.bump_generation();
+    let tee = resource.map(|resource| {
+      Rc::new(TeeResource {
+        inner: resource,
+        copy: BodyCopy::new(self.hot_max_size),
+      })
+    });
+    let result = self
+      .cold
+      .put(
+        request_response.clone(),
+        tee.clone().map(|tee| tee as Rc<dyn Resource>),
+      )
+      .await;
+    if result.is_err() {
+      // Don't keep serving what the backing cache may no longer have.
+      self.delete_hot(request_response.cache_id, &request_response.request_url);
+      return result;
+    }
+    match tee {
+      Some(tee) => match tee.copy.take() {
+        Some(body) => self.put_hot(request_response, Some(body)),
+        None => self
+          .delete_hot(request_response.cache_id, &request_response.request_url),
+      },
+      None => self.put_hot(request_response, None),
+    }
+    Ok(())
+  }
+
+  /// Keeps an entry of the backing cache in memory. Entries that don't fit
+  /// are only kept in the backing cache.
+  fn put_hot(
+    &self,
+    mut request_response: CachePutRequest,
+    body: Option<Bytes>,
+  ) {
+    let Some(hot_id) = self.hot_id(request_response.cache_id) else {
+      return;
+    };
+    let cache_id = request_response.cache_id;
+    let request_url = request_response.request_url.clone();
+    request_response.cache_id = hot_id;
+    if let Err(err) = self.hot.put_body(request_response, body) {
+      log::debug!("Not keeping cache entry in memory: {err}");
+      self.delete_hot(cache_id, &request_url);
+    }
+  }
+
+  fn delete_hot(&self, cache_id: i64, request_url: &str) {
+    if let Some(hot_id) = self.hot_id(cache_id) {
+      self.hot.delete(CacheDeleteRequest {
+        cache_id: hot_id,
+        request_url: request_url.to_string(),
+      });
+    }
+  }
+
+  pub async fn r#match(
+    &self,
+    request: CacheMatchRequest,
+  ) -> Result<
+    Option<(CacheMatchResponseMeta, Option<CacheResponseResource>)>,
+    CacheError,
+  > {
+    if let Some(hot_id) = self.hot_id(request.cache_id)
+      && let Some((meta, body)) = self.hot.r#match(CacheMatchRequest {
+        cache_id: hot_id,
+        request_url: request.request_url.clone(),
+        request_headers: request.request_headers.clone(),
+      })
+    {
+      return Ok(Some((meta, body.map(CacheResponseResource::memory))));
+    }
+
+    let cache_id = request.cache_id;
+    let request_url = request.request_url.clone();
+    let generation = self.generation.get();
+    let Some((meta, resource)) = self.cold.r#match(request).await? else {
+      return Ok(None);
+    };
+    let put = CachePutRequest {
+      cache_id,
+      request_url,
+      request_headers: meta.request_headers.clone(),
+      response_headers: meta.response_headers.clone(),
+      response_status: meta.response_status,
+      response_status_text: meta.response_status_text.clone(),
+      response_rid: None,
+    };
+    let Some(resource) = resource else {
+      self.put_hot(put, None);
+      return Ok(Some((meta, None)));
+    };
+    // The response is kept in memory once the caller has read all of it.
+    let cache = self.clone();
+    let promote = Box::new(move |body: Bytes| {
+      if cache.generation.get() == generation {
+        cache.put_hot(put, Some(body));
+      }
+    });
+    let body = PromotingBody {
+      inner: Rc::new(resource),
+      copy: BodyCopy::new(self.hot_max_size),
+      promote: RefCell::new(Some(promote)),
+    };
+    Ok(Some((meta, Some(CacheResponseResource::Tiered(body)))))
+  }
+
+  pub async fn delete(
+    &self,
+    request: CacheDeleteRequest,
+  ) -> Result<bool, CacheError> {
+    self.bump_generation();
+    self.delete_hot(request.cache_id, &request.request_url);
+    self.cold.delete(request).await
+  }
+
+  pub async fn keys(
+    &self,
+    cache_id: i64,
+    request_url: Option<String>,
+  ) -> Result<Vec<CacheKeyEntry>, CacheError> {
+    self.cold.keys(cache_id, request_url).await
+  }
+
+  pub async fn estimate(&self) -> Result<CacheStorageEstimate, CacheError> {
+    self.cold.estimate().await
+  }
+}
+
+/// A copy of a body being streamed, given up once it gets larger than
+/// `max_size`.
+struct BodyCopy {
+  body: RefCell<Option<BytesMut>>,
+  complete: Cell<bool>,
+  max_size: u64,
+}
+
+impl BodyCopy {
+  fn new(max_size: u64) -> Self {
+    Self {
+      body: RefCell::new(Some(BytesMut::new())),
+      complete: Cell::new(false),
+      max_size,
+    }
+  }
+
+  /// Appends a chunk, an empty one marking the end of the body.
+  fn record(&self, chunk: &[u8]) {
+    if chunk.is_empty() {
+      self.complete.set(true);
+      return;
+    }
+    let mut body = self.body.borrow_mut();
+    if let Some(copy) = body.as_mut() {
+      if (copy.len() + chunk.len()) as u64 > self.max_size {
+        *body = None;
+      } else {
+        copy.extend_from_slice(chunk);
+      }
+    }
+  }
+
+  /// The whole body, if it was read to the end without getting too large.
+  fn take(&self) -> Option<Bytes> {
+    if !self.complete.get() {
+      return None;
+    }
+    self.body.borrow_mut().take().map(BytesMut::freeze)
+  }
+}
+
+/// Feeds a body to the backing cache while copying it.
+struct TeeResource {
+  inner: Rc<dyn Resource>,
+  copy: BodyCopy,
+}
+
+impl Resource for TeeResource {
+  fn name(&self) -> Cow<'_, str> {
+    "cacheBody".into()
+  }
+
+  fn read(self: Rc<Self>, limit: usize) -> AsyncResult<BufView> {
+    Box::pin(async move {
+      let chunk = self.inner.clone().read(limit).await?;
+      self.copy.record(&chunk);
+      Ok(chunk)
+    })
+  }
+
+  fn read_byob(
+    self: Rc<Self>,
+    buf: BufMutView,
+  ) -> AsyncResult<(usize, BufMutView)> {
+    Box::pin(async move {
+      let (nread, buf) = self.inner.clone().read_byob(buf).await?;
+      self.copy.record(&buf[..nread]);
+      Ok((nread, buf))
+    })
+  }
+}
+
+/// A response body of the backing cache, kept in memory once it has been
+/// read to the end.
+pub struct PromotingBody {
+  inner: Rc<CacheResponseResource>,
+  copy: BodyCopy,
+  promote: RefCell<Option<Box<dyn FnOnce(Bytes)>>>,
+}
+
+impl PromotingBody {
+  pub(crate) async fn read(
+    &self,
+    data: &mut [u8],
+  ) -> Result<usize, std::io::Error> {
+    let nread = Box::pin(self.inner.clone().read(data)).await?;
+    self.copy.record(&data[..nread]);
+    if nread == 0
+      && let Some(body) = self.copy.take()
+      && let Some(promote) = self.promote.borrow_mut().take()
+    {
+      promote(body);
+    }
+    Ok(nread)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_core::convert::ByteString;
+
+  use super::*;
+
+  /// Feeds a body to `put`.
+  struct BytesResource(RefCell<Bytes>);
+
+  impl Resource for BytesResource {
+    fn name(&self) -> Cow<'_, str> {
+      "cacheBody".into()
+    }
+
+    fn read(self: Rc<Self>, limit: usize) -> AsyncResult<BufView> {
+      let mut body = self.0.borrow_mut();
+      let chunk = body.split_to(limit.min(body.len()));
+      Box::pin(std::future::ready(Ok(BufView::from(chunk))))
+    }
+  }
+
+  fn body(body: &'static [u8]) -> Option<Rc<dyn Resource>> {
+    Some(Rc::new(BytesResource(RefCell::new(Bytes::from_static(
+      body,
+    )))))
+  }
+
+  fn put_request(cache_id: i64, url: &str) -> CachePutRequest {
+    CachePutRequest {
+      cache_id,
+      request_url: url.to_string(),
+      request_headers: vec![],
+      response_headers: vec![(
+        ByteString::from("content-type"),
+        ByteString::from("text/plain"),
+      )],
+      response_status: 200,
+      response_status_text: "OK".to_string(),
+      response_rid: None,
+    }
+  }
+
+  fn match_request(cache_id: i64, url: &str) -> CacheMatchRequest {
+    CacheMatchRequest {
+      cache_id,
+      request_url: url.to_string(),
+      request_headers: vec![],
+    }
+  }
+
+  fn hot_body(cache: &TieredCache, cache_id: i64, url: &str) -> Option<Bytes> {
+    let hot_id = cache.hot_id(cache_id).unwrap();
+    cache
+      .hot
+      .r#match(match_request(hot_id, url))
+      .map(|(_, body)| body.unwrap())
+  }
+
+  async fn match_body(
+    cache: &TieredCache,
+    cache_id: i64,
+    url: &str,
+  ) -> Option<Bytes> {
+    let (_, body) =
+      cache.r#match(match_request(cache_id, url)).await.unwrap()?;
+    Some(Rc::new(body.unwrap()).read_to_end().await.unwrap())
+  }
+
+  #[tokio::test]
+  async fn writes_through_and_promotes_on_match() {
+    let cold = InMemoryCache::new(None);
+    let cache = TieredCache::new(CacheImpl::Memory(cold.clone()), 1024);
+    let cache_id = cache.storage_open("v1".to_string()).await.unwrap();
+
+    cache
+      .put(put_request(cache_id, "https://a/"), body(b"hello"))
+      .await
+      .unwrap();
+    assert_eq!(
+      hot_body(&cache, cache_id, "https://a/"),
+      Some(Bytes::from_static(b"hello"))
+    );
+    assert!(
+      cold
+        .r#match(match_request(cache_id, "https://a/"))
+        .is_some()
+    );
+
+    // Written by another cache sharing the backing one.
+    cold
+      .put(put_request(cache_id, "https://b/"), body(b"world"))
+      .await
+      .unwrap();
+    assert_eq!(hot_body(&cache, cache_id, "https://b/"), None);
+    assert_eq!(
+      match_body(&cache, cache_id, "https://b/").await,
+      Some(Bytes::from_static(b"world"))
+    );
+    assert_eq!(
+      hot_body(&cache, cache_id, "https://b/"),
+      Some(Bytes::from_static(b"world"))
+    );
+
+    assert!(
+      cache
+        .delete(CacheDeleteRequest {
+          cache_id,
+          request_url: "https://a/".to_string(),
+        })
+        .await
+        .unwrap()
+    );
+    assert_eq!(hot_body(&cache, cache_id, "https://a/"), None);
+    assert_eq!(match_body(&cache, cache_id, "https://a/").await, None);
+  }
+
+  #[tokio::test]
+  async fn keeps_large_bodies_out_of_memory() {
+    let cache =
+      TieredCache::new(CacheImpl::Memory(InMemoryCache::new(None)), 16);
+    let cache_id = cache.storage_open("v1".to_string()).await.unwrap();
+
+    cache
+      .put(put_request(cache_id, "https://a/"), body(b"small"))
+      .await
+      .unwrap();
+    assert!(hot_body(&cache, cache_id, "https://a/").is_some());
+
+    // A new version too large for memory replaces the one kept there.
+    let large = b"a body larger than the in-memory layer";
+    cache
+      .put(put_request(cache_id, "https://a/"), body(large))
+      .await
+      .unwrap();
+    assert_eq!(hot_body(&cache, cache_id, "https://a/"), None);
+    assert_eq!(
+      match_body(&cache, cache_id, "https://a/").await,
+      Some(Bytes::from_static(large))
+    );
+    assert_eq!(hot_body(&cache, cache_id, "https://a/"), None);
+  }
+
+  #[tokio::test]
+  async fn does_not_promote_over_a_newer_put() {
+    let cache =
+      TieredCache::new(CacheImpl::Memory(InMemoryCache::new(None)), 1024);
+    let cache_id = cache.storage_open("v1".to_string()).await.unwrap();
+    cache
+      .put(put_request(cache_id, "https://a/"), body(b"old"))
+      .await
+      .unwrap();
+    let hot_id = cache.hot_id(cache_id).unwrap();
+    cache.hot.delete(CacheDeleteRequest {
+      cache_id: hot_id,
+      request_url: "https://a/".to_string(),
+    });
+
+    let (_, old) = cache
+      .r#match(match_request(cache_id, "https://a/"))
+      .await
+      .unwrap()
+      .unwrap();
+    cache
+      .put(put_request(cache_id, "https://a/"), body(b"new"))
+      .await
+      .unwrap();
+    assert_eq!(
+      Rc::new(old.unwrap()).read_to_end().await.unwrap(),
+      Bytes::from_static(b"old")
+    );
+    assert_eq!(
+      hot_body(&cache, cache_id, "https://a/"),
+      Some(Bytes::from_static(b"new"))
+    );
+  }
+}
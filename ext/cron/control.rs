@@ -0,0 +1,279 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! Control channel of the local cron handler.
+//!
+//! When `DENO_CRON_CONTROL_SOCK` is set, the local handler listens on that
+//! address once the first cron is created. Like the protocol spoken with
+//! `DENO_UNSTABLE_CRON_SOCK`, messages are newline-delimited JSON objects
+//! tagged by `kind`. Every request gets exactly one response:
+//!
+//! - `{"kind":"list"}` is answered with `{"kind":"crons","crons":[...]}`.
+//! - `{"kind":"invoke","name":"..."}` runs the named cron now and is
+//!   answered with `{"kind":"invoked","name":"..."}`.
+//!
+//! Failed requests are answered with `{"kind":"error","message":"..."}`.
+//!
+//! The channel isn't authenticated, so access is limited by where it listens:
+//! a `tcp:` address must be a loopback one, and a `unix:` socket is only
+//! accessible to the user running the process.
+
+use std::rc::Rc;
+
+use deno_core::serde_json;
+use deno_core::unsync::spawn;
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+
+use crate::socket::connect_to_socket;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum ControlRequest {
+  List,
+  Invoke { name: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum ControlResponse {
+  Crons { crons: Vec<CronStatus> },
+  Invoked { name: String },
+  Error { message: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CronStatus {
+  pub name: String,
+  pub schedule: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub timezone: Option<String>,
+  /// When the cron runs next, in milliseconds since the epoch. `None` while
+  /// it is running.
+  pub next_deadline: Option<u64>,
+  /// Failed attempts of the current run.
+  pub retries: u32,
+}
+
+/// Sends `request` to the control socket at `socket_addr` and waits for the
+/// response.
+pub async fn send_control_request(
+  socket_addr: &str,
+  request: &ControlRequest,
+) -> Result<ControlResponse, std::io::Error> {
+  let stream = connect_to_socket(socket_addr).await?;
+  let (reader, mut writer) = tokio::io::split(stream);
+  let mut json =
+    serde_json::to_string(request).map_err(std::io::Error::other)?;
+  json.push('\n');
+  writer.write_all(json.as_bytes()).await?;
+  writer.flush().await?;
+
+  let Some(line) = BufReader::new(reader).lines().next_line().await? else {
+    return Err(std::io::Error::new(
+      std::io::ErrorKind::UnexpectedEof,
+      "cron control socket closed without a response",
+    ));
+  };
+  serde_json::from_str(&line).map_err(std::io::Error::other)
+}
+
+/// Accepts connections on `socket_addr` and answers their requests with
+/// `handle_request` until the returned future is dropped.
+pub(crate) async fn serve_control_socket(
+  socket_addr: String,
+  handle_request: Rc<dyn Fn(ControlRequest) -> ControlResponse>,
+) -> Result<(), std::io::Error> {
+  match socket_addr.split_once(':') {
+    Some(("tcp", addr)) => {
+      let addrs = tokio::net::lookup_host(addr).await?.collect::<Vec<_>>();
+      if addrs.is_empty() || addrs.iter().any(|addr| !addr.ip().is_loopback()) {
+        return Err(std::io::Error::new(
+          std::io::ErrorKind::InvalidInput,
+          format!("{addr} is not a loopback address"),
+        ));
+      }
+      let listener = tokio::net::TcpListener::bind(&*addrs).await?;
+      loop {
+        let (stream, _) = listener.accept().await?;
+        spawn(serve_connection(stream, handle_request.clone()));
+      }
+    }
+    #[cfg(unix)]
+    Some(("unix", path)) => {
+      // A socket left behind by a previous process would make binding fail,
+      // but anything else at that path is not ours to remove.
+      #[allow(
+        clippy::disallowed_methods,
+        reason = "the control socket is always on the real fs"
+      )]
+      match std::fs::symlink_metadata(path) {
+        Ok(metadata) => {
+          use std::os::unix::fs::FileTypeExt;
+          if !metadata.file_type().is_socket() {
+            return Err(std::io::Error::new(
+              std::io::ErrorKind::AlreadyExists,
+              format!("{path} exists and is not a socket"),
+            ));
+          }
+          std::fs::remove_file(path)?;
+        }
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+          return Err(err);
+        }
+        Err(_) => {}
+      }
+      let listener = tokio::net::UnixListener::bind(path)?;
+      {
+        use std::os::unix::fs::PermissionsExt;
+        #[allow(
+          clippy::disallowed_methods,
+          reason = "the control socket is always on the real fs"
+        )]
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+      }
+      loop {
+        let (stream, _) = listener.accept().await?;
+        spawn(serve_connection(stream, handle_request.clone()));
+      }
+    }
+    _ => Err(std::io::Error::new(
+      std::io::ErrorKind::InvalidInput,
+      "invalid socket address",
+    )),
+  }
+}
+
+async fn serve_connection(
+  stream: impl AsyncRead + AsyncWrite,
+  handle_request: Rc<dyn Fn(ControlRequest) -> ControlResponse>,
+) {
+  let (reader, mut writer) = tokio::io::split(stream);
+  let mut lines = BufReader::new(reader).lines();
+  while let Ok(Some(line)) = lines.next_line().await {
+    let response = match serde_json::from_str(&line) {
+      Ok(request) => handle_request(request),
+      Err(err) => ControlResponse::Error {
+        message: format!("Invalid request: {err}"),
+      },
+    };
+    let Ok(mut json) = serde_json::to_string(&response) else {
+      return;
+    };
+    json.push('\n');
+    if writer.write_all(json.as_bytes()).await.is_err()
+      || writer.flush().await.is_err()
+    {
+      return;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn serializes_messages() {
+    assert_eq!(
+      serde_json::from_str::<ControlRequest>(
+        r#"{"kind":"invoke","name":"Daily report"}"#
+      )
+      .unwrap(),
+      ControlRequest::Invoke {
+        name: "Daily report".to_string()
+      }
+    );
+    let response = ControlResponse::Crons {
+      crons: vec![CronStatus {
+        name: "Daily report".to_string(),
+        schedule: "0 9 * * *".to_string(),
+        timezone: None,
+        next_deadline: Some(1_700_000_000_000),
+        retries: 0,
+      }],
+    };
+    assert_eq!(
+      serde_json::to_string(&response).unwrap(),
+      r#"{"kind":"crons","crons":[{"name":"Daily report","schedule":"0 9 * * *","next_deadline":1700000000000,"retries":0}]}"#
+    );
+  }
+
+  #[cfg(unix)]
+  #[test]
+  #[allow(clippy::disallowed_methods, reason = "test uses a real temp file")]
+  fn does_not_remove_non_socket_files() {
+    let path = std::env::temp_dir()
+      .join(format!("deno_cron_control_{}", std::process::id()));
+    std::fs::write(&path, "keep me").unwrap();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+      .enable_all()
+      .build()
+      .unwrap();
+    let err = runtime
+      .block_on(serve_control_socket(
+        format!("unix:{}", path.display()),
+        Rc::new(|_| ControlResponse::Error {
+          message: String::new(),
+        }),
+      ))
+      .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+    std::fs::remove_file(&path).unwrap();
+  }
+
+  #[test]
+  fn only_listens_on_loopback_addresses() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+      .enable_all()
+      .build()
+      .unwrap();
+    for addr in ["tcp:0.0.0.0:0", "tcp:[::]:0"] {
+      let err = runtime
+        .block_on(serve_control_socket(
+          addr.to_string(),
+          Rc::new(|_| ControlResponse::Error {
+            message: String::new(),
+          }),
+        ))
+        .unwrap_err();
+      assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput, "{addr}");
+    }
+  }
+
+  #[cfg(unix)]
+  #[test]
+  #[allow(clippy::disallowed_methods, reason = "test uses a real temp file")]
+  fn unix_socket_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::env::temp_dir()
+      .join(format!("deno_cron_control_{}.sock", std::process::id()));
+    let runtime = tokio::runtime::Builder::new_current_thread()
+      .enable_all()
+      .build()
+      .unwrap();
+    let local = tokio::task::LocalSet::new();
+    local.block_on(&runtime, async {
+      let server = deno_core::unsync::spawn(serve_control_socket(
+        format!("unix:{}", path.display()),
+        Rc::new(|_| ControlResponse::Error {
+          message: String::new(),
+        }),
+      ));
+      while !path.exists() {
+        tokio::task::yield_now().await;
+      }
+      tokio::task::yield_now().await;
+      let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+      assert_eq!(mode & 0o777, 0o600);
+      server.abort();
+    });
+    std::fs::remove_file(&path).unwrap();
+  }
+}
@@ -5,9 +5,12 @@ use std::str::FromStr;
 use chrono::DateTime;
 use chrono::Datelike;
 use chrono::Duration;
+use chrono::LocalResult;
+use chrono::NaiveDate;
+use chrono::NaiveDateTime;
+use chrono::Offset;
 use chrono::TimeZone;
 use chrono::Timelike;
-use chrono::Utc;
 use chrono::Weekday;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -116,17 +119,116 @@ impl FromStr for Schedule {
   }
 }
 
+/// Parses an IANA time zone name such as `Europe/Berlin`.
+pub fn parse_timezone(name: &str) -> Result<chrono_tz::Tz, ParseError> {
+  name.parse::<chrono_tz::Tz>().map_err(|_| ParseError)
+}
+
 impl Schedule {
-  pub fn next_after(&self, date: DateTime<Utc>) -> Option<DateTime<Utc>> {
+  /// Returns the first time after `date` that matches the schedule, reading
+  /// the fields as wall-clock time in the time zone of `date`.
+  ///
+  /// Across daylight saving time transitions this follows Vixie cron: a time
+  /// skipped when the clocks go forward runs at the end of the gap instead,
+  /// and a time repeated when the clocks go back runs only once. Schedules
+  /// that run every hour are intervals rather than times of day, so they
+  /// don't run in the gap and do run in both passes of a repeated hour.
+  pub fn next_after<Tz: TimeZone>(
+    &self,
+    date: DateTime<Tz>,
+  ) -> Option<DateTime<Tz>> {
     // Rare schedules such as 5th Fridays in February can have multi-decade
     // gaps. Keep the bounded search broad enough to preserve saffron behavior.
     const MAX_SEARCH_DAYS: i64 = 366 * 100;
 
-    let mut next = date
-      .with_nanosecond(0)?
-      .with_second(0)?
-      .checked_add_signed(Duration::minutes(1))?;
-    let end = next.checked_add_signed(Duration::days(MAX_SEARCH_DAYS))?;
+    let tz = date.timezone();
+    let start = date.naive_local().with_nanosecond(0)?.with_second(0)?;
+    let end = start.checked_add_signed(Duration::days(MAX_SEARCH_DAYS))?;
+
+    let second_pass = self.next_in_second_pass(&date, start, end);
+    let mut local = start;
+    let next = loop {
+      let Some(next_local) = self.next_local_after(local, end) else {
+        break None;
+      };
+      local = next_local;
+      match tz.from_local_datetime(&local) {
+        LocalResult::Single(next) => {
+          if next > date {
+            break Some(next);
+          }
+        }
+        LocalResult::Ambiguous(earliest, latest) => {
+          if earliest > date {
+            break Some(earliest);
+          }
+          if self.is_hourly() && latest > date {
+            break Some(latest);
+          }
+        }
+        LocalResult::None => {
+          if !self.is_hourly()
+            && let Some(next) = end_of_gap(&tz, local)
+            && next > date
+          {
+            break Some(next);
+          }
+        }
+      }
+    };
+
+    match (next, second_pass) {
+      (Some(next), Some(second_pass)) => Some(next.min(second_pass)),
+      (next, second_pass) => next.or(second_pass),
+    }
+  }
+
+  /// When `date` is in the first pass of a repeated hour, returns the first
+  /// match of an hourly schedule in the second pass. Those wall-clock times
+  /// were already passed, so the forward search doesn't revisit them.
+  fn next_in_second_pass<Tz: TimeZone>(
+    &self,
+    date: &DateTime<Tz>,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+  ) -> Option<DateTime<Tz>> {
+    if !self.is_hourly() {
+      return None;
+    }
+    let tz = date.timezone();
+    let LocalResult::Ambiguous(earliest, _) = tz.from_local_datetime(&start)
+    else {
+      return None;
+    };
+    if earliest.offset().fix() != date.offset().fix() {
+      return None;
+    }
+    let mut fold_start = start;
+    loop {
+      let prev = fold_start.checked_sub_signed(Duration::minutes(1))?;
+      if !matches!(tz.from_local_datetime(&prev), LocalResult::Ambiguous(..)) {
+        break;
+      }
+      fold_start = prev;
+    }
+    let local = self.next_local_after(
+      fold_start.checked_sub_signed(Duration::minutes(1))?,
+      end,
+    )?;
+    match tz.from_local_datetime(&local) {
+      LocalResult::Ambiguous(_, latest) => Some(latest),
+      _ => None,
+    }
+  }
+
+  /// Returns the first wall-clock time after `date` that matches, up to
+  /// `end`.
+  fn next_local_after(
+    &self,
+    date: NaiveDateTime,
+    end: NaiveDateTime,
+  ) -> Option<NaiveDateTime> {
+    let mut next = date.checked_add_signed(Duration::minutes(1))?;
 
     while next <= end {
       if self.contains(next) {
@@ -135,7 +237,7 @@ impl Schedule {
 
       if (self.months & (1u16 << next.month())) == 0 {
         next = self.next_matching_month_start(next)?;
-      } else if !self.matches_day(next) {
+      } else if !self.matches_day(&next) {
         next = next
           .checked_add_signed(Duration::days(1))?
           .with_hour(0)?
@@ -150,14 +252,18 @@ impl Schedule {
     None
   }
 
-  fn contains(&self, date: DateTime<Utc>) -> bool {
+  fn is_hourly(&self) -> bool {
+    self.hours == bit_range(Field::Hour.min(), Field::Hour.max()) as u32
+  }
+
+  fn contains(&self, date: impl Datelike + Timelike) -> bool {
     (self.minutes & (1u64 << date.minute())) != 0
       && (self.hours & (1u32 << date.hour())) != 0
       && (self.months & (1u16 << date.month())) != 0
-      && self.matches_day(date)
+      && self.matches_day(&date)
   }
 
-  fn matches_day(&self, date: DateTime<Utc>) -> bool {
+  fn matches_day(&self, date: &impl Datelike) -> bool {
     match (self.days_of_month.is_any(), self.days_of_week.is_any()) {
       (true, true) => true,
       (true, false) => self.days_of_week.contains(date),
@@ -170,8 +276,8 @@ impl Schedule {
 
   fn next_matching_month_start(
     &self,
-    date: DateTime<Utc>,
-  ) -> Option<DateTime<Utc>> {
+    date: NaiveDateTime,
+  ) -> Option<NaiveDateTime> {
     let mut year = date.year();
     let mut month = date.month().checked_add(1)?;
 
@@ -190,8 +296,8 @@ impl Schedule {
 
   fn next_matching_hour_start(
     &self,
-    date: DateTime<Utc>,
-  ) -> Option<DateTime<Utc>> {
+    date: NaiveDateTime,
+  ) -> Option<NaiveDateTime> {
     for hour in date.hour()..=23 {
       if (self.hours & (1u32 << hour)) != 0 {
         return date.with_hour(hour)?.with_minute(0);
@@ -206,8 +312,8 @@ impl Schedule {
 
   fn next_matching_minute_start(
     &self,
-    date: DateTime<Utc>,
-  ) -> Option<DateTime<Utc>> {
+    date: NaiveDateTime,
+  ) -> Option<NaiveDateTime> {
     for minute in date.minute()..=59 {
       if (self.minutes & (1u64 << minute)) != 0 {
         return date.with_minute(minute);
@@ -223,7 +329,7 @@ impl DaysOfMonth {
     matches!(self, Self::Any)
   }
 
-  fn contains(&self, date: DateTime<Utc>) -> bool {
+  fn contains(&self, date: &impl Datelike) -> bool {
     let day = date.day();
     let days_in_month = days_in_month(date.year(), date.month());
     match *self {
@@ -254,7 +360,7 @@ impl DaysOfWeek {
     matches!(self, Self::Any)
   }
 
-  fn contains(&self, date: DateTime<Utc>) -> bool {
+  fn contains(&self, date: &impl Datelike) -> bool {
     let weekday = date.weekday().num_days_from_sunday();
     match *self {
       Self::Any => true,
@@ -471,10 +577,24 @@ fn ymd_hms(
   hour: u32,
   minute: u32,
   second: u32,
-) -> Option<DateTime<Utc>> {
-  Utc
-    .with_ymd_and_hms(year, month, day, hour, minute, second)
-    .single()
+) -> Option<NaiveDateTime> {
+  NaiveDate::from_ymd_opt(year, month, day)?.and_hms_opt(hour, minute, second)
+}
+
+/// Returns the first instant after a wall-clock time that was skipped when
+/// the clocks went forward.
+fn end_of_gap<Tz: TimeZone>(
+  tz: &Tz,
+  mut local: NaiveDateTime,
+) -> Option<DateTime<Tz>> {
+  // Transitions move the clocks by at most a few hours.
+  for _ in 0..24 * 60 {
+    local = local.checked_add_signed(Duration::minutes(1))?;
+    if let Some(date) = tz.from_local_datetime(&local).earliest() {
+      return Some(date);
+    }
+  }
+  None
 }
 
 fn is_leap_year(year: i32) -> bool {
@@ -500,6 +620,8 @@ fn closest_weekday(year: i32, month: u32, day: u32) -> Option<u32> {
 #[cfg(test)]
 mod tests {
   use chrono::TimeZone;
+  use chrono::Utc;
+  use chrono_tz::Europe::Berlin;
 
   use super::*;
 
@@ -691,4 +813,62 @@ mod tests {
     let schedule = "0 0 31W APR *".parse::<Schedule>().unwrap();
     assert_eq!(schedule.next_after(dt(2021, 4, 1, 0, 0)), None);
   }
+
+  #[test]
+  fn follows_daylight_saving_time() {
+    let next_in = |schedule: &str, date: DateTime<Utc>| {
+      schedule
+        .parse::<Schedule>()
+        .unwrap()
+        .next_after(date.with_timezone(&Berlin))
+        .unwrap()
+        .with_timezone(&Utc)
+    };
+
+    // 09:00 local is 08:00 UTC in winter and 07:00 UTC in summer.
+    for (start, expected) in [
+      (dt(2024, 3, 15, 12, 0), dt(2024, 3, 18, 8, 0)),
+      (dt(2024, 3, 29, 12, 0), dt(2024, 4, 1, 7, 0)),
+      (dt(2024, 10, 25, 12, 0), dt(2024, 10, 28, 8, 0)),
+    ] {
+      assert_eq!(next_in("0 9 * * MON-FRI", start), expected, "{start}");
+    }
+
+    // 02:30 doesn't exist on 2024-03-31, so it runs when the clocks jump
+    // from 02:00 to 03:00 instead.
+    assert_eq!(
+      next_in("30 2 * * *", dt(2024, 3, 30, 12, 0)),
+      dt(2024, 3, 31, 1, 0)
+    );
+    assert_eq!(
+      next_in("30 2 * * *", dt(2024, 3, 31, 1, 0)),
+      dt(2024, 4, 1, 0, 30)
+    );
+    assert_eq!(
+      next_in("30 * * * *", dt(2024, 3, 31, 0, 30)),
+      dt(2024, 3, 31, 1, 30)
+    );
+
+    // 02:30 happens twice on 2024-10-27 and only runs the first time.
+    assert_eq!(
+      next_in("30 2 * * *", dt(2024, 10, 26, 12, 0)),
+      dt(2024, 10, 27, 0, 30)
+    );
+    assert_eq!(
+      next_in("30 2 * * *", dt(2024, 10, 27, 0, 30)),
+      dt(2024, 10, 28, 1, 30)
+    );
+    // Hourly schedules run in both passes.
+    assert_eq!(
+      next_in("30 * * * *", dt(2024, 10, 27, 0, 30)),
+      dt(2024, 10, 27, 1, 30)
+    );
+    assert_eq!(
+      next_in("30 * * * *", dt(2024, 10, 27, 1, 30)),
+      dt(2024, 10, 27, 2, 30)
+    );
+
+    assert!(parse_timezone("Europe/Berlin").is_ok());
+    assert!(parse_timezone("Mars/Olympus_Mons").is_err());
+  }
 }
@@ -1,13 +1,18 @@
 // Copyright 2018-2026 the Deno authors. MIT license.
 
+use std::path::Path;
+use std::path::PathBuf;
 use std::rc::Rc;
 
+use deno_core::VirtualClock;
+
 use crate::CronError;
 use crate::CronHandle;
 use crate::CronHandler;
 use crate::CronSpec;
 use crate::local::LocalCronHandler;
 use crate::socket::SocketCronHandler;
+use crate::store::CRON_STATE_FILE_NAME;
 
 pub enum CronHandlerImpl {
   Local(LocalCronHandler),
@@ -21,6 +26,44 @@ impl CronHandlerImpl {
       Err(_) => Self::Local(LocalCronHandler::new()),
     }
   }
+
+  /// Returns a local handler that persists the state of its crons when
+  /// `DENO_CRON_CATCH_UP` is set, so that ticks missed while the process
+  /// wasn't running are caught up on the next start. The state is kept in
+  /// `DENO_CRON_STATE_PATH`, or next to the KV database in the origin storage
+  /// directory.
+  ///
+  /// Only the main worker uses this. Web workers are created with
+  /// [`Self::create_from_env`] and don't persist their crons, so that crons
+  /// with the same name in several workers don't share state. Embedders opt
+  /// in by putting the returned handler in the op state.
+  pub fn persistent_from_env(
+    origin_storage_dir: Option<&Path>,
+  ) -> Option<Self> {
+    if std::env::var_os("DENO_UNSTABLE_CRON_SOCK").is_some() {
+      return None;
+    }
+    let policy = std::env::var("DENO_CRON_CATCH_UP").ok()?;
+    let Ok(policy) = policy.parse() else {
+      log::warn!("Unknown DENO_CRON_CATCH_UP value, not persisting cron state");
+      return None;
+    };
+    let path = match std::env::var_os("DENO_CRON_STATE_PATH") {
+      Some(path) => PathBuf::from(path),
+      None => match origin_storage_dir {
+        Some(dir) => dir.join(CRON_STATE_FILE_NAME),
+        None => {
+          log::warn!(
+            "DENO_CRON_CATCH_UP is set but there is no origin storage directory, set DENO_CRON_STATE_PATH to persist cron state"
+          );
+          return None;
+        }
+      },
+    };
+    Some(Self::Local(LocalCronHandler::with_state_store(
+      path, policy,
+    )))
+  }
 }
 
 impl CronHandler for CronHandlerImpl {
@@ -31,6 +74,13 @@ impl CronHandler for CronHandlerImpl {
     }
   }
 
+  fn set_virtual_clock(&self, clock: VirtualClock) {
+    match self {
+      Self::Local(h) => h.set_virtual_clock(clock),
+      Self::Socket(h) => h.set_virtual_clock(clock),
+    }
+  }
+
   /// Reload happens when:
   /// - Local → Socket (upgrade)
   /// - Socket(addr1) → Socket(addr2) where addr1 != addr2
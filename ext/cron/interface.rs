@@ -3,6 +3,7 @@
 use std::rc::Rc;
 
 use async_trait::async_trait;
+use deno_core::VirtualClock;
 
 use crate::CronError;
 
@@ -25,6 +26,10 @@ pub trait CronHandler {
   fn maybe_reload(&self) -> Option<Box<dyn CronHandler>> {
     None
   }
+
+  /// Makes the handler schedule crons on `clock` instead of real time. Used
+  /// by `deno test` so that tests can advance time explicitly.
+  fn set_virtual_clock(&self, _clock: VirtualClock) {}
 }
 
 #[async_trait(?Send)]
@@ -39,4 +44,6 @@ pub struct CronSpec {
   pub name: String,
   pub cron_schedule: String,
   pub backoff_schedule: Option<Vec<u32>>,
+  /// IANA time zone the schedule is evaluated in, UTC when not set.
+  pub timezone: Option<String>,
 }
@@ -1,10 +1,12 @@
 // Copyright 2018-2026 the Deno authors. MIT license.
 
+pub mod control;
 mod cron;
 mod handler_impl;
 mod interface;
 pub mod local;
 mod socket;
+mod store;
 
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -21,6 +23,7 @@ use deno_features::FeatureChecker;
 pub use handler_impl::CronHandlerImpl;
 pub use socket::SocketCronHandle;
 pub use socket::SocketCronHandler;
+pub use store::CatchUpPolicy;
 
 pub use crate::interface::*;
 
@@ -79,6 +82,9 @@ pub enum CronError {
   #[class(type)]
   #[error("Invalid backoff schedule")]
   InvalidBackoff,
+  #[class(type)]
+  #[error("Invalid cron time zone: {0}")]
+  InvalidTimezone(String),
   #[class(generic)]
   #[error(transparent)]
   AcquireError(#[from] tokio::sync::AcquireError),
@@ -88,6 +94,9 @@ pub enum CronError {
   #[class(generic)]
   #[error("Error registering cron: {0}")]
   RejectedError(String),
+  #[class(generic)]
+  #[error("Failed to access the cron state store: {0}")]
+  Store(#[from] store::CronStoreError),
   #[class(inherit)]
   #[error(transparent)]
   Other(JsErrorBox),
@@ -100,6 +109,7 @@ fn op_cron_create(
   #[string] name: String,
   #[string] cron_schedule: String,
   #[scoped] backoff_schedule: Option<Vec<u32>>,
+  #[string] timezone: Option<String>,
 ) -> Result<ResourceId, CronError> {
   let cron_handler = {
     let state = state.borrow();
@@ -115,6 +125,7 @@ fn op_cron_create(
     name,
     cron_schedule,
     backoff_schedule,
+    timezone,
   })?;
 
   let handle_rid = {
@@ -152,6 +163,19 @@ async fn op_cron_next(
   cron_handle.next(prev_success).await
 }
 
+/// Checks that the schedule and time zone of a cron can be evaluated.
+fn validate_cron_schedule(spec: &CronSpec) -> Result<(), CronError> {
+  spec
+    .cron_schedule
+    .parse::<cron::Schedule>()
+    .map_err(|_| CronError::InvalidCron)?;
+  if let Some(timezone) = &spec.timezone {
+    cron::parse_timezone(timezone)
+      .map_err(|_| CronError::InvalidTimezone(timezone.clone()))?;
+  }
+  Ok(())
+}
+
 fn validate_cron_name(name: &str) -> Result<(), CronError> {
   if name.len() > 64 {
     return Err(CronError::NameExceeded(name.len()));
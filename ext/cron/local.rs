@@ -3,17 +3,26 @@
 use std::cell::OnceCell;
 use std::cell::RefCell;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::env;
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::rc::Weak;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
 
 use async_trait::async_trait;
+use chrono::DateTime;
+use chrono::Utc;
+use deno_core::VirtualClock;
 use deno_core::futures;
 use deno_core::futures::FutureExt;
 use deno_core::unsync::JoinHandle;
 use deno_core::unsync::spawn;
+use tokio::sync::Notify;
 use tokio::sync::OwnedSemaphorePermit;
 use tokio::sync::Semaphore;
 use tokio::sync::mpsc;
@@ -24,30 +33,58 @@ use crate::CronHandle;
 use crate::CronHandler;
 use crate::CronNextResult;
 use crate::CronSpec;
+use crate::control::ControlRequest;
+use crate::control::ControlResponse;
+use crate::control::CronStatus;
+use crate::control::serve_control_socket;
 use crate::cron::Schedule;
+use crate::cron::parse_timezone;
+use crate::store::CatchUpPolicy;
+use crate::store::CronStateStore;
+use crate::validate_cron_schedule;
 
 const MAX_CRONS: usize = 100;
 const DISPATCH_CONCURRENCY_LIMIT: usize = 50;
 const MAX_BACKOFF_MS: u32 = 60 * 60 * 1_000; // 1 hour
 const MAX_BACKOFF_COUNT: usize = 5;
 const DEFAULT_BACKOFF_SCHEDULE: [u32; 5] = [100, 1_000, 5_000, 30_000, 60_000];
+/// Missed ticks beyond this many are dropped, oldest first, when catching up
+/// on every missed tick.
+const MAX_CATCH_UP_RUNS: usize = 1_000;
 
 pub struct LocalCronHandler {
   cron_schedule_tx: OnceCell<mpsc::Sender<(String, bool)>>,
   concurrency_limiter: Arc<Semaphore>,
   cron_loop_join_handle: OnceCell<JoinHandle<()>>,
   runtime_state: Rc<RefCell<RuntimeState>>,
+  /// Where to persist cron state, and what to do with missed ticks.
+  state_store: Option<(PathBuf, CatchUpPolicy)>,
+  /// Address to serve the control channel on, from `DENO_CRON_CONTROL_SOCK`.
+  control_socket: Option<String>,
+  control_join_handle: OnceCell<JoinHandle<()>>,
+  /// Wakes up the cron loop when a cron is rescheduled from outside of it.
+  wake: Rc<Notify>,
 }
 
 struct RuntimeState {
   crons: HashMap<String, Cron>,
   scheduled_deadlines: BTreeMap<u64, Vec<String>>,
+  /// Opened when the first cron is created, so programs that don't use
+  /// `Deno.cron` never create the database.
+  store: Option<CronStateStore>,
+  /// Set by `deno test` to schedule crons on a virtual clock.
+  clock: Option<VirtualClock>,
 }
 
 struct Cron {
   spec: CronSpec,
   next_tx: mpsc::WeakSender<()>,
   current_execution_retries: u32,
+  /// The scheduled time of the pending or running execution.
+  current_tick: Option<u64>,
+  /// Ticks missed while no process was running, or that failed all of their
+  /// retries in a previous one, to run before the next scheduled one.
+  missed_ticks: VecDeque<u64>,
 }
 
 impl Cron {
@@ -75,13 +112,31 @@ impl LocalCronHandler {
       runtime_state: Rc::new(RefCell::new(RuntimeState {
         crons: HashMap::new(),
         scheduled_deadlines: BTreeMap::new(),
+        store: None,
+        clock: None,
       })),
+      state_store: None,
+      control_socket: env::var("DENO_CRON_CONTROL_SOCK").ok(),
+      control_join_handle: OnceCell::new(),
+      wake: Rc::new(Notify::new()),
     }
   }
 
+  /// Creates a handler that records the last attempted and successful tick
+  /// of each cron in the SQLite database at `path`, along with the ticks that
+  /// failed all of their retries, and applies `policy` to the ticks missed
+  /// since the last successful one and to the failed ones when a cron is
+  /// created.
+  pub fn with_state_store(path: PathBuf, policy: CatchUpPolicy) -> Self {
+    let mut handler = Self::new();
+    handler.state_store = Some((path, policy));
+    handler
+  }
+
   async fn cron_loop(
     runtime_state: Rc<RefCell<RuntimeState>>,
     mut cron_schedule_rx: mpsc::Receiver<(String, bool)>,
+    wake: Rc<Notify>,
   ) -> Result<(), CronError> {
     loop {
       let earliest_deadline = runtime_state
@@ -92,18 +147,24 @@ impl LocalCronHandler {
         .copied();
 
       let sleep_fut = if let Some(earliest_deadline) = earliest_deadline {
-        let now = chrono::Utc::now().timestamp_millis() as u64;
-        if let Some(delta) = earliest_deadline.checked_sub(now) {
-          tokio::time::sleep(std::time::Duration::from_millis(delta)).boxed()
+        let runtime_state = runtime_state.borrow();
+        let now = runtime_state.now().timestamp_millis() as u64;
+        if let Some(clock) = &runtime_state.clock {
+          let deadline =
+            SystemTime::UNIX_EPOCH + Duration::from_millis(earliest_deadline);
+          clock.sleep_until(clock.elapsed_at(deadline)).boxed_local()
+        } else if let Some(delta) = earliest_deadline.checked_sub(now) {
+          tokio::time::sleep(Duration::from_millis(delta)).boxed_local()
         } else {
-          std::future::ready(()).boxed()
+          std::future::ready(()).boxed_local()
         }
       } else {
-        futures::future::pending().boxed()
+        futures::future::pending().boxed_local()
       };
 
       let cron_to_schedule = tokio::select! {
         _ = sleep_fut => None,
+        _ = wake.notified() => None,
         x = cron_schedule_rx.recv() => {
           if x.is_none() {
             return Ok(());
@@ -115,23 +176,55 @@ impl LocalCronHandler {
       // Schedule next execution of the cron if needed.
       if let Some((name, prev_success)) = cron_to_schedule {
         let mut runtime_state = runtime_state.borrow_mut();
-        if let Some(cron) = runtime_state.crons.get_mut(&name) {
-          let backoff_schedule = cron.backoff_schedule();
-          let next_deadline = if !prev_success
-            && cron.current_execution_retries < backoff_schedule.len() as u32
+        let now = runtime_state.now();
+        let RuntimeState {
+          crons,
+          scheduled_deadlines,
+          store,
+          ..
+        } = &mut *runtime_state;
+        if let Some(cron) = crons.get_mut(&name) {
+          let retry = !prev_success
+            && cron.current_execution_retries
+              < cron.backoff_schedule().len() as u32;
+          // Once a tick succeeded or failed all of its retries, record it.
+          // Failed ticks are kept apart from the mark, which a later
+          // successful tick moves past them.
+          if !retry
+            && let Some(tick) = cron.current_tick.take()
+            && let Some(store) = store
           {
+            let result = if prev_success {
+              store.record_success(&name, tick)
+            } else {
+              store.record_failure(&name, tick)
+            };
+            if let Err(err) = result {
+              log::warn!("Failed to record the run of cron {name}: {err}");
+            }
+          }
+          let now_ms = now.timestamp_millis() as u64;
+          let next_deadline = if retry {
+            // Retries are for the same tick.
             let backoff_ms =
-              backoff_schedule[cron.current_execution_retries as usize];
-            let now = chrono::Utc::now().timestamp_millis() as u64;
+              cron.backoff_schedule()[cron.current_execution_retries as usize];
             cron.current_execution_retries += 1;
-            now + backoff_ms as u64
+            now_ms + backoff_ms as u64
+          } else if let Some(tick) = cron.missed_ticks.pop_front() {
+            cron.current_execution_retries = 0;
+            cron.current_tick = Some(tick);
+            now_ms
           } else {
-            let next_ts = compute_next_deadline(&cron.spec.cron_schedule)?;
+            let next_ts = compute_next_deadline(
+              &cron.spec.cron_schedule,
+              cron.spec.timezone.as_deref(),
+              now,
+            )?;
             cron.current_execution_retries = 0;
+            cron.current_tick = Some(next_ts);
             next_ts
           };
-          runtime_state
-            .scheduled_deadlines
+          scheduled_deadlines
             .entry(next_deadline)
             .or_default()
             .push(name.to_string());
@@ -153,10 +246,17 @@ impl LocalCronHandler {
 }
 
 impl RuntimeState {
+  fn now(&self) -> DateTime<Utc> {
+    match &self.clock {
+      Some(clock) => clock.now_system().into(),
+      None => Utc::now(),
+    }
+  }
+
   fn get_ready_crons(
     &mut self,
   ) -> Result<Vec<(String, WeakSender<()>)>, CronError> {
-    let now = chrono::Utc::now().timestamp_millis() as u64;
+    let now = self.now().timestamp_millis() as u64;
 
     let ready = {
       let to_remove = self
@@ -184,8 +284,79 @@ impl RuntimeState {
         .collect::<Vec<_>>()
     };
 
+    if let Some(store) = &self.store {
+      for (name, _) in &ready {
+        if let Some(tick) = self.crons.get(name).and_then(|c| c.current_tick)
+          && let Err(err) = store.record_attempt(name, tick)
+        {
+          log::warn!("Failed to record the run of cron {name}: {err}");
+        }
+      }
+    }
+
     Ok(ready)
   }
+
+  fn scheduled_deadline(&self, name: &str) -> Option<u64> {
+    self
+      .scheduled_deadlines
+      .iter()
+      .find(|(_, names)| names.iter().any(|n| n == name))
+      .map(|(ts, _)| *ts)
+  }
+
+  fn handle_control(&mut self, request: ControlRequest) -> ControlResponse {
+    match request {
+      ControlRequest::List => {
+        let mut crons = self
+          .crons
+          .iter()
+          .map(|(name, cron)| CronStatus {
+            name: name.clone(),
+            schedule: cron.spec.cron_schedule.clone(),
+            timezone: cron.spec.timezone.clone(),
+            next_deadline: self.scheduled_deadline(name),
+            retries: cron.current_execution_retries,
+          })
+          .collect::<Vec<_>>();
+        crons.sort_by(|a, b| a.name.cmp(&b.name));
+        ControlResponse::Crons { crons }
+      }
+      ControlRequest::Invoke { name } => {
+        if !self.crons.contains_key(&name) {
+          return ControlResponse::Error {
+            message: format!("Cron \"{name}\" does not exist"),
+          };
+        }
+        let Some(deadline) = self.scheduled_deadline(&name) else {
+          return ControlResponse::Error {
+            message: format!("Cron \"{name}\" is already running"),
+          };
+        };
+        let now = self.now().timestamp_millis() as u64;
+        if deadline > now {
+          let names = self.scheduled_deadlines.get_mut(&deadline).unwrap();
+          names.retain(|n| *n != name);
+          if names.is_empty() {
+            self.scheduled_deadlines.remove(&deadline);
+          }
+          self
+            .scheduled_deadlines
+            .entry(now)
+            .or_default()
+            .push(name.clone());
+          // A manual run doesn't count as the scheduled tick, which is
+          // computed again once it finishes. While waiting to retry a failed
+          // tick though, it runs the retry early and keeps its state.
+          let cron = self.crons.get_mut(&name).unwrap();
+          if cron.current_execution_retries == 0 {
+            cron.current_tick = None;
+          }
+        }
+        ControlResponse::Invoked { name }
+      }
+    }
+  }
 }
 
 impl CronHandler for LocalCronHandler {
@@ -196,12 +367,41 @@ impl CronHandler for LocalCronHandler {
         mpsc::channel::<(String, bool)>(1);
       self.cron_schedule_tx.set(cron_schedule_tx).unwrap();
       let runtime_state = self.runtime_state.clone();
+      let wake = self.wake.clone();
       spawn(async move {
-        LocalCronHandler::cron_loop(runtime_state, cron_schedule_rx)
+        LocalCronHandler::cron_loop(runtime_state, cron_schedule_rx, wake)
           .await
           .unwrap();
       })
     });
+    if let Some(socket_addr) = &self.control_socket {
+      self.control_join_handle.get_or_init(|| {
+        let runtime_state = Rc::downgrade(&self.runtime_state);
+        let wake = self.wake.clone();
+        let handle_request = Rc::new(move |request: ControlRequest| {
+          let Some(runtime_state) = runtime_state.upgrade() else {
+            return ControlResponse::Error {
+              message: "Crons are shutting down".to_string(),
+            };
+          };
+          let response = runtime_state.borrow_mut().handle_control(request);
+          if matches!(response, ControlResponse::Invoked { .. }) {
+            wake.notify_one();
+          }
+          response
+        });
+        let socket_addr = socket_addr.clone();
+        spawn(async move {
+          if let Err(err) =
+            serve_control_socket(socket_addr.clone(), handle_request).await
+          {
+            log::warn!(
+              "Failed to serve the cron control socket {socket_addr}: {err}"
+            );
+          }
+        })
+      });
+    }
 
     let mut runtime_state = self.runtime_state.borrow_mut();
 
@@ -212,22 +412,43 @@ impl CronHandler for LocalCronHandler {
       return Err(CronError::AlreadyExists);
     }
 
-    // Validate schedule expression.
-    spec
-      .cron_schedule
-      .parse::<Schedule>()
-      .map_err(|_| CronError::InvalidCron)?;
+    validate_cron_schedule(&spec)?;
 
     // Validate backoff_schedule.
     if let Some(backoff_schedule) = &spec.backoff_schedule {
       validate_backoff_schedule(backoff_schedule)?;
     }
 
+    let mut missed_ticks = VecDeque::new();
+    if let Some((path, policy)) = &self.state_store {
+      if runtime_state.store.is_none() {
+        runtime_state.store = Some(CronStateStore::open(path)?);
+      }
+      let now = runtime_state.now().timestamp_millis() as u64;
+      let store = runtime_state.store.as_ref().unwrap();
+      let state = store.register(&spec.name, now)?;
+      missed_ticks = compute_missed_ticks(
+        &spec,
+        *policy,
+        state.handled_until(),
+        &state.failed_ticks,
+        now,
+      )?;
+      // Failed ticks dropped by the policy are not caught up later either.
+      for tick in &state.failed_ticks {
+        if !missed_ticks.contains(tick) {
+          store.forget_failure(&spec.name, *tick)?;
+        }
+      }
+    }
+
     let (next_tx, next_rx) = mpsc::channel::<()>(1);
     let cron = Cron {
       spec: spec.clone(),
       next_tx: next_tx.downgrade(),
       current_execution_retries: 0,
+      current_tick: None,
+      missed_ticks,
     };
     runtime_state.crons.insert(spec.name.clone(), cron);
 
@@ -243,6 +464,12 @@ impl CronHandler for LocalCronHandler {
       }),
     }))
   }
+
+  fn set_virtual_clock(&self, clock: VirtualClock) {
+    self.runtime_state.borrow_mut().clock = Some(clock);
+    // The cron loop may be sleeping on the real clock.
+    self.wake.notify_one();
+  }
 }
 
 impl Drop for LocalCronHandler {
@@ -250,6 +477,9 @@ impl Drop for LocalCronHandler {
     if let Some(handle) = self.cron_loop_join_handle.take() {
       handle.abort();
     }
+    if let Some(handle) = self.control_join_handle.take() {
+      handle.abort();
+    }
   }
 }
 
@@ -321,22 +551,79 @@ impl CronHandle for CronExecutionHandle {
   }
 }
 
-fn compute_next_deadline(cron_expression: &str) -> Result<u64, CronError> {
-  let now = chrono::Utc::now();
-
+fn compute_next_deadline(
+  cron_expression: &str,
+  timezone: Option<&str>,
+  now: DateTime<Utc>,
+) -> Result<u64, CronError> {
   if let Ok(test_schedule) = env::var("DENO_CRON_TEST_SCHEDULE_OFFSET")
     && let Ok(offset) = test_schedule.parse::<u64>()
   {
     return Ok(now.timestamp_millis() as u64 + offset);
   }
 
+  next_tick_after(cron_expression, timezone, now)?.ok_or(CronError::InvalidCron)
+}
+
+/// Returns when `cron_expression` fires next after `after`, in milliseconds
+/// since the epoch, evaluating it in `timezone` (UTC by default).
+pub fn next_tick_after(
+  cron_expression: &str,
+  timezone: Option<&str>,
+  after: DateTime<Utc>,
+) -> Result<Option<u64>, CronError> {
   let cron = cron_expression
     .parse::<Schedule>()
     .map_err(|_| CronError::InvalidCron)?;
-  let Some(next_deadline) = cron.next_after(now) else {
-    return Err(CronError::InvalidCron);
+  let next = match timezone {
+    Some(timezone) => {
+      let timezone = parse_timezone(timezone)
+        .map_err(|_| CronError::InvalidTimezone(timezone.to_string()))?;
+      cron
+        .next_after(after.with_timezone(&timezone))
+        .map(|next| next.timestamp_millis())
+    }
+    None => cron.next_after(after).map(|next| next.timestamp_millis()),
   };
-  Ok(next_deadline.timestamp_millis() as u64)
+  Ok(next.map(|next| next as u64))
+}
+
+/// Returns the ticks of `spec` after `handled_until` and up to `now`, along
+/// with `failed_ticks`, that should still run under `policy`, oldest first.
+fn compute_missed_ticks(
+  spec: &CronSpec,
+  policy: CatchUpPolicy,
+  handled_until: u64,
+  failed_ticks: &[u64],
+  now: u64,
+) -> Result<VecDeque<u64>, CronError> {
+  if policy == CatchUpPolicy::Skip {
+    return Ok(VecDeque::new());
+  }
+  let mut ticks = BTreeSet::from_iter(failed_ticks.iter().copied());
+  while ticks.len() > MAX_CATCH_UP_RUNS {
+    ticks.pop_first();
+  }
+  let mut after = handled_until;
+  while let Some(after_date) = DateTime::from_timestamp_millis(after as i64)
+    && let Some(tick) = next_tick_after(
+      &spec.cron_schedule,
+      spec.timezone.as_deref(),
+      after_date,
+    )?
+    && tick <= now
+  {
+    ticks.insert(tick);
+    if ticks.len() > MAX_CATCH_UP_RUNS {
+      ticks.pop_first();
+    }
+    after = tick;
+  }
+  let mut ticks = VecDeque::from_iter(ticks);
+  if policy == CatchUpPolicy::Once && ticks.len() > 1 {
+    ticks.drain(..ticks.len() - 1);
+  }
+  Ok(ticks)
 }
 
 fn validate_backoff_schedule(
@@ -357,11 +644,161 @@ mod tests {
 
   #[test]
   fn test_compute_next_deadline() {
-    let now = chrono::Utc::now().timestamp_millis() as u64;
-    assert!(compute_next_deadline("*/1 * * * *").unwrap() > now);
-    assert!(compute_next_deadline("* * * * *").unwrap() > now);
-    assert!(compute_next_deadline("bogus").is_err());
-    assert!(compute_next_deadline("* * * * * *").is_err());
-    assert!(compute_next_deadline("* * *").is_err());
+    let now = Utc::now();
+    let now_ms = now.timestamp_millis() as u64;
+    assert!(compute_next_deadline("*/1 * * * *", None, now).unwrap() > now_ms);
+    assert!(compute_next_deadline("* * * * *", None, now).unwrap() > now_ms);
+    assert!(compute_next_deadline("bogus", None, now).is_err());
+    assert!(compute_next_deadline("* * * * * *", None, now).is_err());
+    assert!(compute_next_deadline("* * *", None, now).is_err());
+    assert!(
+      compute_next_deadline("0 9 * * *", Some("Europe/Berlin"), now).unwrap()
+        > now_ms
+    );
+    assert!(compute_next_deadline("0 9 * * *", Some("Nowhere"), now).is_err());
+  }
+
+  #[test]
+  fn test_handle_control() {
+    let (next_tx, _next_rx) = mpsc::channel::<()>(1);
+    let spec = CronSpec {
+      name: "billing".to_string(),
+      cron_schedule: "0 * * * *".to_string(),
+      backoff_schedule: None,
+      timezone: Some("Europe/Berlin".to_string()),
+    };
+    let next_deadline =
+      compute_next_deadline("0 * * * *", None, Utc::now()).unwrap();
+    let mut state = RuntimeState {
+      crons: HashMap::from([(
+        spec.name.clone(),
+        Cron {
+          spec,
+          next_tx: next_tx.downgrade(),
+          current_execution_retries: 0,
+          current_tick: Some(next_deadline),
+          missed_ticks: VecDeque::new(),
+        },
+      )]),
+      scheduled_deadlines: BTreeMap::from([(
+        next_deadline,
+        vec!["billing".to_string()],
+      )]),
+      store: None,
+      clock: None,
+    };
+
+    let ControlResponse::Crons { crons } =
+      state.handle_control(ControlRequest::List)
+    else {
+      panic!("expected a list of crons");
+    };
+    assert_eq!(
+      crons,
+      vec![CronStatus {
+        name: "billing".to_string(),
+        schedule: "0 * * * *".to_string(),
+        timezone: Some("Europe/Berlin".to_string()),
+        next_deadline: Some(next_deadline),
+        retries: 0,
+      }]
+    );
+
+    let invoke = |state: &mut RuntimeState, name: &str| {
+      state.handle_control(ControlRequest::Invoke {
+        name: name.to_string(),
+      })
+    };
+    assert_eq!(
+      invoke(&mut state, "billing"),
+      ControlResponse::Invoked {
+        name: "billing".to_string()
+      }
+    );
+    assert!(state.scheduled_deadline("billing").unwrap() < next_deadline);
+    assert_eq!(state.crons["billing"].current_tick, None);
+    assert_eq!(state.get_ready_crons().unwrap().len(), 1);
+
+    assert!(matches!(
+      invoke(&mut state, "billing"),
+      ControlResponse::Error { message } if message.contains("already running")
+    ));
+    assert!(matches!(
+      invoke(&mut state, "other"),
+      ControlResponse::Error { message } if message.contains("does not exist")
+    ));
+
+    // invoking while waiting to retry a failed tick keeps the retry state
+    let retry_deadline = next_deadline + 60_000;
+    let cron = state.crons.get_mut("billing").unwrap();
+    cron.current_tick = Some(next_deadline);
+    cron.current_execution_retries = 2;
+    state
+      .scheduled_deadlines
+      .insert(retry_deadline, vec!["billing".to_string()]);
+    assert_eq!(
+      invoke(&mut state, "billing"),
+      ControlResponse::Invoked {
+        name: "billing".to_string()
+      }
+    );
+    assert!(state.scheduled_deadline("billing").unwrap() < next_deadline);
+    assert_eq!(state.crons["billing"].current_tick, Some(next_deadline));
+    assert_eq!(state.crons["billing"].current_execution_retries, 2);
+  }
+
+  #[test]
+  fn test_compute_missed_ticks() {
+    let spec = CronSpec {
+      name: "billing".to_string(),
+      cron_schedule: "0 * * * *".to_string(),
+      backoff_schedule: None,
+      timezone: None,
+    };
+    let hour = 60 * 60 * 1_000;
+    // Last handled the 10:00 tick, and it's 13:30 now.
+    let handled_until = 1_700_000_000_000 / hour * hour;
+    let now = handled_until + 3 * hour + hour / 2;
+
+    let missed = |policy| {
+      compute_missed_ticks(&spec, policy, handled_until, &[], now)
+        .unwrap()
+        .into_iter()
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(missed(CatchUpPolicy::Skip), vec![]);
+    assert_eq!(missed(CatchUpPolicy::Once), vec![handled_until + 3 * hour]);
+    assert_eq!(
+      missed(CatchUpPolicy::All),
+      vec![
+        handled_until + hour,
+        handled_until + 2 * hour,
+        handled_until + 3 * hour
+      ]
+    );
+    assert_eq!(
+      compute_missed_ticks(&spec, CatchUpPolicy::All, now, &[], now).unwrap(),
+      VecDeque::new()
+    );
+
+    // Ticks that failed before the last successful one are caught up too.
+    let failed = [handled_until - 2 * hour, handled_until + hour];
+    let missed = |policy| {
+      compute_missed_ticks(&spec, policy, handled_until, &failed, now)
+        .unwrap()
+        .into_iter()
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(missed(CatchUpPolicy::Skip), vec![]);
+    assert_eq!(missed(CatchUpPolicy::Once), vec![handled_until + 3 * hour]);
+    assert_eq!(
+      missed(CatchUpPolicy::All),
+      vec![
+        handled_until - 2 * hour,
+        handled_until + hour,
+        handled_until + 2 * hour,
+        handled_until + 3 * hour
+      ]
+    );
   }
 }
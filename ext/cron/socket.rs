@@ -20,7 +20,7 @@ use crate::CronHandler;
 use crate::CronNextResult;
 use crate::CronSpec;
 use crate::Traceparent;
-use crate::cron::Schedule;
+use crate::validate_cron_schedule;
 
 pub struct SocketCronHandler {
   socket_addr: String,
@@ -57,6 +57,8 @@ struct CronRegistration<'a> {
   schedule: &'a str,
   #[serde(skip_serializing_if = "Option::is_none")]
   backoff_schedule: Option<&'a [u32]>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  timezone: Option<&'a str>,
 }
 
 #[derive(Deserialize)]
@@ -342,7 +344,7 @@ impl tokio::io::AsyncWrite for SocketStream {
   }
 }
 
-async fn connect_to_socket(
+pub(crate) async fn connect_to_socket(
   socket_addr: &str,
 ) -> Result<SocketStream, std::io::Error> {
   use tokio::net::TcpStream;
@@ -419,6 +421,7 @@ async fn register_cron(
     name: &spec.name,
     schedule: &spec.cron_schedule,
     backoff_schedule: spec.backoff_schedule.as_deref(),
+    timezone: spec.timezone.as_deref(),
   };
 
   let msg = OutboundMessage::Register { crons: &[cron] };
@@ -489,10 +492,7 @@ impl CronHandler for SocketCronHandler {
       return Err(CronError::RejectedError(reason.clone()));
     }
 
-    spec
-      .cron_schedule
-      .parse::<Schedule>()
-      .map_err(|_| CronError::InvalidCron)?;
+    validate_cron_schedule(&spec)?;
 
     let (invocation_tx, invocation_rx) = mpsc::channel::<Traceparent>(1);
     let socket_task_tx = self.socket_task_tx.clone();
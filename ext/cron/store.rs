@@ -0,0 +1,295 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! Persistent state for local crons.
+//!
+//! When enabled, the local handler records the last attempted and the last
+//! successful tick of every cron in a SQLite database, so that ticks missed
+//! while the process wasn't running can be caught up on the next start
+//! according to a [`CatchUpPolicy`]. Ticks that failed all of their retries
+//! are recorded separately, since a later successful tick moves the mark past
+//! them, and are caught up like missed ones.
+//!
+//! Only the main worker persists cron state. Web workers, and embedders that
+//! create their handler with `CronHandlerImpl::create_from_env`, always start
+//! from scratch.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use rusqlite::OptionalExtension;
+use rusqlite::params;
+
+/// File name of the cron state database in the origin storage directory,
+/// next to the KV database.
+pub const CRON_STATE_FILE_NAME: &str = "cron.sqlite3";
+
+/// What to do with the ticks of a cron that were missed while no process was
+/// running it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CatchUpPolicy {
+  /// Drop missed ticks and wait for the next one.
+  #[default]
+  Skip,
+  /// Run once for all missed ticks.
+  Once,
+  /// Run once for every missed tick, oldest first.
+  All,
+}
+
+impl FromStr for CatchUpPolicy {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "skip" => Ok(Self::Skip),
+      "once" => Ok(Self::Once),
+      "all" => Ok(Self::All),
+      _ => Err(()),
+    }
+  }
+}
+
+/// The recorded state of a cron. Times are the scheduled ticks in
+/// milliseconds since the epoch, not when the handler actually ran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronState {
+  /// When the cron was first registered.
+  pub since: u64,
+  pub last_attempt: Option<u64>,
+  pub last_success: Option<u64>,
+  /// Ticks that failed all of their retries, oldest first.
+  pub failed_ticks: Vec<u64>,
+}
+
+impl CronState {
+  /// Ticks after this one were not handled successfully.
+  pub fn handled_until(&self) -> u64 {
+    self.last_success.unwrap_or(self.since).max(self.since)
+  }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CronStoreError {
+  #[error(transparent)]
+  Io(#[from] std::io::Error),
+  #[error(transparent)]
+  Sqlite(#[from] rusqlite::Error),
+}
+
+pub struct CronStateStore {
+  conn: rusqlite::Connection,
+}
+
+impl CronStateStore {
+  pub fn open(path: &Path) -> Result<Self, CronStoreError> {
+    if let Some(dir) = path.parent() {
+      #[allow(
+        clippy::disallowed_methods,
+        reason = "the storage directory is always on the real fs"
+      )]
+      std::fs::create_dir_all(dir)?;
+    }
+    let conn = rusqlite::Connection::open(path)?;
+    conn.execute_batch(
+      "
+      PRAGMA journal_mode=WAL;
+      PRAGMA synchronous=NORMAL;
+      ",
+    )?;
+    Self::init(conn)
+  }
+
+  pub fn open_in_memory() -> Result<Self, CronStoreError> {
+    Self::init(rusqlite::Connection::open_in_memory()?)
+  }
+
+  fn init(conn: rusqlite::Connection) -> Result<Self, CronStoreError> {
+    conn.execute(
+      "CREATE TABLE IF NOT EXISTS cron_state (
+        name          TEXT PRIMARY KEY,
+        since         INTEGER NOT NULL,
+        last_attempt  INTEGER,
+        last_success  INTEGER
+      )",
+      (),
+    )?;
+    conn.execute(
+      "CREATE TABLE IF NOT EXISTS cron_failed_ticks (
+        name  TEXT NOT NULL,
+        tick  INTEGER NOT NULL,
+        PRIMARY KEY (name, tick)
+      )",
+      (),
+    )?;
+    Ok(Self { conn })
+  }
+
+  /// Returns the state of the cron, starting to track it at `now` if it
+  /// wasn't registered before.
+  pub fn register(
+    &self,
+    name: &str,
+    now: u64,
+  ) -> Result<CronState, CronStoreError> {
+    self.conn.execute(
+      "INSERT OR IGNORE INTO cron_state (name, since) VALUES (?1, ?2)",
+      params![name, now as i64],
+    )?;
+    Ok(self.state(name)?.expect("cron state was just inserted"))
+  }
+
+  pub fn state(&self, name: &str) -> Result<Option<CronState>, CronStoreError> {
+    let state = self
+      .conn
+      .query_row(
+        "SELECT since, last_attempt, last_success FROM cron_state
+          WHERE name = ?1",
+        params![name],
+        |row| {
+          Ok(CronState {
+            since: row.get::<_, i64>(0)? as u64,
+            last_attempt: row.get::<_, Option<i64>>(1)?.map(|t| t as u64),
+            last_success: row.get::<_, Option<i64>>(2)?.map(|t| t as u64),
+            failed_ticks: Vec::new(),
+          })
+        },
+      )
+      .optional()?;
+    let Some(mut state) = state else {
+      return Ok(None);
+    };
+    let mut stmt = self.conn.prepare(
+      "SELECT tick FROM cron_failed_ticks WHERE name = ?1 ORDER BY tick",
+    )?;
+    state.failed_ticks = stmt
+      .query_map(params![name], |row| Ok(row.get::<_, i64>(0)? as u64))?
+      .collect::<Result<_, _>>()?;
+    Ok(Some(state))
+  }
+
+  pub fn record_attempt(
+    &self,
+    name: &str,
+    tick: u64,
+  ) -> Result<(), CronStoreError> {
+    self.conn.execute(
+      "UPDATE cron_state SET last_attempt = ?2 WHERE name = ?1",
+      params![name, tick as i64],
+    )?;
+    Ok(())
+  }
+
+  pub fn record_success(
+    &self,
+    name: &str,
+    tick: u64,
+  ) -> Result<(), CronStoreError> {
+    // Catch-up runs can finish out of order with regular ones, never move
+    // the mark backwards.
+    self.conn.execute(
+      "UPDATE cron_state SET last_success = MAX(COALESCE(last_success, 0), ?2)
+        WHERE name = ?1",
+      params![name, tick as i64],
+    )?;
+    self.forget_failure(name, tick)
+  }
+
+  /// Records that `tick` failed all of its retries, so that it is caught up
+  /// even once a later tick succeeded.
+  pub fn record_failure(
+    &self,
+    name: &str,
+    tick: u64,
+  ) -> Result<(), CronStoreError> {
+    self.conn.execute(
+      "INSERT OR IGNORE INTO cron_failed_ticks (name, tick) VALUES (?1, ?2)",
+      params![name, tick as i64],
+    )?;
+    Ok(())
+  }
+
+  /// Stops tracking a failed tick, ex. because the catch-up policy dropped
+  /// it.
+  pub fn forget_failure(
+    &self,
+    name: &str,
+    tick: u64,
+  ) -> Result<(), CronStoreError> {
+    self.conn.execute(
+      "DELETE FROM cron_failed_ticks WHERE name = ?1 AND tick = ?2",
+      params![name, tick as i64],
+    )?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn records_ticks() {
+    let store = CronStateStore::open_in_memory().unwrap();
+    let state = store.register("billing", 1_000).unwrap();
+    assert_eq!(
+      state,
+      CronState {
+        since: 1_000,
+        last_attempt: None,
+        last_success: None,
+        failed_ticks: Vec::new(),
+      }
+    );
+    assert_eq!(state.handled_until(), 1_000);
+
+    store.record_attempt("billing", 2_000).unwrap();
+    store.record_success("billing", 2_000).unwrap();
+    store.record_attempt("billing", 3_000).unwrap();
+    // Registering again keeps the recorded state.
+    let state = store.register("billing", 4_000).unwrap();
+    assert_eq!(
+      state,
+      CronState {
+        since: 1_000,
+        last_attempt: Some(3_000),
+        last_success: Some(2_000),
+        failed_ticks: Vec::new(),
+      }
+    );
+    assert_eq!(state.handled_until(), 2_000);
+
+    store.record_success("billing", 1_500).unwrap();
+    assert_eq!(
+      store.state("billing").unwrap().unwrap().last_success,
+      Some(2_000)
+    );
+    assert_eq!(store.state("other").unwrap(), None);
+  }
+
+  #[test]
+  fn keeps_failed_ticks_past_the_mark() {
+    let store = CronStateStore::open_in_memory().unwrap();
+    store.register("billing", 1_000).unwrap();
+    store.record_failure("billing", 3_000).unwrap();
+    store.record_failure("billing", 2_000).unwrap();
+    store.record_success("billing", 4_000).unwrap();
+    let state = store.state("billing").unwrap().unwrap();
+    assert_eq!(state.handled_until(), 4_000);
+    assert_eq!(state.failed_ticks, vec![2_000, 3_000]);
+
+    // a successful catch-up run and a dropped tick are no longer tracked
+    store.record_success("billing", 2_000).unwrap();
+    store.forget_failure("billing", 3_000).unwrap();
+    let state = store.state("billing").unwrap().unwrap();
+    assert_eq!(state.last_success, Some(4_000));
+    assert_eq!(state.failed_ticks, Vec::<u64>::new());
+  }
+
+  #[test]
+  fn parses_catch_up_policy() {
+    assert_eq!("skip".parse(), Ok(CatchUpPolicy::Skip));
+    assert_eq!("once".parse(), Ok(CatchUpPolicy::Once));
+    assert_eq!("all".parse(), Ok(CatchUpPolicy::All));
+    assert_eq!("every".parse::<CatchUpPolicy>(), Err(()));
+  }
+}
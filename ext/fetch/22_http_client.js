@@ -90,6 +90,11 @@ function createHttpClient(options) {
       proxy.transport = "http";
     }
   }
+  if (options.cache === true) {
+    options.cache = {};
+  } else if (!options.cache) {
+    options.cache = undefined;
+  }
   const keyPair = loadTlsKeyPair("Deno.createHttpClient", options);
   return new HttpClient(
     op_fetch_custom_client(
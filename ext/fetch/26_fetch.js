@@ -461,6 +461,7 @@ async function mainFetch(req, recursive, terminator, inspectorCtx = null) {
     reqBody !== null || reqRid !== null,
     reqBody,
     reqRid,
+    req.cacheMode,
   );
 
   // ---- Inspector: Network.requestWillBeSent ------------------------------
@@ -10,6 +10,10 @@ use std::task::Poll;
 use std::task::{self};
 use std::vec;
 
+use deno_net::dns::DnsConfigError;
+use deno_net::dns::DnsResolver;
+use deno_net::dns::DnsResolverOptions;
+use deno_net::dns::DnsTlsOptions;
 use deno_permissions::PermissionsContainer;
 use hickory_resolver::name_server::TokioConnectionProvider;
 use http::Uri;
@@ -89,6 +93,27 @@ impl Resolver {
       kind: ResolverKind::Custom(resolver),
     }
   }
+
+  /// Create a resolver querying the name servers of `options`, or the
+  /// system resolver if there are none, with its host overrides applied.
+  /// Name servers queried over TLS are verified with `tls`.
+  pub fn from_options(
+    options: DnsResolverOptions,
+    tls: DnsTlsOptions,
+  ) -> Result<Self, DnsConfigError> {
+    Ok(Self::custom(Arc::new(DnsResolver::new(options, tls)?)))
+  }
+}
+
+impl Resolve for DnsResolver {
+  fn resolve(&self, name: Name) -> Resolving {
+    let resolver = self.clone();
+    Box::pin(async move {
+      let addrs =
+        DnsResolver::resolve_addr(Some(&resolver), name.as_str(), 0).await?;
+      Ok(addrs.into_iter())
+    })
+  }
 }
 
 type SocketAddrs = vec::IntoIter<SocketAddr>;
@@ -406,6 +431,27 @@ mod tests {
     }
   }
 
+  #[tokio::test]
+  async fn dns_resolver_from_options() {
+    let options = DnsResolverOptions {
+      hosts: [(
+        "api.internal".to_string(),
+        vec!["10.1.2.3".parse().unwrap()],
+      )]
+      .into_iter()
+      .collect(),
+      ..Default::default()
+    };
+    let mut resolver =
+      Resolver::from_options(options, DnsTlsOptions::default()).unwrap();
+    let addrs = resolver
+      .call(Name::from_str("api.internal").unwrap())
+      .await
+      .unwrap()
+      .collect::<Vec<_>>();
+    assert_eq!(addrs, vec!["10.1.2.3:0".parse().unwrap()]);
+  }
+
   #[tokio::test]
   async fn custom_dns_resolver() {
     let mut resolver = Resolver::custom(Arc::new(DebugResolver(
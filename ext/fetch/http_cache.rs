@@ -0,0 +1,1312 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! A private HTTP cache, as described by RFC 9111, for `Deno.HttpClient`.
+//!
+//! Only `GET` responses are stored. A stored response is reused while it is
+//! fresh, and revalidated with `If-None-Match`/`If-Modified-Since` once it is
+//! stale. During its `stale-while-revalidate` window a stale response is
+//! served right away while it is revalidated in the background. Responses
+//! are selected with the request headers named by their `Vary` header, and a
+//! successful unsafe request (e.g. `POST`) invalidates the responses stored
+//! for its URL. How the cache is consulted for a request is decided by its
+//! [`RequestCacheMode`].
+//!
+//! Entries are kept in memory, or as one file per URL in a directory, up to a
+//! maximum total size past which the least recently used ones are evicted.
+//! Files are read and written on blocking threads.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::ready;
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use deno_cache::get_header;
+use deno_cache::get_headers_from_vary_header;
+use deno_cache::vary_header_matches;
+use deno_core::convert::ByteString;
+use deno_error::JsErrorBox;
+use http::HeaderMap;
+use http::Method;
+use http::StatusCode;
+use http::header::AGE;
+use http::header::CACHE_CONTROL;
+use http::header::CONTENT_LENGTH;
+use http::header::ETAG;
+use http::header::EXPIRES;
+use http::header::HeaderName;
+use http::header::HeaderValue;
+use http::header::IF_MATCH;
+use http::header::IF_MODIFIED_SINCE;
+use http::header::IF_NONE_MATCH;
+use http::header::IF_RANGE;
+use http::header::IF_UNMODIFIED_SINCE;
+use http::header::LAST_MODIFIED;
+use http::header::PRAGMA;
+use http::header::RANGE;
+use http::header::VARY;
+use http_body_util::BodyExt;
+use http_body_util::combinators::BoxBody;
+use hyper::body::Body;
+use hyper::body::Frame;
+use hyper::body::SizeHint;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use tokio::task::JoinHandle;
+use tokio::task::spawn_blocking;
+
+use crate::BodyDecoded;
+use crate::Client;
+use crate::FetchError;
+use crate::ReqBody;
+use crate::ResBody;
+
+/// The default maximum size of an [`HttpCache`], in bytes.
+pub const DEFAULT_HTTP_CACHE_MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Heuristic freshness is 10% of the time since `Last-Modified`
+/// (RFC 9111 section 4.2.2), but never more than a day.
+const MAX_HEURISTIC_FRESHNESS: u64 = 24 * 60 * 60;
+
+/// The `cache` mode of a `Request`, attached to it as an extension. See
+/// <https://fetch.spec.whatwg.org/#concept-request-cache-mode>.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RequestCacheMode {
+  #[default]
+  Default,
+  NoStore,
+  Reload,
+  NoCache,
+  ForceCache,
+  OnlyIfCached,
+}
+
+impl RequestCacheMode {
+  pub fn parse(mode: &str) -> Option<Self> {
+    Some(match mode {
+      "default" => Self::Default,
+      "no-store" => Self::NoStore,
+      "reload" => Self::Reload,
+      "no-cache" => Self::NoCache,
+      "force-cache" => Self::ForceCache,
+      "only-if-cached" => Self::OnlyIfCached,
+      _ => return None,
+    })
+  }
+}
+
+/// The directives of the `Cache-Control` headers of a request or response.
+#[derive(Debug, Default)]
+struct CacheControl {
+  no_store: bool,
+  no_cache: bool,
+  must_revalidate: bool,
+  max_age: Option<u64>,
+  /// `Some(None)` for `max-stale` without a value, which accepts any
+  /// staleness.
+  max_stale: Option<Option<u64>>,
+  min_fresh: Option<u64>,
+  stale_while_revalidate: Option<u64>,
+}
+
+impl CacheControl {
+  fn parse<'a>(values: impl Iterator<Item = &'a str>) -> Self {
+    let mut cache_control = Self::default();
+    for directive in values.flat_map(|value| value.split(',')) {
+      let (name, value) = match directive.split_once('=') {
+        Some((name, value)) => {
+          (name.trim(), Some(value.trim().trim_matches('"')))
+        }
+        None => (directive.trim(), None),
+      };
+      // Values too large to parse are capped, as RFC 9111 section 1.2.2
+      // requires.
+      let seconds = value.and_then(|value| {
+        if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+          return None;
+        }
+        Some(value.parse::<u64>().unwrap_or(u64::MAX))
+      });
+      match name.to_ascii_lowercase().as_str() {
+        "no-store" => cache_control.no_store = true,
+        "no-cache" => cache_control.no_cache = true,
+        "must-revalidate" => cache_control.must_revalidate = true,
+        "max-age" => cache_control.max_age = seconds,
+        "max-stale" => cache_control.max_stale = Some(seconds),
+        "min-fresh" => cache_control.min_fresh = seconds,
+        "stale-while-revalidate" => {
+          cache_control.stale_while_revalidate = seconds
+        }
+        _ => {}
+      }
+    }
+    cache_control
+  }
+
+  fn from_request(headers: &HeaderMap) -> Self {
+    let mut cache_control = Self::parse(header_strs(headers, &CACHE_CONTROL));
+    // `Pragma: no-cache` only counts without `Cache-Control`
+    // (RFC 9111 section 5.4).
+    if !headers.contains_key(CACHE_CONTROL)
+      && header_strs(headers, &PRAGMA)
+        .any(|value| value.trim().eq_ignore_ascii_case("no-cache"))
+    {
+      cache_control.no_cache = true;
+    }
+    cache_control
+  }
+}
+
+fn header_strs<'a>(
+  headers: &'a HeaderMap,
+  name: &HeaderName,
+) -> impl Iterator<Item = &'a str> {
+  headers
+    .get_all(name)
+    .into_iter()
+    .filter_map(|value| value.to_str().ok())
+}
+
+fn header_list(headers: &HeaderMap) -> Vec<(ByteString, ByteString)> {
+  headers
+    .iter()
+    .map(|(name, value)| (name.as_str().into(), value.as_bytes().into()))
+    .collect()
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+  time
+    .duration_since(SystemTime::UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_secs()
+}
+
+fn parse_http_date(value: &str) -> Option<u64> {
+  httpdate::parse_http_date(value.trim())
+    .ok()
+    .map(unix_seconds)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredResponse {
+  /// The request headers named by the `Vary` header of the response.
+  request_headers: Vec<(String, String)>,
+  status: u16,
+  headers: Vec<(String, String)>,
+  #[serde(with = "base64_body")]
+  body: Bytes,
+  /// Whether `body` was decompressed. `headers` still describe the encoded
+  /// body in that case.
+  body_decoded: bool,
+  /// When the response was received, in seconds since the epoch.
+  response_time: u64,
+  /// The age of the response when it was received, in seconds.
+  initial_age: u64,
+}
+
+mod base64_body {
+  use base64::Engine;
+  use base64::prelude::BASE64_STANDARD;
+  use bytes::Bytes;
+  use serde::Deserialize;
+  use serde::Deserializer;
+  use serde::Serializer;
+
+  pub fn serialize<S: Serializer>(
+    body: &Bytes,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&BASE64_STANDARD.encode(body))
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+  ) -> Result<Bytes, D::Error> {
+    let body = String::deserialize(deserializer)?;
+    BASE64_STANDARD
+      .decode(body)
+      .map(Bytes::from)
+      .map_err(serde::de::Error::custom)
+  }
+}
+
+impl StoredResponse {
+  /// Returns `None` if the response can't be stored.
+  fn new(
+    request_headers: &HeaderMap,
+    response: &http::response::Parts,
+    request_time: u64,
+    response_time: u64,
+  ) -> Option<Self> {
+    let status = response.status;
+    if status.is_informational()
+      || status == StatusCode::PARTIAL_CONTENT
+      || status == StatusCode::NOT_MODIFIED
+    {
+      return None;
+    }
+    let cache_control =
+      CacheControl::parse(header_strs(&response.headers, &CACHE_CONTROL));
+    if cache_control.no_store {
+      return None;
+    }
+    // A response that can be neither reused without revalidation nor
+    // revalidated is not worth storing.
+    let has_validator = response.headers.contains_key(ETAG)
+      || response.headers.contains_key(LAST_MODIFIED);
+    if cache_control.max_age.is_none()
+      && !response.headers.contains_key(EXPIRES)
+      && !has_validator
+    {
+      return None;
+    }
+
+    let mut stored_request_headers = vec![];
+    for vary in header_strs(&response.headers, &VARY) {
+      for name in get_headers_from_vary_header(vary) {
+        if name == "*" {
+          return None;
+        }
+        if name.is_empty() {
+          continue;
+        }
+        for value in header_strs(request_headers, &name.parse().ok()?) {
+          stored_request_headers.push((name.clone(), value.to_string()));
+        }
+      }
+    }
+
+    let headers = response
+      .headers
+      .iter()
+      .map(|(name, value)| {
+        (
+          name.to_string(),
+          String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        )
+      })
+      .collect();
+    let mut stored = Self {
+      request_headers: stored_request_headers,
+      status: status.as_u16(),
+      headers,
+      body: Bytes::new(),
+      body_decoded: response.extensions.get::<BodyDecoded>().is_some(),
+      response_time,
+      initial_age: 0,
+    };
+    stored.initial_age = stored.corrected_initial_age(request_time);
+    Some(stored)
+  }
+
+  fn header(&self, name: &str) -> Option<&str> {
+    self
+      .headers
+      .iter()
+      .find(|(n, _)| n.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  }
+
+  fn cache_control(&self) -> CacheControl {
+    CacheControl::parse(
+      self
+        .headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("cache-control"))
+        .map(|(_, value)| value.as_str()),
+    )
+  }
+
+  fn date(&self) -> u64 {
+    self
+      .header("date")
+      .and_then(parse_http_date)
+      .unwrap_or(self.response_time)
+  }
+
+  /// RFC 9111 section 4.2.3.
+  fn corrected_initial_age(&self, request_time: u64) -> u64 {
+    let apparent_age = self.response_time.saturating_sub(self.date());
+    let age_value = self
+      .header("age")
+      .and_then(|age| age.trim().parse::<u64>().ok())
+      .unwrap_or(0);
+    let response_delay = self.response_time.saturating_sub(request_time);
+    apparent_age.max(age_value.saturating_add(response_delay))
+  }
+
+  fn current_age(&self, now: u64) -> u64 {
+    self
+      .initial_age
+      .saturating_add(now.saturating_sub(self.response_time))
+  }
+
+  /// RFC 9111 section 4.2.1.
+  fn freshness_lifetime(&self, cache_control: &CacheControl) -> u64 {
+    if let Some(max_age) = cache_control.max_age {
+      return max_age;
+    }
+    if let Some(expires) = self.header("expires") {
+      // An invalid `Expires` means the response is already stale.
+      return parse_http_date(expires)
+        .map(|expires| expires.saturating_sub(self.date()))
+        .unwrap_or(0);
+    }
+    match self.header("last-modified").and_then(parse_http_date) {
+      Some(last_modified) => (self.date().saturating_sub(last_modified) / 10)
+        .min(MAX_HEURISTIC_FRESHNESS),
+      None => 0,
+    }
+  }
+
+  fn variant_matches(
+    &self,
+    request_headers: &[(ByteString, ByteString)],
+  ) -> bool {
+    let headers = self
+      .headers
+      .iter()
+      .map(|(name, value)| (name.as_str().into(), value.as_str().into()))
+      .collect::<Vec<_>>();
+    let Some(vary) = get_header("vary", &headers) else {
+      return true;
+    };
+    let stored_request_headers = self
+      .request_headers
+      .iter()
+      .map(|(name, value)| (name.as_str().into(), value.as_str().into()))
+      .collect::<Vec<_>>();
+    vary_header_matches(&vary, request_headers, &stored_request_headers)
+  }
+
+  /// Updates the stored headers with those of a `304 Not Modified` response
+  /// (RFC 9111 section 4.3.4).
+  fn freshen(
+    &mut self,
+    headers: &HeaderMap,
+    request_time: u64,
+    response_time: u64,
+  ) {
+    for name in headers.keys() {
+      if name == CONTENT_LENGTH {
+        continue;
+      }
+      self
+        .headers
+        .retain(|(n, _)| !n.eq_ignore_ascii_case(name.as_str()));
+      for value in headers.get_all(name) {
+        self.headers.push((
+          name.to_string(),
+          String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        ));
+      }
+    }
+    self.response_time = response_time;
+    self.initial_age = self.corrected_initial_age(request_time);
+  }
+
+  fn size(&self) -> u64 {
+    let headers = self
+      .headers
+      .iter()
+      .chain(&self.request_headers)
+      .map(|(name, value)| name.len() + value.len())
+      .sum::<usize>();
+    (headers + self.body.len()) as u64
+  }
+
+  fn to_response(&self, now: u64) -> http::Response<ResBody> {
+    let body = http_body_util::Full::new(self.body.clone())
+      .map_err(|never| match never {})
+      .boxed();
+    let mut res = http::Response::new(body);
+    *res.status_mut() =
+      StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+    let headers = res.headers_mut();
+    for (name, value) in &self.headers {
+      if let (Ok(name), Ok(value)) = (
+        HeaderName::from_bytes(name.as_bytes()),
+        HeaderValue::from_str(value),
+      ) {
+        headers.append(name, value);
+      }
+    }
+    headers.insert(AGE, HeaderValue::from(self.current_age(now)));
+    if self.body_decoded {
+      res.extensions_mut().insert(BodyDecoded);
+    }
+    res
+  }
+}
+
+/// How a stored response can be used for a request.
+enum Reuse {
+  /// Serve it.
+  Fresh,
+  /// Serve it, and revalidate it in the background.
+  StaleWhileRevalidate,
+  /// Revalidate it first.
+  Revalidate,
+}
+
+fn reuse(
+  stored: &StoredResponse,
+  request: &CacheControl,
+  mode: RequestCacheMode,
+  now: u64,
+) -> Reuse {
+  match mode {
+    RequestCacheMode::ForceCache | RequestCacheMode::OnlyIfCached => {
+      return Reuse::Fresh;
+    }
+    RequestCacheMode::NoCache => return Reuse::Revalidate,
+    _ => {}
+  }
+  let response = stored.cache_control();
+  if request.no_cache || response.no_cache {
+    return Reuse::Revalidate;
+  }
+  let lifetime = stored.freshness_lifetime(&response);
+  let age = stored.current_age(now);
+  if request.max_age.is_some_and(|max_age| age > max_age) {
+    return Reuse::Revalidate;
+  }
+  if age < lifetime {
+    let min_fresh = request.min_fresh.unwrap_or(0);
+    return if lifetime - age >= min_fresh {
+      Reuse::Fresh
+    } else {
+      Reuse::Revalidate
+    };
+  }
+  if response.must_revalidate {
+    return Reuse::Revalidate;
+  }
+  let staleness = age - lifetime;
+  match request.max_stale {
+    Some(None) => return Reuse::Fresh,
+    Some(Some(max_stale)) if staleness <= max_stale => return Reuse::Fresh,
+    _ => {}
+  }
+  match response.stale_while_revalidate {
+    Some(window) if staleness <= window => Reuse::StaleWhileRevalidate,
+    _ => Reuse::Revalidate,
+  }
+}
+
+#[derive(Debug)]
+enum Store {
+  Memory(Mutex<MemoryStore>),
+  Disk(Arc<DiskStore>),
+}
+
+#[derive(Debug, Default)]
+struct MemoryStore {
+  entries: HashMap<String, MemoryEntry>,
+  usage: u64,
+  /// Incremented on every access, to find the least recently used entries.
+  clock: u64,
+  /// The keys of the entries by when they were last used.
+  by_last_use: BTreeMap<u64, String>,
+}
+
+#[derive(Debug)]
+struct MemoryEntry {
+  responses: Vec<StoredResponse>,
+  size: u64,
+  last_used: u64,
+}
+
+impl MemoryStore {
+  fn get(&mut self, key: &str) -> Vec<StoredResponse> {
+    self.clock += 1;
+    let clock = self.clock;
+    match self.entries.get_mut(key) {
+      Some(entry) => {
+        self.by_last_use.remove(&entry.last_used);
+        self.by_last_use.insert(clock, key.to_string());
+        entry.last_used = clock;
+        entry.responses.clone()
+      }
+      None => vec![],
+    }
+  }
+
+  fn insert(&mut self, key: &str, stored: StoredResponse, max_size: u64) {
+    let mut responses = match self.remove(key) {
+      Some(entry) => entry.responses,
+      None => vec![],
+    };
+    replace_variant(&mut responses, stored);
+    let size = responses.iter().map(StoredResponse::size).sum::<u64>();
+    if size > max_size {
+      return;
+    }
+    while self.usage + size > max_size
+      && let Some((_, oldest)) = self.by_last_use.pop_first()
+    {
+      self.remove(&oldest);
+    }
+    self.clock += 1;
+    self.usage += size;
+    self.by_last_use.insert(self.clock, key.to_string());
+    self.entries.insert(
+      key.to_string(),
+      MemoryEntry {
+        responses,
+        size,
+        last_used: self.clock,
+      },
+    );
+  }
+
+  fn remove(&mut self, key: &str) -> Option<MemoryEntry> {
+    let entry = self.entries.remove(key)?;
+    self.by_last_use.remove(&entry.last_used);
+    self.usage -= entry.size;
+    Some(entry)
+  }
+}
+
+/// Stores the responses of each URL in a file named after the hash of the
+/// URL. Files are only touched from blocking threads.
+#[derive(Debug)]
+struct DiskStore {
+  dir: PathBuf,
+  /// Serializes the updates of the file of a URL within this process.
+  locks: Mutex<HashMap<String, Arc<Mutex<()>>>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DiskEntry {
+  url: String,
+  responses: Vec<StoredResponse>,
+}
+
+#[allow(
+  clippy::disallowed_methods,
+  reason = "the HTTP cache directory is always on the real fs"
+)]
+impl DiskStore {
+  fn path(&self, key: &str) -> PathBuf {
+    self
+      .dir
+      .join(format!("{:x}.json", sha2::Sha256::digest(key.as_bytes())))
+  }
+
+  fn read(&self, key: &str) -> Vec<StoredResponse> {
+    std::fs::read(self.path(key))
+      .ok()
+      .and_then(|data| serde_json::from_slice::<DiskEntry>(&data).ok())
+      .filter(|entry| entry.url == key)
+      .map(|entry| entry.responses)
+      .unwrap_or_default()
+  }
+
+  fn get(&self, key: &str) -> Vec<StoredResponse> {
+    let responses = self.read(key);
+    if !responses.is_empty() {
+      // The modification time orders the files for eviction.
+      let _ = std::fs::File::options()
+        .append(true)
+        .open(self.path(key))
+        .and_then(|file| file.set_modified(SystemTime::now()));
+    }
+    responses
+  }
+
+  fn insert(&self, key: &str, stored: StoredResponse, max_size: u64) {
+    self.with_lock(key, || {
+      let mut responses = self.read(key);
+      replace_variant(&mut responses, stored);
+      let entry = DiskEntry {
+        url: key.to_string(),
+        responses,
+      };
+      let Ok(data) = serde_json::to_vec(&entry) else {
+        return;
+      };
+      let path = self.path(key);
+      if data.len() as u64 > max_size {
+        let _ = std::fs::remove_file(&path);
+        return;
+      }
+      // Write to a temporary file first, so that concurrent readers never
+      // see a partially written entry.
+      let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+      if std::fs::write(&tmp_path, data).is_err()
+        || std::fs::rename(&tmp_path, &path).is_err()
+      {
+        let _ = std::fs::remove_file(&tmp_path);
+      }
+    });
+    self.evict(max_size);
+  }
+
+  fn remove(&self, key: &str) {
+    self.with_lock(key, || {
+      let _ = std::fs::remove_file(self.path(key));
+    });
+  }
+
+  /// Removes the least recently used files until the directory holds at most
+  /// `max_size` bytes of entries.
+  fn evict(&self, max_size: u64) {
+    let Ok(read_dir) = std::fs::read_dir(&self.dir) else {
+      return;
+    };
+    let mut files = read_dir
+      .filter_map(|entry| {
+        let entry = entry.ok()?;
+        let path = entry.path();
+        if path.extension()? != "json" {
+          return None;
+        }
+        let metadata = entry.metadata().ok()?;
+        Some((metadata.modified().ok()?, metadata.len(), path))
+      })
+      .collect::<Vec<_>>();
+    let mut usage = files.iter().map(|(_, len, _)| len).sum::<u64>();
+    if usage <= max_size {
+      return;
+    }
+    files.sort();
+    for (_, len, path) in files {
+      if usage <= max_size {
+        break;
+      }
+      if std::fs::remove_file(path).is_ok() {
+        usage -= len;
+      }
+    }
+  }
+
+  fn with_lock<R>(&self, key: &str, f: impl FnOnce() -> R) -> R {
+    let lock = self
+      .locks
+      .lock()
+      .unwrap()
+      .entry(key.to_string())
+      .or_default()
+      .clone();
+    let result = {
+      let _guard = lock.lock().unwrap();
+      f()
+    };
+    let mut locks = self.locks.lock().unwrap();
+    if Arc::strong_count(&lock) == 2 {
+      locks.remove(key);
+    }
+    result
+  }
+}
+
+/// Adds `stored` to `responses`, replacing the response stored for the same
+/// variant.
+fn replace_variant(
+  responses: &mut Vec<StoredResponse>,
+  stored: StoredResponse,
+) {
+  let request_headers = stored
+    .request_headers
+    .iter()
+    .map(|(name, value)| (name.as_str().into(), value.as_str().into()))
+    .collect::<Vec<_>>();
+  responses.retain(|other| !other.variant_matches(&request_headers));
+  responses.push(stored);
+}
+
+/// A private HTTP cache shared by the clones of a [`Client`].
+#[derive(Debug)]
+pub struct HttpCache {
+  store: Store,
+  max_size: u64,
+  /// The URLs revalidated in the background, which are only revalidated once
+  /// at a time.
+  revalidating: Mutex<HashSet<String>>,
+}
+
+impl HttpCache {
+  /// Creates a cache that keeps up to `max_size` bytes of responses in
+  /// memory, evicting the least recently used ones.
+  pub fn in_memory(max_size: u64) -> Self {
+    Self {
+      store: Store::Memory(Default::default()),
+      max_size,
+      revalidating: Default::default(),
+    }
+  }
+
+  /// Creates a cache that keeps up to `max_size` bytes of responses in the
+  /// `path` directory, which is created if it does not exist. The least
+  /// recently used responses are evicted to make room for new ones.
+  #[allow(
+    clippy::disallowed_methods,
+    reason = "the HTTP cache directory is always on the real fs"
+  )]
+  pub fn on_disk(path: PathBuf, max_size: u64) -> std::io::Result<Self> {
+    std::fs::create_dir_all(&path)?;
+    Ok(Self {
+      store: Store::Disk(Arc::new(DiskStore {
+        dir: path,
+        locks: Default::default(),
+      })),
+      max_size,
+      revalidating: Default::default(),
+    })
+  }
+
+  pub(crate) async fn fetch(
+    self: Arc<Self>,
+    client: Client,
+    req: http::Request<ReqBody>,
+  ) -> Result<http::Response<ResBody>, FetchError> {
+    let key = req.uri().to_string();
+    if req.method() != Method::GET {
+      let invalidates = !req.method().is_safe();
+      let res = client.fetch_uncached(req).await?;
+      if invalidates
+        && (res.status().is_success() || res.status().is_redirection())
+      {
+        self.remove(&key).await;
+      }
+      return Ok(res);
+    }
+
+    let mode = effective_mode(&req);
+    if mode == RequestCacheMode::NoStore {
+      return client.fetch_uncached(req).await;
+    }
+
+    let cache_control = CacheControl::from_request(req.headers());
+    let stored = match mode {
+      RequestCacheMode::Reload => None,
+      _ => self.lookup(&key, &header_list(req.headers())).await,
+    };
+    let Some(stored) = stored else {
+      if mode == RequestCacheMode::OnlyIfCached {
+        return Err(FetchError::NetworkError);
+      }
+      return self.fetch_and_store(client, req, key, &cache_control).await;
+    };
+
+    let now = unix_seconds(SystemTime::now());
+    match reuse(&stored, &cache_control, mode, now) {
+      Reuse::Fresh => Ok(stored.to_response(now)),
+      Reuse::StaleWhileRevalidate => {
+        let revalidation = http::Request::builder()
+          .method(Method::GET)
+          .uri(req.uri().clone())
+          .body(ReqBody::empty())
+          .map(|mut revalidation| {
+            *revalidation.headers_mut() = req.headers().clone();
+            revalidation
+          });
+        if let Ok(revalidation) = revalidation
+          && self.revalidating.lock().unwrap().insert(key.clone())
+        {
+          let stored = stored.clone();
+          tokio::spawn(async move {
+            let _guard = RevalidationGuard(self.clone(), key.clone());
+            let res = self
+              .revalidate(client, revalidation, key, stored, &cache_control)
+              .await;
+            // The response is stored once its body has been read.
+            if let Ok(res) = res {
+              let _ = res.into_body().collect().await;
+            }
+          });
+        }
+        Ok(stored.to_response(now))
+      }
+      Reuse::Revalidate => {
+        self
+          .revalidate(client, req, key, stored, &cache_control)
+          .await
+      }
+    }
+  }
+
+  async fn revalidate(
+    self: Arc<Self>,
+    client: Client,
+    mut req: http::Request<ReqBody>,
+    key: String,
+    mut stored: StoredResponse,
+    cache_control: &CacheControl,
+  ) -> Result<http::Response<ResBody>, FetchError> {
+    let validators =
+      [(ETAG, IF_NONE_MATCH), (LAST_MODIFIED, IF_MODIFIED_SINCE)];
+    for (validator, condition) in validators {
+      if let Some(value) = stored
+        .header(validator.as_str())
+        .and_then(|value| HeaderValue::from_str(value).ok())
+      {
+        req.headers_mut().insert(condition, value);
+      }
+    }
+
+    let request_headers = req.headers().clone();
+    let request_time = unix_seconds(SystemTime::now());
+    let res = client.fetch_uncached(req).await?;
+    if res.status() != StatusCode::NOT_MODIFIED {
+      return Ok(self.store_response(
+        res,
+        key,
+        &request_headers,
+        request_time,
+        cache_control,
+      ));
+    }
+
+    let now = unix_seconds(SystemTime::now());
+    stored.freshen(res.headers(), request_time, now);
+    let response = stored.to_response(now);
+    if let Some(write) = self.insert(key, stored) {
+      let _ = write.await;
+    }
+    Ok(response)
+  }
+
+  async fn fetch_and_store(
+    self: Arc<Self>,
+    client: Client,
+    req: http::Request<ReqBody>,
+    key: String,
+    cache_control: &CacheControl,
+  ) -> Result<http::Response<ResBody>, FetchError> {
+    let request_headers = req.headers().clone();
+    let request_time = unix_seconds(SystemTime::now());
+    let res = client.fetch_uncached(req).await?;
+    Ok(self.store_response(
+      res,
+      key,
+      &request_headers,
+      request_time,
+      cache_control,
+    ))
+  }
+
+  /// Returns `res`, storing it once its body has been read if it can be
+  /// stored.
+  fn store_response(
+    self: Arc<Self>,
+    res: http::Response<ResBody>,
+    key: String,
+    request_headers: &HeaderMap,
+    request_time: u64,
+    cache_control: &CacheControl,
+  ) -> http::Response<ResBody> {
+    if cache_control.no_store {
+      return res;
+    }
+    let (parts, body) = res.into_parts();
+    let response_time = unix_seconds(SystemTime::now());
+    let Some(stored) =
+      StoredResponse::new(request_headers, &parts, request_time, response_time)
+    else {
+      return http::Response::from_parts(parts, body);
+    };
+    let body = CachingBody {
+      inner: body,
+      received: Some(Vec::new()),
+      pending: Some((self, key, stored)),
+    };
+    http::Response::from_parts(parts, BoxBody::new(body))
+  }
+
+  async fn lookup(
+    &self,
+    key: &str,
+    request_headers: &[(ByteString, ByteString)],
+  ) -> Option<StoredResponse> {
+    let responses = match &self.store {
+      Store::Memory(store) => store.lock().unwrap().get(key),
+      Store::Disk(store) => {
+        let store = store.clone();
+        let key = key.to_string();
+        spawn_blocking(move || store.get(&key))
+          .await
+          .unwrap_or_default()
+      }
+    };
+    responses
+      .into_iter()
+      .rev()
+      .find(|stored| stored.variant_matches(request_headers))
+  }
+
+  /// Stores `stored`, replacing the response stored for the same variant.
+  /// Returns the pending write of a disk store, which doesn't need to be
+  /// awaited. Outside of a runtime, e.g. when a body is dropped on shutdown,
+  /// the file is written right away.
+  fn insert(
+    &self,
+    key: String,
+    stored: StoredResponse,
+  ) -> Option<JoinHandle<()>> {
+    match &self.store {
+      Store::Memory(store) => {
+        store.lock().unwrap().insert(&key, stored, self.max_size);
+        None
+      }
+      Store::Disk(store) => {
+        let store = store.clone();
+        let max_size = self.max_size;
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+          store.insert(&key, stored, max_size);
+          return None;
+        };
+        Some(
+          runtime.spawn_blocking(move || store.insert(&key, stored, max_size)),
+        )
+      }
+    }
+  }
+
+  async fn remove(&self, key: &str) {
+    match &self.store {
+      Store::Memory(store) => {
+        store.lock().unwrap().remove(key);
+      }
+      Store::Disk(store) => {
+        let store = store.clone();
+        let key = key.to_string();
+        let _ = spawn_blocking(move || store.remove(&key)).await;
+      }
+    }
+  }
+}
+
+/// Lets the URL it holds be revalidated in the background again once
+/// dropped.
+struct RevalidationGuard(Arc<HttpCache>, String);
+
+impl Drop for RevalidationGuard {
+  fn drop(&mut self) {
+    self.0.revalidating.lock().unwrap().remove(&self.1);
+  }
+}
+
+/// The cache mode actually used for `req`.
+fn effective_mode(req: &http::Request<ReqBody>) -> RequestCacheMode {
+  let mode = req
+    .extensions()
+    .get::<RequestCacheMode>()
+    .copied()
+    .unwrap_or_default();
+  let headers = req.headers();
+  // Range requests are passed through, as partial responses are not stored.
+  if headers.contains_key(RANGE) {
+    return RequestCacheMode::NoStore;
+  }
+  // Conditional requests made by the caller are its own business
+  // (https://fetch.spec.whatwg.org/#http-network-or-cache-fetch, step 8.17).
+  let conditional = [
+    IF_MODIFIED_SINCE,
+    IF_NONE_MATCH,
+    IF_UNMODIFIED_SINCE,
+    IF_MATCH,
+    IF_RANGE,
+  ]
+  .iter()
+  .any(|name| headers.contains_key(name));
+  if mode == RequestCacheMode::Default && conditional {
+    return RequestCacheMode::NoStore;
+  }
+  mode
+}
+
+/// Passes a response body through, and stores the response once the body
+/// has been read. Bodies larger than the cache's `max_size` are not stored.
+struct CachingBody {
+  inner: ResBody,
+  /// `None` once the body has grown past the limit.
+  received: Option<Vec<u8>>,
+  pending: Option<(Arc<HttpCache>, String, StoredResponse)>,
+}
+
+impl Body for CachingBody {
+  type Data = Bytes;
+  type Error = JsErrorBox;
+
+  fn poll_frame(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+    let this = self.get_mut();
+    let frame = ready!(Pin::new(&mut this.inner).poll_frame(cx));
+    match &frame {
+      Some(Ok(frame)) => {
+        if let (Some(data), Some(received), Some((cache, ..))) =
+          (frame.data_ref(), &mut this.received, &this.pending)
+        {
+          received.extend_from_slice(data);
+          if received.len() as u64 > cache.max_size {
+            this.received = None;
+          }
+        }
+      }
+      Some(Err(_)) => this.received = None,
+      None => {
+        if let (Some((cache, key, mut stored)), Some(received)) =
+          (this.pending.take(), this.received.take())
+        {
+          stored.body = received.into();
+          cache.insert(key, stored);
+        }
+      }
+    }
+    Poll::Ready(frame)
+  }
+
+  fn is_end_stream(&self) -> bool {
+    self.inner.is_end_stream()
+  }
+
+  fn size_hint(&self) -> SizeHint {
+    self.inner.size_hint()
+  }
+}
+
+impl Drop for CachingBody {
+  fn drop(&mut self) {
+    // A body that is empty to begin with may never be polled.
+    if self.inner.is_end_stream()
+      && let (Some((cache, key, stored)), Some(_)) =
+        (self.pending.take(), self.received.take())
+    {
+      cache.insert(key, stored);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn stored(headers: &[(&str, &str)], response_time: u64) -> StoredResponse {
+    StoredResponse {
+      request_headers: vec![],
+      status: 200,
+      headers: headers
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect(),
+      body: Bytes::new(),
+      body_decoded: false,
+      response_time,
+      initial_age: 0,
+    }
+  }
+
+  fn request(cache_control: &str) -> CacheControl {
+    CacheControl::parse([cache_control].into_iter())
+  }
+
+  #[test]
+  fn parses_cache_control() {
+    let cache_control = CacheControl::parse(
+      [
+        "no-cache, Max-Age=60",
+        "max-stale, stale-while-revalidate=\"30\"",
+      ]
+      .into_iter(),
+    );
+    assert!(cache_control.no_cache);
+    assert!(!cache_control.no_store);
+    assert_eq!(cache_control.max_age, Some(60));
+    assert_eq!(cache_control.max_stale, Some(None));
+    assert_eq!(cache_control.stale_while_revalidate, Some(30));
+
+    let cache_control =
+      CacheControl::parse(["max-age=99999999999999999999999"].into_iter());
+    assert_eq!(cache_control.max_age, Some(u64::MAX));
+    let cache_control = CacheControl::parse(["max-age=-1"].into_iter());
+    assert_eq!(cache_control.max_age, None);
+  }
+
+  #[test]
+  fn freshness_lifetime() {
+    let date = "Sun, 06 Nov 1994 08:49:37 GMT";
+    let response = stored(&[("cache-control", "max-age=60")], 0);
+    assert_eq!(response.freshness_lifetime(&response.cache_control()), 60);
+
+    let response = stored(
+      &[("date", date), ("expires", "Sun, 06 Nov 1994 08:50:37 GMT")],
+      0,
+    );
+    assert_eq!(response.freshness_lifetime(&response.cache_control()), 60);
+
+    let response = stored(&[("date", date), ("expires", "0")], 0);
+    assert_eq!(response.freshness_lifetime(&response.cache_control()), 0);
+
+    let response = stored(
+      &[
+        ("date", date),
+        ("last-modified", "Sun, 06 Nov 1994 08:32:57 GMT"),
+      ],
+      0,
+    );
+    assert_eq!(response.freshness_lifetime(&response.cache_control()), 100);
+  }
+
+  #[test]
+  fn reuse_honours_directives() {
+    let default = RequestCacheMode::Default;
+    let response = stored(
+      &[("cache-control", "max-age=60, stale-while-revalidate=30")],
+      0,
+    );
+    assert!(matches!(
+      reuse(&response, &request(""), default, 10),
+      Reuse::Fresh
+    ));
+    assert!(matches!(
+      reuse(&response, &request("max-age=5"), default, 10),
+      Reuse::Revalidate
+    ));
+    assert!(matches!(
+      reuse(&response, &request("min-fresh=55"), default, 10),
+      Reuse::Revalidate
+    ));
+    assert!(matches!(
+      reuse(&response, &request("no-cache"), default, 10),
+      Reuse::Revalidate
+    ));
+    assert!(matches!(
+      reuse(&response, &request(""), default, 80),
+      Reuse::StaleWhileRevalidate
+    ));
+    assert!(matches!(
+      reuse(&response, &request(""), default, 100),
+      Reuse::Revalidate
+    ));
+    assert!(matches!(
+      reuse(&response, &request("max-stale=50"), default, 100),
+      Reuse::Fresh
+    ));
+    assert!(matches!(
+      reuse(&response, &request(""), RequestCacheMode::ForceCache, 1000),
+      Reuse::Fresh
+    ));
+    assert!(matches!(
+      reuse(&response, &request(""), RequestCacheMode::NoCache, 10),
+      Reuse::Revalidate
+    ));
+
+    let response =
+      stored(&[("cache-control", "max-age=60, must-revalidate")], 0);
+    assert!(matches!(
+      reuse(&response, &request("max-stale"), default, 100),
+      Reuse::Revalidate
+    ));
+  }
+
+  #[test]
+  fn variants_are_selected_by_vary() {
+    let mut response = stored(&[("vary", "Accept-Language")], 0);
+    response.request_headers =
+      vec![("accept-language".to_string(), "en".to_string())];
+    let headers = |value: &str| {
+      vec![(ByteString::from("Accept-Language"), ByteString::from(value))]
+    };
+    assert!(response.variant_matches(&headers("en")));
+    assert!(!response.variant_matches(&headers("de")));
+    assert!(!response.variant_matches(&[]));
+  }
+
+  #[test]
+  fn memory_store_evicts_least_recently_used() {
+    let mut store = MemoryStore::default();
+    let mut response = stored(&[], 0);
+    response.body = Bytes::from(vec![0; 40]);
+    store.insert("a", response.clone(), 100);
+    store.insert("b", response.clone(), 100);
+    assert_eq!(store.get("a").len(), 1);
+    store.insert("c", response.clone(), 100);
+    assert_eq!(store.get("a").len(), 1);
+    assert!(store.get("b").is_empty());
+    assert_eq!(store.get("c").len(), 1);
+
+    response.body = Bytes::from(vec![0; 200]);
+    store.insert("d", response, 100);
+    assert!(store.get("d").is_empty());
+    assert_eq!(store.by_last_use.values().collect::<Vec<_>>(), ["a", "c"]);
+  }
+
+  fn disk_store(dir: &std::path::Path) -> DiskStore {
+    DiskStore {
+      dir: dir.to_path_buf(),
+      locks: Default::default(),
+    }
+  }
+
+  #[test]
+  fn disk_store_round_trips() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = disk_store(dir.path());
+    let mut response = stored(&[("etag", "\"1\"")], 0);
+    response.body = Bytes::from_static(b"hello");
+    store.insert("https://example.com/", response, 1024);
+
+    let store = disk_store(dir.path());
+    let responses = store.get("https://example.com/");
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses[0].body, "hello");
+    assert_eq!(responses[0].header("ETag"), Some("\"1\""));
+
+    store.remove("https://example.com/");
+    assert!(store.get("https://example.com/").is_empty());
+  }
+
+  #[test]
+  #[allow(clippy::disallowed_methods, reason = "test uses a real temp dir")]
+  fn disk_store_evicts_least_recently_used() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = disk_store(dir.path());
+    let mut response = stored(&[], 0);
+    response.body = Bytes::from(vec![0; 200]);
+    store.insert("a", response.clone(), 1024);
+    store.insert("b", response.clone(), 1024);
+    // Make "a" the least recently used entry, regardless of the resolution
+    // of modification times.
+    std::fs::File::options()
+      .append(true)
+      .open(store.path("a"))
+      .unwrap()
+      .set_modified(SystemTime::UNIX_EPOCH)
+      .unwrap();
+    store.insert("c", response, 1024);
+    assert!(store.get("a").is_empty());
+    assert_eq!(store.get("b").len(), 1);
+    assert_eq!(store.get("c").len(), 1);
+  }
+
+  #[test]
+  fn disk_store_keeps_concurrently_stored_variants() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = disk_store(dir.path());
+    std::thread::scope(|scope| {
+      for i in 0..8 {
+        let store = &store;
+        scope.spawn(move || {
+          let mut response = stored(&[("vary", "Accept-Language")], 0);
+          response.request_headers =
+            vec![("accept-language".to_string(), i.to_string())];
+          store.insert("https://example.com/", response, 1024 * 1024);
+        });
+      }
+    });
+    assert_eq!(store.get("https://example.com/").len(), 8);
+  }
+}
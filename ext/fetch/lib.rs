@@ -2,9 +2,12 @@
 
 pub mod dns;
 mod fs_fetch_handler;
+mod http_cache;
 mod proxy;
+mod recording;
 #[cfg(test)]
 mod tests;
+mod timeout;
 
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -14,7 +17,6 @@ use std::future;
 use std::future::Future;
 use std::net::IpAddr;
 use std::path::Path;
-#[cfg(not(windows))]
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::rc::Rc;
@@ -24,6 +26,7 @@ use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
 use std::task::Context;
 use std::task::Poll;
+use std::time::Duration;
 
 use async_compression::tokio::bufread::BrotliDecoder;
 use async_compression::tokio::bufread::GzipDecoder;
@@ -57,6 +60,9 @@ use deno_core::url::Url;
 use deno_core::v8;
 use deno_error::JsErrorBox;
 pub use deno_fs::FsError;
+use deno_net::dns::DnsConfigError;
+use deno_net::dns::DnsResolverOptions;
+use deno_net::dns::DnsTlsOptions;
 use deno_path_util::PathToUrlError;
 use deno_permissions::OpenAccessKind;
 use deno_permissions::PermissionCheckError;
@@ -88,6 +94,9 @@ use http_body_util::BodyDataStream;
 use http_body_util::BodyExt;
 use http_body_util::StreamBody;
 use http_body_util::combinators::BoxBody;
+pub use http_cache::DEFAULT_HTTP_CACHE_MAX_SIZE;
+pub use http_cache::HttpCache;
+pub use http_cache::RequestCacheMode;
 use hyper::body::Frame;
 use hyper::body::Incoming;
 use hyper_util::client::legacy::Builder as HyperClientBuilder;
@@ -100,6 +109,11 @@ use hyper_util::rt::TokioExecutor;
 use hyper_util::rt::TokioIo;
 use hyper_util::rt::TokioTimer;
 pub use proxy::basic_auth;
+pub use recording::FetchRecorder;
+pub use recording::FetchRecordingError;
+use serde::Deserialize;
+pub use timeout::HttpClientTimeouts;
+pub use timeout::TimeoutError;
 use tokio_util::io::ReaderStream;
 use tokio_util::io::StreamReader;
 use tower::BoxError;
@@ -129,6 +143,9 @@ pub struct Options {
   pub client_cert_chain_and_key: TlsKeys,
   pub file_fetch_handler: Rc<dyn FetchHandler>,
   pub resolver: dns::Resolver,
+  /// Records the requests sent by `fetch()`, or replays their responses. See
+  /// [`FetchRecorder`].
+  pub recorder: Option<Arc<FetchRecorder>>,
 }
 
 impl Options {
@@ -152,6 +169,7 @@ impl Default for Options {
       client_cert_chain_and_key: TlsKeys::Null,
       file_fetch_handler: Rc::new(DefaultFileFetchHandler),
       resolver: dns::Resolver::default(),
+      recorder: None,
     }
   }
 }
@@ -259,6 +277,12 @@ pub enum FetchError {
   PermissionCheck(PermissionCheckError),
   #[class(inherit)]
   #[error(transparent)]
+  Recording(#[from] FetchRecordingError),
+  #[class(inherit)]
+  #[error(transparent)]
+  Timeout(#[from] TimeoutError),
+  #[class(inherit)]
+  #[error(transparent)]
   Other(JsErrorBox),
 }
 
@@ -357,6 +381,8 @@ pub fn create_client_from_options(
       local_address: None,
       client_builder_hook: options.client_builder_hook,
       http2_max_header_list_size: None,
+      recorder: options.recorder.clone(),
+      cache: None,
     },
   )
 }
@@ -442,6 +468,7 @@ pub fn op_fetch(
   has_body: bool,
   data: Option<Uint8Array>,
   #[smi] resource: Option<ResourceId>,
+  #[string] cache_mode: Option<String>,
 ) -> Result<FetchReturn, FetchError> {
   let (client, allow_host) = if let Some(rid) = client_rid {
     let r = state.resource_table.get::<HttpClientResource>(rid)?;
@@ -477,6 +504,7 @@ pub fn op_fetch(
     "http" | "https" => {
       let permissions = state.borrow_mut::<PermissionsContainer>();
       permissions.check_net_url(&url, "fetch()")?;
+      permissions.consume_net_quota_url(&url)?;
 
       let maybe_authority = extract_authority(&mut url);
       let uri = url
@@ -527,6 +555,11 @@ pub fn op_fetch(
       if let Some(len) = con_len {
         request.headers_mut().insert(CONTENT_LENGTH, len.into());
       }
+      if let Some(mode) =
+        cache_mode.as_deref().and_then(RequestCacheMode::parse)
+      {
+        request.extensions_mut().insert(mode);
+      }
 
       for (key, value) in headers {
         let name = HeaderName::from_bytes(&key)?;
@@ -546,13 +579,8 @@ pub fn op_fetch(
       let cancel_handle = CancelHandle::new_rc();
       let cancel_handle_ = cancel_handle.clone();
 
-      let fut = async move {
-        client
-          .send(request)
-          .map_err(Into::into)
-          .or_cancel(cancel_handle_)
-          .await
-      };
+      let fut =
+        async move { client.fetch(request).or_cancel(cancel_handle_).await };
 
       let request_rid = state.resource_table.add(FetchRequestResource {
         future: Box::pin(fut),
@@ -806,7 +834,14 @@ impl Resource for FetchResponseResource {
             // safely call `await` on it without creating a race condition.
             Some(_) => match reader.as_mut().next().await.unwrap() {
               Ok(chunk) => assert!(chunk.is_empty()),
-              Err(err) => break Err(JsErrorBox::type_error(err.to_string())),
+              Err(err) => {
+                break Err(match err.downcast::<JsErrorBox>() {
+                  // Timeouts keep their class, so they can be told apart.
+                  Ok(err) if TimeoutError::is_body_timeout(&err) => err,
+                  Ok(err) => JsErrorBox::type_error(err.to_string()),
+                  Err(err) => JsErrorBox::type_error(err.to_string()),
+                });
+              }
             },
             None => break Ok(BufView::empty()),
           }
@@ -863,6 +898,42 @@ pub struct CreateHttpClientArgs {
   allow_host: bool,
   local_address: Option<String>,
   http2_max_header_list_size: Option<u32>,
+  #[from_v8(serde)]
+  cache: Option<HttpCacheArgs>,
+  #[from_v8(serde)]
+  dns: Option<DnsResolverOptions>,
+  #[from_v8(serde)]
+  timeouts: Option<HttpClientTimeoutsArgs>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HttpCacheArgs {
+  path: Option<String>,
+  max_size: Option<u64>,
+}
+
+/// Timeouts in milliseconds.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HttpClientTimeoutsArgs {
+  connect: Option<u64>,
+  tls_handshake: Option<u64>,
+  response_headers: Option<u64>,
+  idle_read: Option<u64>,
+  total: Option<u64>,
+}
+
+impl From<HttpClientTimeoutsArgs> for HttpClientTimeouts {
+  fn from(args: HttpClientTimeoutsArgs) -> Self {
+    HttpClientTimeouts {
+      connect: args.connect.map(Duration::from_millis),
+      tls_handshake: args.tls_handshake.map(Duration::from_millis),
+      response_headers: args.response_headers.map(Duration::from_millis),
+      idle_read: args.idle_read.map(Duration::from_millis),
+      total: args.total.map(Duration::from_millis),
+    }
+  }
 }
 
 #[op2(stack_trace)]
@@ -917,23 +988,69 @@ pub fn op_fetch_custom_client(
     }
   }
 
-  let permissions = state.borrow::<PermissionsContainer>().clone();
-  let options = state.borrow::<Options>();
   let ca_certs = args
     .ca_certs
     .into_iter()
     .map(|cert| cert.into_bytes())
     .collect::<Vec<_>>();
+  let root_cert_store = state
+    .borrow::<Options>()
+    .root_cert_store()
+    .map_err(HttpClientCreateError::RootCertStore)?;
+
+  let dns_resolver = match args.dns {
+    Some(dns) => {
+      let permissions = state.borrow_mut::<PermissionsContainer>();
+      dns.check_name_servers(permissions, "Deno.createHttpClient()")?;
+      // Name servers queried over TLS are verified like the servers the
+      // client connects to.
+      let tls = DnsTlsOptions {
+        root_cert_store: root_cert_store.clone(),
+        ca_certs: ca_certs.clone(),
+        unsafely_ignore_certificate_errors: state
+          .borrow::<Options>()
+          .unsafely_ignore_certificate_errors
+          .clone(),
+      };
+      dns::Resolver::from_options(dns, tls)
+        .map_err(HttpClientCreateError::Dns)?
+    }
+    None => dns::Resolver::default(),
+  };
+
+  let cache = match args.cache {
+    Some(HttpCacheArgs { path, max_size }) => {
+      let max_size = max_size.unwrap_or(DEFAULT_HTTP_CACHE_MAX_SIZE);
+      let cache = match path {
+        Some(path) => {
+          let permissions = state.borrow_mut::<PermissionsContainer>();
+          let path = permissions
+            .check_open(
+              Cow::Owned(PathBuf::from(path)),
+              OpenAccessKind::ReadWrite,
+              Some("Deno.createHttpClient()"),
+            )?
+            .into_path_buf();
+          HttpCache::on_disk(path, max_size)
+            .map_err(HttpClientCreateError::CacheDir)?
+        }
+        None => HttpCache::in_memory(max_size),
+      };
+      Some(Arc::new(cache))
+    }
+    None => None,
+  };
+
+  let permissions = state.borrow::<PermissionsContainer>().clone();
+  let options = state.borrow::<Options>();
 
   let client = create_http_client(
     &options.user_agent,
     CreateHttpClientOptions {
-      root_cert_store: options
-        .root_cert_store()
-        .map_err(HttpClientCreateError::RootCertStore)?,
+      root_cert_store,
       ca_certs,
       proxy: args.proxy,
-      dns_resolver: dns::Resolver::default(),
+      dns_resolver,
       permissions: Some(permissions),
       resolved_deny_check_kind: dns::ResolvedDenyCheckKind::Net,
       unsafely_ignore_certificate_errors: options
@@ -956,6 +1073,9 @@ pub fn op_fetch_custom_client(
       local_address: args.local_address,
       client_builder_hook: options.client_builder_hook,
       http2_max_header_list_size: args.http2_max_header_list_size,
+      recorder: options.recorder.clone(),
+      cache,
+      timeouts: args.timeouts.map(Into::into).unwrap_or_default(),
     },
   )?;
 
@@ -986,6 +1106,12 @@ pub struct CreateHttpClientOptions {
   pub local_address: Option<String>,
   pub client_builder_hook: Option<fn(HyperClientBuilder) -> HyperClientBuilder>,
   pub http2_max_header_list_size: Option<u32>,
+  /// When set, requests sent with [`Client::fetch`] are recorded or replayed.
+  pub recorder: Option<Arc<FetchRecorder>>,
+  /// When set, requests sent with [`Client::fetch`] go through this HTTP
+  /// cache.
+  pub cache: Option<Arc<HttpCache>>,
+  pub timeouts: HttpClientTimeouts,
 }
 
 impl Default for CreateHttpClientOptions {
@@ -1006,6 +1132,9 @@ impl Default for CreateHttpClientOptions {
       local_address: None,
       client_builder_hook: None,
       http2_max_header_list_size: None,
+      recorder: None,
+      cache: None,
+      timeouts: HttpClientTimeouts::default(),
     }
   }
 }
@@ -1032,6 +1161,12 @@ pub enum HttpClientCreateError {
   UnixProxyNotSupportedOnWindows,
   #[error("Vsock proxy is not supported on this platform")]
   VsockProxyNotSupported,
+  #[class(inherit)]
+  #[error("Failed to create the HTTP cache directory: {0}")]
+  CacheDir(std::io::Error),
+  #[class(inherit)]
+  #[error(transparent)]
+  Dns(DnsConfigError),
 }
 
 /// Default `SETTINGS_MAX_HEADER_LIST_SIZE` advertised to HTTP/2 servers. Matches
@@ -1169,6 +1304,7 @@ pub fn create_http_client(
     tls_proxy: proxy_tls_config,
     user_agent: Some(user_agent.clone()),
     permissions,
+    timeouts: options.timeouts,
   };
 
   if let Some(pool_max_idle_per_host) = options.pool_max_idle_per_host {
@@ -1176,9 +1312,7 @@ pub fn create_http_client(
   }
 
   if let Some(pool_idle_timeout) = options.pool_idle_timeout {
-    builder.pool_idle_timeout(
-      pool_idle_timeout.map(std::time::Duration::from_millis),
-    );
+    builder.pool_idle_timeout(pool_idle_timeout.map(Duration::from_millis));
   }
 
   // An explicit `Deno.createHttpClient({ http2MaxHeaderListSize })` takes
@@ -1207,6 +1341,9 @@ pub fn create_http_client(
     inner: decompress,
     connector,
     user_agent,
+    recorder: options.recorder,
+    cache: options.cache,
+    timeouts: options.timeouts,
   })
 }
 
@@ -1220,6 +1357,9 @@ pub struct Client {
   inner: DecompressionService<FetchClient>,
   connector: Connector,
   user_agent: HeaderValue,
+  recorder: Option<Arc<FetchRecorder>>,
+  cache: Option<Arc<HttpCache>>,
+  timeouts: HttpClientTimeouts,
 }
 
 type FetchClient = retry::Retry<
@@ -1557,6 +1697,33 @@ impl Client {
     Ok(resp)
   }
 
+  /// Sends a request on behalf of `fetch()`: through the client's HTTP cache
+  /// and recorder if it has them, and straight to the network otherwise. The
+  /// client's response headers, idle read and total timeouts apply.
+  pub async fn fetch(
+    self,
+    req: http::Request<ReqBody>,
+  ) -> Result<http::Response<ResBody>, FetchError> {
+    let timeouts = self.timeouts;
+    timeout::fetch(timeouts, async move {
+      match self.cache.clone() {
+        Some(cache) => cache.fetch(self, req).await,
+        None => self.fetch_uncached(req).await,
+      }
+    })
+    .await
+  }
+
+  pub(crate) async fn fetch_uncached(
+    self,
+    req: http::Request<ReqBody>,
+  ) -> Result<http::Response<ResBody>, FetchError> {
+    match self.recorder.clone() {
+      Some(recorder) => recorder.fetch(self, req).await,
+      None => Ok(self.send(req).await?),
+    }
+  }
+
   /// Sends a request bypassing the transparent decompression middleware.
   /// The response body will contain raw bytes (potentially compressed).
   /// The caller is responsible for checking Content-Encoding and
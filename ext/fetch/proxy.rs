@@ -17,7 +17,6 @@ use std::sync::Arc;
 use std::task::Context;
 use std::task::Poll;
 
-use deno_core::futures::TryFutureExt;
 #[cfg(not(windows))]
 use deno_permissions::OpenAccessKind;
 use deno_permissions::PermissionsContainer;
@@ -47,6 +46,11 @@ use tokio_vsock::VsockStream;
 use tower_service::Service;
 
 use crate::dns::CheckDst;
+use crate::timeout::HttpClientTimeouts;
+use crate::timeout::TimedConnect;
+use crate::timeout::TimeoutError;
+use crate::timeout::with_handshake_timeout;
+use crate::timeout::with_timeout;
 
 #[derive(Debug, Clone)]
 pub(crate) struct ProxyConnector<C> {
@@ -59,6 +63,8 @@ pub(crate) struct ProxyConnector<C> {
   pub(crate) tls_proxy: Arc<TlsConfig>,
   pub(crate) user_agent: Option<HeaderValue>,
   pub(crate) permissions: Option<PermissionsContainer>,
+  /// Only the connect and TLS handshake timeouts apply here.
+  pub(crate) timeouts: HttpClientTimeouts,
 }
 
 impl<C> ProxyConnector<C> {
@@ -78,6 +84,7 @@ impl<C> ProxyConnector<C> {
       tls_proxy: self.tls_proxy,
       user_agent: self.user_agent,
       permissions: self.permissions,
+      timeouts: self.timeouts,
     })
   }
 
@@ -97,6 +104,7 @@ impl<C> ProxyConnector<C> {
       tls_proxy: self.tls_proxy,
       user_agent: self.user_agent,
       permissions: self.permissions,
+      timeouts: self.timeouts,
     })
   }
 }
@@ -611,12 +619,17 @@ where
           dst: proxy_dst,
           auth,
         } => {
+          let (http, connected) =
+            TimedConnect::new(self.http.clone(), self.timeouts.connect);
           let mut connector =
-            HttpsConnector::from((self.http.clone(), self.tls_proxy.clone()));
+            HttpsConnector::from((http, self.tls_proxy.clone()));
           let connecting = connector.call(proxy_dst);
           let tls = TlsConnector::from(self.tls.clone());
+          let handshake_timeout = self.timeouts.tls_handshake;
           Box::pin(async move {
-            let mut io = connecting.await?;
+            let mut io =
+              with_handshake_timeout(handshake_timeout, connected, connecting)
+                .await?;
 
             if is_https {
               tunnel(&mut io, &orig_dst, user_agent, auth).await?;
@@ -626,9 +639,12 @@ where
                 .and_then(|s| s.strip_suffix(']'))
                 .unwrap_or(host);
               let tokio_io = TokioIo::new(io);
-              let io = tls
-                .connect(TryFrom::try_from(host.to_owned())?, tokio_io)
-                .await?;
+              let io = with_timeout(
+                handshake_timeout,
+                TimeoutError::TlsHandshake,
+                tls.connect(TryFrom::try_from(host.to_owned())?, tokio_io),
+              )
+              .await?;
               Ok(Proxied::HttpTunneled(Box::new(TokioIo::new(io))))
             } else {
               Ok(Proxied::Tcp(io))
@@ -641,6 +657,7 @@ where
         } => {
           let tls = TlsConnector::from(self.tls.clone());
           let mut permissions = self.permissions.clone();
+          let timeouts = self.timeouts;
           Box::pin(async move {
             let socks_addr = (
               proxy_dst.host().unwrap(),
@@ -662,24 +679,33 @@ where
               None if is_https => 443,
               _ => 80,
             };
-            let io = if let Some((user, pass)) = auth {
-              Socks5Stream::connect_with_password(
-                socks_addr,
-                (host, port),
-                &user,
-                &pass,
-              )
-              .await?
-            } else {
-              Socks5Stream::connect(socks_addr, (host, port)).await?
+            let connecting = async {
+              match auth {
+                Some((user, pass)) => {
+                  Socks5Stream::connect_with_password(
+                    socks_addr,
+                    (host, port),
+                    &user,
+                    &pass,
+                  )
+                  .await
+                }
+                None => Socks5Stream::connect(socks_addr, (host, port)).await,
+              }
             };
+            let io =
+              with_timeout(timeouts.connect, TimeoutError::Connect, connecting)
+                .await?;
             let io = TokioIo::new(io.into_inner());
 
             if is_https {
               let tokio_io = TokioIo::new(io);
-              let io = tls
-                .connect(TryFrom::try_from(host.to_owned())?, tokio_io)
-                .await?;
+              let io = with_timeout(
+                timeouts.tls_handshake,
+                TimeoutError::TlsHandshake,
+                tls.connect(TryFrom::try_from(host.to_owned())?, tokio_io),
+              )
+              .await?;
               Ok(Proxied::SocksTls(TokioIo::new(io)))
             } else {
               Ok(Proxied::Socks(io))
@@ -690,16 +716,21 @@ where
           hostname: host,
           port,
         } => {
+          let (http, connected) =
+            TimedConnect::new(self.http.clone(), self.timeouts.connect);
           let mut connector =
-            HttpsConnector::from((self.http.clone(), self.tls_proxy.clone()));
+            HttpsConnector::from((http, self.tls_proxy.clone()));
           let Ok(uri) = format!("http://{}:{}", host, port).parse() else {
             return Box::pin(async {
               Err("failed to parse tcp proxy uri".into())
             });
           };
           let connecting = connector.call(uri);
+          let handshake_timeout = self.timeouts.tls_handshake;
           Box::pin(async move {
-            let io = connecting.await?;
+            let io =
+              with_handshake_timeout(handshake_timeout, connected, connecting)
+                .await?;
             Ok(Proxied::Tcp(io))
           })
         }
@@ -707,6 +738,7 @@ where
         Target::Unix { path } => {
           let mut path = path.clone();
           let mut permissions = self.permissions.clone();
+          let connect_timeout = self.timeouts.connect;
           Box::pin(async move {
             if let Some(permissions) = permissions.as_mut() {
               let resolved_path = permissions
@@ -720,7 +752,12 @@ where
                 .check_net_unix_socket(&resolved_path, Some("fetch() proxy"))?;
               path = resolved_path.into_owned();
             }
-            let io = UnixStream::connect(&path).await?;
+            let io = with_timeout(
+              connect_timeout,
+              TimeoutError::Connect,
+              UnixStream::connect(&path),
+            )
+            .await?;
             Ok(Proxied::Unix(TokioIo::new(io)))
           })
         }
@@ -731,12 +768,18 @@ where
         ))]
         Target::Vsock { cid, port } => {
           let mut permissions = self.permissions.clone();
+          let connect_timeout = self.timeouts.connect;
           Box::pin(async move {
             if let Some(permissions) = permissions.as_mut() {
               permissions.check_net_vsock(cid, port, "fetch() proxy")?;
             }
             let addr = tokio_vsock::VsockAddr::new(cid, port);
-            let io = VsockStream::connect(addr).await?;
+            let io = with_timeout(
+              connect_timeout,
+              TimeoutError::Connect,
+              VsockStream::connect(addr),
+            )
+            .await?;
             Ok(Proxied::Vsock(TokioIo::new(io)))
           })
         }
@@ -747,14 +790,16 @@ where
       });
     }
 
-    let mut connector =
-      HttpsConnector::from((self.http.clone(), self.tls.clone()));
-    Box::pin(
-      connector
-        .call(orig_dst)
-        .map_ok(Proxied::PassThrough)
-        .map_err(Into::into),
-    )
+    let (http, connected) =
+      TimedConnect::new(self.http.clone(), self.timeouts.connect);
+    let mut connector = HttpsConnector::from((http, self.tls.clone()));
+    let connecting = connector.call(orig_dst);
+    let handshake_timeout = self.timeouts.tls_handshake;
+    Box::pin(async move {
+      with_handshake_timeout(handshake_timeout, connected, connecting)
+        .await
+        .map(Proxied::PassThrough)
+    })
   }
 }
 
@@ -0,0 +1,563 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! Recording and replaying of the requests sent by `fetch()`.
+//!
+//! In record mode every request sent through a client with a
+//! [`FetchRecorder`] goes to the network as usual, and is appended to a
+//! cassette file together with its response once the response body has been
+//! read. The cassette holds one JSON object per line: a header with the
+//! cassette version, then one line per interaction. A body that failed or was
+//! dropped before its end is recorded with the error, and replayed with it.
+//!
+//! In replay mode the responses are served from that cassette instead.
+//! Requests are matched on their method, URL and body, each recorded response
+//! is served once, in the order it was recorded, and a request without a
+//! match fails.
+//!
+//! The values of headers carrying credentials, `Set-Cookie` included, are
+//! redacted from the cassette.
+
+use std::fs::File;
+use std::future::Future;
+use std::io::Write;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Context;
+use std::task::Poll;
+use std::task::ready;
+
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use bytes::Bytes;
+use deno_error::JsErrorBox;
+use http::HeaderMap;
+use http::StatusCode;
+use http::header::AUTHORIZATION;
+use http::header::COOKIE;
+use http::header::HeaderName;
+use http::header::HeaderValue;
+use http::header::PROXY_AUTHORIZATION;
+use http::header::SET_COOKIE;
+use http_body_util::BodyExt;
+use http_body_util::combinators::BoxBody;
+use hyper::body::Body;
+use hyper::body::Frame;
+use hyper::body::SizeHint;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use tokio::task::JoinHandle;
+
+use crate::BodyDecoded;
+use crate::Client;
+use crate::FetchError;
+use crate::ReqBody;
+use crate::ResBody;
+
+const CASSETTE_VERSION: u32 = 1;
+
+/// Written in place of the values of headers carrying credentials.
+const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, thiserror::Error, deno_error::JsError)]
+pub enum FetchRecordingError {
+  #[class(generic)]
+  #[error("Failed to read fetch cassette '{}': {source}", path.display())]
+  Read {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+  #[class(generic)]
+  #[error("Failed to write fetch cassette '{}': {source}", path.display())]
+  Write {
+    path: PathBuf,
+    #[source]
+    source: std::io::Error,
+  },
+  #[class(generic)]
+  #[error("Invalid fetch cassette '{}': {source}", path.display())]
+  Parse {
+    path: PathBuf,
+    #[source]
+    source: serde_json::Error,
+  },
+  #[class(generic)]
+  #[error("Invalid fetch cassette '{}': {reason}", path.display())]
+  Invalid { path: PathBuf, reason: String },
+  #[class(type)]
+  #[error(
+    "No response recorded in fetch cassette '{}' for {method} {url}",
+    path.display()
+  )]
+  Unmatched {
+    path: PathBuf,
+    method: String,
+    url: String,
+  },
+}
+
+/// The first line of a cassette.
+#[derive(Debug, Serialize, Deserialize)]
+struct CassetteHeader {
+  version: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Interaction {
+  request: RecordedRequest,
+  response: RecordedResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecordedRequest {
+  method: String,
+  url: String,
+  headers: Vec<(String, String)>,
+  /// Base64 encoded.
+  body: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordedResponse {
+  status: u16,
+  headers: Vec<(String, String)>,
+  /// Base64 encoded. Holds the decompressed body when `body_decoded` is set,
+  /// in which case `headers` still describe the encoded one.
+  body: String,
+  #[serde(default)]
+  body_decoded: bool,
+  /// Why the body ended early, after the data in `body`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  error: Option<String>,
+}
+
+/// A recorded response, validated when the cassette is loaded.
+#[derive(Debug)]
+struct ReplayInteraction {
+  method: String,
+  url: String,
+  request_body_hash: BodyHash,
+  status: StatusCode,
+  headers: HeaderMap,
+  body: Bytes,
+  body_decoded: bool,
+  error: Option<String>,
+}
+
+type BodyHash = sha2::digest::Output<Sha256>;
+
+#[derive(Debug)]
+enum RecorderState {
+  /// The cassette interactions are appended to.
+  Record(Mutex<File>),
+  /// The interactions that have not been replayed yet.
+  Replay(Mutex<Vec<ReplayInteraction>>),
+}
+
+/// Records the requests sent by `fetch()` to a cassette file, or replays
+/// their responses from one. See the module documentation for details.
+#[derive(Debug)]
+pub struct FetchRecorder {
+  path: PathBuf,
+  state: RecorderState,
+}
+
+impl FetchRecorder {
+  /// Creates a recorder writing to the cassette at `path`. Any cassette
+  /// already there is replaced.
+  pub fn record(path: PathBuf) -> Result<Self, FetchRecordingError> {
+    let write_err = |source| FetchRecordingError::Write {
+      path: path.clone(),
+      source,
+    };
+    let mut file = File::create(&path).map_err(write_err)?;
+    let header = CassetteHeader {
+      version: CASSETTE_VERSION,
+    };
+    file.write_all(&json_line(&header)).map_err(write_err)?;
+    Ok(Self {
+      path,
+      state: RecorderState::Record(Mutex::new(file)),
+    })
+  }
+
+  /// Creates a recorder replaying the cassette at `path`.
+  pub fn replay(path: PathBuf) -> Result<Self, FetchRecordingError> {
+    #[allow(
+      clippy::disallowed_methods,
+      reason = "cassettes are read from the real fs"
+    )]
+    let data =
+      std::fs::read(&path).map_err(|source| FetchRecordingError::Read {
+        path: path.clone(),
+        source,
+      })?;
+    let parse_err = |source| FetchRecordingError::Parse {
+      path: path.clone(),
+      source,
+    };
+    let mut lines = data
+      .split(|byte| *byte == b'\n')
+      .filter(|line| !line.trim_ascii().is_empty());
+    let header: CassetteHeader =
+      serde_json::from_slice(lines.next().unwrap_or_default())
+        .map_err(parse_err)?;
+    if header.version != CASSETTE_VERSION {
+      return Err(FetchRecordingError::Invalid {
+        path,
+        reason: format!("unsupported version {}", header.version),
+      });
+    }
+    let interactions = lines
+      .map(|line| {
+        let interaction: Interaction =
+          serde_json::from_slice(line).map_err(parse_err)?;
+        ReplayInteraction::try_from(interaction).map_err(|reason| {
+          FetchRecordingError::Invalid {
+            path: path.clone(),
+            reason,
+          }
+        })
+      })
+      .collect::<Result<Vec<_>, _>>()?;
+    Ok(Self {
+      path,
+      state: RecorderState::Replay(Mutex::new(interactions)),
+    })
+  }
+
+  pub(crate) async fn fetch(
+    self: Arc<Self>,
+    client: Client,
+    req: http::Request<ReqBody>,
+  ) -> Result<http::Response<ResBody>, FetchError> {
+    if let RecorderState::Replay(_) = &self.state {
+      let (parts, body) = req.into_parts();
+      let body = match body {
+        ReqBody::Full(full) => full
+          .collect()
+          .await
+          .unwrap_or_else(|never| match never {})
+          .to_bytes(),
+        ReqBody::Empty(_) => Bytes::new(),
+        ReqBody::Streaming(streaming) => streaming
+          .collect()
+          .await
+          .map_err(FetchError::Other)?
+          .to_bytes(),
+      };
+      return Ok(self.replay_response(
+        parts.method.as_str(),
+        parts.uri.to_string(),
+        &body,
+      )?);
+    }
+
+    let request = RecordedRequest {
+      method: req.method().to_string(),
+      url: req.uri().to_string(),
+      headers: recorded_headers(req.headers()),
+      body: String::new(),
+    };
+    let (parts, body) = req.into_parts();
+    let request_body = Arc::new(Mutex::new(Vec::new()));
+    let body = match body {
+      // Full bodies are cloned rather than wrapped, so that the request can
+      // still be retried.
+      ReqBody::Full(full) => {
+        let data = full
+          .clone()
+          .collect()
+          .await
+          .unwrap_or_else(|never| match never {})
+          .to_bytes();
+        request_body.lock().unwrap().extend_from_slice(&data);
+        ReqBody::Full(full)
+      }
+      ReqBody::Empty(empty) => ReqBody::Empty(empty),
+      ReqBody::Streaming(streaming) => {
+        ReqBody::Streaming(BoxBody::new(TeeBody {
+          inner: streaming,
+          captured: request_body.clone(),
+        }))
+      }
+    };
+
+    let res = client.send(http::Request::from_parts(parts, body)).await?;
+    let (parts, body) = res.into_parts();
+    let response = RecordedResponse {
+      status: parts.status.as_u16(),
+      headers: recorded_headers(&parts.headers),
+      body: String::new(),
+      body_decoded: parts.extensions.get::<BodyDecoded>().is_some(),
+      error: None,
+    };
+    let body = RecordingBody {
+      inner: body,
+      received: Vec::new(),
+      request_body,
+      pending: Some(Interaction { request, response }),
+      writing: None,
+      recorder: self,
+    };
+    Ok(http::Response::from_parts(parts, BoxBody::new(body)))
+  }
+
+  fn replay_response(
+    &self,
+    method: &str,
+    url: String,
+    body: &[u8],
+  ) -> Result<http::Response<ResBody>, FetchRecordingError> {
+    let body_hash = Sha256::digest(body);
+    let interaction = {
+      let RecorderState::Replay(interactions) = &self.state else {
+        unreachable!()
+      };
+      let mut interactions = interactions.lock().unwrap();
+      let index = interactions
+        .iter()
+        .position(|i| {
+          i.method == method && i.url == url && i.request_body_hash == body_hash
+        })
+        .ok_or_else(|| FetchRecordingError::Unmatched {
+          path: self.path.clone(),
+          method: method.to_string(),
+          url,
+        })?;
+      interactions.remove(index)
+    };
+
+    let body = match interaction.error {
+      None => http_body_util::Full::new(interaction.body)
+        .map_err(|never| match never {})
+        .boxed(),
+      Some(error) => {
+        let frames = [
+          Ok(Frame::data(interaction.body)),
+          Err(JsErrorBox::generic(error)),
+        ];
+        BoxBody::new(http_body_util::StreamBody::new(
+          deno_core::futures::stream::iter(frames),
+        ))
+      }
+    };
+    let mut res = http::Response::new(body);
+    *res.status_mut() = interaction.status;
+    *res.headers_mut() = interaction.headers;
+    if interaction.body_decoded {
+      res.extensions_mut().insert(BodyDecoded);
+    }
+    Ok(res)
+  }
+
+  /// Appends `interaction` to the cassette. This blocks, so it is run with
+  /// `spawn_blocking` on the async path.
+  fn push(&self, interaction: &Interaction) -> Result<(), FetchRecordingError> {
+    let line = json_line(interaction);
+    let RecorderState::Record(file) = &self.state else {
+      unreachable!()
+    };
+    file.lock().unwrap().write_all(&line).map_err(|source| {
+      FetchRecordingError::Write {
+        path: self.path.clone(),
+        source,
+      }
+    })
+  }
+}
+
+fn json_line(value: &impl Serialize) -> Vec<u8> {
+  let mut line = serde_json::to_vec(value).unwrap();
+  line.push(b'\n');
+  line
+}
+
+fn is_redacted(name: &HeaderName) -> bool {
+  name == AUTHORIZATION
+    || name == COOKIE
+    || name == PROXY_AUTHORIZATION
+    || name == SET_COOKIE
+}
+
+fn recorded_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+  headers
+    .iter()
+    .map(|(name, value)| {
+      let value = if is_redacted(name) {
+        REDACTED.to_string()
+      } else {
+        String::from_utf8_lossy(value.as_bytes()).into_owned()
+      };
+      (name.to_string(), value)
+    })
+    .collect()
+}
+
+impl TryFrom<Interaction> for ReplayInteraction {
+  type Error = String;
+
+  fn try_from(interaction: Interaction) -> Result<Self, Self::Error> {
+    let Interaction { request, response } = interaction;
+    let describe = |reason: String| {
+      format!("response to {} {}: {reason}", request.method, request.url)
+    };
+    let status = StatusCode::from_u16(response.status)
+      .map_err(|err| describe(err.to_string()))?;
+    let mut headers = HeaderMap::with_capacity(response.headers.len());
+    for (name, value) in response.headers {
+      let name = HeaderName::from_bytes(name.as_bytes())
+        .map_err(|err| describe(err.to_string()))?;
+      let value = HeaderValue::from_str(&value)
+        .map_err(|err| describe(err.to_string()))?;
+      headers.append(name, value);
+    }
+    let request_body = BASE64_STANDARD
+      .decode(&request.body)
+      .map_err(|err| describe(err.to_string()))?;
+    let body = BASE64_STANDARD
+      .decode(&response.body)
+      .map_err(|err| describe(err.to_string()))?;
+    Ok(Self {
+      method: request.method,
+      url: request.url,
+      request_body_hash: Sha256::digest(&request_body),
+      status,
+      headers,
+      body: body.into(),
+      body_decoded: response.body_decoded,
+      error: response.error,
+    })
+  }
+}
+
+/// Passes a streaming request body through, keeping a copy of its data.
+struct TeeBody {
+  inner: BoxBody<Bytes, JsErrorBox>,
+  captured: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Body for TeeBody {
+  type Data = Bytes;
+  type Error = JsErrorBox;
+
+  fn poll_frame(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+    let this = self.get_mut();
+    let frame = ready!(Pin::new(&mut this.inner).poll_frame(cx));
+    if let Some(Ok(frame)) = &frame
+      && let Some(data) = frame.data_ref()
+    {
+      this.captured.lock().unwrap().extend_from_slice(data);
+    }
+    Poll::Ready(frame)
+  }
+
+  fn is_end_stream(&self) -> bool {
+    self.inner.is_end_stream()
+  }
+
+  fn size_hint(&self) -> SizeHint {
+    self.inner.size_hint()
+  }
+}
+
+/// Passes a response body through, and records the interaction it belongs to
+/// once it has been read. The end of the body is only passed on once the
+/// interaction has been written, so that a failed write fails the body. A
+/// body that fails or is dropped before its end is recorded with the data read
+/// so far and the reason it ended.
+struct RecordingBody {
+  inner: ResBody,
+  received: Vec<u8>,
+  request_body: Arc<Mutex<Vec<u8>>>,
+  pending: Option<Interaction>,
+  /// The write of the interaction, once the body has ended.
+  writing: Option<JoinHandle<Result<(), FetchRecordingError>>>,
+  recorder: Arc<FetchRecorder>,
+}
+
+impl RecordingBody {
+  /// Records the interaction, unless it already has been. Returns the
+  /// pending write, which doesn't need to be awaited. Outside of a runtime,
+  /// e.g. when a body is dropped on shutdown, the line is written right away.
+  fn finish(
+    &mut self,
+    error: Option<String>,
+  ) -> Option<JoinHandle<Result<(), FetchRecordingError>>> {
+    let mut interaction = self.pending.take()?;
+    interaction.request.body =
+      BASE64_STANDARD.encode(&*self.request_body.lock().unwrap());
+    interaction.response.body = BASE64_STANDARD.encode(&self.received);
+    interaction.response.error = error;
+    let recorder = self.recorder.clone();
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+      let _ = recorder.push(&interaction);
+      return None;
+    };
+    Some(runtime.spawn_blocking(move || recorder.push(&interaction)))
+  }
+}
+
+impl Body for RecordingBody {
+  type Data = Bytes;
+  type Error = JsErrorBox;
+
+  fn poll_frame(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+    let this = self.get_mut();
+    if this.writing.is_none() {
+      let frame = ready!(Pin::new(&mut this.inner).poll_frame(cx));
+      match &frame {
+        Some(Ok(frame)) => {
+          if let Some(data) = frame.data_ref() {
+            this.received.extend_from_slice(data);
+          }
+        }
+        Some(Err(err)) => {
+          this.finish(Some(err.to_string()));
+        }
+        None => this.writing = this.finish(None),
+      }
+      if this.writing.is_none() {
+        return Poll::Ready(frame);
+      }
+    }
+
+    let writing = this.writing.as_mut().unwrap();
+    let result = ready!(Pin::new(writing).poll(cx));
+    this.writing = None;
+    match result {
+      Ok(Err(err)) => Poll::Ready(Some(Err(JsErrorBox::from_err(err)))),
+      _ => Poll::Ready(None),
+    }
+  }
+
+  fn is_end_stream(&self) -> bool {
+    self.writing.is_none() && self.inner.is_end_stream()
+  }
+
+  fn size_hint(&self) -> SizeHint {
+    self.inner.size_hint()
+  }
+}
+
+impl Drop for RecordingBody {
+  fn drop(&mut self) {
+    let error = if self.inner.is_end_stream() {
+      None
+    } else {
+      Some("The response body was not read to the end".to_string())
+    };
+    self.finish(error);
+  }
+}
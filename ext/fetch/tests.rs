@@ -17,10 +17,13 @@ use deno_tls::rustls::pki_types::PrivateKeyDer;
 use fast_socks5::server::Config as Socks5Config;
 use fast_socks5::server::Socks5Socket;
 use http::header::ACCEPT_ENCODING;
+use http::header::AGE;
+use http::header::AUTHORIZATION;
 use http::header::CONTENT_ENCODING;
 use http::header::CONTENT_LENGTH;
 use http::header::HeaderValue;
 use http::header::RANGE;
+use http::header::SET_COOKIE;
 use http::header::TRANSFER_ENCODING;
 use http_body_util::BodyExt;
 use hyper_util::client::legacy::connect::Connected;
@@ -29,10 +32,16 @@ use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
 use tokio::sync::Mutex;
 
+use super::BodyDecoded;
 use super::CheckoutTracker;
 use super::ConnectionReused;
 use super::ConnectionUsage;
 use super::CreateHttpClientOptions;
+use super::FetchError;
+use super::FetchRecorder;
+use super::FetchRecordingError;
+use super::HttpCache;
+use super::RequestCacheMode;
 use super::create_http_client;
 use crate::dns;
 
@@ -178,6 +187,9 @@ async fn run_test_client_with_resolver(
       local_address: None,
       client_builder_hook: None,
       http2_max_header_list_size: None,
+      recorder: None,
+      cache: None,
+      timeouts: Default::default(),
     },
   )
   .unwrap();
@@ -281,6 +293,9 @@ async fn test_import_client_denies_host_resolving_to_denied_ip() {
       local_address: None,
       client_builder_hook: None,
       http2_max_header_list_size: None,
+      recorder: None,
+      cache: None,
+      timeouts: Default::default(),
     },
   )
   .unwrap();
@@ -336,6 +351,9 @@ async fn test_http_proxy_denies_destination_resolving_to_denied_ip() {
       local_address: None,
       client_builder_hook: None,
       http2_max_header_list_size: None,
+      recorder: None,
+      cache: None,
+      timeouts: Default::default(),
     },
   )
   .unwrap();
@@ -652,6 +670,338 @@ async fn test_fetch_send_no_decompress_preserves_raw_response() {
   assert_eq!(body, GZIP_HELLO_FROM_SERVER);
 }
 
+#[tokio::test]
+async fn test_fetch_recorder_records_and_replays() {
+  let captured_accept_encoding = Arc::new(Mutex::new(None));
+  let src_addr = create_encoded_http_server(
+    captured_accept_encoding.clone(),
+    HeaderValue::from_static("gzip"),
+    GZIP_HELLO_FROM_SERVER,
+  )
+  .await;
+  let dir = tempfile::tempdir().unwrap();
+  let cassette = dir.path().join("fetch.json");
+  let url = format!("http://{}/foo", src_addr);
+
+  let recorder = Arc::new(FetchRecorder::record(cassette.clone()).unwrap());
+  let client = create_http_test_client_with_recorder(recorder);
+  let req = http::Request::builder()
+    .method(http::Method::POST)
+    .uri(&url)
+    .header(AUTHORIZATION, "Bearer secret")
+    .body(crate::ReqBody::full(Bytes::from_static(b"ping")))
+    .unwrap();
+  let resp = client.fetch(req).await.unwrap();
+  assert!(resp.extensions().get::<BodyDecoded>().is_some());
+  let body = resp.collect().await.unwrap().to_bytes();
+  assert_eq!(body, "hello from server");
+
+  let recorded = read_cassette(&cassette);
+  assert_eq!(recorded[0], serde_json::json!({ "version": 1 }));
+  let interaction = &recorded[1];
+  assert_eq!(interaction["request"]["method"], "POST");
+  assert_eq!(interaction["request"]["url"], url);
+  assert_eq!(interaction["request"]["body"], "cGluZw==");
+  assert_eq!(
+    interaction["request"]["headers"],
+    serde_json::json!([["authorization", "[redacted]"]])
+  );
+  assert_eq!(interaction["response"]["status"], 200);
+  assert_eq!(interaction["response"]["bodyDecoded"], true);
+  assert!(interaction["response"].get("error").is_none());
+
+  let recorder = Arc::new(FetchRecorder::replay(cassette.clone()).unwrap());
+  let client = create_http_test_client_with_recorder(recorder);
+  let request = |body: &'static [u8]| {
+    http::Request::builder()
+      .method(http::Method::POST)
+      .uri(&url)
+      .body(crate::ReqBody::full(Bytes::from_static(body)))
+      .unwrap()
+  };
+
+  // Requests are matched on their body too.
+  let err = client.clone().fetch(request(b"pong")).await.unwrap_err();
+  assert!(matches!(
+    err,
+    FetchError::Recording(FetchRecordingError::Unmatched { .. })
+  ));
+
+  let resp = client.clone().fetch(request(b"ping")).await.unwrap();
+  assert_eq!(resp.status(), http::StatusCode::OK);
+  assert_eq!(
+    resp.headers().get(CONTENT_ENCODING).unwrap(),
+    HeaderValue::from_static("gzip")
+  );
+  assert!(resp.extensions().get::<BodyDecoded>().is_some());
+  let body = resp.collect().await.unwrap().to_bytes();
+  assert_eq!(body, "hello from server");
+
+  // Every recorded response is served once.
+  let err = client.fetch(request(b"ping")).await.unwrap_err();
+  assert!(matches!(
+    err,
+    FetchError::Recording(FetchRecordingError::Unmatched { .. })
+  ));
+}
+
+#[tokio::test]
+async fn test_fetch_recorder_records_streaming_request_body() {
+  let src_addr = create_echo_http_server().await;
+  let dir = tempfile::tempdir().unwrap();
+  let cassette = dir.path().join("fetch.json");
+
+  let recorder = Arc::new(FetchRecorder::record(cassette.clone()).unwrap());
+  let client = create_http_test_client_with_recorder(recorder);
+  let chunks = ["pi", "ng"].map(|chunk| {
+    Ok::<_, deno_error::JsErrorBox>(hyper::body::Frame::data(
+      Bytes::from_static(chunk.as_bytes()),
+    ))
+  });
+  let body =
+    http_body_util::StreamBody::new(deno_core::futures::stream::iter(chunks));
+  let req = http::Request::builder()
+    .method(http::Method::POST)
+    .uri(format!("http://{}/foo", src_addr))
+    .body(crate::ReqBody::streaming(body))
+    .unwrap();
+  let resp = client.fetch(req).await.unwrap();
+  let body = resp.collect().await.unwrap().to_bytes();
+  assert_eq!(body, "ping");
+
+  let recorded = read_cassette(&cassette);
+  assert_eq!(recorded[0], serde_json::json!({ "version": 1 }));
+  let interaction = &recorded[1];
+  assert_eq!(interaction["request"]["body"], "cGluZw==");
+  assert_eq!(interaction["response"]["body"], "cGluZw==");
+  assert_eq!(interaction["response"]["bodyDecoded"], false);
+  let headers = interaction["response"]["headers"].as_array().unwrap();
+  assert!(headers.contains(&serde_json::json!(["set-cookie", "[redacted]"])));
+}
+
+#[tokio::test]
+async fn test_fetch_recorder_marks_unfinished_bodies() {
+  let src_addr = create_echo_http_server().await;
+  let dir = tempfile::tempdir().unwrap();
+  let cassette = dir.path().join("fetch.json");
+  let url = format!("http://{}/foo", src_addr);
+  let request = || {
+    http::Request::builder()
+      .method(http::Method::POST)
+      .uri(&url)
+      .body(crate::ReqBody::full(Bytes::from_static(b"ping")))
+      .unwrap()
+  };
+
+  // The response body is dropped without being read.
+  let recorder = Arc::new(FetchRecorder::record(cassette.clone()).unwrap());
+  let client = create_http_test_client_with_recorder(recorder);
+  drop(client.fetch(request()).await.unwrap());
+
+  let recorded = read_cassette_when_written(&cassette, 2).await;
+  assert_eq!(
+    recorded[1]["response"]["error"],
+    "The response body was not read to the end"
+  );
+
+  // It is replayed as a failed body rather than an empty one.
+  let recorder = Arc::new(FetchRecorder::replay(cassette.clone()).unwrap());
+  let client = create_http_test_client_with_recorder(recorder);
+  let resp = client.fetch(request()).await.unwrap();
+  assert_eq!(resp.status(), http::StatusCode::OK);
+  assert!(resp.collect().await.is_err());
+}
+
+fn read_cassette(path: &std::path::Path) -> Vec<serde_json::Value> {
+  std::fs::read_to_string(path)
+    .unwrap()
+    .lines()
+    .map(|line| serde_json::from_str(line).unwrap())
+    .collect()
+}
+
+/// Reads the cassette at `path` once it holds `lines` lines, as the
+/// interactions of dropped bodies are written in the background.
+async fn read_cassette_when_written(
+  path: &std::path::Path,
+  lines: usize,
+) -> Vec<serde_json::Value> {
+  for _ in 0..100 {
+    let data = std::fs::read_to_string(path).unwrap();
+    if data.ends_with('\n') && data.lines().count() == lines {
+      return read_cassette(path);
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+  }
+  panic!("the cassette was not written");
+}
+
+#[tokio::test]
+async fn test_http_cache_serves_fresh_responses() {
+  let hits = Arc::new(AtomicUsize::new(0));
+  let src_addr = create_caching_http_server(hits.clone()).await;
+  let client =
+    create_http_test_client_with_cache(Arc::new(HttpCache::in_memory(1024)));
+  let url = format!("http://{}/fresh", src_addr);
+
+  let (first, _) = fetch_cached(&client, &url, None).await;
+  let (second, parts) = fetch_cached(&client, &url, None).await;
+  assert_eq!(first, "1");
+  assert_eq!(second, "1");
+  assert!(parts.headers.contains_key(AGE));
+  assert_eq!(hits.load(SeqCst), 1);
+
+  // `reload` goes to the network, and stores the new response.
+  let (body, _) =
+    fetch_cached(&client, &url, Some(RequestCacheMode::Reload)).await;
+  assert_eq!(body, "2");
+  let (body, _) = fetch_cached(&client, &url, None).await;
+  assert_eq!(body, "2");
+  // `no-store` neither uses nor updates the cache.
+  let (body, _) =
+    fetch_cached(&client, &url, Some(RequestCacheMode::NoStore)).await;
+  assert_eq!(body, "3");
+  let (body, _) = fetch_cached(&client, &url, None).await;
+  assert_eq!(body, "2");
+
+  // A successful unsafe request invalidates the stored response.
+  let req = http::Request::builder()
+    .method(http::Method::POST)
+    .uri(&url)
+    .body(crate::ReqBody::empty())
+    .unwrap();
+  client.clone().fetch(req).await.unwrap();
+  let (body, _) = fetch_cached(&client, &url, None).await;
+  assert_eq!(body, "5");
+}
+
+#[tokio::test]
+async fn test_http_cache_revalidates_stale_responses() {
+  let hits = Arc::new(AtomicUsize::new(0));
+  let src_addr = create_caching_http_server(hits.clone()).await;
+  let client =
+    create_http_test_client_with_cache(Arc::new(HttpCache::in_memory(1024)));
+  let url = format!("http://{}/etag", src_addr);
+
+  let (first, _) = fetch_cached(&client, &url, None).await;
+  let (second, parts) = fetch_cached(&client, &url, None).await;
+  assert_eq!(first, "1");
+  // The server answered `304 Not Modified`, so the stored body is served.
+  assert_eq!(second, "1");
+  assert_eq!(parts.status, http::StatusCode::OK);
+  assert_eq!(hits.load(SeqCst), 2);
+
+  // `force-cache` uses the stored response without revalidating it.
+  let (body, _) =
+    fetch_cached(&client, &url, Some(RequestCacheMode::ForceCache)).await;
+  assert_eq!(body, "1");
+  assert_eq!(hits.load(SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_http_cache_selects_variants_and_skips_no_store() {
+  let hits = Arc::new(AtomicUsize::new(0));
+  let src_addr = create_caching_http_server(hits.clone()).await;
+  let client =
+    create_http_test_client_with_cache(Arc::new(HttpCache::in_memory(1024)));
+
+  let url = format!("http://{}/vary", src_addr);
+  for language in ["en", "de", "en", "de"] {
+    let req = http::Request::builder()
+      .uri(&url)
+      .header(http::header::ACCEPT_LANGUAGE, language)
+      .body(crate::ReqBody::empty())
+      .unwrap();
+    let resp = client.clone().fetch(req).await.unwrap();
+    let body = resp.collect().await.unwrap().to_bytes();
+    assert_eq!(body, language);
+  }
+  assert_eq!(hits.load(SeqCst), 2);
+
+  let url = format!("http://{}/no-store", src_addr);
+  fetch_cached(&client, &url, None).await;
+  fetch_cached(&client, &url, None).await;
+  assert_eq!(hits.load(SeqCst), 4);
+
+  let url = format!("http://{}/missing", src_addr);
+  let req = http::Request::builder()
+    .uri(&url)
+    .extension(RequestCacheMode::OnlyIfCached)
+    .body(crate::ReqBody::empty())
+    .unwrap();
+  let err = client.clone().fetch(req).await.unwrap_err();
+  assert!(matches!(err, FetchError::NetworkError));
+  assert_eq!(hits.load(SeqCst), 4);
+}
+
+#[tokio::test]
+async fn test_http_cache_revalidates_in_the_background_once() {
+  let hits = Arc::new(AtomicUsize::new(0));
+  let src_addr = create_caching_http_server(hits.clone()).await;
+  let client =
+    create_http_test_client_with_cache(Arc::new(HttpCache::in_memory(1024)));
+  let url = format!("http://{}/swr", src_addr);
+
+  let (body, _) = fetch_cached(&client, &url, None).await;
+  assert_eq!(body, "1");
+  // Stale responses are served right away, while a single revalidation runs.
+  for _ in 0..5 {
+    let (body, _) = fetch_cached(&client, &url, None).await;
+    assert_eq!(body, "1");
+  }
+  tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+  assert_eq!(hits.load(SeqCst), 2);
+  let (body, _) = fetch_cached(&client, &url, None).await;
+  assert_eq!(body, "2");
+}
+
+async fn fetch_cached(
+  client: &crate::Client,
+  url: &str,
+  mode: Option<RequestCacheMode>,
+) -> (Bytes, http::response::Parts) {
+  let mut req = http::Request::builder()
+    .uri(url)
+    .body(crate::ReqBody::empty())
+    .unwrap();
+  if let Some(mode) = mode {
+    req.extensions_mut().insert(mode);
+  }
+  let resp = client.clone().fetch(req).await.unwrap();
+  let (parts, body) = resp.into_parts();
+  (body.collect().await.unwrap().to_bytes(), parts)
+}
+
+fn create_http_test_client_with_recorder(
+  recorder: Arc<FetchRecorder>,
+) -> crate::Client {
+  install_default_crypto_provider();
+
+  create_http_client(
+    "fetch/test",
+    CreateHttpClientOptions {
+      unsafely_ignore_certificate_errors: Some(vec![]),
+      recorder: Some(recorder),
+      ..Default::default()
+    },
+  )
+  .unwrap()
+}
+
+fn create_http_test_client_with_cache(cache: Arc<HttpCache>) -> crate::Client {
+  install_default_crypto_provider();
+
+  create_http_client(
+    "fetch/test",
+    CreateHttpClientOptions {
+      unsafely_ignore_certificate_errors: Some(vec![]),
+      cache: Some(cache),
+      ..Default::default()
+    },
+  )
+  .unwrap()
+}
+
 fn create_http_test_client() -> crate::Client {
   install_default_crypto_provider();
 
@@ -671,6 +1021,9 @@ fn create_http_test_client() -> crate::Client {
       local_address: None,
       client_builder_hook: None,
       http2_max_header_list_size: None,
+      recorder: None,
+      cache: None,
+      timeouts: Default::default(),
       permissions: None,
       resolved_deny_check_kind: Default::default(),
     },
@@ -721,6 +1074,122 @@ async fn create_encoded_http_server(
   addr
 }
 
+/// Responds to every request with its own body, once it has been received,
+/// and sets a cookie.
+async fn create_echo_http_server() -> SocketAddr {
+  let tcp = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let addr = tcp.local_addr().unwrap();
+
+  tokio::spawn(async move {
+    while let Ok((sock, _)) = tcp.accept().await {
+      let fut = hyper::server::conn::http1::Builder::new().serve_connection(
+        hyper_util::rt::TokioIo::new(sock),
+        hyper::service::service_fn(
+          |req: http::Request<hyper::body::Incoming>| async move {
+            let body = req.into_body().collect().await?.to_bytes();
+            Ok::<_, hyper::Error>(
+              http::Response::builder()
+                .header(SET_COOKIE, "session=secret")
+                .body(http_body_util::Full::new(body))
+                .unwrap(),
+            )
+          },
+        ),
+      );
+      tokio::spawn(fut);
+    }
+  });
+
+  addr
+}
+
+/// Responds with the number of requests it has received so far, with caching
+/// headers chosen by the path:
+/// - `/fresh` is fresh for a minute.
+/// - `/etag` must be revalidated, and is answered with `304 Not Modified`
+///   when it is.
+/// - `/vary` varies on, and responds with, the `Accept-Language` header.
+/// - `/no-store` must not be stored.
+async fn create_caching_http_server(hits: Arc<AtomicUsize>) -> SocketAddr {
+  let tcp = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+  let addr = tcp.local_addr().unwrap();
+
+  tokio::spawn(async move {
+    while let Ok((sock, _)) = tcp.accept().await {
+      let hits = hits.clone();
+      let fut = hyper::server::conn::http1::Builder::new().serve_connection(
+        hyper_util::rt::TokioIo::new(sock),
+        hyper::service::service_fn(move |req: http::Request<_>| {
+          let hit = hits.fetch_add(1, SeqCst) + 1;
+          let builder = http::Response::builder();
+          let (builder, body) = match req.uri().path() {
+            "/fresh" => (
+              builder.header(http::header::CACHE_CONTROL, "max-age=60"),
+              hit.to_string(),
+            ),
+            "/etag"
+              if req.headers().get(http::header::IF_NONE_MATCH)
+                == Some(&HeaderValue::from_static("\"v1\"")) =>
+            {
+              (
+                builder.status(http::StatusCode::NOT_MODIFIED),
+                String::new(),
+              )
+            }
+            "/etag" => (
+              builder
+                .header(http::header::CACHE_CONTROL, "no-cache")
+                .header(http::header::ETAG, "\"v1\""),
+              hit.to_string(),
+            ),
+            "/vary" => (
+              builder
+                .header(http::header::CACHE_CONTROL, "max-age=60")
+                .header(http::header::VARY, "Accept-Language"),
+              req
+                .headers()
+                .get(http::header::ACCEPT_LANGUAGE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string(),
+            ),
+            "/no-store" => (
+              builder
+                .header(http::header::CACHE_CONTROL, "no-store, max-age=60"),
+              hit.to_string(),
+            ),
+            "/swr" => (
+              builder.header(
+                http::header::CACHE_CONTROL,
+                "max-age=0, stale-while-revalidate=60",
+              ),
+              hit.to_string(),
+            ),
+            _ => (builder.status(http::StatusCode::NOT_FOUND), String::new()),
+          };
+          // Keep revalidations in flight for a while.
+          let delay = if req.uri().path() == "/swr" && hit > 1 {
+            std::time::Duration::from_millis(200)
+          } else {
+            std::time::Duration::ZERO
+          };
+          async move {
+            tokio::time::sleep(delay).await;
+            Ok::<_, std::convert::Infallible>(
+              builder
+                .body(http_body_util::Full::new(Bytes::from(body)))
+                .unwrap(),
+            )
+          }
+        }),
+      );
+      tokio::spawn(fut);
+    }
+  });
+
+  addr
+}
+
 async fn create_chunked_gzip_http_server(
   captured_accept_encoding: Arc<Mutex<Option<String>>>,
 ) -> SocketAddr {
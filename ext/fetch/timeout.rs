@@ -0,0 +1,411 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! Timeouts of `Deno.HttpClient` requests.
+//!
+//! Each phase of a request can be bounded separately: opening the
+//! connection, the TLS handshake, waiting for the response headers, and
+//! waiting for the next chunk of the response body. A total timeout bounds
+//! the request from start to the end of the response body. Each fails with
+//! its own error class, so callers can tell which phase to retry.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+
+use bytes::Bytes;
+use deno_error::JsErrorBox;
+use deno_error::JsErrorClass;
+use http::Uri;
+use hyper::body::Body;
+use hyper::body::Frame;
+use hyper::body::SizeHint;
+use tokio::sync::oneshot;
+use tokio::time::Instant;
+use tokio::time::Sleep;
+use tower::BoxError;
+use tower_service::Service;
+
+use crate::FetchError;
+use crate::ResBody;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HttpClientTimeouts {
+  /// Resolving the host name and opening the connection.
+  pub connect: Option<Duration>,
+  /// The TLS handshake, once the connection is open.
+  pub tls_handshake: Option<Duration>,
+  /// From sending the request, connecting included, until the response
+  /// headers are received.
+  pub response_headers: Option<Duration>,
+  /// Between two chunks of the response body.
+  pub idle_read: Option<Duration>,
+  /// From sending the request until the response body is fully received.
+  pub total: Option<Duration>,
+}
+
+impl HttpClientTimeouts {
+  fn is_empty(&self) -> bool {
+    *self == Self::default()
+  }
+}
+
+#[derive(Clone, Copy, Debug, thiserror::Error, deno_error::JsError)]
+pub enum TimeoutError {
+  #[class("ConnectTimeout")]
+  #[error("Connecting timed out after {}ms", .0.as_millis())]
+  Connect(Duration),
+  #[class("TlsHandshakeTimeout")]
+  #[error("TLS handshake timed out after {}ms", .0.as_millis())]
+  TlsHandshake(Duration),
+  #[class("ResponseHeadersTimeout")]
+  #[error("Response headers not received within {}ms", .0.as_millis())]
+  ResponseHeaders(Duration),
+  #[class("IdleReadTimeout")]
+  #[error("No response body data received for {}ms", .0.as_millis())]
+  IdleRead(Duration),
+  #[class("TotalTimeout")]
+  #[error("Request did not complete within {}ms", .0.as_millis())]
+  Total(Duration),
+}
+
+impl TimeoutError {
+  /// The timeout error in the source chain of `err`, if any.
+  fn find(err: &(dyn std::error::Error + 'static)) -> Option<Self> {
+    let mut source = Some(err);
+    while let Some(err) = source {
+      if let Some(err) = err.downcast_ref::<TimeoutError>() {
+        return Some(*err);
+      }
+      source = err.source();
+    }
+    None
+  }
+
+  /// Whether `err`, a response body error, is an idle read or total timeout.
+  pub(crate) fn is_body_timeout(err: &JsErrorBox) -> bool {
+    matches!(&*err.get_class(), "IdleReadTimeout" | "TotalTimeout")
+  }
+}
+
+/// Runs `future`, failing with `error` if it does not complete within
+/// `timeout`.
+pub(crate) async fn with_timeout<F, T, E>(
+  timeout: Option<Duration>,
+  error: fn(Duration) -> TimeoutError,
+  future: F,
+) -> Result<T, BoxError>
+where
+  F: Future<Output = Result<T, E>>,
+  E: Into<BoxError>,
+{
+  match timeout {
+    Some(timeout) => match tokio::time::timeout(timeout, future).await {
+      Ok(res) => res.map_err(Into::into),
+      Err(_) => Err(error(timeout).into()),
+    },
+    None => future.await.map_err(Into::into),
+  }
+}
+
+/// Runs the `connecting` future of an `HttpsConnector` wrapping a
+/// [`TimedConnect`], bounding the TLS handshake that follows the signal on
+/// `connected` by `timeout`.
+pub(crate) async fn with_handshake_timeout<F, T, E>(
+  timeout: Option<Duration>,
+  connected: oneshot::Receiver<()>,
+  connecting: F,
+) -> Result<T, BoxError>
+where
+  F: Future<Output = Result<T, E>>,
+  E: Into<BoxError>,
+{
+  let Some(timeout) = timeout else {
+    return connecting.await.map_err(Into::into);
+  };
+  tokio::pin!(connecting);
+  tokio::select! {
+    biased;
+    res = &mut connecting => return res.map_err(Into::into),
+    _ = connected => {}
+  }
+  with_timeout(Some(timeout), TimeoutError::TlsHandshake, connecting).await
+}
+
+/// Wraps the connector underneath an `HttpsConnector`, bounding the time to
+/// connect and signalling once connected so the TLS handshake can be timed
+/// on its own.
+pub(crate) struct TimedConnect<C> {
+  inner: C,
+  timeout: Option<Duration>,
+  connected: Option<oneshot::Sender<()>>,
+}
+
+impl<C> TimedConnect<C> {
+  pub(crate) fn new(
+    inner: C,
+    timeout: Option<Duration>,
+  ) -> (Self, oneshot::Receiver<()>) {
+    let (tx, rx) = oneshot::channel();
+    let connector = Self {
+      inner,
+      timeout,
+      connected: Some(tx),
+    };
+    (connector, rx)
+  }
+}
+
+impl<C> Service<Uri> for TimedConnect<C>
+where
+  C: Service<Uri>,
+  C::Future: Send + 'static,
+  C::Response: Send + 'static,
+  C::Error: Into<BoxError>,
+{
+  type Response = C::Response;
+  type Error = BoxError;
+  type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+
+  fn poll_ready(
+    &mut self,
+    cx: &mut Context<'_>,
+  ) -> Poll<Result<(), Self::Error>> {
+    self.inner.poll_ready(cx).map_err(Into::into)
+  }
+
+  fn call(&mut self, dst: Uri) -> Self::Future {
+    let connecting = self.inner.call(dst);
+    let timeout = self.timeout;
+    let connected = self.connected.take();
+    Box::pin(async move {
+      let io = with_timeout(timeout, TimeoutError::Connect, connecting).await?;
+      if let Some(connected) = connected {
+        let _ = connected.send(());
+      }
+      Ok(io)
+    })
+  }
+}
+
+/// Sends a request with `fetching`, applying the response headers, idle read
+/// and total timeouts.
+pub(crate) async fn fetch<F>(
+  timeouts: HttpClientTimeouts,
+  fetching: F,
+) -> Result<http::Response<ResBody>, FetchError>
+where
+  F: Future<Output = Result<http::Response<ResBody>, FetchError>>,
+{
+  if timeouts.is_empty() {
+    return fetching.await;
+  }
+  let deadline = timeouts
+    .total
+    .map(|total| (Instant::now() + total, TimeoutError::Total(total)));
+  let headers_timeout = match (timeouts.response_headers, timeouts.total) {
+    (Some(headers), Some(total)) if total < headers => {
+      Some((total, TimeoutError::Total(total)))
+    }
+    (Some(headers), _) => {
+      Some((headers, TimeoutError::ResponseHeaders(headers)))
+    }
+    (None, Some(total)) => Some((total, TimeoutError::Total(total))),
+    (None, None) => None,
+  };
+  let res = match headers_timeout {
+    Some((timeout, error)) => tokio::time::timeout(timeout, fetching)
+      .await
+      .map_err(|_| FetchError::Timeout(error))?,
+    None => fetching.await,
+  };
+  let res = res.map_err(|err| match err {
+    FetchError::ClientSend(err) => match TimeoutError::find(&err.source) {
+      Some(timeout) => FetchError::Timeout(timeout),
+      None => FetchError::ClientSend(err),
+    },
+    err => err,
+  })?;
+  if timeouts.idle_read.is_none() && deadline.is_none() {
+    return Ok(res);
+  }
+  Ok(res.map(|body| {
+    ResBody::new(TimeoutBody {
+      inner: body,
+      idle_read: timeouts.idle_read,
+      deadline,
+      sleep: None,
+      timed_out: false,
+    })
+  }))
+}
+
+/// A response body failing once no data is received for the idle read
+/// timeout, or the total deadline passes.
+struct TimeoutBody {
+  inner: ResBody,
+  idle_read: Option<Duration>,
+  deadline: Option<(Instant, TimeoutError)>,
+  /// The timer running while waiting for the next frame.
+  sleep: Option<(Pin<Box<Sleep>>, TimeoutError)>,
+  timed_out: bool,
+}
+
+impl Body for TimeoutBody {
+  type Data = Bytes;
+  type Error = JsErrorBox;
+
+  fn poll_frame(
+    self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+    let this = self.get_mut();
+    if this.timed_out {
+      return Poll::Ready(None);
+    }
+    if let Poll::Ready(frame) = Pin::new(&mut this.inner).poll_frame(cx) {
+      this.sleep = None;
+      return Poll::Ready(frame);
+    }
+    let (sleep, error) = this.sleep.get_or_insert_with(|| {
+      let idle_read = this.idle_read.map(|idle_read| {
+        (
+          Instant::now() + idle_read,
+          TimeoutError::IdleRead(idle_read),
+        )
+      });
+      let (at, error) = match (idle_read, this.deadline) {
+        (Some(idle_read), Some(deadline)) if deadline.0 < idle_read.0 => {
+          deadline
+        }
+        (Some(idle_read), _) => idle_read,
+        (None, Some(deadline)) => deadline,
+        (None, None) => unreachable!("a timeout body without a timeout"),
+      };
+      (Box::pin(tokio::time::sleep_until(at)), error)
+    });
+    if sleep.as_mut().poll(cx).is_pending() {
+      return Poll::Pending;
+    }
+    let error = *error;
+    this.sleep = None;
+    this.timed_out = true;
+    Poll::Ready(Some(Err(JsErrorBox::from_err(error))))
+  }
+
+  fn is_end_stream(&self) -> bool {
+    self.timed_out || self.inner.is_end_stream()
+  }
+
+  fn size_hint(&self) -> SizeHint {
+    self.inner.size_hint()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use deno_core::futures::StreamExt;
+  use http_body_util::BodyExt;
+  use http_body_util::StreamBody;
+
+  use super::*;
+
+  fn stalled_body(chunks: usize) -> ResBody {
+    let stream = deno_core::futures::stream::iter(
+      (0..chunks).map(|_| Frame::data(Bytes::from_static(b"chunk"))),
+    )
+    .chain(deno_core::futures::stream::pending());
+    ResBody::new(StreamBody::new(stream.map(Ok::<_, JsErrorBox>)))
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn idle_read_timeout() {
+    let timeouts = HttpClientTimeouts {
+      idle_read: Some(Duration::from_secs(5)),
+      ..Default::default()
+    };
+    let res =
+      fetch(timeouts, async { Ok(http::Response::new(stalled_body(2))) })
+        .await
+        .unwrap();
+    let mut body = res.into_body();
+    for _ in 0..2 {
+      let frame = body.frame().await.unwrap().unwrap();
+      assert_eq!(frame.into_data().unwrap(), "chunk");
+    }
+    let err = body.frame().await.unwrap().unwrap_err();
+    assert_eq!(err.get_class(), "IdleReadTimeout");
+    assert!(body.frame().await.is_none());
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn response_headers_and_total_timeouts() {
+    let timeouts = HttpClientTimeouts {
+      response_headers: Some(Duration::from_secs(5)),
+      ..Default::default()
+    };
+    let err = fetch(timeouts, deno_core::futures::future::pending())
+      .await
+      .unwrap_err();
+    assert!(matches!(
+      err,
+      FetchError::Timeout(TimeoutError::ResponseHeaders(_))
+    ));
+
+    // The total timeout applies to the headers when it is shorter, and to
+    // the body after them.
+    let timeouts = HttpClientTimeouts {
+      response_headers: Some(Duration::from_secs(5)),
+      total: Some(Duration::from_secs(2)),
+      ..Default::default()
+    };
+    let err = fetch(timeouts, deno_core::futures::future::pending())
+      .await
+      .unwrap_err();
+    assert!(matches!(err, FetchError::Timeout(TimeoutError::Total(_))));
+    let res =
+      fetch(timeouts, async { Ok(http::Response::new(stalled_body(1))) })
+        .await
+        .unwrap();
+    let mut body = res.into_body();
+    assert!(body.frame().await.unwrap().is_ok());
+    let err = body.frame().await.unwrap().unwrap_err();
+    assert_eq!(err.get_class(), "TotalTimeout");
+  }
+
+  #[tokio::test(start_paused = true)]
+  async fn connect_and_handshake_timeouts() {
+    let err = with_timeout(
+      Some(Duration::from_secs(1)),
+      TimeoutError::Connect,
+      deno_core::futures::future::pending::<Result<(), BoxError>>(),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(
+      TimeoutError::find(&*err),
+      Some(TimeoutError::Connect(_))
+    ));
+
+    // The handshake is only timed once connected.
+    let (tx, rx) = oneshot::channel();
+    let connecting = async move {
+      tokio::time::sleep(Duration::from_secs(10)).await;
+      tx.send(()).unwrap();
+      deno_core::futures::future::pending::<Result<(), BoxError>>().await
+    };
+    let started = Instant::now();
+    let err =
+      with_handshake_timeout(Some(Duration::from_secs(1)), rx, connecting)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+      TimeoutError::find(&*err),
+      Some(TimeoutError::TlsHandshake(_))
+    ));
+    assert!(started.elapsed() >= Duration::from_secs(11));
+  }
+}
@@ -27,6 +27,7 @@ use deno_error::JsErrorBox;
 use deno_io::fs::FileResource;
 use deno_io::fs::FsError;
 use deno_io::fs::FsStat;
+use deno_io::fs::with_write_quota;
 use deno_permissions::CheckedPath;
 use deno_permissions::CheckedPathBuf;
 use deno_permissions::OpenAccessKind;
@@ -199,14 +200,16 @@ pub fn op_fs_open_sync(
   let path = Path::new(path);
 
   let fs = state.borrow::<FileSystemRc>().clone();
-  let path = state
-    .borrow_mut::<deno_permissions::PermissionsContainer>()
-    .check_open(
-      Cow::Borrowed(path),
-      options.access_kind(),
-      Some("Deno.openSync()"),
-    )?;
+  let permissions =
+    state.borrow_mut::<deno_permissions::PermissionsContainer>();
+  let path = permissions.check_open(
+    Cow::Borrowed(path),
+    options.access_kind(),
+    Some("Deno.openSync()"),
+  )?;
+  let write_quota = permissions.write_quota(&path, options.access_kind())?;
   let file = fs.open_sync(&path, options).context_path("open", &path)?;
+  let file = with_write_quota(file, write_quota);
   let rid = state
     .resource_table
     .add(FileResource::new(file, "fsFile".to_string()));
@@ -226,23 +229,23 @@ pub async fn op_fs_open_async(
   };
   let path = PathBuf::from(path);
 
-  let (fs, path) = {
+  let (fs, path, write_quota) = {
     let mut state = state.borrow_mut();
-    (
-      state.borrow::<FileSystemRc>().clone(),
-      state
-        .borrow_mut::<deno_permissions::PermissionsContainer>()
-        .check_open(
-          Cow::Owned(path),
-          options.access_kind(),
-          Some("Deno.open()"),
-        )?,
-    )
+    let permissions =
+      state.borrow_mut::<deno_permissions::PermissionsContainer>();
+    let path = permissions.check_open(
+      Cow::Owned(path),
+      options.access_kind(),
+      Some("Deno.open()"),
+    )?;
+    let write_quota = permissions.write_quota(&path, options.access_kind())?;
+    (state.borrow::<FileSystemRc>().clone(), path, write_quota)
   };
   let file = fs
     .open_async(path.as_owned(), options)
     .await
     .context_path("open", &path)?;
+  let file = with_write_quota(file, write_quota);
 
   let rid = state
     .borrow_mut()
@@ -562,8 +565,13 @@ pub fn op_fs_copy_file_sync(
     OpenAccessKind::WriteNoFollow,
     Some("Deno.copyFileSync()"),
   )?;
+  let write_quota = permissions.write_quota(&to, OpenAccessKind::Write)?;
 
   let fs = state.borrow::<FileSystemRc>();
+  if let Some(write_quota) = write_quota {
+    let size = fs.stat_sync(&from).context_path("stat", &from)?.size;
+    write_quota.consume(size)?;
+  }
   fs.copy_file_sync(&from, &to)
     .context_two_path("copy", &from, &to)?;
 
@@ -576,7 +584,7 @@ pub async fn op_fs_copy_file_async(
   #[string] from: String,
   #[string] to: String,
 ) -> Result<(), FsOpsError> {
-  let (fs, from, to) = {
+  let (fs, from, to, write_quota) = {
     let mut state = state.borrow_mut();
     let permissions =
       state.borrow_mut::<deno_permissions::PermissionsContainer>();
@@ -590,8 +598,22 @@ pub async fn op_fs_copy_file_async(
       OpenAccessKind::WriteNoFollow,
       Some("Deno.copyFile()"),
     )?;
-    (state.borrow::<FileSystemRc>().clone(), from, to)
+    let write_quota = permissions.write_quota(&to, OpenAccessKind::Write)?;
+    (
+      state.borrow::<FileSystemRc>().clone(),
+      from,
+      to,
+      write_quota,
+    )
   };
+  if let Some(write_quota) = write_quota {
+    let size = fs
+      .stat_async(from.as_owned())
+      .await
+      .context_path("stat", &from)?
+      .size;
+    write_quota.consume(size)?;
+  }
   fs.copy_file_async(from.as_owned(), to.as_owned())
     .await
     .context_two_path("copy", &from, &to)?;
@@ -1042,17 +1064,25 @@ pub fn op_fs_truncate_sync(
   #[string] path: &str,
   #[number] len: u64,
 ) -> Result<(), FsOpsError> {
-  let path = state
-    .borrow_mut::<deno_permissions::PermissionsContainer>()
-    .check_open(
-      Cow::Borrowed(Path::new(path)),
-      OpenAccessKind::WriteNoFollow,
-      Some("Deno.truncateSync()"),
-    )?;
+  let permissions = state.borrow::<deno_permissions::PermissionsContainer>();
+  let path = permissions.check_open(
+    Cow::Borrowed(Path::new(path)),
+    OpenAccessKind::WriteNoFollow,
+    Some("Deno.truncateSync()"),
+  )?;
 
   let fs = state.borrow::<FileSystemRc>();
-  fs.truncate_sync(&path, len)
-    .context_path("truncate", &path)?;
+  // growing the file writes zeroes, which count against the quotas
+  let growth = fs
+    .stat_sync(&path)
+    .map(|stat| len.saturating_sub(stat.size))
+    .unwrap_or(len);
+  permissions.consume_write_quota(&path, growth)?;
+  let result = fs.truncate_sync(&path, len);
+  if result.is_err() {
+    permissions.release_write_quota(&path, growth);
+  }
+  result.context_path("truncate", &path)?;
 
   Ok(())
 }
@@ -1064,9 +1094,9 @@ pub async fn op_fs_truncate_async(
   #[number] len: u64,
 ) -> Result<(), FsOpsError> {
   let (fs, path) = {
-    let mut state = state.borrow_mut();
+    let state = state.borrow();
     let path = state
-      .borrow_mut::<deno_permissions::PermissionsContainer>()
+      .borrow::<deno_permissions::PermissionsContainer>()
       .check_open(
         Cow::Owned(PathBuf::from(path)),
         OpenAccessKind::WriteNoFollow,
@@ -1075,9 +1105,24 @@ pub async fn op_fs_truncate_async(
     (state.borrow::<FileSystemRc>().clone(), path)
   };
 
-  fs.truncate_async(path.as_owned(), len)
+  // growing the file writes zeroes, which count against the quotas
+  let growth = fs
+    .stat_async(path.as_owned())
     .await
-    .context_path("truncate", &path)?;
+    .map(|stat| len.saturating_sub(stat.size))
+    .unwrap_or(len);
+  state
+    .borrow()
+    .borrow::<deno_permissions::PermissionsContainer>()
+    .consume_write_quota(&path, growth)?;
+  let result = fs.truncate_async(path.as_owned(), len).await;
+  if result.is_err() {
+    state
+      .borrow()
+      .borrow::<deno_permissions::PermissionsContainer>()
+      .release_write_quota(&path, growth);
+  }
+  result.context_path("truncate", &path)?;
 
   Ok(())
 }
@@ -1461,16 +1506,19 @@ pub fn op_fs_write_file_sync(
 
   let options = OpenOptions::write(create, append, create_new, mode);
   let fs = state.borrow::<FileSystemRc>().clone();
-  let path = state
-    .borrow::<deno_permissions::PermissionsContainer>()
-    .check_open(
-      Cow::Borrowed(path),
-      OpenAccessKind::Write,
-      Some("Deno.writeFileSync()"),
-    )?;
+  let permissions = state.borrow::<deno_permissions::PermissionsContainer>();
+  let path = permissions.check_open(
+    Cow::Borrowed(path),
+    OpenAccessKind::Write,
+    Some("Deno.writeFileSync()"),
+  )?;
+  permissions.consume_write_quota(&path, data.len() as u64)?;
 
-  fs.write_file_sync(&path, options, &data)
-    .context_path("writefile", &path)?;
+  let result = fs.write_file_sync(&path, options, &data);
+  if result.is_err() {
+    permissions.release_write_quota(&path, data.len() as u64);
+  }
+  result.context_path("writefile", &path)?;
 
   Ok(())
 }
@@ -1495,16 +1543,23 @@ pub async fn op_fs_write_file_async(
     let state = state.borrow_mut();
     let cancel_handle = cancel_rid
       .and_then(|rid| state.resource_table.get::<CancelHandle>(rid).ok());
-    let path = state
-      .borrow::<deno_permissions::PermissionsContainer>()
-      .check_open(
-        Cow::Owned(path),
-        OpenAccessKind::Write,
-        Some("Deno.writeFile()"),
-      )?;
+    let permissions = state.borrow::<deno_permissions::PermissionsContainer>();
+    let path = permissions.check_open(
+      Cow::Owned(path),
+      OpenAccessKind::Write,
+      Some("Deno.writeFile()"),
+    )?;
+    permissions.consume_write_quota(&path, data.len() as u64)?;
     (state.borrow::<FileSystemRc>().clone(), cancel_handle, path)
   };
 
+  let len = data.len() as u64;
+  let release_quota = || {
+    state
+      .borrow()
+      .borrow::<deno_permissions::PermissionsContainer>()
+      .release_write_quota(&path, len);
+  };
   let fut = fs.write_file_async(path.as_owned(), options, data.0);
 
   if let Some(cancel_handle) = cancel_handle {
@@ -1516,9 +1571,16 @@ pub async fn op_fs_write_file_async(
       res.close();
     };
 
+    if !matches!(res, Ok(Ok(()))) {
+      release_quota();
+    }
     res?.context_path("writefile", &path)?;
   } else {
-    fut.await.context_path("writefile", &path)?;
+    let res = fut.await;
+    if res.is_err() {
+      release_quota();
+    }
+    res.context_path("writefile", &path)?;
   }
 
   Ok(())
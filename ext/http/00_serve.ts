@@ -26,6 +26,7 @@ const {
   op_http_serve,
   op_http_serve_address_override,
   op_http_serve_default_compression,
+  op_http_serve_h3,
   op_http_serve_on,
   op_http_set_promise_complete,
   op_http_set_response_native,
@@ -113,6 +114,7 @@ const {
 const { hasTlsKeyPairOptions, listenTls } = core.loadExtScript(
   "ext:deno_net/02_tls.js",
 );
+const loadQuic = core.createLazyLoader("ext:deno_net/03_quic.js");
 const {
   otelState,
   builtinTracer,
@@ -239,7 +241,7 @@ class InnerRequest {
     }
   }
 
-  _wantsUpgrade(upgradeType) {
+  _wantsUpgrade(upgradeType, extensions = null, perMessageDeflate = null) {
     if (this.#upgraded) {
       throw new Deno.errors.Http("Already upgraded");
     }
@@ -257,7 +259,11 @@ class InnerRequest {
 
       this.#upgraded = true;
 
-      return op_http_upgrade_websocket_next(external);
+      return op_http_upgrade_websocket_next(
+        external,
+        extensions,
+        perMessageDeflate,
+      );
     }
   }
 
@@ -446,6 +452,7 @@ class CallbackContext {
   /** @type {Promise<void> | undefined} */
   closing;
   listener;
+  quicEndpoint;
   asyncContextSnapshot;
   legacyAbort;
 
@@ -472,6 +479,7 @@ class CallbackContext {
     try {
       this.closed = true;
       core.tryClose(this.serverRid);
+      this.quicEndpoint?.close();
     } catch {
       // Pass
     }
@@ -1394,7 +1402,14 @@ function serveInner(options, handler) {
     );
   }
 
+  if (options.http3 && !wantsHttps) {
+    throw new TypeError(
+      "Both 'cert' and 'key' must be provided to enable HTTP/3",
+    );
+  }
+
   let listener;
+  let quicListener;
   if (wantsHttps) {
     if (!options.cert || !options.key) {
       throw new TypeError(
@@ -1406,6 +1421,24 @@ function serveInner(options, handler) {
     listenOpts.alpnProtocols = ["h2", "http/1.1"];
     listener = listenTls(listenOpts);
     listenOpts.port = listener.addr.port;
+    if (options.http3) {
+      // HTTP/3 is served over UDP on the same port as the TLS listener.
+      try {
+        const { QuicEndpoint } = loadQuic();
+        const endpoint = new QuicEndpoint({
+          hostname: listenOpts.hostname,
+          port: listenOpts.port,
+        });
+        quicListener = endpoint.listen({
+          cert: options.cert,
+          key: options.key,
+          alpnProtocols: ["h3"],
+        });
+      } catch (error) {
+        listener.close();
+        throw error;
+      }
+    }
   } else {
     listener = listen(listenOpts);
     listenOpts.port = listener.addr.port;
@@ -1436,11 +1469,13 @@ function serveInner(options, handler) {
     onError,
     onListen,
     automaticCompression,
+    quicListener,
   );
 }
 
 /**
- * Serve HTTP/1.1 and/or HTTP/2 on an arbitrary listener.
+ * Serve HTTP/1.1 and/or HTTP/2 on an arbitrary listener, and HTTP/3 on
+ * `quicListener` if one is given.
  */
 function serveHttpOnListener(
   listener,
@@ -1449,6 +1484,7 @@ function serveHttpOnListener(
   onError,
   onListen,
   automaticCompression = op_http_serve_default_compression(),
+  quicListener = undefined,
 ) {
   let serverContext = undefined;
   let callback = undefined;
@@ -1491,6 +1527,14 @@ function serveHttpOnListener(
     ),
     listener,
   );
+  if (quicListener !== undefined) {
+    const { getListenerResource } = loadQuic();
+    serverContext.quicEndpoint = quicListener.endpoint;
+    op_http_serve_h3(
+      serverContext.serverRid,
+      getListenerResource(quicListener),
+    );
+  }
   callback = mapToCallback(serverContext, handler, onError);
   nativeCallback = mapToNativeResponseCallback(serverContext, handler, onError);
 
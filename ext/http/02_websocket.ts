@@ -4,6 +4,7 @@
 const { core, internals, primordials } = __bootstrap;
 const {
   op_http_websocket_accept_header,
+  op_http_websocket_extensions_header,
   op_http_ws_create_from_stream_resource,
 } = core.ops;
 const {
@@ -89,8 +90,30 @@ function upgradeWebSocket(request, options = { __proto__: null }) {
     }
   }
 
+  // permessage-deflate is only negotiated on the Deno.serve path, where the
+  // compression is handled by the server websocket.
+  let perMessageDeflate = null;
+  let extensions = null;
+  if (options.perMessageDeflate && inner._wantsUpgrade) {
+    perMessageDeflate = options.perMessageDeflate === true
+      ? {}
+      : options.perMessageDeflate;
+    // Called without offers too, so that invalid options always throw.
+    extensions = op_http_websocket_extensions_header(
+      request.headers.get("sec-websocket-extensions"),
+      perMessageDeflate,
+    );
+    if (extensions !== null) {
+      ArrayPrototypePush(r.headerList, [
+        "sec-websocket-extensions",
+        extensions,
+      ]);
+    }
+  }
+
   const {
     _eventLoop,
+    _extensions,
     _idleTimeoutDuration,
     _idleTimeoutTimeout,
     _readyState,
@@ -111,7 +134,11 @@ function upgradeWebSocket(request, options = { __proto__: null }) {
   socket[_idleTimeoutTimeout] = null;
 
   if (inner._wantsUpgrade) {
-    const wsPromise = inner._wantsUpgrade("upgradeWebSocket");
+    const wsPromise = inner._wantsUpgrade(
+      "upgradeWebSocket",
+      extensions,
+      perMessageDeflate,
+    );
 
     // Start the upgrade in the background.
     (async () => {
@@ -119,6 +146,7 @@ function upgradeWebSocket(request, options = { __proto__: null }) {
         const wsRid = await wsPromise;
 
         socket[_rid] = wsRid;
+        socket[_extensions] = extensions ?? "";
         socket[_readyState] = WebSocket.OPEN;
         installServerInspector(socket, request.url);
         const event = new Event("open");
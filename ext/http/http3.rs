@@ -0,0 +1,174 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! HTTP/3 support for `Deno.serve`.
+//!
+//! Each QUIC connection is bridged to an in-memory HTTP/2 connection that is
+//! served like any TCP connection, so HTTP/3 requests go through the same
+//! request and response plumbing as the other protocols. Request and response
+//! bodies and trailers are streamed across the bridge as they arrive.
+
+use std::future::Future;
+use std::rc::Rc;
+use std::time::Duration;
+
+use bytes::Buf;
+use bytes::Bytes;
+use deno_core::CancelFuture;
+use deno_core::CancelHandle;
+use deno_core::unsync::spawn;
+use deno_error::JsErrorBox;
+use http_body_util::BodyExt;
+use http_body_util::Channel;
+use hyper::client::conn::http2;
+use hyper_util::rt::TokioIo;
+use tokio::io::DuplexStream;
+
+use crate::HttpNextError;
+use crate::LocalExecutor;
+
+/// ALPN negotiation for "h3"
+const ALPN_HTTP_3: &str = "h3";
+
+/// How long clients may cache the `Alt-Svc` advertisement, in seconds.
+const ALT_SVC_MAX_AGE: u32 = 86400;
+
+/// Buffer size of the in-memory stream between HTTP/3 and HTTP/2.
+const BRIDGE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// How long clients get to finish their requests once the server shuts down,
+/// before the connection is closed under them.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The `H3_NO_ERROR` application error code.
+const H3_NO_ERROR: u32 = 0x100;
+
+type RequestStream =
+  h3::server::RequestStream<h3_quinn::BidiStream<Bytes>, Bytes>;
+
+type BridgeSender = http2::SendRequest<Channel<Bytes, JsErrorBox>>;
+
+/// Returns the `Alt-Svc` header value advertising HTTP/3 on `port`.
+pub(crate) fn alt_svc(port: u16) -> String {
+  format!("{ALPN_HTTP_3}=\":{port}\"; ma={ALT_SVC_MAX_AGE}")
+}
+
+pub(crate) fn http3_error(error: impl std::fmt::Display) -> HttpNextError {
+  HttpNextError::Http3(error.to_string())
+}
+
+/// Serves the HTTP/3 requests of `connection`, forwarding them to the HTTP/2
+/// connection served by `serve_bridge`. Once `cancel` is triggered no new
+/// requests are accepted, and the connection closes after the ones in flight
+/// or after `SHUTDOWN_TIMEOUT`, whichever comes first.
+pub(crate) async fn serve_connection<F, Fut>(
+  connection: quinn::Connection,
+  cancel: Rc<CancelHandle>,
+  serve_bridge: F,
+) -> Result<(), HttpNextError>
+where
+  F: FnOnce(DuplexStream) -> Fut,
+  Fut: Future<Output = Result<(), hyper::Error>> + 'static,
+{
+  let quic_connection = connection.clone();
+  let mut connection = h3::server::Connection::<_, Bytes>::new(
+    h3_quinn::Connection::new(connection),
+  )
+  .await
+  .map_err(http3_error)?;
+
+  let (client_io, server_io) = tokio::io::duplex(BRIDGE_BUFFER_SIZE);
+  spawn(serve_bridge(server_io));
+  let (sender, bridge) = http2::Builder::new(LocalExecutor)
+    .handshake(TokioIo::new(client_io))
+    .await?;
+  spawn(bridge);
+
+  loop {
+    let accepted = connection.accept().or_cancel(cancel.clone()).await;
+    let Ok(resolver) = accepted else {
+      // Refuse new requests, and let the client finish the ones in flight,
+      // without waiting on a client that keeps the connection open.
+      let drain = async {
+        connection.shutdown(0).await.map_err(http3_error)?;
+        while connection.accept().await.map_err(http3_error)?.is_some() {}
+        Ok::<_, HttpNextError>(())
+      };
+      if tokio::time::timeout(SHUTDOWN_TIMEOUT, drain).await.is_err() {
+        quic_connection.close(quinn::VarInt::from_u32(H3_NO_ERROR), b"");
+      }
+      return Ok(());
+    };
+    let Some(resolver) = resolver.map_err(http3_error)? else {
+      return Ok(());
+    };
+    let sender = sender.clone();
+    spawn(async move {
+      let result = match resolver.resolve_request().await {
+        Ok((request, stream)) => forward_request(sender, request, stream).await,
+        Err(err) => Err(http3_error(err)),
+      };
+      if let Err(err) = result {
+        log::warn!("Failed to serve HTTP/3 request: {err}");
+      }
+    });
+  }
+}
+
+/// Sends `request` over the bridge, and its response back to the client.
+async fn forward_request(
+  mut sender: BridgeSender,
+  request: http::Request<()>,
+  stream: RequestStream,
+) -> Result<(), HttpNextError> {
+  let (mut send, mut recv) = stream.split();
+
+  let (mut body_tx, body) = Channel::new(1);
+  spawn(async move {
+    loop {
+      match recv.recv_data().await {
+        Ok(Some(mut data)) => {
+          let data = data.copy_to_bytes(data.remaining());
+          if body_tx.send_data(data).await.is_err() {
+            return;
+          }
+        }
+        Ok(None) => break,
+        Err(err) => {
+          body_tx.abort(JsErrorBox::new("Http", err.to_string()));
+          return;
+        }
+      }
+    }
+    match recv.recv_trailers().await {
+      Ok(Some(trailers)) => {
+        let _ = body_tx.send_trailers(trailers).await;
+      }
+      Ok(None) => {}
+      Err(err) => body_tx.abort(JsErrorBox::new("Http", err.to_string())),
+    }
+  });
+
+  let (mut parts, ()) = request.into_parts();
+  parts.version = http::Version::HTTP_2;
+  sender.ready().await?;
+  let response = sender
+    .send_request(http::Request::from_parts(parts, body))
+    .await?;
+
+  let (parts, mut body) = response.into_parts();
+  send
+    .send_response(http::Response::from_parts(parts, ()))
+    .await
+    .map_err(http3_error)?;
+  while let Some(frame) = body.frame().await {
+    match frame?.into_data() {
+      Ok(data) => send.send_data(data).await.map_err(http3_error)?,
+      Err(frame) => {
+        if let Ok(trailers) = frame.into_trailers() {
+          send.send_trailers(trailers).await.map_err(http3_error)?;
+        }
+      }
+    }
+  }
+  send.finish().await.map_err(http3_error)
+}
@@ -40,6 +40,8 @@ use deno_core::v8;
 use deno_http_h1 as h1;
 use deno_net::ops_tls::TlsStream;
 use deno_net::raw::NetworkStream;
+use deno_websocket::PerMessageDeflate;
+use deno_websocket::PerMessageDeflateOptions;
 use deno_websocket::ServerWebSocket;
 use deno_websocket::ws_create_server_stream;
 use deno_websocket::ws_create_server_stream_with_guard;
@@ -47,6 +49,7 @@ use fly_accept_encoding::Encoding;
 use hyper::StatusCode;
 use hyper::body::Incoming;
 use hyper::header::ACCEPT_ENCODING;
+use hyper::header::ALT_SVC;
 use hyper::header::CACHE_CONTROL;
 use hyper::header::CONTENT_ENCODING;
 use hyper::header::CONTENT_LENGTH;
@@ -71,6 +74,7 @@ use crate::OtelInfo;
 use crate::OtelInfoAttributes;
 use crate::compressible::is_content_compressible;
 use crate::extract_network_stream;
+use crate::http3;
 use crate::network_buffered_stream::NetworkBufferedStream;
 use crate::network_buffered_stream::NetworkStreamPrefixCheck;
 use crate::request_body::HttpRequestBody;
@@ -339,6 +343,12 @@ pub enum HttpNextError {
   #[class("Http")]
   #[error("invalid HTTP status line")]
   InvalidHttpStatusLine,
+  #[class("Http")]
+  #[error("{0}")]
+  Http3(String),
+  #[class(type)]
+  #[error("HTTP/3 can only be served alongside a listener")]
+  Http3WithoutListener,
   #[class(inherit)]
   #[error(transparent)]
   TakeNetworkStream(
@@ -346,6 +356,9 @@ pub enum HttpNextError {
     #[inherit]
     deno_net::raw::TakeNetworkStreamError,
   ),
+  #[class(inherit)]
+  #[error(transparent)]
+  WebSocketDeflate(#[from] deno_websocket::DeflateError),
 }
 
 impl From<h1::Error> for HttpNextError {
@@ -1398,10 +1411,12 @@ impl RawHttpRecord {
     let mut inner = self.0.borrow_mut();
     let body = inner.response_body.take();
     body.map(|body| {
+      let mut headers = std::mem::take(&mut inner.response_headers);
+      push_raw_alt_svc(&inner.server_state, &mut headers);
       (
         RawResponseParts {
           status: inner.response_status,
-          headers: std::mem::take(&mut inner.response_headers),
+          headers,
           trailers: std::mem::take(&mut inner.response_trailers),
           default_text_content_type: inner.default_text_content_type,
           content_type: inner.content_type.take(),
@@ -1753,10 +1768,20 @@ where
 pub async fn op_http_upgrade_websocket_next(
   state: Rc<RefCell<OpState>>,
   external: *const c_void,
+  #[string] extensions: Option<String>,
+  #[serde] per_message_deflate: Option<PerMessageDeflateOptions>,
 ) -> Result<ResourceId, HttpNextError> {
   let http =
     // SAFETY: op is called with external.
     unsafe { clone_external!(external, "op_http_upgrade_websocket_next") };
+  // `extensions` is the permessage-deflate response negotiated by
+  // `op_http_websocket_extensions_header`.
+  let deflate = match (extensions, per_message_deflate) {
+    (Some(extensions), Some(options)) => {
+      Some(PerMessageDeflate::server(&options, &extensions)?)
+    }
+    _ => None,
+  };
   match http {
     HttpRecordExternal::Raw(record) => {
       let Some(upgrade) = record.0.borrow().upgrade.clone() else {
@@ -1772,6 +1797,7 @@ pub async fn op_http_upgrade_websocket_next(
         stream,
         bytes,
         server_state,
+        deflate,
       ))
     }
     HttpRecordExternal::Hyper(record) => {
@@ -1784,6 +1810,7 @@ pub async fn op_http_upgrade_websocket_next(
         stream,
         bytes,
         server_state,
+        deflate,
       ))
     }
   }
@@ -1804,6 +1831,7 @@ fn register_server_websocket(
   transport: NetworkStream,
   read_buf: Bytes,
   server_state: SignallingRc<HttpServerState>,
+  deflate: Option<PerMessageDeflate>,
 ) -> ResourceId {
   let registry = server_state.active_websockets();
   let key = registry.next_key();
@@ -1812,8 +1840,13 @@ fn register_server_websocket(
     key,
     _server_state: server_state,
   });
-  let rid =
-    ws_create_server_stream_with_guard(state, transport, read_buf, Some(guard));
+  let rid = ws_create_server_stream_with_guard(
+    state,
+    transport,
+    read_buf,
+    deflate,
+    Some(guard),
+  );
   if let Ok(ws) = state.resource_table.get::<ServerWebSocket>(rid) {
     registry.register(key, &ws);
   }
@@ -3461,6 +3494,7 @@ fn raw_response_from_direct_response(
         .collect();
     }
   }
+  push_raw_alt_svc(&record.server_state(), &mut parts.headers);
   let body = match response.body {
     DirectResponseBody::Empty => FlatResponseBody::Empty,
     DirectResponseBody::Bytes(body) => FlatResponseBody::Bytes(body),
@@ -3481,6 +3515,27 @@ fn raw_response_from_direct_response(
   (parts, body)
 }
 
+/// Advertises the HTTP/3 endpoint of the server, unless the handler set its
+/// own `Alt-Svc` header.
+fn push_raw_alt_svc(
+  server_state: &HttpServerState,
+  headers: &mut Vec<RawHeader>,
+) {
+  let Some(alt_svc) = server_state.alt_svc() else {
+    return;
+  };
+  if headers
+    .iter()
+    .any(|header| header.name.eq_ignore_ascii_case(b"alt-svc"))
+  {
+    return;
+  }
+  headers.push(RawHeader {
+    name: b"alt-svc".to_vec(),
+    value: alt_svc.as_bytes().to_vec(),
+  });
+}
+
 fn raw_direct_response_compression(
   record: &RawHttpRecord,
   length: Option<usize>,
@@ -5159,7 +5214,8 @@ fn serve_https(
     let request_info = request_info.clone();
     let server_state = server_state.clone();
     async move {
-      handle_request(
+      let alt_svc = server_state.alt_svc();
+      let mut response = handle_request(
         req,
         request_info,
         server_state,
@@ -5167,7 +5223,11 @@ fn serve_https(
         legacy_abort,
         automatic_compression,
       )
-      .await
+      .await?;
+      if let Some(alt_svc) = alt_svc {
+        response.headers_mut().entry(ALT_SVC).or_insert(alt_svc);
+      }
+      Ok::<_, hyper::Error>(response)
     }
   });
   spawn(
@@ -5335,6 +5395,66 @@ where
   }
 }
 
+/// Serves HTTP/3 connections accepted from a QUIC endpoint, alongside the TLS
+/// listener the server was started on.
+fn serve_h3(
+  incoming: quinn::Incoming,
+  listen_properties: HttpListenProperties,
+  lifetime: HttpLifetime,
+  callback: Rc<ServerCallback>,
+  options: Options,
+) -> JoinHandle<Result<(), HttpNextError>> {
+  let HttpLifetime {
+    server_state,
+    connection_cancel_handle,
+    listen_cancel_handle,
+  } = lifetime;
+
+  let legacy_abort = !options.no_legacy_abort;
+  let automatic_compression = options.automatic_compression;
+  spawn(
+    async move {
+      let connection = incoming.await.map_err(http3::http3_error)?;
+      let remote_address = connection.remote_address();
+      let request_info = HttpConnectionProperties {
+        peer_address: remote_address.ip().to_string().into(),
+        peer_port: Some(remote_address.port() as _),
+        local_port: listen_properties.local_port,
+        stream_type: listen_properties.stream_type,
+        scheme: listen_properties.scheme,
+        fallback_host: listen_properties.fallback_host.into(),
+      };
+      let svc = service_fn(move |req: Request| {
+        let callback = callback.clone();
+        let request_info = request_info.clone();
+        let server_state = server_state.clone();
+        async move {
+          handle_request(
+            req,
+            request_info,
+            server_state,
+            move |record| dispatch_to_js(&callback, record),
+            legacy_abort,
+            automatic_compression,
+          )
+          .await
+        }
+      });
+      let bridge_cancel_handle = listen_cancel_handle.clone();
+      http3::serve_connection(connection, listen_cancel_handle, move |io| {
+        serve_http2_unconditional(
+          io,
+          svc,
+          bridge_cancel_handle,
+          options.http2_builder_hook,
+        )
+      })
+      .await
+    }
+    .try_or_cancel(connection_cancel_handle),
+  )
+}
+
 #[derive(Clone)]
 struct HttpLifetime {
   connection_cancel_handle: Rc<CancelHandle>,
@@ -5342,11 +5462,20 @@ struct HttpLifetime {
   server_state: SignallingRc<HttpServerState>,
 }
 
+/// What a server started on a listener needs to serve HTTP/3 as well.
+#[derive(Clone)]
+struct HttpServeContext {
+  listen_properties: HttpListenProperties,
+  callback: Rc<ServerCallback>,
+  options: Options,
+}
+
 struct HttpJoinHandle {
   join_handle: AsyncRefCell<Option<JoinHandle<Result<(), HttpNextError>>>>,
   connection_cancel_handle: Rc<CancelHandle>,
   listen_cancel_handle: Rc<CancelHandle>,
   server_state: SignallingRc<HttpServerState>,
+  serve_context: RefCell<Option<HttpServeContext>>,
 }
 
 impl HttpJoinHandle {
@@ -5356,6 +5485,7 @@ impl HttpJoinHandle {
       connection_cancel_handle: CancelHandle::new_rc(),
       listen_cancel_handle: CancelHandle::new_rc(),
       server_state: HttpServerState::new(),
+      serve_context: RefCell::new(None),
     }
   }
 
@@ -5449,6 +5579,12 @@ where
     options
   };
 
+  *resource.serve_context.borrow_mut() = Some(HttpServeContext {
+    listen_properties: listen_properties.clone(),
+    callback: callback.clone(),
+    options,
+  });
+
   let listen_properties_clone: HttpListenProperties = listen_properties.clone();
   let handle = spawn(async move {
     loop {
@@ -5553,6 +5689,49 @@ where
   ))
 }
 
+/// Serves HTTP/3 on a QUIC listener alongside the server `rid` was started
+/// by `op_http_serve`, and advertises it with `Alt-Svc` on the responses sent
+/// over TCP. The QUIC listener stops accepting connections along with the
+/// server's listener.
+#[op2]
+pub fn op_http_serve_h3(
+  state: &mut OpState,
+  #[smi] rid: ResourceId,
+  #[cppgc] listener: &deno_net::QuicListenerResource,
+) -> Result<(), HttpNextError> {
+  deno_net::check_unstable(state, "Deno.serve({ http3: true })");
+  let join_handle = state.resource_table.get::<HttpJoinHandle>(rid)?;
+  let Some(context) = join_handle.serve_context.borrow().clone() else {
+    return Err(HttpNextError::Http3WithoutListener);
+  };
+
+  let endpoint = listener.endpoint().clone();
+  let alt_svc = http3::alt_svc(endpoint.local_addr()?.port());
+  join_handle
+    .server_state
+    .set_alt_svc(HeaderValue::from_str(&alt_svc).unwrap());
+
+  let lifetime = join_handle.lifetime();
+  spawn(async move {
+    while let Some(incoming) = endpoint
+      .accept()
+      .or_cancel(lifetime.listen_cancel_handle.clone())
+      .await?
+    {
+      serve_h3(
+        incoming,
+        context.listen_properties.clone(),
+        lifetime.clone(),
+        context.callback.clone(),
+        context.options,
+      );
+    }
+    Ok::<_, HttpNextError>(())
+  });
+
+  Ok(())
+}
+
 /// Wait for the server to finish accepting connections. Resolves
 /// when the accept-loop spawned by `op_http_serve` has exited (either
 /// from listener error or because the resource was closed).
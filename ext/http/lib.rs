@@ -55,6 +55,8 @@ use deno_telemetry::Histogram;
 use deno_telemetry::MeterProvider;
 use deno_telemetry::OTEL_GLOBALS;
 use deno_telemetry::UpDownCounter;
+use deno_websocket::DeflateError;
+use deno_websocket::PerMessageDeflateOptions;
 use deno_websocket::ws_create_server_stream;
 use flate2::Compression;
 use flate2::write::GzEncoder;
@@ -94,6 +96,7 @@ use crate::response_body::brotli_compressor;
 
 pub mod compressible;
 mod fly_accept_encoding;
+mod http3;
 mod http_next;
 mod network_buffered_stream;
 mod reader_stream;
@@ -212,6 +215,7 @@ deno_core::extension!(
     op_http_shutdown,
     op_http_upgrade_websocket,
     op_http_websocket_accept_header,
+    op_http_websocket_extensions_header,
     op_http_write_headers,
     op_http_write_resource,
     op_http_write,
@@ -230,6 +234,7 @@ deno_core::extension!(
     http_next::op_http_try_take_full_request_body_text,
     http_next::op_http_serve_on<HTTP>,
     http_next::op_http_serve<HTTP>,
+    http_next::op_http_serve_h3,
     http_next::op_http_set_promise_complete,
     http_next::op_http_drop_response_native,
     http_next::op_http_new_response_native_headers,
@@ -275,6 +280,7 @@ deno_core::extension!(
     op_http_shutdown,
     op_http_upgrade_websocket,
     op_http_websocket_accept_header,
+    op_http_websocket_extensions_header,
     op_http_write_headers,
     op_http_write_resource,
     op_http_write,
@@ -294,6 +300,7 @@ deno_core::extension!(
     http_next::op_http_try_take_full_request_body_text,
     http_next::op_http_serve_on<DefaultHttpPropertyExtractor>,
     http_next::op_http_serve<DefaultHttpPropertyExtractor>,
+    http_next::op_http_serve_h3,
     http_next::op_http_set_promise_complete,
     http_next::op_http_drop_response_native,
     http_next::op_http_new_response_native_headers,
@@ -1668,6 +1675,17 @@ fn op_http_websocket_accept_header(#[string] key: String) -> String {
   BASE64_STANDARD.encode(digest)
 }
 
+/// Negotiates permessage-deflate with the `Sec-WebSocket-Extensions` offers
+/// of a client, returning the header to respond with if accepted.
+#[op2]
+#[string]
+fn op_http_websocket_extensions_header(
+  #[string] offers: Option<String>,
+  #[serde] options: PerMessageDeflateOptions,
+) -> Result<Option<String>, DeflateError> {
+  options.negotiate(offers.as_deref())
+}
+
 #[op2]
 #[smi]
 async fn op_http_upgrade_websocket(
@@ -33,6 +33,7 @@ use hyper::body::Frame;
 use hyper::body::Incoming;
 use hyper::body::SizeHint;
 use hyper::header::HeaderMap;
+use hyper::header::HeaderValue;
 use hyper::upgrade::OnUpgrade;
 use scopeguard::ScopeGuard;
 use scopeguard::guard;
@@ -97,6 +98,9 @@ pub(crate) struct HttpServerStateInner {
   /// Without this they would leak past `await server.shutdown()` as
   /// `serverWebSocket` resources.
   pub(crate) active_websockets: Rc<ActiveWebSockets>,
+  /// `Alt-Svc` header value advertising the HTTP/3 endpoint serving
+  /// alongside this server, if any.
+  alt_svc: Option<HeaderValue>,
 }
 
 /// Server-initiated shutdown mode passed to [`ActiveWebSockets::begin_shutdown`]
@@ -280,6 +284,7 @@ impl HttpServerState {
     SignallingRc::new(Self(RefCell::new(HttpServerStateInner {
       pool: Vec::new(),
       active_websockets: Rc::new(ActiveWebSockets::default()),
+      alt_svc: None,
     })))
   }
 
@@ -290,6 +295,15 @@ impl HttpServerState {
   pub(crate) fn active_websockets(&self) -> Rc<ActiveWebSockets> {
     self.0.borrow().active_websockets.clone()
   }
+
+  /// Advertises an HTTP/3 endpoint on the responses sent over TCP.
+  pub(crate) fn set_alt_svc(&self, alt_svc: HeaderValue) {
+    self.0.borrow_mut().alt_svc = Some(alt_svc);
+  }
+
+  pub(crate) fn alt_svc(&self) -> Option<HeaderValue> {
+    self.0.borrow().alt_svc.clone()
+  }
 }
 
 impl std::ops::Deref for HttpServerState {
@@ -22,6 +22,7 @@ use deno_core::ResourceId;
 use deno_core::error::ResourceError;
 use deno_error::JsErrorBox;
 use deno_permissions::PermissionCheckError;
+use deno_permissions::quota::WriteQuota;
 #[cfg(windows)]
 use deno_subprocess_windows::Stdio as StdStdio;
 use tokio::task::JoinError;
@@ -354,6 +355,262 @@ pub trait File {
   fn try_clone_inner(self: Rc<Self>) -> FsResult<Rc<dyn File>>;
 }
 
+/// Counts the bytes written to `file` against `quota`, if any.
+pub fn with_write_quota(
+  file: Rc<dyn File>,
+  quota: Option<WriteQuota>,
+) -> Rc<dyn File> {
+  match quota {
+    Some(quota) => Rc::new(WriteQuotaFile { file, quota }),
+    None => file,
+  }
+}
+
+/// A file whose writes count against the write quotas of the permissions it
+/// was opened with.
+struct WriteQuotaFile {
+  file: Rc<dyn File>,
+  quota: WriteQuota,
+}
+
+impl WriteQuotaFile {
+  fn write_counted<T>(
+    &self,
+    len: usize,
+    write: impl FnOnce() -> FsResult<T>,
+    nwritten: impl FnOnce(&T) -> usize,
+  ) -> FsResult<T> {
+    self.quota.consume(len as u64)?;
+    let result = write();
+    let unwritten = match &result {
+      Ok(value) => len - nwritten(value),
+      Err(_) => len,
+    };
+    self.quota.release(unwritten as u64);
+    result
+  }
+}
+
+#[async_trait::async_trait(?Send)]
+impl File for WriteQuotaFile {
+  fn maybe_path(&self) -> Option<&Path> {
+    self.file.maybe_path()
+  }
+
+  fn read_sync(self: Rc<Self>, buf: &mut [u8]) -> FsResult<usize> {
+    self.file.clone().read_sync(buf)
+  }
+  async fn read(self: Rc<Self>, limit: usize) -> FsResult<BufView> {
+    self.file.clone().read(limit).await
+  }
+  async fn read_byob(
+    self: Rc<Self>,
+    buf: BufMutView,
+  ) -> FsResult<(usize, BufMutView)> {
+    self.file.clone().read_byob(buf).await
+  }
+
+  fn write_sync(self: Rc<Self>, buf: &[u8]) -> FsResult<usize> {
+    self.write_counted(buf.len(), || self.file.clone().write_sync(buf), |n| *n)
+  }
+  async fn write(
+    self: Rc<Self>,
+    buf: BufView,
+  ) -> FsResult<deno_core::WriteOutcome> {
+    let len = buf.len();
+    self.quota.consume(len as u64)?;
+    let result = self.file.clone().write(buf).await;
+    let unwritten = match &result {
+      Ok(outcome) => len - outcome.nwritten(),
+      Err(_) => len,
+    };
+    self.quota.release(unwritten as u64);
+    result
+  }
+
+  fn write_all_sync(self: Rc<Self>, buf: &[u8]) -> FsResult<()> {
+    self.write_counted(
+      buf.len(),
+      || self.file.clone().write_all_sync(buf),
+      |_| buf.len(),
+    )
+  }
+  async fn write_all(self: Rc<Self>, buf: BufView) -> FsResult<()> {
+    let len = buf.len();
+    self.quota.consume(len as u64)?;
+    let result = self.file.clone().write_all(buf).await;
+    if result.is_err() {
+      self.quota.release(len as u64);
+    }
+    result
+  }
+
+  fn read_all_sync(self: Rc<Self>) -> FsResult<Cow<'static, [u8]>> {
+    self.file.clone().read_all_sync()
+  }
+  async fn read_all_async(self: Rc<Self>) -> FsResult<Cow<'static, [u8]>> {
+    self.file.clone().read_all_async().await
+  }
+
+  fn chmod_sync(self: Rc<Self>, pathmode: u32) -> FsResult<()> {
+    self.file.clone().chmod_sync(pathmode)
+  }
+  async fn chmod_async(self: Rc<Self>, mode: u32) -> FsResult<()> {
+    self.file.clone().chmod_async(mode).await
+  }
+
+  fn chown_sync(
+    self: Rc<Self>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    self.file.clone().chown_sync(uid, gid)
+  }
+  async fn chown_async(
+    self: Rc<Self>,
+    uid: Option<u32>,
+    gid: Option<u32>,
+  ) -> FsResult<()> {
+    self.file.clone().chown_async(uid, gid).await
+  }
+
+  fn seek_sync(self: Rc<Self>, pos: io::SeekFrom) -> FsResult<u64> {
+    self.file.clone().seek_sync(pos)
+  }
+  async fn seek_async(self: Rc<Self>, pos: io::SeekFrom) -> FsResult<u64> {
+    self.file.clone().seek_async(pos).await
+  }
+
+  fn datasync_sync(self: Rc<Self>) -> FsResult<()> {
+    self.file.clone().datasync_sync()
+  }
+  async fn datasync_async(self: Rc<Self>) -> FsResult<()> {
+    self.file.clone().datasync_async().await
+  }
+
+  fn sync_sync(self: Rc<Self>) -> FsResult<()> {
+    self.file.clone().sync_sync()
+  }
+  async fn sync_async(self: Rc<Self>) -> FsResult<()> {
+    self.file.clone().sync_async().await
+  }
+
+  fn stat_sync(self: Rc<Self>) -> FsResult<FsStat> {
+    self.file.clone().stat_sync()
+  }
+  async fn stat_async(self: Rc<Self>) -> FsResult<FsStat> {
+    self.file.clone().stat_async().await
+  }
+
+  fn lock_sync(self: Rc<Self>, exclusive: bool) -> FsResult<()> {
+    self.file.clone().lock_sync(exclusive)
+  }
+  async fn lock_async(self: Rc<Self>, exclusive: bool) -> FsResult<()> {
+    self.file.clone().lock_async(exclusive).await
+  }
+
+  fn try_lock_sync(self: Rc<Self>, exclusive: bool) -> FsResult<bool> {
+    self.file.clone().try_lock_sync(exclusive)
+  }
+  async fn try_lock_async(self: Rc<Self>, exclusive: bool) -> FsResult<bool> {
+    self.file.clone().try_lock_async(exclusive).await
+  }
+
+  fn unlock_sync(self: Rc<Self>) -> FsResult<()> {
+    self.file.clone().unlock_sync()
+  }
+  async fn unlock_async(self: Rc<Self>) -> FsResult<()> {
+    self.file.clone().unlock_async().await
+  }
+
+  fn truncate_sync(self: Rc<Self>, len: u64) -> FsResult<()> {
+    // growing the file writes zeroes, which count against the quotas
+    let size = self.file.clone().stat_sync()?.size;
+    let growth = len.saturating_sub(size);
+    self.quota.consume(growth)?;
+    let result = self.file.clone().truncate_sync(len);
+    if result.is_err() {
+      self.quota.release(growth);
+    }
+    result
+  }
+  async fn truncate_async(self: Rc<Self>, len: u64) -> FsResult<()> {
+    let size = self.file.clone().stat_async().await?.size;
+    let growth = len.saturating_sub(size);
+    self.quota.consume(growth)?;
+    let result = self.file.clone().truncate_async(len).await;
+    if result.is_err() {
+      self.quota.release(growth);
+    }
+    result
+  }
+
+  fn utime_sync(
+    self: Rc<Self>,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    self.file.clone().utime_sync(
+      atime_secs,
+      atime_nanos,
+      mtime_secs,
+      mtime_nanos,
+    )
+  }
+  async fn utime_async(
+    self: Rc<Self>,
+    atime_secs: i64,
+    atime_nanos: u32,
+    mtime_secs: i64,
+    mtime_nanos: u32,
+  ) -> FsResult<()> {
+    self
+      .file
+      .clone()
+      .utime_async(atime_secs, atime_nanos, mtime_secs, mtime_nanos)
+      .await
+  }
+
+  fn read_at_sync(
+    self: Rc<Self>,
+    buf: &mut [u8],
+    position: u64,
+  ) -> FsResult<usize> {
+    self.file.clone().read_at_sync(buf, position)
+  }
+  async fn read_at_async(
+    self: Rc<Self>,
+    buf: BufMutView,
+    position: u64,
+  ) -> FsResult<(usize, BufMutView)> {
+    self.file.clone().read_at_async(buf, position).await
+  }
+  fn write_at_sync(
+    self: Rc<Self>,
+    buf: &[u8],
+    position: u64,
+  ) -> FsResult<usize> {
+    self.write_counted(
+      buf.len(),
+      || self.file.clone().write_at_sync(buf, position),
+      |n| *n,
+    )
+  }
+
+  fn as_stdio(self: Rc<Self>) -> FsResult<StdStdio> {
+    self.file.clone().as_stdio()
+  }
+  fn backing_fd(self: Rc<Self>) -> Option<ResourceHandleFd> {
+    self.file.clone().backing_fd()
+  }
+  fn try_clone_inner(self: Rc<Self>) -> FsResult<Rc<dyn File>> {
+    let file = self.file.clone().try_clone_inner()?;
+    Ok(with_write_quota(file, Some(self.quota.clone())))
+  }
+}
+
 pub struct FileResource {
   name: String,
   file: Rc<dyn File>,
@@ -7,10 +7,17 @@ const {
 } = core;
 const {
   op_kv_atomic_write,
+  op_kv_backfill_index,
+  op_kv_changes_disable,
+  op_kv_changes_next,
+  op_kv_changes_open,
   op_kv_database_open,
+  op_kv_define_index,
   op_kv_dequeue_next_message,
   op_kv_encode_cursor,
+  op_kv_export_read,
   op_kv_finish_dequeued_message,
+  op_kv_import,
   op_kv_snapshot_read,
   op_kv_watch,
   op_kv_watch_next,
@@ -32,10 +39,12 @@ const {
   ObjectFreeze,
   ObjectGetPrototypeOf,
   ObjectHasOwn,
+  ObjectIs,
   ObjectPrototypeIsPrototypeOf,
   RangeError,
   SafeMap,
   SafeMapIterator,
+  SafeWeakMap,
   StringPrototypeReplace,
   Symbol,
   SymbolAsyncIterator,
@@ -43,7 +52,12 @@ const {
   SymbolFor,
   SymbolToStringTag,
   TypeError,
+  TypedArrayPrototypeGetLength,
   TypedArrayPrototypeGetSymbolToStringTag,
+  TypedArrayPrototypeLastIndexOf,
+  TypedArrayPrototypeSet,
+  TypedArrayPrototypeSubarray,
+  Uint8Array,
 } = primordials;
 
 const { ReadableStream } = core.loadExtScript("ext:deno_web/06_streams.js");
@@ -131,9 +145,70 @@ type RawValue = {
 const kvSymbol = Symbol("KvRid");
 const commitVersionstampSymbol = Symbol("KvCommitVersionstamp");
 
+// Keep in sync with `INDEX_ROOT` in index.rs.
+const indexRoot = "__kv_index__";
+
+interface KvIndex {
+  prefix: Deno.KvKey;
+  key: (value: unknown, key: Deno.KvKey) => Deno.KvKeyPart | null | undefined;
+}
+
+type RawIndexKey = [number, string, Deno.KvKey];
+
+// Keys of the entries listed from an index, relative to the index, from
+// which list cursors are made.
+const indexEntryKeys = new SafeWeakMap<Deno.KvEntry<unknown>, Deno.KvKey>();
+
+function keyHasPrefix(key: Deno.KvKey, prefix: Deno.KvKey): boolean {
+  if (key.length <= prefix.length) return false;
+  for (let i = 0; i < prefix.length; ++i) {
+    const part = key[i];
+    const prefixPart = prefix[i];
+    if (
+      TypedArrayPrototypeGetSymbolToStringTag(part) === "Uint8Array" &&
+      TypedArrayPrototypeGetSymbolToStringTag(prefixPart) === "Uint8Array"
+    ) {
+      const length = TypedArrayPrototypeGetLength(part);
+      if (length !== TypedArrayPrototypeGetLength(prefixPart)) return false;
+      for (let j = 0; j < length; ++j) {
+        if (part[j] !== prefixPart[j]) return false;
+      }
+    } else if (!ObjectIs(part, prefixPart)) {
+      return false;
+    }
+  }
+  return true;
+}
+
+// Index entries are stored at [indexRoot, index, part, ...key].
+function indexedKey(index: string, key: Deno.KvKey | null): Deno.KvKey {
+  const indexed: Deno.KvKeyPart[] = [indexRoot, index];
+  for (let i = 0; i < (key?.length ?? 0); ++i) {
+    ArrayPrototypePush(indexed, key[i]);
+  }
+  return indexed;
+}
+
+function pushIndexKeys(
+  indexKeys: RawIndexKey[],
+  indexes: SafeMap<string, KvIndex>,
+  mutation: number,
+  key: Deno.KvKey,
+  value: unknown,
+) {
+  if (key[key.length - 1] === commitVersionstampSymbol) return;
+  for (const { 0: name, 1: index } of new SafeMapIterator(indexes)) {
+    if (!keyHasPrefix(key, index.prefix)) continue;
+    const part = index.key(value, key);
+    if (part === null || part === undefined) continue;
+    ArrayPrototypePush(indexKeys, [mutation, name, [part]]);
+  }
+}
+
 class Kv {
   #rid: number;
   #isClosed: boolean;
+  #indexes = new SafeMap<string, KvIndex>();
 
   constructor(rid: number = undefined, symbol: symbol = undefined) {
     if (kvSymbol !== symbol) {
@@ -146,7 +221,48 @@ class Kv {
   }
 
   atomic() {
-    return new AtomicOperation(this.#rid);
+    return new AtomicOperation(this.#rid, this.#indexes);
+  }
+
+  async defineIndex(
+    name: string,
+    options: {
+      prefix: Deno.KvKey;
+      key: (
+        value: unknown,
+        key: Deno.KvKey,
+      ) => Deno.KvKeyPart | null | undefined;
+    },
+  ) {
+    if (typeof options?.key !== "function") {
+      throw new TypeError("Index 'key' must be a function");
+    }
+    await op_kv_define_index(this.#rid, name, options.prefix);
+    const index = {
+      prefix: ObjectFreeze(ArrayPrototypeSlice(options.prefix)),
+      key: options.key,
+    };
+    this.#indexes.set(name, index);
+
+    // Index the entries that were set before the index was defined. Entries
+    // that changed since they were listed are skipped, as their writes
+    // already indexed them.
+    let batch: [Deno.KvKey, string, Deno.KvKey | null][] = [];
+    for await (const entry of this.list({ prefix: index.prefix })) {
+      const part = index.key(entry.value, entry.key);
+      ArrayPrototypePush(batch, [
+        entry.key,
+        entry.versionstamp,
+        part === null || part === undefined ? null : [part],
+      ]);
+      if (batch.length >= 100) {
+        await op_kv_backfill_index(this.#rid, name, batch);
+        batch = [];
+      }
+    }
+    if (batch.length > 0) {
+      await op_kv_backfill_index(this.#rid, name, batch);
+    }
   }
 
   commitVersionstamp(): symbol {
@@ -206,11 +322,14 @@ class Kv {
 
   async set(key: Deno.KvKey, value: unknown, options?: { expireIn?: number }) {
     validateExpireIn(options?.expireIn);
+    const indexKeys: RawIndexKey[] = [];
+    pushIndexKeys(indexKeys, this.#indexes, 0, key, value);
     const versionstamp = await doAtomicWriteInPlace(
       this.#rid,
       [],
       [[key, "set", serializeValue(value), options?.expireIn]],
       [],
+      indexKeys,
     );
     if (versionstamp === null) throw new TypeError("Failed to set value");
     return { ok: true, versionstamp };
@@ -234,6 +353,7 @@ class Kv {
       cursor?: string;
       reverse?: boolean;
       consistency?: Deno.KvConsistencyLevel;
+      index?: string;
     } = { __proto__: null },
   ): KvListIterator {
     if (
@@ -253,6 +373,11 @@ class Kv {
     }
     if (options.batchSize === undefined && batchSize > 500) batchSize = 500;
 
+    const index = options.index;
+    if (index !== undefined && !this.#indexes.has(index)) {
+      throw new TypeError(`Unknown index '${index}'`);
+    }
+
     return new KvListIterator({
       limit: options.limit,
       selector,
@@ -260,23 +385,33 @@ class Kv {
       reverse: options.reverse ?? false,
       consistency: options.consistency ?? "strong",
       batchSize,
-      pullBatch: this.#pullBatch(batchSize),
+      pullBatch: this.#pullBatch(batchSize, index),
     });
   }
 
-  #pullBatch(batchSize: number): (
+  #pullBatch(batchSize: number, index: string | undefined): (
     selector: Deno.KvListSelector,
     cursor: string | undefined,
     reverse: boolean,
     consistency: Deno.KvConsistencyLevel,
   ) => Promise<Deno.KvEntry<unknown>[]> {
     return async (selector, cursor, reverse, consistency) => {
+      let prefix = ObjectHasOwn(selector, "prefix") ? selector.prefix : null;
+      let start = ObjectHasOwn(selector, "start") ? selector.start : null;
+      let end = ObjectHasOwn(selector, "end") ? selector.end : null;
+      if (index !== undefined) {
+        if (prefix !== null || start === null || end === null) {
+          prefix = indexedKey(index, prefix);
+        }
+        if (start !== null) start = indexedKey(index, start);
+        if (end !== null) end = indexedKey(index, end);
+      }
       const { 0: entries }: [RawKvEntry[]] = await op_kv_snapshot_read(
         this.#rid,
         [[
-          ObjectHasOwn(selector, "prefix") ? selector.prefix : null,
-          ObjectHasOwn(selector, "start") ? selector.start : null,
-          ObjectHasOwn(selector, "end") ? selector.end : null,
+          prefix,
+          start,
+          end,
           batchSize,
           reverse,
           cursor,
@@ -284,7 +419,17 @@ class Kv {
         consistency,
       );
 
-      return ArrayPrototypeMap(entries, deserializeValue);
+      if (index === undefined) {
+        return ArrayPrototypeMap(entries, deserializeValue);
+      }
+      return ArrayPrototypeMap(entries, (raw) => {
+        const entry = deserializeValue({
+          ...raw,
+          key: ArrayPrototypeSlice(raw.key, 3),
+        });
+        indexEntryKeys.set(entry, ArrayPrototypeSlice(raw.key, 2));
+        return entry;
+      });
     };
   }
 
@@ -442,6 +587,109 @@ class Kv {
     });
   }
 
+  changes(options: { since?: string } = { __proto__: null }) {
+    const dbRid = this.#rid;
+    const since = options.since ?? null;
+    let rid: number;
+    return new ReadableStream({
+      async start() {
+        rid = await op_kv_changes_open(dbRid, since);
+      },
+      async pull(controller) {
+        let commit;
+        try {
+          commit = await op_kv_changes_next(rid);
+        } catch (err) {
+          core.tryClose(rid);
+          controller.error(err);
+          return;
+        }
+        if (commit === null) {
+          core.tryClose(rid);
+          controller.close();
+          return;
+        }
+        const { 0: versionstamp, 1: changes } = commit;
+        const mutations = ArrayPrototypeMap(
+          changes,
+          (change) =>
+            change.value === null
+              ? { type: "delete", key: change.key }
+              : {
+                type: "set",
+                key: change.key,
+                value: deserializeValue(change).value,
+              },
+        );
+        controller.enqueue({ versionstamp, mutations });
+      },
+      cancel() {
+        core.tryClose(rid);
+      },
+    });
+  }
+
+  async disableChanges(): Promise<void> {
+    await op_kv_changes_disable(this.#rid);
+  }
+
+  export(options: { prefix?: Deno.KvKey } = { __proto__: null }) {
+    const rid = this.#rid;
+    const prefix = options.prefix ?? [];
+    let cursor: Uint8Array | null = null;
+    return new ReadableStream({
+      type: "bytes",
+      async pull(controller) {
+        const { 0: chunk, 1: next } = await op_kv_export_read(
+          rid,
+          prefix,
+          cursor,
+        );
+        cursor = next;
+        if (TypedArrayPrototypeGetLength(chunk) > 0) {
+          controller.enqueue(chunk);
+        }
+        if (cursor === null) {
+          controller.close();
+          // Workaround for https://github.com/whatwg/streams/issues/1170
+          controller.byobRequest?.respond(0);
+        }
+      },
+    });
+  }
+
+  async import(
+    source: ReadableStream<Uint8Array> | AsyncIterable<Uint8Array>,
+  ): Promise<number> {
+    let count = 0;
+    let buffered = new Uint8Array(0);
+    for await (const chunk of source) {
+      const merged = new Uint8Array(
+        TypedArrayPrototypeGetLength(buffered) +
+          TypedArrayPrototypeGetLength(chunk),
+      );
+      TypedArrayPrototypeSet(merged, buffered);
+      TypedArrayPrototypeSet(
+        merged,
+        chunk,
+        TypedArrayPrototypeGetLength(buffered),
+      );
+      // Only complete lines are imported, the rest waits for the next chunk.
+      const end = TypedArrayPrototypeLastIndexOf(merged, 0x0a) + 1;
+      if (end > 0) {
+        count += await op_kv_import(
+          this.#rid,
+          TypedArrayPrototypeSubarray(merged, 0, end),
+        );
+      }
+      buffered = TypedArrayPrototypeSubarray(merged, end);
+    }
+    if (TypedArrayPrototypeGetLength(buffered) > 0) {
+      count += await op_kv_import(this.#rid, buffered);
+    }
+    return count;
+  }
+
   close() {
     core.close(this.#rid);
     this.#isClosed = true;
@@ -454,13 +702,16 @@ class Kv {
 
 class AtomicOperation {
   #rid: number;
+  #indexes: SafeMap<string, KvIndex>;
 
   #checks: [Deno.KvKey, string | null][] = [];
   #mutations: [Deno.KvKey, string, RawValue | null, number | undefined][] = [];
   #enqueues: [Uint8Array, number, Deno.KvKey[], number[] | null][] = [];
+  #indexKeys: RawIndexKey[] = [];
 
-  constructor(rid: number) {
+  constructor(rid: number, indexes: SafeMap<string, KvIndex>) {
     this.#rid = rid;
+    this.#indexes = indexes;
   }
 
   check(...checks: Deno.AtomicCheck[]): this {
@@ -503,6 +754,15 @@ class AtomicOperation {
         default:
           throw new TypeError("Invalid mutation type");
       }
+      if (type === "set") {
+        pushIndexKeys(
+          this.#indexKeys,
+          this.#indexes,
+          this.#mutations.length,
+          key,
+          mutation.value,
+        );
+      }
       ArrayPrototypePush(this.#mutations, [key, type, value, expireIn]);
     }
     return this;
@@ -544,6 +804,13 @@ class AtomicOperation {
     options?: { expireIn?: number },
   ): this {
     validateExpireIn(options?.expireIn);
+    pushIndexKeys(
+      this.#indexKeys,
+      this.#indexes,
+      this.#mutations.length,
+      key,
+      value,
+    );
     ArrayPrototypePush(this.#mutations, [
       key,
       "set",
@@ -587,6 +854,7 @@ class AtomicOperation {
       this.#checks,
       this.#mutations,
       this.#enqueues,
+      this.#indexKeys,
     );
     if (versionstamp === null) return { ok: false };
     return { ok: true, versionstamp };
@@ -948,7 +1216,7 @@ class KvListIterator extends AsyncIterator
         ObjectHasOwn(selector, "prefix") ? selector.prefix : null,
         ObjectHasOwn(selector, "start") ? selector.start : null,
         ObjectHasOwn(selector, "end") ? selector.end : null,
-      ], entry.key);
+      ], indexEntryKeys.get(entry) ?? entry.key);
     };
     this.#count++;
     return {
@@ -967,6 +1235,7 @@ async function doAtomicWriteInPlace(
   checks: [Deno.KvKey, string | null][],
   mutations: [Deno.KvKey, string, RawValue | null, number | undefined][],
   enqueues: [Uint8Array, number, Deno.KvKey[], number[] | null][],
+  indexKeys: RawIndexKey[] = [],
 ): Promise<string | null> {
   for (let i = 0; i < mutations.length; ++i) {
     const mutation = mutations[i];
@@ -985,6 +1254,7 @@ async function doAtomicWriteInPlace(
     checks,
     mutations,
     enqueues,
+    indexKeys,
   );
 }
 
@@ -0,0 +1,497 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! Change feed of local KV databases.
+//!
+//! Once a feed is opened on a database, SQLite triggers on the `kv` table of
+//! `denokv_sqlite` record the key, version, kind and written value of every
+//! change in a `kv_changes` table, in the same transaction as the write
+//! itself, so writes from other processes are recorded too. Feeds read that
+//! table and group its rows by the version of the atomic write that made
+//! them, so every change is reported with the value of its own write.
+//!
+//! The triggers only see the rows written, so `sum`, `min` and `max`
+//! mutations are recorded as sets of their result, and expired keys as
+//! deletes.
+//!
+//! Feeds are woken by the writes of the process through the notifier shared
+//! by the connections to the database, and check for writes of other
+//! processes every [`POLL_INTERVAL`].
+//!
+//! The log keeps the last [`CHANGE_LOG_RETENTION`] changes. The
+//! `kv_changes_meta` table records the version after which the log is
+//! complete, which is when it was enabled or the last pruned version.
+//! [`disable_change_log`] drops the log and stops recording changes.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use deno_core::unsync::spawn_blocking;
+use deno_error::JsErrorBox;
+use denokv_proto::KvValue;
+use rusqlite::OptionalExtension;
+use rusqlite::params;
+use tokio::sync::watch;
+
+use crate::ChangeFeed;
+use crate::KvChange;
+use crate::KvCommit;
+use crate::sqlite::ConnGen;
+
+/// Number of changes kept in the change log.
+pub const CHANGE_LOG_RETENTION: i64 = 100_000;
+
+/// How often feeds check for writes of other processes once they are caught
+/// up.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Maximum number of commits read at once.
+const READ_BATCH_SIZE: i64 = 128;
+
+// Value encodings of `denokv_sqlite`.
+const VALUE_ENCODING_V8: i64 = 1;
+const VALUE_ENCODING_LE64: i64 = 2;
+const VALUE_ENCODING_BYTES: i64 = 3;
+
+// Kinds of changes in the change log.
+const CHANGE_KIND_SET: &str = "set";
+const CHANGE_KIND_DELETE: &str = "delete";
+
+/// Creates the change log and its triggers if they don't exist yet.
+pub(crate) fn enable_change_log(
+  conn: &rusqlite::Connection,
+) -> rusqlite::Result<()> {
+  // Logs from before the values were recorded along with the keys start
+  // over.
+  let has_log = conn
+    .prepare("SELECT 1 FROM sqlite_master WHERE name = 'kv_changes'")?
+    .exists([])?;
+  let has_values = conn
+    .prepare(
+      "SELECT 1 FROM pragma_table_info('kv_changes') WHERE name = 'kind'",
+    )?
+    .exists([])?;
+  if has_log && !has_values {
+    disable_change_log(conn)?;
+  }
+  conn.execute_batch(&format!(
+    "
+    CREATE TABLE IF NOT EXISTS kv_changes (
+      id          INTEGER PRIMARY KEY AUTOINCREMENT,
+      version     INTEGER NOT NULL,
+      k           BLOB NOT NULL,
+      kind        TEXT NOT NULL,
+      v           BLOB,
+      v_encoding  INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS kv_changes_meta (
+      k               INTEGER PRIMARY KEY,
+      complete_after  INTEGER NOT NULL
+    );
+    INSERT OR IGNORE INTO kv_changes_meta (k, complete_after)
+      SELECT 0, version FROM data_version WHERE k = 0;
+
+    CREATE TRIGGER IF NOT EXISTS kv_changes_on_insert AFTER INSERT ON kv
+    BEGIN
+      INSERT INTO kv_changes (version, k, kind, v, v_encoding)
+        VALUES (new.version, new.k, '{CHANGE_KIND_SET}', new.v, new.v_encoding);
+    END;
+    CREATE TRIGGER IF NOT EXISTS kv_changes_on_update AFTER UPDATE ON kv
+    BEGIN
+      INSERT INTO kv_changes (version, k, kind, v, v_encoding)
+        VALUES (new.version, new.k, '{CHANGE_KIND_SET}', new.v, new.v_encoding);
+    END;
+    -- Deleted rows don't carry the version of the write deleting them.
+    CREATE TRIGGER IF NOT EXISTS kv_changes_on_delete AFTER DELETE ON kv
+    BEGIN
+      INSERT INTO kv_changes (version, k, kind)
+        VALUES (
+          (SELECT version FROM data_version WHERE k = 0),
+          old.k,
+          '{CHANGE_KIND_DELETE}'
+        );
+    END;
+    CREATE TRIGGER IF NOT EXISTS kv_changes_prune AFTER INSERT ON kv_changes
+    WHEN new.id > {CHANGE_LOG_RETENTION}
+    BEGIN
+      UPDATE kv_changes_meta SET complete_after = COALESCE(
+        (SELECT MAX(version) FROM kv_changes
+          WHERE id <= new.id - {CHANGE_LOG_RETENTION}),
+        complete_after
+      ) WHERE k = 0;
+      DELETE FROM kv_changes WHERE id <= new.id - {CHANGE_LOG_RETENTION};
+    END;
+    "
+  ))
+}
+
+/// Drops the change log and its triggers.
+pub(crate) fn disable_change_log(
+  conn: &rusqlite::Connection,
+) -> rusqlite::Result<()> {
+  conn.execute_batch(
+    "
+    DROP TRIGGER IF EXISTS kv_changes_on_insert;
+    DROP TRIGGER IF EXISTS kv_changes_on_update;
+    DROP TRIGGER IF EXISTS kv_changes_on_delete;
+    DROP TRIGGER IF EXISTS kv_changes_prune;
+    DROP TABLE IF EXISTS kv_changes;
+    DROP TABLE IF EXISTS kv_changes_meta;
+    ",
+  )
+}
+
+pub(crate) fn version_to_versionstamp(version: i64) -> [u8; 10] {
+  let mut versionstamp = [0; 10];
+  versionstamp[..8].copy_from_slice(&version.to_be_bytes());
+  versionstamp
+}
+
+fn versionstamp_to_version(versionstamp: &[u8; 10]) -> i64 {
+  i64::from_be_bytes(versionstamp[..8].try_into().unwrap())
+}
+
+/// Returns the id of the last change before the feed starts: the last one
+/// recorded, or the last one of the write with versionstamp `since`.
+fn start_id(
+  conn: &rusqlite::Connection,
+  since: Option<[u8; 10]>,
+) -> Result<i64, JsErrorBox> {
+  let map_err = |err: rusqlite::Error| JsErrorBox::generic(err.to_string());
+  let Some(since) = since else {
+    return conn
+      .query_row("SELECT COALESCE(MAX(id), 0) FROM kv_changes", [], |row| {
+        row.get(0)
+      })
+      .map_err(map_err);
+  };
+  let version = versionstamp_to_version(&since);
+  let complete_after = conn
+    .query_row(
+      "SELECT complete_after FROM kv_changes_meta WHERE k = 0",
+      [],
+      |row| row.get::<_, i64>(0),
+    )
+    .optional()
+    .map_err(map_err)?
+    .unwrap_or(i64::MAX);
+  if version < complete_after {
+    return Err(JsErrorBox::type_error(format!(
+      "The change feed can't resume from versionstamp {}: changes before {} are not recorded",
+      faster_hex::hex_string(&since),
+      faster_hex::hex_string(&version_to_versionstamp(complete_after)),
+    )));
+  }
+  conn
+    .query_row(
+      "SELECT COALESCE(MAX(id), 0) FROM kv_changes WHERE version <= ?1",
+      params![version],
+      |row| row.get(0),
+    )
+    .map_err(map_err)
+}
+
+/// Reads the commits whose changes come after `last_id`, and returns the id
+/// of the last change read.
+fn read_commits(
+  conn: &rusqlite::Connection,
+  last_id: i64,
+) -> rusqlite::Result<(i64, Vec<KvCommit>)> {
+  // Only read whole commits, which are always visible all at once.
+  let mut stmt = conn.prepare_cached(
+    "SELECT id, version, k, kind, v, v_encoding FROM kv_changes
+      WHERE id > ?1 AND version <= (
+        SELECT MAX(version) FROM (
+          SELECT version FROM kv_changes WHERE id > ?1 ORDER BY id LIMIT ?2
+        )
+      )
+      ORDER BY id",
+  )?;
+  let mut rows = stmt.query(params![last_id, READ_BATCH_SIZE])?;
+  let mut last_id = last_id;
+  let mut commits = Vec::<(i64, KvCommit)>::new();
+  while let Some(row) = rows.next()? {
+    last_id = row.get(0)?;
+    let version: i64 = row.get(1)?;
+    let key: Vec<u8> = row.get(2)?;
+    let kind: String = row.get(3)?;
+    let value = match row.get::<_, Option<Vec<u8>>>(4)? {
+      Some(value) if kind != CHANGE_KIND_DELETE => {
+        decode_value(value, row.get(5)?)
+      }
+      _ => None,
+    };
+    let change = KvChange { key, value };
+    match commits.last_mut() {
+      Some((commit_version, commit)) if *commit_version == version => {
+        commit.changes.push(change)
+      }
+      _ => commits.push((
+        version,
+        KvCommit {
+          versionstamp: version_to_versionstamp(version),
+          changes: vec![change],
+        },
+      )),
+    }
+  }
+  Ok((last_id, commits.into_iter().map(|(_, c)| c).collect()))
+}
+
+fn decode_value(value: Vec<u8>, encoding: Option<i64>) -> Option<KvValue> {
+  match encoding? {
+    VALUE_ENCODING_V8 => Some(KvValue::V8(value)),
+    VALUE_ENCODING_LE64 => {
+      Some(KvValue::U64(u64::from_le_bytes(value.try_into().ok()?)))
+    }
+    VALUE_ENCODING_BYTES => Some(KvValue::Bytes(value)),
+    _ => None,
+  }
+}
+
+pub struct SqliteChangeFeed {
+  conn: Arc<Mutex<rusqlite::Connection>>,
+  commits: watch::Receiver<u64>,
+  last_id: i64,
+  pending: VecDeque<KvCommit>,
+}
+
+impl SqliteChangeFeed {
+  /// Opens a feed, woken by `commits` when the process writes to the
+  /// database.
+  pub(crate) async fn open(
+    conn_gen: ConnGen,
+    commits: watch::Receiver<u64>,
+    since: Option<[u8; 10]>,
+  ) -> Result<Self, JsErrorBox> {
+    let (conn, last_id) = spawn_blocking(move || {
+      let map_err = |err: rusqlite::Error| JsErrorBox::generic(err.to_string());
+      let conn = conn_gen().map_err(map_err)?;
+      conn.busy_timeout(Duration::from_secs(5)).map_err(map_err)?;
+      enable_change_log(&conn).map_err(map_err)?;
+      let last_id = start_id(&conn, since)?;
+      Ok::<_, JsErrorBox>((conn, last_id))
+    })
+    .await
+    .unwrap()?;
+    Ok(Self {
+      conn: Arc::new(Mutex::new(conn)),
+      commits,
+      last_id,
+      pending: VecDeque::new(),
+    })
+  }
+}
+
+#[async_trait(?Send)]
+impl ChangeFeed for SqliteChangeFeed {
+  async fn next(&mut self) -> Result<KvCommit, JsErrorBox> {
+    loop {
+      if let Some(commit) = self.pending.pop_front() {
+        return Ok(commit);
+      }
+      // Writes committed from now on wake the feed up, even if they land
+      // while it is reading.
+      self.commits.borrow_and_update();
+      let conn = self.conn.clone();
+      let last_id = self.last_id;
+      let (last_id, commits) =
+        spawn_blocking(move || read_commits(&conn.lock().unwrap(), last_id))
+          .await
+          .unwrap()
+          .map_err(|err| JsErrorBox::generic(err.to_string()))?;
+      self.last_id = last_id;
+      if commits.is_empty() {
+        tokio::select! {
+          _ = self.commits.changed() => {}
+          _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+      }
+      self.pending.extend(commits);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// The parts of the `denokv_sqlite` schema the change log relies on.
+  fn open_db() -> rusqlite::Connection {
+    let conn = rusqlite::Connection::open_in_memory().unwrap();
+    conn
+      .execute_batch(
+        "
+        CREATE TABLE data_version (k INTEGER PRIMARY KEY, version INTEGER NOT NULL);
+        INSERT INTO data_version (k, version) VALUES (0, 7);
+        CREATE TABLE kv (
+          k BLOB PRIMARY KEY,
+          v BLOB NOT NULL,
+          v_encoding INTEGER NOT NULL,
+          version INTEGER NOT NULL
+        ) WITHOUT ROWID;
+        ",
+      )
+      .unwrap();
+    conn
+  }
+
+  fn commit(conn: &rusqlite::Connection, version: i64, sql: &str) {
+    conn
+      .execute(
+        "UPDATE data_version SET version = ?1 WHERE k = 0",
+        params![version],
+      )
+      .unwrap();
+    conn.execute_batch(sql).unwrap();
+  }
+
+  #[test]
+  fn records_committed_writes() {
+    let conn = open_db();
+    enable_change_log(&conn).unwrap();
+    // Enabling it again keeps the log.
+    enable_change_log(&conn).unwrap();
+    assert_eq!(start_id(&conn, None).unwrap(), 0);
+
+    commit(
+      &conn,
+      10,
+      "INSERT INTO kv VALUES (x'01', x'02', 3, 10);
+       INSERT INTO kv VALUES (x'02', x'0500000000000000', 2, 10);",
+    );
+    let (last_id, commits) = read_commits(&conn, 0).unwrap();
+    assert_eq!(last_id, 2);
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].versionstamp, version_to_versionstamp(10));
+    assert_eq!(commits[0].changes[0].key, vec![1]);
+    assert!(matches!(
+      &commits[0].changes[0].value,
+      Some(KvValue::Bytes(v)) if v == &[2]
+    ));
+    assert!(matches!(commits[0].changes[1].value, Some(KvValue::U64(5))));
+
+    commit(
+      &conn,
+      20,
+      "UPDATE kv SET v = x'03', version = 20 WHERE k = x'01';
+       DELETE FROM kv WHERE k = x'02';",
+    );
+    let (last_id, commits) = read_commits(&conn, last_id).unwrap();
+    assert_eq!(last_id, 4);
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].versionstamp, version_to_versionstamp(20));
+    assert!(matches!(
+      &commits[0].changes[0].value,
+      Some(KvValue::Bytes(v)) if v == &[3]
+    ));
+    assert_eq!(commits[0].changes[1].key, vec![2]);
+    assert!(commits[0].changes[1].value.is_none());
+
+    // Every commit keeps the values it wrote, even for keys written again
+    // or deleted since.
+    let (_, commits) = read_commits(&conn, 0).unwrap();
+    assert_eq!(commits.len(), 2);
+    assert!(matches!(
+      &commits[0].changes[0].value,
+      Some(KvValue::Bytes(v)) if v == &[2]
+    ));
+    assert!(matches!(commits[0].changes[1].value, Some(KvValue::U64(5))));
+    assert!(matches!(
+      &commits[1].changes[0].value,
+      Some(KvValue::Bytes(v)) if v == &[3]
+    ));
+
+    // Resuming after the first write only returns the second one.
+    let last_id = start_id(&conn, Some(version_to_versionstamp(10))).unwrap();
+    let (_, commits) = read_commits(&conn, last_id).unwrap();
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].versionstamp, version_to_versionstamp(20));
+
+    // Writes before the log was enabled weren't recorded.
+    assert!(start_id(&conn, Some(version_to_versionstamp(5))).is_err());
+  }
+
+  #[test]
+  fn reports_the_value_of_each_write() {
+    let conn = open_db();
+    enable_change_log(&conn).unwrap();
+    commit(&conn, 10, "INSERT INTO kv VALUES (x'01', x'0a', 3, 10);");
+    commit(
+      &conn,
+      20,
+      "UPDATE kv SET v = x'14', version = 20 WHERE k = x'01';",
+    );
+
+    let (_, commits) = read_commits(&conn, 0).unwrap();
+    assert_eq!(commits.len(), 2);
+    assert_eq!(commits[0].versionstamp, version_to_versionstamp(10));
+    assert!(matches!(
+      &commits[0].changes[0].value,
+      Some(KvValue::Bytes(v)) if v == &[10]
+    ));
+    assert!(matches!(
+      &commits[1].changes[0].value,
+      Some(KvValue::Bytes(v)) if v == &[20]
+    ));
+  }
+
+  #[test]
+  fn restarts_logs_without_values() {
+    let conn = open_db();
+    conn
+      .execute_batch(
+        "
+        CREATE TABLE kv_changes (
+          id INTEGER PRIMARY KEY AUTOINCREMENT,
+          version INTEGER NOT NULL,
+          k BLOB NOT NULL
+        );
+        CREATE TABLE kv_changes_meta (
+          k INTEGER PRIMARY KEY,
+          complete_after INTEGER NOT NULL
+        );
+        INSERT INTO kv_changes_meta VALUES (0, 1);
+        INSERT INTO kv_changes (version, k) VALUES (5, x'01');
+        ",
+      )
+      .unwrap();
+
+    enable_change_log(&conn).unwrap();
+    assert_eq!(start_id(&conn, None).unwrap(), 0);
+    assert!(start_id(&conn, Some(version_to_versionstamp(5))).is_err());
+    commit(&conn, 10, "INSERT INTO kv VALUES (x'01', x'02', 3, 10);");
+    let (_, commits) = read_commits(&conn, 0).unwrap();
+    assert_eq!(commits.len(), 1);
+    assert!(matches!(
+      &commits[0].changes[0].value,
+      Some(KvValue::Bytes(v)) if v == &[2]
+    ));
+  }
+
+  #[test]
+  fn disables_the_change_log() {
+    let conn = open_db();
+    enable_change_log(&conn).unwrap();
+    commit(&conn, 10, "INSERT INTO kv VALUES (x'01', x'02', 3, 10);");
+    disable_change_log(&conn).unwrap();
+
+    // Writes keep working without the triggers.
+    commit(&conn, 20, "DELETE FROM kv WHERE k = x'01';");
+    assert!(read_commits(&conn, 0).is_err());
+    let tables = conn
+      .query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE name LIKE 'kv_changes%'",
+        [],
+        |row| row.get::<_, i64>(0),
+      )
+      .unwrap();
+    assert_eq!(tables, 0);
+
+    // The log starts over when it is enabled again.
+    enable_change_log(&conn).unwrap();
+    assert!(start_id(&conn, Some(version_to_versionstamp(10))).is_err());
+  }
+}
@@ -11,6 +11,7 @@ use denokv_proto::ReadRangeOutput;
 use denokv_proto::WatchStream;
 
 use crate::AtomicWrite;
+use crate::ChangeFeed;
 use crate::Database;
 use crate::DatabaseHandler;
 use crate::QueueMessageHandle;
@@ -50,15 +51,12 @@ impl MultiBackendDbHandler {
   }
 }
 
-#[async_trait(?Send)]
-impl DatabaseHandler for MultiBackendDbHandler {
-  type DB = RcDynamicDb;
-
-  async fn open(
-    &self,
-    state: Rc<RefCell<OpState>>,
+impl MultiBackendDbHandler {
+  /// Applies the `DENO_KV_DEFAULT_PATH` and `DENO_KV_PATH_PREFIX` overrides
+  /// to the path given to `Deno.openKv()`.
+  fn resolve_path(
     mut path: Option<String>,
-  ) -> Result<Self::DB, JsErrorBox> {
+  ) -> Result<Option<String>, JsErrorBox> {
     if path.is_none()
       && let Ok(x) = std::env::var("DENO_KV_DEFAULT_PATH")
       && !x.is_empty()
@@ -105,16 +103,23 @@ impl DatabaseHandler for MultiBackendDbHandler {
       }
     }
 
+    Ok(path)
+  }
+
+  fn backend_for(
+    &self,
+    path: &Option<String>,
+  ) -> Result<&dyn DynamicDbHandler, JsErrorBox> {
     for (prefixes, handler) in &self.backends {
       for &prefix in *prefixes {
         if prefix.is_empty() {
-          return handler.dyn_open(state.clone(), path.clone()).await;
+          return Ok(handler.as_ref());
         }
-        let Some(path) = &path else {
+        let Some(path) = path else {
           continue;
         };
         if path.starts_with(prefix) {
-          return handler.dyn_open(state.clone(), Some(path.clone())).await;
+          return Ok(handler.as_ref());
         }
       }
     }
@@ -125,6 +130,58 @@ impl DatabaseHandler for MultiBackendDbHandler {
   }
 }
 
+#[async_trait(?Send)]
+impl DatabaseHandler for MultiBackendDbHandler {
+  type DB = RcDynamicDb;
+
+  async fn open(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+  ) -> Result<Self::DB, JsErrorBox> {
+    let path = Self::resolve_path(path)?;
+    self.backend_for(&path)?.dyn_open(state, path).await
+  }
+
+  async fn open_change_feed(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    since: Option<[u8; 10]>,
+  ) -> Result<Box<dyn ChangeFeed>, JsErrorBox> {
+    let path = Self::resolve_path(path)?;
+    self
+      .backend_for(&path)?
+      .dyn_open_change_feed(state, path, since)
+      .await
+  }
+
+  async fn disable_change_log(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+  ) -> Result<(), JsErrorBox> {
+    let path = Self::resolve_path(path)?;
+    self
+      .backend_for(&path)?
+      .dyn_disable_change_log(state, path)
+      .await
+  }
+
+  async fn read_expirations(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    keys: Vec<Vec<u8>>,
+  ) -> Result<Vec<Option<u64>>, JsErrorBox> {
+    let path = Self::resolve_path(path)?;
+    self
+      .backend_for(&path)?
+      .dyn_read_expirations(state, path, keys)
+      .await
+  }
+}
+
 #[async_trait(?Send)]
 pub trait DynamicDbHandler {
   async fn dyn_open(
@@ -132,6 +189,26 @@ pub trait DynamicDbHandler {
     state: Rc<RefCell<OpState>>,
     path: Option<String>,
   ) -> Result<RcDynamicDb, JsErrorBox>;
+
+  async fn dyn_open_change_feed(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    since: Option<[u8; 10]>,
+  ) -> Result<Box<dyn ChangeFeed>, JsErrorBox>;
+
+  async fn dyn_disable_change_log(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+  ) -> Result<(), JsErrorBox>;
+
+  async fn dyn_read_expirations(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    keys: Vec<Vec<u8>>,
+  ) -> Result<Vec<Option<u64>>, JsErrorBox>;
 }
 
 #[async_trait(?Send)]
@@ -145,6 +222,32 @@ impl DatabaseHandler for Box<dyn DynamicDbHandler> {
   ) -> Result<Self::DB, JsErrorBox> {
     (**self).dyn_open(state, path).await
   }
+
+  async fn open_change_feed(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    since: Option<[u8; 10]>,
+  ) -> Result<Box<dyn ChangeFeed>, JsErrorBox> {
+    (**self).dyn_open_change_feed(state, path, since).await
+  }
+
+  async fn disable_change_log(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+  ) -> Result<(), JsErrorBox> {
+    (**self).dyn_disable_change_log(state, path).await
+  }
+
+  async fn read_expirations(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    keys: Vec<Vec<u8>>,
+  ) -> Result<Vec<Option<u64>>, JsErrorBox> {
+    (**self).dyn_read_expirations(state, path, keys).await
+  }
 }
 
 #[async_trait(?Send)]
@@ -160,6 +263,32 @@ where
   ) -> Result<RcDynamicDb, JsErrorBox> {
     Ok(RcDynamicDb(Rc::new(self.open(state, path).await?)))
   }
+
+  async fn dyn_open_change_feed(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    since: Option<[u8; 10]>,
+  ) -> Result<Box<dyn ChangeFeed>, JsErrorBox> {
+    self.open_change_feed(state, path, since).await
+  }
+
+  async fn dyn_disable_change_log(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+  ) -> Result<(), JsErrorBox> {
+    self.disable_change_log(state, path).await
+  }
+
+  async fn dyn_read_expirations(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    keys: Vec<Vec<u8>>,
+  ) -> Result<Vec<Option<u64>>, JsErrorBox> {
+    self.read_expirations(state, path, keys).await
+  }
 }
 
 #[async_trait(?Send)]
@@ -0,0 +1,473 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! Portable export format of KV databases.
+//!
+//! Exports are newline-delimited JSON, one entry per line, ordered by key:
+//!
+//! ```json
+//! {"key":[{"type":"string","value":"users"},{"type":"bigint","value":"1"}],"value":{"type":"v8","value":"/w9vIgRuYW1lIgNBZGF7AQ=="},"versionstamp":"00000000000000010000"}
+//! ```
+//!
+//! Key parts are typed as `string`, `number`, `bigint`, `boolean` or `bytes`
+//! and values as `v8` (serialized with the V8 serializer), `bytes` or `u64`.
+//! Binary data is base64 encoded, bigints and u64 values are decimal strings
+//! and non-finite numbers are written as `"NaN"`, `"Infinity"` and
+//! `"-Infinity"`. Versionstamps are kept for reference, entries get new ones
+//! when they are imported. Entries that expire have an `expire_at` field with
+//! the time they expire at, in milliseconds since the epoch.
+
+use std::path::Path;
+use std::time::Duration;
+
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use chrono::DateTime;
+use chrono::Utc;
+use denokv_proto::AtomicWrite;
+use denokv_proto::Consistency;
+use denokv_proto::Database;
+use denokv_proto::Key;
+use denokv_proto::KeyPart;
+use denokv_proto::KvEntry;
+use denokv_proto::KvValue;
+use denokv_proto::Mutation;
+use denokv_proto::MutationKind;
+use denokv_proto::ReadRange;
+use denokv_proto::SnapshotReadOptions;
+use denokv_proto::decode_key;
+use denokv_proto::encode_key;
+use rusqlite::OptionalExtension;
+use rusqlite::params;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::KvConfig;
+use crate::KvError;
+use crate::KvErrorKind;
+use crate::check_value_size;
+use crate::check_write_key_size;
+
+/// Number of entries read from the database at once when exporting.
+pub const EXPORT_PAGE_SIZE: u32 = 500;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExportEntry {
+  pub key: Vec<ExportKeyPart>,
+  pub value: ExportValue,
+  pub versionstamp: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub expire_at: Option<u64>,
+}
+
+/// An entry parsed from an export, ready to be written.
+#[derive(Debug)]
+pub struct ImportEntry {
+  pub key: Vec<u8>,
+  pub value: KvValue,
+  pub expire_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ExportKeyPart {
+  String(String),
+  Number(ExportNumber),
+  Bigint(String),
+  Boolean(bool),
+  Bytes(String),
+}
+
+/// A number key part. JSON has no representation for non-finite numbers, so
+/// they are written as strings.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ExportNumber {
+  Finite(f64),
+  NonFinite(String),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ExportValue {
+  V8(String),
+  Bytes(String),
+  U64(String),
+}
+
+impl From<KeyPart> for ExportKeyPart {
+  fn from(part: KeyPart) -> Self {
+    match part {
+      KeyPart::String(s) => Self::String(s),
+      KeyPart::Float(n) if n.is_finite() => {
+        Self::Number(ExportNumber::Finite(n))
+      }
+      KeyPart::Float(n) => Self::Number(ExportNumber::NonFinite(
+        if n.is_nan() {
+          "NaN"
+        } else if n > 0.0 {
+          "Infinity"
+        } else {
+          "-Infinity"
+        }
+        .to_string(),
+      )),
+      KeyPart::Int(n) => Self::Bigint(n.to_string()),
+      KeyPart::False => Self::Boolean(false),
+      KeyPart::True => Self::Boolean(true),
+      KeyPart::Bytes(b) => Self::Bytes(BASE64_STANDARD.encode(b)),
+    }
+  }
+}
+
+impl TryFrom<ExportKeyPart> for KeyPart {
+  type Error = KvError;
+
+  fn try_from(part: ExportKeyPart) -> Result<Self, Self::Error> {
+    Ok(match part {
+      ExportKeyPart::String(s) => KeyPart::String(s),
+      ExportKeyPart::Number(ExportNumber::Finite(n)) => KeyPart::Float(n),
+      ExportKeyPart::Number(ExportNumber::NonFinite(n)) => {
+        KeyPart::Float(match n.as_str() {
+          "NaN" => f64::NAN,
+          "Infinity" => f64::INFINITY,
+          "-Infinity" => f64::NEG_INFINITY,
+          _ => return Err(invalid_entry(format!("invalid number \"{n}\""))),
+        })
+      }
+      ExportKeyPart::Bigint(n) => KeyPart::Int(
+        n.parse()
+          .map_err(|_| invalid_entry(format!("invalid bigint \"{n}\"")))?,
+      ),
+      ExportKeyPart::Boolean(false) => KeyPart::False,
+      ExportKeyPart::Boolean(true) => KeyPart::True,
+      ExportKeyPart::Bytes(b) => KeyPart::Bytes(decode_base64(&b)?),
+    })
+  }
+}
+
+impl From<KvValue> for ExportValue {
+  fn from(value: KvValue) -> Self {
+    match value {
+      KvValue::V8(buf) => Self::V8(BASE64_STANDARD.encode(buf)),
+      KvValue::Bytes(buf) => Self::Bytes(BASE64_STANDARD.encode(buf)),
+      KvValue::U64(n) => Self::U64(n.to_string()),
+    }
+  }
+}
+
+impl TryFrom<ExportValue> for KvValue {
+  type Error = KvError;
+
+  fn try_from(value: ExportValue) -> Result<Self, Self::Error> {
+    Ok(match value {
+      ExportValue::V8(buf) => KvValue::V8(decode_base64(&buf)?),
+      ExportValue::Bytes(buf) => KvValue::Bytes(decode_base64(&buf)?),
+      ExportValue::U64(n) => KvValue::U64(
+        n.parse()
+          .map_err(|_| invalid_entry(format!("invalid u64 \"{n}\"")))?,
+      ),
+    })
+  }
+}
+
+impl ExportEntry {
+  fn new(entry: KvEntry, expire_at: Option<u64>) -> std::io::Result<Self> {
+    Ok(ExportEntry {
+      key: decode_key(&entry.key)?
+        .0
+        .into_iter()
+        .map(ExportKeyPart::from)
+        .collect(),
+      value: entry.value.into(),
+      versionstamp: faster_hex::hex_string(&entry.versionstamp),
+      expire_at,
+    })
+  }
+}
+
+fn invalid_entry(message: String) -> KvError {
+  KvErrorKind::InvalidExportEntry(message).into_box()
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u8>, KvError> {
+  BASE64_STANDARD
+    .decode(data)
+    .map_err(|err| invalid_entry(err.to_string()))
+}
+
+/// Encodes a key given as typed key parts.
+pub fn encode_export_key(key: Vec<ExportKeyPart>) -> Result<Vec<u8>, KvError> {
+  let key = key
+    .into_iter()
+    .map(KeyPart::try_from)
+    .collect::<Result<Vec<_>, _>>()?;
+  Ok(encode_key(&Key(key))?)
+}
+
+/// Appends the line of `entry` to `out`. `expire_at` is when the entry
+/// expires, in milliseconds since the epoch.
+pub fn write_export_line(
+  out: &mut Vec<u8>,
+  entry: KvEntry,
+  expire_at: Option<u64>,
+) -> Result<(), KvError> {
+  let entry = ExportEntry::new(entry, expire_at)?;
+  serde_json::to_writer(&mut *out, &entry)
+    .map_err(|err| KvErrorKind::Io(err.into()).into_box())?;
+  out.push(b'\n');
+  Ok(())
+}
+
+/// Parses an exported line into the entry to write.
+pub fn parse_export_line(line: &str) -> Result<ImportEntry, KvError> {
+  let entry = serde_json::from_str::<ExportEntry>(line)
+    .map_err(|err| invalid_entry(err.to_string()))?;
+  if entry.key.is_empty() {
+    return Err(KvErrorKind::EmptyKey.into_box());
+  }
+  let expire_at = match entry.expire_at {
+    Some(expire_at) => Some(
+      i64::try_from(expire_at)
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .ok_or_else(|| {
+          invalid_entry(format!("invalid expire_at {expire_at}"))
+        })?,
+    ),
+    None => None,
+  };
+  Ok(ImportEntry {
+    key: encode_export_key(entry.key)?,
+    value: entry.value.try_into()?,
+    expire_at,
+  })
+}
+
+/// Reads when the entries with `keys` expire from the `kv` table of a local
+/// database, in milliseconds since the epoch.
+pub fn read_sqlite_expirations(
+  conn: &rusqlite::Connection,
+  keys: &[Vec<u8>],
+) -> rusqlite::Result<Vec<Option<u64>>> {
+  // `denokv_sqlite` stores -1 for entries that don't expire.
+  let mut stmt =
+    conn.prepare_cached("SELECT expiration_ms FROM kv WHERE k = ?1")?;
+  keys
+    .iter()
+    .map(|key| {
+      let expiration_ms = stmt
+        .query_row(params![key], |row| row.get::<_, i64>(0))
+        .optional()?;
+      Ok(expiration_ms.and_then(|ms| u64::try_from(ms).ok()))
+    })
+    .collect()
+}
+
+/// Reads up to `limit` entries under `prefix`, starting after the key that
+/// `cursor` was returned for. Returns the entries and the cursor to read the
+/// next page with, or `None` once all entries were read.
+pub async fn read_export_page<D: Database>(
+  db: &D,
+  prefix: &[u8],
+  cursor: Option<&[u8]>,
+  limit: u32,
+) -> Result<(Vec<KvEntry>, Option<Vec<u8>>), KvError> {
+  let start = match cursor {
+    Some(cursor) => cursor.to_vec(),
+    None => prefix.to_vec(),
+  };
+  let mut end = prefix.to_vec();
+  end.push(0xff);
+  let ranges = db
+    .snapshot_read(
+      vec![ReadRange {
+        start,
+        end,
+        limit: std::num::NonZeroU32::new(limit)
+          .ok_or(KvErrorKind::InvalidLimit)?,
+        reverse: false,
+      }],
+      SnapshotReadOptions {
+        consistency: Consistency::Strong,
+      },
+    )
+    .await
+    .map_err(KvErrorKind::Kv)?;
+  let entries = ranges
+    .into_iter()
+    .next()
+    .map(|range| range.entries)
+    .unwrap_or_default();
+  let cursor = if entries.len() < limit as usize {
+    None
+  } else {
+    entries.last().map(|entry| {
+      let mut cursor = entry.key.clone();
+      cursor.push(0x00);
+      cursor
+    })
+  };
+  Ok((entries, cursor))
+}
+
+/// Writes entries to `db` in as few atomic writes as the limits of `config`
+/// allow. Existing entries with the same keys are overwritten.
+///
+/// The import as a whole is not atomic: all entries are checked against the
+/// limits before anything is written, but if a write fails, the entries of
+/// the writes before it stay imported.
+pub async fn import_entries<D: Database>(
+  db: &D,
+  entries: Vec<ImportEntry>,
+  config: &KvConfig,
+) -> Result<usize, KvError> {
+  let count = entries.len();
+  let mut sizes = Vec::with_capacity(entries.len());
+  for entry in &entries {
+    let key_size = check_write_key_size(&entry.key, config)?;
+    let payload_size = check_value_size(&entry.value, config)? + key_size;
+    sizes.push((key_size, payload_size));
+  }
+  let mut mutations = Vec::new();
+  let mut total_payload_size = 0;
+  let mut total_key_size = 0;
+  for (entry, (key_size, payload_size)) in entries.into_iter().zip(sizes) {
+    if !mutations.is_empty()
+      && (mutations.len() >= config.max_mutations
+        || total_payload_size + payload_size
+          > config.max_total_mutation_size_bytes
+        || total_key_size + key_size > config.max_total_key_size_bytes)
+    {
+      commit_mutations(db, std::mem::take(&mut mutations)).await?;
+      total_payload_size = 0;
+      total_key_size = 0;
+    }
+    total_payload_size += payload_size;
+    total_key_size += key_size;
+    mutations.push(Mutation {
+      key: entry.key,
+      kind: MutationKind::Set(entry.value),
+      expire_at: entry.expire_at,
+    });
+  }
+  if !mutations.is_empty() {
+    commit_mutations(db, mutations).await?;
+  }
+  Ok(count)
+}
+
+async fn commit_mutations<D: Database>(
+  db: &D,
+  mutations: Vec<Mutation>,
+) -> Result<(), KvError> {
+  let result = db
+    .atomic_write(AtomicWrite {
+      checks: vec![],
+      mutations,
+      enqueues: vec![],
+    })
+    .await
+    .map_err(KvErrorKind::Kv)?;
+  match result {
+    Some(_) => Ok(()),
+    None => Err(
+      KvErrorKind::Kv(deno_error::JsErrorBox::generic(
+        "Failed to import entries: the write was rejected",
+      ))
+      .into_box(),
+    ),
+  }
+}
+
+/// Copies the SQLite database at `src` to `dest` while it may be in use.
+/// The copy is a consistent snapshot of the database. The source is only
+/// opened for reading.
+pub fn backup_sqlite(src: &Path, dest: &Path) -> Result<(), KvError> {
+  let src = rusqlite::Connection::open_with_flags(
+    src,
+    rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY
+      | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+  )
+  .map_err(KvErrorKind::Sqlite)?;
+  let mut dest =
+    rusqlite::Connection::open(dest).map_err(KvErrorKind::Sqlite)?;
+  let backup = rusqlite::backup::Backup::new(&src, &mut dest)
+    .map_err(KvErrorKind::Sqlite)?;
+  // Copying all pages in one step keeps a read transaction open on the
+  // source for the whole copy, instead of restarting the backup whenever
+  // another connection writes in between steps.
+  backup
+    .run_to_completion(-1, Duration::ZERO, None)
+    .map_err(KvErrorKind::Sqlite)?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn round_trips_entries() {
+    let key = encode_key(&Key(vec![
+      KeyPart::String("users".to_string()),
+      KeyPart::Int(42.into()),
+      KeyPart::Float(f64::NEG_INFINITY),
+      KeyPart::Float(1.5),
+      KeyPart::True,
+      KeyPart::Bytes(vec![1, 2, 3]),
+    ]))
+    .unwrap();
+    let mut out = Vec::new();
+    write_export_line(
+      &mut out,
+      KvEntry {
+        key: key.clone(),
+        value: KvValue::U64(7),
+        versionstamp: [0, 0, 0, 0, 0, 0, 0, 1, 0, 0],
+      },
+      Some(1_700_000_000_000),
+    )
+    .unwrap();
+    let line = String::from_utf8(out).unwrap();
+    assert_eq!(
+      line,
+      concat!(
+        r#"{"key":[{"type":"string","value":"users"},{"type":"bigint","value":"42"},"#,
+        r#"{"type":"number","value":"-Infinity"},{"type":"number","value":1.5},"#,
+        r#"{"type":"boolean","value":true},{"type":"bytes","value":"AQID"}],"#,
+        r#""value":{"type":"u64","value":"7"},"versionstamp":"00000000000000010000","#,
+        r#""expire_at":1700000000000}"#,
+        "\n"
+      )
+    );
+
+    let entry = parse_export_line(line.trim_end()).unwrap();
+    assert_eq!(entry.key, key);
+    assert!(matches!(entry.value, KvValue::U64(7)));
+    assert_eq!(
+      entry
+        .expire_at
+        .map(|expire_at| expire_at.timestamp_millis()),
+      Some(1_700_000_000_000)
+    );
+
+    let entry = parse_export_line(
+      r#"{"key":[{"type":"string","value":"a"}],"value":{"type":"bytes","value":""},"versionstamp":""}"#,
+    )
+    .unwrap();
+    assert!(entry.expire_at.is_none());
+  }
+
+  #[test]
+  fn rejects_invalid_entries() {
+    for line in [
+      "not json",
+      r#"{"key":[],"value":{"type":"bytes","value":""},"versionstamp":""}"#,
+      r#"{"key":[{"type":"bigint","value":"1.5"}],"value":{"type":"bytes","value":""},"versionstamp":""}"#,
+      r#"{"key":[{"type":"string","value":"a"}],"value":{"type":"v8","value":"%"},"versionstamp":""}"#,
+      r#"{"key":[{"type":"string","value":"a"}],"value":{"type":"bytes","value":""},"versionstamp":"","expire_at":18446744073709551615}"#,
+    ] {
+      assert!(parse_export_line(line).is_err(), "{line}");
+    }
+  }
+}
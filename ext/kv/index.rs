@@ -0,0 +1,693 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! Secondary indexes.
+//!
+//! An index is defined by a connection on a key prefix, along with a function
+//! deriving a key part from the values set under that prefix. For every entry
+//! under the prefix the index holds a copy of the entry at
+//! `[INDEX_ROOT, name, part, ...key]`, so that listing `[INDEX_ROOT, name]`
+//! returns the entries ordered by their index part. A reference at
+//! `[INDEX_REF_ROOT, name, ...key]` records the part an entry is indexed
+//! under, so that the copy can be removed once the entry changes.
+//!
+//! Copies and references are written in the same atomic write as the entries,
+//! which checks that the references it read weren't changed in the meantime.
+//!
+//! Index definitions are stored at `[INDEX_DEF_ROOT, name]` and loaded when
+//! the database is opened, so that connections which didn't define an index
+//! can't set entries under its prefix without indexing them. Entries written
+//! before an index was defined are indexed by [`backfill_index`].
+
+use chrono::DateTime;
+use chrono::Utc;
+use denokv_proto::AtomicWrite;
+use denokv_proto::Check;
+use denokv_proto::CommitResult;
+use denokv_proto::Consistency;
+use denokv_proto::Database;
+use denokv_proto::Enqueue;
+use denokv_proto::Key;
+use denokv_proto::KeyPart;
+use denokv_proto::KvValue;
+use denokv_proto::Mutation;
+use denokv_proto::MutationKind;
+use denokv_proto::ReadRange;
+use denokv_proto::SnapshotReadOptions;
+use denokv_proto::decode_key;
+use denokv_proto::encode_key;
+
+use crate::KvError;
+use crate::KvErrorKind;
+
+/// First key part of the entries of indexes.
+pub const INDEX_ROOT: &str = "__kv_index__";
+
+/// First key part of the references from entries to their index parts.
+pub const INDEX_REF_ROOT: &str = "__kv_index_ref__";
+
+/// First key part of the index definitions, which hold the prefix of the
+/// index.
+pub const INDEX_DEF_ROOT: &str = "__kv_index_def__";
+
+/// Number of times a write is retried when the references it read change
+/// before it is committed.
+const MAX_WRITE_ATTEMPTS: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct KvIndex {
+  pub name: String,
+  pub prefix: Vec<u8>,
+  /// Whether the index was defined on this connection, which then knows how
+  /// to derive the index parts of the entries it sets.
+  pub defined: bool,
+}
+
+/// An entry written before its index was defined, with the index part derived
+/// from the value it had at `versionstamp`.
+pub struct BackfillEntry {
+  pub key: Vec<u8>,
+  pub versionstamp: [u8; 10],
+  pub part: Option<Vec<u8>>,
+}
+
+/// The encoded index part derived from the value set by a mutation.
+pub struct IndexKey {
+  pub mutation: usize,
+  pub index: String,
+  pub part: Vec<u8>,
+}
+
+/// What an entry under an index prefix is indexed as after a write.
+#[derive(Debug)]
+struct IndexTarget {
+  index: usize,
+  key: Vec<u8>,
+  entry: Option<IndexedEntry>,
+}
+
+#[derive(Debug)]
+struct IndexedEntry {
+  part: Vec<u8>,
+  value: KvValue,
+  expire_at: Option<DateTime<Utc>>,
+}
+
+fn index_root(root: &str, name: &str) -> Result<Vec<u8>, KvError> {
+  Ok(encode_key(&Key(vec![
+    KeyPart::String(root.to_string()),
+    KeyPart::String(name.to_string()),
+  ]))?)
+}
+
+fn concat(parts: &[&[u8]]) -> Vec<u8> {
+  parts.concat()
+}
+
+/// Works out how `mutations` change the indexes. Entries set without an
+/// index part, because the index function returned nothing for their value,
+/// are removed from the index.
+fn index_targets(
+  indexes: &[KvIndex],
+  mutations: &[Mutation],
+  index_keys: Vec<IndexKey>,
+) -> Result<Vec<IndexTarget>, KvError> {
+  for index_key in &index_keys {
+    let Some(index) = indexes.iter().find(|i| i.name == index_key.index) else {
+      return Err(
+        KvErrorKind::UnknownIndex(index_key.index.clone()).into_box(),
+      );
+    };
+    let in_index = mutations.get(index_key.mutation).is_some_and(|m| {
+      matches!(m.kind, MutationKind::Set(_))
+        && m.key.starts_with(&index.prefix)
+        && m.key.len() > index.prefix.len()
+    });
+    if !in_index {
+      return Err(
+        KvErrorKind::InvalidIndex(format!(
+          "key part for index '{}' given for a key outside of it",
+          index.name
+        ))
+        .into_box(),
+      );
+    }
+  }
+
+  let mut targets = Vec::<IndexTarget>::new();
+  for (i, mutation) in mutations.iter().enumerate() {
+    for (index_pos, index) in indexes.iter().enumerate() {
+      if !mutation.key.starts_with(&index.prefix) {
+        continue;
+      }
+      let entry = match &mutation.kind {
+        MutationKind::SetSuffixVersionstampedKey(_) => {
+          return Err(
+            KvErrorKind::UnsupportedIndexedMutation("versionstamped set")
+              .into_box(),
+          );
+        }
+        _ if mutation.key.len() == index.prefix.len() => continue,
+        MutationKind::Set(_) if !index.defined => {
+          return Err(
+            KvErrorKind::IndexNotDefined(index.name.clone()).into_box(),
+          );
+        }
+        MutationKind::Set(value) => index_keys
+          .iter()
+          .find(|k| k.mutation == i && k.index == index.name)
+          .map(|k| IndexedEntry {
+            part: k.part.clone(),
+            value: value.clone(),
+            expire_at: mutation.expire_at,
+          }),
+        MutationKind::Delete => None,
+        MutationKind::Sum { .. } => {
+          return Err(
+            KvErrorKind::UnsupportedIndexedMutation("sum").into_box(),
+          );
+        }
+        MutationKind::Min(_) => {
+          return Err(
+            KvErrorKind::UnsupportedIndexedMutation("min").into_box(),
+          );
+        }
+        MutationKind::Max(_) => {
+          return Err(
+            KvErrorKind::UnsupportedIndexedMutation("max").into_box(),
+          );
+        }
+      };
+      match targets
+        .iter_mut()
+        .find(|t| t.index == index_pos && t.key == mutation.key)
+      {
+        Some(target) => target.entry = entry,
+        None => targets.push(IndexTarget {
+          index: index_pos,
+          key: mutation.key.clone(),
+          entry,
+        }),
+      }
+    }
+  }
+  Ok(targets)
+}
+
+/// The index parts entries are currently indexed under, along with the
+/// versionstamps of their references.
+async fn read_refs<D: Database>(
+  db: &D,
+  ref_keys: &[Vec<u8>],
+) -> Result<Vec<Option<(Vec<u8>, [u8; 10])>>, KvError> {
+  let ranges = ref_keys
+    .iter()
+    .map(|key| ReadRange {
+      start: key.clone(),
+      end: concat(&[key, &[0]]),
+      limit: std::num::NonZeroU32::MIN,
+      reverse: false,
+    })
+    .collect();
+  let output = db
+    .snapshot_read(
+      ranges,
+      SnapshotReadOptions {
+        consistency: Consistency::Strong,
+      },
+    )
+    .await
+    .map_err(KvErrorKind::Kv)?;
+  Ok(
+    output
+      .into_iter()
+      .map(|range| {
+        let entry = range.entries.into_iter().next()?;
+        match entry.value {
+          KvValue::Bytes(part) => Some((part, entry.versionstamp)),
+          _ => None,
+        }
+      })
+      .collect(),
+  )
+}
+
+/// Reads the index definitions stored in the database.
+pub async fn load_indexes<D: Database>(
+  db: &D,
+) -> Result<Vec<KvIndex>, KvError> {
+  const PAGE_SIZE: u32 = 500;
+  let root =
+    encode_key(&Key(vec![KeyPart::String(INDEX_DEF_ROOT.to_string())]))?;
+  let mut start = concat(&[&root, &[0]]);
+  let end = concat(&[&root, &[0xff]]);
+  let mut indexes = Vec::new();
+  loop {
+    let output = db
+      .snapshot_read(
+        vec![ReadRange {
+          start: start.clone(),
+          end: end.clone(),
+          limit: std::num::NonZeroU32::new(PAGE_SIZE).unwrap(),
+          reverse: false,
+        }],
+        SnapshotReadOptions {
+          consistency: Consistency::Strong,
+        },
+      )
+      .await
+      .map_err(KvErrorKind::Kv)?;
+    let entries = output
+      .into_iter()
+      .next()
+      .map(|range| range.entries)
+      .unwrap_or_default();
+    let count = entries.len();
+    for entry in entries {
+      start = concat(&[&entry.key, &[0]]);
+      let name = match decode_key(&entry.key)?.0.get(1) {
+        Some(KeyPart::String(name)) => name.clone(),
+        _ => continue,
+      };
+      if let KvValue::Bytes(prefix) = entry.value {
+        indexes.push(KvIndex {
+          name,
+          prefix,
+          defined: false,
+        });
+      }
+    }
+    if count < PAGE_SIZE as usize {
+      return Ok(indexes);
+    }
+  }
+}
+
+/// Stores the definition of an index, unless it is already stored. Fails if
+/// an index with the same name is stored for another prefix.
+pub async fn store_index<D: Database>(
+  db: &D,
+  name: &str,
+  prefix: &[u8],
+) -> Result<(), KvError> {
+  let key = index_root(INDEX_DEF_ROOT, name)?;
+  for _ in 0..MAX_WRITE_ATTEMPTS {
+    let output = db
+      .snapshot_read(
+        vec![ReadRange {
+          start: key.clone(),
+          end: concat(&[&key, &[0]]),
+          limit: std::num::NonZeroU32::MIN,
+          reverse: false,
+        }],
+        SnapshotReadOptions {
+          consistency: Consistency::Strong,
+        },
+      )
+      .await
+      .map_err(KvErrorKind::Kv)?;
+    match output
+      .into_iter()
+      .next()
+      .and_then(|r| r.entries.into_iter().next())
+    {
+      Some(entry) => {
+        return match entry.value {
+          KvValue::Bytes(stored) if stored == prefix => Ok(()),
+          _ => {
+            Err(KvErrorKind::IndexAlreadyDefined(name.to_string()).into_box())
+          }
+        };
+      }
+      None => {
+        let write = AtomicWrite {
+          checks: vec![Check {
+            key: key.clone(),
+            versionstamp: None,
+          }],
+          mutations: vec![Mutation {
+            key: key.clone(),
+            kind: MutationKind::Set(KvValue::Bytes(prefix.to_vec())),
+            expire_at: None,
+          }],
+          enqueues: vec![],
+        };
+        if db
+          .atomic_write(write)
+          .await
+          .map_err(KvErrorKind::Kv)?
+          .is_some()
+        {
+          return Ok(());
+        }
+        // Defined concurrently, check that it is the same index.
+      }
+    }
+  }
+  Err(KvErrorKind::IndexContention.into_box())
+}
+
+/// Indexes entries written before `index` was defined. Entries changed since
+/// their index part was derived are skipped, as the write changing them
+/// indexed them already.
+pub async fn backfill_index<D: Database>(
+  db: &D,
+  index: &KvIndex,
+  entries: Vec<BackfillEntry>,
+) -> Result<(), KvError> {
+  if let Some(entry) = entries.iter().find(|entry| {
+    !entry.key.starts_with(&index.prefix)
+      || entry.key.len() == index.prefix.len()
+  }) {
+    return Err(
+      KvErrorKind::InvalidIndex(format!(
+        "key {:?} is outside of index '{}'",
+        entry.key, index.name
+      ))
+      .into_box(),
+    );
+  }
+  let root = index_root(INDEX_ROOT, &index.name)?;
+  let ref_root = index_root(INDEX_REF_ROOT, &index.name)?;
+  let ref_keys = entries
+    .iter()
+    .map(|entry| concat(&[&ref_root, &entry.key]))
+    .collect::<Vec<_>>();
+  let refs = read_refs(db, &ref_keys).await?;
+  let values = db
+    .snapshot_read(
+      entries
+        .iter()
+        .map(|entry| ReadRange {
+          start: entry.key.clone(),
+          end: concat(&[&entry.key, &[0]]),
+          limit: std::num::NonZeroU32::MIN,
+          reverse: false,
+        })
+        .collect(),
+      SnapshotReadOptions {
+        consistency: Consistency::Strong,
+      },
+    )
+    .await
+    .map_err(KvErrorKind::Kv)?;
+
+  for (((entry, ref_key), current), value) in
+    entries.into_iter().zip(ref_keys).zip(refs).zip(values)
+  {
+    let Some(value) = value.entries.into_iter().next() else {
+      continue;
+    };
+    if value.versionstamp != entry.versionstamp
+      || current.as_ref().map(|(part, _)| part) == entry.part.as_ref()
+    {
+      continue;
+    }
+    let mut write = AtomicWrite {
+      checks: vec![
+        Check {
+          key: entry.key.clone(),
+          versionstamp: Some(entry.versionstamp),
+        },
+        Check {
+          key: ref_key.clone(),
+          versionstamp: current.as_ref().map(|(_, versionstamp)| *versionstamp),
+        },
+      ],
+      mutations: vec![],
+      enqueues: vec![],
+    };
+    if let Some((part, _)) = &current {
+      write.mutations.push(Mutation {
+        key: concat(&[&root, part, &entry.key]),
+        kind: MutationKind::Delete,
+        expire_at: None,
+      });
+    }
+    match entry.part {
+      Some(part) => {
+        write.mutations.push(Mutation {
+          key: concat(&[&root, &part, &entry.key]),
+          kind: MutationKind::Set(value.value),
+          expire_at: None,
+        });
+        write.mutations.push(Mutation {
+          key: ref_key,
+          kind: MutationKind::Set(KvValue::Bytes(part)),
+          expire_at: None,
+        });
+      }
+      None => write.mutations.push(Mutation {
+        key: ref_key,
+        kind: MutationKind::Delete,
+        expire_at: None,
+      }),
+    }
+    // A failed check means the entry was written again in the meantime.
+    db.atomic_write(write).await.map_err(KvErrorKind::Kv)?;
+  }
+  Ok(())
+}
+
+/// Commits `write` along with the changes it makes to `indexes`.
+pub async fn atomic_write_indexed<D: Database>(
+  db: &D,
+  indexes: &[KvIndex],
+  write: AtomicWrite,
+  index_keys: Vec<IndexKey>,
+) -> Result<Option<CommitResult>, KvError> {
+  let targets = index_targets(indexes, &write.mutations, index_keys)?;
+  if targets.is_empty() {
+    return Ok(db.atomic_write(write).await.map_err(KvErrorKind::Kv)?);
+  }
+
+  let mut roots = Vec::with_capacity(targets.len());
+  let mut ref_keys = Vec::with_capacity(targets.len());
+  for target in &targets {
+    let name = &indexes[target.index].name;
+    let root = index_root(INDEX_ROOT, name)?;
+    let ref_root = index_root(INDEX_REF_ROOT, name)?;
+    ref_keys.push(concat(&[&ref_root, &target.key]));
+    roots.push(root);
+  }
+
+  let mut refs = read_refs(db, &ref_keys).await?;
+  for _ in 0..MAX_WRITE_ATTEMPTS {
+    let mut attempt = clone_write(&write);
+    for (((target, root), ref_key), current) in
+      targets.iter().zip(&roots).zip(&ref_keys).zip(&refs)
+    {
+      attempt.checks.push(Check {
+        key: ref_key.clone(),
+        versionstamp: current.as_ref().map(|(_, versionstamp)| *versionstamp),
+      });
+      if let Some((part, _)) = current
+        && target
+          .entry
+          .as_ref()
+          .is_none_or(|entry| &entry.part != part)
+      {
+        attempt.mutations.push(Mutation {
+          key: concat(&[root, part, &target.key]),
+          kind: MutationKind::Delete,
+          expire_at: None,
+        });
+      }
+      match &target.entry {
+        Some(entry) => {
+          attempt.mutations.push(Mutation {
+            key: concat(&[root, &entry.part, &target.key]),
+            kind: MutationKind::Set(entry.value.clone()),
+            expire_at: entry.expire_at,
+          });
+          attempt.mutations.push(Mutation {
+            key: ref_key.clone(),
+            kind: MutationKind::Set(KvValue::Bytes(entry.part.clone())),
+            expire_at: entry.expire_at,
+          });
+        }
+        None if current.is_some() => attempt.mutations.push(Mutation {
+          key: ref_key.clone(),
+          kind: MutationKind::Delete,
+          expire_at: None,
+        }),
+        None => {}
+      }
+    }
+
+    if let Some(result) =
+      db.atomic_write(attempt).await.map_err(KvErrorKind::Kv)?
+    {
+      return Ok(Some(result));
+    }
+    // The write failed either on its own checks or because an entry was
+    // reindexed concurrently, in which case it is retried.
+    let latest = read_refs(db, &ref_keys).await?;
+    if latest == refs {
+      return Ok(None);
+    }
+    refs = latest;
+  }
+  Err(KvErrorKind::IndexContention.into_box())
+}
+
+fn clone_write(write: &AtomicWrite) -> AtomicWrite {
+  AtomicWrite {
+    checks: write
+      .checks
+      .iter()
+      .map(|check| Check {
+        key: check.key.clone(),
+        versionstamp: check.versionstamp,
+      })
+      .collect(),
+    mutations: write
+      .mutations
+      .iter()
+      .map(|mutation| Mutation {
+        key: mutation.key.clone(),
+        kind: match &mutation.kind {
+          MutationKind::Set(value) => MutationKind::Set(value.clone()),
+          MutationKind::Delete => MutationKind::Delete,
+          MutationKind::Sum {
+            value,
+            min_v8,
+            max_v8,
+            clamp,
+          } => MutationKind::Sum {
+            value: value.clone(),
+            min_v8: min_v8.clone(),
+            max_v8: max_v8.clone(),
+            clamp: *clamp,
+          },
+          MutationKind::Min(value) => MutationKind::Min(value.clone()),
+          MutationKind::Max(value) => MutationKind::Max(value.clone()),
+          MutationKind::SetSuffixVersionstampedKey(value) => {
+            MutationKind::SetSuffixVersionstampedKey(value.clone())
+          }
+        },
+        expire_at: mutation.expire_at,
+      })
+      .collect(),
+    enqueues: write
+      .enqueues
+      .iter()
+      .map(|enqueue| Enqueue {
+        payload: enqueue.payload.clone(),
+        deadline: enqueue.deadline,
+        keys_if_undelivered: enqueue.keys_if_undelivered.clone(),
+        backoff_schedule: enqueue.backoff_schedule.clone(),
+      })
+      .collect(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn key(parts: &[&str]) -> Vec<u8> {
+    encode_key(&Key(
+      parts
+        .iter()
+        .map(|part| KeyPart::String(part.to_string()))
+        .collect(),
+    ))
+    .unwrap()
+  }
+
+  fn mutation(key: Vec<u8>, kind: MutationKind) -> Mutation {
+    Mutation {
+      key,
+      kind,
+      expire_at: None,
+    }
+  }
+
+  #[test]
+  fn finds_indexed_mutations() {
+    let indexes = vec![KvIndex {
+      name: "by_email".to_string(),
+      prefix: key(&["users"]),
+      defined: true,
+    }];
+    let targets = index_targets(
+      &indexes,
+      &[
+        mutation(key(&["users", "ada"]), MutationKind::Set(KvValue::U64(1))),
+        mutation(key(&["users", "bob"]), MutationKind::Set(KvValue::U64(2))),
+        mutation(key(&["posts", "1"]), MutationKind::Delete),
+        mutation(key(&["users", "bob"]), MutationKind::Delete),
+        // The prefix itself isn't part of the index.
+        mutation(key(&["users"]), MutationKind::Set(KvValue::U64(3))),
+      ],
+      vec![IndexKey {
+        mutation: 0,
+        index: "by_email".to_string(),
+        part: key(&["ada@example.com"]),
+      }],
+    )
+    .unwrap();
+    assert_eq!(targets.len(), 2);
+    assert_eq!(targets[0].key, key(&["users", "ada"]));
+    let entry = targets[0].entry.as_ref().unwrap();
+    assert_eq!(entry.part, key(&["ada@example.com"]));
+    assert!(matches!(entry.value, KvValue::U64(1)));
+    // The last mutation of a key wins.
+    assert_eq!(targets[1].key, key(&["users", "bob"]));
+    assert!(targets[1].entry.is_none());
+  }
+
+  #[test]
+  fn rejects_invalid_indexed_mutations() {
+    let indexes = vec![KvIndex {
+      name: "by_email".to_string(),
+      prefix: key(&["users"]),
+      defined: true,
+    }];
+    let sum = mutation(
+      key(&["users", "ada"]),
+      MutationKind::Sum {
+        value: KvValue::U64(1),
+        min_v8: vec![],
+        max_v8: vec![],
+        clamp: false,
+      },
+    );
+    assert!(index_targets(&indexes, &[sum], vec![]).is_err());
+
+    let set =
+      || mutation(key(&["posts", "1"]), MutationKind::Set(KvValue::U64(1)));
+    let index_key = |index: &str| IndexKey {
+      mutation: 0,
+      index: index.to_string(),
+      part: key(&["x"]),
+    };
+    assert!(
+      index_targets(&indexes, &[set()], vec![index_key("by_email")]).is_err()
+    );
+    assert!(
+      index_targets(&indexes, &[set()], vec![index_key("by_name")]).is_err()
+    );
+  }
+
+  #[test]
+  fn rejects_sets_under_indexes_defined_elsewhere() {
+    let indexes = vec![KvIndex {
+      name: "by_email".to_string(),
+      prefix: key(&["users"]),
+      defined: false,
+    }];
+    let set =
+      mutation(key(&["users", "ada"]), MutationKind::Set(KvValue::U64(1)));
+    let err = index_targets(&indexes, &[set], vec![]).unwrap_err();
+    assert!(matches!(
+      &*err.0,
+      KvErrorKind::IndexNotDefined(name) if name == "by_email"
+    ));
+    // Deleting entries doesn't need the index part.
+    let delete = mutation(key(&["users", "ada"]), MutationKind::Delete);
+    let targets = index_targets(&indexes, &[delete], vec![]).unwrap();
+    assert_eq!(targets.len(), 1);
+    assert!(targets[0].entry.is_none());
+  }
+}
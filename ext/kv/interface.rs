@@ -7,6 +7,7 @@ use async_trait::async_trait;
 use deno_core::OpState;
 use deno_error::JsErrorBox;
 use denokv_proto::Database;
+use denokv_proto::KvValue;
 
 #[async_trait(?Send)]
 pub trait DatabaseHandler {
@@ -17,4 +18,59 @@ pub trait DatabaseHandler {
     state: Rc<RefCell<OpState>>,
     path: Option<String>,
   ) -> Result<Self::DB, JsErrorBox>;
+
+  /// Opens a feed of the writes committed to the database at `path`, either
+  /// from now on or from after the write with versionstamp `since`.
+  async fn open_change_feed(
+    &self,
+    _state: Rc<RefCell<OpState>>,
+    _path: Option<String>,
+    _since: Option<[u8; 10]>,
+  ) -> Result<Box<dyn ChangeFeed>, JsErrorBox> {
+    Err(JsErrorBox::type_error(
+      "Change feeds are only supported for local databases",
+    ))
+  }
+
+  /// Reads when the entries with `keys` of the database at `path` expire, in
+  /// milliseconds since the epoch, for exports. Databases that can't tell
+  /// report that none of them expire.
+  async fn read_expirations(
+    &self,
+    _state: Rc<RefCell<OpState>>,
+    _path: Option<String>,
+    keys: Vec<Vec<u8>>,
+  ) -> Result<Vec<Option<u64>>, JsErrorBox> {
+    Ok(vec![None; keys.len()])
+  }
+
+  /// Stops recording the writes committed to the database at `path` for
+  /// change feeds, and drops the ones recorded so far.
+  async fn disable_change_log(
+    &self,
+    _state: Rc<RefCell<OpState>>,
+    _path: Option<String>,
+  ) -> Result<(), JsErrorBox> {
+    Err(JsErrorBox::type_error(
+      "Change feeds are only supported for local databases",
+    ))
+  }
+}
+
+/// A write committed to a database.
+pub struct KvCommit {
+  pub versionstamp: [u8; 10],
+  pub changes: Vec<KvChange>,
+}
+
+/// A key changed by a write. Deleted keys have no value.
+pub struct KvChange {
+  pub key: Vec<u8>,
+  pub value: Option<KvValue>,
+}
+
+#[async_trait(?Send)]
+pub trait ChangeFeed {
+  /// Waits for the next committed write.
+  async fn next(&mut self) -> Result<KvCommit, JsErrorBox>;
 }
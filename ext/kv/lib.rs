@@ -1,7 +1,10 @@
 // Copyright 2018-2026 the Deno authors. MIT license.
 
+pub mod changes;
 pub mod config;
 pub mod dynamic;
+pub mod export;
+pub mod index;
 mod interface;
 pub mod remote;
 pub mod sqlite;
@@ -55,6 +58,8 @@ use denokv_proto::decode_key;
 use denokv_proto::encode_key;
 use dynamic::DynamicDbHandler;
 use dynamic::RcDynamicDb;
+use index::IndexKey;
+use index::KvIndex;
 use log::debug;
 use serde::Deserialize;
 use serde::Serialize;
@@ -75,6 +80,13 @@ deno_core::extension!(deno_kv,
     op_kv_finish_dequeued_message,
     op_kv_watch,
     op_kv_watch_next,
+    op_kv_export_read,
+    op_kv_import,
+    op_kv_changes_open,
+    op_kv_changes_disable,
+    op_kv_changes_next,
+    op_kv_define_index,
+    op_kv_backfill_index,
   ],
   lazy_loaded_js = [ "01_db.ts" ],
   options = {
@@ -89,6 +101,8 @@ deno_core::extension!(deno_kv,
 
 struct DatabaseResource {
   db: RcDynamicDb,
+  path: Option<String>,
+  indexes: RefCell<Vec<KvIndex>>,
   cancel_handle: Rc<CancelHandle>,
 }
 
@@ -119,6 +133,22 @@ impl Resource for DatabaseWatcherResource {
   }
 }
 
+struct ChangeFeedResource {
+  feed: AsyncRefCell<Box<dyn ChangeFeed>>,
+  db_cancel_handle: Rc<CancelHandle>,
+  cancel_handle: Rc<CancelHandle>,
+}
+
+impl Resource for ChangeFeedResource {
+  fn name(&self) -> Cow<'_, str> {
+    "databaseChangeFeed".into()
+  }
+
+  fn close(self: Rc<Self>) {
+    self.cancel_handle.cancel()
+  }
+}
+
 #[derive(Debug, Boxed, deno_error::JsError)]
 pub struct KvError(pub Box<KvErrorKind>);
 
@@ -208,6 +238,35 @@ pub enum KvErrorKind {
   #[class(type)]
   #[error("Invalid range")]
   InvalidRange,
+  #[class(type)]
+  #[error("Invalid exported entry: {0}")]
+  InvalidExportEntry(String),
+  #[class(generic)]
+  #[error(transparent)]
+  Sqlite(rusqlite::Error),
+  #[class(type)]
+  #[error("Invalid versionstamp")]
+  InvalidVersionstamp,
+  #[class(type)]
+  #[error("Invalid index: {0}")]
+  InvalidIndex(String),
+  #[class(type)]
+  #[error("Index '{0}' is already defined on another prefix")]
+  IndexAlreadyDefined(String),
+  #[class(type)]
+  #[error("Unknown index '{0}'")]
+  UnknownIndex(String),
+  #[class(type)]
+  #[error(
+    "Index '{0}' must be defined with defineIndex() before setting keys under its prefix"
+  )]
+  IndexNotDefined(String),
+  #[class(type)]
+  #[error("'{0}' mutations can't be applied to indexed keys")]
+  UnsupportedIndexedMutation(&'static str),
+  #[class(generic)]
+  #[error("Too many concurrent writes to indexed keys")]
+  IndexContention,
 }
 
 #[op2(stack_trace)]
@@ -224,11 +283,14 @@ async fn op_kv_database_open(
     state.borrow::<Rc<dyn DynamicDbHandler>>().clone()
   };
   let db = handler
-    .dyn_open(state.clone(), path)
+    .dyn_open(state.clone(), path.clone())
     .await
     .map_err(KvErrorKind::DatabaseHandler)?;
+  let indexes = index::load_indexes(&db).await?;
   let rid = state.borrow_mut().resource_table.add(DatabaseResource {
     db,
+    path,
+    indexes: RefCell::new(indexes),
     cancel_handle: CancelHandle::new_rc(),
   });
   Ok(rid)
@@ -893,15 +955,16 @@ async fn op_kv_atomic_write(
   #[serde] checks: Vec<V8KvCheck>,
   #[serde] mutations: Vec<V8KvMutation>,
   #[serde] enqueues: Vec<V8Enqueue>,
+  #[serde] index_keys: Vec<V8KvIndexKey>,
 ) -> Result<Option<String>, KvError> {
   let current_timestamp = chrono::Utc::now();
-  let db = {
+  let (db, indexes) = {
     let state = state.borrow();
     let resource = state
       .resource_table
       .get::<DatabaseResource>(rid)
       .map_err(KvErrorKind::Resource)?;
-    resource.db.clone()
+    (resource.db.clone(), resource.indexes.borrow().clone())
   };
 
   let config = {
@@ -932,6 +995,10 @@ async fn op_kv_atomic_write(
     .map(|e| enqueue_from_v8(e, current_timestamp))
     .collect::<Result<Vec<Enqueue>, std::io::Error>>()
     .map_err(KvErrorKind::InvalidEnqueue)?;
+  let index_keys = index_keys
+    .into_iter()
+    .map(index_key_from_v8)
+    .collect::<Result<Vec<IndexKey>, KvError>>()?;
 
   let mut total_payload_size = 0usize;
   let mut total_key_size = 0usize;
@@ -984,14 +1051,132 @@ async fn op_kv_atomic_write(
     enqueues,
   };
 
-  let result = db
-    .atomic_write(atomic_write)
-    .await
-    .map_err(KvErrorKind::Kv)?;
+  let result =
+    index::atomic_write_indexed(&db, &indexes, atomic_write, index_keys)
+      .await?;
 
   Ok(result.map(|res| faster_hex::hex_string(&res.versionstamp)))
 }
 
+// (mutation index, index name, index key)
+type V8KvIndexKey = (usize, String, KvKey);
+
+fn index_key_from_v8(
+  (mutation, index, key): V8KvIndexKey,
+) -> Result<IndexKey, KvError> {
+  if key.len() != 1 {
+    return Err(
+      KvErrorKind::InvalidIndex(format!(
+        "the key of index '{index}' must be a single key part"
+      ))
+      .into_box(),
+    );
+  }
+  Ok(IndexKey {
+    mutation,
+    index,
+    part: encode_v8_key(key)?,
+  })
+}
+
+#[op2]
+async fn op_kv_define_index(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+  #[string] name: String,
+  #[serde] prefix: KvKey,
+) -> Result<(), KvError> {
+  let resource = state
+    .borrow()
+    .resource_table
+    .get::<DatabaseResource>(rid)
+    .map_err(KvErrorKind::Resource)?;
+  if name.is_empty() {
+    return Err(
+      KvErrorKind::InvalidIndex("the name cannot be empty".to_string())
+        .into_box(),
+    );
+  }
+  let reserved = matches!(
+    prefix.first(),
+    Some(AnyValue::String(part))
+      if part == index::INDEX_ROOT
+        || part == index::INDEX_REF_ROOT
+        || part == index::INDEX_DEF_ROOT
+  );
+  if prefix.is_empty() || reserved {
+    return Err(
+      KvErrorKind::InvalidIndex(format!(
+        "index '{name}' must be defined on a non-empty prefix outside of other indexes"
+      ))
+      .into_box(),
+    );
+  }
+  let prefix = encode_v8_key(prefix)?;
+  index::store_index(&resource.db, &name, &prefix).await?;
+  let mut indexes = resource.indexes.borrow_mut();
+  match indexes.iter_mut().find(|index| index.name == name) {
+    Some(index) if index.prefix == prefix => index.defined = true,
+    Some(_) => return Err(KvErrorKind::IndexAlreadyDefined(name).into_box()),
+    None => indexes.push(KvIndex {
+      name,
+      prefix,
+      defined: true,
+    }),
+  }
+  Ok(())
+}
+
+// (key, versionstamp, index key)
+type V8KvBackfillEntry = (KvKey, String, Option<KvKey>);
+
+#[op2]
+async fn op_kv_backfill_index(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+  #[string] name: String,
+  #[serde] entries: Vec<V8KvBackfillEntry>,
+) -> Result<(), KvError> {
+  let (db, index) = {
+    let state = state.borrow();
+    let resource = state
+      .resource_table
+      .get::<DatabaseResource>(rid)
+      .map_err(KvErrorKind::Resource)?;
+    let index = resource
+      .indexes
+      .borrow()
+      .iter()
+      .find(|index| index.name == name)
+      .cloned();
+    (resource.db.clone(), index)
+  };
+  let Some(index) = index else {
+    return Err(KvErrorKind::UnknownIndex(name).into_box());
+  };
+  let entries = entries
+    .into_iter()
+    .map(|(key, versionstamp, part)| {
+      let mut out = [0u8; 10];
+      if versionstamp.len() != out.len() * 2 {
+        return Err(KvErrorKind::InvalidVersionstamp.into_box());
+      }
+      faster_hex::hex_decode(versionstamp.as_bytes(), &mut out)
+        .map_err(|_| KvErrorKind::InvalidVersionstamp)?;
+      let part = match part {
+        Some(part) => Some(index_key_from_v8((0, name.clone(), part))?.part),
+        None => None,
+      };
+      Ok(index::BackfillEntry {
+        key: encode_v8_key(key)?,
+        versionstamp: out,
+        part,
+      })
+    })
+    .collect::<Result<Vec<_>, KvError>>()?;
+  index::backfill_index(&db, &index, entries).await
+}
+
 // (prefix, start, end)
 type EncodeCursorRangeSelector = (Option<KvKey>, Option<KvKey>, Option<KvKey>);
 
@@ -1007,6 +1192,89 @@ fn op_kv_encode_cursor(
   Ok(cursor)
 }
 
+#[op2]
+#[serde]
+async fn op_kv_export_read(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+  #[serde] prefix: KvKey,
+  #[serde] cursor: Option<JsBuffer>,
+) -> Result<(ToJsBuffer, Option<ToJsBuffer>), KvError> {
+  let (db, handler, path) = {
+    let state = state.borrow();
+    let resource = state
+      .resource_table
+      .get::<DatabaseResource>(rid)
+      .map_err(KvErrorKind::Resource)?;
+    (
+      resource.db.clone(),
+      state.borrow::<Rc<dyn DynamicDbHandler>>().clone(),
+      resource.path.clone(),
+    )
+  };
+
+  let config = {
+    let state = state.borrow();
+    state.borrow::<Rc<KvConfig>>().clone()
+  };
+
+  let prefix = encode_v8_key(prefix)?;
+  check_read_key_size(&prefix, &config)?;
+  if let Some(cursor) = &cursor
+    && !cursor.starts_with(&prefix)
+  {
+    return Err(KvErrorKind::InvalidCursor.into_box());
+  }
+  let limit = export::EXPORT_PAGE_SIZE.min(config.max_read_entries as u32);
+  let (entries, cursor) =
+    export::read_export_page(&db, &prefix, cursor.as_deref(), limit).await?;
+  let expirations = handler
+    .dyn_read_expirations(
+      state.clone(),
+      path,
+      entries.iter().map(|entry| entry.key.clone()).collect(),
+    )
+    .await
+    .map_err(KvErrorKind::DatabaseHandler)?;
+  let mut out = Vec::new();
+  for (entry, expire_at) in entries.into_iter().zip(expirations) {
+    export::write_export_line(&mut out, entry, expire_at)?;
+  }
+  Ok((out.into(), cursor.map(Into::into)))
+}
+
+#[op2]
+#[smi]
+async fn op_kv_import(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+  #[buffer] data: JsBuffer,
+) -> Result<u32, KvError> {
+  let db = {
+    let state = state.borrow();
+    let resource = state
+      .resource_table
+      .get::<DatabaseResource>(rid)
+      .map_err(KvErrorKind::Resource)?;
+    resource.db.clone()
+  };
+
+  let config = {
+    let state = state.borrow();
+    state.borrow::<Rc<KvConfig>>().clone()
+  };
+
+  let text = std::str::from_utf8(&data)
+    .map_err(|err| KvErrorKind::InvalidExportEntry(err.to_string()))?;
+  let entries = text
+    .lines()
+    .filter(|line| !line.trim().is_empty())
+    .map(export::parse_export_line)
+    .collect::<Result<Vec<_>, _>>()?;
+  let count = export::import_entries(&db, entries, &config).await?;
+  Ok(count as u32)
+}
+
 fn check_read_key_size(key: &[u8], config: &KvConfig) -> Result<(), KvError> {
   if key.len() > config.max_read_key_size_bytes {
     Err(
@@ -1061,3 +1329,131 @@ fn check_enqueue_payload_size(
     Ok(payload.len())
   }
 }
+
+#[op2]
+#[smi]
+async fn op_kv_changes_open(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+  #[string] since: Option<String>,
+) -> Result<ResourceId, KvError> {
+  let (handler, path, db_cancel_handle) = {
+    let state = state.borrow();
+    let resource = state
+      .resource_table
+      .get::<DatabaseResource>(rid)
+      .map_err(KvErrorKind::Resource)?;
+    (
+      state.borrow::<Rc<dyn DynamicDbHandler>>().clone(),
+      resource.path.clone(),
+      resource.cancel_handle.clone(),
+    )
+  };
+
+  let since = match since {
+    Some(since) => {
+      let mut out = [0u8; 10];
+      if since.len() != out.len() * 2 {
+        return Err(KvErrorKind::InvalidVersionstamp.into_box());
+      }
+      faster_hex::hex_decode(since.as_bytes(), &mut out)
+        .map_err(|_| KvErrorKind::InvalidVersionstamp)?;
+      Some(out)
+    }
+    None => None,
+  };
+
+  let feed = handler
+    .dyn_open_change_feed(state.clone(), path, since)
+    .await
+    .map_err(KvErrorKind::DatabaseHandler)?;
+  let rid = state.borrow_mut().resource_table.add(ChangeFeedResource {
+    feed: AsyncRefCell::new(feed),
+    db_cancel_handle,
+    cancel_handle: CancelHandle::new_rc(),
+  });
+  Ok(rid)
+}
+
+#[op2]
+async fn op_kv_changes_disable(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+) -> Result<(), KvError> {
+  let (handler, path) = {
+    let state = state.borrow();
+    let resource = state
+      .resource_table
+      .get::<DatabaseResource>(rid)
+      .map_err(KvErrorKind::Resource)?;
+    (
+      state.borrow::<Rc<dyn DynamicDbHandler>>().clone(),
+      resource.path.clone(),
+    )
+  };
+  handler
+    .dyn_disable_change_log(state, path)
+    .await
+    .map_err(KvErrorKind::DatabaseHandler)?;
+  Ok(())
+}
+
+#[derive(Serialize)]
+struct ToV8KvChange {
+  key: KvKey,
+  value: Option<ToV8Value>,
+}
+
+#[op2]
+#[serde]
+async fn op_kv_changes_next(
+  state: Rc<RefCell<OpState>>,
+  #[smi] rid: ResourceId,
+) -> Result<Option<(ByteString, Vec<ToV8KvChange>)>, KvError> {
+  let resource = {
+    let state = state.borrow();
+    state
+      .resource_table
+      .get::<ChangeFeedResource>(rid)
+      .map_err(KvErrorKind::Resource)?
+  };
+
+  let db_cancel_handle = resource.db_cancel_handle.clone();
+  let cancel_handle = resource.cancel_handle.clone();
+  let Ok(Ok(mut feed)) = RcRef::map(resource, |r| &r.feed)
+    .borrow_mut()
+    .or_cancel(db_cancel_handle.clone())
+    .or_cancel(cancel_handle.clone())
+    .await
+  else {
+    return Ok(None);
+  };
+  let Ok(Ok(commit)) = feed
+    .next()
+    .or_cancel(db_cancel_handle)
+    .or_cancel(cancel_handle)
+    .await
+  else {
+    return Ok(None);
+  };
+
+  let commit = commit.map_err(KvErrorKind::DatabaseHandler)?;
+  let changes = commit
+    .changes
+    .into_iter()
+    .map(|change| {
+      Ok(ToV8KvChange {
+        key: decode_key(&change.key)?
+          .0
+          .into_iter()
+          .map(key_part_to_v8)
+          .collect(),
+        value: change.value.map(Into::into),
+      })
+    })
+    .collect::<Result<_, std::io::Error>>()?;
+  Ok(Some((
+    faster_hex::hex_string(&commit.versionstamp).into(),
+    changes,
+  )))
+}
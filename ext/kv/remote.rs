@@ -196,6 +196,9 @@ impl DatabaseHandler for RemoteDbHandler {
         local_address: None,
         client_builder_hook: None,
         http2_max_header_list_size: None,
+        recorder: None,
+        cache: None,
+        timeouts: Default::default(),
       },
     )
     .map_err(JsErrorBox::from_err)?;
@@ -15,17 +15,106 @@ use deno_core::unsync::spawn_blocking;
 use deno_error::JsErrorBox;
 use deno_permissions::OpenAccessKind;
 use deno_permissions::PermissionsContainer;
+use denokv_proto::CommitResult;
+use denokv_proto::ReadRangeOutput;
+use denokv_proto::WatchStream;
 pub use denokv_sqlite::SqliteBackendError;
 use denokv_sqlite::SqliteConfig;
 use denokv_sqlite::SqliteNotifier;
 use rand::SeedableRng;
 use rusqlite::OpenFlags;
+use tokio::sync::watch;
 
+use crate::AtomicWrite;
+use crate::ChangeFeed;
+use crate::Database;
 use crate::DatabaseHandler;
+use crate::ReadRange;
+use crate::SnapshotReadOptions;
+use crate::changes::SqliteChangeFeed;
+use crate::changes::disable_change_log;
+use crate::export::read_sqlite_expirations;
 
-static SQLITE_NOTIFIERS_MAP: OnceLock<Mutex<HashMap<PathBuf, SqliteNotifier>>> =
+static SQLITE_NOTIFIERS_MAP: OnceLock<Mutex<HashMap<PathBuf, Notifiers>>> =
   OnceLock::new();
 
+/// Notifiers shared by the connections of the process to a database file.
+#[derive(Clone)]
+struct Notifiers {
+  kv: SqliteNotifier,
+  /// Counts the writes committed, to wake change feeds.
+  commits: Arc<watch::Sender<u64>>,
+}
+
+impl Default for Notifiers {
+  fn default() -> Self {
+    Self {
+      kv: SqliteNotifier::default(),
+      commits: Arc::new(watch::Sender::new(0)),
+    }
+  }
+}
+
+impl Notifiers {
+  fn for_path(notifier_key: Option<PathBuf>) -> Self {
+    let Some(notifier_key) = notifier_key else {
+      return Self::default();
+    };
+    SQLITE_NOTIFIERS_MAP
+      .get_or_init(Default::default)
+      .lock()
+      .unwrap()
+      .entry(notifier_key)
+      .or_default()
+      .clone()
+  }
+}
+
+/// A local database, waking the change feeds of its file when it commits.
+#[derive(Clone)]
+pub struct SqliteDb {
+  db: denokv_sqlite::Sqlite,
+  commits: Arc<watch::Sender<u64>>,
+}
+
+#[async_trait(?Send)]
+impl Database for SqliteDb {
+  type QMH = <denokv_sqlite::Sqlite as Database>::QMH;
+
+  async fn snapshot_read(
+    &self,
+    requests: Vec<ReadRange>,
+    options: SnapshotReadOptions,
+  ) -> Result<Vec<ReadRangeOutput>, JsErrorBox> {
+    self.db.snapshot_read(requests, options).await
+  }
+
+  async fn atomic_write(
+    &self,
+    write: AtomicWrite,
+  ) -> Result<Option<CommitResult>, JsErrorBox> {
+    let result = self.db.atomic_write(write).await?;
+    if result.is_some() {
+      self.commits.send_modify(|commits| *commits += 1);
+    }
+    Ok(result)
+  }
+
+  async fn dequeue_next_message(
+    &self,
+  ) -> Result<Option<Self::QMH>, JsErrorBox> {
+    self.db.dequeue_next_message().await
+  }
+
+  fn watch(&self, keys: Vec<Vec<u8>>) -> WatchStream {
+    self.db.watch(keys)
+  }
+
+  fn close(&self) {
+    self.db.close()
+  }
+}
+
 pub struct SqliteDbHandler {
   pub default_storage_dir: Option<PathBuf>,
   versionstamp_rng_seed: Option<u64>,
@@ -49,21 +138,23 @@ deno_error::js_error_wrapper!(
   "TypeError"
 );
 
+pub(crate) type ConnGen =
+  Arc<dyn Fn() -> rusqlite::Result<rusqlite::Connection> + Send + Sync>;
+
 #[derive(Debug)]
 enum Mode {
   Disk,
   InMemory,
 }
 
-#[async_trait(?Send)]
-impl DatabaseHandler for SqliteDbHandler {
-  type DB = denokv_sqlite::Sqlite;
-
-  async fn open(
+impl SqliteDbHandler {
+  /// Resolves the connections to the database at `path`, along with the
+  /// canonical path of the database file if it is on disk.
+  async fn resolve_conn_gen(
     &self,
-    state: Rc<RefCell<OpState>>,
+    state: &RefCell<OpState>,
     path: Option<String>,
-  ) -> Result<Self::DB, JsErrorBox> {
+  ) -> Result<(ConnGen, Option<PathBuf>), JsErrorBox> {
     enum PathOrInMemory {
       InMemory,
       Path(PathBuf),
@@ -102,11 +193,9 @@ impl DatabaseHandler for SqliteDbHandler {
       }
     }
 
-    let path = validate_path(&state, path)?;
+    let path = validate_path(state, path)?;
     let default_storage_dir = self.default_storage_dir.clone();
-    type ConnGen =
-      Arc<dyn Fn() -> rusqlite::Result<rusqlite::Connection> + Send + Sync>;
-    let (conn_gen, notifier_key): (ConnGen, _) = spawn_blocking(move || {
+    spawn_blocking(move || {
       denokv_sqlite::sqlite_retry_loop(move || {
         let mode = match std::env::var("DENO_KV_DB_MODE")
           .unwrap_or_default()
@@ -170,45 +259,125 @@ impl DatabaseHandler for SqliteDbHandler {
     })
     .await
     .unwrap()
-    .map_err(JsErrorBox::from_err)?;
-
-    let notifier = if let Some(notifier_key) = notifier_key {
-      SQLITE_NOTIFIERS_MAP
-        .get_or_init(Default::default)
-        .lock()
-        .unwrap()
-        .entry(notifier_key)
-        .or_default()
-        .clone()
-    } else {
-      SqliteNotifier::default()
-    };
+    .map_err(JsErrorBox::from_err)
+  }
+}
 
-    let versionstamp_rng_seed = self.versionstamp_rng_seed;
+#[async_trait(?Send)]
+impl DatabaseHandler for SqliteDbHandler {
+  type DB = SqliteDb;
 
-    let config = SqliteConfig {
-      batch_timeout: None,
-      num_workers: 1,
-    };
+  async fn open(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+  ) -> Result<Self::DB, JsErrorBox> {
+    let (conn_gen, notifier_key) = self.resolve_conn_gen(&state, path).await?;
+    let notifiers = Notifiers::for_path(notifier_key);
+    Ok(SqliteDb {
+      db: open_sqlite(conn_gen, notifiers.kv, self.versionstamp_rng_seed)?,
+      commits: notifiers.commits,
+    })
+  }
 
-    denokv_sqlite::Sqlite::new(
-      move || {
-        let conn =
-          conn_gen().map_err(|e| JsErrorBox::generic(e.to_string()))?;
-        conn
-          .pragma_update(None, "journal_mode", "wal")
-          .map_err(|e| JsErrorBox::generic(e.to_string()))?;
-        Ok((
-          conn,
-          match versionstamp_rng_seed {
-            Some(seed) => Box::new(rand::rngs::StdRng::seed_from_u64(seed)),
-            None => Box::new(rand::rngs::StdRng::from_entropy()),
-          },
-        ))
-      },
-      notifier,
-      config,
-    )
-    .map_err(|e| JsErrorBox::generic(e.to_string()))
+  async fn open_change_feed(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    since: Option<[u8; 10]>,
+  ) -> Result<Box<dyn ChangeFeed>, JsErrorBox> {
+    let (conn_gen, notifier_key) = self.resolve_conn_gen(&state, path).await?;
+    if notifier_key.is_none() {
+      return Err(JsErrorBox::type_error(
+        "Change feeds are not supported for in-memory databases",
+      ));
+    }
+    let commits = Notifiers::for_path(notifier_key).commits.subscribe();
+    Ok(Box::new(
+      SqliteChangeFeed::open(conn_gen, commits, since).await?,
+    ))
   }
+
+  async fn disable_change_log(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+  ) -> Result<(), JsErrorBox> {
+    let (conn_gen, notifier_key) = self.resolve_conn_gen(&state, path).await?;
+    if notifier_key.is_none() {
+      return Ok(());
+    }
+    spawn_blocking(move || {
+      let conn = conn_gen()?;
+      conn.busy_timeout(std::time::Duration::from_secs(5))?;
+      disable_change_log(&conn)
+    })
+    .await
+    .unwrap()
+    .map_err(|err| JsErrorBox::generic(err.to_string()))
+  }
+
+  async fn read_expirations(
+    &self,
+    state: Rc<RefCell<OpState>>,
+    path: Option<String>,
+    keys: Vec<Vec<u8>>,
+  ) -> Result<Vec<Option<u64>>, JsErrorBox> {
+    let (conn_gen, notifier_key) = self.resolve_conn_gen(&state, path).await?;
+    // A new connection to an in-memory database doesn't see its entries.
+    if notifier_key.is_none() {
+      return Ok(vec![None; keys.len()]);
+    }
+    spawn_blocking(move || {
+      let conn = conn_gen()?;
+      conn.busy_timeout(std::time::Duration::from_secs(5))?;
+      read_sqlite_expirations(&conn, &keys)
+    })
+    .await
+    .unwrap()
+    .map_err(|err| JsErrorBox::generic(err.to_string()))
+  }
+}
+
+/// Opens the KV database at `path` without permission checks, for tools
+/// working on database files directly.
+pub fn open_sqlite_file(
+  path: PathBuf,
+) -> Result<denokv_sqlite::Sqlite, JsErrorBox> {
+  let flags = OpenFlags::default().difference(OpenFlags::SQLITE_OPEN_URI);
+  open_sqlite(
+    Arc::new(move || rusqlite::Connection::open_with_flags(&path, flags)),
+    SqliteNotifier::default(),
+    None,
+  )
+}
+
+fn open_sqlite(
+  conn_gen: ConnGen,
+  notifier: SqliteNotifier,
+  versionstamp_rng_seed: Option<u64>,
+) -> Result<denokv_sqlite::Sqlite, JsErrorBox> {
+  let config = SqliteConfig {
+    batch_timeout: None,
+    num_workers: 1,
+  };
+
+  denokv_sqlite::Sqlite::new(
+    move || {
+      let conn = conn_gen().map_err(|e| JsErrorBox::generic(e.to_string()))?;
+      conn
+        .pragma_update(None, "journal_mode", "wal")
+        .map_err(|e| JsErrorBox::generic(e.to_string()))?;
+      Ok((
+        conn,
+        match versionstamp_rng_seed {
+          Some(seed) => Box::new(rand::rngs::StdRng::seed_from_u64(seed)),
+          None => Box::new(rand::rngs::StdRng::from_entropy()),
+        },
+      ))
+    },
+    notifier,
+    config,
+  )
+  .map_err(|e| JsErrorBox::generic(e.to_string()))
 }
@@ -719,6 +719,7 @@ async function connect(args) {
               autoSelectFamily: args.autoSelectFamily ?? true,
               autoSelectFamilyAttemptDelay: args.autoSelectFamilyAttemptDelay ??
                 250,
+              dns: args.dns,
             },
           );
         localAddr.transport = "tcp";
@@ -64,6 +64,7 @@ async function connectTls({
   unsafelyDisableHostnameVerification = false,
   autoSelectFamily = true,
   autoSelectFamilyAttemptDelay = 250,
+  dns = undefined,
 }) {
   if (transport !== "tcp") {
     throw new TypeError(`Unsupported transport: '${transport}'`);
@@ -81,7 +82,7 @@ async function connectTls({
     { hostname, port },
     { caCerts, alpnProtocols, serverName, unsafelyDisableHostnameVerification },
     keyPair,
-    { autoSelectFamily, autoSelectFamilyAttemptDelay },
+    { autoSelectFamily, autoSelectFamilyAttemptDelay, dns },
   );
   localAddr.transport = "tcp";
   remoteAddr.transport = "tcp";
@@ -64,6 +64,7 @@ const {
 } = primordials;
 
 let getEndpointResource;
+let getListenerResource;
 
 function promiseFinallyWithoutUnhandled(p, f) {
   return PromisePrototypeThen(p, f, f);
@@ -210,6 +211,10 @@ class QuicListener {
   stop() {
     op_quic_listener_stop(this.#listener);
   }
+
+  static {
+    getListenerResource = (l) => l.#listener;
+  }
 }
 
 class QuicIncoming {
@@ -605,6 +610,7 @@ internals.getQuicStreamResourceCount = getQuicStreamResourceCount;
 
 export {
   connectQuic,
+  getListenerResource,
   QuicBidirectionalStream,
   QuicConn,
   QuicEndpoint,
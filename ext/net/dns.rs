@@ -0,0 +1,373 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! Custom DNS resolution shared by `Deno.resolveDns()`, `Deno.connect()` and
+//! `Deno.createHttpClient()`.
+//!
+//! A [`DnsResolverOptions`] names the servers to query, over plain DNS,
+//! DNS-over-TLS or DNS-over-HTTPS, and static host overrides that take
+//! precedence over them. Without name servers, names that are not overridden
+//! are resolved by the system resolver. DNS-over-TLS and DNS-over-HTTPS name
+//! servers are verified like any other TLS server Deno connects to, see
+//! [`DnsTlsOptions`].
+
+use std::collections::HashMap;
+use std::io;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use deno_core::OpState;
+use deno_error::JsErrorBox;
+use deno_permissions::PermissionCheckError;
+use deno_permissions::PermissionsContainer;
+use deno_tls::SocketUse;
+use deno_tls::TlsClientConfigOptions;
+use deno_tls::TlsError;
+use deno_tls::TlsKeys;
+use deno_tls::create_client_config;
+use deno_tls::rustls::ClientConfig;
+use deno_tls::rustls::RootCertStore;
+use hickory_proto::xfer::Protocol;
+use hickory_resolver::config::NameServerConfig;
+use hickory_resolver::config::ResolverConfig;
+use hickory_resolver::config::ResolverOpts;
+use hickory_resolver::name_server::TokioConnectionProvider;
+use serde::Deserialize;
+
+use crate::DefaultTlsOptions;
+use crate::UnsafelyIgnoreCertificateErrors;
+use crate::resolve_addr::resolve_addr;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsResolverOptions {
+  #[serde(default)]
+  pub name_servers: Vec<NameServer>,
+  /// Addresses to resolve host names to, instead of querying for them.
+  #[serde(default)]
+  pub hosts: HashMap<String, Vec<IpAddr>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameServer {
+  pub ip_addr: IpAddr,
+  /// Defaults to the well-known port of `protocol`.
+  pub port: Option<u16>,
+  #[serde(default)]
+  pub protocol: NameServerProtocol,
+  /// The name the server's certificate is verified against. Required for
+  /// DNS-over-TLS and DNS-over-HTTPS.
+  pub server_name: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum NameServerProtocol {
+  #[default]
+  Udp,
+  Tcp,
+  Tls,
+  Https,
+}
+
+/// The certificates DNS-over-TLS and DNS-over-HTTPS name servers are verified
+/// against.
+#[derive(Default)]
+pub struct DnsTlsOptions {
+  /// Deno's root certificate store, which accounts for `--cert` and
+  /// `DENO_TLS_CA_STORE`.
+  pub root_cert_store: Option<RootCertStore>,
+  /// Additional certificates, e.g. the `caCerts` of a `Deno.HttpClient`.
+  pub ca_certs: Vec<Vec<u8>>,
+  pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
+}
+
+impl DnsTlsOptions {
+  /// The options of the TLS connections Deno makes, if `options` queries name
+  /// servers over TLS.
+  pub fn from_state(
+    state: &OpState,
+    options: &DnsResolverOptions,
+  ) -> Result<Self, DnsConfigError> {
+    if !options.uses_tls() {
+      return Ok(Self::default());
+    }
+    Ok(Self {
+      root_cert_store: state
+        .borrow::<DefaultTlsOptions>()
+        .root_cert_store()
+        .map_err(DnsConfigError::RootCertStore)?,
+      ca_certs: vec![],
+      unsafely_ignore_certificate_errors: state
+        .try_borrow::<UnsafelyIgnoreCertificateErrors>()
+        .and_then(|it| it.0.clone()),
+    })
+  }
+
+  fn client_config(self) -> Result<ClientConfig, TlsError> {
+    create_client_config(TlsClientConfigOptions {
+      root_cert_store: self.root_cert_store,
+      ca_certs: self.ca_certs,
+      unsafely_ignore_certificate_errors: self
+        .unsafely_ignore_certificate_errors,
+      unsafely_disable_hostname_verification: false,
+      cert_chain_and_key: TlsKeys::Null,
+      // hickory sets the ALPN protocols of DNS-over-HTTPS itself.
+      socket_use: SocketUse::GeneralSsl,
+    })
+  }
+}
+
+#[derive(Debug, thiserror::Error, deno_error::JsError)]
+pub enum DnsConfigError {
+  #[class(type)]
+  #[error("A 'serverName' is required for the {protocol} name server {addr}")]
+  MissingServerName {
+    protocol: &'static str,
+    addr: IpAddr,
+  },
+  #[class(inherit)]
+  #[error(transparent)]
+  Tls(#[from] TlsError),
+  #[class(inherit)]
+  #[error("{0}")]
+  RootCertStore(JsErrorBox),
+}
+
+impl NameServer {
+  pub fn socket_addr(&self) -> SocketAddr {
+    let port = self.port.unwrap_or(match self.protocol {
+      NameServerProtocol::Udp | NameServerProtocol::Tcp => 53,
+      NameServerProtocol::Tls => 853,
+      NameServerProtocol::Https => 443,
+    });
+    SocketAddr::new(self.ip_addr, port)
+  }
+
+  fn config(&self) -> Result<NameServerConfig, DnsConfigError> {
+    let (protocol, name) = match self.protocol {
+      NameServerProtocol::Udp => (Protocol::Udp, None),
+      NameServerProtocol::Tcp => (Protocol::Tcp, None),
+      NameServerProtocol::Tls => (Protocol::Tls, Some("DNS-over-TLS")),
+      NameServerProtocol::Https => (Protocol::Https, Some("DNS-over-HTTPS")),
+    };
+    let mut config = NameServerConfig::new(self.socket_addr(), protocol);
+    if let Some(name) = name {
+      let Some(server_name) = &self.server_name else {
+        return Err(DnsConfigError::MissingServerName {
+          protocol: name,
+          addr: self.ip_addr,
+        });
+      };
+      config.tls_dns_name = Some(server_name.clone());
+    }
+    Ok(config)
+  }
+}
+
+impl DnsResolverOptions {
+  /// The resolver configuration for the name servers, or `None` if the
+  /// system configuration should be used.
+  pub fn resolver_config(
+    &self,
+  ) -> Result<Option<ResolverConfig>, DnsConfigError> {
+    if self.name_servers.is_empty() {
+      return Ok(None);
+    }
+    let name_servers = self
+      .name_servers
+      .iter()
+      .map(NameServer::config)
+      .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(ResolverConfig::from_parts(None, vec![], name_servers)))
+  }
+
+  /// Whether any of the name servers is queried over TLS, in which case the
+  /// resolver needs [`DnsTlsOptions`].
+  pub fn uses_tls(&self) -> bool {
+    self.name_servers.iter().any(|name_server| {
+      matches!(
+        name_server.protocol,
+        NameServerProtocol::Tls | NameServerProtocol::Https
+      )
+    })
+  }
+
+  /// The resolver options for the name servers, verifying the TLS ones with
+  /// `tls`.
+  pub fn resolver_opts(
+    &self,
+    tls: DnsTlsOptions,
+  ) -> Result<ResolverOpts, DnsConfigError> {
+    let mut opts = ResolverOpts::default();
+    if self.uses_tls() {
+      opts.tls_config = tls.client_config()?;
+    }
+    Ok(opts)
+  }
+
+  /// Checks the permission to query the name servers.
+  pub fn check_name_servers(
+    &self,
+    permissions: &mut PermissionsContainer,
+    api_name: &str,
+  ) -> Result<(), PermissionCheckError> {
+    for name_server in &self.name_servers {
+      let addr = name_server.socket_addr();
+      permissions
+        .check_net(&(addr.ip().to_string(), Some(addr.port())), api_name)?;
+    }
+    Ok(())
+  }
+
+  /// The overridden addresses of `host`.
+  pub fn host(&self, host: &str) -> Option<&[IpAddr]> {
+    let host = normalize_host(host);
+    self
+      .hosts
+      .iter()
+      .find(|(name, _)| normalize_host(name) == host)
+      .map(|(_, addrs)| addrs.as_slice())
+  }
+}
+
+fn normalize_host(host: &str) -> String {
+  host.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// Resolves host names as configured by a [`DnsResolverOptions`].
+#[derive(Clone, Debug)]
+pub struct DnsResolver {
+  options: Arc<DnsResolverOptions>,
+  /// `None` to use the system resolver.
+  resolver: Option<hickory_resolver::Resolver<TokioConnectionProvider>>,
+}
+
+impl DnsResolver {
+  pub fn new(
+    options: DnsResolverOptions,
+    tls: DnsTlsOptions,
+  ) -> Result<Self, DnsConfigError> {
+    let resolver = match options.resolver_config()? {
+      Some(config) => Some(
+        hickory_resolver::Resolver::builder_with_config(
+          config,
+          TokioConnectionProvider::default(),
+        )
+        .with_options(options.resolver_opts(tls)?)
+        .build(),
+      ),
+      None => None,
+    };
+    Ok(Self {
+      options: Arc::new(options),
+      resolver,
+    })
+  }
+
+  /// Resolves `hostname` into socket addresses with `port`, like
+  /// [`resolve_addr`] does without a resolver.
+  pub async fn resolve_addr(
+    resolver: Option<&Self>,
+    hostname: &str,
+    port: u16,
+  ) -> Result<Vec<SocketAddr>, io::Error> {
+    let Some(this) = resolver else {
+      return Ok(resolve_addr(hostname, port).await?.collect());
+    };
+    let host = hostname.trim_start_matches('[').trim_end_matches(']');
+    if let Some(addrs) = this.options.host(host) {
+      return Ok(
+        addrs
+          .iter()
+          .map(|addr| SocketAddr::new(*addr, port))
+          .collect(),
+      );
+    }
+    match &this.resolver {
+      Some(resolver) if !host.is_empty() && host.parse::<IpAddr>().is_err() => {
+        let lookup = resolver.lookup_ip(host).await?;
+        Ok(
+          lookup
+            .iter()
+            .map(|addr| SocketAddr::new(addr, port))
+            .collect(),
+        )
+      }
+      _ => Ok(resolve_addr(hostname, port).await?.collect()),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[tokio::test]
+  async fn hosts_override_resolution() {
+    let options: DnsResolverOptions = serde_json::from_value(
+      serde_json::json!({ "hosts": { "API.Example.": ["10.0.0.1", "::1"] } }),
+    )
+    .unwrap();
+    let resolver = DnsResolver::new(options, DnsTlsOptions::default()).unwrap();
+    let addrs = DnsResolver::resolve_addr(Some(&resolver), "api.example", 443)
+      .await
+      .unwrap();
+    assert_eq!(
+      addrs,
+      vec![
+        "10.0.0.1:443".parse::<SocketAddr>().unwrap(),
+        "[::1]:443".parse().unwrap()
+      ]
+    );
+    let addrs = DnsResolver::resolve_addr(Some(&resolver), "127.0.0.1", 80)
+      .await
+      .unwrap();
+    assert_eq!(addrs, vec!["127.0.0.1:80".parse().unwrap()]);
+  }
+
+  #[test]
+  fn name_servers() {
+    let options: DnsResolverOptions =
+      serde_json::from_value(serde_json::json!({
+        "nameServers": [
+          { "ipAddr": "192.0.2.1" },
+          { "ipAddr": "192.0.2.2", "protocol": "tls", "serverName": "dns.test" },
+          { "ipAddr": "192.0.2.3", "port": 8443, "protocol": "https",
+            "serverName": "dns.test" },
+        ]
+      }))
+      .unwrap();
+    let addrs = options
+      .name_servers
+      .iter()
+      .map(NameServer::socket_addr)
+      .collect::<Vec<_>>();
+    assert_eq!(
+      addrs,
+      vec![
+        "192.0.2.1:53".parse::<SocketAddr>().unwrap(),
+        "192.0.2.2:853".parse().unwrap(),
+        "192.0.2.3:8443".parse().unwrap(),
+      ]
+    );
+    let config = options.resolver_config().unwrap().unwrap();
+    assert_eq!(config.name_servers().len(), 3);
+    assert_eq!(config.name_servers()[1].protocol, Protocol::Tls);
+    assert_eq!(
+      config.name_servers()[2].tls_dns_name.as_deref(),
+      Some("dns.test")
+    );
+
+    let options: DnsResolverOptions =
+      serde_json::from_value(serde_json::json!({
+        "nameServers": [{ "ipAddr": "192.0.2.1", "protocol": "https" }]
+      }))
+      .unwrap();
+    assert!(matches!(
+      options.resolver_config(),
+      Err(DnsConfigError::MissingServerName { .. })
+    ));
+  }
+}
@@ -1,5 +1,6 @@
 // Copyright 2018-2026 the Deno authors. MIT license.
 
+pub mod dns;
 pub mod happy_eyeballs;
 pub mod io;
 pub mod ops;
@@ -20,6 +21,7 @@ use deno_core::OpState;
 use deno_features::FeatureChecker;
 use deno_tls::RootCertStoreProvider;
 use deno_tls::rustls::RootCertStore;
+pub use quic::ListenerResource as QuicListenerResource;
 pub use quic::QuicError;
 
 pub const UNSTABLE_FEATURE_NAME: &str = "net";
@@ -70,7 +72,7 @@ pub(crate) fn is_unix_socket_abstract_path(path: &std::path::Path) -> bool {
 }
 
 /// Helper for checking unstable features. Used for sync ops.
-fn check_unstable(state: &OpState, api_name: &str) {
+pub fn check_unstable(state: &OpState, api_name: &str) {
   state
     .borrow::<Arc<FeatureChecker>>()
     .check_or_exit(UNSTABLE_FEATURE_NAME, api_name);
@@ -45,6 +45,10 @@ use socket2::Type;
 use tokio::net::TcpStream;
 use tokio::net::UdpSocket;
 
+use crate::dns::DnsConfigError;
+use crate::dns::DnsResolver;
+use crate::dns::DnsResolverOptions;
+use crate::dns::DnsTlsOptions;
 use crate::io::TcpStreamResource;
 use crate::raw::NetworkListenerResource;
 use crate::resolve_addr::resolve_addr;
@@ -98,6 +102,11 @@ pub struct TcpConnectOptions {
   /// Delay in milliseconds between connection attempts. Default: 250
   #[serde(default = "default_attempt_delay")]
   pub auto_select_family_attempt_delay: u64,
+
+  /// Resolve the hostname with these name servers and host overrides
+  /// instead of the system resolver.
+  #[serde(default)]
+  pub dns: Option<DnsResolverOptions>,
 }
 
 fn default_auto_select_family() -> bool {
@@ -197,6 +206,12 @@ pub enum NetError {
   #[class(generic)]
   #[error("Tunnel is not open")]
   TunnelMissing,
+  #[class(inherit)]
+  #[error(transparent)]
+  DnsConfig(#[from] DnsConfigError),
+  #[class(type)]
+  #[error("The 'nameServer' and 'dns' options cannot be used together")]
+  ConflictingDnsOptions,
 }
 
 pub(crate) fn accept_err(e: std::io::Error) -> NetError {
@@ -544,6 +559,8 @@ pub async fn op_net_connect_tcp_inner(
   resource_abort_id: Option<ResourceId>,
   options: Option<TcpConnectOptions>,
 ) -> Result<(ResourceId, IpAddr, IpAddr), NetError> {
+  let options = options.unwrap_or_default();
+
   {
     let mut state_ = state.borrow_mut();
     // If token exists and the address matches to its resolved ips,
@@ -552,12 +569,22 @@ pub async fn op_net_connect_tcp_inner(
       Some(token) => token.check_host(&addr.hostname).to_string(),
       None => addr.hostname.clone(),
     };
-    state_
-      .borrow_mut::<PermissionsContainer>()
+    let permissions = state_.borrow_mut::<PermissionsContainer>();
+    permissions
       .check_net(&(&hostname_to_check, Some(addr.port)), "Deno.connect()")?;
+    permissions.consume_net_quota(&(&hostname_to_check, Some(addr.port)))?;
+    if let Some(dns) = &options.dns {
+      dns.check_name_servers(permissions, "Deno.connect()")?;
+    }
   }
 
-  let options = options.unwrap_or_default();
+  let dns_resolver = match options.dns.clone() {
+    Some(dns) => {
+      let tls = DnsTlsOptions::from_state(&state.borrow(), &dns)?;
+      Some(DnsResolver::new(dns, tls)?)
+    }
+    None => None,
+  };
 
   // Fetch the cancel handle before the first await point. Aborting from JS
   // closes the resource, so fetching it any later would miss an abort that
@@ -572,11 +599,15 @@ pub async fn op_net_connect_tcp_inner(
 
   let tcp_stream_result = async {
     // Resolve all addresses for Happy Eyeballs
-    let resolve_fut = resolve_addr(&addr.hostname, addr.port);
+    let resolve_fut = DnsResolver::resolve_addr(
+      dns_resolver.as_ref(),
+      &addr.hostname,
+      addr.port,
+    );
     let addrs: Vec<SocketAddr> = if let Some(cancel_handle) = &cancel_handle {
-      resolve_fut.or_cancel(cancel_handle).await??.collect()
+      resolve_fut.or_cancel(cancel_handle).await??
     } else {
-      resolve_fut.await?.collect()
+      resolve_fut.await?
     };
 
     if addrs.is_empty() {
@@ -811,10 +842,12 @@ pub async fn op_net_connect_vsock(
     .borrow::<Arc<FeatureChecker>>()
     .check_or_exit("vsock", "Deno.connect");
 
-  state
-    .borrow_mut()
-    .borrow_mut::<PermissionsContainer>()
-    .check_net_vsock(cid, port, "Deno.connect()")?;
+  {
+    let mut state = state.borrow_mut();
+    let permissions = state.borrow_mut::<PermissionsContainer>();
+    permissions.check_net_vsock(cid, port, "Deno.connect()")?;
+    permissions.consume_net_quota_vsock(cid, port)?;
+  }
 
   let addr = VsockAddr::new(cid, port);
   let vsock_stream = VsockStream::connect(addr).await?;
@@ -1044,6 +1077,8 @@ pub struct ResolveAddrArgs {
 #[derive(FromV8)]
 pub struct ResolveDnsOption {
   name_server: Option<NameServer>,
+  #[from_v8(serde)]
+  dns: Option<DnsResolverOptions>,
 }
 
 #[derive(FromV8)]
@@ -1066,6 +1101,40 @@ pub async fn op_dns_resolve(
     cancel_rid,
   } = args;
 
+  let dns = options.as_ref().and_then(|o| o.dns.as_ref());
+  if dns.is_some() && options.as_ref().is_some_and(|o| o.name_server.is_some())
+  {
+    return Err(NetError::ConflictingDnsOptions);
+  }
+
+  // Host overrides answer address queries without querying name servers.
+  if let Some(addrs) = dns.and_then(|dns| dns.host(&query)) {
+    let records = addrs
+      .iter()
+      .filter_map(|addr| match (record_type, addr) {
+        (RecordType::A, std::net::IpAddr::V4(addr)) => {
+          Some(DnsRecordData::A(addr.to_string()))
+        }
+        (RecordType::AAAA, std::net::IpAddr::V6(addr)) => {
+          Some(DnsRecordData::Aaaa(addr.to_string()))
+        }
+        _ => None,
+      })
+      .map(|data| DnsRecordWithTtl {
+        data,
+        record_type: None,
+        ttl: 0,
+      })
+      .collect::<Vec<_>>();
+    if !records.is_empty() {
+      return Ok(records);
+    }
+  }
+
+  let dns_config = dns
+    .map(DnsResolverOptions::resolver_config)
+    .transpose()?
+    .flatten();
   let (config, mut opts) = if let Some(name_server) =
     options.as_ref().and_then(|o| o.name_server.as_ref())
   {
@@ -1081,6 +1150,13 @@ pub async fn op_dns_resolve(
       }
       opts
     })
+  } else if let (Some(dns), Some(config)) = (dns, dns_config) {
+    let tls = DnsTlsOptions::from_state(&state.borrow(), dns)?;
+    let mut opts = dns.resolver_opts(tls)?;
+    if use_edns {
+      opts.edns0 = true;
+    }
+    (config, opts)
   } else {
     system_conf::read_system_conf()?
   };
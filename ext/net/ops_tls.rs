@@ -52,6 +52,8 @@ use tokio::net::TcpStream;
 
 use crate::DefaultTlsOptions;
 use crate::UnsafelyIgnoreCertificateErrors;
+use crate::dns::DnsResolver;
+use crate::dns::DnsTlsOptions;
 use crate::happy_eyeballs::connect_happy_eyeballs;
 use crate::io::TcpStreamResource;
 use crate::ops::IpAddr;
@@ -59,7 +61,6 @@ use crate::ops::NetError;
 use crate::ops::TcpConnectOptions;
 use crate::ops::TlsHandshakeInfo;
 use crate::raw::NetworkListenerResource;
-use crate::resolve_addr::resolve_addr;
 use crate::resolve_addr::resolve_addr_sync;
 use crate::tcp::TcpListener;
 
@@ -435,6 +436,12 @@ pub async fn op_net_connect_tls(
     permissions
       .check_net(&(&addr.hostname, Some(addr.port)), "Deno.connectTls()")
       .map_err(NetError::Permission)?;
+    permissions
+      .consume_net_quota(&(&addr.hostname, Some(addr.port)))
+      .map_err(NetError::Permission)?;
+    if let Some(dns) = options.as_ref().and_then(|o| o.dns.as_ref()) {
+      dns.check_name_servers(permissions, "Deno.connectTls()")?;
+    }
     if let Some(path) = cert_file {
       Some(
         permissions
@@ -476,7 +483,16 @@ pub async fn op_net_connect_tls(
 
   // Resolve all addresses for Happy Eyeballs.
   let options = options.unwrap_or_default();
-  let addrs: Vec<_> = resolve_addr(&addr.hostname, addr.port).await?.collect();
+  let dns_resolver = match options.dns.clone() {
+    Some(dns) => {
+      let tls = DnsTlsOptions::from_state(&state.borrow(), &dns)?;
+      Some(DnsResolver::new(dns, tls)?)
+    }
+    None => None,
+  };
+  let addrs =
+    DnsResolver::resolve_addr(dns_resolver.as_ref(), &addr.hostname, addr.port)
+      .await?;
 
   if addrs.is_empty() {
     return Err(NetError::NoResolvedAddress);
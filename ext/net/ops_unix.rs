@@ -146,12 +146,15 @@ pub async fn op_net_connect_unix(
 ) -> Result<(ResourceId, Option<String>, Option<String>), NetError> {
   let address_path = {
     let mut state = state.borrow_mut();
-    check_unix_socket_path(
-      state.borrow_mut::<PermissionsContainer>(),
+    let permissions = state.borrow_mut::<PermissionsContainer>();
+    let address_path = check_unix_socket_path(
+      permissions,
       Cow::Owned(PathBuf::from(address_path)),
       OpenAccessKind::ReadWriteNoFollow,
       Some("Deno.connect()"),
-    )?
+    )?;
+    permissions.consume_net_quota_unix_socket(&address_path)?;
+    address_path
   };
   let unix_stream = UnixStream::connect(address_path).await?;
   let local_addr = unix_stream.local_addr()?;
@@ -321,7 +321,15 @@ pub(crate) fn op_quic_endpoint_close(
   Ok(())
 }
 
-struct ListenerResource(quinn::Endpoint, Arc<QuicServerConfig>);
+pub struct ListenerResource(quinn::Endpoint, Arc<QuicServerConfig>);
+
+impl ListenerResource {
+  /// The endpoint connections are accepted from, for embedders serving
+  /// protocols on top of QUIC themselves.
+  pub fn endpoint(&self) -> &quinn::Endpoint {
+    &self.0
+  }
+}
 
 impl Drop for ListenerResource {
   fn drop(&mut self) {
@@ -568,13 +576,16 @@ pub(crate) fn op_quic_endpoint_connect(
   #[scoped] transport_config: TransportConfig,
   #[cppgc] key_pair: &TlsKeysHolder,
 ) -> Result<ConnectingResource, QuicError> {
-  state
-    .borrow_mut()
-    .borrow_mut::<PermissionsContainer>()
-    .check_net(
+  {
+    let mut state = state.borrow_mut();
+    let permissions = state.borrow_mut::<PermissionsContainer>();
+    permissions.check_net(
       &(&args.addr.hostname, Some(args.addr.port)),
       "Deno.connectQuic()",
     )?;
+    permissions
+      .consume_net_quota(&(&args.addr.hostname, Some(args.addr.port)))?;
+  }
 
   let sock_addr = resolve_addr_sync(&args.addr.hostname, args.addr.port)?
     .next()
@@ -21,6 +21,7 @@ use deno_fs::FsFileType;
 use deno_fs::OpenOptions;
 use deno_io::fs::FsResult;
 use deno_io::fs::FsStatFs;
+use deno_io::fs::with_write_quota;
 use deno_permissions::CheckedPath;
 use deno_permissions::CheckedPathBuf;
 use deno_permissions::OpenAccessKind;
@@ -363,11 +364,13 @@ pub fn op_node_open_sync(
   let options = get_open_options(flags, Some(mode));
 
   let fs = state.borrow::<FileSystemRc>().clone();
-  let path = state.borrow_mut::<PermissionsContainer>().check_open(
+  let permissions = state.borrow_mut::<PermissionsContainer>();
+  let path = permissions.check_open(
     Cow::Borrowed(path),
     options.access_kind(),
     Some("node:fs.openSync"),
   )?;
+  let write_quota = permissions.write_quota(&path, options.access_kind())?;
 
   // On Windows, opening with create + truncate uses CREATE_ALWAYS which
   // truncates the file to 0 bytes immediately. If the subsequent CRT fd
@@ -389,7 +392,7 @@ pub fn op_node_open_sync(
   #[cfg(not(windows))]
   let open_options = options;
 
-  let file = fs.open_sync(&path, open_options)?;
+  let file = with_write_quota(fs.open_sync(&path, open_options)?, write_quota);
   // For VFS files (e.g. in deno compile), backing_fd() returns None.
   // Assign a virtual fd so the file can still be used through FdTable.
   let fd = match file.clone().backing_fd() {
@@ -422,16 +425,16 @@ pub async fn op_node_open(
   let path = PathBuf::from(path);
   let options = get_open_options(flags, Some(mode));
 
-  let (fs, path) = {
+  let (fs, path, write_quota) = {
     let mut state = state.borrow_mut();
-    (
-      state.borrow::<FileSystemRc>().clone(),
-      state.borrow_mut::<PermissionsContainer>().check_open(
-        Cow::Owned(path),
-        options.access_kind(),
-        Some("node:fs.open"),
-      )?,
-    )
+    let permissions = state.borrow_mut::<PermissionsContainer>();
+    let path = permissions.check_open(
+      Cow::Owned(path),
+      options.access_kind(),
+      Some("node:fs.open"),
+    )?;
+    let write_quota = permissions.write_quota(&path, options.access_kind())?;
+    (state.borrow::<FileSystemRc>().clone(), path, write_quota)
   };
 
   // See op_node_open_sync for why we defer truncation on Windows.
@@ -450,7 +453,10 @@ pub async fn op_node_open(
   #[cfg(not(windows))]
   let open_options = options;
 
-  let file = fs.open_async(path.as_owned(), open_options).await?;
+  let file = with_write_quota(
+    fs.open_async(path.as_owned(), open_options).await?,
+    write_quota,
+  );
   // For VFS files (e.g. in deno compile), backing_fd() returns None.
   // Assign a virtual fd so the file can still be used through FdTable.
   let fd = match file.clone().backing_fd() {
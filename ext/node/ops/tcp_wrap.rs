@@ -701,10 +701,12 @@ impl TCPWrap {
     // the original hostname instead of the resolved IP address, but only
     // when `address` is one of the token's resolved IPs.
     let check_host = self.net_perm_check_host(address);
-    state.borrow_mut::<PermissionsContainer>().check_net(
+    let permissions = state.borrow_mut::<PermissionsContainer>();
+    permissions.check_net(
       &(check_host.as_str(), Some(port as u16)),
       "node:net.connect()",
     )?;
+    permissions.consume_net_quota(&(check_host.as_str(), Some(port as u16)))?;
 
     let addr_str = format!("{}:{}", address, port);
     let socket_addr = match addr_str.to_socket_addrs() {
@@ -769,10 +771,12 @@ impl TCPWrap {
     scope: &mut v8::PinScope,
   ) -> Result<i32, deno_permissions::PermissionCheckError> {
     let check_host = self.net_perm_check_host(address);
-    state.borrow_mut::<PermissionsContainer>().check_net(
+    let permissions = state.borrow_mut::<PermissionsContainer>();
+    permissions.check_net(
       &(check_host.as_str(), Some(port as u16)),
       "node:net.connect()",
     )?;
+    permissions.consume_net_quota(&(check_host.as_str(), Some(port as u16)))?;
 
     let addr_str = format!("{}:{}", address, port);
     let socket_addr = match addr_str.to_socket_addrs() {
@@ -1413,6 +1413,7 @@ fn check_run_permission(
     }
     permissions.check_run(cmd, api_name)?;
   }
+  permissions.consume_run_quota(cmd)?;
   Ok(())
 }
 
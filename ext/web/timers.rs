@@ -8,6 +8,7 @@ use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
 use deno_core::OpState;
+use deno_core::VirtualClock;
 use deno_core::op2;
 
 pub struct StartTime(Instant);
@@ -36,18 +37,29 @@ fn expose_time(duration: Duration, out: &mut [u8]) {
   }
 }
 
+/// Time elapsed since the start of the runtime, following the virtual clock
+/// of `deno test` when one is installed.
+fn elapsed(state: &OpState) -> Duration {
+  let start_time = state.borrow::<StartTime>();
+  match state.try_borrow::<VirtualClock>() {
+    Some(clock) => clock.now().saturating_duration_since(**start_time),
+    None => start_time.elapsed(),
+  }
+}
+
 #[op2(fast)]
 pub fn op_now(state: &mut OpState, #[buffer] buf: &mut [u8]) {
-  let start_time = state.borrow::<StartTime>();
-  let elapsed = start_time.elapsed();
-  expose_time(elapsed, buf);
+  expose_time(elapsed(state), buf);
 }
 
 #[op2(fast)]
 pub fn op_time_origin(state: &mut OpState, #[buffer] buf: &mut [u8]) {
   // https://w3c.github.io/hr-time/#dfn-estimated-monotonic-time-of-the-unix-epoch
-  let wall_time = SystemTime::now();
-  let monotonic_time = state.borrow::<StartTime>().elapsed();
+  let wall_time = match state.try_borrow::<VirtualClock>() {
+    Some(clock) => clock.now_system(),
+    None => SystemTime::now(),
+  };
+  let monotonic_time = elapsed(state);
   let epoch = wall_time.duration_since(UNIX_EPOCH).unwrap() - monotonic_time;
   expose_time(epoch, buf);
 }
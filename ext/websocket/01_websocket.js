@@ -102,6 +102,7 @@ webidl.converters["WebSocketInit"] = webidl.createDictionaryConverter(
     },
     { key: "client", converter: webidl.converters.any },
     { key: "dispatcher", converter: webidl.converters.any },
+    { key: "perMessageDeflate", converter: webidl.converters.any },
   ],
 );
 
@@ -327,6 +328,7 @@ class WebSocket extends EventTarget {
     let clientRid = null;
     let caCerts = null;
     let unsafelyIgnoreCertificateErrors = false;
+    let perMessageDeflate = null;
 
     if (typeof initOrProtocols === "string") {
       protocols = [initOrProtocols];
@@ -340,6 +342,13 @@ class WebSocket extends EventTarget {
         fillHeaders(headers, initOrProtocols.headers);
       }
 
+      // NOTE: non standard extension. Offers permessage-deflate compression.
+      if (initOrProtocols.perMessageDeflate) {
+        perMessageDeflate = initOrProtocols.perMessageDeflate === true
+          ? {}
+          : initOrProtocols.perMessageDeflate;
+      }
+
       // NOTE: non standard extension. This handles Deno.HttpClient parameter
       if (initOrProtocols.client !== undefined) {
         if (
@@ -492,6 +501,7 @@ class WebSocket extends EventTarget {
         caCerts,
         unsafelyIgnoreCertificateErrors,
         clientRid,
+        perMessageDeflate,
       ),
       (create) => {
         this[_rid] = create.rid;
@@ -1033,6 +1043,7 @@ function createWebSocketBranded() {
 
 export {
   _eventLoop,
+  _extensions,
   _idleTimeoutDuration,
   _idleTimeoutTimeout,
   _protocol,
@@ -0,0 +1,683 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! The `permessage-deflate` extension (RFC 7692).
+//!
+//! fastwebsockets knows nothing about extensions and rejects frames with the
+//! RSV1 bit set, which marks compressed messages. Messages are therefore
+//! compressed and decompressed by [`PerMessageDeflate`] above fastwebsockets,
+//! while [`DeflateFrames`] sets and clears the RSV1 bit of the frames going
+//! through the underlying [`WebSocketStream`](crate::stream::WebSocketStream).
+//!
+//! Messages are always compressed with a 32KiB (15 bits) window, so offers
+//! asking for a smaller one are declined, and servers cannot be configured
+//! with a smaller `server_max_window_bits`. Otherwise the window bits options
+//! only limit the window of the peer.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use fastwebsockets::Role;
+use flate2::Compress;
+use flate2::Compression;
+use flate2::Decompress;
+use flate2::FlushCompress;
+use flate2::FlushDecompress;
+use serde::Deserialize;
+
+pub const PERMESSAGE_DEFLATE: &str = "permessage-deflate";
+
+/// Matches the maximum message size of fastwebsockets.
+const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 64 << 20;
+
+const MIN_WINDOW_BITS: u8 = 8;
+const MAX_WINDOW_BITS: u8 = 15;
+
+/// Removed from the end of compressed messages, and appended back before
+/// decompressing them.
+const DEFLATE_TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+const RSV1: u8 = 0x40;
+const OPCODE_MASK: u8 = 0x0f;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerMessageDeflateOptions {
+  #[serde(default)]
+  pub server_no_context_takeover: bool,
+  #[serde(default)]
+  pub client_no_context_takeover: bool,
+  /// Offered by clients to limit the window of the server. Servers only
+  /// accept 15.
+  pub server_max_window_bits: Option<u8>,
+  /// Sent by servers to limit the window of the client.
+  pub client_max_window_bits: Option<u8>,
+  /// The maximum size of a decompressed message, so that a small compressed
+  /// message cannot exhaust memory.
+  pub max_decompressed_size: Option<usize>,
+}
+
+#[derive(Debug, thiserror::Error, deno_error::JsError)]
+pub enum DeflateError {
+  #[class(type)]
+  #[error(
+    "Invalid permessage-deflate window bits {0}: must be between 8 and 15"
+  )]
+  InvalidWindowBits(u8),
+  #[class(type)]
+  #[error(
+    "Unsupported permessage-deflate server window bits {0}: servers always compress with 15 window bits"
+  )]
+  UnsupportedServerWindowBits(u8),
+  #[class(generic)]
+  #[error("Invalid permessage-deflate negotiation: {0}")]
+  InvalidNegotiation(String),
+  #[class(generic)]
+  #[error("Decompressed message exceeds the maximum size of {0} bytes")]
+  MessageTooLarge(usize),
+  #[class(generic)]
+  #[error("Invalid compressed message: {0}")]
+  Decompress(#[from] flate2::DecompressError),
+  #[class(generic)]
+  #[error(transparent)]
+  Compress(#[from] flate2::CompressError),
+}
+
+/// The parameters of one `permessage-deflate` offer or response.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct DeflateParams {
+  server_no_context_takeover: bool,
+  client_no_context_takeover: bool,
+  server_max_window_bits: Option<u8>,
+  /// `Some(None)` when offered without a value.
+  client_max_window_bits: Option<Option<u8>>,
+}
+
+impl DeflateParams {
+  /// Parses the `permessage-deflate` entries of a `Sec-WebSocket-Extensions`
+  /// header, ignoring other extensions.
+  fn parse_all(header: &str) -> Vec<Result<Self, String>> {
+    header
+      .split(',')
+      .filter_map(|extension| {
+        let mut parts = extension.split(';').map(str::trim);
+        let name = parts.next()?;
+        name
+          .eq_ignore_ascii_case(PERMESSAGE_DEFLATE)
+          .then(|| Self::parse(parts))
+      })
+      .collect()
+  }
+
+  fn parse<'a>(params: impl Iterator<Item = &'a str>) -> Result<Self, String> {
+    let mut parsed = Self::default();
+    for param in params {
+      let (name, value) = match param.split_once('=') {
+        Some((name, value)) => {
+          (name.trim(), Some(value.trim().trim_matches('"')))
+        }
+        None => (param, None),
+      };
+      let window_bits = || {
+        value
+          .and_then(|value| value.parse::<u8>().ok())
+          .filter(|bits| (MIN_WINDOW_BITS..=MAX_WINDOW_BITS).contains(bits))
+          .ok_or_else(|| format!("invalid value for {name}"))
+      };
+      let duplicate = match name.to_ascii_lowercase().as_str() {
+        "server_no_context_takeover" if value.is_none() => {
+          std::mem::replace(&mut parsed.server_no_context_takeover, true)
+        }
+        "client_no_context_takeover" if value.is_none() => {
+          std::mem::replace(&mut parsed.client_no_context_takeover, true)
+        }
+        "server_max_window_bits" => parsed
+          .server_max_window_bits
+          .replace(window_bits()?)
+          .is_some(),
+        "client_max_window_bits" => {
+          let bits = value.map(|_| window_bits()).transpose()?;
+          parsed.client_max_window_bits.replace(bits).is_some()
+        }
+        _ => return Err(format!("unexpected parameter {param}")),
+      };
+      if duplicate {
+        return Err(format!("duplicate parameter {name}"));
+      }
+    }
+    Ok(parsed)
+  }
+
+  fn to_header(self) -> String {
+    let mut header = PERMESSAGE_DEFLATE.to_string();
+    if self.server_no_context_takeover {
+      header.push_str("; server_no_context_takeover");
+    }
+    if self.client_no_context_takeover {
+      header.push_str("; client_no_context_takeover");
+    }
+    if let Some(bits) = self.server_max_window_bits {
+      header.push_str(&format!("; server_max_window_bits={bits}"));
+    }
+    match self.client_max_window_bits {
+      Some(Some(bits)) => {
+        header.push_str(&format!("; client_max_window_bits={bits}"))
+      }
+      Some(None) => header.push_str("; client_max_window_bits"),
+      None => {}
+    }
+    header
+  }
+}
+
+impl PerMessageDeflateOptions {
+  pub fn validate(&self) -> Result<(), DeflateError> {
+    for bits in [self.server_max_window_bits, self.client_max_window_bits]
+      .into_iter()
+      .flatten()
+    {
+      if !(MIN_WINDOW_BITS..=MAX_WINDOW_BITS).contains(&bits) {
+        return Err(DeflateError::InvalidWindowBits(bits));
+      }
+    }
+    Ok(())
+  }
+
+  /// The `Sec-WebSocket-Extensions` header a client offers.
+  pub fn offer(&self) -> String {
+    DeflateParams {
+      server_no_context_takeover: self.server_no_context_takeover,
+      client_no_context_takeover: self.client_no_context_takeover,
+      server_max_window_bits: self.server_max_window_bits,
+      client_max_window_bits: None,
+    }
+    .to_header()
+  }
+
+  /// The `Sec-WebSocket-Extensions` header a server responds with to the
+  /// `offers` of a client, or `None` if no offer is acceptable. Errors if the
+  /// options are not valid for a server, even without offers.
+  pub fn negotiate(
+    &self,
+    offers: Option<&str>,
+  ) -> Result<Option<String>, DeflateError> {
+    self.validate()?;
+    if let Some(bits) = self.server_max_window_bits
+      && bits != MAX_WINDOW_BITS
+    {
+      return Err(DeflateError::UnsupportedServerWindowBits(bits));
+    }
+    let Some(offers) = offers else {
+      return Ok(None);
+    };
+    let response = DeflateParams::parse_all(offers)
+      .into_iter()
+      .filter_map(Result::ok)
+      .find(|offer| {
+        offer
+          .server_max_window_bits
+          .is_none_or(|bits| bits == MAX_WINDOW_BITS)
+      })
+      .map(|offer| {
+        // The client may only be told to use a smaller window if it offered
+        // to.
+        let client_max_window_bits =
+          match (offer.client_max_window_bits, self.client_max_window_bits) {
+            (Some(offered), Some(bits)) => {
+              Some(Some(bits.min(offered.unwrap_or(MAX_WINDOW_BITS))))
+            }
+            _ => None,
+          };
+        DeflateParams {
+          server_no_context_takeover: offer.server_no_context_takeover
+            || self.server_no_context_takeover,
+          client_no_context_takeover: offer.client_no_context_takeover
+            || self.client_no_context_takeover,
+          server_max_window_bits: offer.server_max_window_bits,
+          client_max_window_bits,
+        }
+        .to_header()
+      });
+    Ok(response)
+  }
+}
+
+/// Whether a received data message is compressed. Compressed text messages
+/// are handed to fastwebsockets as binary ones, so that it does not validate
+/// their compressed payload as UTF-8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ReceivedMessage {
+  Uncompressed,
+  Compressed { text: bool },
+}
+
+type ReceivedMessages = Arc<Mutex<VecDeque<ReceivedMessage>>>;
+
+/// Compresses sent messages and decompresses received ones, once the
+/// extension is negotiated.
+pub struct PerMessageDeflate {
+  compress: Compress,
+  decompress: Decompress,
+  /// Whether the compression context is reset after each sent message.
+  reset_compress: bool,
+  /// Whether the peer resets its compression context after each message.
+  reset_decompress: bool,
+  max_decompressed_size: usize,
+  received: ReceivedMessages,
+}
+
+impl PerMessageDeflate {
+  /// Accepts the `response` of a server to the offer of `options`, or
+  /// returns `None` if the server did not accept it.
+  pub fn client(
+    options: &PerMessageDeflateOptions,
+    response: &str,
+  ) -> Result<Option<Self>, DeflateError> {
+    let mut responses = DeflateParams::parse_all(response).into_iter();
+    let Some(params) = responses.next() else {
+      return Ok(None);
+    };
+    if responses.next().is_some() {
+      return Err(DeflateError::InvalidNegotiation(
+        "more than one permessage-deflate response".into(),
+      ));
+    }
+    let params = params.map_err(DeflateError::InvalidNegotiation)?;
+    if params.client_max_window_bits.is_some() {
+      return Err(DeflateError::InvalidNegotiation(
+        "client_max_window_bits was not offered".into(),
+      ));
+    }
+    if let Some(offered) = options.server_max_window_bits
+      && params
+        .server_max_window_bits
+        .is_none_or(|bits| bits > offered)
+    {
+      return Err(DeflateError::InvalidNegotiation(format!(
+        "server_max_window_bits must be at most {offered}"
+      )));
+    }
+    Ok(Some(Self::new(
+      Role::Client,
+      params,
+      options.max_decompressed_size,
+    )))
+  }
+
+  /// Uses the `response` a server negotiated with
+  /// [`PerMessageDeflateOptions::negotiate`].
+  pub fn server(
+    options: &PerMessageDeflateOptions,
+    response: &str,
+  ) -> Result<Self, DeflateError> {
+    let params = DeflateParams::parse_all(response)
+      .into_iter()
+      .next()
+      .unwrap_or_else(|| Err("no permessage-deflate response".into()))
+      .map_err(DeflateError::InvalidNegotiation)?;
+    Ok(Self::new(
+      Role::Server,
+      params,
+      options.max_decompressed_size,
+    ))
+  }
+
+  fn new(
+    role: Role,
+    params: DeflateParams,
+    max_decompressed_size: Option<usize>,
+  ) -> Self {
+    let (reset_compress, reset_decompress) = match role {
+      Role::Server => (
+        params.server_no_context_takeover,
+        params.client_no_context_takeover,
+      ),
+      Role::Client => (
+        params.client_no_context_takeover,
+        params.server_no_context_takeover,
+      ),
+    };
+    Self {
+      compress: Compress::new(Compression::default(), false),
+      decompress: Decompress::new(false),
+      reset_compress,
+      reset_decompress,
+      max_decompressed_size: max_decompressed_size
+        .unwrap_or(DEFAULT_MAX_DECOMPRESSED_SIZE),
+      received: Default::default(),
+    }
+  }
+
+  /// The frame header rewriting for the stream of this websocket.
+  pub(crate) fn frames(&self) -> DeflateFrames {
+    DeflateFrames {
+      read: FrameHeaders::default(),
+      write: FrameHeaders::default(),
+      received: self.received.clone(),
+    }
+  }
+
+  /// Compresses the payload of a sent message.
+  pub(crate) fn compress(
+    &mut self,
+    payload: &[u8],
+  ) -> Result<Vec<u8>, DeflateError> {
+    let mut out = Vec::with_capacity(payload.len() / 2 + 64);
+    let start = self.compress.total_in();
+    loop {
+      let consumed = (self.compress.total_in() - start) as usize;
+      if out.len() == out.capacity() {
+        out.reserve(out.capacity().max(64));
+      }
+      self.compress.compress_vec(
+        &payload[consumed..],
+        &mut out,
+        FlushCompress::Sync,
+      )?;
+      let consumed = (self.compress.total_in() - start) as usize;
+      if consumed == payload.len() && out.len() < out.capacity() {
+        break;
+      }
+    }
+    if out.ends_with(&DEFLATE_TRAILER) {
+      out.truncate(out.len() - DEFLATE_TRAILER.len());
+    }
+    if out.is_empty() {
+      // An empty stored block (RFC 7692, section 7.2.3.6).
+      out.push(0x00);
+    }
+    if self.reset_compress {
+      self.compress.reset();
+    }
+    Ok(out)
+  }
+
+  /// Decompresses the payload of the next received data message if it is
+  /// compressed, and returns whether it is a text message.
+  pub(crate) fn decompress(
+    &mut self,
+    text: bool,
+    payload: &[u8],
+  ) -> Result<(bool, Vec<u8>), DeflateError> {
+    let received = self.received.lock().unwrap().pop_front();
+    let Some(ReceivedMessage::Compressed { text }) = received else {
+      return Ok((text, payload.to_vec()));
+    };
+    let max = self.max_decompressed_size;
+    let mut out = Vec::with_capacity(payload.len().saturating_mul(2).min(max));
+    for input in [payload, &DEFLATE_TRAILER[..]] {
+      let start = self.decompress.total_in();
+      loop {
+        let consumed = (self.decompress.total_in() - start) as usize;
+        if out.len() == out.capacity() {
+          // Leave room for one more byte, to detect oversized messages.
+          let additional = out.capacity().max(64).min(max + 1 - out.len());
+          out.reserve_exact(additional);
+        }
+        self.decompress.decompress_vec(
+          &input[consumed..],
+          &mut out,
+          FlushDecompress::Sync,
+        )?;
+        if out.len() > max {
+          return Err(DeflateError::MessageTooLarge(max));
+        }
+        let consumed = (self.decompress.total_in() - start) as usize;
+        if consumed == input.len() && out.len() < out.capacity() {
+          break;
+        }
+      }
+    }
+    if self.reset_decompress {
+      self.decompress.reset(false);
+    }
+    Ok((text, out))
+  }
+}
+
+/// Sets the RSV1 bit of the data frames written to a stream, and clears it
+/// from the data frames read from it.
+pub(crate) struct DeflateFrames {
+  read: FrameHeaders,
+  write: FrameHeaders,
+  received: ReceivedMessages,
+}
+
+impl DeflateFrames {
+  /// Rewrites the frame headers in `data`, read from the stream.
+  pub(crate) fn read(&mut self, data: &mut [u8]) {
+    let received = &self.received;
+    self.read.rewrite(data, |byte| {
+      let opcode = *byte & OPCODE_MASK;
+      if opcode != OPCODE_TEXT && opcode != OPCODE_BINARY {
+        return;
+      }
+      let message = if *byte & RSV1 == 0 {
+        ReceivedMessage::Uncompressed
+      } else {
+        *byte = (*byte & !(RSV1 | OPCODE_MASK)) | OPCODE_BINARY;
+        ReceivedMessage::Compressed {
+          text: opcode == OPCODE_TEXT,
+        }
+      };
+      received.lock().unwrap().push_back(message);
+    });
+  }
+
+  /// Rewrites the frame headers in `data`, about to be written to the
+  /// stream. Rewriting is idempotent, so `data` can be rewritten ahead of
+  /// writing it with [`Self::peek_write`], and again once written.
+  pub(crate) fn write(&mut self, data: &mut [u8]) {
+    self.write.rewrite(data, set_rsv1);
+  }
+
+  pub(crate) fn peek_write(&self, data: &mut [u8]) {
+    self.write.clone().rewrite(data, set_rsv1);
+  }
+}
+
+fn set_rsv1(byte: &mut u8) {
+  let opcode = *byte & OPCODE_MASK;
+  if opcode == OPCODE_TEXT || opcode == OPCODE_BINARY {
+    *byte |= RSV1;
+  }
+}
+
+/// Tracks the frame boundaries in a stream of frames.
+#[derive(Clone, Default)]
+struct FrameHeaders {
+  /// The header read so far of the current frame.
+  header: [u8; 14],
+  header_len: usize,
+  /// The payload bytes left in the current frame.
+  remaining: u64,
+}
+
+impl FrameHeaders {
+  /// Calls `first_byte` with the first byte of each frame header in `data`.
+  fn rewrite(&mut self, data: &mut [u8], mut first_byte: impl FnMut(&mut u8)) {
+    let mut i = 0;
+    while i < data.len() {
+      if self.remaining > 0 {
+        let skip = self.remaining.min((data.len() - i) as u64);
+        self.remaining -= skip;
+        i += skip as usize;
+        continue;
+      }
+      if self.header_len == 0 {
+        first_byte(&mut data[i]);
+      }
+      self.header[self.header_len] = data[i];
+      self.header_len += 1;
+      i += 1;
+      if let Some(payload_len) = self.payload_len() {
+        self.remaining = payload_len;
+        self.header_len = 0;
+      }
+    }
+  }
+
+  /// The payload length of the current frame, once its header is complete.
+  fn payload_len(&self) -> Option<u64> {
+    if self.header_len < 2 {
+      return None;
+    }
+    let extended_len = match self.header[1] & 0x7f {
+      126 => 2,
+      127 => 8,
+      _ => 0,
+    };
+    let mask_len = if self.header[1] & 0x80 != 0 { 4 } else { 0 };
+    if self.header_len < 2 + extended_len + mask_len {
+      return None;
+    }
+    Some(match extended_len {
+      0 => u64::from(self.header[1] & 0x7f),
+      _ => self.header[2..2 + extended_len]
+        .iter()
+        .fold(0, |len, byte| (len << 8) | u64::from(*byte)),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn negotiate() {
+    let options = PerMessageDeflateOptions {
+      client_max_window_bits: Some(10),
+      ..Default::default()
+    };
+    assert_eq!(
+      options
+        .negotiate(Some(
+          "permessage-deflate; server_max_window_bits=10, \
+           permessage-deflate; client_max_window_bits; \
+           server_no_context_takeover"
+        ))
+        .unwrap(),
+      Some(
+        "permessage-deflate; server_no_context_takeover; \
+         client_max_window_bits=10"
+          .to_string()
+      )
+    );
+    assert_eq!(
+      options.negotiate(Some("x-webkit-deflate-frame")).unwrap(),
+      None
+    );
+    assert_eq!(
+      options
+        .negotiate(Some("permessage-deflate; server_max_window_bits=20"))
+        .unwrap(),
+      None
+    );
+    assert_eq!(options.negotiate(None).unwrap(), None);
+
+    let options = PerMessageDeflateOptions {
+      server_max_window_bits: Some(12),
+      ..Default::default()
+    };
+    assert_eq!(
+      options.offer(),
+      "permessage-deflate; server_max_window_bits=12"
+    );
+    assert!(
+      PerMessageDeflate::client(
+        &options,
+        "permessage-deflate; server_max_window_bits=12"
+      )
+      .unwrap()
+      .is_some()
+    );
+    assert!(PerMessageDeflate::client(&options, "").unwrap().is_none());
+    assert!(PerMessageDeflate::client(&options, "permessage-deflate").is_err());
+    // Servers always compress with a 15 bits window.
+    assert!(matches!(
+      options.negotiate(None),
+      Err(DeflateError::UnsupportedServerWindowBits(12))
+    ));
+    let options = PerMessageDeflateOptions {
+      server_max_window_bits: Some(15),
+      ..Default::default()
+    };
+    assert_eq!(
+      options
+        .negotiate(Some("permessage-deflate"))
+        .unwrap()
+        .as_deref(),
+      Some("permessage-deflate")
+    );
+  }
+
+  #[test]
+  fn roundtrip() {
+    let options = PerMessageDeflateOptions {
+      max_decompressed_size: Some(1024),
+      ..Default::default()
+    };
+    let mut server =
+      PerMessageDeflate::server(&options, "permessage-deflate").unwrap();
+    let mut client = PerMessageDeflate::client(&options, "permessage-deflate")
+      .unwrap()
+      .unwrap();
+
+    // A masked text frame, split across reads.
+    let message = "hello ".repeat(100);
+    let payload = client.compress(message.as_bytes()).unwrap();
+    assert!(payload.len() < message.len());
+    let mut frame = vec![0x81, 0x80 | 126];
+    frame.extend((payload.len() as u16).to_be_bytes());
+    frame.extend([0; 4]);
+    frame.extend(&payload);
+    let mut written = frame.clone();
+    let mut frames = client.frames();
+    frames.peek_write(&mut written);
+    frames.write(&mut written[..3]);
+    frames.write(&mut written[3..]);
+    assert_eq!(written[0], 0xc1);
+    let mut frames = server.frames();
+    frames.read(&mut written[..1]);
+    frames.read(&mut written[1..]);
+    assert_eq!(written[0], 0x82);
+    let (text, decompressed) = server.decompress(false, &payload).unwrap();
+    assert!(text);
+    assert_eq!(decompressed, message.as_bytes());
+
+    // Context takeover: the same message compresses better the second time.
+    let again = client.compress(message.as_bytes()).unwrap();
+    assert!(again.len() < payload.len());
+    server
+      .received
+      .lock()
+      .unwrap()
+      .push_back(ReceivedMessage::Compressed { text: false });
+    let (text, decompressed) = server.decompress(false, &again).unwrap();
+    assert!(!text);
+    assert_eq!(decompressed, message.as_bytes());
+
+    let empty = client.compress(b"").unwrap();
+    server
+      .received
+      .lock()
+      .unwrap()
+      .push_back(ReceivedMessage::Compressed { text: false });
+    assert_eq!(server.decompress(false, &empty).unwrap().1, b"");
+
+    // A decompression bomb.
+    let bomb = client.compress(&[0; 4096]).unwrap();
+    server
+      .received
+      .lock()
+      .unwrap()
+      .push_back(ReceivedMessage::Compressed { text: false });
+    assert!(matches!(
+      server.decompress(false, &bomb),
+      Err(DeflateError::MessageTooLarge(1024))
+    ));
+  }
+}
@@ -50,6 +50,7 @@ use http::StatusCode;
 use http::Uri;
 use http::header::CONNECTION;
 use http::header::HOST;
+use http::header::SEC_WEBSOCKET_EXTENSIONS;
 use http::header::SEC_WEBSOCKET_KEY;
 use http::header::SEC_WEBSOCKET_PROTOCOL;
 use http::header::SEC_WEBSOCKET_VERSION;
@@ -61,8 +62,12 @@ use tokio::io::AsyncWrite;
 use tokio::io::ReadHalf;
 use tokio::io::WriteHalf;
 
+pub use crate::deflate::DeflateError;
+pub use crate::deflate::PerMessageDeflate;
+pub use crate::deflate::PerMessageDeflateOptions;
 use crate::stream::WebSocketStream;
 
+mod deflate;
 mod stream;
 
 static USE_WRITEV: Lazy<bool> = Lazy::new(|| {
@@ -104,6 +109,9 @@ pub enum WebsocketError {
   #[class(inherit)]
   #[error(transparent)]
   Canceled(#[from] deno_core::Canceled),
+  #[class(inherit)]
+  #[error(transparent)]
+  Deflate(#[from] DeflateError),
 }
 
 pub struct WsCancelResource(Rc<CancelHandle>);
@@ -208,15 +216,28 @@ pub enum HandshakeError {
   #[class(type)]
   #[error(transparent)]
   HeaderValue(#[from] http::header::InvalidHeaderValue),
+  #[class(inherit)]
+  #[error(transparent)]
+  Deflate(#[from] DeflateError),
 }
 
+type HandshakeResult = Result<
+  (
+    WebSocket<WebSocketStream>,
+    http::HeaderMap,
+    Option<PerMessageDeflate>,
+  ),
+  HandshakeError,
+>;
+
 async fn handshake_websocket(
   client: deno_fetch::Client,
   allow_host: bool,
   uri: Uri,
   protocols: &str,
   headers: Option<Vec<(ByteString, ByteString)>>,
-) -> Result<(WebSocket<WebSocketStream>, http::HeaderMap), HandshakeError> {
+  deflate: Option<&PerMessageDeflateOptions>,
+) -> HandshakeResult {
   let parts = uri.into_parts();
   let Some(authority) = parts.authority else {
     return Err(HandshakeError::MissingHost);
@@ -238,6 +259,7 @@ async fn handshake_websocket(
     &path_and_query,
     protocols,
     &headers,
+    deflate,
   )
   .await;
 
@@ -249,12 +271,14 @@ async fn handshake_websocket(
         .authority(authority)
         .path_and_query(path_and_query)
         .build()?;
-      handshake_http2(client, allow_host, uri, protocols, &headers).await
+      handshake_http2(client, allow_host, uri, protocols, &headers, deflate)
+        .await
     }
     Err(e) => Err(e),
   }
 }
 
+#[allow(clippy::too_many_arguments, reason = "TODO: improve")]
 async fn handshake_http1(
   client: deno_fetch::Client,
   allow_host: bool,
@@ -263,7 +287,8 @@ async fn handshake_http1(
   path_and_query: &http::uri::PathAndQuery,
   protocols: &str,
   headers: &Option<Vec<(ByteString, ByteString)>>,
-) -> Result<(WebSocket<WebSocketStream>, http::HeaderMap), HandshakeError> {
+  deflate: Option<&PerMessageDeflateOptions>,
+) -> HandshakeResult {
   let connection_uri = Uri::builder()
     .scheme(scheme)
     .authority(authority.clone())
@@ -292,8 +317,9 @@ async fn handshake_http1(
   let mut request = Request::builder().method(Method::GET).uri(req_uri);
 
   client.inject_common_headers(&mut request);
-  request =
-    populate_common_request_headers(request, protocols, headers, allow_host)?;
+  request = populate_common_request_headers(
+    request, protocols, headers, allow_host, deflate,
+  )?;
 
   if let Some(headers) = request.headers_ref()
     && !headers.contains_key(HOST)
@@ -310,7 +336,7 @@ async fn handshake_http1(
     .body(http_body_util::Empty::new())
     .map_err(HandshakeError::Http)?;
 
-  handshake_connection(request, connection).await
+  handshake_connection(request, connection, deflate).await
 }
 
 #[allow(clippy::too_many_arguments, reason = "TODO: improve")]
@@ -320,7 +346,8 @@ async fn handshake_http2(
   uri: Uri,
   protocols: &str,
   headers: &Option<Vec<(ByteString, ByteString)>>,
-) -> Result<(WebSocket<WebSocketStream>, http::HeaderMap), HandshakeError> {
+  deflate: Option<&PerMessageDeflateOptions>,
+) -> HandshakeResult {
   let connection = client.connect(uri.clone(), SocketUse::Http2Only).await?;
   if !connection.connected().is_negotiated_h2() {
     return Err(HandshakeError::NoH2Alpn);
@@ -337,8 +364,9 @@ async fn handshake_http2(
   request = request.method(Method::CONNECT);
   request = request.uri(uri);
   client.inject_common_headers(&mut request);
-  request =
-    populate_common_request_headers(request, protocols, headers, allow_host)?;
+  request = populate_common_request_headers(
+    request, protocols, headers, allow_host, deflate,
+  )?;
   request = request.extension(h2::ext::Protocol::from("websocket"));
   let (resp, send) = send.send_request(request.body(())?, false)?;
   let resp = resp.await?;
@@ -346,15 +374,15 @@ async fn handshake_http2(
     return Err(HandshakeError::InvalidStatusCode(resp.status()));
   }
   let (http::response::Parts { headers, .. }, recv) = resp.into_parts();
-  let mut stream = WebSocket::after_handshake(
-    WebSocketStream::new(stream::WsStreamKind::H2(send, recv), None),
-    Role::Client,
-  );
+  let mut stream =
+    WebSocketStream::new(stream::WsStreamKind::H2(send, recv), None);
+  let deflate = accept_deflate(deflate, &headers, &mut stream)?;
+  let mut stream = WebSocket::after_handshake(stream, Role::Client);
   // We currently don't support vectored writes in the H2 streams
   stream.set_writev(false);
   // TODO(mmastrac): we should be able to use a zero masking key over HTTPS
   // stream.set_auto_apply_mask(false);
-  Ok((stream, headers))
+  Ok((stream, headers, deflate))
 }
 
 async fn handshake_connection<
@@ -362,16 +390,42 @@ async fn handshake_connection<
 >(
   request: Request<http_body_util::Empty<Bytes>>,
   socket: S,
-) -> Result<(WebSocket<WebSocketStream>, http::HeaderMap), HandshakeError> {
+  deflate: Option<&PerMessageDeflateOptions>,
+) -> HandshakeResult {
   let (upgraded, response) =
     fastwebsockets::handshake::client(&LocalExecutor, request, socket).await?;
 
   let upgraded = upgraded.into_inner();
-  let stream =
+  let headers = response.into_parts().0.headers;
+  let mut stream =
     WebSocketStream::new(stream::WsStreamKind::Upgraded(upgraded), None);
+  let deflate = accept_deflate(deflate, &headers, &mut stream)?;
   let stream = WebSocket::after_handshake(stream, Role::Client);
 
-  Ok((stream, response.into_parts().0.headers))
+  Ok((stream, headers, deflate))
+}
+
+/// Checks the permessage-deflate response of the server against our offer,
+/// and enables it on `stream` if the server accepted it.
+fn accept_deflate(
+  options: Option<&PerMessageDeflateOptions>,
+  headers: &http::HeaderMap,
+  stream: &mut WebSocketStream,
+) -> Result<Option<PerMessageDeflate>, HandshakeError> {
+  let Some(options) = options else {
+    return Ok(None);
+  };
+  let response = headers
+    .get_all(SEC_WEBSOCKET_EXTENSIONS)
+    .iter()
+    .filter_map(|header| header.to_str().ok())
+    .collect::<Vec<_>>()
+    .join(",");
+  let deflate = PerMessageDeflate::client(options, &response)?;
+  if let Some(deflate) = &deflate {
+    stream.set_deflate(deflate.frames());
+  }
+  Ok(deflate)
 }
 
 /// Headers common to both http/1.1 and h2 requests.
@@ -380,6 +434,7 @@ fn populate_common_request_headers(
   protocols: &str,
   headers: &Option<Vec<(ByteString, ByteString)>>,
   allow_host: bool,
+  deflate: Option<&PerMessageDeflateOptions>,
 ) -> Result<http::request::Builder, HandshakeError> {
   request = request.header(SEC_WEBSOCKET_VERSION, "13");
 
@@ -387,6 +442,10 @@ fn populate_common_request_headers(
     request = request.header(SEC_WEBSOCKET_PROTOCOL, protocols);
   }
 
+  if let Some(deflate) = deflate {
+    request = request.header(SEC_WEBSOCKET_EXTENSIONS, deflate.offer());
+  }
+
   if let Some(headers) = headers {
     for (key, value) in headers {
       let name = HeaderName::from_bytes(key)?;
@@ -442,6 +501,9 @@ fn create_client_from_websocket_options(
       local_address: None,
       client_builder_hook: options.client_builder_hook,
       http2_max_header_list_size: None,
+      recorder: None,
+      cache: None,
+      timeouts: Default::default(),
     },
   )
 }
@@ -457,17 +519,19 @@ pub async fn op_ws_create(
   #[scoped] ca_certs: Option<Vec<String>>,
   unsafely_ignore_certificate_errors: bool,
   #[smi] client_rid: Option<u32>,
+  #[serde] per_message_deflate: Option<PerMessageDeflateOptions>,
 ) -> Result<CreateResponse, WebsocketError> {
+  if let Some(options) = &per_message_deflate {
+    options.validate()?;
+  }
   let (client, allow_host) = {
     let mut s = state.borrow_mut();
-    s.borrow_mut::<PermissionsContainer>()
-      .check_net_url(
-        &url::Url::parse(&url).map_err(WebsocketError::Url)?,
-        &api_name,
-      )
-      .expect(
-        "Permission check should have been done in op_ws_check_permission",
-      );
+    let parsed_url = url::Url::parse(&url).map_err(WebsocketError::Url)?;
+    let permissions = s.borrow_mut::<PermissionsContainer>();
+    permissions.check_net_url(&parsed_url, &api_name).expect(
+      "Permission check should have been done in op_ws_check_permission",
+    );
+    permissions.consume_net_quota_url(&parsed_url)?;
     if let Some(rid) = client_rid {
       let r = s.resource_table.get::<HttpClientResource>(rid)?;
       (r.client.clone(), r.allow_host)
@@ -500,10 +564,16 @@ pub async fn op_ws_create(
 
   let uri: Uri = url.parse()?;
 
-  let handshake =
-    handshake_websocket(client, allow_host, uri, &protocols, headers)
-      .map_err(WebsocketError::ConnectionFailed);
-  let (stream, response) = match cancel_resource {
+  let handshake = handshake_websocket(
+    client,
+    allow_host,
+    uri,
+    &protocols,
+    headers,
+    per_message_deflate.as_ref(),
+  )
+  .map_err(WebsocketError::ConnectionFailed);
+  let (stream, response, deflate) = match cancel_resource {
     Some(rc) => handshake.try_or_cancel(rc).await?,
     None => handshake.await?,
   };
@@ -534,7 +604,9 @@ pub async fn op_ws_create(
     .collect();
 
   let mut state = state.borrow_mut();
-  let rid = state.resource_table.add(ServerWebSocket::new(stream));
+  let rid = state
+    .resource_table
+    .add(ServerWebSocket::new(stream, deflate));
 
   let protocol = response
     .get("Sec-WebSocket-Protocol")
@@ -574,6 +646,10 @@ pub struct ServerWebSocket {
   string: Cell<Option<String>>,
   ws_read: AsyncRefCell<FragmentCollectorRead<ReadHalf<WebSocketStream>>>,
   ws_write: AsyncRefCell<WebSocketWrite<WriteHalf<WebSocketStream>>>,
+  /// Set when permessage-deflate was negotiated. Only used while holding the
+  /// `ws_read` or `ws_write` lock, for decompressing or compressing
+  /// respectively.
+  deflate: Option<RefCell<PerMessageDeflate>>,
   /// Cancel handle for the pending [`op_ws_next_event`] read. Triggered when
   /// the embedder (e.g. the HTTP server) needs to interrupt the read half
   /// during a server-initiated shutdown after a close frame has been sent.
@@ -587,12 +663,16 @@ pub struct ServerWebSocket {
 }
 
 impl ServerWebSocket {
-  fn new(ws: WebSocket<WebSocketStream>) -> Self {
-    Self::new_with_guard(ws, None)
+  fn new(
+    ws: WebSocket<WebSocketStream>,
+    deflate: Option<PerMessageDeflate>,
+  ) -> Self {
+    Self::new_with_guard(ws, deflate, None)
   }
 
   fn new_with_guard(
     ws: WebSocket<WebSocketStream>,
+    deflate: Option<PerMessageDeflate>,
     lifetime_guard: Option<Box<dyn Any>>,
   ) -> Self {
     let (ws_read, ws_write) = ws.split(tokio::io::split);
@@ -605,6 +685,7 @@ impl ServerWebSocket {
       string: Cell::new(None),
       ws_read: AsyncRefCell::new(FragmentCollectorRead::new(ws_read)),
       ws_write: AsyncRefCell::new(ws_write),
+      deflate: deflate.map(RefCell::new),
       read_cancel: CancelHandle::new_rc(),
       lifetime_guard: Cell::new(lifetime_guard),
     }
@@ -683,12 +764,20 @@ impl ServerWebSocket {
   async fn write_frame(
     self: &Rc<Self>,
     lock: AsyncMutFuture<WebSocketWrite<WriteHalf<WebSocketStream>>>,
-    frame: Frame<'_>,
+    mut frame: Frame<'_>,
   ) -> Result<(), WebsocketError> {
     let mut ws = lock.await;
     if ws.is_closed() {
       return Ok(());
     }
+    // Compress under the write lock, so that messages are compressed in the
+    // order they are sent.
+    if let Some(deflate) = &self.deflate
+      && matches!(frame.opcode, OpCode::Text | OpCode::Binary)
+    {
+      let payload = deflate.borrow_mut().compress(&frame.payload)?;
+      frame.payload = payload.into();
+    }
     ws.write_frame(frame).await?;
     Ok(())
   }
@@ -714,7 +803,7 @@ pub fn ws_create_server_stream(
   transport: NetworkStream,
   read_buf: Bytes,
 ) -> ResourceId {
-  ws_create_server_stream_with_guard(state, transport, read_buf, None)
+  ws_create_server_stream_with_guard(state, transport, read_buf, None, None)
 }
 
 /// Like [`ws_create_server_stream`] but lets the embedder attach an opaque
@@ -723,26 +812,34 @@ pub fn ws_create_server_stream(
 /// the new resource through the returned [`ResourceId`] in order to drive
 /// a server-initiated shutdown later via
 /// [`ServerWebSocket::server_shutdown`].
+///
+/// `deflate` is set when the embedder negotiated permessage-deflate in the
+/// upgrade response.
 pub fn ws_create_server_stream_with_guard(
   state: &mut OpState,
   transport: NetworkStream,
   read_buf: Bytes,
+  deflate: Option<PerMessageDeflate>,
   lifetime_guard: Option<Box<dyn Any>>,
 ) -> ResourceId {
-  let mut ws = WebSocket::after_handshake(
-    WebSocketStream::new(
-      stream::WsStreamKind::Network(transport),
-      Some(read_buf),
-    ),
-    Role::Server,
+  let mut stream = WebSocketStream::new(
+    stream::WsStreamKind::Network(transport),
+    Some(read_buf),
   );
-  ws.set_writev(*USE_WRITEV);
+  if let Some(deflate) = &deflate {
+    stream.set_deflate(deflate.frames());
+  }
+  let mut ws = WebSocket::after_handshake(stream, Role::Server);
+  // Compressed frames are rewritten in a single buffer.
+  ws.set_writev(*USE_WRITEV && deflate.is_none());
   ws.set_auto_close(true);
   ws.set_auto_pong(true);
 
-  state
-    .resource_table
-    .add(ServerWebSocket::new_with_guard(ws, lifetime_guard))
+  state.resource_table.add(ServerWebSocket::new_with_guard(
+    ws,
+    deflate,
+    lifetime_guard,
+  ))
 }
 
 fn send_binary(state: &mut OpState, rid: ResourceId, data: &[u8]) {
@@ -1001,19 +1098,39 @@ pub async fn op_ws_next_event(
     };
 
     break match val.opcode {
-      OpCode::Text => match String::from_utf8(val.payload.to_vec()) {
-        Ok(s) => {
+      OpCode::Text | OpCode::Binary => {
+        let text = matches!(val.opcode, OpCode::Text);
+        let (text, payload) = match &resource.deflate {
+          Some(deflate) => {
+            let message = deflate.borrow_mut().decompress(text, &val.payload);
+            match message {
+              Ok(message) => message,
+              Err(err) => {
+                if matches!(err, DeflateError::MessageTooLarge(_)) {
+                  // Tell the peer why the connection is closed (RFC 6455,
+                  // section 7.4.1).
+                  resource.closed.set(true);
+                  let lock = resource.reserve_lock();
+                  let frame = Frame::close(1009, err.to_string().as_bytes());
+                  let _ = resource.write_frame(lock, frame).await;
+                }
+                resource.set_error(Some(err.to_string()));
+                return MessageKind::Error as u16;
+              }
+            }
+          }
+          None => (text, val.payload.to_vec()),
+        };
+        if !text {
+          resource.buffer.set(Some(payload));
+          MessageKind::Binary as u16
+        } else if let Ok(s) = String::from_utf8(payload) {
           resource.string.set(Some(s));
           MessageKind::Text as u16
-        }
-        Err(_) => {
+        } else {
           resource.set_error(Some("Invalid string data".into()));
           MessageKind::Error as u16
         }
-      },
-      OpCode::Binary => {
-        resource.buffer.set(Some(val.payload.to_vec()));
-        MessageKind::Binary as u16
       }
       OpCode::Close => {
         // Close reason is returned through error
@@ -15,6 +15,8 @@ use tokio::io::AsyncRead;
 use tokio::io::AsyncWrite;
 use tokio::io::ReadBuf;
 
+use crate::deflate::DeflateFrames;
+
 // TODO(bartlomieju): remove this
 #[allow(clippy::large_enum_variant, reason = "TODO: investigate")]
 pub(crate) enum WsStreamKind {
@@ -26,6 +28,7 @@ pub(crate) enum WsStreamKind {
 pub(crate) struct WebSocketStream {
   stream: WsStreamKind,
   pre: Option<Bytes>,
+  deflate: Option<DeflateFrames>,
 }
 
 impl WebSocketStream {
@@ -33,16 +36,55 @@ impl WebSocketStream {
     Self {
       stream,
       pre: buffer,
+      deflate: None,
+    }
+  }
+
+  /// Rewrites the RSV1 bit of data frames once permessage-deflate is
+  /// negotiated.
+  pub fn set_deflate(&mut self, deflate: DeflateFrames) {
+    self.deflate = Some(deflate);
+  }
+
+  fn poll_write_inner(
+    &mut self,
+    cx: &mut std::task::Context<'_>,
+    buf: &[u8],
+  ) -> std::task::Poll<Result<usize, std::io::Error>> {
+    let Some(deflate) = &self.deflate else {
+      return self.poll_write_stream(cx, buf);
+    };
+    let mut data = buf.to_vec();
+    deflate.peek_write(&mut data);
+    let n = ready!(self.poll_write_stream(cx, &data))?;
+    if let Some(deflate) = &mut self.deflate {
+      deflate.write(&mut data[..n]);
     }
+    Poll::Ready(Ok(n))
   }
 }
 
 impl AsyncRead for WebSocketStream {
-  // From hyper's Rewind (https://github.com/hyperium/hyper), MIT License, Copyright (c) Sean McArthur
   fn poll_read(
     mut self: Pin<&mut Self>,
     cx: &mut std::task::Context<'_>,
     buf: &mut ReadBuf<'_>,
+  ) -> Poll<std::io::Result<()>> {
+    let filled = buf.filled().len();
+    ready!(self.as_mut().poll_read_stream(cx, buf))?;
+    if let Some(deflate) = &mut self.deflate {
+      deflate.read(&mut buf.filled_mut()[filled..]);
+    }
+    Poll::Ready(Ok(()))
+  }
+}
+
+impl WebSocketStream {
+  // From hyper's Rewind (https://github.com/hyperium/hyper), MIT License, Copyright (c) Sean McArthur
+  fn poll_read_stream(
+    mut self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+    buf: &mut ReadBuf<'_>,
   ) -> Poll<std::io::Result<()>> {
     if let Some(mut prefix) = self.pre.take() {
       // If there are no remaining bytes, let the bytes get dropped.
@@ -85,11 +127,9 @@ impl AsyncRead for WebSocketStream {
       }
     }
   }
-}
 
-impl AsyncWrite for WebSocketStream {
-  fn poll_write(
-    mut self: Pin<&mut Self>,
+  fn poll_write_stream(
+    &mut self,
     cx: &mut std::task::Context<'_>,
     buf: &[u8],
   ) -> std::task::Poll<Result<usize, std::io::Error>> {
@@ -141,6 +181,16 @@ impl AsyncWrite for WebSocketStream {
       }
     }
   }
+}
+
+impl AsyncWrite for WebSocketStream {
+  fn poll_write(
+    mut self: Pin<&mut Self>,
+    cx: &mut std::task::Context<'_>,
+    buf: &[u8],
+  ) -> std::task::Poll<Result<usize, std::io::Error>> {
+    self.poll_write_inner(cx, buf)
+  }
 
   fn poll_flush(
     mut self: Pin<&mut Self>,
@@ -171,6 +221,9 @@ impl AsyncWrite for WebSocketStream {
   }
 
   fn is_write_vectored(&self) -> bool {
+    if self.deflate.is_some() {
+      return false;
+    }
     match &self.stream {
       WsStreamKind::Network(stream) => stream.is_write_vectored(),
       WsStreamKind::Upgraded(stream) => stream.is_write_vectored(),
@@ -183,6 +236,10 @@ impl AsyncWrite for WebSocketStream {
     cx: &mut std::task::Context<'_>,
     bufs: &[std::io::IoSlice<'_>],
   ) -> std::task::Poll<Result<usize, std::io::Error>> {
+    if self.deflate.is_some() {
+      let buf = bufs.iter().find(|buf| !buf.is_empty());
+      return self.poll_write_inner(cx, buf.map_or(&[][..], |buf| &**buf));
+    }
     match &mut self.stream {
       WsStreamKind::Network(stream) => {
         Pin::new(stream).poll_write_vectored(cx, bufs)
@@ -216,6 +216,9 @@ pub fn convert(result: ParseResult) -> Result<Flags, CliError> {
     Some("bundle") => bundle_parse(&result, &mut flags),
     Some("audit") => audit_parse(&result, &mut flags)?,
     Some("why") => why_parse(&result, &mut flags),
+    Some("cron") => cron_parse(&result, &mut flags)?,
+    Some("kv") => kv_parse(&result, &mut flags)?,
+    Some("permissions") => permissions_parse(&result, &mut flags),
     Some("transpile") => transpile_parse(&result, &mut flags),
     Some("bump-version") => bump_version_parse(&result, &mut flags)?,
     Some("ci") => ci_parse(&result, &mut flags),
@@ -515,13 +518,40 @@ fn runtime_args_parse(
   Ok(())
 }
 
+/// Gets if `text` is a port (ex. `8080`) or a port range (ex. `8000-8100`).
+fn is_net_port_item(text: &str) -> bool {
+  match text.split_once('-') {
+    Some((start, end)) => {
+      start.parse::<u16>().is_ok() && end.parse::<u16>().is_ok()
+    }
+    None => text.parse::<u16>().is_ok(),
+  }
+}
+
 /// Expand bare port entries (e.g. ":8080") into full host:port entries
 /// for 0.0.0.0, 127.0.0.1, and localhost.
 fn expand_net_list(entries: Vec<String>) -> Vec<String> {
-  let mut out = Vec::new();
+  // The values were split on commas, so join port lists such as
+  // `example.com:80,443` back together.
+  let mut joined: Vec<String> = Vec::with_capacity(entries.len());
   for entry in entries {
+    if is_net_port_item(&entry)
+      && let Some(prev) = joined.last_mut()
+      && prev
+        .rsplit_once(':')
+        .is_some_and(|(_, ports)| ports.split(',').all(is_net_port_item))
+    {
+      prev.push(',');
+      prev.push_str(&entry);
+      continue;
+    }
+    joined.push(entry);
+  }
+
+  let mut out = Vec::new();
+  for entry in joined {
     if let Some(port_str) = entry.strip_prefix(':')
-      && port_str.parse::<u16>().is_ok()
+      && port_str.split(',').all(is_net_port_item)
     {
       for host in &["0.0.0.0", "127.0.0.1", "localhost"] {
         out.push(format!("{}:{}", host, port_str));
@@ -576,6 +606,9 @@ fn permission_args_parse(result: &ParseResult, flags: &mut Flags) {
   perm_flag!("deny-ffi", flags.permissions.deny_ffi);
   perm_flag!("allow-import", flags.permissions.allow_import);
   perm_flag!("deny-import", flags.permissions.deny_import);
+  perm_flag!("quota-net", flags.permissions.quota_net);
+  perm_flag!("quota-write", flags.permissions.quota_write);
+  perm_flag!("quota-run", flags.permissions.quota_run);
 
   if result.get_bool("allow-all") {
     flags.permissions.allow_all = true;
@@ -953,6 +986,7 @@ fn unstable_args_parse(result: &ParseResult, flags: &mut Flags) {
     ("unstable-process", None),
     ("unstable-subdomain-wildcards", None),
     ("unstable-temporal", None),
+    ("unstable-test-clock", None),
     ("unstable-unsafe-proto", None),
     ("unstable-vsock", None),
     ("unstable-webgpu", None),
@@ -1769,6 +1803,14 @@ fn test_parse(result: &ParseResult, flags: &mut Flags) -> Result<(), CliError> {
       None => vec![],
     },
     update_snapshots: result.get_bool("update-snapshots"),
+    fetch_cassette: match (
+      result.get_one("record-fetch"),
+      result.get_one("replay-fetch"),
+    ) {
+      (Some(path), _) => Some(FetchCassette::Record(path.to_string())),
+      (None, Some(path)) => Some(FetchCassette::Replay(path.to_string())),
+      (None, None) => None,
+    },
   });
   Ok(())
 }
@@ -2031,6 +2073,14 @@ fn task_parse(result: &ParseResult, flags: &mut Flags) -> Result<(), CliError> {
     ));
   }
 
+  let explain_cache = result.get_bool("explain-cache");
+  if explain_cache && task_name.is_none() {
+    return Err(CliError::new(
+      CliErrorKind::MissingRequired,
+      "[TASK] must be specified when using --explain-cache",
+    ));
+  }
+
   let concurrency = match result.get_one("jobs") {
     Some(s) => Some(s.parse::<NonZeroUsize>().map_err(|_| {
       CliError::new(
@@ -2052,6 +2102,7 @@ fn task_parse(result: &ParseResult, flags: &mut Flags) -> Result<(), CliError> {
     no_prefix: result.get_bool("no-prefix"),
     concurrency,
     if_present: result.get_bool("if-present"),
+    explain_cache,
   });
   Ok(())
 }
@@ -2871,6 +2922,127 @@ fn outdated_parse(result: &ParseResult, flags: &mut Flags, is_update: bool) {
   min_dep_age_arg_parse(result, flags);
 }
 
+fn cron_parse(result: &ParseResult, flags: &mut Flags) -> Result<(), CliError> {
+  compile_args_without_check_parse(result, flags);
+
+  let mut args = result
+    .get_many("args")
+    .map(|v| v.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    .unwrap_or_default();
+  let kind = match result.get_one("action") {
+    Some("list") => {
+      if args.is_empty() {
+        return Err(CliError::new(
+          CliErrorKind::MissingRequired,
+          "At least one entrypoint must be specified for 'deno cron list'",
+        ));
+      }
+      CronFlagsKind::List { files: args }
+    }
+    Some("trigger") => {
+      if args.len() > 1 {
+        return Err(CliError::new(
+          CliErrorKind::UnexpectedPositional,
+          format!("unexpected argument '{}' found", args[1]),
+        ));
+      }
+      let Some(name) = args.pop() else {
+        return Err(CliError::new(
+          CliErrorKind::MissingRequired,
+          "The name of the cron must be specified for 'deno cron trigger'",
+        ));
+      };
+      CronFlagsKind::Trigger { name }
+    }
+    _ => {
+      if let Some(arg) = args.first() {
+        return Err(CliError::new(
+          CliErrorKind::UnexpectedPositional,
+          format!("unexpected argument '{arg}' found"),
+        ));
+      }
+      CronFlagsKind::Status
+    }
+  };
+  flags.subcommand = DenoSubcommand::Cron(CronFlags {
+    kind,
+    connect: result.get_one("connect").map(|s| s.to_string()),
+  });
+  Ok(())
+}
+
+fn kv_parse(result: &ParseResult, flags: &mut Flags) -> Result<(), CliError> {
+  let args = result
+    .get_many("args")
+    .map(|v| v.iter().map(|s| s.to_string()).collect::<Vec<_>>())
+    .unwrap_or_default();
+  let action = result.get_one("action").unwrap_or_default();
+  let max_args = if action == "export" { 1 } else { 2 };
+  if let Some(arg) = args.get(max_args) {
+    return Err(CliError::new(
+      CliErrorKind::UnexpectedPositional,
+      format!("unexpected argument '{arg}' found"),
+    ));
+  }
+  let mut args = args.into_iter();
+  let Some(database) = args.next() else {
+    return Err(CliError::new(
+      CliErrorKind::MissingRequired,
+      format!("The database must be specified for 'deno kv {action}'"),
+    ));
+  };
+  let prefix = result.get_one("prefix").map(|s| s.to_string());
+  let output = result.get_one("output").map(|s| s.to_string());
+  if prefix.is_some() && action != "export" {
+    return Err(CliError::new(
+      CliErrorKind::InvalidValue,
+      "--prefix can only be used with 'deno kv export'",
+    ));
+  }
+  let kind = match action {
+    "export" => KvFlagsKind::Export {
+      database,
+      prefix,
+      output,
+    },
+    "import" => {
+      if output.is_some() {
+        return Err(CliError::new(
+          CliErrorKind::InvalidValue,
+          "--output can't be used with 'deno kv import'",
+        ));
+      }
+      KvFlagsKind::Import {
+        database,
+        input: args.next().filter(|input| input != "-"),
+      }
+    }
+    _ => {
+      let Some(output) = args.next().or(output) else {
+        return Err(CliError::new(
+          CliErrorKind::MissingRequired,
+          "The backup file must be specified for 'deno kv backup'",
+        ));
+      };
+      KvFlagsKind::Backup { database, output }
+    }
+  };
+  flags.subcommand = DenoSubcommand::Kv(KvFlags { kind });
+  Ok(())
+}
+
+fn permissions_parse(result: &ParseResult, flags: &mut Flags) {
+  let audit_logs = result
+    .get_many("audit_logs")
+    .map(|v| v.iter().map(|s| s.to_string()).collect())
+    .unwrap_or_default();
+  flags.subcommand = DenoSubcommand::Permissions(PermissionsConfigFlags {
+    audit_logs,
+    name: result.get_one("name").unwrap_or("default").to_string(),
+    exact: result.get_bool("exact"),
+  });
+}
+
 fn clean_parse(result: &ParseResult, flags: &mut Flags) {
   let mut except_paths = Vec::new();
   if result.get_bool("except") {
@@ -165,6 +165,27 @@ pub static PERMISSION_ARGS: &[ArgDef] = &[
     .require_equals()
     .value_delimiter(',')
 .help("Deny importing from remote hosts. Optionally specify denied IP addresses and host names, with ports as necessary.")
+.hidden(),
+  ArgDef::new("quota-net")
+    .long("quota-net")
+    .action(ArgAction::Append)
+    .num_args(NumArgs::OneOrMore)
+    .require_equals()
+    .value_delimiter(',')
+.hidden(),
+  ArgDef::new("quota-write")
+    .long("quota-write")
+    .action(ArgAction::Append)
+    .num_args(NumArgs::OneOrMore)
+    .require_equals()
+    .value_delimiter(',')
+.hidden(),
+  ArgDef::new("quota-run")
+    .long("quota-run")
+    .action(ArgAction::Append)
+    .num_args(NumArgs::OneOrMore)
+    .require_equals()
+    .value_delimiter(',')
 .hidden(),
   ArgDef::new("no-prompt").long("no-prompt").set_true()
 .hidden(),
@@ -524,6 +545,11 @@ ArgDef::new("unstable-bare-node-builtins")
     .set_true()
     .hidden()
 .help("Enable unstable Temporal API"),
+  ArgDef::new("unstable-test-clock")
+    .long("unstable-test-clock")
+    .set_true()
+    .hidden()
+.help("Enable unstable `Deno.test.clock` API"),
   ArgDef::new("unstable-unsafe-proto")
     .long("unstable-unsafe-proto")
     .long_aliases(&["unsafe-proto"])
@@ -1191,6 +1217,19 @@ pub static TEST_SUBCOMMAND: CommandDef = CommandDef {
       .long("update-snapshots")
       .set_true()
 .help("Update snapshots created with `t.assertSnapshot()` instead of failing when they do not match"),
+    ArgDef::new("record-fetch")
+      .long("record-fetch")
+      .action(ArgAction::Set)
+      .num_args(NumArgs::Exact(1))
+      .require_equals()
+      .conflicts_with(&["replay-fetch"])
+.help("Send fetch() requests to the network and record them, with their responses, to the cassette file at PATH"),
+    ArgDef::new("replay-fetch")
+      .long("replay-fetch")
+      .action(ArgAction::Set)
+      .num_args(NumArgs::Exact(1))
+      .require_equals()
+.help("Serve fetch() responses from the cassette file at PATH written by --record-fetch. Requests are matched on their method, URL and body, and fail without a recorded response"),
     ArgDef::new("trace-leaks")
       .long("trace-leaks")
       .set_true()
@@ -1628,6 +1667,9 @@ pub static TASK_SUBCOMMAND: CommandDef = CommandDef {
 .help("Evaluate the passed value as if it was a task in a configuration file"),
     ArgDef::new("if-present").long("if-present").set_true()
 .help("Exit with code 0 instead of an error when the task is not found"),
+    ArgDef::new("explain-cache").long("explain-cache").set_true()
+      .conflicts_with(&["eval"])
+.help("Explain whether the task and its dependencies would be skipped by the task cache,\nand what changed since they last ran, without running them"),
     ArgDef::new("no-prefix").long("no-prefix").set_true()
 .help("Disable prefixing the output of concurrently-executing tasks with the task name"),
     ArgDef::new("jobs")
@@ -3373,6 +3415,108 @@ pub static WHY_SUBCOMMAND: CommandDef = CommandDef {
   keep_double_dash: false,
 };
 
+pub static CRON_SUBCOMMAND: CommandDef = CommandDef {
+  name: "cron",
+  about: "List, inspect and trigger Deno.cron jobs\n\n  deno cron list main.ts             Find the crons registered by a module graph\n  deno cron status                   Show the crons of a running process and when they run next\n  deno cron trigger \"Daily report\"   Run a cron of a running process now\n\n\"status\" and \"trigger\" talk to a process started with DENO_CRON_CONTROL_SOCK set, for example \"unix:/tmp/cron.sock\" or \"tcp:127.0.0.1:9000\".",
+  aliases: &[],
+  args: &[
+    ArgDef::new("action")
+      .positional()
+      .required()
+      .value_parser(ValueParser::Choices(&["list", "status", "trigger"]))
+.help("What to do: list, status or trigger"),
+    ArgDef::new("args")
+      .positional()
+      .action(ArgAction::Append)
+      .num_args(NumArgs::ZeroOrMore)
+      .value_name("ARGS")
+.help("Entrypoints for list, or the cron name for trigger"),
+    ArgDef::new("connect")
+      .long("connect")
+      .action(ArgAction::Set)
+      .num_args(NumArgs::Exact(1))
+      .value_name("ADDRESS")
+.help("Control socket of the running process. Defaults to DENO_CRON_CONTROL_SOCK"),
+  ],
+  arg_groups: &[UNSTABLE_DEPRECATED_ARG, UNSTABLE_FEATURE_ARGS, COMPILE_ARGS],
+  subcommands: &[],
+  default_subcommand: None,
+  trailing_var_arg: false,
+  passthrough: false,
+  keep_double_dash: false,
+};
+
+pub static KV_SUBCOMMAND: CommandDef = CommandDef {
+  name: "kv",
+  about: "Export, import and back up local Deno KV databases\n\n  deno kv export kv.sqlite3 > kv.ndjson                 Export all entries as newline-delimited JSON\n  deno kv export kv.sqlite3 --prefix '[\"users\"]'       Export the entries under a key prefix\n  deno kv import other.sqlite3 kv.ndjson               Import an export, overwriting existing keys\n  deno kv backup kv.sqlite3 backup.sqlite3             Copy a database while it is in use\n\nExports are consistent snapshots of the database. Imports are written in batches and are not atomic: if an import fails, the entries before the failing batch stay imported. Use Deno.Kv.prototype.export() and import() to move data to and from remote databases.",
+  aliases: &[],
+  args: &[
+    ArgDef::new("action")
+      .positional()
+      .required()
+      .value_parser(ValueParser::Choices(&["export", "import", "backup"]))
+      .help("What to do: export, import or backup"),
+    ArgDef::new("args")
+      .positional()
+      .action(ArgAction::Append)
+      .num_args(NumArgs::ZeroOrMore)
+      .value_name("ARGS")
+      .help("The database, followed by the file to import or the backup file"),
+    ArgDef::new("prefix")
+      .long("prefix")
+      .action(ArgAction::Set)
+      .num_args(NumArgs::Exact(1))
+      .value_name("KEY")
+      .help(
+        "Only export the entries under this key prefix, given as a JSON array",
+      ),
+    ArgDef::new("output")
+      .long("output")
+      .short('o')
+      .action(ArgAction::Set)
+      .num_args(NumArgs::Exact(1))
+      .value_name("FILE")
+      .help("Write the export to this file instead of stdout"),
+  ],
+  arg_groups: &[UNSTABLE_DEPRECATED_ARG, UNSTABLE_FEATURE_ARGS],
+  subcommands: &[],
+  default_subcommand: None,
+  trailing_var_arg: false,
+  passthrough: false,
+  keep_double_dash: false,
+};
+
+pub static PERMISSIONS_SUBCOMMAND: CommandDef = CommandDef {
+  name: "permissions",
+  about: "Generate a least-privilege permissions config from permission audit logs\n\nReplays one or more files written via DENO_AUDIT_PERMISSIONS and prints a \"permissions\" object that can be pasted into deno.json.",
+  aliases: &[],
+  args: &[
+    ArgDef::new("audit_logs")
+      .positional()
+      .action(ArgAction::Append)
+      .num_args(NumArgs::OneOrMore)
+      .required()
+      .value_name("AUDIT_LOG")
+      .help("Audit log files written with DENO_AUDIT_PERMISSIONS"),
+    ArgDef::new("name")
+      .long("name")
+      .action(ArgAction::Set)
+      .num_args(NumArgs::Exact(1))
+      .default_value("default")
+      .help("Name of the permission set to generate"),
+    ArgDef::new("exact")
+      .long("exact")
+      .set_true()
+.help("Emit every accessed path, host and env var as-is instead of collapsing them into directories and wildcard groups"),
+  ],
+  arg_groups: &[UNSTABLE_DEPRECATED_ARG, UNSTABLE_FEATURE_ARGS],
+  subcommands: &[],
+  default_subcommand: None,
+  trailing_var_arg: false,
+  passthrough: false,
+  keep_double_dash: false,
+};
+
 pub static LINK_SUBCOMMAND: CommandDef = CommandDef {
   name: "link",
   about: "Link a local JSR package into the current project for development",
@@ -3504,6 +3648,9 @@ pub static DENO_ROOT: CommandDef = CommandDef {
     BUNDLE_SUBCOMMAND,
     AUDIT_SUBCOMMAND,
     WHY_SUBCOMMAND,
+    CRON_SUBCOMMAND,
+    KV_SUBCOMMAND,
+    PERMISSIONS_SUBCOMMAND,
     TRANSPILE_SUBCOMMAND,
     BUMP_VERSION_SUBCOMMAND,
     CI_SUBCOMMAND,
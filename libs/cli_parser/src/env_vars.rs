@@ -14,11 +14,21 @@ pub static ENV_VARS: &[EnvVar] = &[
     description: "A semi-colon separated list of bearer tokens and hostnames\nto use when fetching remote modules from private repositories",
     example: Some(r#"(e.g. "abcde12345@deno.land;54321edcba@github.com")"#),
   },
+  EnvVar {
+    name: "DENO_CACHE_BACKEND",
+    description: "Backend of the Web cache: \"memory\" keeps responses in memory only,\n\"tiered\" adds an in-memory layer in front of the default backend.",
+    example: None,
+  },
   EnvVar {
     name: "DENO_CACHE_DB_MODE",
     description: "Controls whether Web cache should use disk based or in-memory database.",
     example: None,
   },
+  EnvVar {
+    name: "DENO_CACHE_MAX_SIZE",
+    description: "Maximum size in bytes of the Web cache of an origin. The least recently\nmatched responses are evicted when it is exceeded, and stale responses are\nno longer matched.",
+    example: None,
+  },
   EnvVar {
     name: "DENO_CERT",
     description: "Load certificate authorities from PEM encoded file.",
@@ -39,6 +49,21 @@ pub static ENV_VARS: &[EnvVar] = &[
     description: "Set the directory for collecting code coverage profiles.\nEquivalent to using the --coverage flag.",
     example: None,
   },
+  EnvVar {
+    name: "DENO_CRON_CATCH_UP",
+    description: "Persist the state of local Deno.cron jobs and catch up on ticks missed\nwhile the process wasn't running or that failed all of their retries:\n\"skip\", \"once\" or \"all\". Crons in web workers are not persisted.",
+    example: None,
+  },
+  EnvVar {
+    name: "DENO_CRON_CONTROL_SOCK",
+    description: "Serve a control socket for local Deno.cron jobs, used by\n`deno cron status` and `deno cron trigger`. TCP addresses must be loopback\naddresses.",
+    example: Some(r#"("unix:/tmp/cron.sock" or "tcp:127.0.0.1:9000")"#),
+  },
+  EnvVar {
+    name: "DENO_CRON_STATE_PATH",
+    description: "Set the SQLite file that DENO_CRON_CATCH_UP persists cron state in.\nDefaults to a file next to the Deno.openKv() database.",
+    example: None,
+  },
   EnvVar {
     name: "DENO_DIR",
     description: "Set the cache directory",
@@ -116,6 +141,11 @@ pub static ENV_VARS: &[EnvVar] = &[
     description: "If the entrypoint contains export default { fetch }, `deno run`\nbehaves like `deno serve`.",
     example: None,
   },
+  EnvVar {
+    name: "DENO_TASK_CACHE_READ_ONLY",
+    description: "Set to 1 or true to only restore task results from the remote\n\"taskCache\" and never upload to it, e.g. for untrusted pull requests.",
+    example: None,
+  },
   EnvVar {
     name: "DENO_TLS_CA_STORE",
     // Descriptions are plain text here (this table is shared by the plain
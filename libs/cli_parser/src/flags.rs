@@ -66,6 +66,55 @@ pub struct WhyFlags {
   pub package: String,
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PermissionsConfigFlags {
+  pub audit_logs: Vec<String>,
+  /// Name of the permission set in the generated config.
+  pub name: String,
+  /// Don't collapse accessed values into broader grants.
+  pub exact: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CronFlagsKind {
+  /// Statically find the crons registered by the module graphs of `files`.
+  List { files: Vec<String> },
+  /// Show the crons of a running process.
+  Status,
+  /// Run a cron of a running process now.
+  Trigger { name: String },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CronFlags {
+  pub kind: CronFlagsKind,
+  /// Address of the control socket of the running process.
+  pub connect: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KvFlagsKind {
+  /// Write the entries of `database`, or of a key prefix given as a JSON
+  /// array, to `output` or stdout as newline-delimited JSON.
+  Export {
+    database: String,
+    prefix: Option<String>,
+    output: Option<String>,
+  },
+  /// Write the entries of `input` or stdin to `database`.
+  Import {
+    database: String,
+    input: Option<String>,
+  },
+  /// Copy `database` to `output` while it may be in use.
+  Backup { database: String, output: String },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct KvFlags {
+  pub kind: KvFlagsKind,
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct AuditFlags {
   pub severity: String,
@@ -632,6 +681,9 @@ pub struct TaskFlags {
   pub concurrency: Option<NonZeroUsize>,
   /// Exit with code 0 instead of an error when the named task is not found.
   pub if_present: bool,
+  /// Report whether the task and its dependencies would hit the task cache,
+  /// and what changed since they last ran, instead of running them.
+  pub explain_cache: bool,
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
@@ -677,6 +729,16 @@ pub struct TestFlags {
   /// Run only test modules that depend on the given source files (`--related`).
   pub related: Vec<String>,
   pub update_snapshots: bool,
+  /// Record `fetch()` requests to, or replay them from, a cassette file.
+  pub fetch_cassette: Option<FetchCassette>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FetchCassette {
+  /// `--record-fetch=<path>`
+  Record(String),
+  /// `--replay-fetch=<path>`
+  Replay(String),
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Default)]
@@ -796,6 +858,7 @@ pub enum DenoSubcommand {
   Clean(CleanFlags),
   Compile(CompileFlags),
   Completions(CompletionsFlags),
+  Cron(CronFlags),
   Desktop(DesktopFlags),
   Coverage(CoverageFlags),
   Deploy(DeployFlags),
@@ -808,6 +871,7 @@ pub enum DenoSubcommand {
   Install(InstallFlags),
   JSONReference(JSONReferenceFlags),
   Jupyter(JupyterFlags),
+  Kv(KvFlags),
   Uninstall(UninstallFlags),
   Link(LinkFlags),
   Unlink(UnlinkFlags),
@@ -821,6 +885,7 @@ pub enum DenoSubcommand {
   Test(TestFlags),
   Transpile(TranspileFlags),
   Outdated(OutdatedFlags),
+  Permissions(PermissionsConfigFlags),
   Types,
   Upgrade(UpgradeFlags),
   Vendor,
@@ -1019,6 +1084,9 @@ pub struct PermissionFlags {
   pub no_prompt: bool,
   pub allow_import: Option<Vec<String>>,
   pub deny_import: Option<Vec<String>>,
+  pub quota_net: Option<Vec<String>>,
+  pub quota_write: Option<Vec<String>>,
+  pub quota_run: Option<Vec<String>>,
 }
 
 impl PermissionFlags {
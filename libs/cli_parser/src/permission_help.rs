@@ -20,8 +20,8 @@ Docs: https://docs.deno.com/go/permissions
   -I, --allow-import[=<IP_OR_HOSTNAME>...]  Allow importing from remote hosts. Optionally specify allowed IP addresses and host names, with ports as necessary.
                                             Default value: deno.land:443,jsr.io:443,esm.sh:443,raw.esm.sh:443,cdn.jsdelivr.net:443,raw.githubusercontent.com:443,gist.githubusercontent.com:443
                                             --allow-import  |  --allow-import=\"example.com,github.com\"
-  -N, --allow-net[=<IP_OR_HOSTNAME>...]     Allow network access. Optionally specify allowed IP addresses and host names, with ports as necessary. A Unix domain socket can be scoped with unix:<absolute-path>.
-                                            --allow-net  |  --allow-net=\"localhost:8080,deno.land\"  |  --allow-net=\"unix:/var/run/docker.sock\"
+  -N, --allow-net[=<IP_OR_HOSTNAME>...]     Allow network access. Optionally specify allowed IP addresses and host names, with ports, port ranges or port lists as necessary. A Unix domain socket can be scoped with unix:<absolute-path>.
+                                            --allow-net  |  --allow-net=\"localhost:8080,deno.land\"  |  --allow-net=\"127.0.0.1:30000-31000\"  |  --allow-net=\"unix:/var/run/docker.sock\"
   -E, --allow-env[=<VARIABLE_NAME>...]      Allow access to environment variables. Optionally specify accessible environment variables.
                                             --allow-env  |  --allow-env=\"PORT,HOME,PATH\"
   -S, --allow-sys[=<API_NAME>...]           Allow access to OS information. Optionally allow specific APIs by function name.
@@ -34,7 +34,7 @@ Docs: https://docs.deno.com/go/permissions
                                             --deny-read  |  --deny-read=\"/etc,/var/log.txt\"
   --deny-write[=<PATH>...]                  Deny file system write access. Optionally specify denied paths.
                                             --deny-write  |  --deny-write=\"/etc,/var/log.txt\"
-  --deny-net[=<IP_OR_HOSTNAME>...]          Deny network access. Optionally specify defined IP addresses and host names, with ports as necessary.
+  --deny-net[=<IP_OR_HOSTNAME>...]          Deny network access. Optionally specify defined IP addresses and host names, with ports, port ranges or port lists as necessary.
                                             --deny-net  |  --deny-net=\"localhost:8080,deno.land\"
   --deny-env[=<VARIABLE_NAME>...]           Deny access to environment variables. Optionally specify inacessible environment variables.
                                             --deny-env  |  --deny-env=\"PORT,HOME,PATH\"
@@ -50,6 +50,12 @@ Docs: https://docs.deno.com/go/permissions
                                             --ignore-env  |  --ignore-env=\"PORT,HOME,PATH\"
   --ignore-read[=<PATH>...]                 Ignore file system read access with a `NotFound` error. Optionally specify ignored paths.
                                             --ignore-read  |  --ignore-read=\"/etc,/var/log.txt\"
+  --quota-net=<LIMIT|HOST=LIMIT>...         Limit the number of outbound connections and requests, in total or to specific hosts.
+                                            --quota-net=100  |  --quota-net=\"api.example.com=10\"
+  --quota-write=<LIMIT|PATH=LIMIT>...       Limit the number of bytes written to files, in total or under specific paths.
+                                            --quota-write=1048576  |  --quota-write=\"./out=1048576\"
+  --quota-run=<LIMIT|PROGRAM=LIMIT>...      Limit the number of subprocesses spawned, in total or of specific programs.
+                                            --quota-run=5  |  --quota-run=\"git=2\"
   DENO_TRACE_PERMISSIONS                    Environmental variable to enable stack traces in permission prompts.
                                             DENO_TRACE_PERMISSIONS=1 deno run main.ts
   DENO_AUDIT_PERMISSIONS                    Environmental variable to audit all permissions accesses. Set to a file path for JSONL output, or \"otel\" to emit as OpenTelemetry log events via the configured OTel exporter.
@@ -2383,6 +2383,37 @@ fn ignore_env_ignorelist() {
   );
 }
 
+#[test]
+fn quota_flags() {
+  let r = flags_from_vec(svec![
+    "deno",
+    "run",
+    "--quota-net=100,api.example.com=10",
+    "--quota-write=./out=1048576",
+    "--quota-run=5",
+    "script.ts"
+  ]);
+  assert_eq!(
+    r.unwrap(),
+    Flags {
+      subcommand: DenoSubcommand::Run(RunFlags::new_default(
+        "script.ts".to_string(),
+      )),
+      permissions: PermissionFlags {
+        quota_net: Some(svec!["100", "api.example.com=10"]),
+        quota_write: Some(svec!["./out=1048576"]),
+        quota_run: Some(svec!["5"]),
+        ..Default::default()
+      },
+      code_cache_enabled: true,
+      ..Flags::default()
+    }
+  );
+
+  let r = flags_from_vec(svec!["deno", "run", "--quota-net", "script.ts"]);
+  assert!(r.is_err());
+}
+
 #[test]
 fn allow_env_allowlist_multiple() {
   let r =
@@ -3649,6 +3680,39 @@ fn deny_net_denylist_with_ports() {
   );
 }
 
+#[test]
+fn allow_net_allowlist_with_port_ranges_and_lists() {
+  let r = flags_from_vec(svec![
+    "deno",
+    "run",
+    "--allow-net=127.0.0.1:30000-31000,example.com:80,443,8000-8100,deno.land,:8000,8443",
+    "--deny-net=127.0.0.1:30500,30600",
+    "script.ts"
+  ]);
+  assert_eq!(
+    r.unwrap(),
+    Flags {
+      subcommand: DenoSubcommand::Run(RunFlags::new_default(
+        "script.ts".to_string(),
+      )),
+      permissions: PermissionFlags {
+        allow_net: Some(svec![
+          "127.0.0.1:30000-31000",
+          "example.com:80,443,8000-8100",
+          "deno.land",
+          "0.0.0.0:8000,8443",
+          "127.0.0.1:8000,8443",
+          "localhost:8000,8443"
+        ]),
+        deny_net: Some(svec!["127.0.0.1:30500,30600"]),
+        ..Default::default()
+      },
+      code_cache_enabled: true,
+      ..Flags::default()
+    }
+  );
+}
+
 #[test]
 fn allow_net_allowlist_with_ipv6_address() {
   let r = flags_from_vec(svec![
@@ -3756,6 +3820,7 @@ fn test_with_flags() {
         changed: None,
         related: vec![],
         update_snapshots: false,
+        fetch_cassette: None,
       }),
       no_npm: true,
       no_remote: true,
@@ -3866,6 +3931,7 @@ fn test_with_fail_fast() {
         changed: None,
         related: vec![],
         update_snapshots: false,
+        fetch_cassette: None,
       }),
       type_check_mode: TypeCheckMode::Local,
       permissions: PermissionFlags {
@@ -3918,6 +3984,7 @@ fn test_with_enable_testing_features() {
         changed: None,
         related: vec![],
         update_snapshots: false,
+        fetch_cassette: None,
       }),
       permissions: PermissionFlags {
         no_prompt: true,
@@ -4119,6 +4186,7 @@ fn test_shuffle() {
         changed: None,
         related: vec![],
         update_snapshots: false,
+        fetch_cassette: None,
       }),
       permissions: PermissionFlags {
         no_prompt: true,
@@ -4164,6 +4232,7 @@ fn test_retry_and_repeats() {
         changed: None,
         related: vec![],
         update_snapshots: false,
+        fetch_cassette: None,
       }),
       permissions: PermissionFlags {
         no_prompt: true,
@@ -4194,6 +4263,37 @@ fn test_shard() {
   assert!(flags_from_vec(svec!["deno", "test", "--shard=foo"]).is_err());
 }
 
+#[test]
+fn test_fetch_cassette() {
+  let r = flags_from_vec(svec!["deno", "test", "--record-fetch=fetch.json"]);
+  assert!(matches!(
+    r.unwrap().subcommand,
+    DenoSubcommand::Test(TestFlags {
+      fetch_cassette: Some(FetchCassette::Record(path)),
+      ..
+    }) if path == "fetch.json"
+  ));
+
+  let r = flags_from_vec(svec!["deno", "test", "--replay-fetch=fetch.json"]);
+  assert!(matches!(
+    r.unwrap().subcommand,
+    DenoSubcommand::Test(TestFlags {
+      fetch_cassette: Some(FetchCassette::Replay(path)),
+      ..
+    }) if path == "fetch.json"
+  ));
+
+  assert!(
+    flags_from_vec(svec![
+      "deno",
+      "test",
+      "--record-fetch=a.json",
+      "--replay-fetch=b.json"
+    ])
+    .is_err()
+  );
+}
+
 #[test]
 fn test_watch() {
   let r = flags_from_vec(svec!["deno", "test", "--watch"]);
@@ -4228,6 +4328,7 @@ fn test_watch() {
         changed: None,
         related: vec![],
         update_snapshots: false,
+        fetch_cassette: None,
       }),
       permissions: PermissionFlags {
         no_prompt: true,
@@ -4273,6 +4374,7 @@ fn test_watch_explicit_cwd() {
         changed: None,
         related: vec![],
         update_snapshots: false,
+        fetch_cassette: None,
       }),
       permissions: PermissionFlags {
         no_prompt: true,
@@ -4319,6 +4421,7 @@ fn test_watch_with_no_clear_screen() {
         changed: None,
         related: vec![],
         update_snapshots: false,
+        fetch_cassette: None,
       }),
       type_check_mode: TypeCheckMode::Local,
       permissions: PermissionFlags {
@@ -5386,6 +5489,7 @@ fn task_subcommand() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       argv: svec!["hello", "world"],
       ..Flags::default()
@@ -5407,6 +5511,7 @@ fn task_subcommand() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       ..Flags::default()
     }
@@ -5427,6 +5532,7 @@ fn task_subcommand() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       ..Flags::default()
     }
@@ -5447,6 +5553,7 @@ fn task_subcommand() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       ..Flags::default()
     }
@@ -5467,6 +5574,7 @@ fn task_subcommand() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       ..Flags::default()
     }
@@ -5487,6 +5595,7 @@ fn task_subcommand() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       ..Flags::default()
     }
@@ -5507,6 +5616,7 @@ fn task_subcommand() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       ..Flags::default()
     }
@@ -5534,6 +5644,7 @@ fn task_subcommand() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       ..Flags::default()
     }
@@ -5567,6 +5678,7 @@ fn task_subcommand_jobs() {
           no_prefix: false,
           concurrency: Some(NonZeroUsize::new(1).unwrap()),
           if_present: false,
+          explain_cache: false,
         }),
         ..Flags::default()
       },
@@ -5607,6 +5719,7 @@ fn task_subcommand_double_hyphen() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       argv: svec!["--", "hello", "world"],
       config_flag: ConfigFlag::Path("deno.json".to_owned()),
@@ -5631,6 +5744,7 @@ fn task_subcommand_double_hyphen() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       argv: svec!["--", "hello", "world"],
       ..Flags::default()
@@ -5656,6 +5770,7 @@ fn task_subcommand_double_hyphen_only() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       argv: svec!["--"],
       ..Flags::default()
@@ -5680,6 +5795,7 @@ fn task_following_arg() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       argv: svec!["-1", "--test"],
       ..Flags::default()
@@ -5704,6 +5820,7 @@ fn task_following_double_hyphen_arg() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       argv: svec!["--test"],
       ..Flags::default()
@@ -5729,6 +5846,7 @@ fn task_with_global_flags() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       log_level: Some(log::Level::Error),
       ..Flags::default()
@@ -5753,6 +5871,7 @@ fn task_subcommand_empty() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       ..Flags::default()
     }
@@ -5776,6 +5895,7 @@ fn task_subcommand_config() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
       ..Flags::default()
@@ -5800,6 +5920,7 @@ fn task_subcommand_config_short() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       config_flag: ConfigFlag::Path("deno.jsonc".to_string()),
       ..Flags::default()
@@ -5830,6 +5951,7 @@ fn task_subcommand_env_file() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       env_file: Some(vec![".env".to_owned()]),
       ..Flags::default()
@@ -5857,6 +5979,7 @@ fn task_subcommand_env_file() {
         no_prefix: false,
         concurrency: None,
         if_present: false,
+        explain_cache: false,
       }),
       env_file: Some(vec![".env.dev".to_owned(), ".env.local".to_owned()]),
       ..Flags::default()
@@ -5881,12 +6004,43 @@ fn task_subcommand_if_present() {
         no_prefix: false,
         concurrency: None,
         if_present: true,
+        explain_cache: false,
       }),
       ..Flags::default()
     }
   );
 }
 
+#[test]
+fn task_subcommand_explain_cache() {
+  let r = flags_from_vec(svec!["deno", "task", "--explain-cache", "build"]);
+  assert_eq!(
+    r.unwrap(),
+    Flags {
+      subcommand: DenoSubcommand::Task(TaskFlags {
+        cwd: None,
+        task: Some("build".to_string()),
+        is_run: false,
+        recursive: false,
+        members: false,
+        filter: None,
+        eval: false,
+        no_prefix: false,
+        concurrency: None,
+        if_present: false,
+        explain_cache: true,
+      }),
+      ..Flags::default()
+    }
+  );
+
+  let r = flags_from_vec(svec!["deno", "task", "--explain-cache"]);
+  assert!(r.is_err());
+  let r =
+    flags_from_vec(svec!["deno", "task", "--explain-cache", "--eval", "echo"]);
+  assert!(r.is_err());
+}
+
 #[test]
 fn bench_with_flags() {
   let r = flags_from_vec(svec![
@@ -8890,6 +9044,172 @@ fn why_package() {
   );
 }
 
+#[test]
+fn cron() {
+  let r = flags_from_vec(svec!["deno", "cron", "list", "main.ts", "jobs.ts"]);
+  assert_eq!(
+    r.unwrap(),
+    Flags {
+      subcommand: DenoSubcommand::Cron(CronFlags {
+        kind: CronFlagsKind::List {
+          files: svec!["main.ts", "jobs.ts"],
+        },
+        connect: None,
+      }),
+      ..Flags::default()
+    }
+  );
+
+  let r = flags_from_vec(svec!["deno", "cron", "status"]);
+  assert_eq!(
+    r.unwrap(),
+    Flags {
+      subcommand: DenoSubcommand::Cron(CronFlags {
+        kind: CronFlagsKind::Status,
+        connect: None,
+      }),
+      ..Flags::default()
+    }
+  );
+
+  let r = flags_from_vec(svec![
+    "deno",
+    "cron",
+    "trigger",
+    "--connect",
+    "unix:/tmp/cron.sock",
+    "Daily report"
+  ]);
+  assert_eq!(
+    r.unwrap(),
+    Flags {
+      subcommand: DenoSubcommand::Cron(CronFlags {
+        kind: CronFlagsKind::Trigger {
+          name: "Daily report".to_string(),
+        },
+        connect: Some("unix:/tmp/cron.sock".to_string()),
+      }),
+      ..Flags::default()
+    }
+  );
+
+  for args in [
+    svec!["deno", "cron"],
+    svec!["deno", "cron", "run"],
+    svec!["deno", "cron", "list"],
+    svec!["deno", "cron", "status", "main.ts"],
+    svec!["deno", "cron", "trigger"],
+    svec!["deno", "cron", "trigger", "a", "b"],
+  ] {
+    assert!(flags_from_vec(args.clone()).is_err(), "{args:?}");
+  }
+}
+
+#[test]
+fn kv() {
+  let r = flags_from_vec(svec![
+    "deno",
+    "kv",
+    "export",
+    "kv.sqlite3",
+    "--prefix",
+    "[\"users\"]",
+    "-o",
+    "users.ndjson"
+  ]);
+  assert_eq!(
+    r.unwrap(),
+    Flags {
+      subcommand: DenoSubcommand::Kv(KvFlags {
+        kind: KvFlagsKind::Export {
+          database: "kv.sqlite3".to_string(),
+          prefix: Some("[\"users\"]".to_string()),
+          output: Some("users.ndjson".to_string()),
+        },
+      }),
+      ..Flags::default()
+    }
+  );
+
+  let r = flags_from_vec(svec!["deno", "kv", "import", "kv.sqlite3", "-"]);
+  assert_eq!(
+    r.unwrap(),
+    Flags {
+      subcommand: DenoSubcommand::Kv(KvFlags {
+        kind: KvFlagsKind::Import {
+          database: "kv.sqlite3".to_string(),
+          input: None,
+        },
+      }),
+      ..Flags::default()
+    }
+  );
+
+  let r =
+    flags_from_vec(svec!["deno", "kv", "backup", "kv.sqlite3", "backup.db"]);
+  assert_eq!(
+    r.unwrap(),
+    Flags {
+      subcommand: DenoSubcommand::Kv(KvFlags {
+        kind: KvFlagsKind::Backup {
+          database: "kv.sqlite3".to_string(),
+          output: "backup.db".to_string(),
+        },
+      }),
+      ..Flags::default()
+    }
+  );
+
+  for args in [
+    svec!["deno", "kv"],
+    svec!["deno", "kv", "export"],
+    svec!["deno", "kv", "export", "kv.sqlite3", "out.ndjson"],
+    svec!["deno", "kv", "import", "kv.sqlite3", "--prefix", "[]"],
+    svec!["deno", "kv", "backup", "kv.sqlite3"],
+    svec!["deno", "kv", "restore", "kv.sqlite3"],
+  ] {
+    assert!(flags_from_vec(args.clone()).is_err(), "{args:?}");
+  }
+}
+
+#[test]
+fn permissions_from_audit_logs() {
+  let r = flags_from_vec(svec!["deno", "permissions", "a.jsonl", "b.jsonl"]);
+  assert_eq!(
+    r.unwrap(),
+    Flags {
+      subcommand: DenoSubcommand::Permissions(PermissionsConfigFlags {
+        audit_logs: svec!["a.jsonl", "b.jsonl"],
+        name: "default".to_string(),
+        exact: false,
+      }),
+      ..Flags::default()
+    }
+  );
+
+  let r = flags_from_vec(svec![
+    "deno",
+    "permissions",
+    "--name=server",
+    "--exact",
+    "audit.jsonl"
+  ]);
+  assert_eq!(
+    r.unwrap(),
+    Flags {
+      subcommand: DenoSubcommand::Permissions(PermissionsConfigFlags {
+        audit_logs: svec!["audit.jsonl"],
+        name: "server".to_string(),
+        exact: true,
+      }),
+      ..Flags::default()
+    }
+  );
+
+  let r = flags_from_vec(svec!["deno", "permissions"]);
+  assert!(r.is_err());
+}
+
 #[test]
 fn why_package_with_version() {
   let r = flags_from_vec(svec!["deno", "why", "express@4.18.2"]);
@@ -46,6 +46,7 @@ pub use permissions::AllowDenyPermissionConfig;
 pub use permissions::AllowDenyPermissionConfigValue;
 pub use permissions::PermissionConfigValue;
 pub use permissions::PermissionNameOrObject;
+pub use permissions::PermissionQuotasConfig;
 pub use permissions::PermissionsConfig;
 pub use permissions::PermissionsObject;
 pub use permissions::PermissionsObjectWithBase;
@@ -1146,6 +1147,20 @@ pub struct TaskDefinition {
   /// not part of the cache key, even if the command reads them.
   #[serde(default)]
   pub env: Vec<String>,
+  /// Directory to run the task in, relative to the directory of the config
+  /// file that defines it. Takes precedence over `--cwd`.
+  #[serde(default)]
+  pub cwd: Option<String>,
+  /// Seconds after which an attempt at running the task is killed and counts
+  /// as failed. Must be at least 1.
+  ///
+  /// Like `retries`, this can only be set for tasks in a config file:
+  /// package.json scripts always run once without a time limit.
+  #[serde(default)]
+  pub timeout: Option<u64>,
+  /// How many more times to run the task after a failed attempt.
+  #[serde(default)]
+  pub retries: u32,
 }
 
 #[cfg(test)]
@@ -1158,6 +1173,9 @@ impl From<&str> for TaskDefinition {
       files: Vec::new(),
       output: Vec::new(),
       env: Vec::new(),
+      cwd: None,
+      timeout: None,
+      retries: 0,
     }
   }
 }
@@ -1200,9 +1218,19 @@ impl TaskDefinition {
               files: Vec::new(),
               output: Vec::new(),
               env: Vec::new(),
+              cwd: None,
+              timeout: None,
+              retries: 0,
             },
             serde_json::Value::Object(_) => {
-              serde_json::from_value(value).map_err(serde::de::Error::custom)?
+              let task_def: TaskDefinition = serde_json::from_value(value)
+                .map_err(serde::de::Error::custom)?;
+              if task_def.timeout == Some(0) {
+                return Err(serde::de::Error::custom(format!(
+                  "Task \"{key}\" has a \"timeout\" of 0, but it must be at least 1 second"
+                )));
+              }
+              task_def
             }
             _ => {
               return Err(serde::de::Error::custom("invalid task definition"));
@@ -1496,6 +1524,31 @@ pub struct DeployConfig {
   pub files: FilePatterns,
 }
 
+/// `taskCache` config representation for serde
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct SerializedTaskCacheConfig {
+  pub remote: String,
+  #[serde(default)]
+  pub read_only: bool,
+}
+
+/// Where `deno task` shares cached task results beyond the local cache.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TaskCacheRemote {
+  /// A content-addressed HTTP store, read with `GET` and written with `PUT`.
+  Http(Url),
+  /// A directory shared between machines, such as a network mount.
+  Directory(PathBuf),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TaskCacheConfig {
+  pub remote: TaskCacheRemote,
+  /// Only pull from the remote cache and never push to it.
+  pub read_only: bool,
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConfigFileJson {
@@ -1524,6 +1577,7 @@ pub struct ConfigFileJson {
   pub publish: Option<Value>,
   pub deploy: Option<Value>,
   pub allow_scripts: Option<Value>,
+  pub task_cache: Option<Value>,
 
   pub catalog: Option<IndexMap<String, String>>,
   pub catalogs: Option<IndexMap<String, IndexMap<String, String>>>,
@@ -2630,6 +2684,36 @@ impl ConfigFile {
     }
   }
 
+  pub fn to_task_cache_config(
+    &self,
+  ) -> Result<Option<TaskCacheConfig>, ToInvalidConfigError> {
+    let Some(config) = self.json.task_cache.clone() else {
+      return Ok(None);
+    };
+    let serialized: SerializedTaskCacheConfig = serde_json::from_value(config)
+      .map_err(|error| ToInvalidConfigError::Parse {
+        config: "taskCache",
+        source: error,
+      })?;
+    let remote = if serialized.remote.starts_with("http://")
+      || serialized.remote.starts_with("https://")
+    {
+      let url = Url::parse(&serialized.remote).map_err(|error| {
+        ToInvalidConfigError::InvalidConfig {
+          config: "taskCache",
+          source: IntoResolvedErrorKind::UrlParse(error).into_box(),
+        }
+      })?;
+      TaskCacheRemote::Http(url)
+    } else {
+      TaskCacheRemote::Directory(self.dir_path().join(&serialized.remote))
+    };
+    Ok(Some(TaskCacheConfig {
+      remote,
+      read_only: serialized.read_only,
+    }))
+  }
+
   pub fn to_minimum_dependency_age_config(
     &self,
     sys: &impl sys_traits::SystemTimeNow,
@@ -2938,6 +3022,12 @@ mod tests {
           "description": "Build client project",
           "command": "deno run -A client.js",
           "dependencies": ["build"]
+        },
+        "integration": {
+          "command": "deno test -A",
+          "cwd": "tests/integration",
+          "timeout": 300,
+          "retries": 2
         }
       },
       "unstable": ["kv", "ffi"]
@@ -3031,6 +3121,23 @@ mod tests {
         files: Vec::new(),
         output: Vec::new(),
         env: Vec::new(),
+        cwd: None,
+        timeout: None,
+        retries: 0,
+      }
+    );
+    assert_eq!(
+      tasks_config["integration"],
+      TaskDefinition {
+        description: None,
+        command: Some("deno test -A".to_string()),
+        dependencies: Vec::new(),
+        files: Vec::new(),
+        output: Vec::new(),
+        env: Vec::new(),
+        cwd: Some("tests/integration".to_string()),
+        timeout: Some(300),
+        retries: 2,
       }
     );
 
@@ -3040,6 +3147,28 @@ mod tests {
     )
   }
 
+  #[test]
+  fn tasks_config_rejects_zero_timeout() {
+    let config_text = r#"{
+      "tasks": {
+        "build": {
+          "command": "deno run build.ts",
+          "timeout": 0
+        }
+      }
+    }"#;
+    let config_specifier = Url::parse("file:///deno/deno.json").unwrap();
+    let config_file = ConfigFile::new(config_text, config_specifier).unwrap();
+    let err = config_file.to_tasks_config().unwrap_err();
+    let ToInvalidConfigError::Parse { source, .. } = err else {
+      panic!("expected a parse error");
+    };
+    assert_eq!(
+      source.to_string(),
+      "Task \"build\" has a \"timeout\" of 0, but it must be at least 1 second"
+    );
+  }
+
   #[test]
   fn test_parse_config_exclude_lower_priority_path() {
     let config_text = r#"{
@@ -3958,4 +4087,50 @@ mod tests {
       "tags are not supported in 'npm:chalk@next'"
     );
   }
+
+  #[test]
+  fn test_to_task_cache_config() {
+    fn get_result(
+      text: &str,
+    ) -> Result<Option<TaskCacheConfig>, ToInvalidConfigError> {
+      let config_specifier = root_url().join("deno.json").unwrap();
+      let config_file = ConfigFile::new(text, config_specifier).unwrap();
+      config_file.to_task_cache_config()
+    }
+
+    assert_eq!(get_result(r#"{}"#).unwrap(), None);
+    assert_eq!(
+      get_result(
+        r#"{
+        "taskCache": {
+          "remote": "https://cache.example.com/tasks/",
+          "readOnly": true
+        }
+      }"#
+      )
+      .unwrap(),
+      Some(TaskCacheConfig {
+        remote: TaskCacheRemote::Http(
+          Url::parse("https://cache.example.com/tasks/").unwrap()
+        ),
+        read_only: true,
+      })
+    );
+    assert_eq!(
+      get_result(r#"{ "taskCache": { "remote": "../shared/task_cache" } }"#)
+        .unwrap(),
+      Some(TaskCacheConfig {
+        remote: TaskCacheRemote::Directory(
+          url_to_file_path(&root_url())
+            .unwrap()
+            .join("../shared/task_cache")
+        ),
+        read_only: false,
+      })
+    );
+    assert!(get_result(r#"{ "taskCache": {} }"#).is_err());
+    assert!(
+      get_result(r#"{ "taskCache": { "remote": "https://[::1" } }"#).is_err()
+    );
+  }
 }
@@ -1,5 +1,7 @@
 // Copyright 2018-2026 the Deno authors. MIT license.
 
+use std::collections::BTreeMap;
+
 use indexmap::IndexMap;
 use serde::Deserialize;
 use url::Url;
@@ -164,6 +166,25 @@ fn deserialize_allow_deny_ignore<'de, D: serde::Deserializer<'de>>(
   })
 }
 
+/// Limits on how much of a granted permission may be used. Each entry maps a
+/// host, path or program to a limit, with `"*"` for a total.
+#[derive(Deserialize, Default, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(default, deny_unknown_fields)]
+pub struct PermissionQuotasConfig {
+  /// Outbound connections and requests.
+  pub net: BTreeMap<String, u64>,
+  /// Bytes written with `Deno.writeFile()`.
+  pub write: BTreeMap<String, u64>,
+  /// Subprocesses spawned.
+  pub run: BTreeMap<String, u64>,
+}
+
+impl PermissionQuotasConfig {
+  pub fn is_empty(&self) -> bool {
+    self.net.is_empty() && self.write.is_empty() && self.run.is_empty()
+  }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Hash)]
 #[serde(untagged)]
 pub enum PermissionNameOrObject {
@@ -198,6 +219,12 @@ pub struct PermissionsObject {
   pub ffi: AllowDenyPermissionConfig,
   #[serde(default, deserialize_with = "deserialize_allow_deny")]
   pub sys: AllowDenyPermissionConfig,
+  /// Further restrictions for the code of npm and JSR packages, keyed by
+  /// package (ex. `npm:chalk` or `jsr:@std/fs`).
+  #[serde(default)]
+  pub packages: BTreeMap<String, PermissionsObject>,
+  #[serde(default)]
+  pub quotas: PermissionQuotasConfig,
 }
 
 impl PermissionsObject {
@@ -212,6 +239,8 @@ impl PermissionsObject {
       && self.run.is_none()
       && self.ffi.is_none()
       && self.sys.is_none()
+      && self.packages.is_empty()
+      && self.quotas.is_empty()
   }
 }
 
@@ -326,7 +355,9 @@ mod test {
         sys: AllowDenyPermissionConfig {
           allow: Some(PermissionConfigValue::None),
           deny: None,
-        }
+        },
+        packages: Default::default(),
+        quotas: Default::default(),
       }
     );
 
@@ -377,7 +408,9 @@ mod test {
         sys: AllowDenyPermissionConfig {
           allow: Some(PermissionConfigValue::Some(vec!["test".to_string()])),
           deny: None,
-        }
+        },
+        packages: Default::default(),
+        quotas: Default::default(),
       }
     );
 
@@ -440,5 +473,65 @@ mod test {
         ..Default::default()
       }
     );
+
+    assert_eq!(
+      serde_json::from_value::<PermissionsObject>(json!({
+        "net": true,
+        "packages": {
+          "npm:some-logger": {
+            "net": false,
+            "run": false,
+          },
+        },
+      }))
+      .unwrap(),
+      PermissionsObject {
+        net: AllowDenyPermissionConfig {
+          allow: Some(PermissionConfigValue::All),
+          deny: None,
+        },
+        packages: BTreeMap::from([(
+          "npm:some-logger".to_string(),
+          PermissionsObject {
+            net: AllowDenyPermissionConfig {
+              allow: Some(PermissionConfigValue::None),
+              deny: None,
+            },
+            run: AllowDenyPermissionConfig {
+              allow: Some(PermissionConfigValue::None),
+              deny: None,
+            },
+            ..Default::default()
+          },
+        )]),
+        ..Default::default()
+      }
+    );
+
+    assert_eq!(
+      serde_json::from_value::<PermissionsObject>(json!({
+        "net": true,
+        "quotas": {
+          "net": { "*": 100, "api.example.com": 10 },
+          "run": { "git": 2 },
+        },
+      }))
+      .unwrap(),
+      PermissionsObject {
+        net: AllowDenyPermissionConfig {
+          allow: Some(PermissionConfigValue::All),
+          deny: None,
+        },
+        quotas: PermissionQuotasConfig {
+          net: BTreeMap::from([
+            ("*".to_string(), 100),
+            ("api.example.com".to_string(), 10),
+          ]),
+          write: Default::default(),
+          run: BTreeMap::from([("git".to_string(), 2)]),
+        },
+        ..Default::default()
+      }
+    );
   }
 }
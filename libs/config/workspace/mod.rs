@@ -65,6 +65,7 @@ use crate::deno_json::NodeModulesLinkerParseError;
 use crate::deno_json::PermissionsConfig;
 use crate::deno_json::PermissionsObjectWithBase;
 use crate::deno_json::PublishConfig;
+use crate::deno_json::TaskCacheConfig;
 pub use crate::deno_json::TaskDefinition;
 use crate::deno_json::TestConfig;
 use crate::deno_json::ToInvalidConfigError;
@@ -1291,6 +1292,12 @@ impl Workspace {
           kind: WorkspaceDiagnosticKind::RootOnlyOption("allowScripts"),
         });
       }
+      if member_config.json.task_cache.is_some() {
+        diagnostics.push(WorkspaceDiagnostic {
+          config_url: member_config.specifier.clone(),
+          kind: WorkspaceDiagnosticKind::RootOnlyOption("taskCache"),
+        });
+      }
       if member_config.json.prefer_package_json.is_some() {
         diagnostics.push(WorkspaceDiagnostic {
           config_url: member_config.specifier.clone(),
@@ -1851,6 +1858,16 @@ impl Workspace {
       .transpose()
       .map(|v| v.unwrap_or_default())
   }
+
+  pub fn task_cache(
+    &self,
+  ) -> Result<Option<TaskCacheConfig>, ToInvalidConfigError> {
+    self
+      .root_deno_json()
+      .map(|c| c.to_task_cache_config())
+      .transpose()
+      .map(|v| v.flatten())
+  }
 }
 
 #[derive(Debug, Clone)]
@@ -163,6 +163,11 @@ pub enum CoreErrorKind {
   )]
   PendingPromiseResolution,
   #[class(generic)]
+  #[error(
+    "Timers are pending on the virtual clock, but nothing is left to advance it"
+  )]
+  PendingVirtualTimers,
+  #[class(generic)]
   #[error(
     "Module evaluation is still pending after multiple event loop iterations, \
      but no stalled top-level await was found. This is a bug in Deno."
@@ -511,6 +516,10 @@ pub struct JsError {
   pub stack_is_custom: bool,
 }
 
+/// Number of frames captured for ops with `stack_trace`. A stack with this
+/// many frames may have been cut off.
+pub const OP_STACK_TRACE_LIMIT: usize = 256;
+
 const MAX_ERROR_CONVERSION_DEPTH: usize = 32;
 const MAX_ERROR_CONVERSION_NODES: usize = 256;
 
@@ -831,6 +840,61 @@ impl JsStackFrame {
     })
   }
 
+  /// Captures up to `limit` frames of the current stack, innermost first,
+  /// with source maps applied.
+  ///
+  /// Unlike the frames of an error, these don't depend on
+  /// `Error.stackTraceLimit` or `Error.prepareStackTrace`, which user code
+  /// can change.
+  pub fn current_stack<'s, 'i>(
+    scope: &mut v8::PinScope<'s, 'i>,
+    limit: usize,
+  ) -> Vec<Self> {
+    let Some(stack_trace) = v8::StackTrace::current_stack_trace(scope, limit)
+    else {
+      return Vec::new();
+    };
+    let frame_count = stack_trace.get_frame_count();
+    let mut frames = Vec::with_capacity(frame_count);
+    for i in 0..frame_count {
+      let Some(frame) = stack_trace.get_frame(scope, i) else {
+        continue;
+      };
+      let function_name = frame
+        .get_function_name(scope)
+        .map(|name| name.to_rust_string_lossy(scope))
+        .filter(|name| !name.is_empty());
+      let file_name = frame
+        .get_script_name(scope)
+        .map(|name| name.to_rust_string_lossy(scope));
+      let is_wasm = frame.is_wasm();
+      let line_number = frame.get_line_number() as i64;
+      let column_number = frame.get_column() as i64;
+      let (file_name, line_number, column_number) = match file_name {
+        Some(file_name) if !is_wasm => {
+          let state = JsRuntime::state_from(scope);
+          let mut source_mapper = state.source_mapper.borrow_mut();
+          let (file_name, line_number, column_number) = apply_source_map(
+            &mut source_mapper,
+            file_name.into(),
+            line_number,
+            column_number,
+          );
+          (Some(file_name.into_owned()), line_number, column_number)
+        }
+        file_name => (file_name, line_number, column_number),
+      };
+      frames.push(Self {
+        function_name,
+        is_eval: frame.is_eval(),
+        is_constructor: frame.is_constructor(),
+        is_wasm,
+        ..Self::from_location(file_name, Some(line_number), Some(column_number))
+      });
+    }
+    frames
+  }
+
   /// Gets the source mapped stack frame corresponding to the
   /// (script_resource_name, line_number, column_number) from a v8 message.
   /// For non-syntax errors, it should also correspond to the first stack frame.
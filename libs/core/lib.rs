@@ -44,6 +44,7 @@ mod tasks;
   reason = "generated bindings match external API naming"
 )]
 pub mod uv_compat;
+mod virtual_clock;
 mod web_timeout;
 pub mod webidl;
 
@@ -206,6 +207,9 @@ pub use crate::source_map::SourceMapData;
 pub use crate::source_map::SourceMapper;
 pub use crate::tasks::V8CrossThreadTaskSpawner;
 pub use crate::tasks::V8TaskSpawner;
+pub use crate::virtual_clock::VirtualClock;
+pub use crate::virtual_clock::VirtualSleep;
+pub use crate::virtual_clock::set_virtual_clock;
 
 // Ensure we can use op2 in deno_core without any hackery.
 extern crate self as deno_core;
@@ -230,6 +234,8 @@ pub mod _ops {
   pub use super::extensions::OpDecl;
   pub use super::extensions::OpMethodDecl;
   pub use super::ops::OpCtx;
+  pub use super::ops::OpStackTraceGuard;
+  pub use super::ops::op_stack_trace;
   #[cfg(debug_assertions)]
   pub use super::ops::reentrancy_check;
   pub use super::ops_metrics::OpMetricsEvent;
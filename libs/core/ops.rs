@@ -59,6 +59,41 @@ pub fn reentrancy_check(decl: &'static OpDecl) -> Option<ReentrancyGuard> {
   Some(ReentrancyGuard {})
 }
 
+/// Passes the current stack to the [`OpStackTraceCallback`] before an op
+/// with `stack_trace` runs.
+///
+/// The callback is called again with an empty stack when the returned guard
+/// is dropped after the op returns, so that work done outside of the op is
+/// never attributed to its stack.
+#[doc(hidden)]
+pub fn op_stack_trace<'a, 's, 'i>(
+  scope: &mut v8::PinScope<'s, 'i>,
+  state: &'a RefCell<OpState>,
+) -> OpStackTraceGuard<'a> {
+  let frames =
+    JsStackFrame::current_stack(scope, crate::error::OP_STACK_TRACE_LIMIT);
+  if let Some(callback) = &state.borrow().op_stack_trace_callback {
+    callback(frames);
+  }
+  OpStackTraceGuard { state }
+}
+
+#[doc(hidden)]
+pub struct OpStackTraceGuard<'a> {
+  state: &'a RefCell<OpState>,
+}
+
+impl Drop for OpStackTraceGuard<'_> {
+  fn drop(&mut self) {
+    // a reentrant op may still be borrowing the state
+    if let Ok(state) = self.state.try_borrow()
+      && let Some(callback) = &state.op_stack_trace_callback
+    {
+      callback(Vec::new());
+    }
+  }
+}
+
 #[derive(Clone, Copy)]
 pub struct OpMetadata {
   /// A description of the op for use in sanitizer output.
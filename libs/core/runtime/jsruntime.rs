@@ -2649,6 +2649,12 @@ impl JsRuntime {
         }
       }
 
+      if pending_state.has_pending_virtual_timers {
+        return Poll::Ready(Err(
+          CoreErrorKind::PendingVirtualTimers.into_box(),
+        ));
+      }
+
       return Poll::Ready(Ok(()));
     }
 
@@ -3028,6 +3034,7 @@ impl JsRuntimeForSnapshot {
 pub(crate) struct EventLoopPendingState {
   has_pending_ops: bool,
   has_pending_refed_ops: bool,
+  has_pending_virtual_timers: bool,
   has_pending_dyn_imports: bool,
   has_pending_dyn_module_evaluation: bool,
   has_pending_module_evaluation: bool,
@@ -3052,7 +3059,12 @@ impl EventLoopPendingState {
     let has_pending_tasks = state.task_spawner_factory.has_pending_tasks();
     // User timers: JS manages these; the timer handle is refed when
     // there are refed timers (timer_info[0] > 0).
-    let has_pending_refed_user_timers = state.user_timer.is_refed();
+    // Timers on a virtual clock only fire when something advances the
+    // clock, so on their own they can't keep the event loop alive.
+    let has_pending_virtual_timers =
+      state.user_timer.is_refed() && state.user_timer.is_virtual();
+    let has_pending_refed_user_timers =
+      state.user_timer.is_refed() && !has_pending_virtual_timers;
     let has_pending_dyn_imports = modules.has_pending_dynamic_imports();
     let has_pending_dyn_module_evaluation =
       modules.has_pending_dyn_module_evaluation();
@@ -3082,6 +3094,7 @@ impl EventLoopPendingState {
     EventLoopPendingState {
       has_pending_ops: has_pending_refed_ops || (num_pending_ops > 0),
       has_pending_refed_ops,
+      has_pending_virtual_timers,
       has_pending_dyn_imports,
       has_pending_dyn_module_evaluation,
       has_pending_module_evaluation,
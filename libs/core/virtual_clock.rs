@@ -0,0 +1,224 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! A manually advanced clock for deterministic tests.
+//!
+//! Once a [`VirtualClock`] is installed with [`set_virtual_clock`], the
+//! timers of a realm only fire when the clock is advanced past their
+//! deadline, and extensions that keep their own schedules (such as
+//! `Deno.cron`) can sleep on the same clock with [`VirtualClock::sleep_until`].
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+use crate::runtime::JsRealm;
+
+/// A clock that only moves when it is advanced. Clones share the same time.
+///
+/// Virtual time starts at the real time the clock was created and is
+/// expressed as the duration elapsed since then.
+#[derive(Clone)]
+pub struct VirtualClock(Rc<VirtualClockInner>);
+
+struct VirtualClockInner {
+  start_instant: Instant,
+  start_system: SystemTime,
+  elapsed: Cell<Duration>,
+  next_sleep_id: Cell<u64>,
+  /// Pending sleeps keyed by deadline, with the waker of their last poll.
+  sleeps: RefCell<BTreeMap<(Duration, u64), Option<Waker>>>,
+}
+
+impl Default for VirtualClock {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl VirtualClock {
+  pub fn new() -> Self {
+    Self(Rc::new(VirtualClockInner {
+      start_instant: Instant::now(),
+      start_system: SystemTime::now(),
+      elapsed: Cell::new(Duration::ZERO),
+      next_sleep_id: Cell::new(0),
+      sleeps: RefCell::new(BTreeMap::new()),
+    }))
+  }
+
+  /// Virtual time elapsed since the clock was created.
+  pub fn elapsed(&self) -> Duration {
+    self.0.elapsed.get()
+  }
+
+  /// The current virtual time as an [`Instant`].
+  pub fn now(&self) -> Instant {
+    self.0.start_instant + self.elapsed()
+  }
+
+  /// The current virtual wall-clock time.
+  pub fn now_system(&self) -> SystemTime {
+    self.0.start_system + self.elapsed()
+  }
+
+  /// Converts a wall-clock time to the virtual time elapsed at that point,
+  /// which is zero for times before the clock was created.
+  pub fn elapsed_at(&self, time: SystemTime) -> Duration {
+    time
+      .duration_since(self.0.start_system)
+      .unwrap_or(Duration::ZERO)
+  }
+
+  /// Moves the clock forward by `duration` and wakes the sleeps that are due.
+  pub fn advance(&self, duration: Duration) {
+    self.advance_to(self.elapsed() + duration);
+  }
+
+  /// Moves the clock forward to `elapsed` and wakes the sleeps that are due.
+  /// The clock never goes backwards.
+  pub fn advance_to(&self, elapsed: Duration) {
+    if elapsed <= self.elapsed() {
+      return;
+    }
+    self.0.elapsed.set(elapsed);
+    let wakers = self
+      .0
+      .sleeps
+      .borrow_mut()
+      .range_mut(..=(elapsed, u64::MAX))
+      .filter_map(|(_, waker)| waker.take())
+      .collect::<Vec<_>>();
+    for waker in wakers {
+      waker.wake();
+    }
+  }
+
+  /// The earliest deadline of the pending sleeps, including the ones that
+  /// are due but weren't polled since.
+  pub fn next_deadline(&self) -> Option<Duration> {
+    self
+      .0
+      .sleeps
+      .borrow()
+      .keys()
+      .next()
+      .map(|(deadline, _)| *deadline)
+  }
+
+  /// Returns a future that resolves once the clock reaches `deadline`, in
+  /// virtual time elapsed since the clock was created.
+  pub fn sleep_until(&self, deadline: Duration) -> VirtualSleep {
+    let id = self.0.next_sleep_id.get();
+    self.0.next_sleep_id.set(id + 1);
+    let key = (deadline, id);
+    self.0.sleeps.borrow_mut().insert(key, None);
+    VirtualSleep {
+      clock: self.clone(),
+      key,
+    }
+  }
+
+  /// Returns a future that resolves once `duration` of virtual time passed.
+  pub fn sleep(&self, duration: Duration) -> VirtualSleep {
+    self.sleep_until(self.elapsed() + duration)
+  }
+}
+
+/// A sleep on a [`VirtualClock`]. It stops counting as pending once it has
+/// resolved or is dropped.
+pub struct VirtualSleep {
+  clock: VirtualClock,
+  key: (Duration, u64),
+}
+
+impl VirtualSleep {
+  pub fn deadline(&self) -> Duration {
+    self.key.0
+  }
+}
+
+impl Future for VirtualSleep {
+  type Output = ();
+
+  fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+    let mut sleeps = self.clock.0.sleeps.borrow_mut();
+    if self.key.0 <= self.clock.elapsed() {
+      sleeps.remove(&self.key);
+      return Poll::Ready(());
+    }
+    if let Some(waker) = sleeps.get_mut(&self.key) {
+      *waker = Some(cx.waker().clone());
+    }
+    Poll::Pending
+  }
+}
+
+impl Drop for VirtualSleep {
+  fn drop(&mut self) {
+    self.clock.0.sleeps.borrow_mut().remove(&self.key);
+  }
+}
+
+/// Makes the timers of the realm of `scope` follow `clock` instead of real
+/// time. Timers that were already scheduled fire on the next turn of the
+/// event loop and are rescheduled on the virtual clock.
+pub fn set_virtual_clock(scope: &mut v8::PinScope, clock: VirtualClock) {
+  let context_state = JsRealm::state_from_scope(scope);
+  context_state.user_timer.set_virtual_clock(clock);
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::Arc;
+  use std::task::Wake;
+
+  use super::*;
+
+  struct CountingWaker(std::sync::atomic::AtomicUsize);
+
+  impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+      self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+  }
+
+  #[test]
+  fn sleeps_resolve_when_advanced() {
+    let clock = VirtualClock::new();
+    let counter = Arc::new(CountingWaker(Default::default()));
+    let waker = Waker::from(counter.clone());
+    let mut cx = Context::from_waker(&waker);
+
+    let mut later = clock.sleep(Duration::from_millis(100));
+    let mut sooner = clock.sleep(Duration::from_millis(10));
+    assert_eq!(clock.next_deadline(), Some(Duration::from_millis(10)));
+    assert!(Pin::new(&mut later).poll(&mut cx).is_pending());
+    assert!(Pin::new(&mut sooner).poll(&mut cx).is_pending());
+
+    clock.advance(Duration::from_millis(50));
+    assert_eq!(counter.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert!(Pin::new(&mut sooner).poll(&mut cx).is_ready());
+    assert!(Pin::new(&mut later).poll(&mut cx).is_pending());
+    assert_eq!(clock.next_deadline(), Some(Duration::from_millis(100)));
+
+    // Going backwards is ignored.
+    clock.advance_to(Duration::from_millis(20));
+    assert_eq!(clock.elapsed(), Duration::from_millis(50));
+
+    drop(later);
+    assert_eq!(clock.next_deadline(), None);
+    assert_eq!(
+      clock.now_system(),
+      clock.0.start_system + Duration::from_millis(50)
+    );
+  }
+}
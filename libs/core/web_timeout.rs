@@ -1,7 +1,9 @@
 // Copyright 2018-2026 the Deno authors. MIT license.
 
 use std::cell::Cell;
+use std::cell::RefCell;
 use std::cell::UnsafeCell;
+use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
@@ -17,6 +19,8 @@ use futures::task::AtomicWaker;
 use crate::reactor::Reactor;
 use crate::reactor::ReactorInstant;
 use crate::reactor::ReactorTimer;
+use crate::virtual_clock::VirtualClock;
+use crate::virtual_clock::VirtualSleep;
 
 struct MutableSleep<Tmr: ReactorTimer> {
   sleep: UnsafeCell<Option<Tmr>>,
@@ -63,6 +67,10 @@ impl<Tmr: ReactorTimer + 'static> MutableSleep<Tmr> {
     Poll::Pending
   }
 
+  fn is_scheduled(&self) -> bool {
+    unsafe { self.sleep.get().as_ref().unwrap_unchecked().is_some() }
+  }
+
   fn clear(&self) {
     unsafe {
       *self.sleep.get() = None;
@@ -130,6 +138,17 @@ pub(crate) struct UserTimer<R: Reactor> {
   base_instant: R::Instant,
   /// Whether the timer handle is "ref'd" (keeps event loop alive).
   refed: Cell<bool>,
+  /// Set in tests to follow a virtual clock instead of the reactor.
+  virtual_clock: RefCell<Option<VirtualTime>>,
+}
+
+struct VirtualTime {
+  clock: VirtualClock,
+  /// `now()` when the clock was installed, minus the clock's elapsed time
+  /// then, so that `now()` doesn't jump back. Rounded up to a whole
+  /// millisecond so that timers fire exactly when the clock reaches them.
+  base_ms: f64,
+  sleep: Option<VirtualSleep>,
 }
 
 impl<R: Reactor + Default> Default for UserTimer<R> {
@@ -145,28 +164,67 @@ impl<R: Reactor> UserTimer<R> {
       sleep: MutableSleep::new(),
       reactor,
       refed: Cell::new(false),
+      virtual_clock: RefCell::new(None),
     }
   }
 
+  /// Follow `clock` instead of real time from now on. A pending wakeup is
+  /// moved to the current virtual time, so that the JS side reschedules it
+  /// on the clock.
+  pub fn set_virtual_clock(&self, clock: VirtualClock) {
+    let base_ms = (self.now() - clock.elapsed().as_secs_f64() * 1000.0).ceil();
+    let sleep = self
+      .sleep
+      .is_scheduled()
+      .then(|| clock.sleep_until(clock.elapsed()));
+    self.sleep.clear();
+    *self.virtual_clock.borrow_mut() = Some(VirtualTime {
+      clock,
+      base_ms,
+      sleep,
+    });
+  }
+
   /// Schedule a wakeup after `delay` from now.
   pub fn schedule(&self, delay: Duration) {
+    if let Some(virtual_time) = &mut *self.virtual_clock.borrow_mut() {
+      virtual_time.sleep = Some(virtual_time.clock.sleep(delay));
+      return;
+    }
     let deadline = self.reactor.now().checked_add(delay).unwrap();
     self.sleep.change(self.reactor.timer(deadline));
   }
 
   /// Cancel any pending wakeup.
   pub fn clear(&self) {
+    if let Some(virtual_time) = &mut *self.virtual_clock.borrow_mut() {
+      virtual_time.sleep = None;
+    }
     self.sleep.clear();
   }
 
   /// Poll for the scheduled wakeup.
   pub fn poll_ready(&self, cx: &mut Context) -> Poll<()> {
+    if let Some(virtual_time) = &mut *self.virtual_clock.borrow_mut() {
+      let Some(sleep) = &mut virtual_time.sleep else {
+        return Poll::Pending;
+      };
+      if Pin::new(sleep).poll(cx).is_pending() {
+        return Poll::Pending;
+      }
+      virtual_time.sleep = None;
+      return Poll::Ready(());
+    }
     self.sleep.poll_ready(cx)
   }
 
   /// Get the current monotonic time in milliseconds since this timer
   /// was created (process start).
   pub fn now(&self) -> f64 {
+    if let Some(virtual_time) = &*self.virtual_clock.borrow() {
+      return virtual_time.base_ms
+        + virtual_time.clock.elapsed().as_secs_f64() * 1000.0;
+    }
     self.base_instant.elapsed().as_secs_f64() * 1000.0
   }
 
@@ -184,6 +242,12 @@ impl<R: Reactor> UserTimer<R> {
   pub fn is_refed(&self) -> bool {
     self.refed.get()
   }
+
+  /// Whether the timer follows a virtual clock, which only moves when it is
+  /// advanced explicitly.
+  pub fn is_virtual(&self) -> bool {
+    self.virtual_clock.borrow().is_some()
+  }
 }
 
 #[cfg(all(test, feature = "reactor-tokio"))]
@@ -3251,6 +3251,7 @@ const DENO_SUBCOMMANDS: &[&str] = &[
   "completions",
   "coverage",
   "create",
+  "cron",
   "deploy",
   "desktop",
   "doc",
@@ -3263,12 +3264,14 @@ const DENO_SUBCOMMANDS: &[&str] = &[
   "install",
   "json_reference",
   "jupyter",
+  "kv",
   "link",
   "lint",
   "list",
   "lsp",
   "outdated",
   "pack",
+  "permissions",
   "publish",
   "remove",
   "repl",
@@ -166,6 +166,13 @@ pub static FEATURE_DESCRIPTIONS: &[UnstableFeatureDescription] = &[
     kind: UnstableFeatureKind::Runtime,
     env_var: None,
   },
+  UnstableFeatureDescription {
+    name: "test-clock",
+    help_text: "Enable unstable `Deno.test.clock` API",
+    show_in_help: false,
+    kind: UnstableFeatureKind::Runtime,
+    env_var: None,
+  },
   UnstableFeatureDescription {
     name: "unsafe-proto",
     help_text: "Enable unsafe __proto__ support. This is a security risk.",
@@ -82,6 +82,41 @@ class TimedOut extends Error {
   }
 }
 
+class ConnectTimeout extends TimedOut {
+  constructor(msg, opts) {
+    super(msg, opts);
+    this.name = "ConnectTimeout";
+  }
+}
+
+class TlsHandshakeTimeout extends TimedOut {
+  constructor(msg, opts) {
+    super(msg, opts);
+    this.name = "TlsHandshakeTimeout";
+  }
+}
+
+class ResponseHeadersTimeout extends TimedOut {
+  constructor(msg, opts) {
+    super(msg, opts);
+    this.name = "ResponseHeadersTimeout";
+  }
+}
+
+class IdleReadTimeout extends TimedOut {
+  constructor(msg, opts) {
+    super(msg, opts);
+    this.name = "IdleReadTimeout";
+  }
+}
+
+class TotalTimeout extends TimedOut {
+  constructor(msg, opts) {
+    super(msg, opts);
+    this.name = "TotalTimeout";
+  }
+}
+
 class WriteZero extends Error {
   constructor(msg, opts) {
     super(msg, opts);
@@ -172,6 +207,11 @@ const errors = {
   AlreadyExists,
   InvalidData,
   TimedOut,
+  ConnectTimeout,
+  TlsHandshakeTimeout,
+  ResponseHeadersTimeout,
+  IdleReadTimeout,
+  TotalTimeout,
   Interrupted,
   WriteZero,
   WouldBlock,
@@ -313,6 +313,10 @@ const windowOrWorkerGlobalScope = {
     (c) => c.Cache,
     lazyCache,
   ),
+  StorageManager: core.propNonEnumerableLazyLoaded(
+    (c) => c.StorageManager,
+    lazyCache,
+  ),
   console: core.propNonEnumerable(
     new console.Console((msg, level) => core.print(msg, level > 1)),
   ),
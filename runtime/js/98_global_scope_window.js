@@ -21,6 +21,10 @@ const globalInterfaces = core.loadExtScript(
   "ext:deno_web/04_global_interfaces.js",
 );
 const loadLocks = core.createLazyLoader("ext:deno_web/locks.js");
+let _cacheMod;
+const lazyCache = () =>
+  _cacheMod ??
+    (_cacheMod = core.loadExtScript("ext:deno_cache/01_cache.js"));
 import {
   NavigatorUAData,
   navigatorUAData,
@@ -192,6 +196,15 @@ ObjectDefineProperties(Navigator.prototype, {
       return platform();
     },
   },
+  storage: {
+    __proto__: null,
+    configurable: true,
+    enumerable: true,
+    get() {
+      webidl.assertBranded(this, NavigatorPrototype);
+      return lazyCache().storageManager();
+    },
+  },
   userAgentData: {
     __proto__: null,
     configurable: true,
@@ -457,6 +457,11 @@ core.registerErrorClass("PermissionDenied", errors.PermissionDenied);
 core.registerErrorClass("AlreadyExists", errors.AlreadyExists);
 core.registerErrorClass("InvalidData", errors.InvalidData);
 core.registerErrorClass("TimedOut", errors.TimedOut);
+core.registerErrorClass("ConnectTimeout", errors.ConnectTimeout);
+core.registerErrorClass("TlsHandshakeTimeout", errors.TlsHandshakeTimeout);
+core.registerErrorClass("ResponseHeadersTimeout", errors.ResponseHeadersTimeout);
+core.registerErrorClass("IdleReadTimeout", errors.IdleReadTimeout);
+core.registerErrorClass("TotalTimeout", errors.TotalTimeout);
 core.registerErrorClass("WouldBlock", errors.WouldBlock);
 core.registerErrorClass("WriteZero", errors.WriteZero);
 core.registerErrorClass("UnexpectedEof", errors.UnexpectedEof);
@@ -0,0 +1,303 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! Permission audit log (`DENO_AUDIT_PERMISSIONS`).
+//!
+//! Every permission check is recorded with its outcome as a versioned JSON
+//! object (see `cli/schemas/permission-audit.v2.json`), either as a line of a
+//! JSONL file or as an OpenTelemetry log event with the same attributes.
+
+use std::cell::Cell;
+use std::io::Write;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::prompter::CURRENT_STACK_FRAMES;
+use crate::prompter::MAYBE_CURRENT_STACKTRACE;
+
+/// Version of the audit records, written as `v`.
+pub const AUDIT_VERSION: u32 = 2;
+
+pub type OtelAuditFn = fn(record: &AuditRecord);
+
+pub enum AuditSink {
+  File(Mutex<std::fs::File>),
+  Otel(OtelAuditFn),
+}
+
+pub static AUDIT_SINK: OnceLock<AuditSink> = OnceLock::new();
+
+static MONOTONIC_START: Lazy<Instant> = Lazy::new(Instant::now);
+
+thread_local! {
+  static CURRENT_WORKER_ID: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Sets the id of the worker running on the current thread, which is
+/// recorded as `workerId`. The main worker is `0`.
+pub fn set_current_worker_id(worker_id: u32) {
+  CURRENT_WORKER_ID.with(|id| id.set(worker_id));
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditDecision {
+  Granted,
+  Denied,
+}
+
+impl AuditDecision {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AuditDecision::Granted => "granted",
+      AuditDecision::Denied => "denied",
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditDecidedBy {
+  /// An allow, deny or ignore rule from the flags or the config. Access
+  /// granted at a prompt is also remembered as an allow rule.
+  Rule,
+  /// The user answered a prompt, now or for an earlier check.
+  Prompt,
+  /// The permission broker.
+  Broker,
+  /// Nothing matched and prompting wasn't possible.
+  Default,
+}
+
+impl AuditDecidedBy {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      AuditDecidedBy::Rule => "rule",
+      AuditDecidedBy::Prompt => "prompt",
+      AuditDecidedBy::Broker => "broker",
+      AuditDecidedBy::Default => "default",
+    }
+  }
+}
+
+/// A single record of the audit log.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+  pub v: u32,
+  pub datetime: String,
+  /// Milliseconds since the first record, on a monotonic clock.
+  pub monotonic_time: f64,
+  pub worker_id: u32,
+  pub permission: String,
+  pub value: Option<String>,
+  pub decision: AuditDecision,
+  pub decided_by: AuditDecidedBy,
+  /// The rule that matched, written as a flag (ex. `--allow-read=/tmp`).
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub rule: Option<String>,
+  /// The innermost stack frame that isn't runtime code, when stack traces
+  /// are captured for ops.
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub caller: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub stack: Option<Vec<String>>,
+}
+
+impl AuditRecord {
+  /// The record as the attributes of an OpenTelemetry log event.
+  pub fn otel_attributes(&self) -> Vec<(&'static str, String)> {
+    let mut attributes = vec![
+      ("deno.permission.v", self.v.to_string()),
+      ("deno.permission.datetime", self.datetime.clone()),
+      (
+        "deno.permission.monotonic_time",
+        self.monotonic_time.to_string(),
+      ),
+      ("deno.permission.worker_id", self.worker_id.to_string()),
+      ("deno.permission.type", self.permission.clone()),
+      (
+        "deno.permission.value",
+        self.value.clone().unwrap_or_default(),
+      ),
+      (
+        "deno.permission.decision",
+        self.decision.as_str().to_string(),
+      ),
+      (
+        "deno.permission.decided_by",
+        self.decided_by.as_str().to_string(),
+      ),
+    ];
+    if let Some(rule) = &self.rule {
+      attributes.push(("deno.permission.rule", rule.clone()));
+    }
+    if let Some(caller) = &self.caller {
+      attributes.push(("deno.permission.caller", caller.clone()));
+    }
+    if let Some(stack) = &self.stack {
+      attributes.push(("deno.permission.stack", stack.join("\n")));
+    }
+    attributes
+  }
+}
+
+#[inline(always)]
+pub(crate) fn is_audit_enabled() -> bool {
+  AUDIT_SINK.get().is_some()
+}
+
+/// Records access that was granted because the whole permission is granted.
+pub(crate) fn write_audit<T>(flag_name: &str, value: T)
+where
+  T: Serialize,
+{
+  if !is_audit_enabled() {
+    return;
+  }
+  write_audit_record(
+    flag_name,
+    audit_value(value),
+    AuditDecision::Granted,
+    AuditDecidedBy::Rule,
+    Some(format!("--allow-{flag_name}")),
+  );
+}
+
+pub(crate) fn write_audit_record(
+  flag_name: &str,
+  value: Option<String>,
+  decision: AuditDecision,
+  decided_by: AuditDecidedBy,
+  rule: Option<String>,
+) {
+  let Some(sink) = AUDIT_SINK.get() else {
+    return;
+  };
+
+  let stack = {
+    let get_stack = MAYBE_CURRENT_STACKTRACE.lock();
+    get_stack.as_ref().map(|s| s())
+  };
+  let record = AuditRecord {
+    v: AUDIT_VERSION,
+    #[allow(
+      clippy::disallowed_methods,
+      reason = "TODO: support passing in a sys here"
+    )]
+    datetime: chrono::Utc::now()
+      .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+    monotonic_time: MONOTONIC_START.elapsed().as_secs_f64() * 1000.0,
+    worker_id: CURRENT_WORKER_ID.with(|id| id.get()),
+    permission: flag_name.to_string(),
+    value,
+    decision,
+    decided_by,
+    rule,
+    caller: current_caller(),
+    stack,
+  };
+
+  match sink {
+    AuditSink::File(file) => {
+      let mut line = serde_json::to_string(&record).unwrap();
+      line.push('\n');
+      let _ = file.lock().write_all(line.as_bytes());
+    }
+    AuditSink::Otel(report_fn) => report_fn(&record),
+  }
+}
+
+fn audit_value<T: Serialize>(value: T) -> Option<String> {
+  match serde_json::to_value(value) {
+    Ok(serde_json::Value::Null) | Err(_) => None,
+    Ok(serde_json::Value::String(s)) => Some(s),
+    Ok(other) => Some(other.to_string()),
+  }
+}
+
+fn current_caller() -> Option<String> {
+  CURRENT_STACK_FRAMES.with(|frames| {
+    let frames = frames.borrow();
+    let frame = frames.iter().find(|frame| {
+      !frame.file_name.starts_with("ext:")
+        && !frame.file_name.starts_with("node:")
+    })?;
+    Some(match (frame.line_number, frame.column_number) {
+      (Some(line), Some(column)) => {
+        format!("{}:{}:{}", frame.file_name, line, column)
+      }
+      (Some(line), None) => format!("{}:{}", frame.file_name, line),
+      _ => frame.file_name.clone(),
+    })
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::prompter::StackFrameLocation;
+  use crate::prompter::clear_current_stacktrace;
+  use crate::prompter::set_current_stacktrace;
+
+  #[test]
+  fn caller_skips_runtime_frames() {
+    set_current_stacktrace(
+      Box::new(Vec::new),
+      vec![
+        StackFrameLocation {
+          file_name: "ext:deno_fs/30_fs.js".to_string(),
+          line_number: Some(10),
+          column_number: Some(3),
+        },
+        StackFrameLocation {
+          file_name: "node:fs".to_string(),
+          line_number: Some(20),
+          column_number: Some(5),
+        },
+        StackFrameLocation {
+          file_name: "file:///app/main.ts".to_string(),
+          line_number: Some(4),
+          column_number: Some(1),
+        },
+      ],
+      false,
+    );
+    assert_eq!(current_caller().unwrap(), "file:///app/main.ts:4:1");
+
+    clear_current_stacktrace();
+    assert_eq!(current_caller(), None);
+  }
+
+  #[test]
+  fn record_serialization() {
+    let record = AuditRecord {
+      v: AUDIT_VERSION,
+      datetime: "2026-01-01T00:00:00.000Z".to_string(),
+      monotonic_time: 1.5,
+      worker_id: 1,
+      permission: "net".to_string(),
+      value: Some("example.com:443".to_string()),
+      decision: AuditDecision::Denied,
+      decided_by: AuditDecidedBy::Rule,
+      rule: Some("--deny-net=example.com".to_string()),
+      caller: None,
+      stack: None,
+    };
+    assert_eq!(
+      serde_json::to_string(&record).unwrap(),
+      r#"{"v":2,"datetime":"2026-01-01T00:00:00.000Z","monotonicTime":1.5,"workerId":1,"permission":"net","value":"example.com:443","decision":"denied","decidedBy":"rule","rule":"--deny-net=example.com"}"#
+    );
+    let attributes = record.otel_attributes();
+    assert!(
+      attributes.contains(&("deno.permission.decision", "denied".to_string()))
+    );
+    assert!(attributes.contains(&(
+      "deno.permission.rule",
+      "--deny-net=example.com".to_string()
+    )));
+  }
+}
@@ -1,12 +1,32 @@
 // Copyright 2018-2026 the Deno authors. MIT license.
 
+//! Permission brokers decide permission checks instead of the flags and
+//! prompts.
+//!
+//! Embedders can implement [`PermissionBroker`] and install it on a worker
+//! with `WorkerOptions::permission_broker` or
+//! `WebWorkerOptions::permission_broker`, which sets it on the worker's
+//! [`PermissionsContainer`](crate::PermissionsContainer) and every clone of
+//! it, including the clones made before the broker was set. Web workers
+//! inherit the broker of their parent unless they are given their own. A broker
+//! installed with [`set_broker`] applies to the whole process, which is how
+//! the CLI connects to an external broker over `DENO_PERMISSION_BROKER_PATH`
+//! (see [`IpcPermissionBroker`]).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::future::Future;
 use std::io::BufRead;
 use std::io::BufReader;
 use std::io::Write;
 use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
 use std::sync::OnceLock;
 use std::sync::atomic::AtomicU32;
 
+use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 
 use super::BrokerResponse;
@@ -16,16 +36,202 @@ use crate::ipc_pipe::IpcPipe;
 // be documented
 static BROKER_EXIT_CODE: i32 = 87;
 
-static PERMISSION_BROKER: OnceLock<PermissionBroker> = OnceLock::new();
+static PERMISSION_BROKER: OnceLock<Arc<CachingBroker>> = OnceLock::new();
 static PID: OnceLock<u32> = OnceLock::new();
 
-pub fn set_broker(broker: PermissionBroker) {
-  assert!(PERMISSION_BROKER.set(broker).is_ok());
-  assert!(PID.set(std::process::id()).is_ok());
+thread_local! {
+  /// The broker of the permissions container making a check on the current
+  /// thread, which takes precedence over the process wide one. Only set for
+  /// the duration of the check, see [`enter`].
+  static CURRENT_BROKER: RefCell<Option<Arc<CachingBroker>>> =
+    const { RefCell::new(None) };
+}
+
+/// Runs the futures of brokers, so they never run on the event loop of the
+/// worker waiting for their decision.
+static BROKER_RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
+  tokio::runtime::Builder::new_multi_thread()
+    .worker_threads(1)
+    .thread_name("permission-broker")
+    .enable_all()
+    .build()
+    .expect("failed to create the permission broker runtime")
+});
+
+pub type BrokerDecisionFuture =
+  Pin<Box<dyn Future<Output = BrokerDecision> + Send>>;
+
+/// A permission check sent to a broker.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BrokerRequest {
+  /// The name of the permission (ex. `read` or `net`).
+  pub permission: &'static str,
+  /// The value being checked, or `None` when the whole permission is.
+  pub value: Option<String>,
+}
+
+/// How long a decision of a broker may be reused for.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BrokerCacheHint {
+  /// Ask the broker again for every check.
+  #[default]
+  None,
+  /// Reuse the decision for checks of the same permission and value.
+  Value,
+  /// Reuse the decision for every check of the permission.
+  Permission,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BrokerDecision {
+  pub response: BrokerResponse,
+  pub cache: BrokerCacheHint,
+}
+
+impl From<BrokerResponse> for BrokerDecision {
+  fn from(response: BrokerResponse) -> Self {
+    Self {
+      response,
+      cache: BrokerCacheHint::None,
+    }
+  }
+}
+
+/// Decides permission checks instead of the flags and prompts.
+///
+/// Permission checks are synchronous, so the worker making the check is
+/// blocked until the returned future completes. The future is run on a
+/// dedicated Tokio runtime, so it may use Tokio, but it must not wait on the
+/// event loop of the worker making the check.
+///
+/// Errors are up to the broker to handle, usually by denying access.
+pub trait PermissionBroker: Send + Sync {
+  fn check(&self, request: BrokerRequest) -> BrokerDecisionFuture;
+}
+
+/// Number of decisions for a single value kept by [`CachingBroker`]. The
+/// oldest decision is forgotten first.
+const MAX_CACHED_VALUES: usize = 1024;
+
+type ValueKey = (&'static str, Option<String>);
+
+#[derive(Default)]
+struct ValueCache {
+  responses: HashMap<ValueKey, BrokerResponse>,
+  /// Keys of `responses` in insertion order.
+  order: VecDeque<ValueKey>,
+}
+
+impl ValueCache {
+  fn insert(&mut self, key: ValueKey, response: BrokerResponse) {
+    if self.responses.insert(key.clone(), response).is_some() {
+      return;
+    }
+    self.order.push_back(key);
+    if self.order.len() > MAX_CACHED_VALUES
+      && let Some(oldest) = self.order.pop_front()
+    {
+      self.responses.remove(&oldest);
+    }
+  }
+}
+
+/// Wraps a broker to remember the decisions it allows to be reused.
+pub(crate) struct CachingBroker {
+  inner: Arc<dyn PermissionBroker>,
+  by_permission: Mutex<HashMap<&'static str, BrokerResponse>>,
+  by_value: Mutex<ValueCache>,
+}
+
+impl CachingBroker {
+  pub(crate) fn new(inner: Arc<dyn PermissionBroker>) -> Self {
+    Self {
+      inner,
+      by_permission: Default::default(),
+      by_value: Default::default(),
+    }
+  }
+
+  fn check(&self, request: BrokerRequest) -> BrokerResponse {
+    if let Some(response) = self.by_permission.lock().get(request.permission) {
+      return response.clone();
+    }
+    let key = (request.permission, request.value.clone());
+    if let Some(response) = self.by_value.lock().responses.get(&key) {
+      return response.clone();
+    }
+    let decision = wait_for_decision(self.inner.check(request));
+    match decision.cache {
+      BrokerCacheHint::None => {}
+      BrokerCacheHint::Value => {
+        self.by_value.lock().insert(key, decision.response.clone());
+      }
+      BrokerCacheHint::Permission => {
+        self
+          .by_permission
+          .lock()
+          .insert(key.0, decision.response.clone());
+      }
+    }
+    decision.response
+  }
+}
+
+fn wait_for_decision(future: BrokerDecisionFuture) -> BrokerDecision {
+  let (tx, rx) = std::sync::mpsc::sync_channel(1);
+  BROKER_RUNTIME.spawn(async move {
+    let _ = tx.send(future.await);
+  });
+  rx.recv().unwrap_or_else(|_| {
+    BrokerResponse::Deny {
+      message: Some("The permission broker failed to decide".to_string()),
+    }
+    .into()
+  })
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("A process wide permission broker is already set")]
+pub struct BrokerAlreadySetError;
+
+/// Sets the broker used by every worker that doesn't have its own. This can
+/// only be done once per process.
+pub fn set_broker(
+  broker: Arc<dyn PermissionBroker>,
+) -> Result<(), BrokerAlreadySetError> {
+  PERMISSION_BROKER
+    .set(Arc::new(CachingBroker::new(broker)))
+    .map_err(|_| BrokerAlreadySetError)
+}
+
+/// Makes `broker` decide the checks made on the current thread until the
+/// returned scope is dropped.
+pub(crate) fn enter(broker: Option<Arc<CachingBroker>>) -> BrokerScope {
+  BrokerScope {
+    previous: CURRENT_BROKER.with(|current| current.replace(broker)),
+  }
+}
+
+pub(crate) struct BrokerScope {
+  previous: Option<Arc<CachingBroker>>,
+}
+
+impl Drop for BrokerScope {
+  fn drop(&mut self) {
+    let previous = self.previous.take();
+    CURRENT_BROKER.with(|current| *current.borrow_mut() = previous);
+  }
+}
+
+fn current_broker() -> Option<Arc<CachingBroker>> {
+  CURRENT_BROKER
+    .with(|current| current.borrow().clone())
+    .or_else(|| PERMISSION_BROKER.get().cloned())
 }
 
 pub fn has_broker() -> bool {
-  PERMISSION_BROKER.get().is_some()
+  CURRENT_BROKER.with(|current| current.borrow().is_some())
+    || PERMISSION_BROKER.get().is_some()
 }
 
 #[derive(serde::Serialize)]
@@ -45,15 +251,20 @@ struct PermissionBrokerResponse {
   id: u32,
   result: String,
   reason: Option<String>,
+  #[serde(default)]
+  cache: Option<String>,
 }
 
-pub struct PermissionBroker {
+/// A broker running in another process, spoken to over a socket with the
+/// `permission-broker-request.v1` protocol.
+pub struct IpcPermissionBroker {
   stream: Mutex<IpcPipe>,
   next_id: AtomicU32,
 }
 
-impl PermissionBroker {
+impl IpcPermissionBroker {
   pub fn new(socket_path: impl Into<PathBuf>) -> Self {
+    PID.get_or_init(std::process::id);
     let socket_path = socket_path.into();
     let stream = match IpcPipe::connect(&socket_path) {
       Ok(s) => s,
@@ -68,11 +279,11 @@ impl PermissionBroker {
     }
   }
 
-  fn check(
+  fn send(
     &self,
     permission: &str,
     stringified_value: Option<String>,
-  ) -> std::io::Result<BrokerResponse> {
+  ) -> std::io::Result<BrokerDecision> {
     let mut stream = self.stream.lock();
     let id = self
       .next_id
@@ -108,6 +319,16 @@ impl PermissionBroker {
       ));
     }
 
+    let cache = match response.cache.as_deref() {
+      None | Some("none") => BrokerCacheHint::None,
+      Some("value") => BrokerCacheHint::Value,
+      Some("permission") => BrokerCacheHint::Permission,
+      Some(_) => {
+        return Err(std::io::Error::other(
+          "Permission broker unknown cache variant",
+        ));
+      }
+    };
     let prompt_response = match response.result.as_str() {
       "allow" => BrokerResponse::Allow,
       "deny" => BrokerResponse::Deny {
@@ -120,22 +341,155 @@ impl PermissionBroker {
       }
     };
 
-    Ok(prompt_response)
+    Ok(BrokerDecision {
+      response: prompt_response,
+      cache,
+    })
+  }
+}
+
+impl PermissionBroker for IpcPermissionBroker {
+  fn check(&self, request: BrokerRequest) -> BrokerDecisionFuture {
+    let decision = match self.send(request.permission, request.value) {
+      Ok(decision) => decision,
+      Err(err) => {
+        log::error!("{:?}", err);
+        std::process::exit(BROKER_EXIT_CODE);
+      }
+    };
+    Box::pin(std::future::ready(decision))
   }
 }
 
 pub fn maybe_check_with_broker(
-  name: &str,
+  name: &'static str,
   stringified_value_fn: impl Fn() -> Option<String>,
 ) -> Option<BrokerResponse> {
-  let broker = PERMISSION_BROKER.get()?;
+  let broker = current_broker()?;
+  Some(broker.check(BrokerRequest {
+    permission: name,
+    value: stringified_value_fn(),
+  }))
+}
+
+#[cfg(test)]
+mod test {
+  use std::sync::atomic::Ordering;
+
+  use super::*;
+
+  struct TestBroker {
+    calls: Arc<AtomicU32>,
+    cache: BrokerCacheHint,
+  }
 
-  let resp = match broker.check(name, stringified_value_fn()) {
-    Ok(resp) => resp,
-    Err(err) => {
-      log::error!("{:?}", err);
-      std::process::exit(BROKER_EXIT_CODE);
+  impl PermissionBroker for TestBroker {
+    fn check(&self, request: BrokerRequest) -> BrokerDecisionFuture {
+      self.calls.fetch_add(1, Ordering::SeqCst);
+      let cache = self.cache;
+      Box::pin(async move {
+        let response = if request.value.as_deref() == Some("/allowed") {
+          BrokerResponse::Allow
+        } else {
+          BrokerResponse::Deny {
+            message: Some(format!("{} denied", request.permission)),
+          }
+        };
+        BrokerDecision { response, cache }
+      })
     }
-  };
-  Some(resp)
+  }
+
+  fn caching(broker: TestBroker) -> Option<Arc<CachingBroker>> {
+    Some(Arc::new(CachingBroker::new(Arc::new(broker))))
+  }
+
+  #[test]
+  fn current_broker_decisions_are_cached() {
+    let check = |value: &str| {
+      maybe_check_with_broker("read", || Some(value.to_string())).unwrap()
+    };
+    let denied = BrokerResponse::Deny {
+      message: Some("read denied".to_string()),
+    };
+
+    let calls = Arc::new(AtomicU32::new(0));
+    {
+      let _scope = enter(caching(TestBroker {
+        calls: calls.clone(),
+        cache: BrokerCacheHint::None,
+      }));
+      assert!(has_broker());
+      assert_eq!(check("/allowed"), BrokerResponse::Allow);
+      assert_eq!(check("/allowed"), BrokerResponse::Allow);
+      assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    let calls = Arc::new(AtomicU32::new(0));
+    {
+      let _scope = enter(caching(TestBroker {
+        calls: calls.clone(),
+        cache: BrokerCacheHint::Value,
+      }));
+      assert_eq!(check("/allowed"), BrokerResponse::Allow);
+      assert_eq!(check("/allowed"), BrokerResponse::Allow);
+      assert_eq!(check("/other"), denied);
+      assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    let calls = Arc::new(AtomicU32::new(0));
+    {
+      let _scope = enter(caching(TestBroker {
+        calls: calls.clone(),
+        cache: BrokerCacheHint::Permission,
+      }));
+      assert_eq!(check("/other"), denied);
+      assert_eq!(check("/allowed"), denied);
+      assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    assert!(!has_broker());
+    assert_eq!(maybe_check_with_broker("read", || None), None);
+  }
+
+  #[test]
+  fn value_cache_is_bounded() {
+    let mut cache = ValueCache::default();
+    for i in 0..MAX_CACHED_VALUES + 10 {
+      cache.insert(("read", Some(i.to_string())), BrokerResponse::Allow);
+    }
+    assert_eq!(cache.responses.len(), MAX_CACHED_VALUES);
+    assert_eq!(cache.order.len(), MAX_CACHED_VALUES);
+    assert!(
+      !cache
+        .responses
+        .contains_key(&("read", Some("0".to_string())))
+    );
+    assert!(
+      cache
+        .responses
+        .contains_key(&("read", Some((MAX_CACHED_VALUES + 9).to_string())))
+    );
+  }
+
+  struct TimerBroker;
+
+  impl PermissionBroker for TimerBroker {
+    fn check(&self, _request: BrokerRequest) -> BrokerDecisionFuture {
+      Box::pin(async {
+        tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+        BrokerResponse::Allow.into()
+      })
+    }
+  }
+
+  #[tokio::test(flavor = "current_thread")]
+  async fn broker_does_not_block_on_the_calling_runtime() {
+    let _scope =
+      enter(Some(Arc::new(CachingBroker::new(Arc::new(TimerBroker)))));
+    assert_eq!(
+      maybe_check_with_broker("net", || None),
+      Some(BrokerResponse::Allow)
+    );
+  }
 }
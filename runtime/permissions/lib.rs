@@ -2,21 +2,21 @@
 
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fmt;
 use std::fmt::Debug;
 use std::hash::Hash;
-use std::io::Write;
 use std::net::IpAddr;
 use std::net::Ipv6Addr;
 use std::net::SocketAddr;
 use std::ops::Deref;
+use std::ops::DerefMut;
 use std::path::Path;
 use std::path::PathBuf;
 use std::string::ToString;
 use std::sync::Arc;
-use std::sync::OnceLock;
 
 use capacity_builder::StringBuilder;
 use deno_path_util::normalize_path;
@@ -27,27 +27,44 @@ use fqdn::FQDN;
 use ipnet::IpNet;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
+use parking_lot::MutexGuard;
 use serde::Deserialize;
 use serde::Deserializer;
 use serde::Serialize;
 use serde::de;
 use url::Url;
 
+pub mod audit;
 pub mod broker;
 mod ipc_pipe;
+pub mod packages;
 pub mod prompter;
+pub mod quota;
 mod runtime_descriptor_parser;
 pub mod which;
 
-use prompter::MAYBE_CURRENT_STACKTRACE;
 use prompter::PERMISSION_EMOJI;
 use prompter::permission_prompt;
 pub use runtime_descriptor_parser::RuntimePermissionDescriptorParser;
 
+pub use self::audit::AUDIT_SINK;
+use self::audit::AuditDecidedBy;
+use self::audit::AuditDecision;
+pub use self::audit::AuditSink;
+pub use self::audit::OtelAuditFn;
+use self::audit::is_audit_enabled;
+use self::audit::write_audit;
+use self::audit::write_audit_record;
+use self::packages::PackagePermissions;
+use self::prompter::BroaderGrantOption;
 use self::prompter::PromptResponse;
+use self::prompter::record_prompt_grant;
+use self::quota::PermissionQuotas;
+use self::quota::QuotaParseError;
+use self::quota::WriteQuota;
 use self::which::WhichSys;
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum BrokerResponse {
   Allow,
   Deny { message: Option<String> },
@@ -56,16 +73,6 @@ pub enum BrokerResponse {
 use self::broker::has_broker;
 use self::broker::maybe_check_with_broker;
 
-pub type OtelAuditFn =
-  fn(permission: &str, value: &str, stack: Option<&[String]>);
-
-pub enum AuditSink {
-  File(Mutex<std::fs::File>),
-  Otel(OtelAuditFn),
-}
-
-pub static AUDIT_SINK: OnceLock<AuditSink> = OnceLock::new();
-
 #[derive(Debug, thiserror::Error, deno_error::JsError)]
 #[error("{}", custom_message.as_ref().cloned().unwrap_or_else(|| format!("Requires {access}, {}", format_permission_error(.name))))]
 #[class("NotCapable")]
@@ -86,68 +93,14 @@ fn format_permission_error(name: &'static str) -> String {
   }
 }
 
-fn write_audit<T>(flag_name: &str, value: T)
-where
-  T: Serialize,
-{
-  let Some(sink) = AUDIT_SINK.get() else {
-    return;
-  };
-
-  let get_stack = MAYBE_CURRENT_STACKTRACE.lock();
-  let stack = get_stack.as_ref().map(|s| s());
-
-  match sink {
-    AuditSink::File(file) => {
-      let mut file = file.lock();
-
-      let mut map = serde_json::Map::with_capacity(6);
-      let _ = map.insert("v".into(), serde_json::Value::Number(1.into()));
-      let _ = map.insert(
-        "datetime".into(),
-        #[allow(
-          clippy::disallowed_methods,
-          reason = "TODO: support passing in a sys here"
-        )]
-        serde_json::Value::String(
-          chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
-        ),
-      );
-      let _ = map.insert(
-        "permission".into(),
-        serde_json::to_value(flag_name).unwrap(),
-      );
-      let _ = map.insert("value".into(), serde_json::to_value(&value).unwrap());
-
-      if let Some(ref stack) = stack {
-        let _ =
-          map.insert("stack".into(), serde_json::to_value(stack).unwrap());
-      }
-
-      let _ = file.write_all(
-        format!("{}\n", serde_json::to_string(&map).unwrap()).as_bytes(),
-      );
-    }
-    AuditSink::Otel(report_fn) => {
-      let value_str = serde_json::to_value(&value)
-        .map(|v| match v {
-          serde_json::Value::String(s) => s,
-          other => other.to_string(),
-        })
-        .unwrap_or_default();
-
-      report_fn(flag_name, &value_str, stack.as_deref());
-    }
-  }
-}
-
 /// Fast exit from permission check routines if this permission
 /// is in the "fully-granted" state.
 macro_rules! audit_and_skip_check_if_is_permission_fully_granted {
   ($this:expr, $flag_name:expr, $value:expr) => {
-    write_audit($flag_name, $value);
-
     if $this.is_allow_all() {
+      if is_audit_enabled() {
+        write_audit($flag_name, $value);
+      }
       return Ok(());
     }
   };
@@ -445,6 +398,15 @@ struct PromptOptions<'a> {
   api_name: Option<&'a str>,
   info: Option<&'a str>,
   is_unary: bool,
+  broader: Option<BroaderGrantOption<'a>>,
+}
+
+/// What was granted by answering a prompt.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PromptGrant {
+  Requested,
+  Broader,
+  All,
 }
 
 impl PermissionState {
@@ -491,32 +453,33 @@ impl PermissionState {
 
   fn prompt(
     options: PromptOptions<'_>,
-  ) -> (Result<(), PermissionDeniedError>, bool) {
+  ) -> (Result<(), PermissionDeniedError>, PromptGrant) {
     let PromptOptions {
       name,
       msg,
       api_name,
       info,
       is_unary,
+      broader,
     } = options;
-    match permission_prompt(msg, name, api_name, is_unary) {
-      PromptResponse::Allow => {
-        Self::log_perm_access(name, || info.map(|i| i.to_string()));
-        (Ok(()), false)
-      }
-      PromptResponse::AllowAll => {
-        Self::log_perm_access(name, || info.map(|i| i.to_string()));
-        (Ok(()), true)
+    let grant = match permission_prompt(msg, name, api_name, is_unary, broader)
+    {
+      PromptResponse::Allow => PromptGrant::Requested,
+      PromptResponse::AllowBroader => PromptGrant::Broader,
+      PromptResponse::AllowAll => PromptGrant::All,
+      PromptResponse::Deny => {
+        return (
+          Err(Self::permission_denied_error(
+            name,
+            info,
+            PermissionState::Denied,
+          )),
+          PromptGrant::Requested,
+        );
       }
-      PromptResponse::Deny => (
-        Err(Self::permission_denied_error(
-          name,
-          info,
-          PermissionState::Denied,
-        )),
-        false,
-      ),
-    }
+    };
+    Self::log_perm_access(name, || info.map(|i| i.to_string()));
+    (Ok(()), grant)
   }
 
   #[inline]
@@ -527,12 +490,13 @@ impl PermissionState {
     stringify_value_fn: impl Fn() -> Option<String>,
     info: impl Fn() -> Option<String>,
     prompt: bool,
-  ) -> (Result<(), PermissionDeniedError>, bool, bool) {
+    broader: Option<BroaderGrantOption<'_>>,
+  ) -> (Result<(), PermissionDeniedError>, bool, PromptGrant) {
     if let Some(resp) = maybe_check_with_broker(name, &stringify_value_fn) {
       match resp {
         BrokerResponse::Allow => {
           Self::log_perm_access(name, info);
-          return (Ok(()), false, false);
+          return (Ok(()), false, PromptGrant::Requested);
         }
         BrokerResponse::Deny { message } => {
           return (
@@ -543,7 +507,7 @@ impl PermissionState {
               state: PermissionState::Denied,
             }),
             false,
-            false,
+            PromptGrant::Requested,
           );
         }
       }
@@ -552,7 +516,7 @@ impl PermissionState {
     match self {
       PermissionState::Granted => {
         Self::log_perm_access(name, info);
-        (Ok(()), false, false)
+        (Ok(()), false, PromptGrant::Requested)
       }
       PermissionState::Prompt if prompt => {
         let info = info();
@@ -565,18 +529,19 @@ impl PermissionState {
           }
         })
         .unwrap();
-        let (result, is_allow_all) = Self::prompt(PromptOptions {
+        let (result, grant) = Self::prompt(PromptOptions {
           name,
           msg: &msg,
           api_name,
           info: info.as_deref(),
           is_unary: true,
+          broader,
         });
-        (result, true, is_allow_all)
+        (result, true, grant)
       }
       state => {
         let err = Self::permission_denied_error(name, info().as_deref(), state);
-        (Err(err), false, false)
+        (Err(err), false, PromptGrant::Requested)
       }
     }
   }
@@ -603,8 +568,10 @@ impl UnitPermission {
           self.name,
           Some("Deno.permissions.query()"),
           false,
+          None,
         )
       {
+        record_prompt_grant(self.name, None);
         self.state = PermissionState::Granted;
       } else {
         self.state = PermissionState::Denied;
@@ -625,12 +592,17 @@ impl UnitPermission {
     stringify_value_fn: impl Fn() -> Option<String>,
     info: impl Fn() -> Option<String>,
   ) -> Result<(), PermissionDeniedError> {
-    let (result, prompted, _is_allow_all) =
-      self
-        .state
-        .check(self.name, None, stringify_value_fn, info, self.prompt);
+    let (result, prompted, _grant) = self.state.check(
+      self.name,
+      None,
+      stringify_value_fn,
+      info,
+      self.prompt,
+      None,
+    );
     if prompted {
       if result.is_ok() {
+        record_prompt_grant(self.name, None);
         self.state = PermissionState::Granted;
       } else {
         self.state = PermissionState::Denied;
@@ -712,6 +684,9 @@ pub trait AllowDescriptor: Debug + Eq + Clone + Hash {
 
 pub trait DenyDescriptor: Debug + Eq + Clone + Hash {
   fn cmp_deny(&self, other: &Self) -> Ordering;
+
+  /// The value of the flag that created this descriptor, for the audit log.
+  fn rule_value(&self) -> Cow<'_, str>;
 }
 
 pub trait QueryDescriptor: Debug {
@@ -740,6 +715,34 @@ pub trait QueryDescriptor: Debug {
   fn revokes(&self, other: &Self::AllowDesc) -> bool;
   fn stronger_than_deny(&self, other: &Self::DenyDesc) -> bool;
   fn overlaps_deny(&self, other: &Self::DenyDesc) -> bool;
+
+  /// A grant broader than this descriptor to offer when prompting for it.
+  fn broader_allow(&self) -> Option<BroaderAllow<Self::AllowDesc>> {
+    None
+  }
+}
+
+/// A grant broader than the requested descriptor, such as the directory
+/// containing a file, the subdomains of a host or the environment variables
+/// sharing a prefix.
+#[derive(Debug)]
+pub struct BroaderAllow<TAllowDesc> {
+  pub desc: TAllowDesc,
+  /// The value of the equivalent `--allow-*` flag.
+  pub flag_value: String,
+  /// The key that selects it at the prompt.
+  pub key: char,
+  /// What it grants, ex. `everything in "./public"`.
+  pub description: String,
+}
+
+impl<TAllowDesc> BroaderAllow<TAllowDesc> {
+  fn as_prompt_option(&self) -> BroaderGrantOption<'_> {
+    BroaderGrantOption {
+      key: self.key,
+      description: &self.description,
+    }
+  }
 }
 
 fn format_display_name(display_name: Cow<'_, str>) -> Cow<'_, str> {
@@ -1002,22 +1005,26 @@ impl<
     assert_non_partial: bool,
     api_name: Option<&str>,
   ) -> Result<(), PermissionDeniedError> {
-    let (result, prompted, is_allow_all) = self
-      .query_desc(desc, AllowPartial::from(!assert_non_partial))
-      .check(
-        TAllowDesc::QueryDesc::flag_name(),
-        api_name,
-        || desc.map(|d| d.display_name().to_string()),
-        || desc.map(|d| format_display_name(d.display_name()).into_owned()),
-        self.prompt,
-      );
+    let state = self.query_desc(desc, AllowPartial::from(!assert_non_partial));
+    let broader = if state == PermissionState::Prompt && self.prompt {
+      desc.and_then(|d| d.broader_allow())
+    } else {
+      None
+    };
+    let (result, prompted, grant) = state.check(
+      TAllowDesc::QueryDesc::flag_name(),
+      api_name,
+      || desc.map(|d| d.display_name().to_string()),
+      || desc.map(|d| format_display_name(d.display_name()).into_owned()),
+      self.prompt,
+      broader.as_ref().map(|b| b.as_prompt_option()),
+    );
+    if is_audit_enabled() {
+      self.audit_check(desc, result.is_ok(), prompted);
+    }
     if prompted {
       if result.is_ok() {
-        if is_allow_all {
-          self.insert_granted(None);
-        } else {
-          self.insert_granted(desc);
-        }
+        self.insert_prompt_granted(desc, grant, broader);
       } else {
         self.insert_prompt_denied(desc.map(|d| d.as_deny()));
       }
@@ -1025,6 +1032,105 @@ impl<
     result
   }
 
+  fn audit_check(
+    &self,
+    desc: Option<&TAllowDesc::QueryDesc<'_>>,
+    granted: bool,
+    prompted: bool,
+  ) {
+    let (decided_by, rule) = if has_broker() {
+      (AuditDecidedBy::Broker, None)
+    } else if prompted {
+      (AuditDecidedBy::Prompt, None)
+    } else {
+      self.audit_rule(desc, granted)
+    };
+    write_audit_record(
+      TAllowDesc::QueryDesc::flag_name(),
+      desc.map(|d| d.display_name().into_owned()),
+      if granted {
+        AuditDecision::Granted
+      } else {
+        AuditDecision::Denied
+      },
+      decided_by,
+      rule,
+    );
+  }
+
+  /// Finds what decided a check that didn't prompt, in the same order as
+  /// `query_desc`.
+  fn audit_rule(
+    &self,
+    desc: Option<&TAllowDesc::QueryDesc<'_>>,
+    granted: bool,
+  ) -> (AuditDecidedBy, Option<String>) {
+    let name = TAllowDesc::QueryDesc::flag_name();
+    let deny_rule = |prefix: &str, deny: &TDenyDesc| {
+      (
+        AuditDecidedBy::Rule,
+        Some(format!("--{prefix}-{name}={}", deny.rule_value())),
+      )
+    };
+    let partial_deny_rule = || {
+      let desc = desc?;
+      self.descriptors.iter().find_map(|item| match item {
+        UnaryPermissionDesc::FlagDenied(v) if desc.overlaps_deny(v) => {
+          Some(deny_rule("deny", v))
+        }
+        _ => None,
+      })
+    };
+    if let Some(desc) = desc {
+      for item in self.descriptors.iter() {
+        match item {
+          UnaryPermissionDesc::Granted(v) => {
+            if desc.matches_allow(v) {
+              if !granted && let Some(rule) = partial_deny_rule() {
+                return rule;
+              }
+              let allow = TAllowDesc::QueryDesc::from_allow(v);
+              return (
+                AuditDecidedBy::Rule,
+                Some(format!("--allow-{name}={}", allow.display_name())),
+              );
+            }
+          }
+          UnaryPermissionDesc::FlagDenied(v) => {
+            if desc.matches_deny(v) {
+              return deny_rule("deny", v);
+            }
+          }
+          UnaryPermissionDesc::FlagIgnored(v) => {
+            if desc.matches_deny(v) {
+              return deny_rule("ignore", v);
+            }
+          }
+          UnaryPermissionDesc::PromptDenied(v) => {
+            if desc.stronger_than_deny(v) {
+              return (AuditDecidedBy::Prompt, None);
+            }
+          }
+        }
+      }
+    }
+    if self.flag_ignored_global {
+      (AuditDecidedBy::Rule, Some(format!("--ignore-{name}")))
+    } else if !granted && let Some(rule) = partial_deny_rule() {
+      rule
+    } else if self.flag_denied_global {
+      (AuditDecidedBy::Rule, Some(format!("--deny-{name}")))
+    } else if self.prompt_denied_global
+      || desc.is_none() && self.descriptors.has_prompt_denied()
+    {
+      (AuditDecidedBy::Prompt, None)
+    } else if self.granted_global {
+      (AuditDecidedBy::Rule, Some(format!("--allow-{name}")))
+    } else {
+      (AuditDecidedBy::Default, None)
+    }
+  }
+
   fn query_desc(
     &self,
     desc: Option<&TAllowDesc::QueryDesc<'_>>,
@@ -1135,25 +1241,24 @@ impl<
       }
     })
     .unwrap();
-    match permission_prompt(
+    let broader = desc.and_then(|d| d.broader_allow());
+    let grant = match permission_prompt(
       &message,
       TAllowDesc::QueryDesc::flag_name(),
       Some("Deno.permissions.request()"),
       true,
+      broader.as_ref().map(|b| b.as_prompt_option()),
     ) {
-      PromptResponse::Allow => {
-        self.insert_granted(desc);
-        PermissionState::Granted
-      }
+      PromptResponse::Allow => PromptGrant::Requested,
+      PromptResponse::AllowBroader => PromptGrant::Broader,
+      PromptResponse::AllowAll => PromptGrant::All,
       PromptResponse::Deny => {
         self.insert_prompt_denied(desc.map(|d| d.as_deny()));
-        PermissionState::Denied
-      }
-      PromptResponse::AllowAll => {
-        self.insert_granted(None);
-        PermissionState::Granted
+        return PermissionState::Denied;
       }
-    }
+    };
+    self.insert_prompt_granted(desc, grant, broader);
+    PermissionState::Granted
   }
 
   fn revoke_desc(
@@ -1214,6 +1319,30 @@ impl<
     true
   }
 
+  /// Inserts what was granted at a prompt and remembers it for
+  /// `prompter::prompt_grant_flags`.
+  fn insert_prompt_granted(
+    &mut self,
+    desc: Option<&TAllowDesc::QueryDesc<'_>>,
+    grant: PromptGrant,
+    broader: Option<BroaderAllow<TAllowDesc>>,
+  ) {
+    let name = TAllowDesc::QueryDesc::flag_name();
+    if grant == PromptGrant::Broader
+      && let Some(broader) = broader
+    {
+      self
+        .descriptors
+        .insert(UnaryPermissionDesc::Granted(broader.desc));
+      record_prompt_grant(name, Some(broader.flag_value));
+    } else if grant == PromptGrant::All || desc.is_none() {
+      self.insert_granted(None);
+      record_prompt_grant(name, None);
+    } else if self.insert_granted(desc) {
+      record_prompt_grant(name, desc.map(|d| d.display_name().into_owned()));
+    }
+  }
+
   fn insert_prompt_denied(&mut self, desc: Option<TDenyDesc>) {
     Self::list_insert(
       desc.map(UnaryPermissionDesc::PromptDenied),
@@ -1470,6 +1599,43 @@ impl<'a> PathQueryDescriptor<'a> {
     }
   }
 
+  /// Offers everything in the directory containing this path, except for
+  /// root directories.
+  fn broader_allow<T>(
+    &self,
+    into_allow: impl FnOnce(PathDescriptor) -> T,
+  ) -> Option<BroaderAllow<T>> {
+    if self.is_windows_device_path {
+      return None;
+    }
+    let parent = self.path.parent()?;
+    parent.parent()?;
+    let mut desc =
+      PathQueryDescriptor::new_known_absolute(Cow::Borrowed(parent));
+    // keep the parent relative when the path was requested relative
+    if let Some(requested) = self
+      .requested
+      .as_deref()
+      .map(Path::new)
+      .filter(|requested| requested.file_name().is_some())
+      .and_then(|requested| requested.parent())
+    {
+      let requested = if requested.as_os_str().is_empty() {
+        Path::new(".")
+      } else {
+        requested
+      };
+      desc = desc.with_requested(requested.to_string_lossy().into_owned());
+    }
+    let flag_value = desc.display_name().into_owned();
+    Some(BroaderAllow {
+      desc: into_allow(desc.into_descriptor()),
+      description: format!("everything in \"{flag_value}\""),
+      flag_value,
+      key: 'd',
+    })
+  }
+
   pub fn into_ffi(self) -> FfiQueryDescriptor<'a> {
     FfiQueryDescriptor(self)
   }
@@ -1542,6 +1708,10 @@ impl QueryDescriptor for ReadQueryDescriptor<'_> {
   fn overlaps_deny(&self, other: &Self::DenyDesc) -> bool {
     self.stronger_than_deny(other)
   }
+
+  fn broader_allow(&self) -> Option<BroaderAllow<Self::AllowDesc>> {
+    self.0.broader_allow(PathDescriptor::into_read)
+  }
 }
 
 #[derive(Clone, Debug)]
@@ -1691,6 +1861,10 @@ impl DenyDescriptor for ReadDescriptor {
   fn cmp_deny(&self, other: &Self) -> Ordering {
     self.0.cmp_deny_deny(&other.0)
   }
+
+  fn rule_value(&self) -> Cow<'_, str> {
+    self.0.display_name()
+  }
 }
 
 #[derive(Clone, Debug)]
@@ -1752,6 +1926,10 @@ impl QueryDescriptor for WriteQueryDescriptor<'_> {
   fn overlaps_deny(&self, other: &Self::DenyDesc) -> bool {
     self.stronger_than_deny(other)
   }
+
+  fn broader_allow(&self) -> Option<BroaderAllow<Self::AllowDesc>> {
+    self.0.broader_allow(PathDescriptor::into_write)
+  }
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
@@ -1774,6 +1952,10 @@ impl DenyDescriptor for WriteDescriptor {
   fn cmp_deny(&self, other: &Self) -> Ordering {
     self.0.cmp_deny_deny(&other.0)
   }
+
+  fn rule_value(&self) -> Cow<'_, str> {
+    self.0.display_name()
+  }
 }
 
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
@@ -1908,8 +2090,115 @@ impl Host {
   }
 }
 
+/// A host with an optional port, port range or port list.
+///
+/// Breaking change: the port used to be an `Option<u32>`. Embedders that
+/// construct descriptors can keep passing a `u16`/`u32` through
+/// `NetPort::from` (or `.into()`), and those that read the port of a
+/// descriptor for a connection can use [`NetDescriptor::single_port`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug, PartialOrd, Ord)]
+pub struct NetDescriptor(pub Host, pub Option<NetPort>);
+
+/// The port part of a [`NetDescriptor`].
+///
+/// Descriptors created for an actual connection always have a single port,
+/// while descriptors from flags, the config or a permission query may also
+/// contain a range (ex. `30000-31000`) or a list (ex. `80,443,8000-8100`).
 #[derive(Clone, Eq, PartialEq, Hash, Debug, PartialOrd, Ord)]
-pub struct NetDescriptor(pub Host, pub Option<u32>);
+pub enum NetPort {
+  Single(u32),
+  /// Inclusive range of ports.
+  Range(u32, u32),
+  /// List of single ports and ranges.
+  List(Vec<NetPort>),
+}
+
+impl From<u16> for NetPort {
+  fn from(port: u16) -> Self {
+    NetPort::Single(port.into())
+  }
+}
+
+impl From<u32> for NetPort {
+  fn from(port: u32) -> Self {
+    NetPort::Single(port)
+  }
+}
+
+impl NetPort {
+  fn parse(text: &str) -> Option<Self> {
+    fn parse_item(text: &str) -> Option<NetPort> {
+      match text.split_once('-') {
+        Some((start, end)) => {
+          let start = start.parse::<u16>().ok()?;
+          let end = end.parse::<u16>().ok()?;
+          match start.cmp(&end) {
+            Ordering::Less => Some(NetPort::Range(start.into(), end.into())),
+            Ordering::Equal => Some(NetPort::Single(start.into())),
+            Ordering::Greater => None,
+          }
+        }
+        None => text.parse::<u16>().ok().map(Into::into),
+      }
+    }
+
+    if text.contains(',') {
+      let items = text.split(',').map(parse_item).collect::<Option<_>>()?;
+      Some(NetPort::List(items))
+    } else {
+      parse_item(text)
+    }
+  }
+
+  fn ranges(&self) -> Vec<(u32, u32)> {
+    match self {
+      NetPort::Single(port) => vec![(*port, *port)],
+      NetPort::Range(start, end) => vec![(*start, *end)],
+      NetPort::List(items) => items.iter().flat_map(|i| i.ranges()).collect(),
+    }
+  }
+
+  /// Gets if every port of `other` is also a port of this one.
+  pub fn contains(&self, other: &NetPort) -> bool {
+    if let (NetPort::Single(a), NetPort::Single(b)) = (self, other) {
+      return a == b;
+    }
+    let ranges = self.ranges();
+    other.ranges().into_iter().all(|(other_start, other_end)| {
+      ranges
+        .iter()
+        .any(|(start, end)| *start <= other_start && other_end <= *end)
+    })
+  }
+
+  /// Gets if at least one port of `other` is also a port of this one.
+  pub fn overlaps(&self, other: &NetPort) -> bool {
+    let ranges = self.ranges();
+    other.ranges().into_iter().any(|(other_start, other_end)| {
+      ranges
+        .iter()
+        .any(|(start, end)| *start <= other_end && other_start <= *end)
+    })
+  }
+}
+
+impl fmt::Display for NetPort {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      NetPort::Single(port) => write!(f, "{port}"),
+      NetPort::Range(start, end) => write!(f, "{start}-{end}"),
+      NetPort::List(items) => {
+        for (i, item) in items.iter().enumerate() {
+          if i > 0 {
+            write!(f, ",")?;
+          }
+          write!(f, "{item}")?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
 
 impl QueryDescriptor for NetDescriptor {
   type AllowDesc = NetDescriptor;
@@ -1949,24 +2238,13 @@ impl QueryDescriptor for NetDescriptor {
   }
 
   fn matches_allow(&self, other: &Self::AllowDesc) -> bool {
-    if other.1.is_some() && self.1 != other.1 {
-      return false;
-    }
-    match (&other.0, &self.0) {
-      (Host::Fqdn(a), Host::Fqdn(b)) => a == b,
-      (Host::FqdnWithSubdomainWildcard(a), Host::Fqdn(b)) => {
-        b.is_subdomain_of(a)
+    if let Some(other_port) = &other.1 {
+      match &self.1 {
+        Some(port) if other_port.contains(port) => {}
+        _ => return false,
       }
-      (
-        Host::FqdnWithSubdomainWildcard(a),
-        Host::FqdnWithSubdomainWildcard(b),
-      ) => a == b,
-      (Host::Ip(a), Host::Ip(b)) => a == b,
-      (Host::Vsock(a), Host::Vsock(b)) => a == b,
-      (Host::UnixSocket(a), Host::UnixSocket(b)) => a == b,
-      (Host::IpSubnet(a), Host::Ip(b)) => a.contains(b),
-      _ => false,
     }
+    self.host_matches(other)
   }
 
   fn matches_deny(&self, other: &Self::DenyDesc) -> bool {
@@ -1981,8 +2259,57 @@ impl QueryDescriptor for NetDescriptor {
     self.matches_deny(other)
   }
 
-  fn overlaps_deny(&self, _other: &Self::DenyDesc) -> bool {
-    false
+  fn overlaps_deny(&self, other: &Self::DenyDesc) -> bool {
+    // ex. querying `localhost:8000-8100` when `localhost:8080` is denied
+    match (&self.1, &other.1) {
+      (Some(port), Some(other_port)) => {
+        other_port.overlaps(port)
+          && !other_port.contains(port)
+          && self.host_matches(other)
+      }
+      _ => false,
+    }
+  }
+
+  fn broader_allow(&self) -> Option<BroaderAllow<Self::AllowDesc>> {
+    let Host::Fqdn(fqdn) = &self.0 else {
+      return None;
+    };
+    // only widen the host, a grant for one port must not open the others
+    let desc = NetDescriptor(
+      Host::FqdnWithSubdomainWildcard(fqdn.clone()),
+      self.1.clone(),
+    );
+    let description = match &self.1 {
+      Some(port) => format!("\"{fqdn}\" and its subdomains on port {port}"),
+      None => format!("\"{fqdn}\" and its subdomains"),
+    };
+    Some(BroaderAllow {
+      flag_value: desc.to_string(),
+      desc,
+      key: 's',
+      description,
+    })
+  }
+}
+
+impl NetDescriptor {
+  fn host_matches(&self, other: &NetDescriptor) -> bool {
+    match (&other.0, &self.0) {
+      (Host::Fqdn(a), Host::Fqdn(b)) => a == b,
+      (Host::FqdnWithSubdomainWildcard(a), Host::Fqdn(b)) => {
+        b.is_subdomain_of(a)
+      }
+      (
+        Host::FqdnWithSubdomainWildcard(a),
+        Host::FqdnWithSubdomainWildcard(b),
+      ) => a == b,
+      (Host::Ip(a), Host::Ip(b)) => a == b,
+      (Host::Vsock(a), Host::Vsock(b)) => a == b,
+      (Host::UnixSocket(a), Host::UnixSocket(b)) => a == b,
+      (Host::IpSubnet(a), Host::Ip(b)) => a.contains(b),
+      _ => false,
+    }
   }
 }
 
@@ -2013,12 +2340,25 @@ impl DenyDescriptor for NetDescriptor {
   fn cmp_deny(&self, other: &Self) -> Ordering {
     self.cmp_allow(other)
   }
+
+  fn rule_value(&self) -> Cow<'_, str> {
+    Cow::Owned(self.to_string())
+  }
 }
 
 impl NetDescriptor {
   pub fn into_import(self) -> ImportDescriptor {
     ImportDescriptor(self)
   }
+
+  /// The port when this descriptor has exactly one, which is always the case
+  /// for descriptors created for a connection.
+  pub fn single_port(&self) -> Option<u32> {
+    match self.1 {
+      Some(NetPort::Single(port)) => Some(port),
+      _ => None,
+    }
+  }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -2085,7 +2425,7 @@ impl NetDescriptor {
       let Some(port) = split.next().and_then(|p| p.parse().ok()) else {
         return Err(NetDescriptorParseError::InvalidVsock(hostname.into()));
       };
-      return Ok(NetDescriptor(Host::Vsock(cid), Some(port)));
+      return Ok(NetDescriptor(Host::Vsock(cid), Some(NetPort::Single(port))));
     }
 
     if let Some(rest) = hostname.strip_prefix("unix:") {
@@ -2126,7 +2466,7 @@ impl NetDescriptor {
               ip: ip_str.to_string(),
             })?;
         let port = if let Some(port) = after.strip_prefix(':') {
-          let port = port.parse::<u16>().map_err(|_| {
+          let port = NetPort::parse(port).ok_or_else(|| {
             NetDescriptorParseError::InvalidPort {
               hostname: hostname.to_string(),
               port: port.to_string(),
@@ -2140,10 +2480,7 @@ impl NetDescriptor {
             hostname.to_string(),
           ));
         };
-        return Ok(NetDescriptor(
-          Host::Ip(normalize_ip(IpAddr::V6(ip))),
-          port.map(Into::into),
-        ));
+        return Ok(NetDescriptor(Host::Ip(normalize_ip(IpAddr::V6(ip))), port));
       } else {
         return Err(NetDescriptorParseError::InvalidHost(hostname.to_string()));
       }
@@ -2162,7 +2499,7 @@ impl NetDescriptor {
     let port = if port.is_empty() {
       None
     } else {
-      let port = port.parse::<u16>().map_err(|_| {
+      let port = NetPort::parse(port).ok_or_else(|| {
         // If the user forgot to enclose an IPv6 address in square brackets, we
         // should give them a hint. There are always at least two colons in an
         // IPv6 address, so this heuristic finds likely a bare IPv6 address.
@@ -2180,7 +2517,7 @@ impl NetDescriptor {
       Some(port)
     };
 
-    Ok(NetDescriptor(host, port.map(Into::into)))
+    Ok(NetDescriptor(host, port))
   }
 
   pub fn from_url(url: &Url) -> Result<Self, NetDescriptorFromUrlParseError> {
@@ -2196,7 +2533,7 @@ impl NetDescriptor {
     cid: u32,
     port: u32,
   ) -> Result<Self, NetDescriptorParseError> {
-    Ok(NetDescriptor(Host::Vsock(cid), Some(port)))
+    Ok(NetDescriptor(Host::Vsock(cid), Some(port.into())))
   }
 }
 
@@ -2211,7 +2548,7 @@ impl fmt::Display for NetDescriptor {
       Host::Vsock(cid) => write!(f, "vsock:{cid}"),
       Host::UnixSocket(path) => write!(f, "unix:{}", path.display()),
     }?;
-    if let Some(port) = self.1 {
+    if let Some(port) = &self.1 {
       write!(f, ":{}", port)?;
     }
     Ok(())
@@ -2277,6 +2614,16 @@ impl QueryDescriptor for ImportDescriptor {
   fn overlaps_deny(&self, other: &Self::DenyDesc) -> bool {
     self.0.overlaps_deny(&other.0)
   }
+
+  fn broader_allow(&self) -> Option<BroaderAllow<Self::AllowDesc>> {
+    let broader = self.0.broader_allow()?;
+    Some(BroaderAllow {
+      desc: ImportDescriptor(broader.desc),
+      flag_value: broader.flag_value,
+      key: broader.key,
+      description: broader.description,
+    })
+  }
 }
 
 impl ImportDescriptor {
@@ -2308,6 +2655,10 @@ impl DenyDescriptor for ImportDescriptor {
   fn cmp_deny(&self, other: &Self) -> Ordering {
     DenyDescriptor::cmp_deny(&self.0, &other.0)
   }
+
+  fn rule_value(&self) -> Cow<'_, str> {
+    self.0.rule_value()
+  }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -2366,6 +2717,15 @@ impl DenyDescriptor for EnvDescriptor {
   fn cmp_deny(&self, other: &Self) -> Ordering {
     cmp_env_descriptor(self, other)
   }
+
+  fn rule_value(&self) -> Cow<'_, str> {
+    match self {
+      EnvDescriptor::Name(name) => Cow::Borrowed(name.as_ref()),
+      EnvDescriptor::PrefixPattern(prefix) => {
+        Cow::Owned(format!("{}*", prefix.as_ref()))
+      }
+    }
+  }
 }
 
 #[derive(Clone, Debug)]
@@ -2525,6 +2885,26 @@ impl QueryDescriptor for EnvQueryDescriptor<'_> {
   fn overlaps_deny(&self, _other: &Self::DenyDesc) -> bool {
     false
   }
+
+  fn broader_allow(&self) -> Option<BroaderAllow<Self::AllowDesc>> {
+    let EnvQueryDescriptorInner::Name(name) = &self.0 else {
+      return None;
+    };
+    // offer the variables sharing the first segment of the name,
+    // ex. `AWS_*` for `AWS_REGION`
+    let name = name.as_ref();
+    let index = name.find('_').filter(|i| *i > 0 && *i + 1 < name.len())?;
+    let prefix = &name[..=index];
+    let flag_value = format!("{prefix}*");
+    Some(BroaderAllow {
+      desc: EnvDescriptor::PrefixPattern(EnvVarName::new(Cow::Borrowed(
+        prefix,
+      ))),
+      description: format!("variables matching \"{flag_value}\""),
+      flag_value,
+      key: 'p',
+    })
+  }
 }
 
 impl AsRef<str> for EnvQueryDescriptor<'_> {
@@ -2839,6 +3219,13 @@ impl DenyDescriptor for DenyRunDescriptor {
       },
     }
   }
+
+  fn rule_value(&self) -> Cow<'_, str> {
+    match self {
+      DenyRunDescriptor::Name(name) => Cow::Borrowed(name),
+      DenyRunDescriptor::Path(path) => path.display_name(),
+    }
+  }
 }
 
 impl DenyRunDescriptor {
@@ -3044,6 +3431,10 @@ impl DenyDescriptor for SysDescriptor {
   fn cmp_deny(&self, other: &Self) -> Ordering {
     self.cmp(other)
   }
+
+  fn rule_value(&self) -> Cow<'_, str> {
+    Cow::Borrowed(&self.0)
+  }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -3105,6 +3496,10 @@ impl QueryDescriptor for FfiQueryDescriptor<'_> {
   fn overlaps_deny(&self, other: &Self::DenyDesc) -> bool {
     self.stronger_than_deny(other)
   }
+
+  fn broader_allow(&self) -> Option<BroaderAllow<Self::AllowDesc>> {
+    self.0.broader_allow(PathDescriptor::into_ffi)
+  }
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
@@ -3127,6 +3522,10 @@ impl DenyDescriptor for FfiDescriptor {
   fn cmp_deny(&self, other: &Self) -> Ordering {
     self.0.cmp_deny_deny(&other.0)
   }
+
+  fn rule_value(&self) -> Cow<'_, str> {
+    self.0.display_name()
+  }
 }
 
 impl UnaryPermission<ReadDescriptor> {
@@ -3622,6 +4021,10 @@ pub struct Permissions {
   pub run: UnaryPermission<AllowRunDescriptor>,
   pub ffi: UnaryPermission<FfiDescriptor>,
   pub import: UnaryPermission<ImportDescriptor>,
+  /// Restrictions for the code of specific npm and JSR packages.
+  pub packages: PackagePermissions,
+  /// Limits on how much of the granted permissions may be used.
+  pub quotas: PermissionQuotas,
 }
 
 impl Permissions {
@@ -3668,7 +4071,16 @@ pub struct PermissionsOptions {
   pub deny_write: Option<Vec<String>>,
   pub allow_import: Option<Vec<String>>,
   pub deny_import: Option<Vec<String>>,
+  /// Quotas written as `<LIMIT>` for a total or `<VALUE>=<LIMIT>` for the
+  /// checks matching a value (ex. `api.example.com=100`).
+  pub quota_net: Option<Vec<String>>,
+  pub quota_write: Option<Vec<String>>,
+  pub quota_run: Option<Vec<String>>,
   pub prompt: bool,
+  /// Permissions of the packages whose access is restricted further, keyed
+  /// by package (ex. `npm:chalk`).
+  #[serde(default)]
+  pub packages: BTreeMap<String, PermissionsOptions>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -3685,6 +4097,8 @@ pub enum PermissionsFromOptionsError {
   RunDescriptorParse(#[from] RunDescriptorParseError),
   #[error("Empty command name not allowed in --allow-run=...")]
   RunEmptyCommandName,
+  #[error("{0}")]
+  Quota(#[from] QuotaParseError),
 }
 
 impl Permissions {
@@ -3893,6 +4307,8 @@ impl Permissions {
         })?,
         opts.prompt,
       ),
+      packages: PackagePermissions::from_options(parser, &opts.packages)?,
+      quotas: PermissionQuotas::from_options(parser, opts)?,
     })
   }
 
@@ -3907,6 +4323,8 @@ impl Permissions {
       run: UnaryPermission::allow_all(),
       ffi: UnaryPermission::allow_all(),
       import: UnaryPermission::allow_all(),
+      packages: Default::default(),
+      quotas: Default::default(),
     }
   }
 
@@ -3930,10 +4348,23 @@ impl Permissions {
       run: Permissions::new_unary(None, None, prompt),
       ffi: Permissions::new_unary(None, None, prompt),
       import: Permissions::new_unary(None, None, prompt),
+      packages: Default::default(),
+      quotas: Default::default(),
     }
   }
 }
 
+fn check_package_scopes_for_net(
+  inner: &Permissions,
+  desc: &NetDescriptor,
+) -> Result<(), PermissionDeniedError> {
+  inner.packages.check(
+    NetDescriptor::flag_name(),
+    || Some(format_display_name(desc.display_name()).into_owned()),
+    |perms| perms.net.query(Some(desc)),
+  )
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum CheckSpecifierKind {
   Static,
@@ -4061,6 +4492,32 @@ pub struct PermissionsContainer {
   /// Hosts already resolved for the import deny check. See
   /// [`PermissionsContainer::resolve_host`].
   resolved_import_hosts: ResolvedHostsCache,
+  /// Decides the checks made through this container and its clones. See
+  /// [`broker`].
+  broker: SharedBroker,
+}
+
+type SharedBroker = Arc<Mutex<Option<Arc<broker::CachingBroker>>>>;
+
+/// The locked permissions of a container, with its broker deciding the
+/// checks made while the guard is held.
+struct PermissionsGuard<'a> {
+  permissions: MutexGuard<'a, Permissions>,
+  _broker: broker::BrokerScope,
+}
+
+impl Deref for PermissionsGuard<'_> {
+  type Target = Permissions;
+
+  fn deref(&self) -> &Permissions {
+    &self.permissions
+  }
+}
+
+impl DerefMut for PermissionsGuard<'_> {
+  fn deref_mut(&mut self) -> &mut Permissions {
+    &mut self.permissions
+  }
 }
 
 impl PermissionsContainer {
@@ -4072,14 +4529,34 @@ impl PermissionsContainer {
       descriptor_parser,
       inner: Arc::new(Mutex::new(perms)),
       resolved_import_hosts: Default::default(),
+      broker: Default::default(),
     }
   }
 
+  /// Sets the broker deciding the checks made through this container, its
+  /// clones (including the ones made before the broker was set) and the
+  /// containers created from it, such as the ones of web workers.
+  pub fn set_broker(&self, broker: Arc<dyn broker::PermissionBroker>) {
+    *self.broker.lock() = Some(Arc::new(broker::CachingBroker::new(broker)));
+  }
+
+  fn lock(&self) -> PermissionsGuard<'_> {
+    PermissionsGuard {
+      _broker: broker::enter(self.broker.lock().clone()),
+      permissions: self.inner.lock(),
+    }
+  }
+
+  fn snapshot_broker(&self) -> SharedBroker {
+    Arc::new(Mutex::new(self.broker.lock().clone()))
+  }
+
   pub fn deep_clone(&self) -> PermissionsContainer {
     Self {
       descriptor_parser: self.descriptor_parser.clone(),
       inner: Arc::new(Mutex::new(self.inner.lock().clone())),
       resolved_import_hosts: Default::default(),
+      broker: self.snapshot_broker(),
     }
   }
 
@@ -4095,6 +4572,7 @@ impl PermissionsContainer {
       descriptor_parser: self.descriptor_parser.clone(),
       inner: Arc::new(Mutex::new(permissions)),
       resolved_import_hosts: Default::default(),
+      broker: self.snapshot_broker(),
     }
   }
 
@@ -4110,7 +4588,7 @@ impl PermissionsContainer {
   ) -> Result<PermissionsContainer, ChildPermissionError> {
     let mut worker_perms = Permissions::none_without_prompt();
 
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
 
     // WARNING: When adding a permission here, ensure it is handled
     // in the worker_perms.all block above
@@ -4180,10 +4658,83 @@ impl PermissionsContainer {
       },
     )?;
 
-    Ok(PermissionsContainer::new(
-      self.descriptor_parser.clone(),
-      worker_perms,
-    ))
+    // package scopes keep applying to the code running in the worker
+    worker_perms.packages = inner.packages.clone();
+    worker_perms.quotas = inner.quotas.clone();
+
+    let mut container =
+      PermissionsContainer::new(self.descriptor_parser.clone(), worker_perms);
+    container.broker = self.snapshot_broker();
+    Ok(container)
+  }
+
+  /// Whether the code of some packages is restricted further than the rest.
+  ///
+  /// The packages making a check are found from the JavaScript stack, so
+  /// ops must capture it when this is true.
+  pub fn has_package_scopes(&self) -> bool {
+    !self.lock().packages.is_empty()
+  }
+
+  /// Checks access to a path against the scopes of the packages on the
+  /// current stack.
+  fn check_package_scopes_for_path(
+    &self,
+    inner: &Permissions,
+    path: &Path,
+    access_kind: OpenAccessKind,
+  ) -> Result<(), PermissionCheckError> {
+    if inner.packages.is_empty() {
+      return Ok(());
+    }
+    let desc = self
+      .descriptor_parser
+      .parse_path_query(Cow::Borrowed(path))?;
+    let info = || Some(format_display_name(desc.display_name()).into_owned());
+    if access_kind.is_read() {
+      inner.packages.check(
+        ReadQueryDescriptor::flag_name(),
+        info,
+        |perms| perms.read.query(Some(&desc.clone().into_read())),
+      )?;
+    }
+    if access_kind.is_write() {
+      inner.packages.check(
+        WriteQueryDescriptor::flag_name(),
+        info,
+        |perms| perms.write.query(Some(&desc.clone().into_write())),
+      )?;
+    }
+    Ok(())
+  }
+
+  fn check_package_scopes_for_ffi(
+    &self,
+    inner: &Permissions,
+    path: Option<&Path>,
+  ) -> Result<(), PermissionCheckError> {
+    if inner.packages.is_empty() {
+      return Ok(());
+    }
+    let desc = match path {
+      Some(path) => Some(
+        self
+          .descriptor_parser
+          .parse_path_query(Cow::Borrowed(path))?
+          .into_ffi(),
+      ),
+      None => None,
+    };
+    inner.packages.check(
+      FfiQueryDescriptor::flag_name(),
+      || {
+        desc
+          .as_ref()
+          .map(|d| format_display_name(d.display_name()).into_owned())
+      },
+      |perms| perms.ffi.query(desc.as_ref()),
+    )?;
+    Ok(())
   }
 
   #[inline(always)]
@@ -4192,7 +4743,31 @@ impl PermissionsContainer {
     specifier: &Url,
     kind: CheckSpecifierKind,
   ) -> Result<(), PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
+    if kind != CheckSpecifierKind::Static && !inner.packages.is_empty() {
+      match specifier.scheme() {
+        "file" => {
+          if let Ok(path) = url_to_file_path(specifier) {
+            self.check_package_scopes_for_path(
+              &inner,
+              &path,
+              OpenAccessKind::Read,
+            )?;
+          }
+        }
+        "data" | "blob" => {}
+        _ => {
+          let desc = self
+            .descriptor_parser
+            .parse_import_descriptor_from_url(specifier)?;
+          inner.packages.check(
+            ImportDescriptor::flag_name(),
+            || Some(format_display_name(desc.display_name()).into_owned()),
+            |perms| perms.import.query(Some(&desc)),
+          )?;
+        }
+      }
+    }
     match specifier.scheme() {
       "file" => {
         if inner.read.is_allow_all() {
@@ -4274,7 +4849,8 @@ impl PermissionsContainer {
     api_name: Option<&str>,
   ) -> Result<CheckedPath<'a>, PermissionCheckError> {
     let path = {
-      let mut inner = self.inner.lock();
+      let mut inner = self.lock();
+      self.check_package_scopes_for_path(&inner, &path, access_kind)?;
       if inner.all_granted() {
         write_audit(ReadQueryDescriptor::flag_name(), &path);
         write_audit(WriteQueryDescriptor::flag_name(), &path);
@@ -4348,9 +4924,13 @@ impl PermissionsContainer {
     &self,
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
-    self
-      .inner
-      .lock()
+    let mut inner = self.lock();
+    inner.packages.check(
+      ReadQueryDescriptor::flag_name(),
+      || None,
+      |perms| perms.read.query(None),
+    )?;
+    inner
       .read
       .check_all(Some(api_name))
       .map_err(ignored_to_not_found)
@@ -4358,7 +4938,7 @@ impl PermissionsContainer {
 
   #[inline(always)]
   pub fn query_read_all(&self) -> bool {
-    self.inner.lock().read.query(None) == PermissionState::Granted
+    self.lock().read.query(None) == PermissionState::Granted
   }
 
   #[inline(always)]
@@ -4366,7 +4946,13 @@ impl PermissionsContainer {
     &self,
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
-    self.inner.lock().write.check_all(Some(api_name))?;
+    let mut inner = self.lock();
+    inner.packages.check(
+      WriteQueryDescriptor::flag_name(),
+      || None,
+      |perms| perms.write.query(None),
+    )?;
+    inner.write.check_all(Some(api_name))?;
     Ok(())
   }
 
@@ -4376,7 +4962,8 @@ impl PermissionsContainer {
     path: Cow<'a, Path>,
     api_name: &str,
   ) -> Result<CheckedPath<'a>, PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
+    self.check_package_scopes_for_path(&inner, &path, OpenAccessKind::Write)?;
     let inner = &mut inner.write;
     if inner.is_allow_all() {
       write_audit(WriteQueryDescriptor::flag_name(), &path);
@@ -4414,7 +5001,8 @@ impl PermissionsContainer {
     path: Cow<'a, Path>,
     api_name: &str,
   ) -> Result<CheckedPath<'a>, PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
+    self.check_package_scopes_for_path(&inner, &path, OpenAccessKind::Write)?;
     let inner = &mut inner.write;
     if inner.is_allow_all() {
       write_audit(WriteQueryDescriptor::flag_name(), &path);
@@ -4440,13 +5028,157 @@ impl PermissionsContainer {
     }
   }
 
-  #[inline(always)]
-  pub fn check_run(
+  #[inline(always)]
+  pub fn check_run(
+    &self,
+    cmd: &RunQueryDescriptor,
+    api_name: &str,
+  ) -> Result<(), PermissionCheckError> {
+    let mut inner = self.lock();
+    inner.packages.check(
+      RunQueryDescriptor::flag_name(),
+      || Some(format_display_name(cmd.display_name()).into_owned()),
+      |perms| perms.run.query(Some(cmd)),
+    )?;
+    inner.run.check(cmd, Some(api_name))?;
+    Ok(())
+  }
+
+  /// Counts a spawn of `cmd` against the run quotas.
+  ///
+  /// Called when the subprocess is about to be spawned, after its command
+  /// was checked with [`Self::check_run`] or [`Self::query_run_all`].
+  #[inline(always)]
+  pub fn consume_run_quota(
+    &self,
+    cmd: &RunQueryDescriptor,
+  ) -> Result<(), PermissionCheckError> {
+    let inner = self.lock();
+    if !inner.quotas.has_run() {
+      return Ok(());
+    }
+    inner.quotas.consume_run(cmd)?;
+    Ok(())
+  }
+
+  /// Counts `bytes` about to be written to `path` against the write quotas.
+  ///
+  /// `path` must already have been checked with [`Self::check_open`]. If
+  /// the write fails, give the bytes back with
+  /// [`Self::release_write_quota`].
+  #[inline(always)]
+  pub fn consume_write_quota(
+    &self,
+    path: &Path,
+    bytes: u64,
+  ) -> Result<(), PermissionCheckError> {
+    let inner = self.lock();
+    if !inner.quotas.has_write() {
+      return Ok(());
+    }
+    let path = self
+      .descriptor_parser
+      .parse_path_query(Cow::Borrowed(path))?
+      .into_write();
+    inner.quotas.consume_write(&path, bytes)?;
+    Ok(())
+  }
+
+  /// Gives back `bytes` counted by [`Self::consume_write_quota`] that were
+  /// not written to `path`.
+  pub fn release_write_quota(&self, path: &Path, bytes: u64) {
+    let inner = self.lock();
+    if !inner.quotas.has_write() {
+      return;
+    }
+    if let Ok(path) =
+      self.descriptor_parser.parse_path_query(Cow::Borrowed(path))
+    {
+      inner.quotas.release_write(&path.into_write(), bytes);
+    }
+  }
+
+  /// The write quotas applying to a file opened at `path` with
+  /// `access_kind`, or `None` if the file isn't opened for writing or no
+  /// quota applies.
+  ///
+  /// `path` must already have been checked with [`Self::check_open`].
+  pub fn write_quota(
+    &self,
+    path: &Path,
+    access_kind: OpenAccessKind,
+  ) -> Result<Option<WriteQuota>, PermissionCheckError> {
+    let inner = self.lock();
+    if !access_kind.is_write() || !inner.quotas.has_write() {
+      return Ok(None);
+    }
+    let path = self
+      .descriptor_parser
+      .parse_path_query(Cow::Owned(path.to_path_buf()))?
+      .into_write();
+    Ok(Some(WriteQuota::new(inner.quotas.clone(), path)))
+  }
+
+  /// Counts an outbound connection or request to `host` against the net
+  /// quotas, once access to it was checked with [`Self::check_net`].
+  pub fn consume_net_quota<T: AsRef<str>>(
+    &self,
+    host: &(T, Option<u16>),
+  ) -> Result<(), PermissionCheckError> {
+    let inner = self.lock();
+    if !inner.quotas.has_net() {
+      return Ok(());
+    }
+    let hostname = Host::parse_for_query(host.0.as_ref())?;
+    inner
+      .quotas
+      .consume_net(&NetDescriptor(hostname, host.1.map(Into::into)))?;
+    Ok(())
+  }
+
+  /// Like [`Self::consume_net_quota`], for a request to `url`.
+  pub fn consume_net_quota_url(
     &self,
-    cmd: &RunQueryDescriptor,
-    api_name: &str,
+    url: &Url,
+  ) -> Result<(), PermissionCheckError> {
+    let inner = self.lock();
+    if !inner.quotas.has_net() {
+      return Ok(());
+    }
+    let desc = self.descriptor_parser.parse_net_descriptor_from_url(url)?;
+    inner.quotas.consume_net(&desc)?;
+    Ok(())
+  }
+
+  /// Like [`Self::consume_net_quota`], for a connection to a unix socket.
+  pub fn consume_net_quota_unix_socket(
+    &self,
+    path: &Path,
+  ) -> Result<(), PermissionCheckError> {
+    let inner = self.lock();
+    if !inner.quotas.has_net() {
+      return Ok(());
+    }
+    inner.quotas.consume_net(&NetDescriptor(
+      Host::UnixSocket(path.to_path_buf()),
+      None,
+    ))?;
+    Ok(())
+  }
+
+  /// Like [`Self::consume_net_quota`], for a connection to a vsock.
+  pub fn consume_net_quota_vsock(
+    &self,
+    cid: u32,
+    port: u32,
   ) -> Result<(), PermissionCheckError> {
-    self.inner.lock().run.check(cmd, Some(api_name))?;
+    let inner = self.lock();
+    if !inner.quotas.has_net() {
+      return Ok(());
+    }
+    inner
+      .quotas
+      .consume_net(&NetDescriptor(Host::Vsock(cid), Some(port.into())))?;
     Ok(())
   }
 
@@ -4455,13 +5187,35 @@ impl PermissionsContainer {
     &mut self,
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
-    self.inner.lock().run.check_all(Some(api_name))?;
+    let mut inner = self.lock();
+    inner.packages.check(
+      RunQueryDescriptor::flag_name(),
+      || None,
+      |perms| perms.run.query(None),
+    )?;
+    inner.run.check_all(Some(api_name))?;
     Ok(())
   }
 
   #[inline(always)]
   pub fn query_run_all(&mut self, api_name: &str) -> bool {
-    self.inner.lock().run.query_all(Some(api_name))
+    let mut inner = self.lock();
+    // a package scope restricting run must be checked against the command
+    if inner
+      .packages
+      .check(
+        RunQueryDescriptor::flag_name(),
+        || None,
+        |perms| match perms.run.query(None) {
+          PermissionState::Granted => PermissionState::Granted,
+          _ => PermissionState::Denied,
+        },
+      )
+      .is_err()
+    {
+      return false;
+    }
+    inner.run.query_all(Some(api_name))
   }
 
   #[inline(always)]
@@ -4470,34 +5224,62 @@ impl PermissionsContainer {
     kind: &str,
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
-    self.inner.lock().sys.check(
-      &self.descriptor_parser.parse_sys_descriptor(kind)?,
-      Some(api_name),
+    let mut inner = self.lock();
+    let desc = self.descriptor_parser.parse_sys_descriptor(kind)?;
+    inner.packages.check(
+      SysDescriptor::flag_name(),
+      || Some(format_display_name(desc.display_name()).into_owned()),
+      |perms| perms.sys.query(Some(&desc)),
     )?;
+    inner.sys.check(&desc, Some(api_name))?;
     Ok(())
   }
 
   #[inline(always)]
   pub fn check_env(&self, var: &str) -> Result<(), PermissionCheckError> {
-    self.inner.lock().env.check(var, None)?;
+    let mut inner = self.lock();
+    inner.packages.check(
+      EnvQueryDescriptor::flag_name(),
+      || Some(format_display_name(Cow::Borrowed(var)).into_owned()),
+      |perms| perms.env.query(Some(var)),
+    )?;
+    inner.env.check(var, None)?;
     Ok(())
   }
 
   #[inline(always)]
   pub fn check_env_all(&self) -> Result<(), PermissionCheckError> {
-    self.inner.lock().env.check_all()?;
+    let mut inner = self.lock();
+    inner.packages.check(
+      EnvQueryDescriptor::flag_name(),
+      || None,
+      |perms| perms.env.query(None),
+    )?;
+    inner.env.check_all()?;
     Ok(())
   }
 
   #[inline(always)]
   pub fn check_sys_all(&self) -> Result<(), PermissionCheckError> {
-    self.inner.lock().sys.check_all()?;
+    let mut inner = self.lock();
+    inner.packages.check(
+      SysDescriptor::flag_name(),
+      || None,
+      |perms| perms.sys.query(None),
+    )?;
+    inner.sys.check_all()?;
     Ok(())
   }
 
   #[inline(always)]
   pub fn check_ffi_all(&self) -> Result<(), PermissionCheckError> {
-    self.inner.lock().ffi.check_all()?;
+    let mut inner = self.lock();
+    inner.packages.check(
+      FfiQueryDescriptor::flag_name(),
+      || None,
+      |perms| perms.ffi.query(None),
+    )?;
+    inner.ffi.check_all()?;
     Ok(())
   }
 
@@ -4506,7 +5288,7 @@ impl PermissionsContainer {
     &self,
     context_path: &Path,
   ) -> Result<(), PermissionCheckError> {
-    let inner = self.inner.lock();
+    let inner = self.lock();
     if inner.all_granted() {
       Ok(())
     } else {
@@ -4681,7 +5463,11 @@ impl PermissionsContainer {
     url: &Url,
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
+    if !inner.packages.is_empty() {
+      let desc = self.descriptor_parser.parse_net_descriptor_from_url(url)?;
+      check_package_scopes_for_net(&inner, &desc)?;
+    }
     audit_and_skip_check_if_is_permission_fully_granted!(
       inner.net,
       NetDescriptor::flag_name(),
@@ -4698,7 +5484,12 @@ impl PermissionsContainer {
     host: &(T, Option<u16>),
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
+    if !inner.packages.is_empty() {
+      let hostname = Host::parse_for_query(host.0.as_ref())?;
+      let desc = NetDescriptor(hostname, host.1.map(Into::into));
+      check_package_scopes_for_net(&inner, &desc)?;
+    }
     let inner = &mut inner.net;
     audit_and_skip_check_if_is_permission_fully_granted!(
       inner,
@@ -4727,7 +5518,7 @@ impl PermissionsContainer {
     // it, so the API name is only here to keep call sites uniform
     _api_name: &str,
   ) -> Result<(), PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
     let desc = NetDescriptor(Host::Ip(*resolved_ip), Some(port.into()));
     inner.net.check_resolved_ip_deny(&desc)?;
     Ok(())
@@ -4744,7 +5535,7 @@ impl PermissionsContainer {
     port: u16,
     _api_name: &str,
   ) -> Result<(), PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
     let desc = ImportDescriptor(NetDescriptor(
       Host::Ip(*resolved_ip),
       Some(port.into()),
@@ -4767,7 +5558,7 @@ impl PermissionsContainer {
     &self,
     url: &Url,
   ) -> Result<(), PermissionCheckError> {
-    if !self.inner.lock().import.has_ip_deny_rules() {
+    if !self.lock().import.has_ip_deny_rules() {
       return Ok(());
     }
     // an IP literal was already compared against the deny rules directly
@@ -4783,7 +5574,7 @@ impl PermissionsContainer {
     if ips.is_empty() {
       return Ok(());
     }
-    let inner = self.inner.lock();
+    let inner = self.lock();
     for ip in ips {
       let desc =
         ImportDescriptor(NetDescriptor(Host::Ip(ip), Some(port.into())));
@@ -4827,13 +5618,17 @@ impl PermissionsContainer {
     port: u32,
     api_name: &str,
   ) -> Result<(), PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
+    check_package_scopes_for_net(
+      &inner,
+      &NetDescriptor(Host::Vsock(cid), Some(port.into())),
+    )?;
     audit_and_skip_check_if_is_permission_fully_granted!(
       inner.net,
       NetDescriptor::flag_name(),
       format!("{cid}:{port}")
     );
-    let desc = NetDescriptor(Host::Vsock(cid), Some(port));
+    let desc = NetDescriptor(Host::Vsock(cid), Some(port.into()));
     inner.net.check(&desc, Some(api_name))?;
     Ok(())
   }
@@ -4856,7 +5651,11 @@ impl PermissionsContainer {
     path: &Path,
     api_name: Option<&str>,
   ) -> Result<(), PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
+    check_package_scopes_for_net(
+      &inner,
+      &NetDescriptor(Host::UnixSocket(path.to_path_buf()), None),
+    )?;
     audit_and_skip_check_if_is_permission_fully_granted!(
       inner.net,
       NetDescriptor::flag_name(),
@@ -4872,7 +5671,8 @@ impl PermissionsContainer {
     &mut self,
     path: Cow<'a, Path>,
   ) -> Result<Cow<'a, Path>, PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
+    self.check_package_scopes_for_ffi(&inner, Some(path.as_ref()))?;
     let inner = &mut inner.ffi;
     if inner.is_allow_all() {
       write_audit(FfiQueryDescriptor::flag_name(), &path);
@@ -4889,7 +5689,8 @@ impl PermissionsContainer {
   pub fn check_ffi_partial_no_path(
     &mut self,
   ) -> Result<(), PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
+    self.check_package_scopes_for_ffi(&inner, None)?;
     let inner = &mut inner.ffi;
     if !inner.is_allow_all() {
       inner.check_partial(None)?;
@@ -4905,7 +5706,8 @@ impl PermissionsContainer {
     &mut self,
     path: Cow<'a, Path>,
   ) -> Result<Cow<'a, Path>, PermissionCheckError> {
-    let mut inner = self.inner.lock();
+    let mut inner = self.lock();
+    self.check_package_scopes_for_ffi(&inner, Some(path.as_ref()))?;
     let inner = &mut inner.ffi;
     if inner.is_allow_all() {
       write_audit(FfiQueryDescriptor::flag_name(), &path);
@@ -4924,7 +5726,7 @@ impl PermissionsContainer {
     &self,
     path: Option<&str>,
   ) -> Result<PermissionState, PathResolveError> {
-    let inner = self.inner.lock();
+    let inner = self.lock();
     let permission = &inner.read;
     if permission.is_allow_all() {
       return Ok(PermissionState::Granted);
@@ -4951,7 +5753,7 @@ impl PermissionsContainer {
     &self,
     path: Option<&str>,
   ) -> Result<PermissionState, PathResolveError> {
-    let inner = self.inner.lock();
+    let inner = self.lock();
     let permission = &inner.write;
     if permission.is_allow_all() {
       return Ok(PermissionState::Granted);
@@ -4978,7 +5780,7 @@ impl PermissionsContainer {
     &self,
     host: Option<&str>,
   ) -> Result<PermissionState, NetDescriptorParseError> {
-    let inner = self.inner.lock();
+    let inner = self.lock();
     let permission = &inner.net;
     if permission.is_allow_all() {
       return Ok(PermissionState::Granted);
@@ -4996,7 +5798,7 @@ impl PermissionsContainer {
 
   #[inline(always)]
   pub fn query_env(&self, var: Option<&str>) -> PermissionState {
-    let inner = self.inner.lock();
+    let inner = self.lock();
     let permission = &inner.env;
     if permission.is_allow_all() {
       return PermissionState::Granted;
@@ -5009,7 +5811,7 @@ impl PermissionsContainer {
     &self,
     kind: Option<&str>,
   ) -> Result<PermissionState, SysDescriptorParseError> {
-    let inner = self.inner.lock();
+    let inner = self.lock();
     let permission = &inner.sys;
     if permission.is_allow_all() {
       return Ok(PermissionState::Granted);
@@ -5029,7 +5831,7 @@ impl PermissionsContainer {
     &self,
     cmd: Option<&str>,
   ) -> Result<PermissionState, RunDescriptorParseError> {
-    let inner = self.inner.lock();
+    let inner = self.lock();
     let permission = &inner.run;
     if permission.is_allow_all() {
       return Ok(PermissionState::Granted);
@@ -5049,7 +5851,7 @@ impl PermissionsContainer {
     &self,
     path: Option<&str>,
   ) -> Result<PermissionState, PathResolveError> {
-    let inner = self.inner.lock();
+    let inner = self.lock();
     let permission = &inner.ffi;
     if permission.is_allow_all() {
       return Ok(PermissionState::Granted);
@@ -5076,7 +5878,7 @@ impl PermissionsContainer {
     &self,
     host: Option<&str>,
   ) -> Result<PermissionState, NetDescriptorParseError> {
-    let inner = self.inner.lock();
+    let inner = self.lock();
     let permission = &inner.import;
     if permission.is_allow_all() {
       return Ok(PermissionState::Granted);
@@ -5102,7 +5904,7 @@ impl PermissionsContainer {
     path: Option<&str>,
   ) -> Result<PermissionState, PathResolveError> {
     Ok(
-      self.inner.lock().read.revoke(
+      self.lock().read.revoke(
         path
           .map(|path| {
             Ok::<_, PathResolveError>(
@@ -5124,7 +5926,7 @@ impl PermissionsContainer {
     path: Option<&str>,
   ) -> Result<PermissionState, PathResolveError> {
     Ok(
-      self.inner.lock().write.revoke(
+      self.lock().write.revoke(
         path
           .map(|path| {
             Ok::<_, PathResolveError>(
@@ -5146,7 +5948,7 @@ impl PermissionsContainer {
     host: Option<&str>,
   ) -> Result<PermissionState, NetDescriptorParseError> {
     Ok(
-      self.inner.lock().net.revoke(
+      self.lock().net.revoke(
         match host {
           None => None,
           Some(h) => Some(self.descriptor_parser.parse_net_query(h)?),
@@ -5158,7 +5960,7 @@ impl PermissionsContainer {
 
   #[inline(always)]
   pub fn revoke_env(&self, var: Option<&str>) -> PermissionState {
-    self.inner.lock().env.revoke(var)
+    self.lock().env.revoke(var)
   }
 
   #[inline(always)]
@@ -5167,7 +5969,7 @@ impl PermissionsContainer {
     kind: Option<&str>,
   ) -> Result<PermissionState, SysDescriptorParseError> {
     Ok(
-      self.inner.lock().sys.revoke(
+      self.lock().sys.revoke(
         kind
           .map(|kind| self.descriptor_parser.parse_sys_descriptor(kind))
           .transpose()?
@@ -5182,7 +5984,7 @@ impl PermissionsContainer {
     cmd: Option<&str>,
   ) -> Result<PermissionState, RunDescriptorParseError> {
     Ok(
-      self.inner.lock().run.revoke(
+      self.lock().run.revoke(
         cmd
           .map(|request| self.descriptor_parser.parse_run_query(request))
           .transpose()?
@@ -5197,7 +5999,7 @@ impl PermissionsContainer {
     path: Option<&str>,
   ) -> Result<PermissionState, PathResolveError> {
     Ok(
-      self.inner.lock().ffi.revoke(
+      self.lock().ffi.revoke(
         path
           .map(|path| {
             Ok::<_, PathResolveError>(
@@ -5219,7 +6021,7 @@ impl PermissionsContainer {
     host: Option<&str>,
   ) -> Result<PermissionState, NetDescriptorParseError> {
     Ok(
-      self.inner.lock().import.revoke(
+      self.lock().import.revoke(
         match host {
           None => None,
           Some(h) => {
@@ -5239,7 +6041,7 @@ impl PermissionsContainer {
     path: Option<&str>,
   ) -> Result<PermissionState, PathResolveError> {
     Ok(
-      self.inner.lock().read.request(
+      self.lock().read.request(
         path
           .map(|path| {
             Ok::<_, PathResolveError>(
@@ -5261,7 +6063,7 @@ impl PermissionsContainer {
     path: Option<&str>,
   ) -> Result<PermissionState, PathResolveError> {
     Ok(
-      self.inner.lock().write.request(
+      self.lock().write.request(
         path
           .map(|path| {
             Ok::<_, PathResolveError>(
@@ -5283,7 +6085,7 @@ impl PermissionsContainer {
     host: Option<&str>,
   ) -> Result<PermissionState, NetDescriptorParseError> {
     Ok(
-      self.inner.lock().net.request(
+      self.lock().net.request(
         match host {
           None => None,
           Some(h) => Some(self.descriptor_parser.parse_net_query(h)?),
@@ -5295,7 +6097,7 @@ impl PermissionsContainer {
 
   #[inline(always)]
   pub fn request_env(&self, var: Option<&str>) -> PermissionState {
-    self.inner.lock().env.request(var)
+    self.lock().env.request(var)
   }
 
   #[inline(always)]
@@ -5304,7 +6106,7 @@ impl PermissionsContainer {
     kind: Option<&str>,
   ) -> Result<PermissionState, SysDescriptorParseError> {
     Ok(
-      self.inner.lock().sys.request(
+      self.lock().sys.request(
         kind
           .map(|kind| self.descriptor_parser.parse_sys_descriptor(kind))
           .transpose()?
@@ -5319,7 +6121,7 @@ impl PermissionsContainer {
     cmd: Option<&str>,
   ) -> Result<PermissionState, RunDescriptorParseError> {
     Ok(
-      self.inner.lock().run.request(
+      self.lock().run.request(
         cmd
           .map(|request| self.descriptor_parser.parse_run_query(request))
           .transpose()?
@@ -5334,7 +6136,7 @@ impl PermissionsContainer {
     path: Option<&str>,
   ) -> Result<PermissionState, PathResolveError> {
     Ok(
-      self.inner.lock().ffi.request(
+      self.lock().ffi.request(
         path
           .map(|path| {
             Ok::<_, PathResolveError>(
@@ -5356,7 +6158,7 @@ impl PermissionsContainer {
     host: Option<&str>,
   ) -> Result<PermissionState, NetDescriptorParseError> {
     Ok(
-      self.inner.lock().import.request(
+      self.lock().import.request(
         match host {
           None => None,
           Some(h) => {
@@ -5890,6 +6692,82 @@ mod tests {
     }
   }
 
+  struct DenyBroker;
+
+  impl broker::PermissionBroker for DenyBroker {
+    fn check(
+      &self,
+      _request: broker::BrokerRequest,
+    ) -> broker::BrokerDecisionFuture {
+      Box::pin(async { BrokerResponse::Deny { message: None }.into() })
+    }
+  }
+
+  #[test]
+  fn broker_applies_to_its_container_and_children() {
+    let parser = Arc::new(TestPermissionDescriptorParser);
+    let plain = PermissionsContainer::allow_all(parser.clone());
+    let brokered = PermissionsContainer::allow_all(parser);
+    // ex. the clone given to the module loader before the worker is created
+    let cloned_before = brokered.clone();
+    let deep_cloned_before = brokered.deep_clone();
+    brokered.set_broker(Arc::new(DenyBroker));
+
+    assert!(brokered.check_env("HOME").is_err());
+    assert!(plain.check_env("HOME").is_ok());
+    assert!(cloned_before.check_env("HOME").is_err());
+    assert!(deep_cloned_before.check_env("HOME").is_ok());
+    assert!(brokered.clone().check_env("HOME").is_err());
+    assert!(brokered.deep_clone().check_env("HOME").is_err());
+    let child = brokered
+      .create_child_permissions(ChildPermissionsArg::inherit())
+      .unwrap();
+    assert!(child.check_env("HOME").is_err());
+  }
+
+  #[test]
+  fn quotas_are_consumed_where_access_is_used() {
+    let perms = Permissions::from_options(
+      &TestPermissionDescriptorParser,
+      &PermissionsOptions {
+        allow_net: Some(vec![]),
+        allow_run: Some(vec![]),
+        quota_net: Some(svec!["1"]),
+        quota_run: Some(svec!["1"]),
+        quota_write: Some(svec!["4"]),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    let mut perms = PermissionsContainer::new(
+      Arc::new(TestPermissionDescriptorParser),
+      perms,
+    );
+
+    // checking access doesn't use up the quota, opening a connection does
+    assert!(perms.check_net(&("example.com", Some(443)), "api").is_ok());
+    assert!(perms.check_net(&("example.com", Some(443)), "api").is_ok());
+    assert!(perms.consume_net_quota(&("example.com", Some(443))).is_ok());
+    assert!(
+      perms
+        .consume_net_quota(&("example.com", Some(443)))
+        .is_err()
+    );
+
+    // spawns count even when every program may be run
+    let cmd = RunQueryDescriptor::Name("deno".to_string());
+    assert!(perms.query_run_all("api"));
+    assert!(perms.consume_run_quota(&cmd).is_ok());
+    assert!(perms.consume_run_quota(&cmd).is_err());
+
+    // bytes that weren't written are given back
+    let path = Path::new("/tmp/out.txt");
+    assert!(perms.consume_write_quota(path, 4).is_ok());
+    assert!(perms.consume_write_quota(path, 1).is_err());
+    perms.release_write_quota(path, 4);
+    assert!(perms.consume_write_quota(path, 4).is_ok());
+  }
+
   #[test]
   fn deep_clone_without_prompt_preserves_state_without_prompting() {
     set_prompter(Box::new(TestPrompter));
@@ -6189,7 +7067,7 @@ mod tests {
 
     for (host, port, is_ok) in domain_tests {
       let host = Host::parse_for_query(host).unwrap();
-      let descriptor = NetDescriptor(host, Some(port));
+      let descriptor = NetDescriptor(host, Some(NetPort::Single(port)));
       assert_eq!(
         is_ok,
         perms.net.check(&descriptor, None).is_ok(),
@@ -6235,7 +7113,7 @@ mod tests {
 
     for (host_str, port) in domain_tests {
       let host = Host::parse_for_query(host_str).unwrap();
-      let descriptor = NetDescriptor(host, Some(port));
+      let descriptor = NetDescriptor(host, Some(NetPort::Single(port)));
       assert!(
         perms.net.check(&descriptor, None).is_ok(),
         "expected {host_str}:{port} to pass"
@@ -6280,7 +7158,7 @@ mod tests {
 
     for (host_str, port) in domain_tests {
       let host = Host::parse_for_query(host_str).unwrap();
-      let descriptor = NetDescriptor(host, Some(port));
+      let descriptor = NetDescriptor(host, Some(NetPort::Single(port)));
       assert!(
         perms.net.check(&descriptor, None).is_err(),
         "expected {host_str}:{port} to fail"
@@ -6308,7 +7186,7 @@ mod tests {
     // The resolved IP 127.0.0.1 should be denied regardless of original
     // hostname.
     let denied_ip = std::net::IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
-    let desc = NetDescriptor(Host::Ip(denied_ip), Some(12345));
+    let desc = NetDescriptor(Host::Ip(denied_ip), Some(NetPort::Single(12345)));
     assert!(
       perms.net.check_resolved_ip_deny(&desc).is_err(),
       "resolved 127.0.0.1 should be denied"
@@ -6316,7 +7194,8 @@ mod tests {
 
     // A different IP should not be denied.
     let allowed_ip = std::net::IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1));
-    let desc = NetDescriptor(Host::Ip(allowed_ip), Some(12345));
+    let desc =
+      NetDescriptor(Host::Ip(allowed_ip), Some(NetPort::Single(12345)));
     assert!(
       perms.net.check_resolved_ip_deny(&desc).is_ok(),
       "resolved 192.168.1.1 should not be denied"
@@ -6342,7 +7221,7 @@ mod tests {
 
     // 127.0.0.1 falls within the 127.0.0.0/8 subnet — should be denied.
     let denied_ip = std::net::IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
-    let desc = NetDescriptor(Host::Ip(denied_ip), Some(8000));
+    let desc = NetDescriptor(Host::Ip(denied_ip), Some(NetPort::Single(8000)));
     assert!(
       perms.net.check_resolved_ip_deny(&desc).is_err(),
       "resolved 127.0.0.1 should be denied by 127.0.0.0/8 subnet rule"
@@ -6350,7 +7229,7 @@ mod tests {
 
     // 127.1.2.3 also falls within 127.0.0.0/8 — should be denied.
     let denied_ip2 = std::net::IpAddr::V4(Ipv4Addr::new(127, 1, 2, 3));
-    let desc = NetDescriptor(Host::Ip(denied_ip2), Some(9000));
+    let desc = NetDescriptor(Host::Ip(denied_ip2), Some(NetPort::Single(9000)));
     assert!(
       perms.net.check_resolved_ip_deny(&desc).is_err(),
       "resolved 127.1.2.3 should be denied by 127.0.0.0/8 subnet rule"
@@ -6358,7 +7237,7 @@ mod tests {
 
     // 192.168.1.1 is outside the subnet — should not be denied.
     let allowed_ip = std::net::IpAddr::V4(Ipv4Addr::new(192, 168, 1, 1));
-    let desc = NetDescriptor(Host::Ip(allowed_ip), Some(8000));
+    let desc = NetDescriptor(Host::Ip(allowed_ip), Some(NetPort::Single(8000)));
     assert!(
       perms.net.check_resolved_ip_deny(&desc).is_ok(),
       "resolved 192.168.1.1 should not be denied by 127.0.0.0/8 subnet rule"
@@ -6668,12 +7547,12 @@ mod tests {
       assert_eq!(perms1.net.query(None), PermissionState::Granted);
       assert_eq!(perms1.net.query(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), None))), PermissionState::Granted);
       assert_eq!(perms2.net.query(None), PermissionState::Prompt);
-      assert_eq!(perms2.net.query(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(8000)))), PermissionState::Granted);
+      assert_eq!(perms2.net.query(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(NetPort::Single(8000))))), PermissionState::Granted);
       assert_eq!(perms3.net.query(None), PermissionState::Prompt);
-      assert_eq!(perms3.net.query(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(8000)))), PermissionState::Denied);
+      assert_eq!(perms3.net.query(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(NetPort::Single(8000))))), PermissionState::Denied);
       assert_eq!(perms4.net.query(None), PermissionState::GrantedPartial);
-      assert_eq!(perms4.net.query(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(8000)))), PermissionState::Denied);
-      assert_eq!(perms4.net.query(Some(&NetDescriptor(Host::must_parse("192.168.0.1"), Some(8000)))), PermissionState::Granted);
+      assert_eq!(perms4.net.query(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(NetPort::Single(8000))))), PermissionState::Denied);
+      assert_eq!(perms4.net.query(Some(&NetDescriptor(Host::must_parse("192.168.0.1"), Some(NetPort::Single(8000))))), PermissionState::Granted);
       assert_eq!(perms1.env.query(None), PermissionState::Granted);
       assert_eq!(perms1.env.query(Some("HOME")), PermissionState::Granted);
       assert_eq!(perms2.env.query(None), PermissionState::Prompt);
@@ -6709,10 +7588,10 @@ mod tests {
       assert_eq!(perms4.run.query(Some(&deno_run_query)), PermissionState::Denied);
       assert_eq!(perms4.run.query(Some(&node_run_query)), PermissionState::Granted);
       assert_eq!(perms3.import.query(None), PermissionState::Prompt);
-      assert_eq!(perms3.import.query(Some(&ImportDescriptor(NetDescriptor(Host::must_parse("example.com"), Some(443))))), PermissionState::Denied);
+      assert_eq!(perms3.import.query(Some(&ImportDescriptor(NetDescriptor(Host::must_parse("example.com"), Some(NetPort::Single(443)))))), PermissionState::Denied);
       assert_eq!(perms4.import.query(None), PermissionState::GrantedPartial);
-      assert_eq!(perms4.import.query(Some(&ImportDescriptor(NetDescriptor(Host::must_parse("example.com"), Some(443))))), PermissionState::Denied);
-      assert_eq!(perms4.import.query(Some(&ImportDescriptor(NetDescriptor(Host::must_parse("deno.land"), Some(443))))), PermissionState::Granted);
+      assert_eq!(perms4.import.query(Some(&ImportDescriptor(NetDescriptor(Host::must_parse("example.com"), Some(NetPort::Single(443)))))), PermissionState::Denied);
+      assert_eq!(perms4.import.query(Some(&ImportDescriptor(NetDescriptor(Host::must_parse("deno.land"), Some(NetPort::Single(443)))))), PermissionState::Granted);
     };
     #[rustfmt::skip]
     {
@@ -6813,7 +7692,7 @@ mod tests {
       prompt_value.set(true);
       assert_eq!(perms.net.request(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), None))), PermissionState::Granted);
       prompt_value.set(false);
-      assert_eq!(perms.net.request(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(8000)))), PermissionState::Granted);
+      assert_eq!(perms.net.request(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(NetPort::Single(8000))))), PermissionState::Granted);
       prompt_value.set(true);
       assert_eq!(perms.env.request(Some("HOME")), PermissionState::Granted);
       assert_eq!(perms.env.query(None), PermissionState::Prompt);
@@ -6882,9 +7761,9 @@ mod tests {
       assert_eq!(perms.ffi.revoke(Some(&ffi_query("/foo/bar"))), PermissionState::Prompt);
       assert_eq!(perms.ffi.query(Some(&ffi_query("/foo"))), PermissionState::Prompt);
       assert_eq!(perms.ffi.query(Some(&ffi_query("/foo/baz"))), PermissionState::Granted);
-      assert_eq!(perms.net.revoke(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(9000)))), PermissionState::Prompt);
+      assert_eq!(perms.net.revoke(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(NetPort::Single(9000))))), PermissionState::Prompt);
       assert_eq!(perms.net.query(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), None))), PermissionState::Prompt);
-      assert_eq!(perms.net.query(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(8000)))), PermissionState::Granted);
+      assert_eq!(perms.net.query(Some(&NetDescriptor(Host::must_parse("127.0.0.1"), Some(NetPort::Single(8000))))), PermissionState::Granted);
       assert_eq!(perms.env.revoke(Some("HOME")), PermissionState::Prompt);
       assert_eq!(perms.env.revoke(Some("hostname")), PermissionState::Prompt);
       let run_query = RunQueryDescriptor::Path(PathQueryDescriptor::new_known_absolute(Cow::Owned(PathBuf::from("/deno"))).with_requested("deno".to_string()));
@@ -6940,7 +7819,10 @@ mod tests {
       perms
         .net
         .check(
-          &NetDescriptor(Host::must_parse("127.0.0.1"), Some(8000)),
+          &NetDescriptor(
+            Host::must_parse("127.0.0.1"),
+            Some(NetPort::Single(8000))
+          ),
           None
         )
         .is_ok()
@@ -6950,7 +7832,10 @@ mod tests {
       perms
         .net
         .check(
-          &NetDescriptor(Host::must_parse("127.0.0.1"), Some(8000)),
+          &NetDescriptor(
+            Host::must_parse("127.0.0.1"),
+            Some(NetPort::Single(8000))
+          ),
           None
         )
         .is_ok()
@@ -6959,7 +7844,10 @@ mod tests {
       perms
         .net
         .check(
-          &NetDescriptor(Host::must_parse("127.0.0.1"), Some(8001)),
+          &NetDescriptor(
+            Host::must_parse("127.0.0.1"),
+            Some(NetPort::Single(8001))
+          ),
           None
         )
         .is_err()
@@ -6974,7 +7862,10 @@ mod tests {
       perms
         .net
         .check(
-          &NetDescriptor(Host::must_parse("deno.land"), Some(8000)),
+          &NetDescriptor(
+            Host::must_parse("deno.land"),
+            Some(NetPort::Single(8000))
+          ),
           None
         )
         .is_err()
@@ -7097,7 +7988,10 @@ mod tests {
       perms
         .net
         .check(
-          &NetDescriptor(Host::must_parse("127.0.0.1"), Some(8000)),
+          &NetDescriptor(
+            Host::must_parse("127.0.0.1"),
+            Some(NetPort::Single(8000))
+          ),
           None
         )
         .is_err()
@@ -7107,7 +8001,10 @@ mod tests {
       perms
         .net
         .check(
-          &NetDescriptor(Host::must_parse("127.0.0.1"), Some(8000)),
+          &NetDescriptor(
+            Host::must_parse("127.0.0.1"),
+            Some(NetPort::Single(8000))
+          ),
           None
         )
         .is_err()
@@ -7116,7 +8013,10 @@ mod tests {
       perms
         .net
         .check(
-          &NetDescriptor(Host::must_parse("127.0.0.1"), Some(8001)),
+          &NetDescriptor(
+            Host::must_parse("127.0.0.1"),
+            Some(NetPort::Single(8001))
+          ),
           None
         )
         .is_ok()
@@ -7125,7 +8025,10 @@ mod tests {
       perms
         .net
         .check(
-          &NetDescriptor(Host::must_parse("deno.land"), Some(8000)),
+          &NetDescriptor(
+            Host::must_parse("deno.land"),
+            Some(NetPort::Single(8000))
+          ),
           None
         )
         .is_ok()
@@ -7135,7 +8038,10 @@ mod tests {
       perms
         .net
         .check(
-          &NetDescriptor(Host::must_parse("127.0.0.1"), Some(8001)),
+          &NetDescriptor(
+            Host::must_parse("127.0.0.1"),
+            Some(NetPort::Single(8001))
+          ),
           None
         )
         .is_ok()
@@ -7144,7 +8050,10 @@ mod tests {
       perms
         .net
         .check(
-          &NetDescriptor(Host::must_parse("deno.land"), Some(8000)),
+          &NetDescriptor(
+            Host::must_parse("deno.land"),
+            Some(NetPort::Single(8000))
+          ),
           None
         )
         .is_ok()
@@ -8224,7 +9133,10 @@ mod tests {
       ),
       (
         "deno.land:8000",
-        Some(NetDescriptor(Host::Fqdn(fqdn!("deno.land")), Some(8000))),
+        Some(NetDescriptor(
+          Host::Fqdn(fqdn!("deno.land")),
+          Some(NetPort::Single(8000)),
+        )),
       ),
       ("*.deno.land", None),
       ("deno.land:", None),
@@ -8244,7 +9156,7 @@ mod tests {
         "1.1.1.1:8000",
         Some(NetDescriptor(
           Host::Ip(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1))),
-          Some(8000),
+          Some(NetPort::Single(8000)),
         )),
       ),
       ("::", None),
@@ -8266,7 +9178,7 @@ mod tests {
         "[::1]:443",
         Some(NetDescriptor(
           Host::Ip(IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1))),
-          Some(443),
+          Some(NetPort::Single(443)),
         )),
       ),
       ("", None),
@@ -8276,14 +9188,14 @@ mod tests {
         "[fe80::1%18]:1234",
         Some(NetDescriptor(
           Host::Ip(IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1))),
-          Some(1234),
+          Some(NetPort::Single(1234)),
         )),
       ),
       (
         "[fe80::1%eth0]:8080",
         Some(NetDescriptor(
           Host::Ip(IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1))),
-          Some(8080),
+          Some(NetPort::Single(8080)),
         )),
       ),
     ];
@@ -8310,7 +9222,10 @@ mod tests {
       ),
       (
         "deno.land:8000",
-        Some(NetDescriptor(Host::Fqdn(fqdn!("deno.land")), Some(8000))),
+        Some(NetDescriptor(
+          Host::Fqdn(fqdn!("deno.land")),
+          Some(NetPort::Single(8000)),
+        )),
       ),
       (
         "*.deno.land",
@@ -8336,7 +9251,7 @@ mod tests {
         "1.1.1.1:8000",
         Some(NetDescriptor(
           Host::Ip(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1))),
-          Some(8000),
+          Some(NetPort::Single(8000)),
         )),
       ),
       ("::", None),
@@ -8358,7 +9273,7 @@ mod tests {
         "[::1]:443",
         Some(NetDescriptor(
           Host::Ip(IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1))),
-          Some(443),
+          Some(NetPort::Single(443)),
         )),
       ),
       ("", None),
@@ -8368,14 +9283,14 @@ mod tests {
         "[fe80::1%18]:1234",
         Some(NetDescriptor(
           Host::Ip(IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1))),
-          Some(1234),
+          Some(NetPort::Single(1234)),
         )),
       ),
       (
         "[fe80::1%eth0]:8080",
         Some(NetDescriptor(
           Host::Ip(IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1))),
-          Some(8080),
+          Some(NetPort::Single(8080)),
         )),
       ),
       // Unix socket rules are lexically normalized at parse time (`.`/`..`
@@ -8399,6 +9314,55 @@ mod tests {
       ),
       ("unix:", None),
       ("unix:relative.sock", None),
+      // port ranges and lists
+      (
+        "deno.land:8000-8100",
+        Some(NetDescriptor(
+          Host::Fqdn(fqdn!("deno.land")),
+          Some(NetPort::Range(8000, 8100)),
+        )),
+      ),
+      (
+        "deno.land:8000-8000",
+        Some(NetDescriptor(
+          Host::Fqdn(fqdn!("deno.land")),
+          Some(NetPort::Single(8000)),
+        )),
+      ),
+      (
+        "*.deno.land:80,443,8000-8100",
+        Some(NetDescriptor(
+          Host::FqdnWithSubdomainWildcard(fqdn!("deno.land")),
+          Some(NetPort::List(vec![
+            NetPort::Single(80),
+            NetPort::Single(443),
+            NetPort::Range(8000, 8100),
+          ])),
+        )),
+      ),
+      (
+        "[::1]:30000-31000",
+        Some(NetDescriptor(
+          Host::Ip(IpAddr::V6(Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1))),
+          Some(NetPort::Range(30000, 31000)),
+        )),
+      ),
+      (
+        "10.0.0.0/8:80,443",
+        Some(NetDescriptor(
+          Host::IpSubnet("10.0.0.0/8".parse().unwrap()),
+          Some(NetPort::List(vec![
+            NetPort::Single(80),
+            NetPort::Single(443),
+          ])),
+        )),
+      ),
+      ("deno.land:8100-8000", None),
+      ("deno.land:8000-", None),
+      ("deno.land:-8000", None),
+      ("deno.land:80,", None),
+      ("deno.land:80,,443", None),
+      ("deno.land:8000-70000", None),
     ];
 
     for (input, expected) in cases {
@@ -8668,21 +9632,21 @@ mod tests {
     assert_eq!(
       perms.net.query(Some(&NetDescriptor(
         Host::must_parse("example.com"),
-        Some(8080)
+        Some(NetPort::Single(8080))
       ))),
       PermissionState::Granted
     );
     assert_eq!(
       perms.net.query(Some(&NetDescriptor(
         Host::must_parse("example.com"),
-        Some(443)
+        Some(NetPort::Single(443))
       ))),
       PermissionState::Granted
     );
     assert_eq!(
       perms.net.query(Some(&NetDescriptor(
         Host::must_parse("example.com"),
-        Some(20)
+        Some(NetPort::Single(20))
       ))),
       PermissionState::Denied
     );
@@ -8694,6 +9658,257 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_net_port_ranges() {
+    let parser = TestPermissionDescriptorParser;
+    let perms = Permissions::from_options(
+      &parser,
+      &PermissionsOptions {
+        allow_net: Some(svec![
+          "127.0.0.1:30000-31000",
+          "*.cluster.internal:9000,9100-9110",
+          "10.0.0.0/8:5432-5433"
+        ]),
+        deny_net: Some(svec!["127.0.0.1:30500", "db.cluster.internal:9105"]),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    let query = |host: &str| {
+      perms
+        .net
+        .query(Some(&NetDescriptor::parse_for_query(host).unwrap()))
+    };
+
+    assert_eq!(query("127.0.0.1:30000"), PermissionState::Granted);
+    assert_eq!(query("127.0.0.1:31000"), PermissionState::Granted);
+    assert_eq!(query("127.0.0.1:31001"), PermissionState::Prompt);
+    assert_eq!(query("127.0.0.1:30500"), PermissionState::Denied);
+    assert_eq!(query("127.0.0.1:30000-30499"), PermissionState::Granted);
+    assert_eq!(
+      query("127.0.0.1:30000-31000"),
+      PermissionState::GrantedPartial
+    );
+    assert_eq!(query("127.0.0.1:30000,30600"), PermissionState::Granted);
+    assert_eq!(query("127.0.0.1:30000,31001"), PermissionState::Prompt);
+    assert_eq!(query("127.0.0.1"), PermissionState::Prompt);
+    assert_eq!(query("a.cluster.internal:9000"), PermissionState::Granted);
+    assert_eq!(query("a.cluster.internal:9105"), PermissionState::Granted);
+    assert_eq!(query("a.cluster.internal:9050"), PermissionState::Prompt);
+    assert_eq!(query("db.cluster.internal:9105"), PermissionState::Denied);
+    assert_eq!(
+      query("db.cluster.internal:9100-9110"),
+      PermissionState::GrantedPartial
+    );
+    assert_eq!(query("10.1.2.3:5433"), PermissionState::Granted);
+    assert_eq!(query("10.1.2.3:5434"), PermissionState::Prompt);
+
+    assert_eq!(
+      NetDescriptor::parse_for_list("*.cluster.internal:9000,9100-9110")
+        .unwrap()
+        .to_string(),
+      "*.cluster.internal:9000,9100-9110"
+    );
+    assert_eq!(
+      NetDescriptor::parse_for_query("deno.land:443")
+        .unwrap()
+        .single_port(),
+      Some(443)
+    );
+    assert_eq!(
+      NetDescriptor::parse_for_query("deno.land:80-443")
+        .unwrap()
+        .single_port(),
+      None
+    );
+  }
+
+  #[test]
+  fn test_audit_rule() {
+    let parser = TestPermissionDescriptorParser;
+    let perms = Permissions::from_options(
+      &parser,
+      &PermissionsOptions {
+        allow_read: Some(svec!["/foo"]),
+        deny_read: Some(svec!["/foo/secret"]),
+        allow_net: Some(svec!["127.0.0.1:30000-31000"]),
+        deny_net: Some(svec!["127.0.0.1:30500"]),
+        allow_env: Some(vec![]),
+        deny_env: Some(svec!["AWS_*"]),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    let read_query = |path: &str| {
+      parser
+        .parse_path_query(Cow::Owned(PathBuf::from(path)))
+        .unwrap()
+        .into_read()
+    };
+    let rule = |decided_by: AuditDecidedBy, rule: &str| {
+      (decided_by, Some(rule.to_string()))
+    };
+
+    assert_eq!(
+      perms.read.audit_rule(Some(&read_query("/foo/bar")), true),
+      rule(AuditDecidedBy::Rule, "--allow-read=/foo")
+    );
+    assert_eq!(
+      perms
+        .read
+        .audit_rule(Some(&read_query("/foo/secret/key")), false),
+      rule(AuditDecidedBy::Rule, "--deny-read=/foo/secret")
+    );
+    // partially denied
+    assert_eq!(
+      perms.read.audit_rule(Some(&read_query("/foo")), false),
+      rule(AuditDecidedBy::Rule, "--deny-read=/foo/secret")
+    );
+    assert_eq!(
+      perms.read.audit_rule(Some(&read_query("/bar")), false),
+      (AuditDecidedBy::Default, None)
+    );
+    assert_eq!(
+      perms.net.audit_rule(
+        Some(&NetDescriptor::parse_for_query("127.0.0.1:30001").unwrap()),
+        true
+      ),
+      rule(AuditDecidedBy::Rule, "--allow-net=127.0.0.1:30000-31000")
+    );
+    assert_eq!(
+      perms.net.audit_rule(
+        Some(&NetDescriptor::parse_for_query("127.0.0.1:30500").unwrap()),
+        false
+      ),
+      rule(AuditDecidedBy::Rule, "--deny-net=127.0.0.1:30500")
+    );
+    assert_eq!(
+      perms.env.audit_rule(
+        Some(&EnvQueryDescriptor::new(Cow::Borrowed("AWS_SECRET"))),
+        false
+      ),
+      rule(AuditDecidedBy::Rule, "--deny-env=AWS_*")
+    );
+    assert_eq!(
+      perms.env.audit_rule(
+        Some(&EnvQueryDescriptor::new(Cow::Borrowed("HOME"))),
+        true
+      ),
+      rule(AuditDecidedBy::Rule, "--allow-env")
+    );
+  }
+
+  #[test]
+  fn test_broader_allow() {
+    let parser = TestPermissionDescriptorParser;
+    let mut perms = Permissions::from_options(
+      &parser,
+      &PermissionsOptions {
+        deny_read: Some(svec!["/app/public/secret"]),
+        prompt: true,
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    let read_query = |path: &str| {
+      parser
+        .parse_path_query(Cow::Owned(PathBuf::from(path)))
+        .unwrap()
+        .into_read()
+    };
+    let query = |perms: &Permissions, path: &str| {
+      perms.read.query_desc(
+        Some(&read_query(path)),
+        AllowPartial::TreatAsPartialGranted,
+      )
+    };
+
+    let broader = read_query("/app/public/index.html")
+      .broader_allow()
+      .unwrap();
+    assert_eq!(broader.key, 'd');
+    assert_eq!(
+      PathBuf::from(&broader.flag_value),
+      PathBuf::from("/app/public")
+    );
+    assert!(read_query("/index.html").broader_allow().is_none());
+
+    perms.read.insert_prompt_granted(
+      Some(&read_query("/app/public/index.html")),
+      PromptGrant::Broader,
+      Some(broader),
+    );
+    assert_eq!(
+      query(&perms, "/app/public/css/main.css"),
+      PermissionState::Granted
+    );
+    // deny rules still apply within the broader grant
+    assert_eq!(
+      query(&perms, "/app/public/secret/key.pem"),
+      PermissionState::Denied
+    );
+    assert_eq!(query(&perms, "/app/main.ts"), PermissionState::Prompt);
+
+    let net = NetDescriptor::parse_for_query("api.example.com:443").unwrap();
+    let broader = net.broader_allow().unwrap();
+    assert_eq!(broader.flag_value, "*.api.example.com:443");
+    assert_eq!(
+      broader.description,
+      "\"api.example.com\" and its subdomains on port 443"
+    );
+    perms.net.insert_prompt_granted(
+      Some(&net),
+      PromptGrant::Broader,
+      Some(broader),
+    );
+    let net = NetDescriptor::parse_for_query("v2.api.example.com:443").unwrap();
+    assert_eq!(
+      perms
+        .net
+        .query_desc(Some(&net), AllowPartial::TreatAsPartialGranted),
+      PermissionState::Granted
+    );
+    // the other ports are still prompted for
+    let net =
+      NetDescriptor::parse_for_query("v2.api.example.com:8080").unwrap();
+    assert_eq!(
+      perms
+        .net
+        .query_desc(Some(&net), AllowPartial::TreatAsPartialGranted),
+      PermissionState::Prompt
+    );
+    assert!(
+      NetDescriptor::parse_for_query("127.0.0.1:80")
+        .unwrap()
+        .broader_allow()
+        .is_none()
+    );
+
+    let env = EnvQueryDescriptor::new(Cow::Borrowed("AWS_REGION"));
+    let broader = env.broader_allow().unwrap();
+    assert_eq!(broader.flag_value, "AWS_*");
+    perms.env.insert_prompt_granted(
+      Some(&env),
+      PromptGrant::Broader,
+      Some(broader),
+    );
+    assert_eq!(
+      perms.env.query_desc(
+        Some(&EnvQueryDescriptor::new(Cow::Borrowed("AWS_PROFILE"))),
+        AllowPartial::TreatAsPartialGranted
+      ),
+      PermissionState::Granted
+    );
+    for name in ["PATH", "_PRIVATE", "TRAILING_"] {
+      assert!(
+        EnvQueryDescriptor::new(Cow::Borrowed(name))
+          .broader_allow()
+          .is_none(),
+        "{name}"
+      );
+    }
+  }
+
   #[test]
   fn test_path_ordering_same_specificity() {
     let parser = TestPermissionDescriptorParser;
@@ -10620,7 +11835,7 @@ mod tests {
         .check(
           &ImportDescriptor(NetDescriptor(
             Host::must_parse("deno.land"),
-            Some(443)
+            Some(NetPort::Single(443))
           )),
           None,
         )
@@ -10634,7 +11849,7 @@ mod tests {
         .check(
           &ImportDescriptor(NetDescriptor(
             Host::must_parse("evil.com"),
-            Some(443)
+            Some(NetPort::Single(443))
           )),
           None,
         )
@@ -10648,7 +11863,7 @@ mod tests {
         .check(
           &ImportDescriptor(NetDescriptor(
             Host::must_parse("unknown.com"),
-            Some(443)
+            Some(NetPort::Single(443))
           )),
           None,
         )
@@ -10673,7 +11888,7 @@ mod tests {
         .check(
           &ImportDescriptor(NetDescriptor(
             Host::must_parse("deno.land"),
-            Some(443)
+            Some(NetPort::Single(443))
           )),
           None,
         )
@@ -10687,7 +11902,7 @@ mod tests {
         .check(
           &ImportDescriptor(NetDescriptor(
             Host::must_parse("evil.com"),
-            Some(443)
+            Some(NetPort::Single(443))
           )),
           None,
         )
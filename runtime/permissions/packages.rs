@@ -0,0 +1,297 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! Per-package permission scopes.
+//!
+//! A scope restricts what the code of a single npm or JSR package may do on
+//! top of the permissions granted to the whole process. Access is attributed
+//! to a package when one of its modules is on the JavaScript stack of the op
+//! performing the check.
+//!
+//! The stack is captured by ops with a fixed number of frames that user code
+//! can't change (see [`crate::prompter::set_current_stacktrace`]), so
+//! embedders must enable stack traces in ops when any scope is configured.
+//! When the callers can't be known, the access must be allowed by every
+//! scope. This is the case when the stack wasn't captured (ex. for modules
+//! loaded by a dynamic import), when it only contains runtime frames (ex. an
+//! API passed directly as a timer or promise callback) or when it was cut
+//! off.
+
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use url::Url;
+
+use crate::PermissionDeniedError;
+use crate::PermissionDescriptorParser;
+use crate::PermissionState;
+use crate::Permissions;
+use crate::PermissionsFromOptionsError;
+use crate::PermissionsOptions;
+use crate::prompter::CURRENT_STACK_FRAMES;
+use crate::prompter::CURRENT_STACK_TRUNCATED;
+
+/// Maps the module of a stack frame to the package it belongs to.
+pub trait PackageResolver: Send + Sync {
+  /// Returns the package as written in the config (ex. `npm:chalk` or
+  /// `jsr:@std/fs`), or `None` if the module isn't part of a package.
+  fn resolve_package(&self, specifier: &Url) -> Option<String>;
+}
+
+static PACKAGE_RESOLVER: Lazy<Mutex<Option<Arc<dyn PackageResolver>>>> =
+  Lazy::new(|| Mutex::new(None));
+
+pub fn set_package_resolver(resolver: Arc<dyn PackageResolver>) {
+  *PACKAGE_RESOLVER.lock() = Some(resolver);
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PackagePermissions {
+  scopes: Vec<PackageScope>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct PackageScope {
+  package: String,
+  permissions: Permissions,
+}
+
+impl PackagePermissions {
+  pub fn from_options(
+    parser: &dyn PermissionDescriptorParser,
+    packages: &BTreeMap<String, PermissionsOptions>,
+  ) -> Result<Self, PermissionsFromOptionsError> {
+    let mut scopes = Vec::with_capacity(packages.len());
+    for (package, options) in packages {
+      let mut permissions = Permissions::from_options(parser, options)?;
+      // a scope can only narrow what was granted to the process
+      permissions.disable_prompting();
+      scopes.push(PackageScope {
+        package: package.clone(),
+        permissions,
+      });
+    }
+    Ok(Self { scopes })
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.scopes.is_empty()
+  }
+
+  /// Checks the scopes of every package on the current stack.
+  ///
+  /// `query` is run against the permissions of each of those scopes and must
+  /// not prompt or audit, since the process wide check takes care of that.
+  #[inline(always)]
+  pub(crate) fn check(
+    &self,
+    name: &'static str,
+    info: impl FnOnce() -> Option<String>,
+    query: impl Fn(&Permissions) -> PermissionState,
+  ) -> Result<(), PermissionDeniedError> {
+    if self.scopes.is_empty() {
+      return Ok(());
+    }
+    // when the callers are unknown, any of the packages could be making the
+    // call, so the access must be allowed for all of them
+    let packages = packages_on_current_stack();
+    for scope in &self.scopes {
+      if let Some(packages) = &packages
+        && !packages.contains(scope.package.as_str())
+      {
+        continue;
+      }
+      match query(&scope.permissions) {
+        PermissionState::Granted | PermissionState::GrantedPartial => {}
+        _ => {
+          let access = PermissionState::fmt_access(name, info().as_deref());
+          let reason = if packages.is_some() {
+            ""
+          } else {
+            " and the calling module could not be determined"
+          };
+          return Err(PermissionDeniedError {
+            custom_message: Some(format!(
+              "Requires {access}, which is not allowed for \"{}\" by the \"permissions\" config{reason}",
+              scope.package
+            )),
+            access,
+            name,
+            state: PermissionState::Denied,
+          });
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Packages with a module on the current stack, or `None` when the modules
+/// making the call are unknown.
+fn packages_on_current_stack() -> Option<HashSet<String>> {
+  let resolver = PACKAGE_RESOLVER.lock().clone()?;
+  if CURRENT_STACK_TRUNCATED.with(|truncated| truncated.get()) {
+    return None;
+  }
+  CURRENT_STACK_FRAMES.with(|frames| {
+    let frames = frames.borrow();
+    let mut user_frames = frames
+      .iter()
+      .filter(|frame| {
+        !frame.file_name.starts_with("ext:")
+          && !frame.file_name.starts_with("node:")
+      })
+      .peekable();
+    user_frames.peek()?;
+    Some(
+      user_frames
+        .filter_map(|frame| Url::parse(&frame.file_name).ok())
+        .filter_map(|specifier| resolver.resolve_package(&specifier))
+        .collect(),
+    )
+  })
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::RuntimePermissionDescriptorParser;
+  use crate::prompter::StackFrameLocation;
+  use crate::prompter::clear_current_stacktrace;
+  use crate::prompter::set_current_stacktrace;
+
+  struct TestPackageResolver;
+
+  impl PackageResolver for TestPackageResolver {
+    fn resolve_package(&self, specifier: &Url) -> Option<String> {
+      let path = specifier.path();
+      let rest = path.strip_prefix("/node_modules/")?;
+      let name = rest.split('/').next()?;
+      Some(format!("npm:{}", name))
+    }
+  }
+
+  fn frames(file_names: &[&str]) -> Vec<StackFrameLocation> {
+    file_names
+      .iter()
+      .map(|file_name| StackFrameLocation {
+        file_name: file_name.to_string(),
+        ..Default::default()
+      })
+      .collect()
+  }
+
+  #[test]
+  fn check_package_scopes() {
+    set_package_resolver(Arc::new(TestPackageResolver));
+    let parser =
+      RuntimePermissionDescriptorParser::new(sys_traits::impls::RealSys);
+    let packages = BTreeMap::from([(
+      "npm:logger".to_string(),
+      PermissionsOptions {
+        allow_env: Some(vec![]),
+        ..Default::default()
+      },
+    )]);
+    let scopes = PackagePermissions::from_options(&parser, &packages).unwrap();
+    let check_net = || {
+      scopes.check(
+        "net",
+        || Some("\"example.com\"".to_string()),
+        |perms| perms.net.query(None),
+      )
+    };
+    let check_env = || {
+      scopes.check(
+        "env",
+        || Some("\"HOME\"".to_string()),
+        |perms| perms.env.query(Some("HOME")),
+      )
+    };
+
+    // not called from the package
+    set_current_stacktrace(
+      Box::new(Vec::new),
+      frames(&["file:///app/main.ts"]),
+      false,
+    );
+    assert!(check_net().is_ok());
+
+    // called from the package, directly or through other modules
+    set_current_stacktrace(
+      Box::new(Vec::new),
+      frames(&[
+        "file:///node_modules/other/index.js",
+        "file:///node_modules/logger/index.js",
+        "file:///app/main.ts",
+      ]),
+      false,
+    );
+    let err = check_net().unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Requires net access to \"example.com\", which is not allowed for \"npm:logger\" by the \"permissions\" config"
+    );
+    assert!(check_env().is_ok());
+
+    // the callers can't be known, so every scope applies
+    let unknown_err = "Requires net access to \"example.com\", which is not allowed for \"npm:logger\" by the \"permissions\" config and the calling module could not be determined";
+    set_current_stacktrace(
+      Box::new(Vec::new),
+      frames(&["ext:deno_web/02_timers.js"]),
+      false,
+    );
+    assert_eq!(check_net().unwrap_err().to_string(), unknown_err);
+    assert!(check_env().is_ok());
+    set_current_stacktrace(
+      Box::new(Vec::new),
+      frames(&["file:///app/main.ts"]),
+      true,
+    );
+    assert_eq!(check_net().unwrap_err().to_string(), unknown_err);
+    clear_current_stacktrace();
+    assert_eq!(check_net().unwrap_err().to_string(), unknown_err);
+    assert!(check_env().is_ok());
+  }
+
+  #[test]
+  fn package_scope_applies_under_allow_run() {
+    set_package_resolver(Arc::new(TestPackageResolver));
+    let parser = Arc::new(RuntimePermissionDescriptorParser::new(
+      sys_traits::impls::RealSys,
+    ));
+    let options = PermissionsOptions {
+      allow_run: Some(vec![]),
+      packages: BTreeMap::from([(
+        "npm:logger".to_string(),
+        PermissionsOptions {
+          allow_env: Some(vec![]),
+          ..Default::default()
+        },
+      )]),
+      ..Default::default()
+    };
+    let mut permissions = crate::PermissionsContainer::new(
+      parser.clone(),
+      Permissions::from_options(parser.as_ref(), &options).unwrap(),
+    );
+
+    set_current_stacktrace(
+      Box::new(Vec::new),
+      frames(&["file:///app/main.ts"]),
+      false,
+    );
+    assert!(permissions.query_run_all("Deno.Command().spawn()"));
+
+    set_current_stacktrace(
+      Box::new(Vec::new),
+      frames(&["file:///node_modules/logger/index.js"]),
+      false,
+    );
+    assert!(!permissions.query_run_all("Deno.Command().spawn()"));
+
+    clear_current_stacktrace();
+  }
+}
@@ -1,5 +1,8 @@
 // Copyright 2018-2026 the Deno authors. MIT license.
 
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 
@@ -69,6 +72,18 @@ pub enum PromptResponse {
   Allow,
   Deny,
   AllowAll,
+  /// Allow the broader grant offered by the prompt.
+  AllowBroader,
+}
+
+/// A grant broader than the requested resource that a prompt offers, such as
+/// the directory containing a file.
+#[derive(Clone, Copy, Debug)]
+pub struct BroaderGrantOption<'a> {
+  /// The key that selects it.
+  pub key: char,
+  /// What it grants, ex. `everything in "./public"`.
+  pub description: &'a str,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -92,8 +107,46 @@ pub(crate) static MAYBE_CURRENT_STACKTRACE: Lazy<
   Mutex<Option<GetFormattedStackFn>>,
 > = Lazy::new(|| Mutex::new(None));
 
-pub fn set_current_stacktrace(get_stack: GetFormattedStackFn) {
+/// Location of a frame of the current stack.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StackFrameLocation {
+  pub file_name: String,
+  pub line_number: Option<i64>,
+  pub column_number: Option<i64>,
+}
+
+thread_local! {
+  /// Frames of the current stack, innermost first.
+  ///
+  /// Unlike the formatted stack, these are not consumed by prompts because
+  /// package scopes and the audit log look at them for every check made by
+  /// the op. They're kept per thread since each worker runs its ops on its
+  /// own thread.
+  pub(crate) static CURRENT_STACK_FRAMES: std::cell::RefCell<Vec<StackFrameLocation>> =
+    const { std::cell::RefCell::new(Vec::new()) };
+
+  /// Whether the frames of the current stack were cut off, in which case
+  /// the outermost callers are unknown.
+  pub(crate) static CURRENT_STACK_TRUNCATED: std::cell::Cell<bool> =
+    const { std::cell::Cell::new(false) };
+}
+
+pub fn set_current_stacktrace(
+  get_stack: GetFormattedStackFn,
+  frames: Vec<StackFrameLocation>,
+  truncated: bool,
+) {
   *MAYBE_CURRENT_STACKTRACE.lock() = Some(get_stack);
+  CURRENT_STACK_FRAMES.with(|current| *current.borrow_mut() = frames);
+  CURRENT_STACK_TRUNCATED.with(|current| current.set(truncated));
+}
+
+/// Forgets the current stack once the op that captured it returned, so that
+/// checks made outside of an op aren't attributed to a stale stack.
+pub fn clear_current_stacktrace() {
+  *MAYBE_CURRENT_STACKTRACE.lock() = None;
+  CURRENT_STACK_FRAMES.with(|current| current.borrow_mut().clear());
+  CURRENT_STACK_TRUNCATED.with(|current| current.set(false));
 }
 
 pub fn permission_prompt(
@@ -101,6 +154,7 @@ pub fn permission_prompt(
   flag: &str,
   api_name: Option<&str>,
   is_unary: bool,
+  broader: Option<BroaderGrantOption<'_>>,
 ) -> PromptResponse {
   if let Some(before_callback) = MAYBE_BEFORE_PROMPT_CALLBACK.lock().as_mut() {
     before_callback();
@@ -108,7 +162,7 @@ pub fn permission_prompt(
   let stack = MAYBE_CURRENT_STACKTRACE.lock().take();
   let r = PERMISSION_PROMPTER
     .lock()
-    .prompt(message, flag, api_name, is_unary, stack);
+    .prompt(message, flag, api_name, is_unary, broader, stack);
   if let Some(after_callback) = MAYBE_AFTER_PROMPT_CALLBACK.lock().as_mut() {
     after_callback();
   }
@@ -127,6 +181,49 @@ pub fn set_prompter(prompter: Box<dyn PermissionPrompter>) {
   *PERMISSION_PROMPTER.lock() = prompter;
 }
 
+/// Values granted at prompts so far, keyed by permission name. `None` when
+/// the whole permission was granted.
+static PROMPT_GRANTS: Lazy<
+  Mutex<BTreeMap<&'static str, Option<BTreeSet<String>>>>,
+> = Lazy::new(Default::default);
+
+/// Remembers access granted at a prompt, written as the value of an
+/// `--allow-*` flag, or `None` for the whole permission.
+pub(crate) fn record_prompt_grant(name: &'static str, value: Option<String>) {
+  let mut grants = PROMPT_GRANTS.lock();
+  match value {
+    Some(value) => {
+      if let Some(values) =
+        grants.entry(name).or_insert_with(|| Some(BTreeSet::new()))
+      {
+        values.insert(value);
+      }
+    }
+    None => {
+      grants.insert(name, None);
+    }
+  }
+}
+
+/// The `--allow-*` flags equivalent to everything granted at prompts so far.
+pub fn prompt_grant_flags() -> Vec<String> {
+  PROMPT_GRANTS
+    .lock()
+    .iter()
+    .map(|(name, values)| match values {
+      Some(values) => format!(
+        "--allow-{name}={}",
+        values
+          .iter()
+          .map(|v| v.as_str())
+          .collect::<Vec<_>>()
+          .join(",")
+      ),
+      None => format!("--allow-{name}"),
+    })
+    .collect()
+}
+
 /// Guards direct reads from the process terminal input.
 ///
 /// Permission prompts also read from stdin directly. Holding this lock around
@@ -150,6 +247,7 @@ pub trait PermissionPrompter: Send + Sync {
     name: &str,
     api_name: Option<&str>,
     is_unary: bool,
+    broader: Option<BroaderGrantOption<'_>>,
     get_stack: Option<GetFormattedStackFn>,
   ) -> PromptResponse;
 }
@@ -164,6 +262,7 @@ impl PermissionPrompter for DeniedPrompter {
     _name: &str,
     _api_name: Option<&str>,
     _is_unary: bool,
+    _broader: Option<BroaderGrantOption<'_>>,
     _get_stack: Option<GetFormattedStackFn>,
   ) -> PromptResponse {
     PromptResponse::Deny
@@ -419,6 +518,7 @@ impl PermissionPrompter for TtyPrompter {
     name: &str,
     api_name: Option<&str>,
     is_unary: bool,
+    broader: Option<BroaderGrantOption<'_>>,
     get_stack: Option<GetFormattedStackFn>,
   ) -> PromptResponse {
     use std::fmt::Write;
@@ -493,9 +593,16 @@ impl PermissionPrompter for TtyPrompter {
     let message = escape_control_characters(message);
     let name = escape_control_characters(name);
     let api_name = api_name.map(escape_control_characters);
+    let broader = broader.filter(|_| is_unary).map(|broader| {
+      (broader.key, escape_control_characters(broader.description))
+    });
 
     // print to stderr so that if stdout is piped this is still displayed.
-    let opts: String = if is_unary {
+    let opts: String = if let Some((key, description)) = &broader {
+      format!(
+        "[y/n/{key}/A] (y = yes, allow; n = no, deny; {key} = allow {description}; A = allow all {name} permissions)"
+      )
+    } else if is_unary {
       format!(
         "[y/n/A] (y = yes, allow; n = no, deny; A = allow all {name} permissions)"
       )
@@ -604,6 +711,13 @@ impl PermissionPrompter for TtyPrompter {
           writeln!(stderr_lock, "✅ {}", colors::bold(&msg)).unwrap();
           break PromptResponse::AllowAll;
         }
+        c if broader.as_ref().is_some_and(|(key, _)| *key == c) => {
+          clear_n_lines(&mut stderr_lock, clear_n);
+          let (_, description) = broader.as_ref().unwrap();
+          let msg = format!("Granted {name} access to {description}.");
+          writeln!(stderr_lock, "✅ {}", colors::bold(&msg)).unwrap();
+          break PromptResponse::AllowBroader;
+        }
         _ => {
           // If we don't get a recognized option try again.
           clear_n_lines(&mut stderr_lock, 1);
@@ -662,6 +776,7 @@ pub mod tests {
       _name: &str,
       _api_name: Option<&str>,
       _is_unary: bool,
+      _broader: Option<BroaderGrantOption<'_>>,
       _get_stack: Option<GetFormattedStackFn>,
     ) -> PromptResponse {
       if STUB_PROMPT_VALUE.load(Ordering::SeqCst) {
@@ -686,6 +801,20 @@ pub mod tests {
     }
   }
 
+  #[cfg(not(target_arch = "wasm32"))]
+  #[test]
+  fn prompt_grants_as_flags() {
+    record_prompt_grant("test-values", Some("b".to_string()));
+    record_prompt_grant("test-values", Some("a".to_string()));
+    record_prompt_grant("test-values", Some("a".to_string()));
+    record_prompt_grant("test-all", Some("a".to_string()));
+    record_prompt_grant("test-all", None);
+    record_prompt_grant("test-all", Some("b".to_string()));
+    let flags = prompt_grant_flags();
+    assert!(flags.contains(&"--allow-test-values=a,b".to_string()));
+    assert!(flags.contains(&"--allow-test-all".to_string()));
+  }
+
   #[cfg(not(target_arch = "wasm32"))]
   #[test]
   fn escape_control_characters_escapes_bidi_formatting_marks() {
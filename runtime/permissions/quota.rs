@@ -0,0 +1,379 @@
+// Copyright 2018-2026 the Deno authors. MIT license.
+
+//! Quotas on how much of a granted permission may be used.
+//!
+//! A quota is either a total (ex. `--quota-run=5`) or applies to the checks
+//! matching a host, path or command (ex. `--quota-net=api.example.com=100`).
+//! Usage is counted where the granted access is used rather than when it is
+//! checked: when a connection is opened or a request is sent, when bytes are
+//! written to a file and when a subprocess is spawned. It is shared by every
+//! copy of the permissions, so workers count against the quotas of the
+//! thread that created them.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use crate::DenyRunDescriptor;
+use crate::NetDescriptor;
+use crate::PermissionCheckError;
+use crate::PermissionDeniedError;
+use crate::PermissionDescriptorParser;
+use crate::PermissionState;
+use crate::PermissionsFromOptionsError;
+use crate::PermissionsOptions;
+use crate::QueryDescriptor;
+use crate::RunQueryDescriptor;
+use crate::WriteDescriptor;
+use crate::WriteQueryDescriptor;
+use crate::format_display_name;
+
+#[derive(Debug, thiserror::Error)]
+#[error(
+  "Invalid quota '{0}'. Expected <LIMIT> or <VALUE>=<LIMIT>, where <LIMIT> is a non-negative integer."
+)]
+pub struct QuotaParseError(String);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Quota<T> {
+  /// `None` for a total.
+  target: Option<T>,
+  limit: u64,
+  /// The quota as written in the options, for error messages.
+  text: String,
+}
+
+#[derive(Debug, Default)]
+struct QuotaUsage {
+  net: Vec<u64>,
+  write: Vec<u64>,
+  run: Vec<u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PermissionQuotas {
+  /// Outbound connections and requests.
+  net: Vec<Quota<NetDescriptor>>,
+  /// Bytes written.
+  write: Vec<Quota<WriteDescriptor>>,
+  /// Subprocesses spawned.
+  run: Vec<Quota<DenyRunDescriptor>>,
+  usage: Arc<Mutex<QuotaUsage>>,
+}
+
+impl PartialEq for PermissionQuotas {
+  fn eq(&self, other: &Self) -> bool {
+    self.net == other.net && self.write == other.write && self.run == other.run
+  }
+}
+
+impl Eq for PermissionQuotas {}
+
+impl PermissionQuotas {
+  pub fn from_options(
+    parser: &dyn PermissionDescriptorParser,
+    opts: &PermissionsOptions,
+  ) -> Result<Self, PermissionsFromOptionsError> {
+    let net = parse_quotas(opts.quota_net.as_deref(), |text| {
+      parser.parse_net_descriptor(text).map_err(Into::into)
+    })?;
+    let write = parse_quotas(opts.quota_write.as_deref(), |text| {
+      parser.parse_write_descriptor(text).map_err(Into::into)
+    })?;
+    let run = parse_quotas(opts.quota_run.as_deref(), |text| {
+      parser.parse_deny_run_descriptor(text).map_err(Into::into)
+    })?;
+    let usage = QuotaUsage {
+      net: vec![0; net.len()],
+      write: vec![0; write.len()],
+      run: vec![0; run.len()],
+    };
+    Ok(Self {
+      net,
+      write,
+      run,
+      usage: Arc::new(Mutex::new(usage)),
+    })
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.net.is_empty() && self.write.is_empty() && self.run.is_empty()
+  }
+
+  #[inline(always)]
+  pub(crate) fn has_net(&self) -> bool {
+    !self.net.is_empty()
+  }
+
+  #[inline(always)]
+  pub(crate) fn has_write(&self) -> bool {
+    !self.write.is_empty()
+  }
+
+  #[inline(always)]
+  pub(crate) fn has_run(&self) -> bool {
+    !self.run.is_empty()
+  }
+
+  /// Counts an outbound connection or request to `desc`.
+  pub(crate) fn consume_net(
+    &self,
+    desc: &NetDescriptor,
+  ) -> Result<(), PermissionDeniedError> {
+    if self.net.is_empty() {
+      return Ok(());
+    }
+    let mut usage = self.usage.lock();
+    consume(&self.net, &mut usage.net, 1, |target| {
+      desc.matches_allow(target)
+    })
+    .map_err(|quota| {
+      exceeded_error(
+        NetDescriptor::flag_name(),
+        format_display_name(desc.display_name()).into_owned(),
+        quota,
+      )
+    })
+  }
+
+  /// Counts a spawn of `cmd`.
+  pub(crate) fn consume_run(
+    &self,
+    cmd: &RunQueryDescriptor,
+  ) -> Result<(), PermissionDeniedError> {
+    if self.run.is_empty() {
+      return Ok(());
+    }
+    let mut usage = self.usage.lock();
+    consume(&self.run, &mut usage.run, 1, |target| {
+      cmd.matches_deny(target)
+    })
+    .map_err(|quota| {
+      exceeded_error(
+        RunQueryDescriptor::flag_name(),
+        format_display_name(cmd.display_name()).into_owned(),
+        quota,
+      )
+    })
+  }
+
+  /// Counts `bytes` written to `path`.
+  pub(crate) fn consume_write(
+    &self,
+    path: &WriteQueryDescriptor,
+    bytes: u64,
+  ) -> Result<(), PermissionDeniedError> {
+    if self.write.is_empty() {
+      return Ok(());
+    }
+    let mut usage = self.usage.lock();
+    consume(&self.write, &mut usage.write, bytes, |target| {
+      path.matches_allow(target)
+    })
+    .map_err(|quota| {
+      exceeded_error(
+        WriteQueryDescriptor::flag_name(),
+        format_display_name(path.display_name()).into_owned(),
+        quota,
+      )
+    })
+  }
+
+  /// Gives back `bytes` counted for `path` that were not written.
+  pub(crate) fn release_write(&self, path: &WriteQueryDescriptor, bytes: u64) {
+    let mut usage = self.usage.lock();
+    for (quota, used) in self.write.iter().zip(usage.write.iter_mut()) {
+      if quota.target.as_ref().is_none_or(|t| path.matches_allow(t)) {
+        *used = used.saturating_sub(bytes);
+      }
+    }
+  }
+}
+
+/// The write quotas applying to a file, so the bytes written to it can be
+/// counted without checking its path again.
+#[derive(Clone, Debug)]
+pub struct WriteQuota {
+  quotas: PermissionQuotas,
+  path: WriteQueryDescriptor<'static>,
+}
+
+impl WriteQuota {
+  pub(crate) fn new(
+    quotas: PermissionQuotas,
+    path: WriteQueryDescriptor<'static>,
+  ) -> Self {
+    Self { quotas, path }
+  }
+
+  /// Counts `bytes` about to be written.
+  pub fn consume(&self, bytes: u64) -> Result<(), PermissionCheckError> {
+    Ok(self.quotas.consume_write(&self.path, bytes)?)
+  }
+
+  /// Gives back bytes counted by [`Self::consume`] that were not written,
+  /// ex. after a partial write.
+  pub fn release(&self, bytes: u64) {
+    self.quotas.release_write(&self.path, bytes);
+  }
+}
+
+fn parse_quotas<T>(
+  items: Option<&[String]>,
+  parse_target: impl Fn(&str) -> Result<T, PermissionsFromOptionsError>,
+) -> Result<Vec<Quota<T>>, PermissionsFromOptionsError> {
+  let Some(items) = items else {
+    return Ok(Vec::new());
+  };
+  let parse_limit = |text: &str, item: &str| {
+    text
+      .parse::<u64>()
+      .map_err(|_| QuotaParseError(item.to_string()))
+  };
+  let mut quotas = Vec::with_capacity(items.len());
+  for item in items {
+    let quota = match item.rsplit_once('=') {
+      Some((target, limit)) => {
+        if target.is_empty() {
+          return Err(QuotaParseError(item.to_string()).into());
+        }
+        Quota {
+          target: Some(parse_target(target)?),
+          limit: parse_limit(limit, item)?,
+          text: item.clone(),
+        }
+      }
+      None => Quota {
+        target: None,
+        limit: parse_limit(item, item)?,
+        text: item.clone(),
+      },
+    };
+    quotas.push(quota);
+  }
+  Ok(quotas)
+}
+
+/// Adds `amount` to the usage of every matching quota, unless that would
+/// exceed one of them.
+fn consume<'a, T>(
+  quotas: &'a [Quota<T>],
+  usage: &mut [u64],
+  amount: u64,
+  matches: impl Fn(&T) -> bool,
+) -> Result<(), &'a Quota<T>> {
+  let applies =
+    |quota: &Quota<T>| quota.target.as_ref().is_none_or(|t| matches(t));
+  for (quota, used) in quotas.iter().zip(usage.iter()) {
+    if applies(quota) && used.saturating_add(amount) > quota.limit {
+      return Err(quota);
+    }
+  }
+  for (quota, used) in quotas.iter().zip(usage.iter_mut()) {
+    if applies(quota) {
+      *used += amount;
+    }
+  }
+  Ok(())
+}
+
+fn exceeded_error<T>(
+  name: &'static str,
+  info: String,
+  quota: &Quota<T>,
+) -> PermissionDeniedError {
+  let access = PermissionState::fmt_access(name, Some(&info));
+  PermissionDeniedError {
+    custom_message: Some(format!(
+      "Requires {access}, which exceeds the quota set by --quota-{name}={}",
+      quota.text
+    )),
+    access,
+    name,
+    state: PermissionState::Denied,
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use std::borrow::Cow;
+  use std::path::Path;
+  use std::path::PathBuf;
+
+  use super::*;
+  use crate::RuntimePermissionDescriptorParser;
+
+  #[test]
+  fn consume_quotas() {
+    let parser =
+      RuntimePermissionDescriptorParser::new(sys_traits::impls::RealSys);
+    let quotas = PermissionQuotas::from_options(
+      &parser,
+      &PermissionsOptions {
+        quota_net: Some(vec!["3".to_string(), "example.com=1".to_string()]),
+        quota_write: Some(vec!["/tmp/out=10".to_string()]),
+        ..Default::default()
+      },
+    )
+    .unwrap();
+    let net = |host: &str| {
+      quotas.consume_net(&NetDescriptor::parse_for_query(host).unwrap())
+    };
+
+    assert!(net("example.com:443").is_ok());
+    let err = net("example.com:443").unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "Requires net access to \"example.com:443\", which exceeds the quota set by --quota-net=example.com=1"
+    );
+    assert!(net("deno.land:443").is_ok());
+    assert!(net("deno.land:443").is_ok());
+    assert!(net("deno.land:443").is_err());
+
+    // copies share their usage
+    let copy = quotas.clone();
+    assert!(
+      copy
+        .consume_net(&NetDescriptor::parse_for_query("deno.land").unwrap())
+        .is_err()
+    );
+
+    let write = |path: &str, bytes: u64| {
+      let path = parser
+        .parse_path_query(Cow::Borrowed(Path::new(path)))
+        .unwrap()
+        .into_write();
+      quotas.consume_write(&path, bytes)
+    };
+    assert!(write("/tmp/out/a.txt", 6).is_ok());
+    assert!(write("/tmp/out/b.txt", 5).is_err());
+    assert!(write("/tmp/out/b.txt", 4).is_ok());
+    assert!(write("/tmp/other.txt", 100).is_ok());
+
+    let path = parser
+      .parse_path_query(Cow::Owned(PathBuf::from("/tmp/out/c.txt")))
+      .unwrap()
+      .into_write();
+    let quota = WriteQuota::new(quotas.clone(), path);
+    assert!(quota.consume(1).is_err());
+    quota.release(4);
+    assert!(quota.consume(4).is_ok());
+    assert!(quota.consume(1).is_err());
+  }
+
+  #[test]
+  fn invalid_quotas() {
+    let parser =
+      RuntimePermissionDescriptorParser::new(sys_traits::impls::RealSys);
+    for quota in ["", "ten", "example.com=", "=5", "example.com=-1"] {
+      let result = PermissionQuotas::from_options(
+        &parser,
+        &PermissionsOptions {
+          quota_net: Some(vec![quota.to_string()]),
+          ..Default::default()
+        },
+      );
+      assert!(result.is_err(), "{quota}");
+    }
+  }
+}
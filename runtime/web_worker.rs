@@ -11,8 +11,10 @@ use std::sync::atomic::Ordering;
 use std::task::Context;
 use std::task::Poll;
 
+use deno_cache::CacheBackendMode;
 use deno_cache::CacheImpl;
 use deno_cache::CreateCache;
+use deno_cache::InMemoryCache;
 use deno_cache::SqliteBackedCache;
 use deno_core::CancelHandle;
 use deno_core::CompiledWasmModuleStore;
@@ -48,6 +50,7 @@ use deno_napi::DenoRtNativeAddonLoaderRc;
 use deno_node::ExtNodeSys;
 use deno_node::NodeExtInitServices;
 use deno_permissions::PermissionsContainer;
+use deno_permissions::broker::PermissionBroker;
 use deno_process::NpmProcessStateProviderRc;
 use deno_terminal::colors;
 use deno_tls::RootCertStoreProvider;
@@ -405,6 +408,8 @@ pub struct WebWorkerOptions {
   /// during snapshot creation; emitted by the snapshot build script.
   pub residual_lazy_esm_sources: &'static [(&'static str, &'static str)],
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
+  /// Records the requests sent by `fetch()`, or replays their responses.
+  pub fetch_recorder: Option<Arc<deno_fetch::FetchRecorder>>,
   /// Optional isolate creation parameters, such as heap limits.
   pub create_params: Option<v8::CreateParams>,
   pub seed: Option<u64>,
@@ -423,6 +428,9 @@ pub struct WebWorkerOptions {
   pub maybe_cpu_prof_config: Option<CpuProfilerConfig>,
   pub enable_raw_imports: bool,
   pub enable_stack_trace_arg_in_ops: bool,
+  /// Decides the permission checks of this worker instead of the flags and
+  /// prompts. When `None`, the broker of the parent's permissions is used.
+  pub permission_broker: Option<Arc<dyn PermissionBroker>>,
   pub wait_for_debugger_on_start: bool,
   pub wait_for_page_wait_for_debugger: bool,
 }
@@ -497,10 +505,24 @@ impl WebWorker {
     >,
     mut options: WebWorkerOptions,
   ) -> (Self, SendableWebWorkerHandle, BootstrapOptions) {
+    // this runs on the worker's thread, so permission audit records made by
+    // its ops are attributed to it
+    deno_permissions::audit::set_current_worker_id(options.worker_id.as_u32());
+    let permission_broker = options.permission_broker.take();
+
     // Permissions: many ops depend on this
     let enable_testing_features = options.bootstrap.enable_testing_features;
 
     fn create_cache_inner(options: &WebWorkerOptions) -> Option<CreateCache> {
+      let backend_mode = CacheBackendMode::from_env();
+      if backend_mode == CacheBackendMode::Memory {
+        let create_cache_fn = || {
+          let max_size = deno_cache::max_size_from_env();
+          Ok(CacheImpl::Memory(InMemoryCache::new(max_size)))
+        };
+        return Some(CreateCache(Arc::new(create_cache_fn)));
+      }
+
       if let Ok(var) = std::env::var("DENO_CACHE_LSC_ENDPOINT") {
         let elems: Vec<_> = var.split(",").collect();
         if elems.len() == 2 {
@@ -514,7 +536,7 @@ impl WebWorker {
             let x = deno_cache::LscBackend::default();
             x.set_shard(shard.clone());
 
-            Ok(CacheImpl::Lsc(x))
+            Ok(backend_mode.wrap(CacheImpl::Lsc(x)))
           };
           #[allow(
             clippy::arc_with_non_send_sync,
@@ -528,7 +550,7 @@ impl WebWorker {
         let storage_dir = storage_dir.clone();
         let create_cache_fn = move || {
           let s = SqliteBackedCache::new(storage_dir.clone())?;
-          Ok(CacheImpl::Sqlite(s))
+          Ok(backend_mode.wrap(CacheImpl::Sqlite(s)))
         };
         return Some(CreateCache(Arc::new(create_cache_fn)));
       }
@@ -561,6 +583,7 @@ impl WebWorker {
           .clone(),
         file_fetch_handler: Rc::new(deno_fetch::FsFetchHandler),
         request_builder_hook: Some(request_builder_hook),
+        recorder: options.fetch_recorder.clone(),
         ..Default::default()
       }),
       deno_cache::deno_cache::init(create_cache),
@@ -589,6 +612,7 @@ impl WebWorker {
         )),
         deno_kv::KvConfig::builder().build(),
       ),
+      // Unlike the main worker, web workers never persist cron state.
       deno_cron::deno_cron::init(Box::new(CronHandlerImpl::create_from_env())),
       deno_napi::deno_napi::init(services.deno_rt_native_addon_loader.clone()),
       deno_http::deno_http::init(deno_http::Options {
@@ -680,7 +704,11 @@ impl WebWorker {
       let state = js_runtime.op_state();
       let mut state = state.borrow_mut();
 
-      state.put::<PermissionsContainer>(services.permissions);
+      let permissions = services.permissions;
+      if let Some(broker) = permission_broker {
+        permissions.set_broker(broker);
+      }
+      state.put::<PermissionsContainer>(permissions);
       state.put(ops::TestingFeaturesEnabled(enable_testing_features));
       state.put(services.feature_checker);
 
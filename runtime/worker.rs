@@ -11,8 +11,10 @@ use std::sync::atomic::Ordering;
 use std::time::Duration;
 use std::time::Instant;
 
+use deno_cache::CacheBackendMode;
 use deno_cache::CacheImpl;
 use deno_cache::CreateCache;
+use deno_cache::InMemoryCache;
 use deno_cache::SqliteBackedCache;
 use deno_core::CompiledWasmModuleStore;
 use deno_core::Extension;
@@ -45,6 +47,7 @@ use deno_node::ExtNodeSys;
 use deno_node::NodeExtInitServices;
 use deno_os::ExitCode;
 use deno_permissions::PermissionsContainer;
+use deno_permissions::broker::PermissionBroker;
 use deno_process::NpmProcessStateProviderRc;
 use deno_tls::RootCertStoreProvider;
 use deno_tls::TlsKeys;
@@ -290,6 +293,8 @@ pub struct WorkerOptions {
   pub create_params: Option<v8::CreateParams>,
 
   pub unsafely_ignore_certificate_errors: Option<Vec<String>>,
+  /// Records the requests sent by `fetch()`, or replays their responses.
+  pub fetch_recorder: Option<Arc<deno_fetch::FetchRecorder>>,
   pub seed: Option<u64>,
 
   // Callbacks invoked when creating new instance of WebWorker
@@ -311,6 +316,9 @@ pub struct WorkerOptions {
   pub stdio: Stdio,
   pub enable_raw_imports: bool,
   pub enable_stack_trace_arg_in_ops: bool,
+  /// Decides the permission checks of this worker instead of the flags and
+  /// prompts.
+  pub permission_broker: Option<Arc<dyn PermissionBroker>>,
 
   pub unconfigured_runtime: Option<UnconfiguredRuntime>,
 }
@@ -324,6 +332,7 @@ impl Default for WorkerOptions {
       skip_op_registration: false,
       seed: None,
       unsafely_ignore_certificate_errors: Default::default(),
+      fetch_recorder: None,
       should_break_on_first_statement: Default::default(),
       should_wait_for_inspector_session: Default::default(),
       trace_ops: Default::default(),
@@ -339,6 +348,7 @@ impl Default for WorkerOptions {
       stdio: Default::default(),
       enable_raw_imports: false,
       enable_stack_trace_arg_in_ops: false,
+      permission_broker: None,
       unconfigured_runtime: None,
     }
   }
@@ -470,7 +480,18 @@ impl MainWorker {
     >,
     mut options: WorkerOptions,
   ) -> (Self, BootstrapOptions) {
+    let permission_broker = options.permission_broker.take();
+
     fn create_cache_inner(options: &WorkerOptions) -> Option<CreateCache> {
+      let backend_mode = CacheBackendMode::from_env();
+      if backend_mode == CacheBackendMode::Memory {
+        let create_cache_fn = || {
+          let max_size = deno_cache::max_size_from_env();
+          Ok(CacheImpl::Memory(InMemoryCache::new(max_size)))
+        };
+        return Some(CreateCache(Arc::new(create_cache_fn)));
+      }
+
       if let Ok(var) = std::env::var("DENO_CACHE_LSC_ENDPOINT") {
         let elems: Vec<_> = var.split(",").collect();
         if elems.len() == 2 {
@@ -484,7 +505,7 @@ impl MainWorker {
             let x = deno_cache::LscBackend::default();
             x.set_shard(shard.clone());
 
-            Ok(CacheImpl::Lsc(x))
+            Ok(backend_mode.wrap(CacheImpl::Lsc(x)))
           };
           #[allow(
             clippy::arc_with_non_send_sync,
@@ -498,7 +519,7 @@ impl MainWorker {
         let storage_dir = storage_dir.clone();
         let create_cache_fn = move || {
           let s = SqliteBackedCache::new(storage_dir.clone())?;
-          Ok(CacheImpl::Sqlite(s))
+          Ok(backend_mode.wrap(CacheImpl::Sqlite(s)))
         };
         return Some(CreateCache(Arc::new(create_cache_fn)));
       }
@@ -559,6 +580,17 @@ impl MainWorker {
       })
     };
 
+    // Persisting local cron state needs the origin storage dir, which isn't
+    // known when the extensions are created.
+    if let Some(cron_handler) = CronHandlerImpl::persistent_from_env(
+      options.origin_storage_dir.as_deref(),
+    ) {
+      js_runtime
+        .op_state()
+        .borrow_mut()
+        .put::<Rc<dyn CronHandler>>(Rc::new(cron_handler));
+    }
+
     js_runtime
       .set_eval_context_code_cache_cbs(services.v8_code_cache.map(|cache| {
       let cache_clone = cache.clone();
@@ -626,6 +658,7 @@ impl MainWorker {
           file_fetch_handler: Rc::new(deno_fetch::FsFetchHandler),
           resolver: services.fetch_dns_resolver,
           request_builder_hook: Some(request_builder_hook),
+          recorder: options.fetch_recorder.clone(),
           ..Default::default()
         }),
         deno_cache::deno_cache::args(create_cache),
@@ -692,7 +725,11 @@ impl MainWorker {
       // executing a CJS entrypoint.
       state.put(js_runtime.inspector());
 
-      state.put::<PermissionsContainer>(services.permissions);
+      let permissions = services.permissions;
+      if let Some(broker) = permission_broker {
+        permissions.set_broker(broker);
+      }
+      state.put::<PermissionsContainer>(permissions);
       state.put(ops::TestingFeaturesEnabled(enable_testing_features));
       state.put(services.feature_checker);
     }
@@ -1327,16 +1364,36 @@ fn common_runtime(opts: CommonRuntimeOptions) -> JsRuntime {
 pub fn create_permissions_stack_trace_callback()
 -> deno_core::OpStackTraceCallback {
   Box::new(|stack: Vec<deno_core::error::JsStackFrame>| {
-    deno_permissions::prompter::set_current_stacktrace(Box::new(move || {
-      stack
-        .iter()
-        .map(|frame| {
-          deno_core::error::format_frame::<deno_core::error::NoAnsiColors>(
-            frame, None,
-          )
+    // called with an empty stack once the op returns
+    if stack.is_empty() {
+      deno_permissions::prompter::clear_current_stacktrace();
+      return;
+    }
+    let truncated = stack.len() >= deno_core::error::OP_STACK_TRACE_LIMIT;
+    let frames = stack
+      .iter()
+      .filter_map(|frame| {
+        Some(deno_permissions::prompter::StackFrameLocation {
+          file_name: frame.file_name.clone()?,
+          line_number: frame.line_number,
+          column_number: frame.column_number,
         })
-        .collect()
-    }))
+      })
+      .collect();
+    deno_permissions::prompter::set_current_stacktrace(
+      Box::new(move || {
+        stack
+          .iter()
+          .map(|frame| {
+            deno_core::error::format_frame::<deno_core::error::NoAnsiColors>(
+              frame, None,
+            )
+          })
+          .collect()
+      }),
+      frames,
+      truncated,
+    )
   }) as _
 }
 
@@ -251,7 +251,7 @@ fn permissions_prompt_allow_all() {
         "┠─ To see a stack trace for this prompt, set the DENO_TRACE_PERMISSIONS environmental variable.\r\n",
         "┠─ Learn more at: https://docs.deno.com/go/--allow-read\r\n",
         "┠─ Run again with --allow-read to bypass this prompt.\r\n",
-        "┗ Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all read permissions)",
+        "┗ Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \".\"; A = allow all read permissions)",
       ));
       console.human_delay();
       console.write_line_raw("A");
@@ -263,7 +263,7 @@ fn permissions_prompt_allow_all() {
         "┠─ To see a stack trace for this prompt, set the DENO_TRACE_PERMISSIONS environmental variable.\r\n",
         "┠─ Learn more at: https://docs.deno.com/go/--allow-write\r\n",
         "┠─ Run again with --allow-write to bypass this prompt.\r\n",
-        "┗ Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all write permissions)",
+        "┗ Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \".\"; A = allow all write permissions)",
       ));
       console.human_delay();
       console.write_line_raw("A");
@@ -275,7 +275,7 @@ fn permissions_prompt_allow_all() {
         "┠─ To see a stack trace for this prompt, set the DENO_TRACE_PERMISSIONS environmental variable.\r\n",
         "┠─ Learn more at: https://docs.deno.com/go/--allow-net\r\n",
         "┠─ Run again with --allow-net to bypass this prompt.\r\n",
-        "┗ Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all net permissions)",
+        "┗ Allow? [y/n/s/A] (y = yes, allow; n = no, deny; s = allow \"foo\" and its subdomains; A = allow all net permissions)",
       ));
       console.human_delay();
       console.write_line_raw("A");
@@ -311,7 +311,7 @@ fn permissions_prompt_allow_all() {
         "┠─ To see a stack trace for this prompt, set the DENO_TRACE_PERMISSIONS environmental variable.\r\n",
         "┠─ Learn more at: https://docs.deno.com/go/--allow-ffi\r\n",
         "┠─ Run again with --allow-ffi to bypass this prompt.\r\n",
-        "┗ Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all ffi permissions)",
+        "┗ Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \".\"; A = allow all ffi permissions)",
       ));
       console.human_delay();
       console.write_line_raw("A");
@@ -351,7 +351,7 @@ fn permissions_prompt_allow_all_2() {
       console.write_line_raw("A");
       console.expect("Granted all sys access.");
 
-      let text = console.read_until("Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all read permissions)");
+      let text = console.read_until("A = allow all read permissions)");
       // "read" permissions
       test_util::assertions::assert_wildcard_match(&text, concat!(
         "\r\n",
@@ -360,7 +360,7 @@ fn permissions_prompt_allow_all_2() {
         "┠─ To see a stack trace for this prompt, set the DENO_TRACE_PERMISSIONS environmental variable.\r\n",
         "┠─ Learn more at: https://docs.deno.com/go/--allow-read\r\n",
         "┠─ Run again with --allow-read to bypass this prompt.\r\n",
-        "┗ Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all read permissions)",
+        "┗ Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \"[WILDCARD]tests\"; A = allow all read permissions)",
       ));
       console.human_delay();
       console.write_line_raw("A");
@@ -433,7 +433,7 @@ fn permissions_cache() {
         "┠─ To see a stack trace for this prompt, set the DENO_TRACE_PERMISSIONS environmental variable.\r\n",
         "┠─ Learn more at: https://docs.deno.com/go/--allow-read\r\n",
         "┠─ Run again with --allow-read to bypass this prompt.\r\n",
-        "┗ Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all read permissions)",
+        "┗ Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \".\"; A = allow all read permissions)",
       ));
       console.human_delay();
       console.write_line_raw("y");
@@ -485,10 +485,10 @@ fn permissions_audit() {
   let file = std::fs::read_to_string(path).unwrap();
   test_util::assertions::assert_wildcard_match(
     &file,
-    r#"{"v":1,"datetime":"[WILDCARD]","permission":"sys","value":"hostname"}
-{"v":1,"datetime":"[WILDCARD]","permission":"read","value":"[WILDCARD]"}
-{"v":1,"datetime":"[WILDCARD]","permission":"write","value":"[WILDCARD]"}
-{"v":1,"datetime":"[WILDCARD]","permission":"env","value":"FOO"}
+    r#"{"v":2,"datetime":"[WILDCARD]","monotonicTime":[WILDCARD],"workerId":0,"permission":"sys","value":"hostname","decision":"granted","decidedBy":"rule","rule":"--allow-sys"}
+{"v":2,"datetime":"[WILDCARD]","monotonicTime":[WILDCARD],"workerId":0,"permission":"read","value":"[WILDCARD]","decision":"granted","decidedBy":"rule","rule":"--allow-read"}
+{"v":2,"datetime":"[WILDCARD]","monotonicTime":[WILDCARD],"workerId":0,"permission":"write","value":"[WILDCARD]","decision":"granted","decidedBy":"rule","rule":"--allow-write"}
+{"v":2,"datetime":"[WILDCARD]","monotonicTime":[WILDCARD],"workerId":0,"permission":"env","value":"FOO","decision":"granted","decidedBy":"rule","rule":"--allow-env"}
 "#,
   );
 }
@@ -510,10 +510,10 @@ fn permissions_audit_with_traces() {
   let file = std::fs::read_to_string(path).unwrap();
   test_util::assertions::assert_wildcard_match(
     &file,
-    r#"{"v":1,"datetime":"[WILDCARD]","permission":"sys","value":"hostname","stack":[WILDCARD]}
-{"v":1,"datetime":"[WILDCARD]","permission":"read","value":"[WILDCARD]","stack":[WILDCARD]}
-{"v":1,"datetime":"[WILDCARD]","permission":"write","value":"[WILDCARD]","stack":[WILDCARD]}
-{"v":1,"datetime":"[WILDCARD]","permission":"env","value":"FOO","stack":[WILDCARD]}
+    r#"{"v":2,"datetime":"[WILDCARD]","monotonicTime":[WILDCARD],"workerId":0,"permission":"sys","value":"hostname","decision":"granted","decidedBy":"rule","rule":"--allow-sys","caller":"[WILDCARD]permissions_audit.ts:[WILDCARD]","stack":[WILDCARD]}
+{"v":2,"datetime":"[WILDCARD]","monotonicTime":[WILDCARD],"workerId":0,"permission":"read","value":"[WILDCARD]","decision":"granted","decidedBy":"rule","rule":"--allow-read","caller":"[WILDCARD]permissions_audit.ts:[WILDCARD]","stack":[WILDCARD]}
+{"v":2,"datetime":"[WILDCARD]","monotonicTime":[WILDCARD],"workerId":0,"permission":"write","value":"[WILDCARD]","decision":"granted","decidedBy":"rule","rule":"--allow-write","caller":"[WILDCARD]permissions_audit.ts:[WILDCARD]","stack":[WILDCARD]}
+{"v":2,"datetime":"[WILDCARD]","monotonicTime":[WILDCARD],"workerId":0,"permission":"env","value":"FOO","decision":"granted","decidedBy":"rule","rule":"--allow-env","caller":"[WILDCARD]permissions_audit.ts:[WILDCARD]","stack":[WILDCARD]}
 "#,
   );
 }
@@ -1563,7 +1563,7 @@ mod permissions {
           "┠─ To see a stack trace for this prompt, set the DENO_TRACE_PERMISSIONS environmental variable.\r\n",
           "┠─ Learn more at: https://docs.deno.com/go/--allow-read\r\n",
           "┠─ Run again with --allow-read to bypass this prompt.\r\n",
-          "┗ Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all read permissions)",
+          "┗ Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \".\"; A = allow all read permissions)",
         ));
         console.human_delay();
         console.write_line_raw("y");
@@ -1573,7 +1573,7 @@ mod permissions {
           "┠─ To see a stack trace for this prompt, set the DENO_TRACE_PERMISSIONS environmental variable.\r\n",
           "┠─ Learn more at: https://docs.deno.com/go/--allow-read\r\n",
           "┠─ Run again with --allow-read to bypass this prompt.\r\n",
-          "┗ Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all read permissions)",
+          "┗ Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \".\"; A = allow all read permissions)",
         ));
         console.human_delay();
         console.write_line_raw("n");
@@ -1595,7 +1595,7 @@ mod permissions {
           "┠─ To see a stack trace for this prompt, set the DENO_TRACE_PERMISSIONS environmental variable.\r\n",
           "┠─ Learn more at: https://docs.deno.com/go/--allow-read\r\n",
           "┠─ Run again with --allow-read to bypass this prompt.\r\n",
-          "┗ Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all read permissions)",
+          "┗ Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \".\"; A = allow all read permissions)",
         ));
         console.human_delay();
         console.write_line_raw("y");
@@ -1605,7 +1605,7 @@ mod permissions {
           "┠─ To see a stack trace for this prompt, set the DENO_TRACE_PERMISSIONS environmental variable.\r\n",
           "┠─ Learn more at: https://docs.deno.com/go/--allow-read\r\n",
           "┠─ Run again with --allow-read to bypass this prompt.\r\n",
-          "┗ Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all read permissions)",
+          "┗ Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \".\"; A = allow all read permissions)",
         ));
         console.human_delay();
         console.write_line_raw("n");
@@ -2872,7 +2872,7 @@ fn stdio_streams_are_locked_in_permission_prompt() {
       console.expect(malicious_output);
       console.write_line(r#"Deno.readTextFileSync('../Cargo.toml');"#);
       // We will get a permission prompt
-      console.expect("Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all read permissions) > ");
+      console.expect("Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \"..\"; A = allow all read permissions) > ");
       // The worker is blocked, so nothing else should get written here
       console.human_delay();
       console.write_line_raw("i");
@@ -2887,12 +2887,12 @@ fn stdio_streams_are_locked_in_permission_prompt() {
         // outputs a bunch of control characters, so we instead rely on the last assertion
         // in this test that checks to ensure we didn't receive any malicious output during
         // the permission prompts
-        console.expect("Unrecognized option. Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all read permissions) >");
+        console.expect("Unrecognized option. Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \"..\"; A = allow all read permissions) >");
         console.human_delay();
         console.write_line_raw("y");
         console.expect("Granted read access to");
       } else {
-        console.expect_raw_next(format!("i{newline}\u{1b}[1A\u{1b}[0J┗ Unrecognized option. Allow? [y/n/A] (y = yes, allow; n = no, deny; A = allow all read permissions) > "));
+        console.expect_raw_next(format!("i{newline}\u{1b}[1A\u{1b}[0J┗ Unrecognized option. Allow? [y/n/d/A] (y = yes, allow; n = no, deny; d = allow everything in \"..\"; A = allow all read permissions) > "));
         console.human_delay();
         console.write_line_raw("y");
         // We ensure that nothing gets written here between the permission prompt and this text, despite the delay
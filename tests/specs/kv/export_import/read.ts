@@ -0,0 +1,25 @@
+for (const path of ["target.sqlite3", "backup.sqlite3"]) {
+  const kv = await Deno.openKv(path);
+  for await (const entry of kv.list({ prefix: [] })) {
+    console.log(entry.key, entry.value);
+  }
+  console.log(await kv.get(["settings"]).then((entry) => entry.value));
+  kv.close();
+}
+
+// Expiration times are kept.
+const target = await Deno.openKv("target.sqlite3");
+const session = await new Response(
+  target.export({ prefix: ["users", "session"] }),
+).text();
+console.log(JSON.parse(session).expire_at > Date.now());
+target.close();
+
+// Round trip through the API as well.
+const source = await Deno.openKv("source.sqlite3");
+const copy = await Deno.openKv(":memory:");
+console.log(await copy.import(source.export({ prefix: ["users"] })));
+const text = await new Response(copy.export()).text();
+console.log(text.trim().split("\n").length);
+source.close();
+copy.close();
@@ -0,0 +1,7 @@
+const kv = await Deno.openKv("source.sqlite3");
+await kv.set(["users", 1n], { name: "Ada" });
+await kv.set(["users", "bob"], new Uint8Array([1, 2, 3]));
+await kv.set(["users", "count"], new Deno.KvU64(2n));
+await kv.set(["users", "session"], "token", { expireIn: 60 * 60 * 1000 });
+await kv.set(["settings"], "not exported");
+kv.close();
@@ -0,0 +1,4 @@
+for (const name of ["a", "b", "c"]) {
+  Deno.readTextFileSync(`./data/${name}.txt`);
+}
+Deno.env.get("APP_NAME");
@@ -0,0 +1,29 @@
+import {
+  call,
+  readPublic,
+  readSecret,
+  readSecretInCallback,
+  readSecretWithoutStackTrace,
+} from "logger";
+
+function readSecretDeep(depth: number): string | undefined {
+  return depth === 0 ? Deno.env.get("SECRET") : readSecretDeep(depth - 1);
+}
+
+console.log(Deno.env.get("SECRET"));
+console.log(readPublic());
+for (
+  const read of [
+    readSecret,
+    readSecretWithoutStackTrace,
+    readSecretInCallback,
+    // the package is cut off from the bottom of the stack
+    () => call(() => readSecretDeep(300)),
+  ]
+) {
+  try {
+    console.log(await read());
+  } catch (err) {
+    console.log((err as Error).message);
+  }
+}
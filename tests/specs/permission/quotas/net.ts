@@ -0,0 +1,11 @@
+// listening doesn't count, only the connections made
+const listener = Deno.listen({ hostname: "127.0.0.1", port: 0 });
+const { port } = listener.addr;
+const conn = await Deno.connect({ hostname: "127.0.0.1", port });
+conn.close();
+try {
+  await Deno.connect({ hostname: "127.0.0.1", port });
+} catch (err) {
+  console.log((err as Error).message);
+}
+listener.close();
@@ -0,0 +1,8 @@
+// the quota applies even though every program may be run
+const command = new Deno.Command(Deno.execPath(), { args: ["--version"] });
+await command.output();
+try {
+  await command.output();
+} catch (err) {
+  console.log((err as Error).message);
+}
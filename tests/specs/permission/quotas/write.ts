@@ -0,0 +1,16 @@
+const file = await Deno.open("out.txt", { write: true, create: true });
+await file.write(new Uint8Array(6));
+file.writeSync(new Uint8Array(4));
+try {
+  await file.write(new Uint8Array(1));
+} catch (err) {
+  console.log((err as Error).message);
+}
+file.close();
+console.log(Deno.statSync("out.txt").size);
+
+try {
+  await Deno.copyFile("out.txt", "copy.txt");
+} catch (err) {
+  console.log((err as Error).message);
+}
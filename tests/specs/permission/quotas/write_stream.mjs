@@ -0,0 +1,7 @@
+import fs from "node:fs";
+
+const stream = fs.createWriteStream("out.txt");
+stream.on("error", (err) => console.log(err.message));
+stream.write("a".repeat(8));
+stream.write("b".repeat(8));
+stream.end();
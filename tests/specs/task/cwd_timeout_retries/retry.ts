@@ -0,0 +1,10 @@
+// fails until the third attempt
+let attempts = 0;
+try {
+  attempts = Number(Deno.readTextFileSync("attempts.txt"));
+} catch {
+  // first attempt
+}
+attempts++;
+Deno.writeTextFileSync("attempts.txt", String(attempts));
+Deno.exit(attempts < 3 ? 1 : 0);
@@ -0,0 +1,10 @@
+Deno.test("fetch", async () => {
+  const res = await fetch("http://localhost:4545/run/001_hello.js");
+  if (res.status !== 200) {
+    throw new Error(`unexpected status ${res.status}`);
+  }
+  const text = await res.text();
+  if (text !== 'console.log("Hello World");\n') {
+    throw new Error(`unexpected body ${text}`);
+  }
+});
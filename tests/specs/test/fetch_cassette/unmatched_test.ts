@@ -0,0 +1,3 @@
+Deno.test("unmatched fetch", async () => {
+  await fetch("http://localhost:4545/run/002_hello.ts");
+});
@@ -0,0 +1,47 @@
+import { assertEquals } from "@std/assert";
+
+Deno.test.clock.install();
+
+Deno.test("timers fire when the clock reaches them", async () => {
+  const fired: string[] = [];
+  const start = Deno.test.clock.now();
+  const elapsed = () => Deno.test.clock.now() - start;
+  setTimeout(() => fired.push(`timeout ${elapsed()}`), 1000);
+  const interval = setInterval(
+    () => fired.push(`interval ${elapsed()}`),
+    400,
+  );
+
+  await Deno.test.clock.tick(999);
+  assertEquals(fired, ["interval 400", "interval 800"]);
+
+  await Deno.test.clock.tick(201);
+  clearInterval(interval);
+  assertEquals(fired, [
+    "interval 400",
+    "interval 800",
+    "timeout 1000",
+    "interval 1200",
+  ]);
+});
+
+Deno.test("crons retry at their backoff deadlines", async () => {
+  const attempts: number[] = [];
+  const ac = new AbortController();
+  const cron = Deno.cron("sync", "*/20 * * * *", {
+    signal: ac.signal,
+    backoffSchedule: [10, 20],
+  }, () => {
+    attempts.push(Deno.test.clock.now());
+    throw new Error("flaky");
+  });
+
+  await Deno.test.clock.tick(20 * 60 * 1000 + 1000);
+  ac.abort();
+  await cron;
+
+  assertEquals(attempts.length, 3);
+  assertEquals(attempts[0] % (20 * 60 * 1000), 0);
+  assertEquals(attempts[1] - attempts[0], 10);
+  assertEquals(attempts[2] - attempts[0], 30);
+});
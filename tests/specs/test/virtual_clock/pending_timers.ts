@@ -0,0 +1,5 @@
+Deno.test.clock.install();
+
+Deno.test("waits on a timer without ticking the clock", async () => {
+  await new Promise((resolve) => setTimeout(resolve, 1000));
+});
@@ -420,3 +420,28 @@ Deno.test(async function cachePutResource() {
   const res = await cache.match(request);
   assertEquals(await res?.text(), "Contents".repeat(1024));
 });
+
+Deno.test(async function storageEstimateCountsCachedResponses() {
+  const cacheName = "cache-estimate";
+  await caches.delete(cacheName);
+  const before = await navigator.storage.estimate();
+
+  const cache = await caches.open(cacheName);
+  await cache.put(
+    new Request("https://example.com/estimate"),
+    new Response("x".repeat(4096)),
+  );
+  const after = await navigator.storage.estimate();
+  assert(after.usage! >= before.usage! + 4096);
+
+  // Freshness is ignored unless the cache size is limited.
+  await cache.put(
+    new Request("https://example.com/stale"),
+    new Response("stale", { headers: { "cache-control": "max-age=0" } }),
+  );
+  assertEquals(
+    await (await cache.match("https://example.com/stale"))?.text(),
+    "stale",
+  );
+  await caches.delete(cacheName);
+});
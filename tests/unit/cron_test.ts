@@ -113,6 +113,20 @@ Deno.test(function invalidBackoffScheduleTest() {
   );
 });
 
+Deno.test(function invalidTimezoneTest() {
+  assertThrows(
+    () =>
+      Deno.cron(
+        "abc",
+        "0 9 * * *",
+        { timezone: "Mars/Olympus_Mons" },
+        () => {},
+      ),
+    TypeError,
+    "Invalid cron time zone: Mars/Olympus_Mons",
+  );
+});
+
 Deno.test(async function tooManyCrons() {
   const crons: Promise<void>[] = [];
   const ac = new AbortController();
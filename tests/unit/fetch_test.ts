@@ -1357,6 +1357,119 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { net: true } },
+  async function createHttpClientDnsHosts() {
+    using client = Deno.createHttpClient({
+      dns: { hosts: { "fetch.deno.test": ["127.0.0.1"] } },
+    });
+    const response = await fetch("http://fetch.deno.test:4545/echo_server", {
+      client,
+      method: "POST",
+      body: "hello",
+    });
+    assertEquals(await response.text(), "hello");
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  function createHttpClientDnsRequiresServerName() {
+    assertThrows(
+      () =>
+        Deno.createHttpClient({
+          dns: { nameServers: [{ ipAddr: "127.0.0.1", protocol: "https" }] },
+        }),
+      TypeError,
+      "A 'serverName' is required for the DNS-over-HTTPS name server 127.0.0.1",
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function createHttpClientResponseHeadersTimeout() {
+    const { promise: unblock, resolve } = Promise.withResolvers<void>();
+    const started = Promise.withResolvers<number>();
+    await using _server = Deno.serve({
+      port: 0,
+      onListen: ({ port }) => started.resolve(port),
+    }, async () => {
+      await unblock;
+      return new Response("late");
+    });
+    const port = await started.promise;
+    using client = Deno.createHttpClient({
+      timeouts: { responseHeaders: 100 },
+    });
+    const err = await assertRejects(
+      () => fetch(`http://localhost:${port}/`, { client }),
+      Deno.errors.ResponseHeadersTimeout,
+      "Response headers not received within 100ms",
+    );
+    assert(err instanceof Deno.errors.TimedOut);
+    resolve();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function createHttpClientIdleReadTimeout() {
+    const { promise: unblock, resolve } = Promise.withResolvers<void>();
+    const started = Promise.withResolvers<number>();
+    await using _server = Deno.serve({
+      port: 0,
+      onListen: ({ port }) => started.resolve(port),
+    }, () => {
+      const body = new ReadableStream({
+        async start(controller) {
+          controller.enqueue(new TextEncoder().encode("first"));
+          await unblock;
+          controller.close();
+        },
+      });
+      return new Response(body);
+    });
+    const port = await started.promise;
+    using client = Deno.createHttpClient({
+      timeouts: { responseHeaders: 5000, idleRead: 100 },
+    });
+    const response = await fetch(`http://localhost:${port}/`, { client });
+    const reader = response.body!.getReader();
+    const { value } = await reader.read();
+    assertEquals(new TextDecoder().decode(value), "first");
+    await assertRejects(
+      () => reader.read(),
+      Deno.errors.IdleReadTimeout,
+      "No response body data received for 100ms",
+    );
+    resolve();
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function createHttpClientTlsHandshakeTimeout() {
+    // Accepts connections, but never answers the TLS handshake.
+    const listener = Deno.listen({ hostname: "127.0.0.1", port: 0 });
+    const conns: Deno.Conn[] = [];
+    const accepting = (async () => {
+      for await (const conn of listener) conns.push(conn);
+    })();
+    using client = Deno.createHttpClient({
+      timeouts: { connect: 5000, tlsHandshake: 100 },
+    });
+    await assertRejects(
+      () => fetch(`https://localhost:${listener.addr.port}/`, { client }),
+      Deno.errors.TlsHandshakeTimeout,
+      "TLS handshake timed out after 100ms",
+    );
+    listener.close();
+    await accepting;
+    for (const conn of conns) conn.close();
+  },
+);
+
 Deno.test(
   { permissions: { net: true } },
   async function fetchCustomClientUserAgent(): Promise<
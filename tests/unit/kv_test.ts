@@ -2600,3 +2600,215 @@ dbTest("AtomicOperation custom inspect - handles special values", (db) => {
   assert(inspected.includes('set([ "undefined" ], undefined)'));
   assert(inspected.includes('set([ "bigint" ], 9007199254740991n)'));
 });
+
+dbTest("export and import entries", async (db) => {
+  await db.set(["users", 1n], { name: "Ada" });
+  await db.set(["users", "bob"], new Uint8Array([1, 2, 3]));
+  await db.set(["users", NaN], new Deno.KvU64(7n));
+  await db.set(["other"], "skipped");
+
+  const text = await new Response(db.export({ prefix: ["users"] })).text();
+  const lines = text.trim().split("\n").map((line) => JSON.parse(line));
+  assertEquals(lines.map((line) => line.key), [
+    [{ type: "string", value: "users" }, { type: "string", value: "bob" }],
+    [{ type: "string", value: "users" }, { type: "bigint", value: "1" }],
+    [{ type: "string", value: "users" }, { type: "number", value: "NaN" }],
+  ]);
+  assertEquals(lines[1].value.type, "v8");
+  assertEquals(lines[2].value, { type: "u64", value: "7" });
+
+  const copy = await Deno.openKv(":memory:");
+  try {
+    // Split the input mid-line to check that lines are reassembled.
+    const bytes = new TextEncoder().encode(text);
+    const count = await copy.import(ReadableStream.from([
+      bytes.subarray(0, 10),
+      bytes.subarray(10),
+    ]));
+    assertEquals(count, 3);
+    assertEquals((await copy.get(["users", 1n])).value, { name: "Ada" });
+    assertEquals(
+      (await copy.get(["users", "bob"])).value,
+      new Uint8Array([1, 2, 3]),
+    );
+    assertEquals((await copy.get(["users", NaN])).value, new Deno.KvU64(7n));
+    assertEquals((await copy.get(["other"])).value, null);
+
+    await assertRejects(
+      () => copy.import(ReadableStream.from([new TextEncoder().encode("{}")])),
+      TypeError,
+    );
+  } finally {
+    copy.close();
+  }
+});
+
+Deno.test({
+  name: "kv changes",
+  // https://github.com/denoland/deno/issues/18363
+  ignore: Deno.build.os === "darwin" && isCI,
+  async fn() {
+    const filename = await Deno.makeTempFile({ prefix: "kv_changes_db" });
+    try {
+      const db = await Deno.openKv(filename);
+      const other = await Deno.openKv(filename);
+      try {
+        const first = await db.set(["a"], 1);
+        const reader = db.changes({ since: first.versionstamp }).getReader();
+        try {
+          const second = await db.atomic()
+            .set(["b"], new Uint8Array([1]))
+            .sum(["c"], new Deno.KvU64(2n))
+            .commit();
+          assert(second.ok);
+          // Writes from other connections are part of the feed too.
+          await other.delete(["a"]);
+          // Commits keep the values they wrote.
+          await db.set(["b"], new Uint8Array([2]));
+
+          const { value: commit } = await reader.read();
+          assertEquals(commit, {
+            versionstamp: second.versionstamp,
+            mutations: [
+              { type: "set", key: ["b"], value: new Uint8Array([1]) },
+              { type: "set", key: ["c"], value: new Deno.KvU64(2n) },
+            ],
+          });
+          const { value: deletion } = await reader.read();
+          assertEquals(deletion!.mutations, [{ type: "delete", key: ["a"] }]);
+          const { value: rewrite } = await reader.read();
+          assertEquals(rewrite!.mutations, [
+            { type: "set", key: ["b"], value: new Uint8Array([2]) },
+          ]);
+        } finally {
+          await reader.cancel();
+        }
+
+        // Writes before the feed was first opened were not recorded.
+        const resumed = db.changes({ since: "00000000000000000000" })
+          .getReader();
+        await assertRejects(() => resumed.read(), TypeError);
+
+        // Disabling the change log drops the recorded writes.
+        await db.disableChanges();
+        const afterDisable = db.changes({ since: first.versionstamp })
+          .getReader();
+        await assertRejects(() => afterDisable.read(), TypeError);
+      } finally {
+        db.close();
+        other.close();
+      }
+    } finally {
+      await Deno.remove(filename);
+    }
+  },
+});
+
+dbTest("kv changes are not supported in memory", async (db) => {
+  await assertRejects(() => db.changes().getReader().read(), TypeError);
+});
+
+dbTest("secondary indexes", async (db) => {
+  const email = (user: unknown) => (user as { email?: string }).email;
+  await db.defineIndex("usersByEmail", { prefix: ["users"], key: email });
+  // Defining the same index again is fine, on another prefix it isn't.
+  await db.defineIndex("usersByEmail", { prefix: ["users"], key: email });
+  await assertRejects(
+    () => db.defineIndex("usersByEmail", { prefix: ["posts"], key: () => 1 }),
+    TypeError,
+  );
+
+  await db.set(["users", "ada"], { email: "ada@example.com" });
+  await db.atomic()
+    .set(["users", "bob"], { email: "bob@example.com" })
+    .set(["users", "eve"], {})
+    .set(["posts", 1], { email: "ada@example.com" })
+    .commit();
+
+  const byEmail = (selector: Deno.KvListSelector) =>
+    collect(db.list(selector, { index: "usersByEmail" }));
+  let entries = await byEmail({ prefix: ["ada@example.com"] });
+  assertEquals(entries.map((entry) => entry.key), [["users", "ada"]]);
+  assertEquals(entries[0].value, { email: "ada@example.com" });
+  entries = await byEmail({ prefix: [] });
+  assertEquals(entries.map((entry) => entry.key), [
+    ["users", "ada"],
+    ["users", "bob"],
+  ]);
+
+  // Changing or deleting an entry updates the index.
+  await db.set(["users", "ada"], { email: "ada@example.org" });
+  await db.delete(["users", "bob"]);
+  assertEquals(await byEmail({ prefix: ["ada@example.com"] }), []);
+  entries = await byEmail({ prefix: [] });
+  assertEquals(entries.map((entry) => entry.key), [["users", "ada"]]);
+  assertEquals(entries[0].value, { email: "ada@example.org" });
+
+  // Cursors of index listings resume where they left off.
+  await db.set(["users", "zoe"], { email: "zoe@example.com" });
+  const iter = db.list({ prefix: [] }, { index: "usersByEmail", limit: 1 });
+  assertEquals((await collect(iter)).map((entry) => entry.key), [
+    ["users", "ada"],
+  ]);
+  const rest = db.list({ prefix: [] }, {
+    index: "usersByEmail",
+    cursor: iter.cursor,
+  });
+  assertEquals((await collect(rest)).map((entry) => entry.key), [
+    ["users", "zoe"],
+  ]);
+
+  await assertRejects(
+    () => db.atomic().sum(["users", "count"], 1n).commit(),
+    TypeError,
+  );
+  assertThrows(() => db.list({ prefix: [] }, { index: "unknown" }), TypeError);
+});
+
+dbTest("secondary indexes include entries set before", async (db) => {
+  await db.set(["users", "ada"], { email: "ada@example.com" });
+  await db.set(["users", "eve"], {});
+  await db.defineIndex("usersByEmail", {
+    prefix: ["users"],
+    key: (user) => (user as { email?: string }).email,
+  });
+  const entries = await collect(
+    db.list({ prefix: [] }, { index: "usersByEmail" }),
+  );
+  assertEquals(entries.map((entry) => entry.key), [["users", "ada"]]);
+});
+
+Deno.test({
+  name: "secondary indexes are stored in the database",
+  // https://github.com/denoland/deno/issues/18363
+  ignore: Deno.build.os === "darwin" && isCI,
+  async fn() {
+    const filename = await Deno.makeTempFile({ prefix: "kv_index_db" });
+    try {
+      const db = await Deno.openKv(filename);
+      try {
+        await db.defineIndex("usersByEmail", {
+          prefix: ["users"],
+          key: (user) => (user as { email: string }).email,
+        });
+      } finally {
+        db.close();
+      }
+
+      // A connection that hasn't defined the index can't keep it up to date.
+      const other = await Deno.openKv(filename);
+      try {
+        await assertRejects(
+          () => other.set(["users", "ada"], { email: "ada@example.com" }),
+          TypeError,
+          "must be defined",
+        );
+        await other.set(["posts", 1], "unindexed");
+      } finally {
+        other.close();
+      }
+    } finally {
+      await Deno.remove(filename);
+    }
+  },
+});
@@ -341,6 +341,20 @@ Deno.test({ permissions: { net: true } }, async function netTcpDialListen() {
   conn.close();
 });
 
+Deno.test({ permissions: { net: true } }, async function netTcpDialDnsHosts() {
+  const listener = Deno.listen({ hostname: "127.0.0.1", port: listenPort });
+  const accepted = listener.accept();
+  const conn = await Deno.connect({
+    hostname: "connect.deno.test",
+    port: listenPort,
+    dns: { hosts: { "connect.deno.test": ["127.0.0.1"] } },
+  });
+  assertEquals(conn.remoteAddr.hostname, "127.0.0.1");
+  (await accepted).close();
+  conn.close();
+  listener.close();
+});
+
 Deno.test({ permissions: { net: true } }, async function netTcpSetNoDelay() {
   const listener = Deno.listen({ port: listenPort });
   listener.accept().then(
@@ -1518,6 +1532,51 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { net: true } },
+  async function resolveDnsHostsOverride() {
+    const dns = { hosts: { "resolve.deno.test": ["10.0.0.1", "::1"] } };
+    assertEquals(
+      await Deno.resolveDns("resolve.deno.test", "A", { dns }),
+      ["10.0.0.1"],
+    );
+    assertEquals(
+      await Deno.resolveDns("Resolve.Deno.Test.", "AAAA", { dns }),
+      ["::1"],
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: true } },
+  async function resolveDnsRejectsNameServerWithDns() {
+    await assertRejects(
+      () =>
+        Deno.resolveDns("example.com", "A", {
+          nameServer: { ipAddr: "127.0.0.1" },
+          dns: { hosts: { "example.com": ["10.0.0.1"] } },
+        }),
+      TypeError,
+      "cannot be used together",
+    );
+  },
+);
+
+Deno.test(
+  { permissions: { net: ["127.0.0.1:4545"] } },
+  async function connectDnsNameServerRequiresPermission() {
+    await assertRejects(
+      () =>
+        Deno.connect({
+          hostname: "127.0.0.1",
+          port: 4545,
+          dns: { nameServers: [{ ipAddr: "192.0.2.1" }] },
+        }),
+      Deno.errors.NotCapable,
+    );
+  },
+);
+
 Deno.test({
   ignore: Deno.build.os === "linux",
   permissions: { net: true },
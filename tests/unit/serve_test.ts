@@ -4493,6 +4493,112 @@ Deno.test(
   },
 );
 
+Deno.test(
+  { permissions: { read: true, net: true } },
+  async function httpServerWithHttp3() {
+    const ac = new AbortController();
+    const listeningDeferred = Promise.withResolvers<void>();
+
+    await using server = Deno.serve({
+      handler: () => new Response("Hello World"),
+      hostname: "localhost",
+      port: servePort,
+      signal: ac.signal,
+      onListen: onListen(listeningDeferred.resolve),
+      onError: createOnErrorCb(ac),
+      cert: Deno.readTextFileSync("tests/testdata/tls/localhost.crt"),
+      key: Deno.readTextFileSync("tests/testdata/tls/localhost.key"),
+      http3: true,
+    });
+
+    await listeningDeferred.promise;
+    const caCert = Deno.readTextFileSync("tests/testdata/tls/RootCA.pem");
+    const client = Deno.createHttpClient({ caCerts: [caCert] });
+    const resp = await fetch(`https://localhost:${servePort}/`, { client });
+    assertEquals(resp.headers.get("alt-svc"), `h3=":${servePort}"; ma=86400`);
+    assertEquals(await resp.text(), "Hello World");
+    client.close();
+
+    // QUIC connections negotiating HTTP/3 are accepted on the same port.
+    const conn = await Deno.connectQuic({
+      hostname: "localhost",
+      port: servePort,
+      caCerts: [caCert],
+      alpnProtocols: ["h3"],
+    });
+    assertEquals(conn.protocol, "h3");
+
+    // Send a GET request over HTTP/3 by hand. The control stream opens with
+    // an empty SETTINGS frame, and the request headers only use the static
+    // QPACK table.
+    const control = await conn.createUnidirectionalStream();
+    await control.getWriter().write(new Uint8Array([0x00, 0x04, 0x00]));
+    const authority = new TextEncoder().encode(`localhost:${servePort}`);
+    const fields = new Uint8Array([
+      0x00, // required insert count
+      0x00, // delta base
+      0xd1, // :method GET
+      0xd7, // :scheme https
+      0xc1, // :path /
+      0x50, // :authority with a literal value
+      authority.length,
+      ...authority,
+    ]);
+    const request = await conn.createBidirectionalStream();
+    const writer = request.writable.getWriter();
+    await writer.write(new Uint8Array([0x01, fields.length, ...fields]));
+    await writer.close();
+
+    const response = new Uint8Array(
+      await new Response(request.readable).arrayBuffer(),
+    );
+    let headers: Uint8Array | undefined;
+    let body = "";
+    let offset = 0;
+    while (offset < response.length) {
+      let type, length;
+      [type, offset] = readQuicVarint(response, offset);
+      [length, offset] = readQuicVarint(response, offset);
+      const payload = response.subarray(offset, offset + length);
+      offset += length;
+      if (type === 0x01) headers ??= payload;
+      if (type === 0x00) body += new TextDecoder().decode(payload);
+    }
+    // :status 200 is the first field line after the field section prefix.
+    assertEquals(headers?.[2], 0xd9);
+    assertEquals(body, "Hello World");
+    conn.close();
+
+    ac.abort();
+    await server.finished;
+  },
+);
+
+function readQuicVarint(bytes: Uint8Array, offset: number): [number, number] {
+  const length = 1 << (bytes[offset] >> 6);
+  let value = bytes[offset] & 0x3f;
+  for (let i = 1; i < length; i++) {
+    value = value * 256 + bytes[offset + i];
+  }
+  return [value, offset + length];
+}
+
+Deno.test(
+  { permissions: { net: true } },
+  function httpServerHttp3RequiresTls() {
+    assertThrows(
+      () =>
+        Deno.serve({
+          handler: () => new Response(),
+          port: servePort,
+          http3: true,
+        }),
+      TypeError,
+      "Both 'cert' and 'key' must be provided to enable HTTP/3",
+    );
+  },
+);
+
 Deno.test(
   { permissions: { net: true, write: true, read: true } },
   async function httpServerRequestCLTE() {
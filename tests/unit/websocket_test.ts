@@ -1293,3 +1293,168 @@ Deno.test("WebSocket close with reason but no code doesn't send 1005", async ()
   conn.close();
   await server.finished;
 });
+
+Deno.test(async function websocketPerMessageDeflate() {
+  const ac = new AbortController();
+  const listeningDeferred = Promise.withResolvers<void>();
+  const serverExtensions = Promise.withResolvers<string>();
+  const server = Deno.serve({
+    handler: (req) => {
+      const { socket, response } = Deno.upgradeWebSocket(req, {
+        idleTimeout: 0,
+        perMessageDeflate: { clientNoContextTakeover: true },
+      });
+      socket.onopen = () => serverExtensions.resolve(socket.extensions);
+      socket.onmessage = (e) => socket.send(e.data);
+      socket.onclose = () => ac.abort();
+      return response;
+    },
+    signal: ac.signal,
+    onListen: () => listeningDeferred.resolve(),
+    hostname: "localhost",
+    port: servePort,
+  });
+  await listeningDeferred.promise;
+
+  const ws = new WebSocket(serveUrl, { perMessageDeflate: true });
+  ws.binaryType = "arraybuffer";
+  const messages: (string | ArrayBuffer)[] = [];
+  const closed = Promise.withResolvers<void>();
+  const text = JSON.stringify(
+    Array.from({ length: 1000 }, (_, i) => ({ id: i, name: "deno" })),
+  );
+  const binary = new Uint8Array(100_000).fill(42);
+  ws.onerror = () => fail();
+  ws.onopen = () => {
+    assertEquals(
+      ws.extensions,
+      "permessage-deflate; client_no_context_takeover",
+    );
+    ws.send(text);
+    ws.send(binary);
+    ws.send("");
+  };
+  ws.onmessage = (e) => {
+    messages.push(e.data);
+    if (messages.length === 3) ws.close();
+  };
+  ws.onclose = () => closed.resolve();
+  await Promise.all([closed.promise, server.finished]);
+
+  assertEquals(messages[0], text);
+  assertEquals(new Uint8Array(messages[1] as ArrayBuffer), binary);
+  assertEquals(messages[2], "");
+  assertEquals(
+    await serverExtensions.promise,
+    "permessage-deflate; client_no_context_takeover",
+  );
+});
+
+Deno.test(async function websocketPerMessageDeflateMessageTooLarge() {
+  const ac = new AbortController();
+  const listeningDeferred = Promise.withResolvers<void>();
+  const serverError = Promise.withResolvers<string>();
+  const server = Deno.serve({
+    handler: (req) => {
+      const { socket, response } = Deno.upgradeWebSocket(req, {
+        idleTimeout: 0,
+        perMessageDeflate: { maxDecompressedSize: 1024 },
+      });
+      socket.onerror = (e) => serverError.resolve((e as ErrorEvent).message);
+      socket.onclose = () => ac.abort();
+      return response;
+    },
+    signal: ac.signal,
+    onListen: () => listeningDeferred.resolve(),
+    hostname: "localhost",
+    port: servePort,
+  });
+  await listeningDeferred.promise;
+
+  const ws = new WebSocket(serveUrl, { perMessageDeflate: true });
+  const closed = Promise.withResolvers<CloseEvent>();
+  ws.onopen = () => ws.send(new Uint8Array(4096));
+  ws.onclose = (e) => closed.resolve(e);
+  const event = await closed.promise;
+  await server.finished;
+
+  assertEquals(event.code, 1009);
+  assertEquals(
+    await serverError.promise,
+    "Decompressed message exceeds the maximum size of 1024 bytes",
+  );
+});
+
+Deno.test(async function websocketPerMessageDeflateServerWindowBits() {
+  const ac = new AbortController();
+  const listeningDeferred = Promise.withResolvers<void>();
+  const upgradeError = Promise.withResolvers<unknown>();
+  const server = Deno.serve({
+    handler: (req) => {
+      try {
+        Deno.upgradeWebSocket(req, {
+          perMessageDeflate: { serverMaxWindowBits: 10 },
+        });
+      } catch (error) {
+        upgradeError.resolve(error);
+      }
+      ac.abort();
+      return new Response(null, { status: 400 });
+    },
+    signal: ac.signal,
+    onListen: () => listeningDeferred.resolve(),
+    hostname: "localhost",
+    port: servePort,
+  });
+  await listeningDeferred.promise;
+
+  const ws = new WebSocket(serveUrl);
+  const closed = Promise.withResolvers<void>();
+  ws.onerror = () => {};
+  ws.onclose = () => closed.resolve();
+  const error = await upgradeError.promise;
+  await Promise.all([closed.promise, server.finished]);
+
+  assert(error instanceof TypeError);
+  assertEquals(
+    error.message,
+    "Unsupported permessage-deflate server window bits 10: servers always compress with 15 window bits",
+  );
+});
+
+Deno.test(async function websocketPerMessageDeflateNotOffered() {
+  const ac = new AbortController();
+  const listeningDeferred = Promise.withResolvers<void>();
+  const server = Deno.serve({
+    handler: (req) => {
+      const { socket, response } = Deno.upgradeWebSocket(req, {
+        idleTimeout: 0,
+        perMessageDeflate: true,
+      });
+      socket.onmessage = (e) => socket.send(e.data);
+      socket.onclose = () => ac.abort();
+      return response;
+    },
+    signal: ac.signal,
+    onListen: () => listeningDeferred.resolve(),
+    hostname: "localhost",
+    port: servePort,
+  });
+  await listeningDeferred.promise;
+
+  const ws = new WebSocket(serveUrl);
+  const message = Promise.withResolvers<string>();
+  const closed = Promise.withResolvers<void>();
+  ws.onerror = () => fail();
+  ws.onopen = () => {
+    assertEquals(ws.extensions, "");
+    ws.send("hello");
+  };
+  ws.onmessage = (e) => {
+    message.resolve(e.data);
+    ws.close();
+  };
+  ws.onclose = () => closed.resolve();
+  await Promise.all([closed.promise, server.finished]);
+  assertEquals(await message.promise, "hello");
+});